@@ -0,0 +1,262 @@
+//! 热点路径的内存分配次数
+//!
+//! 与 `throughput` 使用同样的进程内模拟服务器，但以当前线程上的内存分配次数作为测量值，
+//! 而不是耗时。模拟服务器运行在单独的线程上，其分配不计入结果：
+//!
+//! ```bash
+//! cargo bench --features test-util --bench allocations
+//! ```
+
+use async_translate::{
+    LanguageIdentifier, TranslateOptions,
+    microsoft::{MicrosoftConfig, MicrosoftTranslator},
+    openai::{OpenAIConfig, OpenAITranslator},
+};
+use criterion::measurement::{Measurement, ValueFormatter};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use serde_json::json;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use tokio::runtime::Runtime;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+const BATCH_SIZE: usize = 100;
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// 统计每个线程分配次数的分配器
+struct CountingAllocator;
+
+fn count_allocation() {
+    // 线程退出时 thread_local 已被销毁，此时不再计数
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// 以当前线程的分配次数作为测量值
+struct Allocations;
+
+impl Measurement for Allocations {
+    type Intermediate = u64;
+    type Value = u64;
+
+    fn start(&self) -> u64 {
+        ALLOCATIONS.with(Cell::get)
+    }
+
+    fn end(&self, start: u64) -> u64 {
+        ALLOCATIONS.with(Cell::get) - start
+    }
+
+    fn add(&self, v1: &u64, v2: &u64) -> u64 {
+        v1 + v2
+    }
+
+    fn zero(&self) -> u64 {
+        0
+    }
+
+    fn to_f64(&self, value: &u64) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &AllocationFormatter
+    }
+}
+
+struct AllocationFormatter;
+
+impl ValueFormatter for AllocationFormatter {
+    fn scale_values(&self, _typical_value: f64, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical_value: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        match throughput {
+            Throughput::Elements(elements) => {
+                for value in values {
+                    *value /= *elements as f64;
+                }
+                "allocs/elem"
+            }
+            _ => "allocs",
+        }
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+}
+
+/// 为请求中的每条文本返回一条译文的微软模拟响应
+struct MicrosoftEcho;
+
+impl Respond for MicrosoftEcho {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let body: Vec<serde_json::Value> = serde_json::from_slice(&request.body).unwrap();
+        let results: Vec<serde_json::Value> = body
+            .iter()
+            .map(|_| json!({"translations": [{"text": "译文", "to": "zh"}]}))
+            .collect();
+        ResponseTemplate::new(200).set_body_json(results)
+    }
+}
+
+/// 请求在当前线程上执行，分配次数才会计入
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+fn microsoft(c: &mut Criterion<Allocations>) {
+    let runtime = runtime();
+    let server = runtime.block_on(async {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(MicrosoftEcho)
+            .mount(&server)
+            .await;
+        server
+    });
+    let failing = runtime.block_on(async {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "error": {"code": 400036, "message": "The target language is not valid."}
+            })))
+            .mount(&server)
+            .await;
+        server
+    });
+    let translator = |server: &MockServer| {
+        MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("bench-key"))
+                .build(),
+        )
+    };
+    let (translator, failing) = (translator(&server), translator(&failing));
+
+    let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+    let options = TranslateOptions::default().no_retries();
+    let texts: Vec<String> = (0..BATCH_SIZE)
+        .map(|i| format!("The quick brown fox jumps over the lazy dog, item {}.", i))
+        .collect();
+    let mut group = c.benchmark_group("microsoft_allocations");
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+    group.bench_function("translate_batch", |b| {
+        b.to_async(&runtime).iter(|| async {
+            translator
+                .translate_batch(&texts, &target_lang, None, &options)
+                .await
+                .unwrap()
+        })
+    });
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("translate_text", |b| {
+        b.to_async(&runtime).iter(|| async {
+            translator
+                .translate_text(&texts[0], &target_lang, None, &options)
+                .await
+                .unwrap()
+        })
+    });
+    group.bench_function("error_response", |b| {
+        b.to_async(&runtime).iter(|| async {
+            failing
+                .translate_text(&texts[0], &target_lang, None, &options)
+                .await
+                .unwrap_err()
+        })
+    });
+    group.finish();
+}
+
+fn openai(c: &mut Criterion<Allocations>) {
+    let runtime = runtime();
+    let failing = runtime.block_on(async {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "error": {
+                    "message": "This model's maximum context length is 8192 tokens.",
+                    "type": "invalid_request_error",
+                    "code": "context_length_exceeded"
+                }
+            })))
+            .mount(&server)
+            .await;
+        server
+    });
+    let translator = OpenAITranslator::new(
+        OpenAIConfig::builder()
+            .base_url(failing.uri())
+            .api_keys(vec!["bench-key"])
+            .rpm_limit(0)
+            .build(),
+    );
+
+    let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+    let options = TranslateOptions::default().no_retries();
+    let mut group = c.benchmark_group("openai_allocations");
+    group.bench_function("error_response", |b| {
+        b.to_async(&runtime).iter(|| async {
+            translator
+                .translate_text("Hello, world!", &target_lang, None, &options)
+                .await
+                .unwrap_err()
+        })
+    });
+    group.finish();
+}
+
+/// 分配次数没有波动，无法绘制分布图
+fn allocations() -> Criterion<Allocations> {
+    Criterion::default()
+        .with_measurement(Allocations)
+        .without_plots()
+}
+
+criterion_group! {
+    name = benches;
+    config = allocations();
+    targets = microsoft, openai
+}
+criterion_main!(benches);
@@ -0,0 +1,146 @@
+//! 吞吐量基准测试
+//!
+//! 使用进程内的模拟 HTTP 服务器，不需要访问真实网络：
+//!
+//! ```bash
+//! cargo bench --features test-util
+//! ```
+
+use async_translate::{
+    LanguageIdentifier, TranslateOptions,
+    microsoft::{MicrosoftConfig, MicrosoftTranslator},
+    openai::{OpenAIConfig, OpenAITranslator},
+    testing::{LoadGenerator, MockTranslator},
+};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use serde_json::json;
+use tokio::runtime::Runtime;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+const OPENAI_BATCH_SIZE: usize = 50;
+
+/// 为请求中的每条文本返回一条译文的微软模拟响应
+struct MicrosoftEcho;
+
+impl Respond for MicrosoftEcho {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let body: Vec<serde_json::Value> = serde_json::from_slice(&request.body).unwrap();
+        let results: Vec<serde_json::Value> = body
+            .iter()
+            .map(|_| json!({"translations": [{"text": "译文", "to": "zh"}]}))
+            .collect();
+        ResponseTemplate::new(200).set_body_json(results)
+    }
+}
+
+fn openai_batch(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let server = runtime.block_on(async {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"message": {"role": "assistant", "content": "译文"}}]
+            })))
+            .mount(&server)
+            .await;
+        server
+    });
+
+    let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+    let texts: Vec<&str> = vec!["Hello, world!"; OPENAI_BATCH_SIZE];
+    let mut group = c.benchmark_group("openai_translate_batch");
+    group.throughput(Throughput::Elements(OPENAI_BATCH_SIZE as u64));
+    for key_count in [1, 4] {
+        for rpm_limit in [0, 1_000_000] {
+            let keys: Vec<String> = (0..key_count).map(|i| format!("key-{}", i)).collect();
+            let translator = OpenAITranslator::new(
+                OpenAIConfig::builder()
+                    .base_url(server.uri())
+                    .api_keys(keys)
+                    .rpm_limit(rpm_limit)
+                    .build(),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("keys_{}", key_count), format!("rpm_{}", rpm_limit)),
+                &translator,
+                |b, translator| {
+                    b.to_async(&runtime).iter(|| async {
+                        translator
+                            .translate_batch(
+                                &texts,
+                                &target_lang,
+                                None,
+                                &TranslateOptions::default(),
+                            )
+                            .await
+                            .unwrap()
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn microsoft_batch(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let server = runtime.block_on(async {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(MicrosoftEcho)
+            .mount(&server)
+            .await;
+        server
+    });
+    let translator = MicrosoftTranslator::new(
+        MicrosoftConfig::builder()
+            .endpoint(server.uri())
+            .api_key(Some("bench-key"))
+            .build(),
+    );
+
+    let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+    let text = "a".repeat(1_000);
+    let mut group = c.benchmark_group("microsoft_translate_batch");
+    // 40 条 * 1000 字符可放入单个请求；120 条需要拆分为 3 个请求
+    for (name, count, options) in [
+        ("single_request", 40, TranslateOptions::default()),
+        ("chunked", 120, TranslateOptions::default().auto_split(true)),
+    ] {
+        let texts: Vec<&str> = vec![text.as_str(); count];
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_function(name, |b| {
+            b.to_async(&runtime).iter(|| async {
+                translator
+                    .translate_batch(&texts, &target_lang, None, &options)
+                    .await
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn load_generator(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let translator = MockTranslator::new();
+    let mut group = c.benchmark_group("load_generator");
+    for concurrency in [1, 16] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(concurrency),
+            &concurrency,
+            |b, &concurrency| {
+                let generator = LoadGenerator::new(100).concurrency(concurrency);
+                b.to_async(&runtime)
+                    .iter(|| async { generator.run(&translator).await })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, openai_batch, microsoft_batch, load_generator);
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+//! # chaos-example
+//!
+//! 展示如何用 `ChaosTranslator` 模拟翻译服务降级，验证上层代码的容错行为。
+//!
+//! 运行：`cargo run --example chaos_example --features test-util`
+
+use async_translate::{
+    LanguageIdentifier, TranslationError, TranslationManager,
+    testing::{ChaosTranslator, LatencyDistribution, MockTranslator},
+};
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() {
+    // 第 3~5 次调用失败后恢复，其余调用有 10% 的概率超时，延迟在 10~50ms 之间
+    let chaos = ChaosTranslator::new(MockTranslator::new().prefix("译文: "))
+        .fail_calls(3..=5, || {
+            TranslationError::ServiceError("injected outage".to_string())
+        })
+        .fail_with(0.1, || TranslationError::TimeoutError)
+        .latency(LatencyDistribution::Uniform {
+            min: Duration::from_millis(10),
+            max: Duration::from_millis(50),
+        })
+        .seed(42);
+
+    let mut manager = TranslationManager::new();
+    // 克隆共享计数器，保留一份用于查看统计
+    manager.add_translator("chaos", Box::new(chaos.clone()));
+
+    let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+    for i in 1..=10 {
+        let text = format!("message {}", i);
+        match manager.translate("chaos", &text, &target_lang, None).await {
+            Ok(result) => println!("#{:>2} ok:   {}", i, result),
+            Err(e) => println!("#{:>2} fail: {}", i, e),
+        }
+    }
+
+    println!(
+        "calls: {}, injected: {}, passed through: {}",
+        chaos.calls(),
+        chaos.injected(),
+        chaos.passed_through()
+    );
+}
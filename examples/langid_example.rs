@@ -1,6 +1,9 @@
 //! # LanguageIdentifier 示例
 //!
 //! 展示如何使用 unic-langid 库进行类型安全的语言定义
+//!
+//! 不访问翻译服务运行：
+//! `ASYNC_TRANSLATE_OFFLINE=1 cargo run --example langid_example --features test-util`
 
 use anyhow::Result;
 use async_translate::{
@@ -10,16 +13,27 @@ use async_translate::{
 };
 use std::time::Duration;
 
+/// 设置 `ASYNC_TRANSLATE_OFFLINE=1` 并启用 `test-util` feature 时改用确定性的
+/// `StaticTranslator`，不访问真实的翻译服务
+fn offline_or(translator: impl Translator + 'static) -> Box<dyn Translator> {
+    #[cfg(feature = "test-util")]
+    if async_translate::testing::offline() {
+        return Box::new(async_translate::testing::StaticTranslator::new());
+    }
+    Box::new(translator)
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     println!("🗣️  LanguageIdentifier 使用示例\n");
 
     // 创建翻译器
-    let translator = MicrosoftTranslator::new(MicrosoftConfig {
+    let translator = offline_or(MicrosoftTranslator::new(MicrosoftConfig {
         endpoint: None, // 使用默认端点
         api_key: None,  // 使用自动认证
         concurrent_limit: 10,
-    });
+        ..Default::default()
+    }));
 
     // 解析语言标识符
     let chinese: LanguageIdentifier = "zh-CN".parse().unwrap();
@@ -61,10 +75,11 @@ async fn main() -> Result<()> {
     let mut manager = TranslationManager::new();
     manager.add_translator(
         "microsoft",
-        Box::new(MicrosoftTranslator::new(MicrosoftConfig {
+        offline_or(MicrosoftTranslator::new(MicrosoftConfig {
             endpoint: None, // 使用默认端点
             api_key: None,  // 使用自动认证
             concurrent_limit: 5,
+            ..Default::default()
         })),
     );
 
@@ -5,6 +5,9 @@
 //! 示例展示了两种使用方式：
 //! 1. 通过 TranslationManager 统一管理多个翻译器
 //! 2. 直接使用单个翻译器实例
+//!
+//! 不访问翻译服务运行：
+//! `ASYNC_TRANSLATE_OFFLINE=1 cargo run --example translation_example --features test-util`
 
 use anyhow::Result;
 use async_translate::{
@@ -13,10 +16,19 @@ use async_translate::{
     microsoft::{MicrosoftConfig, MicrosoftTranslator},
     openai::{OpenAIConfig, OpenAITranslator},
 };
-use std::sync::Arc;
 use std::time::Duration;
 use tracing::{error, info};
 
+/// 设置 `ASYNC_TRANSLATE_OFFLINE=1` 并启用 `test-util` feature 时改用确定性的
+/// `StaticTranslator`，不访问真实的翻译服务
+fn offline_or(translator: impl Translator + 'static) -> Box<dyn Translator> {
+    #[cfg(feature = "test-util")]
+    if async_translate::testing::offline() {
+        return Box::new(async_translate::testing::StaticTranslator::new());
+    }
+    Box::new(translator)
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // 初始化日志
@@ -29,12 +41,13 @@ async fn main() -> Result<()> {
     let openai_config_default = OpenAIConfig {
         base_url: "https://api.openai.com/v1".to_string(),
         model: "gpt-3.5-turbo".to_string(),
-        api_keys: vec!["your-first-openai-api-key".to_string()],
+        api_keys: vec!["your-first-openai-api-key".into()],
         rpm_limit: 60,
         concurrent_limit: 10,
         system_prompt: None, // 使用默认提示词
+        ..Default::default()
     };
-    let openai_translator_default = Box::new(OpenAITranslator::new(openai_config_default));
+    let openai_translator_default = offline_or(OpenAITranslator::new(openai_config_default));
     manager.add_translator("openai_default", openai_translator_default);
 
     // 配置OpenAI翻译器（使用自定义提示词）
@@ -42,13 +55,14 @@ async fn main() -> Result<()> {
         base_url: "https://api.openai.com/v1".to_string(),
         model: "gpt-3.5-turbo".to_string(),
         api_keys: vec![
-            "your-second-openai-api-key".to_string(),
+            "your-second-openai-api-key".into(),
         ],
         rpm_limit: 60,
         concurrent_limit: 10,
         system_prompt: Some("You are a professional translator with expertise in technical documentation. Please translate the following text to high-quality {target_lang} while preserving technical accuracy and context.".to_string()),
+        ..Default::default()
     };
-    let openai_translator_custom = Box::new(OpenAITranslator::new(openai_config_custom));
+    let openai_translator_custom = offline_or(OpenAITranslator::new(openai_config_custom));
     manager.add_translator("openai_custom", openai_translator_custom);
 
     // 配置微软翻译器（自动获取认证token）
@@ -56,15 +70,13 @@ async fn main() -> Result<()> {
         endpoint: None, // 使用默认端点
         api_key: None,  // 使用自动认证
         concurrent_limit: 10,
+        ..Default::default()
     };
-    let microsoft_translator = Box::new(MicrosoftTranslator::new(microsoft_config));
+    let microsoft_translator = offline_or(MicrosoftTranslator::new(microsoft_config));
     manager.add_translator("microsoft", microsoft_translator);
 
-    // 将manager包装成Arc以在线程间共享
-    let manager = Arc::new(manager);
-
     // 测试翻译
-    let texts = vec![
+    let texts = [
         "Hello, world!",
         "How are you today?",
         "The weather is nice.",
@@ -83,8 +95,8 @@ async fn main() -> Result<()> {
         let text_str = text.to_string();
         let target_lang_clone = target_lang.clone();
 
-        // 为每个任务创建manager的引用
-        let manager1 = Arc::clone(&manager);
+        // 克隆的管理器是共享同一组翻译器的句柄
+        let manager1 = manager.clone();
         let target_lang_clone1 = target_lang_clone.clone();
         let task1 = tokio::spawn(async move {
             match manager1
@@ -111,7 +123,7 @@ async fn main() -> Result<()> {
 
         let text_str = text.to_string();
         let target_lang_clone2 = target_lang_clone.clone();
-        let manager2 = Arc::clone(&manager);
+        let manager2 = manager.clone();
         let task2 = tokio::spawn(async move {
             match manager2
                 .translate("openai_custom", &text_str, &target_lang_clone2, None)
@@ -136,7 +148,7 @@ async fn main() -> Result<()> {
         tasks.push(task2);
 
         let text_str = text.to_string();
-        let manager3 = Arc::clone(&manager);
+        let manager3 = manager.clone();
         let task3 = tokio::spawn(async move {
             match manager3
                 .translate("microsoft", &text_str, &target_lang_clone, None)
@@ -171,8 +183,9 @@ async fn main() -> Result<()> {
         endpoint: None,      // 使用默认端点
         api_key: None,       // 使用自动认证
         concurrent_limit: 5, // 直接使用时可以设置较小的并发限制
+        ..Default::default()
     };
-    let microsoft_translator = MicrosoftTranslator::new(microsoft_config);
+    let microsoft_translator = offline_or(MicrosoftTranslator::new(microsoft_config));
 
     // 直接调用翻译器
     let texts = vec!["Good morning!", "How are you?", "Thank you!"];
@@ -263,28 +276,48 @@ async fn main() -> Result<()> {
     // 示例5：批量翻译
     info!("\n=== 批量翻译示例 ===");
 
-    let batch_texts = vec!["Hello", "World", "Rust", "Translation", "Example"];
+    // 批量接口接受任何产生字符串的集合，例如从文件逐行读取的 Vec<String>
+    let batch_texts: Vec<String> = "Hello\nWorld\nRust\nTranslation\nExample"
+        .lines()
+        .map(String::from)
+        .collect();
+    // 微软翻译器特有的批量接口返回检测到的语言，离线模式下改用统一的批量接口
     match microsoft_translator
-        .translate_batch(&batch_texts, &chinese, None, &TranslateOptions::default())
-        .await
+        .as_any()
+        .downcast_ref::<MicrosoftTranslator>()
     {
-        Ok(results) => {
-            info!("Batch translation results:");
-            for (i, result) in results.iter().enumerate() {
-                if let Some(translation) = result.translations.first() {
-                    info!("  {}: '{}' -> '{}'", i, batch_texts[i], translation.text);
-                    // 显示检测到的语言（如果有的话）
-                    if let Some(detected) = &result.detected_language {
-                        info!(
-                            "    Detected language: {} (confidence: {:.2})",
-                            detected.language, detected.score
-                        );
+        Some(microsoft) => match microsoft
+            .translate_batch(&batch_texts, &chinese, None, &TranslateOptions::default())
+            .await
+        {
+            Ok(results) => {
+                info!("Batch translation results:");
+                for (i, result) in results.iter().enumerate() {
+                    if let Some(translation) = result.translations.first() {
+                        info!("  {}: '{}' -> '{}'", i, batch_texts[i], translation.text);
+                        // 显示检测到的语言（如果有的话）
+                        if let Some(detected) = &result.detected_language {
+                            info!(
+                                "    Detected language: {} (confidence: {:.2})",
+                                detected.language, detected.score
+                            );
+                        }
                     }
                 }
             }
-        }
-        Err(e) => {
-            error!("Batch translation error: {}", e);
+            Err(e) => {
+                error!("Batch translation error: {}", e);
+            }
+        },
+        None => {
+            let texts: Vec<&str> = batch_texts.iter().map(String::as_str).collect();
+            let results = microsoft_translator
+                .translate_batch_with_options(&texts, &chinese, None, &TranslateOptions::default())
+                .await?;
+            info!("Batch translation results:");
+            for (i, result) in results.iter().enumerate() {
+                info!("  {}: '{}' -> '{}'", i, batch_texts[i], result);
+            }
         }
     }
 
@@ -0,0 +1,151 @@
+//! 翻译器的计划维护窗口
+//!
+//! 翻译服务通常会提前公布维护时间。通过
+//! [`crate::TranslationManager::with_availability`] 为翻译器设置 [`DowntimeWindow`] 后，
+//! 维护期间直接调用该翻译器会立即返回 [`crate::TranslationError::TranslatorUnavailable`]，
+//! 自适应选择和回退链会跳过该翻译器。
+//!
+//! 窗口按 `timezone_offset` 指定的时区解释，结束时间不晚于开始时间的窗口跨过零点，
+//! 在次日的结束时间结束。
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DAY_SECS: i64 = 24 * 60 * 60;
+
+/// 星期
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Weekday {
+    /// 星期一
+    Monday,
+    /// 星期二
+    Tuesday,
+    /// 星期三
+    Wednesday,
+    /// 星期四
+    Thursday,
+    /// 星期五
+    Friday,
+    /// 星期六
+    Saturday,
+    /// 星期日
+    Sunday,
+}
+
+impl Weekday {
+    /// 从 Unix 纪元起第 `day` 天是星期几，1970-01-01 是星期四
+    fn from_days_since_epoch(day: i64) -> Self {
+        const DAYS: [Weekday; 7] = [
+            Weekday::Monday,
+            Weekday::Tuesday,
+            Weekday::Wednesday,
+            Weekday::Thursday,
+            Weekday::Friday,
+            Weekday::Saturday,
+            Weekday::Sunday,
+        ];
+        DAYS[(day + 3).rem_euclid(7) as usize]
+    }
+}
+
+/// 一个按周重复的维护窗口
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DowntimeWindow {
+    /// 窗口在哪几天开始，为空表示每天；跨过零点的窗口按开始的那天计算
+    pub days: Vec<Weekday>,
+    /// 开始时间，为距当地零点的时长
+    pub start: Duration,
+    /// 结束时间，为距当地零点的时长；不晚于 `start` 时在次日结束
+    pub end: Duration,
+    /// 时区相对 UTC 的偏移（分钟），例如 UTC+8 为 480
+    pub timezone_offset: i32,
+}
+
+impl DowntimeWindow {
+    /// 创建每天 UTC `start` 到 `end` 的窗口
+    pub fn new(start: Duration, end: Duration) -> Self {
+        Self {
+            days: Vec::new(),
+            start,
+            end,
+            timezone_offset: 0,
+        }
+    }
+
+    /// 只在指定的几天开始
+    pub fn on(mut self, days: impl IntoIterator<Item = Weekday>) -> Self {
+        self.days = days.into_iter().collect();
+        self
+    }
+
+    /// 设置时区相对 UTC 的偏移（分钟）
+    pub fn timezone_offset(mut self, minutes: i32) -> Self {
+        self.timezone_offset = minutes;
+        self
+    }
+
+    /// `now` 处于窗口中时返回窗口结束的时间，否则返回 None
+    pub fn active_until(&self, now: SystemTime) -> Option<SystemTime> {
+        let offset = i64::from(self.timezone_offset) * 60;
+        let local = unix_secs(now) + offset;
+        let day = local.div_euclid(DAY_SECS);
+        let time = local.rem_euclid(DAY_SECS);
+        let start = (self.start.as_secs() as i64).min(DAY_SECS);
+        let end = (self.end.as_secs() as i64).min(DAY_SECS);
+
+        let until = if start < end {
+            (self.starts_on(day) && (start..end).contains(&time)).then_some(day * DAY_SECS + end)
+        } else if self.starts_on(day) && time >= start {
+            Some((day + 1) * DAY_SECS + end)
+        } else if self.starts_on(day - 1) && time < end {
+            Some(day * DAY_SECS + end)
+        } else {
+            None
+        };
+        until.map(|local| from_unix_secs(local - offset))
+    }
+
+    fn starts_on(&self, day: i64) -> bool {
+        self.days.is_empty() || self.days.contains(&Weekday::from_days_since_epoch(day))
+    }
+}
+
+/// `now` 处于任意窗口中时返回恢复可用的时间
+///
+/// 首尾相接或重叠的窗口合并计算，返回最后一个窗口结束的时间
+pub(crate) fn unavailable_until(windows: &[DowntimeWindow], now: SystemTime) -> Option<SystemTime> {
+    let mut until = None;
+    let mut at = now;
+    // 每次至少越过一个窗口，循环次数不超过窗口数
+    for _ in 0..windows.len() {
+        match windows
+            .iter()
+            .filter_map(|window| window.active_until(at))
+            .max()
+        {
+            Some(end) => {
+                until = Some(end);
+                at = end;
+            }
+            None => break,
+        }
+    }
+    until
+}
+
+fn unix_secs(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs() as i64),
+    }
+}
+
+fn from_unix_secs(secs: i64) -> SystemTime {
+    if secs >= 0 {
+        UNIX_EPOCH + Duration::from_secs(secs as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,189 @@
+#[cfg(test)]
+mod tests {
+    use crate::availability::{DowntimeWindow, Weekday, unavailable_until};
+    use crate::error::TranslationError;
+    use crate::manager::TranslationManager;
+    use crate::options::TranslateOptions;
+    use crate::testing::{ChaosTranslator, MockClock, MockTranslator};
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use unic_langid::LanguageIdentifier;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// 从 Unix 纪元起第 `days` 天的 `hours` 时 `minutes` 分（UTC），第 0 天是星期四
+    fn at(days: u64, hours: u64, minutes: u64) -> SystemTime {
+        UNIX_EPOCH + DAY * days as u32 + HOUR * hours as u32 + Duration::from_secs(minutes * 60)
+    }
+
+    #[test]
+    fn test_window_spanning_midnight() {
+        // 每周五 23:00 到周六 01:00，第 1 天是星期五
+        let window = DowntimeWindow::new(HOUR * 23, HOUR).on([Weekday::Friday]);
+        assert_eq!(window.active_until(at(1, 22, 59)), None);
+        assert_eq!(window.active_until(at(1, 23, 0)), Some(at(2, 1, 0)));
+        assert_eq!(window.active_until(at(2, 0, 30)), Some(at(2, 1, 0)));
+        assert_eq!(window.active_until(at(2, 1, 0)), None);
+        // 周六晚上不在窗口中，下周五再次生效
+        assert_eq!(window.active_until(at(2, 23, 30)), None);
+        assert_eq!(window.active_until(at(8, 23, 30)), Some(at(9, 1, 0)));
+    }
+
+    #[test]
+    fn test_window_timezone_offset() {
+        // UTC+8 每天 02:00 到 04:00，即 UTC 前一天 18:00 到 20:00
+        let window = DowntimeWindow::new(HOUR * 2, HOUR * 4).timezone_offset(480);
+        assert_eq!(window.active_until(at(3, 2, 30)), None);
+        assert_eq!(window.active_until(at(3, 18, 30)), Some(at(3, 20, 0)));
+
+        // UTC-5 的周四 22:00 到周五 02:00 是 UTC 周五 03:00 到 07:00
+        let window = DowntimeWindow::new(HOUR * 22, HOUR * 2)
+            .on([Weekday::Thursday])
+            .timezone_offset(-300);
+        assert_eq!(window.active_until(at(1, 2, 59)), None);
+        assert_eq!(window.active_until(at(1, 3, 0)), Some(at(1, 7, 0)));
+        assert_eq!(window.active_until(at(1, 6, 59)), Some(at(1, 7, 0)));
+    }
+
+    #[test]
+    fn test_adjacent_windows_merge() {
+        let windows = [
+            DowntimeWindow::new(HOUR * 22, HOUR * 23),
+            DowntimeWindow::new(HOUR * 23, HOUR * 2),
+        ];
+        assert_eq!(
+            unavailable_until(&windows, at(5, 22, 30)),
+            Some(at(6, 2, 0))
+        );
+        assert_eq!(unavailable_until(&windows, at(5, 12, 0)), None);
+        assert_eq!(unavailable_until(&[], at(5, 22, 30)), None);
+    }
+
+    #[tokio::test]
+    async fn test_direct_call_fails_during_window() {
+        let clock = MockClock::new().wall_clock(at(1, 23, 30));
+        let primary = MockTranslator::new();
+        let mut manager = TranslationManager::new()
+            .with_availability(
+                "primary",
+                vec![DowntimeWindow::new(HOUR * 23, HOUR).on([Weekday::Friday])],
+            )
+            .with_availability_clock(Arc::new(clock.clone()));
+        manager.add_translator("primary", Box::new(primary.clone()));
+        manager.add_alias("primary", "main").unwrap();
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+
+        for name in ["primary", "main"] {
+            match manager.translate(name, "Hello", &zh, None).await {
+                Err(TranslationError::TranslatorUnavailable { translator, until }) => {
+                    assert_eq!(translator, "primary");
+                    assert_eq!(until, at(2, 1, 0));
+                }
+                other => panic!("expected TranslatorUnavailable, got {:?}", other),
+            }
+        }
+        assert_eq!(primary.calls(), 0);
+        assert_eq!(manager.unavailable_until("main"), Some(at(2, 1, 0)));
+
+        // 窗口在次日 01:00 结束
+        clock.advance(HOUR * 2);
+        assert_eq!(manager.unavailable_until("primary"), None);
+        assert_eq!(
+            manager
+                .translate("primary", "Hello", &zh, None)
+                .await
+                .unwrap(),
+            "Translated: Hello"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fallback_chain_skips_unavailable_translators() {
+        let clock = MockClock::new().wall_clock(at(4, 2, 30));
+        let primary = MockTranslator::new().prefix("primary: ");
+        let secondary = MockTranslator::new().prefix("secondary: ");
+        let backup = MockTranslator::new().prefix("backup: ");
+        let mut manager = TranslationManager::new()
+            .with_availability("primary", vec![DowntimeWindow::new(HOUR * 2, HOUR * 3)])
+            .with_availability("backup", vec![DowntimeWindow::new(HOUR * 2, HOUR * 4)])
+            .with_availability_clock(Arc::new(clock.clone()));
+        manager.add_translator("primary", Box::new(primary.clone()));
+        manager.add_translator(
+            "secondary",
+            Box::new(
+                ChaosTranslator::new(secondary.clone())
+                    .fail_calls(1..=1, || TranslationError::TimeoutError),
+            ),
+        );
+        manager.add_translator("backup", Box::new(backup.clone()));
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        let chain = ["primary", "secondary", "backup"];
+
+        // primary 和 backup 都在维护，secondary 第一次调用失败时返回它的错误
+        let result = manager
+            .translate_with_fallback(&chain, "Hello", &zh, None, &options)
+            .await;
+        assert!(matches!(result, Err(TranslationError::TimeoutError)));
+        assert_eq!(
+            manager
+                .translate_with_fallback(&chain, "Hello", &zh, None, &options)
+                .await
+                .unwrap(),
+            "secondary: Hello"
+        );
+        assert_eq!((primary.calls(), backup.calls()), (0, 0));
+
+        // 只剩维护中的翻译器时返回最早恢复的那个
+        match manager
+            .translate_with_fallback(&["backup", "primary"], "Hello", &zh, None, &options)
+            .await
+        {
+            Err(TranslationError::TranslatorUnavailable { translator, until }) => {
+                assert_eq!(translator, "primary");
+                assert_eq!(until, at(4, 3, 0));
+            }
+            other => panic!("expected TranslatorUnavailable, got {:?}", other),
+        }
+
+        // 主翻译器恢复后重新排在最前
+        clock.advance(HOUR);
+        assert_eq!(
+            manager
+                .translate_with_fallback(&chain, "Hello", &zh, None, &options)
+                .await
+                .unwrap(),
+            "primary: Hello"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_routing_skips_unavailable_translators() {
+        let clock = MockClock::new().wall_clock(at(10, 12, 0));
+        let primary = MockTranslator::new();
+        let backup = MockTranslator::new();
+        let mut manager = TranslationManager::new()
+            .with_adaptive_routing(1.0, 0.5)
+            .with_availability("primary", vec![DowntimeWindow::new(HOUR * 12, HOUR * 13)])
+            .with_availability_clock(Arc::new(clock.clone()));
+        manager.add_translator("primary", Box::new(primary.clone()));
+        manager.add_translator("backup", Box::new(backup.clone()));
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+
+        for _ in 0..10 {
+            manager
+                .translate_adaptive(&["primary", "backup"], "Hello", &zh, None, &options)
+                .await
+                .unwrap();
+        }
+        assert_eq!((primary.calls(), backup.calls()), (0, 10));
+        assert!(matches!(
+            manager
+                .translate_adaptive(&["primary"], "Hello", &zh, None, &options)
+                .await,
+            Err(TranslationError::TranslatorUnavailable { .. })
+        ));
+    }
+}
@@ -0,0 +1,290 @@
+//! 带标签的批量翻译
+//!
+//! 每条输入附带调用方的标签（例如数据库行 ID），标签始终和对应的文本一起传递：
+//! 去重时相同文本的标签归为一组，翻译完成后结果直接交给这一组标签，
+//! 不依赖文本在批次中的位置。
+//!
+//! [`StartJitter`] 为批量翻译中的每条文本加上随机的启动延迟，避免同时发出大量请求。
+//!
+//! 批量翻译中每条文本的翻译都通过 [`catch_panic`] 运行，某一条文本的翻译或后处理
+//! panic 时只有这一条得到错误，其他结果不受影响。
+//!
+//! [`CharBudget`] 在发出请求之前检查整批输入的字符数是否超出 `max_batch_chars`，
+//! 启用 `truncate_to_budget` 时得到预算内需要发送的部分（[`Truncation`]）。
+
+use crate::{clock::Clock, error::TranslationError, options::EffectiveOptions, preflight};
+use futures::FutureExt;
+use futures::stream::{self, StreamExt};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 批量翻译中每条文本首次请求前的随机延迟
+#[derive(Debug)]
+pub(crate) struct StartJitter {
+    rng: Mutex<StdRng>,
+}
+
+impl StartJitter {
+    pub(crate) fn new(rng: StdRng) -> Self {
+        Self {
+            rng: Mutex::new(rng),
+        }
+    }
+
+    /// 等待 `[0, max)` 内均匀分布的时长，`max` 为 None 或零时不等待
+    pub(crate) async fn wait(&self, max: Option<Duration>, clock: &dyn Clock) {
+        let Some(max) = max.filter(|max| !max.is_zero()) else {
+            return;
+        };
+        let delay = self.rng.lock().unwrap().random_range(Duration::ZERO..max);
+        clock.sleep(delay).await;
+    }
+}
+
+impl Default for StartJitter {
+    fn default() -> Self {
+        Self::new(StdRng::from_os_rng())
+    }
+}
+
+/// 一次批量调用的字符数预算（`max_batch_chars` 和 `truncate_to_budget`）
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct CharBudget {
+    /// 允许的输入字符总数，None 表示不限制
+    max_chars: Option<usize>,
+    /// 超出时是否只处理预算内的前几条
+    truncate: bool,
+}
+
+impl CharBudget {
+    pub(crate) fn new(max_chars: Option<usize>, truncate: bool) -> Self {
+        Self {
+            max_chars,
+            truncate,
+        }
+    }
+
+    pub(crate) fn of(options: &EffectiveOptions) -> Self {
+        Self::new(options.max_batch_chars, options.truncate_to_budget)
+    }
+
+    /// 按整条文本截断：返回预算内的前几条的数量和其余条目的错误
+    ///
+    /// 超出预算且不截断时返回 [`TranslationError::BudgetExceeded`]
+    pub(crate) fn apply<S: AsRef<str>>(&self, texts: &[S]) -> Result<Truncation, TranslationError> {
+        let Some(max) = self.max_chars else {
+            return Ok(Truncation::none(texts.len()));
+        };
+        let lengths: Vec<usize> = texts
+            .iter()
+            .map(|text| text.as_ref().chars().count())
+            .collect();
+        let total: usize = lengths.iter().sum();
+        if total <= max {
+            return Ok(Truncation::none(texts.len()));
+        }
+        let error = TranslationError::BudgetExceeded { total, max };
+        if !self.truncate {
+            return Err(error);
+        }
+        let mut used = 0;
+        let fit = lengths
+            .iter()
+            .take_while(|&&length| {
+                used += length;
+                used <= max
+            })
+            .count();
+        Ok(Truncation {
+            fit,
+            prefix: None,
+            error: Some(error),
+        })
+    }
+
+    /// 按 `auto_split` 拆分出的片段截断：第一条超出预算的文本按 `max_segment` 和 `measure`
+    /// 拆分，预算内的前几个片段仍然处理，其余与 [`Self::apply`] 相同
+    pub(crate) fn apply_split<S: AsRef<str>>(
+        &self,
+        texts: &[S],
+        max_segment: usize,
+        measure: impl Fn(&str) -> usize,
+    ) -> Result<Truncation, TranslationError> {
+        let mut truncation = self.apply(texts)?;
+        let (Some(max), Some(_)) = (self.max_chars, &truncation.error) else {
+            return Ok(truncation);
+        };
+        let Some(text) = texts.get(truncation.fit).map(AsRef::as_ref) else {
+            return Ok(truncation);
+        };
+        let used: usize = texts[..truncation.fit]
+            .iter()
+            .map(|text| text.as_ref().chars().count())
+            .sum();
+        // 前缀以片段结尾，不含之后的空白
+        let (mut offset, mut chars, mut prefix) = (0, 0, 0);
+        for segment in preflight::split_text(text, max_segment, measure) {
+            if used + chars + segment.text.chars().count() > max {
+                break;
+            }
+            offset += segment.text.len();
+            prefix = offset;
+            chars += segment.text.chars().count() + segment.separator.chars().count();
+            offset += segment.separator.len();
+        }
+        truncation.prefix = (prefix > 0).then_some(prefix);
+        Ok(truncation)
+    }
+
+    /// 超出预算时返回错误，不截断，用于只能整批返回结果的方法
+    pub(crate) fn check<S: AsRef<str>>(&self, texts: &[S]) -> Result<(), TranslationError> {
+        Self::new(self.max_chars, false).apply(texts).map(drop)
+    }
+}
+
+/// 按预算截断一批文本的结果
+#[derive(Debug)]
+pub(crate) struct Truncation {
+    /// 完整处理的前几条的数量
+    pub(crate) fit: usize,
+    /// 第 `fit` 条文本在预算内的前缀的字节长度：只在按片段截断、且至少有一个片段在预算内时存在
+    pub(crate) prefix: Option<usize>,
+    /// 跳过的条目的错误，没有截断时为 None
+    pub(crate) error: Option<TranslationError>,
+}
+
+impl Truncation {
+    /// 不截断的结果
+    fn none(len: usize) -> Self {
+        Self {
+            fit: len,
+            prefix: None,
+            error: None,
+        }
+    }
+
+    /// 需要发送的文本：预算内的前几条，之后是第 `fit` 条在预算内的前缀
+    pub(crate) fn sent<'a, S: AsRef<str>>(&self, texts: &'a [S]) -> Vec<&'a str> {
+        let mut sent: Vec<&str> = texts[..self.fit].iter().map(AsRef::as_ref).collect();
+        if let Some(prefix) = self.prefix {
+            sent.push(&texts[self.fit].as_ref()[..prefix]);
+        }
+        sent
+    }
+
+    /// 第一条完全跳过的文本的下标
+    pub(crate) fn skipped_from(&self) -> usize {
+        self.fit + usize::from(self.prefix.is_some())
+    }
+}
+
+/// 运行一条文本的翻译，其中的 panic 转换为 `TranslationError::Other("panicked: ...")`
+pub(crate) async fn catch_panic<T>(
+    translation: impl Future<Output = Result<T, TranslationError>>,
+) -> Result<T, TranslationError> {
+    AssertUnwindSafe(translation)
+        .catch_unwind()
+        .await
+        .unwrap_or_else(|payload| {
+            Err(TranslationError::Other(format!(
+                "panicked: {}",
+                panic_message(payload.as_ref())
+            )))
+        })
+}
+
+/// panic 的消息，`panic!` 的参数是 `&str` 或 `String`
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown payload")
+}
+
+/// 翻译带标签的文本，结果按输入顺序排列
+///
+/// `dedup` 为 true 时相同的文本只翻译一次，失败或 panic 时每个标签都会得到该错误的副本。
+/// 超出 `budget` 时每个标签都得到预算错误；截断时只翻译预算内的前几条，其余的得到预算错误
+pub(crate) async fn translate_tagged<T, R, F, Fut>(
+    mut items: Vec<(T, String)>,
+    dedup: bool,
+    concurrency: usize,
+    budget: CharBudget,
+    translate: F,
+) -> Vec<(T, Result<R, TranslationError>)>
+where
+    T: Send,
+    R: Clone,
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<R, TranslationError>>,
+{
+    let texts: Vec<&str> = items.iter().map(|(_, text)| text.as_str()).collect();
+    let (fit, skipped_error) = match budget.apply(&texts) {
+        Ok(truncation) => (truncation.fit, truncation.error),
+        Err(error) => (0, Some(error)),
+    };
+    let skipped = items.split_off(fit);
+
+    // 每组为一段文本和使用它的标签，标签附带输入中的序号，只用于排列结果
+    let mut groups: Vec<(String, Vec<(usize, T)>)> = Vec::new();
+    let mut group_of_text: HashMap<String, usize> = HashMap::new();
+    for (order, (tag, text)) in items.into_iter().enumerate() {
+        if dedup {
+            if let Some(&group) = group_of_text.get(&text) {
+                groups[group].1.push((order, tag));
+                continue;
+            }
+            group_of_text.insert(text.clone(), groups.len());
+        }
+        groups.push((text, vec![(order, tag)]));
+    }
+
+    let translate = &translate;
+    let mut translated: Vec<(usize, T, Result<R, TranslationError>)> = stream::iter(groups)
+        .map(|(text, tags)| async move { (tags, catch_panic(translate(text)).await) })
+        .buffer_unordered(concurrency.max(1))
+        .flat_map(|(tags, result)| stream::iter(fan_out(tags, result)))
+        .collect()
+        .await;
+    translated.sort_by_key(|(order, _, _)| *order);
+    translated
+        .into_iter()
+        .map(|(_, tag, result)| (tag, result))
+        .chain(skipped.into_iter().map(|(tag, _)| {
+            let error = skipped_error.as_ref().expect("skipped items have an error");
+            (tag, Err(error.duplicate()))
+        }))
+        .collect()
+}
+
+/// 把一段文本的结果交给使用它的每个标签
+fn fan_out<T, R: Clone>(
+    tags: Vec<(usize, T)>,
+    result: Result<R, TranslationError>,
+) -> Vec<(usize, T, Result<R, TranslationError>)> {
+    let mut tags = tags.into_iter();
+    let Some((first_order, first_tag)) = tags.next() else {
+        return Vec::new();
+    };
+    let mut results: Vec<_> = tags
+        .map(|(order, tag)| {
+            let copy = match &result {
+                Ok(value) => Ok(value.clone()),
+                Err(error) => Err(error.duplicate()),
+            };
+            (order, tag, copy)
+        })
+        .collect();
+    results.push((first_order, first_tag, result));
+    results
+}
+
+#[cfg(test)]
+mod tests;
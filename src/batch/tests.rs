@@ -0,0 +1,288 @@
+#[cfg(test)]
+mod tests {
+    use crate::batch::{CharBudget, translate_tagged};
+    use crate::error::TranslationError;
+    use crate::manager::TranslationManager;
+    use crate::options::TranslateOptions;
+    use crate::postprocess::PostProcessor;
+    use crate::testing::MockTranslator;
+    use crate::translator::Translator;
+    use std::sync::{Arc, Mutex};
+    use unic_langid::LanguageIdentifier;
+
+    /// 处理到包含 "boom" 的译文时 panic 的后处理器
+    struct PanickingPostProcessor;
+
+    impl PostProcessor for PanickingPostProcessor {
+        fn process(&self, text: String, _target: &LanguageIdentifier) -> String {
+            if text.contains("boom") {
+                panic!("post-processor bug on {:?}", text);
+            }
+            text.to_uppercase()
+        }
+    }
+
+    fn is_panic(result: &Result<String, TranslationError>, expected: &str) -> bool {
+        matches!(result, Err(TranslationError::Other(msg)) if msg.starts_with("panicked: ") && msg.contains(expected))
+    }
+
+    #[tokio::test]
+    async fn test_tags_follow_their_text() {
+        let seen = Mutex::new(Vec::new());
+        let items = vec![
+            (1, "b".to_string()),
+            (2, "a".to_string()),
+            (3, "b".to_string()),
+            (4, "fail".to_string()),
+            (5, "fail".to_string()),
+        ];
+        let results = translate_tagged(items, true, 2, CharBudget::default(), |text| {
+            seen.lock().unwrap().push(text.clone());
+            async move {
+                if text == "fail" {
+                    Err(TranslationError::ServiceError("unavailable".to_string()))
+                } else {
+                    Ok(text.to_uppercase())
+                }
+            }
+        })
+        .await;
+
+        let mut seen = seen.into_inner().unwrap();
+        seen.sort();
+        assert_eq!(seen, ["a", "b", "fail"]);
+        let tags: Vec<i32> = results.iter().map(|(tag, _)| *tag).collect();
+        assert_eq!(tags, [1, 2, 3, 4, 5]);
+        assert_eq!(results[0].1.as_deref().unwrap(), "B");
+        assert_eq!(results[1].1.as_deref().unwrap(), "A");
+        assert_eq!(results[2].1.as_deref().unwrap(), "B");
+        for (_, result) in &results[3..] {
+            assert!(
+                matches!(result, Err(TranslationError::ServiceError(msg)) if msg == "unavailable")
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_without_dedup_every_item_is_translated() {
+        let calls = Mutex::new(0);
+        let items = vec![("x", "same".to_string()), ("y", "same".to_string())];
+        let results = translate_tagged(items, false, 4, CharBudget::default(), |text| {
+            *calls.lock().unwrap() += 1;
+            async move { Ok(text) }
+        })
+        .await;
+        assert_eq!(*calls.lock().unwrap(), 2);
+        assert_eq!(results.len(), 2);
+        assert!(
+            translate_tagged(
+                Vec::<((), String)>::new(),
+                true,
+                0,
+                CharBudget::default(),
+                |text| async move { Ok(text) }
+            )
+            .await
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_char_budget_boundaries() {
+        let texts = ["你好", "ab", "世界"];
+        // 总数恰好等于预算时不受影响
+        let truncation = CharBudget::new(Some(6), false).apply(&texts).unwrap();
+        assert_eq!(truncation.fit, 3);
+        assert!(truncation.error.is_none());
+        let truncation = CharBudget::new(None, false).apply(&texts).unwrap();
+        assert_eq!((truncation.fit, truncation.skipped_from()), (3, 3));
+        let error = CharBudget::new(Some(5), false).apply(&texts).unwrap_err();
+        assert!(matches!(
+            error,
+            TranslationError::BudgetExceeded { total: 6, max: 5 }
+        ));
+        assert!(CharBudget::new(Some(5), true).check(&texts).is_err());
+
+        for (max, fit) in [(5, 2), (4, 2), (3, 1), (2, 1), (1, 0), (0, 0)] {
+            let truncation = CharBudget::new(Some(max), true).apply(&texts).unwrap();
+            assert_eq!(truncation.fit, fit, "max {}", max);
+            assert_eq!(truncation.prefix, None);
+            assert_eq!(truncation.sent(&texts), texts[..fit]);
+            assert!(matches!(
+                truncation.error,
+                Some(TranslationError::BudgetExceeded { total: 6, max: m }) if m == max
+            ));
+        }
+    }
+
+    #[test]
+    fn test_char_budget_split_boundaries() {
+        let texts = ["ab", "one two six", "cd"];
+        let chars = |text: &str| text.chars().count();
+        // 第二条拆分为 "one"、"two"、"six" 三个片段，按片段截断
+        for (max, sent) in [
+            (4, vec!["ab"]),
+            (5, vec!["ab", "one"]),
+            (8, vec!["ab", "one"]),
+            (9, vec!["ab", "one two"]),
+            (12, vec!["ab", "one two"]),
+            (13, vec!["ab", "one two six"]),
+            (14, vec!["ab", "one two six"]),
+        ] {
+            let truncation = CharBudget::new(Some(max), true)
+                .apply_split(&texts, 4, chars)
+                .unwrap();
+            assert_eq!(truncation.sent(&texts), sent, "max {}", max);
+            assert!(truncation.error.is_some());
+        }
+        let truncation = CharBudget::new(Some(5), true)
+            .apply_split(&texts, 4, chars)
+            .unwrap();
+        assert_eq!((truncation.fit, truncation.skipped_from()), (1, 2));
+        // 整条都在预算内时不需要前缀
+        let truncation = CharBudget::new(Some(13), true)
+            .apply_split(&texts, 4, chars)
+            .unwrap();
+        assert_eq!((truncation.fit, truncation.prefix), (2, None));
+        // 不超出预算或不截断时与按整条截断相同
+        let truncation = CharBudget::new(Some(15), true)
+            .apply_split(&texts, 4, chars)
+            .unwrap();
+        assert_eq!(truncation.sent(&texts), texts);
+        assert!(
+            CharBudget::new(Some(14), false)
+                .apply_split(&texts, 4, chars)
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tagged_budget_skips_or_rejects() {
+        let items = || {
+            vec![
+                (1, "abc".to_string()),
+                (2, "de".to_string()),
+                (3, "f".to_string()),
+            ]
+        };
+        let calls = Mutex::new(Vec::new());
+        let translate = |text: String| {
+            calls.lock().unwrap().push(text.clone());
+            async move { Ok(text.to_uppercase()) }
+        };
+
+        let results =
+            translate_tagged(items(), true, 4, CharBudget::new(Some(5), true), translate).await;
+        assert_eq!(*calls.lock().unwrap(), ["abc", "de"]);
+        let tags: Vec<i32> = results.iter().map(|(tag, _)| *tag).collect();
+        assert_eq!(tags, [1, 2, 3]);
+        assert_eq!(results[1].1.as_deref().unwrap(), "DE");
+        assert!(matches!(
+            results[2].1,
+            Err(TranslationError::BudgetExceeded { total: 6, max: 5 })
+        ));
+
+        calls.lock().unwrap().clear();
+        let results =
+            translate_tagged(items(), true, 4, CharBudget::new(Some(5), false), translate).await;
+        assert!(calls.lock().unwrap().is_empty());
+        assert!(results.iter().all(|(_, result)| matches!(
+            result,
+            Err(TranslationError::BudgetExceeded { total: 6, max: 5 })
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_panic_is_isolated_to_its_item() {
+        let items = vec![
+            (1, "a".to_string()),
+            (2, "boom".to_string()),
+            (3, "b".to_string()),
+            (4, "boom".to_string()),
+        ];
+        let results = translate_tagged(items, true, 4, CharBudget::default(), |text| async move {
+            if text == "boom" {
+                panic!("translator bug");
+            }
+            Ok(text.to_uppercase())
+        })
+        .await;
+        let tags: Vec<i32> = results.iter().map(|(tag, _)| *tag).collect();
+        assert_eq!(tags, [1, 2, 3, 4]);
+        assert_eq!(results[0].1.as_deref().unwrap(), "A");
+        assert_eq!(results[2].1.as_deref().unwrap(), "B");
+        // 相同文本的标签都得到同一个错误
+        assert!(is_panic(&results[1].1, "translator bug"));
+        assert!(is_panic(&results[3].1, "translator bug"));
+    }
+
+    #[tokio::test]
+    async fn test_panicking_post_processor_does_not_affect_other_items() {
+        let mut manager = TranslationManager::new();
+        manager.add_translator("mock", Box::new(MockTranslator::new().prefix("")));
+        manager.add_post_processor(Arc::new(PanickingPostProcessor));
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+        let items: Vec<(usize, String)> = ["one", "boom", "three", "four"]
+            .into_iter()
+            .map(str::to_string)
+            .enumerate()
+            .collect();
+
+        let results = manager
+            .translate_batch_tagged("mock", items, &zh, None, &TranslateOptions::default())
+            .await;
+        let tags: Vec<usize> = results.iter().map(|(tag, _)| *tag).collect();
+        assert_eq!(tags, [0, 1, 2, 3]);
+        assert!(is_panic(&results[1].1, "post-processor bug on \"boom\""));
+        for (index, expected) in [(0, "ONE"), (2, "THREE"), (3, "FOUR")] {
+            assert_eq!(results[index].1.as_deref().unwrap(), expected);
+        }
+        // 管理器在 panic 后仍然可用
+        assert_eq!(
+            manager.translate("mock", "again", &zh, None).await.unwrap(),
+            "AGAIN"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_batch_reports_panic_as_error() {
+        struct PanickingTranslator;
+
+        #[async_trait::async_trait]
+        impl Translator for PanickingTranslator {
+            async fn translate_with_options(
+                &self,
+                text: &str,
+                _target_lang: &LanguageIdentifier,
+                _source_lang: Option<&LanguageIdentifier>,
+                _options: &TranslateOptions,
+            ) -> Result<String, TranslationError> {
+                if text == "boom" {
+                    panic!("translator bug");
+                }
+                Ok(text.to_string())
+            }
+
+            fn as_any(&self) -> &dyn std::any::Any {
+                self
+            }
+        }
+
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        let result = PanickingTranslator
+            .translate_batch_with_options(&["a", "boom"], &zh, None, &options)
+            .await;
+        assert!(is_panic(
+            &result.map(|texts| texts.join("")),
+            "translator bug"
+        ));
+        assert_eq!(
+            PanickingTranslator
+                .translate_batch_with_options(&["a", "b"], &zh, None, &options)
+                .await
+                .unwrap(),
+            ["a", "b"]
+        );
+    }
+}
@@ -0,0 +1,380 @@
+//! 翻译结果缓存的键
+//!
+//! 缓存键按内容计算：除了原文和语言对，还包括翻译器、模型、系统提示词、术语表和
+//! 影响译文的选项，这些输入由各翻译器通过 [`Translator::cache_key_inputs`] 提供。
+//! 修改提示词或更换模型后键随之变化，旧的译文不会再被命中。
+//!
+//! 键使用 SHA-256 计算，在不同平台和版本之间保持一致，可以用于持久化的缓存。
+//!
+//! [`TranslationCache`] 是按这种键保存译文的内存缓存，通过
+//! [`crate::TranslationManager::with_cache`] 启用。缓存可以导出为 JSON 文件并导入另一个
+//! 管理器，例如在 CI 中预先翻译、在生产环境中预热缓存。每个条目都记录了翻译器名称、
+//! 命名空间、翻译服务、模型和提示词的哈希，导入时与当前配置不一致的条目会被跳过，
+//! 不会提供按其他配置生成的译文。
+//!
+//! [`Translator::cache_key_inputs`]: crate::Translator::cache_key_inputs
+
+use crate::glossary::Glossary;
+use crate::manager::TranslationManager;
+use crate::options::TranslateOptions;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use unic_langid::LanguageIdentifier;
+
+/// 键格式的版本，格式变化时修改，使旧的键全部失效
+const CACHE_KEY_VERSION: &str = "async-translate/cache-key/v1";
+
+/// 导出文件格式的版本
+const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// 翻译器提供的、影响译文的输入
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheKeyInputs {
+    /// 翻译服务名称，例如 `"openai"`
+    pub provider: String,
+    /// 模型或自定义模型的类别，None 表示翻译服务不区分模型
+    pub model: Option<String>,
+    /// 规范化后的系统提示词的哈希
+    pub prompt_hash: Option<String>,
+    /// 术语表的哈希
+    pub glossary_hash: Option<String>,
+    /// 其他影响译文的选项，例如 `html`
+    pub options: BTreeMap<String, String>,
+}
+
+impl CacheKeyInputs {
+    /// 创建只包含翻译服务名称的输入
+    pub fn new(provider: impl Into<String>) -> Self {
+        Self {
+            provider: provider.into(),
+            ..Default::default()
+        }
+    }
+
+    /// 设置模型
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// 设置系统提示词，只保存规范化后的哈希
+    ///
+    /// 规范化会去掉首尾空白、合并连续的空白，只改变空白的提示词得到相同的哈希
+    pub fn prompt(mut self, prompt: &str) -> Self {
+        self.prompt_hash = Some(prompt_hash(prompt));
+        self
+    }
+
+    /// 设置术语表，只保存哈希
+    pub fn glossary(mut self, glossary: &Glossary) -> Self {
+        self.glossary_hash = Some(glossary_hash(glossary));
+        self
+    }
+
+    /// 添加一个影响译文的选项
+    pub fn option(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.options.insert(name.into(), value.to_string());
+        self
+    }
+
+    /// 除提示词外所有输入的哈希，不含原文和语言
+    ///
+    /// 指纹相同表示翻译器以相同的服务、模型、术语表和选项翻译，用于
+    /// [`crate::Provenance::options_fingerprint`]
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = FieldHasher::default();
+        hasher.field(&self.provider);
+        hasher.optional(self.model.as_deref());
+        hasher.optional(self.glossary_hash.as_deref());
+        hasher.field(&self.options.len().to_string());
+        for (name, value) in &self.options {
+            hasher.field(name);
+            hasher.field(value);
+        }
+        hasher.finish()
+    }
+}
+
+/// 规范化后的系统提示词的哈希
+pub(crate) fn prompt_hash(prompt: &str) -> String {
+    let mut hasher = FieldHasher::default();
+    hasher.field(&prompt.split_whitespace().collect::<Vec<_>>().join(" "));
+    hasher.finish()
+}
+
+/// 术语表的哈希，与术语的添加顺序无关
+pub fn glossary_hash(glossary: &Glossary) -> String {
+    let mut hasher = FieldHasher::default();
+    hasher.field(if glossary.is_case_insensitive() {
+        "case-insensitive"
+    } else {
+        "case-sensitive"
+    });
+    for (term, translation) in glossary.entries() {
+        hasher.field(term);
+        hasher.field(translation);
+    }
+    hasher.finish()
+}
+
+/// 内容寻址的缓存键：十六进制的 SHA-256
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CacheKey(String);
+
+impl CacheKey {
+    /// 计算缓存键
+    ///
+    /// # 参数
+    ///
+    /// * `namespace` - 命名空间，修改后所有键都会变化，用于手动使缓存失效
+    /// * `inputs` - 翻译器提供的输入
+    /// * `text` - 原文
+    /// * `target_lang` - 目标语言
+    /// * `source_lang` - 源语言，None 表示自动检测
+    pub fn compute(
+        namespace: Option<&str>,
+        inputs: &CacheKeyInputs,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+    ) -> Self {
+        let mut hasher = FieldHasher::default();
+        hasher.field(CACHE_KEY_VERSION);
+        hasher.optional(namespace);
+        hasher.field(&inputs.provider);
+        hasher.optional(inputs.model.as_deref());
+        hasher.optional(inputs.prompt_hash.as_deref());
+        hasher.optional(inputs.glossary_hash.as_deref());
+        hasher.field(&inputs.options.len().to_string());
+        for (name, value) in &inputs.options {
+            hasher.field(name);
+            hasher.field(value);
+        }
+        hasher.field(&target_lang.to_string());
+        hasher.optional(source_lang.map(|lang| lang.to_string()).as_deref());
+        hasher.field(text);
+        CacheKey(hasher.finish())
+    }
+
+    /// 十六进制表示
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// 缓存中的一条译文及其元数据
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// 缓存键
+    pub key: CacheKey,
+    /// 生成译文的翻译器名称
+    pub translator: String,
+    /// 管理器的缓存命名空间
+    pub namespace: Option<String>,
+    /// 计算键时翻译器提供的输入
+    pub inputs: CacheKeyInputs,
+    /// 目标语言
+    pub target_lang: String,
+    /// 源语言，None 表示自动检测
+    pub source_lang: Option<String>,
+    /// 译文
+    pub translation: String,
+}
+
+/// [`TranslationCache::import`] 的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// 导入的条目数，包括缓存中已有相同译文的条目
+    pub loaded: usize,
+    /// 与当前配置不一致而跳过的条目数
+    pub skipped: usize,
+    /// 缓存中已有不同译文、保留原有译文的条目数
+    pub conflicting: usize,
+}
+
+/// 导出文件的内容
+#[derive(Serialize, Deserialize)]
+struct CacheExport {
+    version: u32,
+    entries: Vec<CacheEntry>,
+}
+
+/// 按 [`CacheKey`] 保存译文的内存缓存
+///
+/// 克隆得到的是共享同一份条目的句柄，写入后立即可以从任何句柄读到
+#[derive(Debug, Clone, Default)]
+pub struct TranslationCache {
+    entries: Arc<RwLock<HashMap<CacheKey, CacheEntry>>>,
+}
+
+impl TranslationCache {
+    /// 创建空的缓存
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 取出键对应的译文
+    pub fn get(&self, key: &CacheKey) -> Option<String> {
+        self.entries
+            .read()
+            .unwrap()
+            .get(key)
+            .map(|entry| entry.translation.clone())
+    }
+
+    /// 写入一条译文，覆盖键相同的条目
+    pub fn insert(&self, entry: CacheEntry) {
+        self.entries
+            .write()
+            .unwrap()
+            .insert(entry.key.clone(), entry);
+    }
+
+    /// 条目数
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// 是否没有任何条目
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 清空所有条目
+    pub fn clear(&self) {
+        self.entries.write().unwrap().clear();
+    }
+
+    /// 把所有条目按键的顺序导出为 JSON 文件，返回导出的条目数
+    pub fn export(&self, path: impl AsRef<Path>) -> io::Result<usize> {
+        let mut entries: Vec<CacheEntry> = self.entries.read().unwrap().values().cloned().collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        let count = entries.len();
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(
+            &mut writer,
+            &CacheExport {
+                version: EXPORT_FORMAT_VERSION,
+                entries,
+            },
+        )?;
+        writer.flush()?;
+        Ok(count)
+    }
+
+    /// 从 [`TranslationCache::export`] 导出的文件中导入条目，已有的条目保留
+    ///
+    /// 只导入与 `manager` 当前配置一致的条目：翻译器必须已注册，缓存命名空间、
+    /// 翻译服务、模型和提示词的哈希必须与该翻译器按条目的语言对（使用默认选项）
+    /// 提供的输入相同，否则计入 `skipped`。文件无法读取或格式不正确时返回错误，
+    /// 不导入任何条目。
+    pub fn import(
+        &self,
+        path: impl AsRef<Path>,
+        manager: &TranslationManager,
+    ) -> io::Result<ImportReport> {
+        let export: CacheExport = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        if export.version != EXPORT_FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported cache export version {}", export.version),
+            ));
+        }
+        let mut report = ImportReport::default();
+        let mut entries = self.entries.write().unwrap();
+        for entry in export.entries {
+            if !is_compatible(&entry, manager) {
+                report.skipped += 1;
+                continue;
+            }
+            match entries.get(&entry.key) {
+                Some(existing) if existing.translation != entry.translation => {
+                    report.conflicting += 1;
+                }
+                Some(_) => report.loaded += 1,
+                None => {
+                    entries.insert(entry.key.clone(), entry);
+                    report.loaded += 1;
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// 条目的元数据是否与管理器的当前配置一致
+fn is_compatible(entry: &CacheEntry, manager: &TranslationManager) -> bool {
+    if entry.namespace.as_deref() != manager.cache_namespace() {
+        return false;
+    }
+    let Ok(target_lang) = entry.target_lang.parse::<LanguageIdentifier>() else {
+        return false;
+    };
+    let source_lang = match entry
+        .source_lang
+        .as_deref()
+        .map(str::parse::<LanguageIdentifier>)
+    {
+        Some(Ok(lang)) => Some(lang),
+        Some(Err(_)) => return false,
+        None => None,
+    };
+    let Some(inputs) = manager.cache_key_inputs(
+        &entry.translator,
+        &target_lang,
+        source_lang.as_ref(),
+        &TranslateOptions::default(),
+    ) else {
+        return false;
+    };
+    inputs.provider == entry.inputs.provider
+        && inputs.model == entry.inputs.model
+        && inputs.prompt_hash == entry.inputs.prompt_hash
+}
+
+/// 依次哈希每个字段，字段前加上长度，避免拼接产生歧义
+#[derive(Default)]
+struct FieldHasher(Sha256);
+
+impl FieldHasher {
+    fn field(&mut self, value: &str) {
+        self.0.update((value.len() as u64).to_le_bytes());
+        self.0.update(value);
+    }
+
+    /// 可选字段先写入是否存在的标记，未设置与空字符串不同
+    fn optional(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.0.update([1]);
+                self.field(value);
+            }
+            None => self.0.update([0]),
+        }
+    }
+
+    /// 十六进制的摘要
+    fn finish(self) -> String {
+        self.0
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,336 @@
+#[cfg(test)]
+mod tests {
+    use crate::cache::{CacheKey, CacheKeyInputs, ImportReport, TranslationCache, glossary_hash};
+    use crate::glossary::Glossary;
+    use crate::manager::TranslationManager;
+    use crate::testing::MockTranslator;
+    use std::path::PathBuf;
+    use unic_langid::LanguageIdentifier;
+
+    fn lang(tag: &str) -> LanguageIdentifier {
+        tag.parse().unwrap()
+    }
+
+    fn inputs() -> CacheKeyInputs {
+        CacheKeyInputs::new("openai")
+            .model("gpt-4")
+            .prompt("Translate {source_lang} to {target_lang}.")
+            .glossary(&Glossary::new().term("Rust", "Rust"))
+            .option("html", false)
+    }
+
+    fn key(namespace: Option<&str>, inputs: &CacheKeyInputs) -> CacheKey {
+        CacheKey::compute(
+            namespace,
+            inputs,
+            "Hello",
+            &lang("zh-Hans"),
+            Some(&lang("en")),
+        )
+    }
+
+    #[test]
+    fn test_cache_key_is_stable() {
+        // 固定的键值保证不同平台和版本计算出相同的键
+        assert_eq!(
+            key(Some("v1"), &inputs()).as_str(),
+            "b7bbd57e7ec7a462409edc5347def48cfe0b972c63d1a19d1a89490c7a55376d"
+        );
+        assert_eq!(key(None, &inputs()), key(None, &inputs()));
+        assert_eq!(key(None, &inputs()).to_string().len(), 64);
+    }
+
+    #[test]
+    fn test_cache_key_changes_with_every_input() {
+        let base = key(None, &inputs());
+        let mut variants = vec![
+            key(Some("v2"), &inputs()),
+            key(Some(""), &inputs()),
+            key(
+                None,
+                &CacheKeyInputs {
+                    provider: "microsoft".to_string(),
+                    ..inputs()
+                },
+            ),
+            key(None, &inputs().model("gpt-4o")),
+            key(
+                None,
+                &CacheKeyInputs {
+                    model: None,
+                    ..inputs()
+                },
+            ),
+            key(None, &inputs().prompt("Translate carefully.")),
+            key(
+                None,
+                &CacheKeyInputs {
+                    prompt_hash: None,
+                    ..inputs()
+                },
+            ),
+            key(
+                None,
+                &inputs().glossary(&Glossary::new().term("Rust", "锈")),
+            ),
+            key(
+                None,
+                &inputs().glossary(&Glossary::new().term("Rust", "Rust").case_insensitive(true)),
+            ),
+            key(
+                None,
+                &CacheKeyInputs {
+                    glossary_hash: None,
+                    ..inputs()
+                },
+            ),
+            key(None, &inputs().option("html", true)),
+            key(None, &inputs().option("category", "custom")),
+            CacheKey::compute(
+                None,
+                &inputs(),
+                "Hello!",
+                &lang("zh-Hans"),
+                Some(&lang("en")),
+            ),
+            CacheKey::compute(
+                None,
+                &inputs(),
+                "Hello",
+                &lang("zh-Hant"),
+                Some(&lang("en")),
+            ),
+            CacheKey::compute(
+                None,
+                &inputs(),
+                "Hello",
+                &lang("zh-Hans"),
+                Some(&lang("fr")),
+            ),
+            CacheKey::compute(None, &inputs(), "Hello", &lang("zh-Hans"), None),
+        ];
+        let count = variants.len();
+        variants.push(base);
+        variants.sort();
+        variants.dedup();
+        assert_eq!(variants.len(), count + 1);
+    }
+
+    #[test]
+    fn test_cache_key_field_boundaries() {
+        // 字段边界不同的输入不会拼接成相同的键
+        let split =
+            |provider: &str, model: &str| key(None, &CacheKeyInputs::new(provider).model(model));
+        assert_ne!(split("open", "ai-gpt"), split("openai", "-gpt"));
+        let options =
+            |name: &str, value: &str| key(None, &CacheKeyInputs::new("p").option(name, value));
+        assert_ne!(options("ab", "c"), options("a", "bc"));
+    }
+
+    #[test]
+    fn test_prompt_whitespace_is_normalized() {
+        assert_eq!(
+            CacheKeyInputs::new("openai").prompt("  Translate\n\tto  {target_lang}. "),
+            CacheKeyInputs::new("openai").prompt("Translate to {target_lang}.")
+        );
+        // 术语的添加顺序不影响哈希
+        assert_eq!(
+            glossary_hash(&Glossary::new().term("a", "1").term("b", "2")),
+            glossary_hash(&Glossary::new().term("b", "2").term("a", "1"))
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_prompt() {
+        let inputs = CacheKeyInputs::new("openai")
+            .model("gpt-4o")
+            .option("html", true);
+        assert_eq!(
+            inputs.fingerprint(),
+            inputs.clone().prompt("Translate to zh.").fingerprint()
+        );
+        assert_ne!(
+            inputs.fingerprint(),
+            inputs.clone().option("html", false).fingerprint()
+        );
+        assert_ne!(
+            inputs.fingerprint(),
+            inputs.clone().model("gpt-4").fingerprint()
+        );
+    }
+
+    /// 测试专用的导出文件路径，测试结束时删除
+    struct ExportFile(PathBuf);
+
+    impl ExportFile {
+        fn new(name: &str) -> Self {
+            Self(std::env::temp_dir().join(format!(
+                "async-translate-{}-{}.json",
+                name,
+                std::process::id()
+            )))
+        }
+    }
+
+    impl Drop for ExportFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    /// 使用模型 `model` 的模拟翻译器和新的缓存创建管理器
+    fn cached_manager(model: &str, prefix: &str) -> (TranslationManager, MockTranslator) {
+        let translator = MockTranslator::new().prefix(prefix).cache_key_inputs(
+            CacheKeyInputs::new("mock")
+                .model(model)
+                .prompt("Translate."),
+        );
+        let mut manager = TranslationManager::new().with_cache(TranslationCache::new());
+        manager.add_translator("mock", Box::new(translator.clone()));
+        (manager, translator)
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let (ci, ci_translator) = cached_manager("gpt-4o", "CI: ");
+        let zh = lang("zh");
+        for text in ["Hello", "World", "Hello"] {
+            ci.translate("mock", text, &zh, Some(&lang("en")))
+                .await
+                .unwrap();
+        }
+        // 写入的译文立即可以读到
+        assert_eq!(ci_translator.calls(), 2);
+        assert_eq!(ci.cache().unwrap().len(), 2);
+
+        let file = ExportFile::new("round-trip");
+        assert_eq!(ci.cache().unwrap().export(&file.0).unwrap(), 2);
+
+        let (production, translator) = cached_manager("gpt-4o", "Production: ");
+        let cache = production.cache().unwrap();
+        assert_eq!(
+            cache.import(&file.0, &production).unwrap(),
+            ImportReport {
+                loaded: 2,
+                skipped: 0,
+                conflicting: 0
+            }
+        );
+        assert_eq!(
+            production
+                .translate("mock", "World", &zh, Some(&lang("en")))
+                .await
+                .unwrap(),
+            "CI: World"
+        );
+        // 源语言不同，键也不同
+        assert_eq!(
+            production
+                .translate("mock", "World", &zh, None)
+                .await
+                .unwrap(),
+            "Production: World"
+        );
+        assert_eq!(translator.calls(), 1);
+
+        // 重复导入不改变缓存，已有的相同译文计为导入
+        let report = cache.import(&file.0, &production).unwrap();
+        assert_eq!((report.loaded, report.conflicting), (2, 0));
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_import_skips_mismatched_entries() {
+        let (ci, _) = cached_manager("gpt-4o", "CI: ");
+        ci.translate("mock", "Hello", &lang("zh"), None)
+            .await
+            .unwrap();
+        let file = ExportFile::new("mismatched");
+        ci.cache().unwrap().export(&file.0).unwrap();
+
+        // 模型不同的管理器跳过所有条目，之后按自己的配置翻译
+        let (other_model, translator) = cached_manager("gpt-4o-mini", "Mini: ");
+        let report = other_model
+            .cache()
+            .unwrap()
+            .import(&file.0, &other_model)
+            .unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                loaded: 0,
+                skipped: 1,
+                conflicting: 0
+            }
+        );
+        assert!(other_model.cache().unwrap().is_empty());
+        assert_eq!(
+            other_model
+                .translate("mock", "Hello", &lang("zh"), None)
+                .await
+                .unwrap(),
+            "Mini: Hello"
+        );
+        assert_eq!(translator.calls(), 1);
+
+        // 命名空间不同或翻译器未注册时同样跳过
+        let cache = TranslationCache::new();
+        let mut namespaced = TranslationManager::new().with_cache_namespace("v2");
+        namespaced.add_translator(
+            "mock",
+            Box::new(
+                MockTranslator::new().cache_key_inputs(
+                    CacheKeyInputs::new("mock")
+                        .model("gpt-4o")
+                        .prompt("Translate."),
+                ),
+            ),
+        );
+        assert_eq!(cache.import(&file.0, &namespaced).unwrap().skipped, 1);
+        assert_eq!(
+            cache
+                .import(&file.0, &TranslationManager::new())
+                .unwrap()
+                .skipped,
+            1
+        );
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_keeps_conflicting_entries() {
+        let (ci, _) = cached_manager("gpt-4o", "CI: ");
+        let (production, _) = cached_manager("gpt-4o", "Production: ");
+        for manager in [&ci, &production] {
+            manager
+                .translate("mock", "Hello", &lang("zh"), None)
+                .await
+                .unwrap();
+        }
+        let file = ExportFile::new("conflicting");
+        ci.cache().unwrap().export(&file.0).unwrap();
+
+        let report = production
+            .cache()
+            .unwrap()
+            .import(&file.0, &production)
+            .unwrap();
+        assert_eq!(report.conflicting, 1);
+        assert_eq!(
+            production
+                .translate("mock", "Hello", &lang("zh"), None)
+                .await
+                .unwrap(),
+            "Production: Hello"
+        );
+
+        std::fs::write(&file.0, "not json").unwrap();
+        assert!(
+            production
+                .cache()
+                .unwrap()
+                .import(&file.0, &production)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,39 @@
+//! 时钟抽象
+//!
+//! RPM 限制、token 过期、重试退避和统计窗口都通过 [`Clock`] 获取时间和等待，
+//! 测试中可以替换为 `testing::MockClock` 手动推进时间，避免依赖真实时间。
+
+use std::fmt;
+use std::time::{Duration, Instant, SystemTime};
+
+/// 时钟接口
+#[async_trait::async_trait]
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// 获取当前时间
+    fn now(&self) -> Instant;
+
+    /// 获取当前的日历时间，用于按整点或整天对齐的统计窗口和维护窗口
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    /// 等待指定的时长
+    async fn sleep(&self, duration: Duration);
+}
+
+/// 基于 tokio 的默认时钟
+///
+/// 使用 `tokio::time`，因此在 tokio 的暂停时间模式下同样生效
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioClock;
+
+#[async_trait::async_trait]
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
@@ -0,0 +1,333 @@
+//! 并发许可的获取
+//!
+//! 两个翻译器都通过 [`ConcurrencyLimiter`] 获取并发许可。可以限制排队等待的请求数，
+//! 以及单个请求等待许可的最长时间，超出时立即返回 [`TranslationError::Overloaded`]，
+//! 便于调用方削减负载。
+//!
+//! [`ConcurrencyLimiter::close`] 关闭后，新的请求和仍在排队的请求都返回
+//! [`TranslationError::TranslatorClosed`]，已持有许可的请求不受影响。
+//!
+//! 启用自适应并发后按 AIMD（加性增、乘性减）调整生效的许可数：每收到一个 429 或 503
+//! 响应减半（不低于 1），此后每连续成功与当前许可数相同的次数加 1，直到恢复配置的许可数。
+//! 减少时先移除空闲的许可，其余的在请求归还许可时移除。
+//!
+//! 设置了 [`QueueMetrics`] 时，每次拿到许可记录等待时长，排队的请求数变化时更新深度。
+
+use crate::clock::Clock;
+use crate::error::TranslationError;
+use crate::metrics::QueueMetrics;
+use crate::options::Deadline;
+use reqwest::StatusCode;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{Notify, Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+/// 带排队限制的并发许可
+#[derive(Debug)]
+pub(crate) struct ConcurrencyLimiter {
+    semaphore: Semaphore,
+    /// 许可总数
+    permits: usize,
+    /// 是否已关闭
+    closed: AtomicBool,
+    /// 关闭时唤醒排队的请求
+    closing: Notify,
+    /// 正在等待许可的请求数
+    waiters: AtomicUsize,
+    /// 允许同时等待许可的最大请求数，None 表示不限制
+    max_queue_depth: Option<usize>,
+    /// 自适应并发的状态，None 表示不启用
+    adaptive: Option<Mutex<Adaptive>>,
+    /// 排队指标，None 表示不记录
+    metrics: Option<Arc<QueueMetrics>>,
+}
+
+/// 自适应并发的状态，`limit + forgotten + debt` 总是等于许可总数
+#[derive(Debug)]
+struct Adaptive {
+    /// 当前生效的许可数
+    limit: usize,
+    /// 已从信号量中移除的许可数
+    forgotten: usize,
+    /// 正被请求持有、归还时需要移除的许可数
+    debt: usize,
+    /// 上次调整后连续成功的请求数
+    successes: usize,
+}
+
+/// 并发许可，释放时归还给 [`ConcurrencyLimiter`]
+pub(crate) struct Permit<'a> {
+    permit: Option<SemaphorePermit<'a>>,
+    limiter: &'a ConcurrencyLimiter,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if let Some(permit) = self.permit.take() {
+            if self.limiter.take_debt() {
+                permit.forget();
+            }
+        }
+    }
+}
+
+/// 离开等待队列时减少等待计数，请求被取消时同样生效
+struct WaiterGuard<'a>(&'a ConcurrencyLimiter);
+
+impl Drop for WaiterGuard<'_> {
+    fn drop(&mut self) {
+        self.0.waiters.fetch_sub(1, Ordering::AcqRel);
+        if let Some(metrics) = &self.0.metrics {
+            metrics.leave();
+        }
+    }
+}
+
+impl ConcurrencyLimiter {
+    pub(crate) fn new(permits: usize, max_queue_depth: Option<usize>) -> Self {
+        Self {
+            semaphore: Semaphore::new(permits),
+            permits,
+            closed: AtomicBool::new(false),
+            closing: Notify::new(),
+            waiters: AtomicUsize::new(0),
+            max_queue_depth,
+            adaptive: None,
+            metrics: None,
+        }
+    }
+
+    /// 记录排队指标，多个限制器可以共享同一份指标
+    pub(crate) fn metrics(mut self, metrics: Arc<QueueMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// 启用或关闭自适应并发
+    pub(crate) fn adaptive(mut self, enabled: bool) -> Self {
+        self.adaptive = enabled.then(|| {
+            Mutex::new(Adaptive {
+                limit: self.permits,
+                forgotten: 0,
+                debt: 0,
+                successes: 0,
+            })
+        });
+        self
+    }
+
+    /// 包装拿到的许可并记录等待时长，`depth` 为开始排队时的排队请求数，无需排队时为 0
+    fn permit<'a>(
+        &'a self,
+        permit: SemaphorePermit<'a>,
+        wait: Duration,
+        depth: usize,
+    ) -> Permit<'a> {
+        if let Some(metrics) = &self.metrics {
+            metrics.granted(wait, depth);
+        }
+        Permit {
+            permit: Some(permit),
+            limiter: self,
+        }
+    }
+
+    /// 获取一个并发许可
+    ///
+    /// 有空闲许可时立即返回；否则排队等待，等待的请求数超过 `max_queue_depth`
+    /// 或等待时间超过 `max_wait` 时返回 [`TranslationError::Overloaded`]，
+    /// 已关闭时返回 [`TranslationError::TranslatorClosed`]
+    pub(crate) async fn acquire(
+        &self,
+        max_wait: Option<Duration>,
+    ) -> Result<Permit<'_>, TranslationError> {
+        self.acquire_with(max_wait, None).await
+    }
+
+    /// 获取一个并发许可，`deadline` 不为 None 时用其中的时钟等待总时限的剩余时间，
+    /// 到期时返回 [`TranslationError::DeadlineExceeded`]
+    async fn acquire_with(
+        &self,
+        max_wait: Option<Duration>,
+        deadline: Option<(Deadline, &dyn Clock)>,
+    ) -> Result<Permit<'_>, TranslationError> {
+        // 先注册关闭通知再检查标志，避免错过排队期间的关闭
+        let closing = self.closing.notified();
+        tokio::pin!(closing);
+        closing.as_mut().enable();
+        if self.is_closed() {
+            return Err(TranslationError::TranslatorClosed);
+        }
+        if let Ok(permit) = self.semaphore.try_acquire() {
+            return Ok(self.permit(permit, Duration::ZERO, 0));
+        }
+
+        let start = Instant::now();
+        let waiters = self.waiters.fetch_add(1, Ordering::AcqRel) + 1;
+        let depth = self
+            .metrics
+            .as_ref()
+            .map_or(waiters, |metrics| metrics.enter());
+        let _guard = WaiterGuard(self);
+        if self.max_queue_depth.is_some_and(|depth| waiters > depth) {
+            return Err(TranslationError::Overloaded {
+                waited: Duration::ZERO,
+            });
+        }
+
+        let expired = async {
+            match deadline {
+                Some((deadline, clock)) => clock.sleep(deadline.remaining(clock.now())).await,
+                None => std::future::pending().await,
+            }
+        };
+        let wait = async {
+            tokio::select! {
+                biased;
+                _ = closing => Err(TranslationError::TranslatorClosed),
+                // 信号量只在关闭时关闭
+                acquired = self.semaphore.acquire() => {
+                    acquired.map_err(|_| TranslationError::TranslatorClosed)
+                }
+                () = expired => Err(TranslationError::DeadlineExceeded {
+                    budget: deadline.map_or(Duration::ZERO, |(deadline, _)| deadline.budget()),
+                }),
+            }
+        };
+        let permit = match max_wait {
+            Some(max_wait) => tokio::time::timeout(max_wait, wait).await.map_err(|_| {
+                TranslationError::Overloaded {
+                    waited: start.elapsed(),
+                }
+            })??,
+            None => wait.await?,
+        };
+        Ok(self.permit(permit, start.elapsed(), depth))
+    }
+
+    /// 获取一个并发许可，等待时间同时不超过总时限的剩余时间
+    ///
+    /// 总时限按 `clock` 计时，先于 `max_wait` 到期时返回 [`TranslationError::DeadlineExceeded`]
+    pub(crate) async fn acquire_within(
+        &self,
+        max_wait: Option<Duration>,
+        deadline: Option<Deadline>,
+        clock: &dyn Clock,
+    ) -> Result<Permit<'_>, TranslationError> {
+        let Some(deadline) = deadline else {
+            return self.acquire(max_wait).await;
+        };
+        if self.is_closed() {
+            return Err(TranslationError::TranslatorClosed);
+        }
+        if let Ok(permit) = self.semaphore.try_acquire() {
+            return Ok(self.permit(permit, Duration::ZERO, 0));
+        }
+        let remaining = deadline.remaining(clock.now());
+        if max_wait.is_some_and(|max_wait| max_wait < remaining) {
+            return self.acquire(max_wait).await;
+        }
+        deadline.check(clock.now(), Duration::ZERO)?;
+        self.acquire_with(None, Some((deadline, clock))).await
+    }
+
+    /// 关闭：拒绝新的请求和正在排队的请求，并等待已发出的许可全部归还
+    ///
+    /// 可以多次调用，每次调用都等到许可全部归还后返回
+    pub(crate) async fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.closing.notify_waiters();
+        // 恢复自适应并发移除的许可，之后不再调整
+        if let Some(adaptive) = &self.adaptive {
+            let mut adaptive = adaptive.lock().unwrap();
+            self.semaphore.add_permits(adaptive.forgotten);
+            *adaptive = Adaptive {
+                limit: self.permits,
+                forgotten: 0,
+                debt: 0,
+                successes: 0,
+            };
+        }
+        let permits = u32::try_from(self.permits).unwrap_or(u32::MAX);
+        // 排队的请求被唤醒后放弃等待，拿到全部许可说明没有请求仍在进行。另一次 close
+        // 先完成时这里获取失败，它关闭信号量后同时归还了全部许可
+        if let Ok(all) = self.semaphore.acquire_many(permits).await {
+            self.semaphore.close();
+            drop(all);
+        }
+    }
+
+    /// 是否已关闭
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// 当前空闲的许可数
+    pub(crate) fn available_permits(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// 当前生效的许可数，未启用自适应并发时等于许可总数
+    pub(crate) fn effective_limit(&self) -> usize {
+        self.adaptive
+            .as_ref()
+            .map_or(self.permits, |adaptive| adaptive.lock().unwrap().limit)
+    }
+
+    /// 根据响应的状态码调整生效的许可数，未启用自适应并发时不做任何事
+    pub(crate) fn observe(&self, status: StatusCode) {
+        let Some(adaptive) = &self.adaptive else {
+            return;
+        };
+        let mut adaptive = adaptive.lock().unwrap();
+        if self.is_closed() {
+            return;
+        }
+        if status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE {
+            let limit = (adaptive.limit / 2).max(1);
+            let reduce = adaptive.limit - limit;
+            let forgotten = self.semaphore.forget_permits(reduce);
+            adaptive.forgotten += forgotten;
+            adaptive.debt += reduce - forgotten;
+            adaptive.limit = limit;
+            adaptive.successes = 0;
+        } else if status.is_success() && adaptive.limit < self.permits {
+            adaptive.successes += 1;
+            if adaptive.successes >= adaptive.limit {
+                adaptive.successes = 0;
+                adaptive.limit += 1;
+                if adaptive.debt > 0 {
+                    adaptive.debt -= 1;
+                } else {
+                    adaptive.forgotten -= 1;
+                    self.semaphore.add_permits(1);
+                }
+            }
+        }
+    }
+
+    /// 归还许可时是否需要移除它
+    fn take_debt(&self) -> bool {
+        let Some(adaptive) = &self.adaptive else {
+            return false;
+        };
+        let mut adaptive = adaptive.lock().unwrap();
+        if adaptive.debt == 0 {
+            return false;
+        }
+        adaptive.debt -= 1;
+        adaptive.forgotten += 1;
+        true
+    }
+
+    /// 当前等待许可的请求数
+    #[cfg(test)]
+    pub(crate) fn waiters(&self) -> usize {
+        self.waiters.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,203 @@
+#[cfg(test)]
+mod tests {
+    use crate::clock::{Clock, TokioClock};
+    use crate::concurrency::ConcurrencyLimiter;
+    use crate::error::TranslationError;
+    use crate::options::Deadline;
+    use reqwest::StatusCode;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_max_wait_rejects_after_deadline() {
+        let limiter = ConcurrencyLimiter::new(1, None);
+        let held = limiter.acquire(None).await.unwrap();
+
+        let start = tokio::time::Instant::now();
+        match limiter.acquire(Some(Duration::from_millis(200))).await {
+            Err(TranslationError::Overloaded { waited }) => {
+                assert_eq!(waited, Duration::from_millis(200));
+            }
+            other => panic!("Expected Overloaded, got {:?}", other.err()),
+        }
+        assert_eq!(start.elapsed(), Duration::from_millis(200));
+        assert_eq!(limiter.waiters(), 0);
+
+        // 超时的请求不占用许可
+        drop(held);
+        assert_eq!(limiter.available_permits(), 1);
+        let _permit = limiter
+            .acquire(Some(Duration::from_millis(200)))
+            .await
+            .unwrap();
+        assert_eq!(start.elapsed(), Duration::from_millis(200));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_queue_depth_rejects_immediately() {
+        let limiter = Arc::new(ConcurrencyLimiter::new(1, Some(1)));
+        let held = limiter.acquire(None).await.unwrap();
+
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire(None).await.map(drop) }
+        });
+        while limiter.waiters() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let start = tokio::time::Instant::now();
+        match limiter.acquire(Some(Duration::from_secs(10))).await {
+            Err(TranslationError::Overloaded { waited }) => assert_eq!(waited, Duration::ZERO),
+            other => panic!("Expected Overloaded, got {:?}", other.err()),
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        assert_eq!(limiter.waiters(), 1);
+
+        // 排队中的请求在许可释放后正常获得许可
+        drop(held);
+        queued.await.unwrap().unwrap();
+        assert_eq!(limiter.waiters(), 0);
+        assert_eq!(limiter.available_permits(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cancelled_waiter_leaves_queue() {
+        let limiter = Arc::new(ConcurrencyLimiter::new(1, Some(1)));
+        let _held = limiter.acquire(None).await.unwrap();
+
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire(None).await.map(drop) }
+        });
+        while limiter.waiters() == 0 {
+            tokio::task::yield_now().await;
+        }
+        queued.abort();
+        let _ = queued.await;
+        assert_eq!(limiter.waiters(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_deadline_bounds_queue_wait() {
+        let limiter = ConcurrencyLimiter::new(1, None);
+        let _held = limiter.acquire(None).await.unwrap();
+        let deadline = Deadline::new(Duration::from_secs(10)).start(TokioClock.now());
+
+        let start = tokio::time::Instant::now();
+        assert!(matches!(
+            limiter
+                .acquire_within(None, Some(deadline), &TokioClock)
+                .await,
+            Err(TranslationError::DeadlineExceeded { .. })
+        ));
+        assert_eq!(start.elapsed(), Duration::from_secs(10));
+
+        // 已到期的总时限不再等待，较短的 `max_wait` 仍按过载处理
+        assert!(matches!(
+            limiter
+                .acquire_within(None, Some(deadline), &TokioClock)
+                .await,
+            Err(TranslationError::DeadlineExceeded { .. })
+        ));
+        assert_eq!(start.elapsed(), Duration::from_secs(10));
+        let deadline = Deadline::new(Duration::from_secs(10)).start(TokioClock.now());
+        assert!(matches!(
+            limiter
+                .acquire_within(Some(Duration::from_secs(1)), Some(deadline), &TokioClock)
+                .await,
+            Err(TranslationError::Overloaded { .. })
+        ));
+        assert_eq!(limiter.waiters(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_close_rejects_queue_and_waits_for_permits() {
+        let limiter = Arc::new(ConcurrencyLimiter::new(1, None));
+        let held = limiter.acquire(None).await.unwrap();
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire(None).await.map(drop) }
+        });
+        while limiter.waiters() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let close = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.close().await }
+        });
+        assert!(matches!(
+            queued.await.unwrap(),
+            Err(TranslationError::TranslatorClosed)
+        ));
+        assert!(matches!(
+            limiter.acquire(Some(Duration::from_secs(1))).await,
+            Err(TranslationError::TranslatorClosed)
+        ));
+
+        // 持有的许可归还后 close 才返回，重复关闭立即返回
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert!(!close.is_finished());
+        drop(held);
+        close.await.unwrap();
+        assert_eq!(limiter.available_permits(), 1);
+        limiter.close().await;
+        let deadline = Deadline::new(Duration::from_secs(10)).start(TokioClock.now());
+        assert!(matches!(
+            limiter
+                .acquire_within(None, Some(deadline), &TokioClock)
+                .await,
+            Err(TranslationError::TranslatorClosed)
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_adaptive_limit_decreases_and_recovers() {
+        let limiter = ConcurrencyLimiter::new(4, None).adaptive(true);
+        let held: Vec<_> = futures::future::join_all((0..3).map(|_| limiter.acquire(None)))
+            .await
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+
+        // 4 -> 2：空闲的一个许可立即移除，另一个在归还时移除
+        limiter.observe(StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limiter.effective_limit(), 2);
+        assert_eq!(limiter.available_permits(), 0);
+        drop(held);
+        assert_eq!(limiter.available_permits(), 2);
+        limiter.observe(StatusCode::SERVICE_UNAVAILABLE);
+        limiter.observe(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(limiter.effective_limit(), 1);
+        assert_eq!(limiter.available_permits(), 1);
+
+        // 其他错误不影响，连续成功与当前许可数相同的次数后加 1
+        limiter.observe(StatusCode::BAD_REQUEST);
+        assert_eq!(limiter.effective_limit(), 1);
+        for expected in [2, 2, 3, 3, 3, 4, 4, 4, 4] {
+            limiter.observe(StatusCode::OK);
+            assert_eq!(limiter.effective_limit(), expected);
+        }
+        assert_eq!(limiter.available_permits(), 4);
+
+        // 关闭时恢复全部许可，之后不再调整
+        limiter.observe(StatusCode::TOO_MANY_REQUESTS);
+        let held = limiter.acquire(None).await.unwrap();
+        limiter.observe(StatusCode::TOO_MANY_REQUESTS);
+        let close = limiter.close();
+        drop(held);
+        close.await;
+        assert_eq!(limiter.available_permits(), 4);
+        limiter.observe(StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limiter.effective_limit(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_non_adaptive_ignores_status() {
+        let limiter = ConcurrencyLimiter::new(4, None);
+        limiter.observe(StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limiter.effective_limit(), 4);
+        assert_eq!(limiter.available_permits(), 4);
+    }
+}
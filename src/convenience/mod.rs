@@ -0,0 +1,105 @@
+//! 常用翻译场景的便捷函数
+//!
+//! [`convert_chinese`] 通过任意翻译器在简体和繁体中文之间转换。微软翻译把简繁转换当作
+//! 普通的语言对处理，OpenAI 翻译器则根据语言代码中的地区（例如 `zh-Hant-TW`）选择用词。
+//! 源语言总是显式指定，避免短文本被误判为日文或其他语言。
+
+use crate::{error::TranslationError, options::TranslateOptions, translator::Translator};
+use unic_langid::LanguageIdentifier;
+
+/// 中文的书写变体
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChineseVariant {
+    /// 简体中文（`zh-Hans`）
+    Hans,
+    /// 繁体中文（`zh-Hant`）
+    Hant,
+    /// 香港繁体中文（`zh-Hant-HK`）
+    HK,
+    /// 台湾繁体中文（`zh-Hant-TW`）
+    TW,
+    /// 粤语书面语（`yue`），需要港式粤语用词而不只是繁体字时使用
+    Yue,
+}
+
+impl ChineseVariant {
+    /// 该变体对应的语言标识符
+    pub fn language(self) -> LanguageIdentifier {
+        let code = match self {
+            ChineseVariant::Hans => "zh-Hans",
+            ChineseVariant::Hant => "zh-Hant",
+            ChineseVariant::HK => "zh-Hant-HK",
+            ChineseVariant::TW => "zh-Hant-TW",
+            ChineseVariant::Yue => "yue",
+        };
+        code.parse()
+            .expect("built-in Chinese language tags are valid")
+    }
+
+    /// 转换为该变体时使用的源语言：转换为简体时为 `zh-Hant`，其他为 `zh-Hans`
+    pub fn source_language(self) -> LanguageIdentifier {
+        match self {
+            ChineseVariant::Hans => ChineseVariant::Hant.language(),
+            _ => ChineseVariant::Hans.language(),
+        }
+    }
+}
+
+/// 把中文文本转换为指定的书写变体
+///
+/// # 参数
+///
+/// * `translator` - 执行转换的翻译器
+/// * `text` - 需要转换的文本
+/// * `variant` - 目标变体
+/// * `options` - 翻译配置选项
+///
+/// # 返回值
+///
+/// 返回转换后的文本，如果出错则返回错误信息
+pub async fn convert_chinese(
+    translator: &dyn Translator,
+    text: &str,
+    variant: ChineseVariant,
+    options: &TranslateOptions,
+) -> Result<String, TranslationError> {
+    translator
+        .translate_with_options(
+            text,
+            &variant.language(),
+            Some(&variant.source_language()),
+            options,
+        )
+        .await
+}
+
+/// 批量把中文文本转换为指定的书写变体，使用翻译器的批量接口
+///
+/// # 参数
+///
+/// * `translator` - 执行转换的翻译器
+/// * `texts` - 需要转换的文本
+/// * `variant` - 目标变体
+/// * `options` - 翻译配置选项
+///
+/// # 返回值
+///
+/// 返回转换后的文本，顺序与输入一致
+pub async fn convert_chinese_batch(
+    translator: &dyn Translator,
+    texts: &[&str],
+    variant: ChineseVariant,
+    options: &TranslateOptions,
+) -> Result<Vec<String>, TranslationError> {
+    translator
+        .translate_batch_with_options(
+            texts,
+            &variant.language(),
+            Some(&variant.source_language()),
+            options,
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests {
+    use crate::convenience::{ChineseVariant, convert_chinese, convert_chinese_batch};
+    use crate::error::TranslationError;
+    use crate::langmap;
+    use crate::options::TranslateOptions;
+    use crate::translator::Translator;
+    use std::sync::Mutex;
+    use unic_langid::LanguageIdentifier;
+
+    #[test]
+    fn test_variant_languages() {
+        let cases = [
+            (ChineseVariant::Hans, "zh-Hans", "zh-Hant", "zh-Hans"),
+            (ChineseVariant::Hant, "zh-Hant", "zh-Hans", "zh-Hant"),
+            (ChineseVariant::HK, "zh-Hant-HK", "zh-Hans", "zh-Hant"),
+            (ChineseVariant::TW, "zh-Hant-TW", "zh-Hans", "zh-Hant"),
+            (ChineseVariant::Yue, "yue", "zh-Hans", "yue"),
+        ];
+        for (variant, target, source, microsoft) in cases {
+            assert_eq!(variant.language().to_string(), target);
+            assert_eq!(variant.source_language().to_string(), source);
+            assert_eq!(langmap::microsoft_code(&variant.language()), microsoft);
+        }
+    }
+
+    /// 记录每次调用的语言对的翻译器，批量调用单独记录
+    #[derive(Default)]
+    struct RecordingTranslator {
+        calls: Mutex<Vec<(String, String, Option<String>)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Translator for RecordingTranslator {
+        async fn translate_with_options(
+            &self,
+            text: &str,
+            target_lang: &LanguageIdentifier,
+            source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            self.calls.lock().unwrap().push((
+                "single".to_string(),
+                target_lang.to_string(),
+                source_lang.map(|lang| lang.to_string()),
+            ));
+            Ok(format!("[{}]{}", target_lang, text))
+        }
+
+        async fn translate_batch_with_options(
+            &self,
+            texts: &[&str],
+            target_lang: &LanguageIdentifier,
+            source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<Vec<String>, TranslationError> {
+            self.calls.lock().unwrap().push((
+                format!("batch of {}", texts.len()),
+                target_lang.to_string(),
+                source_lang.map(|lang| lang.to_string()),
+            ));
+            Ok(texts
+                .iter()
+                .map(|text| format!("[{}]{}", target_lang, text))
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_convert_chinese_sets_source_and_uses_batch() {
+        let translator = RecordingTranslator::default();
+        let options = TranslateOptions::default();
+
+        let converted = convert_chinese(&translator, "软件", ChineseVariant::TW, &options)
+            .await
+            .unwrap();
+        assert_eq!(converted, "[zh-Hant-TW]软件");
+        let converted = convert_chinese_batch(
+            &translator,
+            &["軟體", "網路"],
+            ChineseVariant::Hans,
+            &options,
+        )
+        .await
+        .unwrap();
+        assert_eq!(converted, vec!["[zh-Hans]軟體", "[zh-Hans]網路"]);
+
+        assert_eq!(
+            *translator.calls.lock().unwrap(),
+            vec![
+                (
+                    "single".to_string(),
+                    "zh-Hant-TW".to_string(),
+                    Some("zh-Hans".to_string())
+                ),
+                (
+                    "batch of 2".to_string(),
+                    "zh-Hans".to_string(),
+                    Some("zh-Hant".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_batch_translates_each_text() {
+        let translator = crate::testing::MockTranslator::new().prefix("T:");
+        let converted = convert_chinese_batch(
+            &translator,
+            &["a", "b"],
+            ChineseVariant::Hant,
+            &TranslateOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(converted, vec!["T:a", "T:b"]);
+        assert_eq!(translator.calls(), 2);
+    }
+
+    #[cfg(feature = "microsoft")]
+    #[tokio::test]
+    async fn test_convert_chinese_batch_with_microsoft() {
+        use crate::microsoft::{MicrosoftConfig, MicrosoftTranslator};
+        use serde_json::json;
+        use wiremock::matchers::{body_json, method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(query_param("from", "zh-Hans"))
+            .and(query_param("to", "zh-Hant"))
+            .and(body_json(json!([{"text": "软件"}, {"text": "网络"}])))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"translations": [{"text": "軟體", "to": "zh-Hant"}]},
+                {"translations": [{"text": "網路", "to": "zh-Hant"}]}
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .build(),
+        );
+
+        let converted = convert_chinese_batch(
+            &translator,
+            &["软件", "网络"],
+            ChineseVariant::TW,
+            &TranslateOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(converted, vec!["軟體", "網路"]);
+    }
+}
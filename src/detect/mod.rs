@@ -0,0 +1,415 @@
+//! 基于文字和常用词的轻量级语言检测
+//!
+//! 不依赖外部模型：先统计各文字系统的字符占比，非拉丁文字直接映射到对应语言；
+//! 拉丁文字再根据常用虚词的命中情况区分具体语言。结果只作为提示使用，
+//! 短文本或混合文本的置信度会比较低。
+
+use crate::guard;
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+use std::collections::{BTreeMap, HashMap};
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+use std::sync::Mutex;
+use unic_langid::LanguageIdentifier;
+
+/// 文字系统
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Han,
+    Kana,
+    Hangul,
+    Cyrillic,
+    Greek,
+    Arabic,
+    Hebrew,
+    Thai,
+    Devanagari,
+}
+
+impl Script {
+    /// 按声明顺序排列的所有文字系统，下标与判别值一致
+    const ALL: [Script; 10] = [
+        Script::Latin,
+        Script::Han,
+        Script::Kana,
+        Script::Hangul,
+        Script::Cyrillic,
+        Script::Greek,
+        Script::Arabic,
+        Script::Hebrew,
+        Script::Thai,
+        Script::Devanagari,
+    ];
+
+    fn of(c: char) -> Option<Script> {
+        let script = match c {
+            'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' => Script::Latin,
+            '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '\u{F900}'..='\u{FAFF}' => {
+                Script::Han
+            }
+            '\u{3040}'..='\u{30FF}' => Script::Kana,
+            '\u{AC00}'..='\u{D7AF}' | '\u{1100}'..='\u{11FF}' => Script::Hangul,
+            '\u{0400}'..='\u{04FF}' => Script::Cyrillic,
+            '\u{0370}'..='\u{03FF}' => Script::Greek,
+            '\u{0600}'..='\u{06FF}' => Script::Arabic,
+            '\u{0590}'..='\u{05FF}' => Script::Hebrew,
+            '\u{0E00}'..='\u{0E7F}' => Script::Thai,
+            '\u{0900}'..='\u{097F}' => Script::Devanagari,
+            _ => return None,
+        };
+        Some(script)
+    }
+
+    /// 只使用单一语言的文字系统对应的语言
+    fn language(self) -> Option<&'static str> {
+        match self {
+            Script::Han => Some("zh"),
+            Script::Kana => Some("ja"),
+            Script::Hangul => Some("ko"),
+            Script::Cyrillic => Some("ru"),
+            Script::Greek => Some("el"),
+            Script::Arabic => Some("ar"),
+            Script::Hebrew => Some("he"),
+            Script::Thai => Some("th"),
+            Script::Devanagari => Some("hi"),
+            Script::Latin => None,
+        }
+    }
+}
+
+/// 拉丁文字语言的常用虚词
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "and", "is", "are", "of", "to", "in", "that", "it", "you", "this", "was", "for",
+            "with", "on", "be", "have", "not", "what", "i",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "des", "est", "et", "un", "une", "du", "que", "pas", "pour", "dans",
+            "je", "vous", "nous", "ce", "qui", "sur", "avec",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ein", "eine", "ich", "sie", "es", "zu",
+            "mit", "den", "auf", "für", "von", "wir", "sind", "auch",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "la", "los", "las", "es", "y", "un", "una", "que", "de", "en", "no", "por",
+            "con", "para", "del", "se", "lo", "está", "muy",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "lo", "la", "gli", "le", "è", "e", "un", "una", "che", "di", "non", "per", "con",
+            "sono", "del", "della", "questo", "mi", "ti",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "os", "as", "é", "e", "um", "uma", "que", "de", "não", "para", "com", "do", "da",
+            "em", "você", "eu", "isso", "mas", "está",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "is", "niet", "van", "ik", "je", "dat", "op", "met", "zijn",
+            "voor", "wat", "er", "maar", "ook", "hij", "we",
+        ],
+    ),
+];
+
+/// 能够给出满置信度所需的最少字符数
+const MIN_CONFIDENT_CHARS: usize = 5;
+
+/// 检测文本的语言
+///
+/// 返回检测到的语言及 0 到 1 之间的置信度；无法判断时返回 None
+pub fn detect(text: &str) -> Option<(LanguageIdentifier, f64)> {
+    let mut counts = [0usize; Script::ALL.len()];
+    for c in text.chars() {
+        if let Some(script) = Script::of(c) {
+            counts[script as usize] += 1;
+        }
+    }
+    let total: usize = counts.iter().sum();
+    if total == 0 {
+        return None;
+    }
+    let count = |script: Script| counts[script as usize];
+
+    // 含有假名的汉字文本按日语处理
+    let (script, script_count) = if count(Script::Kana) > 0 {
+        (Script::Kana, count(Script::Kana) + count(Script::Han))
+    } else {
+        Script::ALL
+            .iter()
+            .map(|&script| (script, count(script)))
+            .max_by_key(|&(_, n)| n)?
+    };
+    let share = script_count as f64 / total as f64;
+    let length_factor = (script_count as f64 / MIN_CONFIDENT_CHARS as f64).min(1.0);
+
+    let (language, language_confidence) = match script.language() {
+        Some(language) => (language, 1.0),
+        None => detect_latin(text)?,
+    };
+    let confidence = share * length_factor * language_confidence;
+    Some((language.parse().ok()?, confidence))
+}
+
+/// 根据常用虚词区分拉丁文字语言
+fn detect_latin(text: &str) -> Option<(&'static str, f64)> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    let mut scores: Vec<(&'static str, usize)> = STOPWORDS
+        .iter()
+        .map(|(language, stopwords)| {
+            let hits = words
+                .iter()
+                .filter(|word| stopwords.contains(&word.as_str()))
+                .count();
+            (*language, hits)
+        })
+        .collect();
+    scores.sort_by_key(|&(_, hits)| std::cmp::Reverse(hits));
+    let (language, best) = scores[0];
+    if best == 0 {
+        return None;
+    }
+    let second = scores[1].1;
+    // 虚词约占自然文本的一半，按命中率的两倍计算覆盖度，再乘以领先幅度
+    let coverage = (best as f64 * 2.0 / words.len() as f64).min(1.0);
+    let margin = (best - second) as f64 / best as f64;
+    Some((language, coverage * margin))
+}
+
+/// 译文中目标语言所用文字的加权占比低于该值时，视为不是目标语言
+const MIN_TARGET_SCRIPT_SHARE: f64 = 0.5;
+/// 中文译文中假名的加权占比超过该值时视为日语
+const MAX_CHINESE_KANA_SHARE: f64 = 0.1;
+/// 区分拉丁文字语言所需的最少词数
+const MIN_LATIN_WORDS: usize = 3;
+/// 判定为另一种拉丁文字语言所需的最低置信度
+const MIN_LATIN_MISMATCH_CONFIDENCE: f64 = 0.6;
+
+/// 语言通常使用的文字系统，未列出的语言不做检查
+fn language_scripts(language: &str) -> Option<&'static [Script]> {
+    let scripts: &[Script] = match language {
+        "zh" => &[Script::Han],
+        "ja" => &[Script::Kana, Script::Han],
+        "ko" => &[Script::Hangul, Script::Han],
+        "ru" | "uk" | "be" | "bg" | "mk" | "kk" | "ky" | "mn" | "tg" => &[Script::Cyrillic],
+        "sr" => &[Script::Cyrillic, Script::Latin],
+        "el" => &[Script::Greek],
+        "ar" | "fa" | "ur" | "ps" => &[Script::Arabic],
+        "he" | "yi" => &[Script::Hebrew],
+        "th" => &[Script::Thai],
+        "hi" | "mr" | "ne" => &[Script::Devanagari],
+        "en" | "fr" | "de" | "es" | "it" | "pt" | "nl" | "pl" | "cs" | "sk" | "sl" | "hr"
+        | "ro" | "hu" | "sv" | "da" | "nb" | "nn" | "no" | "fi" | "et" | "lv" | "lt" | "tr"
+        | "az" | "id" | "ms" | "vi" | "tl" | "sw" | "ca" | "eu" | "gl" | "ga" | "is" | "af" => {
+            &[Script::Latin]
+        }
+        _ => return None,
+    };
+    Some(scripts)
+}
+
+/// 检查译文是否为目标语言
+///
+/// 按文字系统的加权占比判断，混有少量其他文字（例如品牌名）的译文仍视为目标语言；
+/// 目标语言使用拉丁文字时，再根据常用虚词区分较长译文的具体语言。
+/// 不是目标语言时返回检测到的语言（无法判断时为 `und`），无法判断或目标语言未知时返回 None
+#[cfg_attr(not(feature = "openai"), allow(dead_code))]
+pub(crate) fn output_language_mismatch(
+    text: &str,
+    target_lang: &LanguageIdentifier,
+) -> Option<String> {
+    let language = target_lang.language.as_str();
+    let expected = language_scripts(language)?;
+    let mut weights = [0.0; Script::ALL.len()];
+    for c in text.chars() {
+        if let Some(script) = Script::of(c) {
+            weights[script as usize] += guard::char_weight(c);
+        }
+    }
+    let total: f64 = weights.iter().sum();
+    if total == 0.0 {
+        return None;
+    }
+    let share =
+        |scripts: &[Script]| scripts.iter().map(|&s| weights[s as usize]).sum::<f64>() / total;
+    let detected = || detect(text).map_or_else(|| "und".to_string(), |(lang, _)| lang.to_string());
+
+    if share(expected) < MIN_TARGET_SCRIPT_SHARE {
+        return Some(detected());
+    }
+    if language == "zh" && share(&[Script::Kana]) > MAX_CHINESE_KANA_SHARE {
+        return Some("ja".to_string());
+    }
+    // 只能区分有常用词表的语言，单词或短语无法可靠区分
+    if expected == [Script::Latin] && STOPWORDS.iter().any(|(known, _)| *known == language) {
+        let words = text
+            .split(|c: char| !c.is_alphabetic())
+            .filter(|word| !word.is_empty())
+            .count();
+        if words >= MIN_LATIN_WORDS {
+            if let Some((found, confidence)) = detect_latin(text) {
+                if found != language && confidence >= MIN_LATIN_MISMATCH_CONFIDENCE {
+                    return Some(found.to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+/// 参与缓存的最长文本（字符数），更长的文本很少原样重复
+pub(crate) const DETECTION_CACHE_MAX_CHARS: usize = 256;
+
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+/// 默认的检测缓存大小
+pub(crate) const DEFAULT_DETECTION_CACHE_SIZE: usize = 256;
+
+/// 语言检测缓存的统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DetectionCacheStats {
+    /// 命中缓存的次数
+    pub hits: u64,
+    /// 未命中缓存的次数（不参与缓存的长文本不计入）
+    pub misses: u64,
+    /// 当前缓存的条目数
+    pub len: usize,
+}
+
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+/// 检测结果的 LRU 缓存，键为规范化后的文本
+///
+/// 固定文本的语言不会变化，条目只会因为容量不足被淘汰
+pub(crate) struct DetectionCache<V> {
+    capacity: usize,
+    state: Mutex<CacheState<V>>,
+}
+
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+struct CacheState<V> {
+    /// 键对应的结果和最近一次使用的序号
+    entries: HashMap<String, (V, u64)>,
+    /// 按使用序号排列的键，第一个最久未使用
+    order: BTreeMap<u64, String>,
+    next_tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+impl<V: Clone> DetectionCache<V> {
+    /// 创建最多保存 `capacity` 条结果的缓存，0 表示不缓存
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(CacheState {
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                next_tick: 0,
+                hits: 0,
+                misses: 0,
+            }),
+        }
+    }
+
+    /// 缓存键：合并空白并转为小写，空文本、长文本或缓存关闭时为 None
+    fn key(&self, text: &str) -> Option<String> {
+        if self.capacity == 0 || text.chars().count() > DETECTION_CACHE_MAX_CHARS {
+            return None;
+        }
+        let key = text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        (!key.is_empty()).then_some(key)
+    }
+
+    /// 查找缓存的结果，命中时将其标记为最近使用
+    pub(crate) fn get(&self, text: &str) -> Option<V> {
+        let key = self.key(text)?;
+        let mut state = self.state.lock().unwrap();
+        let tick = state.next_tick;
+        let CacheState {
+            entries,
+            order,
+            hits,
+            misses,
+            ..
+        } = &mut *state;
+        match entries.get_mut(&key) {
+            Some((value, last_used)) => {
+                let value = value.clone();
+                order.remove(last_used);
+                *last_used = tick;
+                order.insert(tick, key);
+                *hits += 1;
+                state.next_tick += 1;
+                Some(value)
+            }
+            None => {
+                *misses += 1;
+                None
+            }
+        }
+    }
+
+    /// 保存检测结果，超出容量时淘汰最久未使用的条目
+    pub(crate) fn insert(&self, text: &str, value: V) {
+        let Some(key) = self.key(text) else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        let tick = state.next_tick;
+        state.next_tick += 1;
+        if let Some((_, last_used)) = state.entries.insert(key.clone(), (value, tick)) {
+            state.order.remove(&last_used);
+        }
+        state.order.insert(tick, key);
+        while state.entries.len() > self.capacity {
+            let Some((_, oldest)) = state.order.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+    }
+
+    /// 命中统计
+    pub(crate) fn stats(&self) -> DetectionCacheStats {
+        let state = self.state.lock().unwrap();
+        DetectionCacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            len: state.entries.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod tests {
+    use crate::detect::{detect, output_language_mismatch};
+
+    fn language(text: &str) -> Option<(String, f64)> {
+        detect(text).map(|(lang, confidence)| (lang.to_string(), confidence))
+    }
+
+    #[test]
+    fn test_detect_non_latin_scripts() {
+        for (text, expected) in [
+            ("今天天气很好，我们去公园散步吧。", "zh"),
+            ("今日はいい天気ですね。", "ja"),
+            ("오늘 날씨가 좋네요", "ko"),
+            ("Сегодня хорошая погода", "ru"),
+        ] {
+            let (lang, confidence) = language(text).unwrap();
+            assert_eq!(lang, expected, "{}", text);
+            assert!(confidence > 0.9, "{}: {}", text, confidence);
+        }
+    }
+
+    #[test]
+    fn test_detect_latin_languages() {
+        for (text, expected) in [
+            ("The weather is nice and I want to go to the park.", "en"),
+            ("Le chat est sur la table et je suis dans le jardin.", "fr"),
+            (
+                "Der Hund ist nicht in dem Haus und ich bin auch hier.",
+                "de",
+            ),
+        ] {
+            let (lang, confidence) = language(text).unwrap();
+            assert_eq!(lang, expected, "{}", text);
+            assert!(confidence >= 0.5, "{}: {}", text, confidence);
+        }
+    }
+
+    #[test]
+    fn test_detect_ambiguous_inputs() {
+        // 没有可识别的字符或常用词
+        assert_eq!(language(""), None);
+        assert_eq!(language("12345 !?"), None);
+        assert_eq!(language("OK"), None);
+
+        // 过短的文本置信度较低
+        let (lang, confidence) = language("好").unwrap();
+        assert_eq!(lang, "zh");
+        assert!(confidence < 0.5);
+    }
+
+    #[test]
+    fn test_detect_mixed_text_lowers_confidence() {
+        let (lang, mixed) = language("I really like 苹果 and 香蕉 for the breakfast").unwrap();
+        assert_eq!(lang, "en");
+        let (_, pure) = language("I really like apples and bananas for the breakfast").unwrap();
+        assert!(mixed < pure);
+    }
+
+    #[test]
+    fn test_output_language_mismatch() {
+        let cases: &[(&str, &str, Option<&str>)] = &[
+            ("今日はいい天気ですね。", "ja", None),
+            // 只有汉字的日语和夹杂品牌名的译文仍是目标语言
+            ("東京", "ja", None),
+            ("iPhone 15 Pro の新機能", "ja", None),
+            ("Hello", "ja", Some("und")),
+            ("The weather is nice today", "ja", Some("en")),
+            ("今天天气很好", "zh-Hant", None),
+            ("今日はいい天気ですね。", "zh", Some("ja")),
+            ("Привет, мир", "ru", None),
+            ("Hello world", "ru", Some("und")),
+            ("مرحبا بالعالم", "ar", None),
+            ("Bonjour", "ar", Some("und")),
+            ("오늘 날씨가 좋네요", "ko", None),
+            ("Le chat est sur la table", "fr", None),
+            ("The cat is on the table", "fr", Some("en")),
+            ("Сегодня хорошая погода", "en", Some("ru")),
+            // 拉丁文字之间的单词和短语无法区分，视为通过
+            ("Bonjour", "en", None),
+            ("le chat", "en", None),
+            ("Hello", "sv", None),
+            // 没有文字或未知的目标语言不做判断
+            ("42 %", "ja", None),
+            ("Hello", "tlh", None),
+        ];
+        for &(text, target, expected) in cases {
+            let target = target.parse().unwrap();
+            assert_eq!(
+                output_language_mismatch(text, &target).as_deref(),
+                expected,
+                "{} -> {}",
+                text,
+                target
+            );
+        }
+    }
+
+    #[cfg(any(feature = "openai", feature = "microsoft"))]
+    #[test]
+    fn test_detection_cache_lru() {
+        use crate::detect::{DETECTION_CACHE_MAX_CHARS, DetectionCache, DetectionCacheStats};
+
+        let cache = DetectionCache::new(2);
+        assert_eq!(cache.get("Hello"), None);
+        cache.insert("Hello", "en");
+        cache.insert("Bonjour", "fr");
+        // 空白和大小写不同的文本共用同一个条目
+        assert_eq!(cache.get("  hello "), Some("en"));
+
+        // 超出容量时淘汰最久未使用的 "Bonjour"
+        cache.insert("Hallo", "de");
+        assert_eq!(cache.get("Bonjour"), None);
+        assert_eq!(cache.get("HELLO"), Some("en"));
+        assert_eq!(cache.get("Hallo"), Some("de"));
+        assert_eq!(
+            cache.stats(),
+            DetectionCacheStats {
+                hits: 3,
+                misses: 2,
+                len: 2
+            }
+        );
+
+        // 长文本和空文本不参与缓存，也不计入统计
+        let long = "a".repeat(DETECTION_CACHE_MAX_CHARS + 1);
+        cache.insert(&long, "en");
+        assert_eq!(cache.get(&long), None);
+        assert_eq!(cache.get("   "), None);
+        assert_eq!(cache.stats().misses, 2);
+
+        let disabled = DetectionCache::new(0);
+        disabled.insert("Hello", "en");
+        assert_eq!(disabled.get("Hello"), None);
+        assert_eq!(disabled.stats(), DetectionCacheStats::default());
+    }
+}
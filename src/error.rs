@@ -1,21 +1,65 @@
 //! 翻译错误类型定义
 
+use crate::error_code::{self, ErrorCode};
+use crate::trace::RequestTrace;
+use crate::translator::TranslationDetail;
 use std::fmt;
+use unic_langid::LanguageIdentifier;
 
 /// 翻译错误类型
 #[derive(Debug)]
 pub enum TranslationError {
     /// 网络请求错误
-    NetworkError(reqwest::Error),
+    NetworkError {
+        /// 错误的类别
+        kind: NetworkErrorKind,
+        /// 请求的主机名，无法确定时为 None
+        host: Option<String>,
+        /// reqwest 返回的原始错误；主机名在 `dns_failure_ttl` 内解析失败过、
+        /// 没有发出请求而直接失败时，以及复制得到的错误为 None
+        source: Option<reqwest::Error>,
+        /// 认证地址冷却期内直接返回的错误记录冷却期的剩余时长，此时不可重试；其他情况为 None
+        cooldown: Option<std::time::Duration>,
+    },
     /// HTTP 状态错误
     HttpError {
         status: reqwest::StatusCode,
         body: String,
+        /// 请求的请求ID和 traceparent，都没有设置时为 None
+        trace: Option<Box<RequestTrace>>,
+    },
+    /// 翻译服务返回的结构化错误，保留服务端的错误码
+    Provider {
+        /// 翻译服务名称，例如 `"openai"`、`"microsoft"`
+        provider: String,
+        /// 服务端的错误码
+        code: String,
+        /// 服务端的错误信息
+        message: String,
+        /// HTTP 状态码
+        http_status: Option<reqwest::StatusCode>,
+        /// 由各翻译服务根据错误码判断是否可以重试
+        retryable: bool,
+        /// 请求的请求ID和 traceparent，都没有设置时为 None
+        trace: Option<Box<RequestTrace>>,
+    },
+    /// 禁止回退到通用模型时，指定的自定义翻译模型不可用
+    CustomModelUnavailable {
+        /// 翻译服务名称
+        provider: String,
+        /// 自定义模型的类别 ID
+        category: String,
+        /// 服务端的错误信息
+        message: String,
     },
     /// 认证错误
     AuthenticationError(String),
-    /// 超时错误
+    /// 超时错误（包括等待响应头和读取响应体时的超时）
     TimeoutError,
+    /// 响应内容无法解析
+    MalformedResponse(String),
+    /// 响应体超出 `max_response_bytes` 限制
+    ResponseTooLarge { max: usize },
     /// 重试次数耗尽（包含每次尝试的错误信息）
     MaxRetriesExceeded {
         attempts: u32,
@@ -25,37 +69,455 @@ pub enum TranslationError {
     ServiceError(String),
     /// 配置错误
     ConfigurationError(String),
+    /// 文本超出翻译服务的长度限制
+    TextTooLong { length: usize, max: usize },
+    /// 翻译服务以 413 拒绝了请求体
+    RequestTooLarge {
+        /// 被拒绝的请求体字节数
+        bytes: usize,
+    },
+    /// 翻译管理器正在关闭，不再接受新的翻译请求
+    ShuttingDown,
+    /// 翻译器已调用 `close`，不再接受新的翻译请求
+    TranslatorClosed,
+    /// 翻译器过载：等待并发许可超过 `max_queue_wait`，或排队的请求数超过 `max_queue_depth`
+    Overloaded {
+        /// 被拒绝前等待的时长，因排队已满被拒绝时为零
+        waited: std::time::Duration,
+    },
+    /// 自动检测源语言的置信度低于配置的阈值
+    LowConfidenceDetection {
+        /// 检测到的语言
+        language: String,
+        /// 检测置信度
+        score: f64,
+        /// 配置的最低置信度
+        min_score: f64,
+    },
+    /// 译文与原文的长度比例超出 `length_ratio_guard` 范围，可以重试
+    SuspiciousOutput {
+        /// 实际的长度比例
+        ratio: f64,
+        /// 允许的最小比例
+        min_ratio: f64,
+        /// 允许的最大比例
+        max_ratio: f64,
+        /// 被拒绝的译文，重试耗尽后可能作为最终结果返回
+        candidate: Box<TranslationDetail>,
+    },
+    /// 译文不是目标语言（`assert_target_language`），可以重试
+    WrongOutputLanguage {
+        /// 目标语言
+        expected: String,
+        /// 在译文中检测到的语言，无法判断具体语言时为 `und`
+        detected: String,
+    },
+    /// 调用超出 `deadline` 设置的总时限，包括等待会超出时限而提前放弃的情况
+    DeadlineExceeded {
+        /// 总时限的长度
+        budget: std::time::Duration,
+    },
+    /// 翻译器处于计划的维护窗口中，调用不会发送到翻译服务
+    TranslatorUnavailable {
+        /// 翻译器名称
+        translator: String,
+        /// 维护窗口结束的时间
+        until: std::time::SystemTime,
+    },
+    /// 译文中缺少 `protect_numbers` 或 `protect_pattern` 替换出的占位符，受保护的原文无法还原
+    ProtectedTextLost {
+        /// 占位符对应的原文
+        text: String,
+    },
+    /// 一次批量调用的输入字符数超出 `max_batch_chars`，没有发出任何请求；
+    /// 启用 `truncate_to_budget` 时表示该条目因预算用完被跳过
+    BudgetExceeded {
+        /// 整批输入的字符数
+        total: usize,
+        /// 配置的预算
+        max: usize,
+    },
+    /// 译文与原文相同（`error_on_unchanged`），翻译服务可能没有翻译这段文本
+    NotTranslated {
+        /// 翻译器名称
+        translator: String,
+    },
+    /// 内容策略禁止发送原文，没有发出任何请求
+    PolicyViolation {
+        /// 策略给出的原因
+        reason: String,
+    },
     /// 其他错误
     Other(String),
 }
 
+/// 网络错误的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkErrorKind {
+    /// 无法解析主机名
+    Dns,
+    /// TCP 连接失败，例如连接被拒绝、主机不可达
+    Connect,
+    /// TLS 握手或证书校验失败
+    Tls,
+    /// 其他网络错误，例如连接建立后中途断开
+    Other,
+}
+
+impl NetworkErrorKind {
+    /// 按 reqwest 错误的错误链分类
+    ///
+    /// 连接阶段的错误中，hyper 的 DNS 错误信息以 `dns error` 开头，TCP 连接错误带有
+    /// [`std::io::Error`]，其余的来自 TLS 握手
+    fn of(error: &reqwest::Error) -> Self {
+        if !error.is_connect() {
+            return NetworkErrorKind::Other;
+        }
+        let mut source = std::error::Error::source(error);
+        while let Some(err) = source {
+            if err.to_string().starts_with("dns error") {
+                return NetworkErrorKind::Dns;
+            }
+            if err.downcast_ref::<std::io::Error>().is_some() {
+                return NetworkErrorKind::Connect;
+            }
+            source = err.source();
+        }
+        NetworkErrorKind::Tls
+    }
+
+    /// 没有原始错误时显示的失败原因
+    fn reason(self) -> &'static str {
+        match self {
+            NetworkErrorKind::Dns => "host could not be resolved",
+            NetworkErrorKind::Connect => "connection failed",
+            NetworkErrorKind::Tls => "TLS handshake failed",
+            NetworkErrorKind::Other => "request failed",
+        }
+    }
+}
+
 impl TranslationError {
+    /// 复制错误，用于把同一个错误交给多个调用方
+    ///
+    /// [`reqwest::Error`] 无法复制，网络错误复制为类别和主机名相同、`source` 为 None 的错误
+    pub(crate) fn duplicate(&self) -> TranslationError {
+        match self {
+            TranslationError::NetworkError {
+                kind,
+                host,
+                cooldown,
+                ..
+            } => TranslationError::NetworkError {
+                kind: *kind,
+                host: host.clone(),
+                source: None,
+                cooldown: *cooldown,
+            },
+            TranslationError::HttpError {
+                status,
+                body,
+                trace,
+            } => TranslationError::HttpError {
+                status: *status,
+                body: body.clone(),
+                trace: trace.clone(),
+            },
+            TranslationError::Provider {
+                provider,
+                code,
+                message,
+                http_status,
+                retryable,
+                trace,
+            } => TranslationError::Provider {
+                provider: provider.clone(),
+                code: code.clone(),
+                message: message.clone(),
+                http_status: *http_status,
+                retryable: *retryable,
+                trace: trace.clone(),
+            },
+            TranslationError::CustomModelUnavailable {
+                provider,
+                category,
+                message,
+            } => TranslationError::CustomModelUnavailable {
+                provider: provider.clone(),
+                category: category.clone(),
+                message: message.clone(),
+            },
+            TranslationError::AuthenticationError(msg) => {
+                TranslationError::AuthenticationError(msg.clone())
+            }
+            TranslationError::TimeoutError => TranslationError::TimeoutError,
+            TranslationError::MalformedResponse(msg) => {
+                TranslationError::MalformedResponse(msg.clone())
+            }
+            TranslationError::ResponseTooLarge { max } => {
+                TranslationError::ResponseTooLarge { max: *max }
+            }
+            TranslationError::MaxRetriesExceeded { attempts, errors } => {
+                TranslationError::MaxRetriesExceeded {
+                    attempts: *attempts,
+                    errors: errors.iter().map(TranslationError::duplicate).collect(),
+                }
+            }
+            TranslationError::ServiceError(msg) => TranslationError::ServiceError(msg.clone()),
+            TranslationError::ConfigurationError(msg) => {
+                TranslationError::ConfigurationError(msg.clone())
+            }
+            TranslationError::TextTooLong { length, max } => TranslationError::TextTooLong {
+                length: *length,
+                max: *max,
+            },
+            TranslationError::RequestTooLarge { bytes } => {
+                TranslationError::RequestTooLarge { bytes: *bytes }
+            }
+            TranslationError::ShuttingDown => TranslationError::ShuttingDown,
+            TranslationError::TranslatorClosed => TranslationError::TranslatorClosed,
+            TranslationError::Overloaded { waited } => {
+                TranslationError::Overloaded { waited: *waited }
+            }
+            TranslationError::LowConfidenceDetection {
+                language,
+                score,
+                min_score,
+            } => TranslationError::LowConfidenceDetection {
+                language: language.clone(),
+                score: *score,
+                min_score: *min_score,
+            },
+            TranslationError::SuspiciousOutput {
+                ratio,
+                min_ratio,
+                max_ratio,
+                candidate,
+            } => TranslationError::SuspiciousOutput {
+                ratio: *ratio,
+                min_ratio: *min_ratio,
+                max_ratio: *max_ratio,
+                candidate: candidate.clone(),
+            },
+            TranslationError::WrongOutputLanguage { expected, detected } => {
+                TranslationError::WrongOutputLanguage {
+                    expected: expected.clone(),
+                    detected: detected.clone(),
+                }
+            }
+            TranslationError::DeadlineExceeded { budget } => {
+                TranslationError::DeadlineExceeded { budget: *budget }
+            }
+            TranslationError::TranslatorUnavailable { translator, until } => {
+                TranslationError::TranslatorUnavailable {
+                    translator: translator.clone(),
+                    until: *until,
+                }
+            }
+            TranslationError::ProtectedTextLost { text } => {
+                TranslationError::ProtectedTextLost { text: text.clone() }
+            }
+            TranslationError::BudgetExceeded { total, max } => TranslationError::BudgetExceeded {
+                total: *total,
+                max: *max,
+            },
+            TranslationError::NotTranslated { translator } => TranslationError::NotTranslated {
+                translator: translator.clone(),
+            },
+            TranslationError::PolicyViolation { reason } => TranslationError::PolicyViolation {
+                reason: reason.clone(),
+            },
+            TranslationError::Other(msg) => TranslationError::Other(msg.clone()),
+        }
+    }
+
     /// 判断错误是否可以重试
     pub fn is_retryable(&self) -> bool {
         match self {
-            TranslationError::NetworkError(_) => true,
+            // 主机名解析失败后直接失败的错误和冷却期内的错误不再重试，其他网络错误可以重试
+            TranslationError::NetworkError {
+                kind,
+                source,
+                cooldown,
+                ..
+            } => cooldown.is_none() && (source.is_some() || *kind != NetworkErrorKind::Dns),
             TranslationError::HttpError { status, .. } => {
                 // 5xx 状态码通常是服务器端问题，可以重试
                 status.is_server_error()
             }
+            TranslationError::Provider { retryable, .. } => *retryable,
             TranslationError::TimeoutError => true,
+            TranslationError::SuspiciousOutput { .. } => true,
+            TranslationError::WrongOutputLanguage { .. } => true,
             // 其他错误类型，如认证、配置、服务错误等，通常不可重试
             _ => false,
         }
     }
+
+    /// 错误的稳定分类，适合界面据此决定提示方式
+    ///
+    /// 翻译服务的错误按 HTTP 状态码和服务端错误码细分，重试耗尽时取最后一次尝试的分类
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            TranslationError::NetworkError { .. } => ErrorCode::NetworkUnavailable,
+            TranslationError::HttpError { status, .. } => status_code(*status),
+            TranslationError::Provider {
+                provider,
+                code,
+                http_status,
+                ..
+            } => provider_code(provider, code, *http_status),
+            TranslationError::CustomModelUnavailable { .. } => ErrorCode::ModelUnavailable,
+            TranslationError::AuthenticationError(_) => ErrorCode::AuthFailed,
+            TranslationError::TimeoutError | TranslationError::DeadlineExceeded { .. } => {
+                ErrorCode::Timeout
+            }
+            TranslationError::MalformedResponse(_) | TranslationError::ResponseTooLarge { .. } => {
+                ErrorCode::InvalidResponse
+            }
+            TranslationError::MaxRetriesExceeded { errors, .. } => errors
+                .last()
+                .map_or(ErrorCode::ProviderError, TranslationError::code),
+            TranslationError::ServiceError(_) => ErrorCode::ProviderError,
+            TranslationError::ConfigurationError(_) => ErrorCode::Configuration,
+            TranslationError::TextTooLong { .. } | TranslationError::RequestTooLarge { .. } => {
+                ErrorCode::TextTooLong
+            }
+            TranslationError::ShuttingDown => ErrorCode::Cancelled,
+            TranslationError::TranslatorClosed => ErrorCode::Cancelled,
+            TranslationError::Overloaded { .. } => ErrorCode::Overloaded,
+            TranslationError::LowConfidenceDetection { .. } => ErrorCode::DetectionUncertain,
+            TranslationError::SuspiciousOutput { .. } => ErrorCode::SuspiciousOutput,
+            TranslationError::WrongOutputLanguage { .. } => ErrorCode::SuspiciousOutput,
+            TranslationError::BudgetExceeded { .. } => ErrorCode::TextTooLong,
+            TranslationError::NotTranslated { .. } => ErrorCode::SuspiciousOutput,
+            TranslationError::TranslatorUnavailable { .. } => ErrorCode::TranslatorUnavailable,
+            TranslationError::ProtectedTextLost { .. } => ErrorCode::ProtectedTextLost,
+            TranslationError::PolicyViolation { .. } => ErrorCode::PolicyViolation,
+            TranslationError::Other(_) => ErrorCode::Internal,
+        }
+    }
+
+    /// 可以直接展示给最终用户的错误信息
+    ///
+    /// 内置英文和中文，其他语言可以通过 [`crate::register_messages`] 注册，
+    /// 没有对应语言的信息时使用英文
+    pub fn user_message(&self, lang: &LanguageIdentifier) -> String {
+        error_code::message(self.code(), lang)
+    }
+}
+
+/// Microsoft Translator 表示语言无效或不受支持的错误码
+const UNSUPPORTED_LANGUAGE_CODES: [&str; 4] = ["400019", "400023", "400035", "400036"];
+
+/// 按服务端错误码和 HTTP 状态码分类结构化错误
+fn provider_code(
+    provider: &str,
+    code: &str,
+    http_status: Option<reqwest::StatusCode>,
+) -> ErrorCode {
+    match (provider, code) {
+        ("openai", "insufficient_quota" | "billing_hard_limit_reached")
+        | ("microsoft", "403001") => ErrorCode::QuotaExceeded,
+        ("openai", "model_not_found") => ErrorCode::ModelUnavailable,
+        ("openai", "invalid_api_key") => ErrorCode::AuthFailed,
+        ("microsoft", code) if UNSUPPORTED_LANGUAGE_CODES.contains(&code) => {
+            ErrorCode::UnsupportedLanguage
+        }
+        _ => http_status.map_or(ErrorCode::ProviderError, status_code),
+    }
+}
+
+/// 按 HTTP 状态码分类
+fn status_code(status: reqwest::StatusCode) -> ErrorCode {
+    match status.as_u16() {
+        401 | 403 => ErrorCode::AuthFailed,
+        408 | 504 => ErrorCode::Timeout,
+        413 => ErrorCode::TextTooLong,
+        429 => ErrorCode::RateLimited,
+        503 => ErrorCode::Overloaded,
+        _ => ErrorCode::ProviderError,
+    }
 }
 
 impl fmt::Display for TranslationError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            TranslationError::NetworkError(e) => write!(f, "Network error: {}", e),
-            TranslationError::HttpError { status, body } => {
-                write!(f, "HTTP error {}: {}", status, body)
+            TranslationError::NetworkError {
+                source: Some(e), ..
+            } => write!(f, "Network error: {}", e),
+            TranslationError::NetworkError {
+                kind,
+                host,
+                cooldown: Some(remaining),
+                ..
+            } => write!(
+                f,
+                "Network error: {} for host '{}', not retrying for {}s",
+                kind.reason(),
+                host.as_deref().unwrap_or_default(),
+                remaining.as_secs_f64().ceil()
+            ),
+            TranslationError::NetworkError {
+                kind: NetworkErrorKind::Dns,
+                host,
+                ..
+            } => write!(
+                f,
+                "Network error: host '{}' could not be resolved recently, not retrying",
+                host.as_deref().unwrap_or_default()
+            ),
+            TranslationError::NetworkError { kind, host, .. } => write!(
+                f,
+                "Network error: {} for host '{}'",
+                kind.reason(),
+                host.as_deref().unwrap_or_default()
+            ),
+            TranslationError::HttpError {
+                status,
+                body,
+                trace,
+            } => {
+                write!(f, "HTTP error {}: {}", status, body)?;
+                if let Some(trace) = trace {
+                    write!(f, " [{}]", trace)?;
+                }
+                Ok(())
+            }
+            TranslationError::Provider {
+                provider,
+                code,
+                message,
+                http_status,
+                trace,
+                ..
+            } => {
+                write!(f, "{} error {}: {}", provider, code, message)?;
+                if let Some(status) = http_status {
+                    write!(f, " (HTTP {})", status)?;
+                }
+                if let Some(trace) = trace {
+                    write!(f, " [{}]", trace)?;
+                }
+                Ok(())
             }
+            TranslationError::CustomModelUnavailable {
+                provider,
+                category,
+                message,
+            } => write!(
+                f,
+                "{} custom model for category '{}' is unavailable: {}",
+                provider, category, message
+            ),
             TranslationError::AuthenticationError(msg) => {
                 write!(f, "Authentication error: {}", msg)
             }
             TranslationError::TimeoutError => write!(f, "Request timeout"),
+            TranslationError::MalformedResponse(msg) => write!(f, "Malformed response: {}", msg),
+            TranslationError::ResponseTooLarge { max } => {
+                write!(f, "Response too large: exceeds the limit of {} bytes", max)
+            }
             TranslationError::MaxRetriesExceeded { attempts, errors } => {
                 writeln!(f, "Max retries exceeded after {} attempts", attempts)?;
                 for (i, error) in errors.iter().enumerate() {
@@ -65,6 +527,75 @@ impl fmt::Display for TranslationError {
             }
             TranslationError::ServiceError(msg) => write!(f, "Service error: {}", msg),
             TranslationError::ConfigurationError(msg) => write!(f, "Configuration error: {}", msg),
+            TranslationError::TextTooLong { length, max } => {
+                write!(f, "Text too long: {} exceeds the limit of {}", length, max)
+            }
+            TranslationError::RequestTooLarge { bytes } => write!(
+                f,
+                "Request too large: the service rejected a body of {} bytes",
+                bytes
+            ),
+            TranslationError::ShuttingDown => write!(f, "Translation manager is shutting down"),
+            TranslationError::TranslatorClosed => write!(f, "Translator has been closed"),
+            TranslationError::Overloaded { waited } => write!(
+                f,
+                "Translator overloaded: no concurrency permit after waiting {:?}",
+                waited
+            ),
+            TranslationError::LowConfidenceDetection {
+                language,
+                score,
+                min_score,
+            } => write!(
+                f,
+                "Low confidence language detection: {} (score {}, minimum {})",
+                language, score, min_score
+            ),
+            TranslationError::SuspiciousOutput {
+                ratio,
+                min_ratio,
+                max_ratio,
+                ..
+            } => write!(
+                f,
+                "Suspicious output: length ratio {:.2} outside [{}, {}]",
+                ratio, min_ratio, max_ratio
+            ),
+            TranslationError::WrongOutputLanguage { expected, detected } => write!(
+                f,
+                "Output language mismatch: expected {}, detected {}",
+                expected, detected
+            ),
+            TranslationError::DeadlineExceeded { budget } => {
+                write!(f, "Deadline of {:?} exceeded", budget)
+            }
+            TranslationError::TranslatorUnavailable { translator, until } => write!(
+                f,
+                "Translator '{}' is unavailable for scheduled maintenance until {} (Unix time)",
+                translator,
+                until
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs()
+            ),
+            TranslationError::ProtectedTextLost { text } => write!(
+                f,
+                "Protected text '{}' was lost in translation: its placeholder is missing",
+                text
+            ),
+            TranslationError::BudgetExceeded { total, max } => write!(
+                f,
+                "Batch of {} characters exceeds the budget of {} characters",
+                total, max
+            ),
+            TranslationError::NotTranslated { translator } => write!(
+                f,
+                "Translator '{}' returned the original text unchanged",
+                translator
+            ),
+            TranslationError::PolicyViolation { reason } => {
+                write!(f, "Blocked by content policy: {}", reason)
+            }
             TranslationError::Other(msg) => write!(f, "Error: {}", msg),
         }
     }
@@ -73,22 +604,56 @@ impl fmt::Display for TranslationError {
 impl std::error::Error for TranslationError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            TranslationError::NetworkError(e) => Some(e),
+            TranslationError::NetworkError {
+                source: Some(e), ..
+            } => Some(e),
             TranslationError::MaxRetriesExceeded { .. } => None,
             _ => None,
         }
     }
 }
 
+/// 判断 reqwest 错误是否由超时引起
+///
+/// 读取响应体时发生的超时可能被包装成 body 或 decode 错误，
+/// 因此需要沿着错误链查找 `io::ErrorKind::TimedOut`
+pub(crate) fn is_timeout(error: &reqwest::Error) -> bool {
+    if error.is_timeout() {
+        return true;
+    }
+    let mut source = std::error::Error::source(error);
+    while let Some(err) = source {
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            if io.kind() == std::io::ErrorKind::TimedOut {
+                return true;
+            }
+        }
+        source = err.source();
+    }
+    false
+}
+
 // 为常见的错误类型实现转换
 impl From<reqwest::Error> for TranslationError {
     fn from(error: reqwest::Error) -> Self {
-        if error.is_timeout() {
+        let host = error
+            .url()
+            .and_then(|url| url.host_str())
+            .map(str::to_string);
+        // 请求 URL 中可能带有 Key，不保留在错误信息中
+        let error = error.without_url();
+        // 超时优先判断，避免读取响应体时的超时被归类为解析错误
+        if is_timeout(&error) {
             TranslationError::TimeoutError
-        } else if error.is_connect() {
-            TranslationError::NetworkError(error)
+        } else if error.is_decode() {
+            TranslationError::MalformedResponse(error.to_string())
         } else {
-            TranslationError::NetworkError(error)
+            TranslationError::NetworkError {
+                kind: NetworkErrorKind::of(&error),
+                host,
+                source: Some(error),
+                cooldown: None,
+            }
         }
     }
 }
@@ -104,3 +669,34 @@ impl From<anyhow::Error> for TranslationError {
         TranslationError::Other(error.to_string())
     }
 }
+
+/// 批量翻译中失败的条目
+#[derive(Debug, Default)]
+pub struct BatchErrorReport {
+    /// 失败条目在输入中的下标及其错误，按下标排序
+    pub failed: Vec<(usize, TranslationError)>,
+}
+
+impl BatchErrorReport {
+    /// 是否没有失败的条目
+    pub fn is_empty(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// 失败条目的下标
+    pub fn indices(&self) -> Vec<usize> {
+        self.failed.iter().map(|(index, _)| *index).collect()
+    }
+}
+
+impl Clone for BatchErrorReport {
+    fn clone(&self) -> Self {
+        Self {
+            failed: self
+                .failed
+                .iter()
+                .map(|(index, error)| (*index, error.duplicate()))
+                .collect(),
+        }
+    }
+}
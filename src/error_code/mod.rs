@@ -0,0 +1,222 @@
+//! 面向用户界面的错误码与本地化错误信息
+//!
+//! [`crate::TranslationError::code`] 把错误归入稳定的 [`ErrorCode`]，`as_str()` 返回的
+//! 名称不会随版本变化，可以写入日志或交给前端判断。
+//! [`crate::TranslationError::user_message`] 按语言返回可以直接展示给最终用户的信息，
+//! 内置英文和中文，其他语言通过 [`register_messages`] 注册。
+//!
+//! 查找信息时依次尝试完整的语言标签（例如 `zh-Hant`）、只有语言子标签的标签（`zh`）
+//! 和英文，注册的信息优先于内置信息。
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
+use unic_langid::LanguageIdentifier;
+
+/// 稳定的错误码
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// 无法连接翻译服务
+    NetworkUnavailable,
+    /// 请求或读取响应超时，或超出调用的总时限
+    Timeout,
+    /// 请求过于频繁，被翻译服务限流
+    RateLimited,
+    /// 账户额度已用完
+    QuotaExceeded,
+    /// 认证失败
+    AuthFailed,
+    /// 不支持的语言或语言对
+    UnsupportedLanguage,
+    /// 指定的模型或自定义模型不可用
+    ModelUnavailable,
+    /// 文本超出长度限制
+    TextTooLong,
+    /// 翻译服务返回了其他错误
+    ProviderError,
+    /// 翻译服务的响应无法使用
+    InvalidResponse,
+    /// 译文未通过检查
+    SuspiciousOutput,
+    /// 无法可靠地识别源语言
+    DetectionUncertain,
+    /// 译文中丢失了受保护的内容
+    ProtectedTextLost,
+    /// 翻译器过载
+    Overloaded,
+    /// 翻译器处于维护中
+    TranslatorUnavailable,
+    /// 翻译被取消，例如服务正在关闭
+    Cancelled,
+    /// 内容策略禁止发送原文
+    PolicyViolation,
+    /// 配置错误
+    Configuration,
+    /// 其他内部错误
+    Internal,
+}
+
+impl ErrorCode {
+    /// 所有错误码
+    pub const ALL: &'static [ErrorCode] = &[
+        ErrorCode::NetworkUnavailable,
+        ErrorCode::Timeout,
+        ErrorCode::RateLimited,
+        ErrorCode::QuotaExceeded,
+        ErrorCode::AuthFailed,
+        ErrorCode::UnsupportedLanguage,
+        ErrorCode::ModelUnavailable,
+        ErrorCode::TextTooLong,
+        ErrorCode::ProviderError,
+        ErrorCode::InvalidResponse,
+        ErrorCode::SuspiciousOutput,
+        ErrorCode::DetectionUncertain,
+        ErrorCode::ProtectedTextLost,
+        ErrorCode::Overloaded,
+        ErrorCode::TranslatorUnavailable,
+        ErrorCode::Cancelled,
+        ErrorCode::PolicyViolation,
+        ErrorCode::Configuration,
+        ErrorCode::Internal,
+    ];
+
+    /// 稳定的名称，例如 `"rate_limited"`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::NetworkUnavailable => "network_unavailable",
+            ErrorCode::Timeout => "timeout",
+            ErrorCode::RateLimited => "rate_limited",
+            ErrorCode::QuotaExceeded => "quota_exceeded",
+            ErrorCode::AuthFailed => "auth_failed",
+            ErrorCode::UnsupportedLanguage => "unsupported_language",
+            ErrorCode::ModelUnavailable => "model_unavailable",
+            ErrorCode::TextTooLong => "text_too_long",
+            ErrorCode::ProviderError => "provider_error",
+            ErrorCode::InvalidResponse => "invalid_response",
+            ErrorCode::SuspiciousOutput => "suspicious_output",
+            ErrorCode::DetectionUncertain => "detection_uncertain",
+            ErrorCode::ProtectedTextLost => "protected_text_lost",
+            ErrorCode::Overloaded => "overloaded",
+            ErrorCode::TranslatorUnavailable => "translator_unavailable",
+            ErrorCode::Cancelled => "cancelled",
+            ErrorCode::PolicyViolation => "policy_violation",
+            ErrorCode::Configuration => "configuration",
+            ErrorCode::Internal => "internal",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 一种语言的错误信息表，没有对应信息时返回 None
+type Messages = Arc<dyn Fn(ErrorCode) -> Option<String> + Send + Sync>;
+
+fn registered() -> &'static RwLock<HashMap<String, Messages>> {
+    static REGISTERED: OnceLock<RwLock<HashMap<String, Messages>>> = OnceLock::new();
+    REGISTERED.get_or_init(Default::default)
+}
+
+/// 为语言 `language`（例如 `"ja"` 或 `"pt-BR"`）注册错误信息，替换之前为该语言注册的信息
+///
+/// `messages` 没有提供的错误码依次回退到该语言的内置信息和英文
+pub fn register_messages(
+    language: &LanguageIdentifier,
+    messages: impl Fn(ErrorCode) -> Option<String> + Send + Sync + 'static,
+) {
+    registered()
+        .write()
+        .unwrap()
+        .insert(language.to_string(), Arc::new(messages));
+}
+
+/// 按语言查找错误码的信息
+pub(crate) fn message(code: ErrorCode, lang: &LanguageIdentifier) -> String {
+    let primary = LanguageIdentifier::from_parts(lang.language, None, None, &[]);
+    for candidate in [lang, &primary] {
+        let tag = candidate.to_string();
+        let custom = registered().read().unwrap().get(&tag).cloned();
+        if let Some(message) = custom.and_then(|messages| messages(code)) {
+            return message;
+        }
+        if let Some(message) = builtin(&tag, code) {
+            return message.to_string();
+        }
+    }
+    english(code).to_string()
+}
+
+fn builtin(tag: &str, code: ErrorCode) -> Option<&'static str> {
+    match tag {
+        "en" => Some(english(code)),
+        "zh" => Some(chinese(code)),
+        _ => None,
+    }
+}
+
+fn english(code: ErrorCode) -> &'static str {
+    match code {
+        ErrorCode::NetworkUnavailable => {
+            "The translation service could not be reached. Check your network connection and try again."
+        }
+        ErrorCode::Timeout => "The translation took too long. Please try again.",
+        ErrorCode::RateLimited => {
+            "Too many translation requests. Please wait a moment and try again."
+        }
+        ErrorCode::QuotaExceeded => "The translation quota has been used up.",
+        ErrorCode::AuthFailed => "The translation service rejected the credentials.",
+        ErrorCode::UnsupportedLanguage => "This language is not supported.",
+        ErrorCode::ModelUnavailable => "The selected translation model is not available.",
+        ErrorCode::TextTooLong => "The text is too long to translate.",
+        ErrorCode::ProviderError => "The translation service returned an error.",
+        ErrorCode::InvalidResponse => "The translation service returned an unusable response.",
+        ErrorCode::SuspiciousOutput => "The translation did not look right and was discarded.",
+        ErrorCode::DetectionUncertain => {
+            "The source language could not be identified. Please choose it explicitly."
+        }
+        ErrorCode::ProtectedTextLost => {
+            "The translation dropped numbers or other protected text and was discarded."
+        }
+        ErrorCode::Overloaded => "The translation service is busy. Please try again later.",
+        ErrorCode::TranslatorUnavailable => {
+            "The translation service is under maintenance. Please try again later."
+        }
+        ErrorCode::Cancelled => "The translation was cancelled.",
+        ErrorCode::PolicyViolation => {
+            "This text contains information that may not be sent for translation."
+        }
+        ErrorCode::Configuration => "Translation is not configured correctly.",
+        ErrorCode::Internal => "Something went wrong while translating.",
+    }
+}
+
+fn chinese(code: ErrorCode) -> &'static str {
+    match code {
+        ErrorCode::NetworkUnavailable => "无法连接翻译服务，请检查网络后重试。",
+        ErrorCode::Timeout => "翻译超时，请重试。",
+        ErrorCode::RateLimited => "翻译请求过于频繁，请稍后重试。",
+        ErrorCode::QuotaExceeded => "翻译额度已用完。",
+        ErrorCode::AuthFailed => "翻译服务认证失败。",
+        ErrorCode::UnsupportedLanguage => "不支持该语言。",
+        ErrorCode::ModelUnavailable => "所选的翻译模型不可用。",
+        ErrorCode::TextTooLong => "文本过长，无法翻译。",
+        ErrorCode::ProviderError => "翻译服务返回了错误。",
+        ErrorCode::InvalidResponse => "翻译服务的响应无法使用。",
+        ErrorCode::SuspiciousOutput => "译文看起来不正确，已被丢弃。",
+        ErrorCode::DetectionUncertain => "无法识别原文的语言，请手动选择。",
+        ErrorCode::ProtectedTextLost => "译文丢失了数字等受保护的内容，已被丢弃。",
+        ErrorCode::Overloaded => "翻译服务繁忙，请稍后重试。",
+        ErrorCode::TranslatorUnavailable => "翻译服务正在维护，请稍后重试。",
+        ErrorCode::Cancelled => "翻译已取消。",
+        ErrorCode::PolicyViolation => "该文本包含不允许发送翻译的信息。",
+        ErrorCode::Configuration => "翻译配置有误。",
+        ErrorCode::Internal => "翻译时出现错误。",
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,261 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::{NetworkErrorKind, TranslationError};
+    use crate::error_code::{ErrorCode, register_messages};
+    use crate::translator::TranslationDetail;
+    use reqwest::StatusCode;
+    use std::collections::HashSet;
+    use std::time::{Duration, SystemTime};
+    use unic_langid::LanguageIdentifier;
+
+    fn lang(tag: &str) -> LanguageIdentifier {
+        tag.parse().unwrap()
+    }
+
+    fn provider(provider: &str, code: &str, status: u16) -> TranslationError {
+        TranslationError::Provider {
+            provider: provider.into(),
+            code: code.into(),
+            message: "message".into(),
+            http_status: Some(StatusCode::from_u16(status).unwrap()),
+            retryable: false,
+            trace: None,
+        }
+    }
+
+    #[test]
+    fn test_every_variant_has_a_code() {
+        let network = reqwest::Client::new()
+            .get("http://[::1")
+            .build()
+            .unwrap_err();
+        let cases = [
+            (
+                TranslationError::NetworkError {
+                    kind: NetworkErrorKind::Other,
+                    host: None,
+                    source: Some(network),
+                    cooldown: None,
+                },
+                ErrorCode::NetworkUnavailable,
+            ),
+            (
+                TranslationError::HttpError {
+                    status: StatusCode::TOO_MANY_REQUESTS,
+                    body: String::new(),
+                    trace: None,
+                },
+                ErrorCode::RateLimited,
+            ),
+            (
+                TranslationError::HttpError {
+                    status: StatusCode::BAD_GATEWAY,
+                    body: String::new(),
+                    trace: None,
+                },
+                ErrorCode::ProviderError,
+            ),
+            (
+                provider("openai", "invalid_api_key", 401),
+                ErrorCode::AuthFailed,
+            ),
+            (
+                provider("openai", "rate_limit_exceeded", 429),
+                ErrorCode::RateLimited,
+            ),
+            (
+                provider("openai", "insufficient_quota", 429),
+                ErrorCode::QuotaExceeded,
+            ),
+            (
+                provider("openai", "model_not_found", 404),
+                ErrorCode::ModelUnavailable,
+            ),
+            (
+                provider("microsoft", "403001", 403),
+                ErrorCode::QuotaExceeded,
+            ),
+            (
+                provider("microsoft", "400036", 400),
+                ErrorCode::UnsupportedLanguage,
+            ),
+            (
+                provider("microsoft", "400000", 400),
+                ErrorCode::ProviderError,
+            ),
+            (
+                TranslationError::CustomModelUnavailable {
+                    provider: "microsoft".into(),
+                    category: "general".into(),
+                    message: String::new(),
+                },
+                ErrorCode::ModelUnavailable,
+            ),
+            (
+                TranslationError::AuthenticationError(String::new()),
+                ErrorCode::AuthFailed,
+            ),
+            (TranslationError::TimeoutError, ErrorCode::Timeout),
+            (
+                TranslationError::MalformedResponse(String::new()),
+                ErrorCode::InvalidResponse,
+            ),
+            (
+                TranslationError::ResponseTooLarge { max: 1 },
+                ErrorCode::InvalidResponse,
+            ),
+            // 重试耗尽时取最后一次尝试的分类
+            (
+                TranslationError::MaxRetriesExceeded {
+                    attempts: 2,
+                    errors: vec![
+                        TranslationError::TimeoutError,
+                        provider("openai", "rate_limit_exceeded", 429),
+                    ],
+                },
+                ErrorCode::RateLimited,
+            ),
+            (
+                TranslationError::ServiceError(String::new()),
+                ErrorCode::ProviderError,
+            ),
+            (
+                TranslationError::ConfigurationError(String::new()),
+                ErrorCode::Configuration,
+            ),
+            (
+                TranslationError::TextTooLong { length: 2, max: 1 },
+                ErrorCode::TextTooLong,
+            ),
+            (
+                TranslationError::RequestTooLarge { bytes: 2 },
+                ErrorCode::TextTooLong,
+            ),
+            (TranslationError::ShuttingDown, ErrorCode::Cancelled),
+            (TranslationError::TranslatorClosed, ErrorCode::Cancelled),
+            (
+                TranslationError::Overloaded {
+                    waited: Duration::ZERO,
+                },
+                ErrorCode::Overloaded,
+            ),
+            (
+                TranslationError::LowConfidenceDetection {
+                    language: "en".into(),
+                    score: 0.1,
+                    min_score: 0.5,
+                },
+                ErrorCode::DetectionUncertain,
+            ),
+            (
+                TranslationError::SuspiciousOutput {
+                    ratio: 5.0,
+                    min_ratio: 0.5,
+                    max_ratio: 2.0,
+                    candidate: Box::new(TranslationDetail::from_text("")),
+                },
+                ErrorCode::SuspiciousOutput,
+            ),
+            (
+                TranslationError::WrongOutputLanguage {
+                    expected: "ja".into(),
+                    detected: "en".into(),
+                },
+                ErrorCode::SuspiciousOutput,
+            ),
+            (
+                TranslationError::DeadlineExceeded {
+                    budget: Duration::from_secs(1),
+                },
+                ErrorCode::Timeout,
+            ),
+            (
+                TranslationError::TranslatorUnavailable {
+                    translator: "mock".into(),
+                    until: SystemTime::UNIX_EPOCH,
+                },
+                ErrorCode::TranslatorUnavailable,
+            ),
+            (
+                TranslationError::ProtectedTextLost { text: "1.2".into() },
+                ErrorCode::ProtectedTextLost,
+            ),
+            (
+                TranslationError::BudgetExceeded { total: 11, max: 10 },
+                ErrorCode::TextTooLong,
+            ),
+            (
+                TranslationError::NotTranslated {
+                    translator: "mock".into(),
+                },
+                ErrorCode::SuspiciousOutput,
+            ),
+            (
+                TranslationError::PolicyViolation {
+                    reason: String::new(),
+                },
+                ErrorCode::PolicyViolation,
+            ),
+            (TranslationError::Other(String::new()), ErrorCode::Internal),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(error.code(), expected, "classifying {:?}", error);
+        }
+
+        // 名称唯一，且每个错误码都有内置的中英文信息
+        let names: HashSet<&str> = ErrorCode::ALL.iter().map(|code| code.as_str()).collect();
+        assert_eq!(names.len(), ErrorCode::ALL.len());
+        assert_eq!(ErrorCode::RateLimited.to_string(), "rate_limited");
+        for code in ErrorCode::ALL {
+            assert_ne!(
+                crate::error_code::message(*code, &lang("en")),
+                crate::error_code::message(*code, &lang("zh")),
+                "{} has the same message in both languages",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_user_message_locales() {
+        let error = provider("openai", "rate_limit_exceeded", 429);
+        assert_eq!(
+            error.user_message(&lang("en-US")),
+            "Too many translation requests. Please wait a moment and try again."
+        );
+        assert_eq!(
+            error.user_message(&lang("zh-Hant-TW")),
+            "翻译请求过于频繁，请稍后重试。"
+        );
+        // 没有信息的语言回退到英文
+        assert_eq!(
+            TranslationError::TimeoutError.user_message(&lang("fr")),
+            "The translation took too long. Please try again."
+        );
+
+        // 注册的信息优先，未提供的错误码回退到内置信息
+        register_messages(&lang("ja"), |code| match code {
+            ErrorCode::RateLimited => Some("リクエストが多すぎます。".to_string()),
+            _ => None,
+        });
+        register_messages(&lang("zh-Hant"), |code| {
+            (code == ErrorCode::Timeout).then(|| "翻譯逾時，請重試。".to_string())
+        });
+        assert_eq!(
+            error.user_message(&lang("ja-JP")),
+            "リクエストが多すぎます。"
+        );
+        assert_eq!(
+            TranslationError::ShuttingDown.user_message(&lang("ja")),
+            "The translation was cancelled."
+        );
+        assert_eq!(
+            TranslationError::TimeoutError.user_message(&lang("zh-Hant")),
+            "翻譯逾時，請重試。"
+        );
+        assert_eq!(
+            error.user_message(&lang("zh-Hant")),
+            "翻译请求过于频繁，请稍后重试。"
+        );
+    }
+}
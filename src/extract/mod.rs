@@ -0,0 +1,116 @@
+//! 从模型输出中提取 JSON
+//!
+//! 即使提示词要求只输出 JSON，部分模型仍会把结果放在 ```` ```json ```` 代码块中，
+//! 或者在前后加上说明文字。[`json`] 依次尝试：整段严格解析、代码块内的内容、
+//! 按括号配对找到的每个顶层对象或数组，取第一个能解析为目标类型的候选。
+//!
+//! 只用于要求结构化输出的请求。普通译文中的括号是内容的一部分，不能用这里的方法处理。
+
+use serde::de::DeserializeOwned;
+
+/// 代码块的分隔符
+const FENCE: &str = "```";
+
+/// 从模型输出中提取并解析 JSON
+///
+/// 没有找到任何候选时返回整段严格解析的错误；找到了候选但都无法解析时返回第一个候选的错误
+pub(crate) fn json<T: DeserializeOwned>(content: &str) -> Result<T, serde_json::Error> {
+    let strict = match serde_json::from_str(content.trim()) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+    let mut first_error = None;
+    let fenced = fenced_blocks(content).map(str::trim);
+    for candidate in fenced.chain(bracketed(content)) {
+        match serde_json::from_str(candidate) {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    Err(first_error.unwrap_or(strict))
+}
+
+/// 依次返回每个代码块的内容，跳过开头一行的语言标记（例如 `json`）
+///
+/// 没有闭合的代码块取到文本末尾，模型输出被截断时也能找到候选
+fn fenced_blocks(content: &str) -> impl Iterator<Item = &str> {
+    let mut rest = content;
+    std::iter::from_fn(move || {
+        let start = rest.find(FENCE)? + FENCE.len();
+        let block = &rest[start..];
+        let body = match block.find('\n') {
+            Some(newline) if is_language_tag(&block[..newline]) => &block[newline + 1..],
+            _ => block,
+        };
+        let (inner, next) = match body.find(FENCE) {
+            Some(end) => (&body[..end], &body[end + FENCE.len()..]),
+            None => (body, ""),
+        };
+        rest = next;
+        Some(inner)
+    })
+}
+
+/// 代码块开头的语言标记：只包含字母、数字和 `-`、`_`、`+`
+fn is_language_tag(line: &str) -> bool {
+    line.trim()
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '+'))
+}
+
+/// 依次返回每个括号配对完整的顶层对象或数组
+///
+/// 字符串中的括号和转义的引号不参与配对。配对不完整的起点被跳过，
+/// 配对完整但无法解析的候选（例如说明文字中的 `[注]`）由调用方跳过，
+/// 之后从该候选内部的下一个括号继续查找
+fn bracketed(content: &str) -> impl Iterator<Item = &str> {
+    let mut next = 0;
+    std::iter::from_fn(move || {
+        while let Some(offset) = content[next..].find(['{', '[']) {
+            let start = next + offset;
+            next = start + 1;
+            if let Some(end) = matching_close(&content[start..]) {
+                return Some(&content[start..start + end]);
+            }
+        }
+        None
+    })
+}
+
+/// 以括号开头的文本中与第一个括号配对的位置（不含），未配对时返回 None
+fn matching_close(text: &str) -> Option<usize> {
+    let mut stack = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => stack.push('}'),
+            '[' => stack.push(']'),
+            '}' | ']' => {
+                if stack.pop() != Some(c) {
+                    return None;
+                }
+                if stack.is_empty() {
+                    return Some(index + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests;
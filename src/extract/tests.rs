@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use crate::extract::json;
+    use serde_json::{Value, json};
+
+    fn strings(content: &str) -> Vec<String> {
+        json::<Vec<String>>(content).unwrap_or_else(|e| panic!("{:?}: {}", content, e))
+    }
+
+    #[test]
+    fn test_plain_json() {
+        assert_eq!(strings(r#"["你好", "世界"]"#), ["你好", "世界"]);
+        assert_eq!(strings("\n  [\"你好\"]\n"), ["你好"]);
+        assert_eq!(strings("[]"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_code_fences() {
+        let cases = [
+            "```json\n[\"你好\", \"世界\"]\n```",
+            "```JSON\n[\"你好\", \"世界\"]\n```\n",
+            "```\n[\"你好\", \"世界\"]\n```",
+            "```[\"你好\", \"世界\"]```",
+            // 输出被截断，代码块没有闭合
+            "```json\n[\"你好\", \"世界\"]\n",
+            "Here you go:\n\n```json\n[\n  \"你好\",\n  \"世界\"\n]\n```\n\nLet me know if you need anything else.",
+        ];
+        for content in cases {
+            assert_eq!(strings(content), ["你好", "世界"], "{:?}", content);
+        }
+    }
+
+    #[test]
+    fn test_preamble_and_trailing_commentary() {
+        let cases = [
+            "Here is the translation:\n[\"你好\", \"世界\"]",
+            "Sure! [\"你好\", \"世界\"] Hope this helps.",
+            "[\"你好\", \"世界\"]\n\nNote: \"世界\" can also mean \"world\" in a broader sense.",
+            // 说明文字中的括号不是 JSON，跳过后继续查找
+            "Translation [zh-Hans]:\n[\"你好\", \"世界\"]",
+            "(1) {see below}\n[\"你好\", \"世界\"]",
+            "下面是译文：\n[\"你好\", \"世界\"]\n（共 2 条）",
+        ];
+        for content in cases {
+            assert_eq!(strings(content), ["你好", "世界"], "{:?}", content);
+        }
+    }
+
+    #[test]
+    fn test_brackets_inside_strings() {
+        assert_eq!(
+            strings(r#"Output: ["a ] b", "{not an object}", "say \"[hi]\"", "back\\slash"]"#),
+            ["a ] b", "{not an object}", "say \"[hi]\"", "back\\slash"]
+        );
+        assert_eq!(
+            json::<Value>("Result:\n{\"text\": \"}{\", \"items\": [{\"a\": [1, 2]}, {}]}\nDone")
+                .unwrap(),
+            json!({"text": "}{", "items": [{"a": [1, 2]}, {}]})
+        );
+    }
+
+    #[test]
+    fn test_first_candidate_of_target_type() {
+        // 对象不能解析为字符串数组时继续查找后面的数组
+        assert_eq!(
+            strings("{\"count\": 2}\n[\"你好\", \"世界\"]"),
+            ["你好", "世界"]
+        );
+        // 数组中的元素本身是候选时不会被单独取出
+        assert_eq!(
+            json::<Vec<Value>>("[[1], [2]]").unwrap(),
+            [json!([1]), json!([2])]
+        );
+        assert_eq!(
+            json::<Value>("first {\"a\": 1} then {\"b\": 2}").unwrap(),
+            json!({"a": 1})
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        // 没有候选时返回整段严格解析的错误
+        let error = json::<Vec<String>>("你好，世界").unwrap_err();
+        assert_eq!(error.line(), 1);
+        assert_eq!(error.column(), 1);
+        assert!(json::<Vec<String>>("").is_err());
+        // 配对不完整
+        assert!(json::<Vec<String>>("[\"你好\", \"世界\"").is_err());
+        assert!(json::<Vec<String>>("[\"unterminated]").is_err());
+        // 括号不匹配
+        assert!(json::<Vec<String>>("[\"你好\"}").is_err());
+        // 有候选但都不是合法 JSON
+        let error = json::<Vec<String>>("Translation: [你好, 世界]").unwrap_err();
+        assert!(error.is_syntax(), "{}", error);
+        // 合法 JSON 但类型不符
+        let error = json::<Vec<String>>("```json\n[1, 2]\n```").unwrap_err();
+        assert!(error.is_data(), "{}", error);
+    }
+}
@@ -0,0 +1,531 @@
+//! CSV/TSV 表格的列翻译
+//!
+//! 逐条读取记录，只翻译选中列的单元格，其余内容按原样写出：未翻译的字段、引号、
+//! 分隔符、换行符、表头和 UTF-8 BOM 都与输入逐字节相同。译文在原字段带引号或含有
+//! 分隔符、引号、换行时加引号写出。选中列的单元格按 [`CsvOptions::batch_size`] 成批
+//! 翻译，同一批中相同的文本只翻译一次，内存占用与文件大小无关。
+//!
+//! 单元格翻译失败时默认保留原文并记录在 [`CsvReport::failures`] 中，继续处理后面的行；
+//! 启用 [`CsvOptions::abort_on_error`] 后在第一个失败处停止。
+
+use crate::batch::{self, CharBudget};
+use crate::manager::TAGGED_BATCH_CONCURRENCY;
+use crate::{error::TranslationError, options::TranslateOptions, translator::Translator};
+use ::csv::{ByteRecord, ReaderBuilder};
+use std::io::{self, Read, Write};
+use std::ops::Range;
+use unic_langid::LanguageIdentifier;
+
+/// UTF-8 BOM
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 默认每批翻译的单元格数
+pub const DEFAULT_BATCH_SIZE: usize = 64;
+
+/// 要翻译的列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvColumn {
+    /// 从 0 开始的列序号
+    Index(usize),
+    /// 表头中的列名，要求 [`CsvOptions::has_headers`] 为 true
+    Name(String),
+}
+
+impl From<usize> for CsvColumn {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl From<&str> for CsvColumn {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl From<String> for CsvColumn {
+    fn from(name: String) -> Self {
+        Self::Name(name)
+    }
+}
+
+/// CSV/TSV 表格的翻译选项
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// 每次翻译请求使用的配置选项
+    pub translate: TranslateOptions,
+    /// 字段分隔符，默认为 `,`
+    pub delimiter: u8,
+    /// 引号字符，默认为 `"`
+    pub quote: u8,
+    /// 第一条记录是否为表头，表头不翻译
+    pub has_headers: bool,
+    /// 每批翻译的单元格数上限
+    pub batch_size: usize,
+    /// 是否在第一个翻译失败的单元格处停止并返回错误
+    pub abort_on_error: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            translate: TranslateOptions::default(),
+            delimiter: b',',
+            quote: b'"',
+            has_headers: true,
+            batch_size: DEFAULT_BATCH_SIZE,
+            abort_on_error: false,
+        }
+    }
+}
+
+impl CsvOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 以制表符分隔的 TSV 表格
+    pub fn tsv() -> Self {
+        Self::default().delimiter(b'\t')
+    }
+
+    pub fn translate_options(mut self, translate: TranslateOptions) -> Self {
+        self.translate = translate;
+        self
+    }
+
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn quote(mut self, quote: u8) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    pub fn has_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn abort_on_error(mut self, abort_on_error: bool) -> Self {
+        self.abort_on_error = abort_on_error;
+        self
+    }
+}
+
+/// 翻译失败的单元格
+#[derive(Debug)]
+pub struct CsvCellFailure {
+    /// 数据行的序号，从 0 开始，不含表头
+    pub row: usize,
+    /// 列序号
+    pub column: usize,
+    /// 翻译错误
+    pub error: TranslationError,
+}
+
+/// 表格翻译的结果统计
+#[derive(Debug, Default)]
+pub struct CsvReport {
+    /// 写出的数据行数，不含表头
+    pub rows: usize,
+    /// 成功翻译的单元格数
+    pub translated: usize,
+    /// 翻译失败、保留原文的单元格，按行列顺序排列
+    pub failures: Vec<CsvCellFailure>,
+}
+
+/// 表格翻译的错误
+#[derive(Debug)]
+pub enum CsvError {
+    /// 读取或解析 CSV 失败
+    Csv(::csv::Error),
+    /// 写出失败
+    Io(io::Error),
+    /// 表头中没有该列，或没有表头时按列名选择
+    UnknownColumn(String),
+    /// 启用 `abort_on_error` 时第一个翻译失败的单元格
+    Translation(CsvCellFailure),
+}
+
+impl std::fmt::Display for CsvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsvError::Csv(e) => write!(f, "CSV error: {}", e),
+            CsvError::Io(e) => write!(f, "I/O error: {}", e),
+            CsvError::UnknownColumn(name) => write!(f, "Unknown column: {}", name),
+            CsvError::Translation(failure) => write!(
+                f,
+                "Translation failed at row {}, column {}: {}",
+                failure.row, failure.column, failure.error
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CsvError::Csv(e) => Some(e),
+            CsvError::Io(e) => Some(e),
+            CsvError::UnknownColumn(_) => None,
+            CsvError::Translation(failure) => Some(&failure.error),
+        }
+    }
+}
+
+impl From<::csv::Error> for CsvError {
+    fn from(e: ::csv::Error) -> Self {
+        CsvError::Csv(e)
+    }
+}
+
+impl From<io::Error> for CsvError {
+    fn from(e: io::Error) -> Self {
+        CsvError::Io(e)
+    }
+}
+
+/// 记录读取过的原始字节，用于按原样写出记录
+struct Recorder<R> {
+    inner: R,
+    /// 从 `offset` 开始、尚未取出的原始字节
+    buffer: Vec<u8>,
+    offset: u64,
+}
+
+impl<R: Read> Read for Recorder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.buffer.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<R> Recorder<R> {
+    /// 取出直到绝对位置 `end` 的原始字节
+    fn take(&mut self, end: u64) -> Vec<u8> {
+        let len = ((end - self.offset) as usize).min(self.buffer.len());
+        self.offset += len as u64;
+        self.buffer.drain(..len).collect()
+    }
+}
+
+/// 一条记录中待翻译的单元格
+struct Cell {
+    /// 字段在原始字节中的范围，包括引号
+    span: Range<usize>,
+    column: usize,
+    /// 原字段是否带引号
+    quoted: bool,
+    /// 解码后的内容
+    text: String,
+    /// 译文，None 表示保留原文
+    translation: Option<String>,
+}
+
+/// 等待翻译的一条记录
+struct PendingRow {
+    row: usize,
+    raw: Vec<u8>,
+    cells: Vec<Cell>,
+}
+
+/// 原始记录中各字段的字节范围，跳过开头的 BOM（仅 `first` 为 true 时）和上一条记录剩下的换行符
+///
+/// 记录已经由 csv 解析过，这里只需要按引号和分隔符定位字段
+fn field_spans(raw: &[u8], delimiter: u8, quote: u8, first: bool) -> Vec<Range<usize>> {
+    let mut pos = if first && raw.starts_with(BOM) {
+        BOM.len()
+    } else {
+        0
+    };
+    while pos < raw.len() && matches!(raw[pos], b'\r' | b'\n') {
+        pos += 1;
+    }
+    let mut spans = Vec::new();
+    loop {
+        let start = pos;
+        if raw.get(pos) == Some(&quote) {
+            pos += 1;
+            while pos < raw.len() {
+                if raw[pos] == quote {
+                    if raw.get(pos + 1) == Some(&quote) {
+                        pos += 2;
+                        continue;
+                    }
+                    pos += 1;
+                    break;
+                }
+                pos += 1;
+            }
+        }
+        while pos < raw.len() && raw[pos] != delimiter && !matches!(raw[pos], b'\r' | b'\n') {
+            pos += 1;
+        }
+        spans.push(start..pos);
+        if raw.get(pos) != Some(&delimiter) {
+            return spans;
+        }
+        pos += 1;
+    }
+}
+
+/// 把译文编码为字段，原字段带引号或内容需要时加引号
+fn encode_field(text: &str, quoted: bool, delimiter: u8, quote: u8) -> Vec<u8> {
+    let needs_quotes = quoted
+        || text
+            .bytes()
+            .any(|b| b == delimiter || b == quote || b == b'\r' || b == b'\n');
+    if !needs_quotes {
+        return text.as_bytes().to_vec();
+    }
+    let mut field = Vec::with_capacity(text.len() + 2);
+    field.push(quote);
+    for b in text.bytes() {
+        if b == quote {
+            field.push(quote);
+        }
+        field.push(b);
+    }
+    field.push(quote);
+    field
+}
+
+/// 把列选择解析为列序号，列名按表头查找
+fn resolve_columns(
+    columns: &[CsvColumn],
+    headers: Option<&ByteRecord>,
+) -> Result<Vec<usize>, CsvError> {
+    columns
+        .iter()
+        .map(|column| match column {
+            CsvColumn::Index(index) => Ok(*index),
+            CsvColumn::Name(name) => headers
+                .and_then(|headers| headers.iter().position(|field| field == name.as_bytes()))
+                .ok_or_else(|| CsvError::UnknownColumn(name.clone())),
+        })
+        .collect()
+}
+
+/// 翻译一批记录中的单元格并按原样写出记录
+async fn flush(
+    translator: &dyn Translator,
+    pending: &mut Vec<PendingRow>,
+    target_lang: &LanguageIdentifier,
+    source_lang: Option<&LanguageIdentifier>,
+    options: &CsvOptions,
+    report: &mut CsvReport,
+    writer: &mut impl Write,
+) -> Result<(), CsvError> {
+    let items: Vec<((usize, usize), String)> = pending
+        .iter()
+        .enumerate()
+        .flat_map(|(row, pending_row)| {
+            pending_row
+                .cells
+                .iter()
+                .enumerate()
+                .map(move |(cell, c)| ((row, cell), c.text.trim().to_string()))
+        })
+        .collect();
+    let results = batch::translate_tagged(
+        items,
+        true,
+        TAGGED_BATCH_CONCURRENCY,
+        CharBudget::default(),
+        |text| async move {
+            translator
+                .translate_with_options(&text, target_lang, source_lang, &options.translate)
+                .await
+        },
+    )
+    .await;
+
+    for ((row, cell), result) in results {
+        let pending_row = &mut pending[row];
+        let cell = &mut pending_row.cells[cell];
+        match result {
+            Ok(translation) => {
+                // 保留单元格内容首尾的空白
+                let text = &cell.text;
+                let leading = &text[..text.len() - text.trim_start().len()];
+                let trailing = &text[text.trim_end().len()..];
+                cell.translation = Some(format!("{}{}{}", leading, translation, trailing));
+                report.translated += 1;
+            }
+            Err(error) => {
+                let failure = CsvCellFailure {
+                    row: pending_row.row,
+                    column: cell.column,
+                    error,
+                };
+                if options.abort_on_error {
+                    return Err(CsvError::Translation(failure));
+                }
+                report.failures.push(failure);
+            }
+        }
+    }
+
+    for pending_row in pending.drain(..) {
+        let mut written = 0;
+        for cell in &pending_row.cells {
+            let Some(translation) = &cell.translation else {
+                continue;
+            };
+            writer.write_all(&pending_row.raw[written..cell.span.start])?;
+            writer.write_all(&encode_field(
+                translation,
+                cell.quoted,
+                options.delimiter,
+                options.quote,
+            ))?;
+            written = cell.span.end;
+        }
+        writer.write_all(&pending_row.raw[written..])?;
+        report.rows += 1;
+    }
+    Ok(())
+}
+
+/// 翻译 CSV/TSV 表格中的指定列
+///
+/// # 参数
+///
+/// * `translator` - 使用的翻译器
+/// * `reader` - 表格内容
+/// * `writer` - 写出翻译后的表格
+/// * `columns` - 要翻译的列，可以混合使用列序号和列名，例如 `&[0.into(), "description".into()]`
+/// * `target_lang` - 目标语言标识符
+/// * `source_lang` - 源语言标识符 (None表示自动检测)
+/// * `options` - 表格翻译选项
+///
+/// # 返回值
+///
+/// 返回各行的翻译统计。某一行缺少选中的列时跳过该列，空白单元格不翻译。
+/// 返回错误时已写出的内容只包含之前的批次
+pub async fn translate_columns<R: Read, W: Write>(
+    translator: &dyn Translator,
+    reader: R,
+    mut writer: W,
+    columns: &[CsvColumn],
+    target_lang: &LanguageIdentifier,
+    source_lang: Option<&LanguageIdentifier>,
+    options: &CsvOptions,
+) -> Result<CsvReport, CsvError> {
+    let mut csv_reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .from_reader(Recorder {
+            inner: reader,
+            buffer: Vec::new(),
+            offset: 0,
+        });
+    let mut record = ByteRecord::new();
+    let mut targets = Vec::new();
+    let mut first = true;
+    let mut report = CsvReport::default();
+    let mut pending: Vec<PendingRow> = Vec::new();
+    let mut pending_cells = 0;
+    let mut row = 0;
+
+    while csv_reader.read_byte_record(&mut record)? {
+        let end = csv_reader.position().byte();
+        let raw = csv_reader.get_mut().take(end);
+        if first {
+            targets = resolve_columns(columns, options.has_headers.then_some(&record))?;
+            if options.has_headers {
+                writer.write_all(&raw)?;
+                first = false;
+                continue;
+            }
+        }
+
+        let spans = field_spans(&raw, options.delimiter, options.quote, first);
+        first = false;
+        let mut cells = Vec::new();
+        // 字段数与解析结果不一致时不翻译这一行，按原样写出
+        if spans.len() == record.len() {
+            for &column in &targets {
+                if cells.iter().any(|cell: &Cell| cell.column == column) {
+                    continue;
+                }
+                let Some(span) = spans.get(column) else {
+                    continue;
+                };
+                let text = match std::str::from_utf8(&record[column]) {
+                    Ok(text) if text.trim().is_empty() => continue,
+                    Ok(text) => text.to_string(),
+                    Err(e) => {
+                        let failure = CsvCellFailure {
+                            row,
+                            column,
+                            error: TranslationError::Other(format!(
+                                "Cell is not valid UTF-8: {}",
+                                e
+                            )),
+                        };
+                        if options.abort_on_error {
+                            return Err(CsvError::Translation(failure));
+                        }
+                        report.failures.push(failure);
+                        continue;
+                    }
+                };
+                cells.push(Cell {
+                    span: span.clone(),
+                    column,
+                    quoted: raw.get(span.start) == Some(&options.quote),
+                    text,
+                    translation: None,
+                });
+            }
+        }
+        // 按字段在记录中的位置排列，写出时依次替换
+        cells.sort_by_key(|cell| cell.span.start);
+        pending_cells += cells.len();
+        pending.push(PendingRow { row, raw, cells });
+        row += 1;
+
+        if pending_cells >= options.batch_size.max(1) {
+            flush(
+                translator,
+                &mut pending,
+                target_lang,
+                source_lang,
+                options,
+                &mut report,
+                &mut writer,
+            )
+            .await?;
+            pending_cells = 0;
+        }
+    }
+    flush(
+        translator,
+        &mut pending,
+        target_lang,
+        source_lang,
+        options,
+        &mut report,
+        &mut writer,
+    )
+    .await?;
+    // 最后一条记录之后的空行
+    writer.write_all(&csv_reader.get_mut().buffer)?;
+    writer.flush()?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,207 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::TranslationError;
+    use crate::formats::csv::{
+        CsvColumn, CsvError, CsvOptions, CsvReport, encode_field, field_spans, translate_columns,
+    };
+    use crate::options::TranslateOptions;
+    use crate::testing::MockTranslator;
+    use crate::translator::Translator;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use unic_langid::LanguageIdentifier;
+
+    /// 带 BOM、CRLF 换行、含逗号和换行的引号字段的表格
+    const PRODUCTS: &[u8] = include_bytes!("../../../tests/fixtures/csv/products.csv");
+    /// 含制表符和转义引号的字段、空行、缺少最后一列的 TSV 表格
+    const LABELS: &[u8] = include_bytes!("../../../tests/fixtures/csv/labels.tsv");
+
+    /// 转为大写的翻译器，原文为 `Gizmo` 时失败
+    #[derive(Default)]
+    struct UppercaseTranslator {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Translator for UppercaseTranslator {
+        async fn translate_with_options(
+            &self,
+            text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if text == "Gizmo" {
+                return Err(TranslationError::ServiceError("unavailable".to_string()));
+            }
+            Ok(text.to_uppercase())
+        }
+    }
+
+    async fn translate(
+        translator: &dyn Translator,
+        input: &[u8],
+        columns: &[CsvColumn],
+        options: &CsvOptions,
+    ) -> (Vec<u8>, Result<CsvReport, CsvError>) {
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let mut output = Vec::new();
+        let result = translate_columns(
+            translator,
+            input,
+            &mut output,
+            columns,
+            &target_lang,
+            None,
+            options,
+        )
+        .await;
+        (output, result)
+    }
+
+    #[test]
+    fn test_field_spans() {
+        let raw = b"\n1,\"a, \"\"b\"\"\",,x\r";
+        let fields: Vec<&[u8]> = field_spans(raw, b',', b'"', false)
+            .into_iter()
+            .map(|span| &raw[span])
+            .collect();
+        assert_eq!(fields, [&b"1"[..], b"\"a, \"\"b\"\"\"", b"", b"x"].to_vec());
+
+        let raw = b"\xEF\xBB\xBFid\tname\n";
+        assert_eq!(field_spans(raw, b'\t', b'"', true), [3..5, 6..10]);
+
+        assert_eq!(encode_field("plain", false, b',', b'"'), b"plain");
+        assert_eq!(encode_field("plain", true, b',', b'"'), b"\"plain\"");
+        assert_eq!(
+            encode_field("a, \"b\"\nc", false, b',', b'"'),
+            b"\"a, \"\"b\"\"\nc\""
+        );
+        assert_eq!(encode_field("a,b", false, b'\t', b'"'), b"a,b");
+    }
+
+    #[tokio::test]
+    async fn test_untouched_columns_round_trip() {
+        // 恒等翻译时输出与输入逐字节相同
+        let translator = MockTranslator::new().prefix("");
+        for (input, options) in [
+            (PRODUCTS, CsvOptions::new()),
+            (LABELS, CsvOptions::tsv()),
+            (LABELS, CsvOptions::tsv().has_headers(false)),
+        ] {
+            let (output, result) =
+                translate(&translator, input, &[1.into(), 2.into()], &options).await;
+            let report = result.unwrap();
+            assert!(report.failures.is_empty());
+            assert_eq!(
+                String::from_utf8(output).unwrap(),
+                String::from_utf8(input.to_vec()).unwrap()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_translate_columns_by_name() {
+        let translator = UppercaseTranslator::default();
+        let (output, result) = translate(
+            &translator,
+            PRODUCTS,
+            &["name".into(), "description".into()],
+            &CsvOptions::new(),
+        )
+        .await;
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\u{feff}id,name,description,price\r\n\
+             1,WIDGET,\"SMALL, BLUE WIDGET\",9.99\r\n\
+             2,\"GADGET\",\"MULTI-LINE\nPRODUCT \"\"DELUXE\"\" EDITION\",  19.50 \r\n\
+             3,Gizmo,,5\r\n\
+             4,WIDGET, SPARE PARTS ,\"1,000\"\r\n"
+        );
+
+        // 失败的单元格保留原文，空单元格不翻译，同一批中的 `Widget` 只翻译一次
+        let report = result.unwrap();
+        assert_eq!(report.rows, 4);
+        assert_eq!(report.translated, 6);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!((report.failures[0].row, report.failures[0].column), (2, 1));
+        assert!(matches!(
+            report.failures[0].error,
+            TranslationError::ServiceError(_)
+        ));
+        assert_eq!(translator.calls.load(Ordering::SeqCst), 6);
+
+        // 每批一个单元格时不同批次中的相同文本各自翻译
+        let translator = UppercaseTranslator::default();
+        let (_, result) = translate(
+            &translator,
+            PRODUCTS,
+            &["name".into()],
+            &CsvOptions::new().batch_size(1),
+        )
+        .await;
+        assert_eq!(result.unwrap().translated, 3);
+        assert_eq!(translator.calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_translate_tsv_columns_by_index() {
+        let translator = UppercaseTranslator::default();
+        let (output, result) = translate(
+            &translator,
+            LABELS,
+            &[1.into(), 2.into()],
+            &CsvOptions::tsv(),
+        )
+        .await;
+        // 最后一行没有 `note` 列，空行按原样保留
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "sku\tlabel\tnote\n\
+             A1\tRED APPLE\t\"TAB\tINSIDE\"\n\
+             B2\t\"GREEN \"\"PEAR\"\"\"\tFRESH\n\
+             \n\
+             C3\tYELLOW BANANA\n"
+        );
+        assert_eq!(result.unwrap().rows, 3);
+
+        // 没有表头时第一行也翻译
+        let (output, _) = translate(
+            &translator,
+            LABELS,
+            &[0.into()],
+            &CsvOptions::tsv().has_headers(false),
+        )
+        .await;
+        assert!(String::from_utf8(output).unwrap().starts_with("SKU\tlabel"));
+    }
+
+    #[tokio::test]
+    async fn test_abort_on_error_and_unknown_columns() {
+        let translator = UppercaseTranslator::default();
+        let options = CsvOptions::new().batch_size(1).abort_on_error(true);
+        let (output, result) = translate(&translator, PRODUCTS, &["name".into()], &options).await;
+        match result {
+            Err(CsvError::Translation(failure)) => {
+                assert_eq!((failure.row, failure.column), (2, 1));
+            }
+            other => panic!("Expected Translation error, got {:?}", other),
+        }
+        // 只写出了失败之前的批次
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\u{feff}id,name,description,price\r\n\
+             1,WIDGET,\"Small, blue widget\",9.99\r\n\
+             2,\"GADGET\",\"Multi-line\nproduct \"\"deluxe\"\" edition\",  19.50 \r"
+        );
+
+        for (columns, options) in [
+            (vec!["missing".into()], CsvOptions::new()),
+            (vec!["name".into()], CsvOptions::new().has_headers(false)),
+        ] {
+            let (output, result) = translate(&translator, PRODUCTS, &columns, &options).await;
+            assert!(matches!(result, Err(CsvError::UnknownColumn(_))));
+            assert!(output.is_empty());
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! 按文件格式翻译文档
+//!
+//! 每种格式负责把文档拆分成需要翻译的片段，并在翻译后按原有结构重新拼接。
+
+#[cfg(feature = "csv")]
+pub mod csv;
+pub mod text;
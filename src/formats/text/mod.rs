@@ -0,0 +1,186 @@
+//! 纯文本文档翻译
+//!
+//! 按行拆分文档，空行、行首缩进、行尾空白和换行符（`\n` 或 `\r\n`）保持不变，
+//! 只翻译每行去除首尾空白后的内容。启用 [`TextDocumentOptions::join_paragraphs`] 时，
+//! 同一段落（连续的非空行）会合并成一条文本翻译，译文再按原各行的长度比例拆分回去。
+
+use crate::{error::TranslationError, options::TranslateOptions, translator::Translator};
+use futures::future::join_all;
+use unic_langid::LanguageIdentifier;
+
+/// 纯文本文档的翻译选项
+#[derive(Debug, Clone, Default)]
+pub struct TextDocumentOptions {
+    /// 每次翻译请求使用的配置选项
+    pub translate: TranslateOptions,
+    /// 是否把同一段落的连续行合并翻译，合并后译文更连贯，但换行位置按比例估算
+    pub join_paragraphs: bool,
+}
+
+impl TextDocumentOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn translate_options(mut self, translate: TranslateOptions) -> Self {
+        self.translate = translate;
+        self
+    }
+
+    pub fn join_paragraphs(mut self, join_paragraphs: bool) -> Self {
+        self.join_paragraphs = join_paragraphs;
+        self
+    }
+}
+
+/// 文档中的一行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Line<'a> {
+    /// 行首空白
+    pub indent: &'a str,
+    /// 去除首尾空白后的内容，空行为空字符串
+    pub content: &'a str,
+    /// 行尾空白
+    pub trailing: &'a str,
+    /// 换行符：`"\n"`、`"\r\n"`，最后一行没有换行符时为空字符串
+    pub ending: &'a str,
+}
+
+/// 按行拆分文档，拼接所有字段即可还原原文
+pub(crate) fn split_lines(input: &str) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+    let mut rest = input;
+    while !rest.is_empty() {
+        let (line, ending, next) = match rest.find('\n') {
+            Some(pos) if rest[..pos].ends_with('\r') => {
+                (&rest[..pos - 1], "\r\n", &rest[pos + 1..])
+            }
+            Some(pos) => (&rest[..pos], "\n", &rest[pos + 1..]),
+            None => (rest, "", ""),
+        };
+        let content = line.trim();
+        let (indent, trailing) = if content.is_empty() {
+            // 只有空白的行整体视为缩进
+            (line, "")
+        } else {
+            let start = line.len() - line.trim_start().len();
+            (&line[..start], &line[start + content.len()..])
+        };
+        lines.push(Line {
+            indent,
+            content,
+            trailing,
+            ending,
+        });
+        rest = next;
+    }
+    lines
+}
+
+/// 按 `weights` 的比例把文本拆分成多段，拆分点尽量落在空白处
+pub(crate) fn split_proportionally(text: &str, weights: &[usize]) -> Vec<String> {
+    if weights.len() <= 1 {
+        return vec![text.trim().to_string()];
+    }
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let byte_at = |index: usize| chars.get(index).map_or(text.len(), |&(byte, _)| byte);
+    let total: usize = weights.iter().sum::<usize>().max(1);
+
+    let mut parts = Vec::with_capacity(weights.len());
+    let mut start = 0;
+    let mut cumulative = 0;
+    for weight in &weights[..weights.len() - 1] {
+        cumulative += weight;
+        let target = (chars.len() * cumulative / total).max(start);
+        // 段落剩余部分中离目标位置最近、且不超过半段距离的空白
+        let reach = weight / 2 + 1;
+        let cut = (start..chars.len())
+            .filter(|&i| chars[i].1.is_whitespace() && i.abs_diff(target) <= reach)
+            .min_by_key(|&i| i.abs_diff(target))
+            .unwrap_or(target);
+        parts.push(text[byte_at(start)..byte_at(cut)].trim().to_string());
+        start = cut;
+    }
+    parts.push(text[byte_at(start)..].trim().to_string());
+    parts
+}
+
+/// 翻译纯文本文档，保持原有的行结构
+///
+/// # 参数
+///
+/// * `translator` - 使用的翻译器
+/// * `input` - 文档内容
+/// * `target_lang` - 目标语言标识符
+/// * `source_lang` - 源语言标识符 (None表示自动检测)
+/// * `options` - 文档翻译选项
+///
+/// # 返回值
+///
+/// 返回翻译后的文档，任意一段翻译失败时返回该错误
+pub async fn translate_text_document(
+    translator: &dyn Translator,
+    input: &str,
+    target_lang: &LanguageIdentifier,
+    source_lang: Option<&LanguageIdentifier>,
+    options: &TextDocumentOptions,
+) -> Result<String, TranslationError> {
+    let lines = split_lines(input);
+
+    // 每组是一次翻译请求覆盖的行号
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut previous_blank = true;
+    for (index, line) in lines.iter().enumerate() {
+        if line.content.is_empty() {
+            previous_blank = true;
+            continue;
+        }
+        match groups.last_mut() {
+            Some(group) if options.join_paragraphs && !previous_blank => group.push(index),
+            _ => groups.push(vec![index]),
+        }
+        previous_blank = false;
+    }
+
+    let requests: Vec<String> = groups
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .map(|&index| lines[index].content)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+    let results = join_all(requests.iter().map(|text| {
+        translator.translate_with_options(text, target_lang, source_lang, &options.translate)
+    }))
+    .await;
+
+    let mut translated: Vec<Option<String>> = vec![None; lines.len()];
+    for (group, result) in groups.iter().zip(results) {
+        let text = result?;
+        // 权重包含合并时插入的空格，恒等翻译时拆分点恰好落在原来的换行处
+        let weights: Vec<usize> = group
+            .iter()
+            .map(|&index| lines[index].content.chars().count() + 1)
+            .collect();
+        for (&index, part) in group.iter().zip(split_proportionally(&text, &weights)) {
+            translated[index] = Some(part);
+        }
+    }
+
+    let mut output = String::with_capacity(input.len());
+    for (line, text) in lines.iter().zip(translated) {
+        output.push_str(line.indent);
+        if let Some(text) = text {
+            output.push_str(&text);
+            output.push_str(line.trailing);
+        }
+        output.push_str(line.ending);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,199 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::TranslationError;
+    use crate::formats::text::{
+        Line, TextDocumentOptions, split_lines, split_proportionally, translate_text_document,
+    };
+    use crate::options::TranslateOptions;
+    use crate::testing::MockTranslator;
+    use crate::translator::Translator;
+    use unic_langid::LanguageIdentifier;
+
+    /// 混合换行符、缩进、连续空行和行尾空白的文档
+    const POEM: &str =
+        "Roses are red,\r\n  Violets are blue,\n\n\n\tSugar is sweet \r\n   \r\nAnd so are you.";
+    /// 以换行符结尾、带列表缩进的更新日志
+    const CHANGELOG: &str = "## 0.2.0\n\n- Added streaming\n  responses for OpenAI\n- Fixed retries\n\n\n## 0.1.0\n\n- Initial release\n";
+
+    /// 把整段文本转为大写的翻译器
+    struct UppercaseTranslator;
+
+    #[async_trait::async_trait]
+    impl Translator for UppercaseTranslator {
+        async fn translate_with_options(
+            &self,
+            text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            Ok(text.to_uppercase())
+        }
+    }
+
+    /// 译文长度是原文两倍的翻译器
+    struct DoublingTranslator;
+
+    #[async_trait::async_trait]
+    impl Translator for DoublingTranslator {
+        async fn translate_with_options(
+            &self,
+            text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            Ok(text
+                .split_whitespace()
+                .map(|word| format!("{0} {0}", word))
+                .collect::<Vec<_>>()
+                .join(" "))
+        }
+    }
+
+    async fn translate(translator: &dyn Translator, input: &str, join_paragraphs: bool) -> String {
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TextDocumentOptions::new().join_paragraphs(join_paragraphs);
+        translate_text_document(translator, input, &target_lang, None, &options)
+            .await
+            .unwrap()
+    }
+
+    /// 去掉每行内容后的结构：缩进、行尾空白和换行符
+    fn skeleton(document: &str) -> Vec<(String, bool, String, String)> {
+        split_lines(document)
+            .into_iter()
+            .map(|line| {
+                (
+                    line.indent.to_string(),
+                    line.content.is_empty(),
+                    line.trailing.to_string(),
+                    line.ending.to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_split_lines() {
+        assert_eq!(
+            split_lines("  a b \r\n\n \t\nc"),
+            vec![
+                Line {
+                    indent: "  ",
+                    content: "a b",
+                    trailing: " ",
+                    ending: "\r\n",
+                },
+                Line {
+                    indent: "",
+                    content: "",
+                    trailing: "",
+                    ending: "\n",
+                },
+                Line {
+                    indent: " \t",
+                    content: "",
+                    trailing: "",
+                    ending: "\n",
+                },
+                Line {
+                    indent: "",
+                    content: "c",
+                    trailing: "",
+                    ending: "",
+                },
+            ]
+        );
+        assert!(split_lines("").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_identity_round_trip() {
+        let translator = MockTranslator::new().prefix("");
+        for fixture in [POEM, CHANGELOG, "", "\n", "\r\n\r\n", "single line"] {
+            assert_eq!(translate(&translator, fixture, false).await, fixture);
+            assert_eq!(translate(&translator, fixture, true).await, fixture);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lines_translated_separately() {
+        let translator = MockTranslator::new().prefix("T:");
+        assert_eq!(
+            translate(&translator, POEM, false).await,
+            "T:Roses are red,\r\n  T:Violets are blue,\n\n\n\tT:Sugar is sweet \r\n   \r\nT:And so are you."
+        );
+        // 每个非空行一次请求
+        assert_eq!(translator.calls(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_join_paragraphs() {
+        let translator = MockTranslator::new().prefix("");
+        let output = translate(&UppercaseTranslator, CHANGELOG, true).await;
+        assert_eq!(output, CHANGELOG.to_uppercase());
+
+        // 合并后每个段落只请求一次
+        translate(&translator, CHANGELOG, true).await;
+        assert_eq!(translator.calls(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_join_paragraphs_resplits_longer_translation() {
+        for fixture in [POEM, CHANGELOG] {
+            let output = translate(&DoublingTranslator, fixture, true).await;
+            assert_eq!(skeleton(&output), skeleton(fixture));
+            // 拆分点落在空白处，没有单词被截断
+            let words: Vec<&str> = output.split_whitespace().collect();
+            let expected: Vec<String> = fixture
+                .split_whitespace()
+                .flat_map(|word| [word.to_string(), word.to_string()])
+                .collect();
+            assert_eq!(words, expected);
+        }
+    }
+
+    #[test]
+    fn test_split_proportionally() {
+        assert_eq!(split_proportionally(" only ", &[3]), vec!["only"]);
+        assert_eq!(
+            split_proportionally("aaaa bbbbbbbb cccc", &[5, 9, 5]),
+            vec!["aaaa", "bbbbbbbb", "cccc"]
+        );
+        // 没有空白时按字符比例拆分
+        assert_eq!(
+            split_proportionally("一二三四五六", &[2, 4]),
+            vec!["一二", "三四五六"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_translation_error_returned() {
+        struct FailingTranslator;
+
+        #[async_trait::async_trait]
+        impl Translator for FailingTranslator {
+            async fn translate_with_options(
+                &self,
+                _text: &str,
+                _target_lang: &LanguageIdentifier,
+                _source_lang: Option<&LanguageIdentifier>,
+                _options: &TranslateOptions,
+            ) -> Result<String, TranslationError> {
+                Err(TranslationError::ServiceError("boom".to_string()))
+            }
+        }
+
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let result = translate_text_document(
+            &FailingTranslator,
+            POEM,
+            &target_lang,
+            None,
+            &TextDocumentOptions::default(),
+        )
+        .await;
+        assert!(matches!(result, Err(TranslationError::ServiceError(_))));
+    }
+}
@@ -0,0 +1,162 @@
+//! 术语表
+//!
+//! 术语按最长优先、从左到右的顺序匹配，互不重叠。由字母或数字组成的术语边缘
+//! 要求位于单词边界上（例如 `cat` 不会匹配 `category`），中日韩文字之间没有空格，
+//! 不做边界检查。术语按字面匹配，不会被当作正则表达式解释。
+
+use crate::guard;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+/// 术语表：术语及其指定译法
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Glossary {
+    entries: BTreeMap<String, String>,
+    case_insensitive: bool,
+}
+
+/// 原文中匹配到的一个术语
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TermMatch<'a> {
+    /// 术语在原文中的字节范围
+    pub range: Range<usize>,
+    /// 术语的指定译法
+    pub translation: &'a str,
+}
+
+impl Glossary {
+    /// 创建空术语表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一个术语，空术语会被忽略
+    pub fn term(mut self, term: impl Into<String>, translation: impl Into<String>) -> Self {
+        let term = term.into();
+        if !term.is_empty() {
+            self.entries.insert(term, translation.into());
+        }
+        self
+    }
+
+    /// 设置匹配时是否忽略大小写
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// 匹配时是否忽略大小写
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    /// 术语数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 术语表是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 按术语顺序遍历所有术语及其译法
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(term, translation)| (term.as_str(), translation.as_str()))
+    }
+
+    /// 查找原文中的所有术语
+    pub fn find_terms(&self, text: &str) -> Vec<TermMatch<'_>> {
+        // 较长的术语优先
+        let mut terms: Vec<(&str, &str, usize)> = self
+            .entries()
+            .map(|(term, translation)| (term, translation, term.chars().count()))
+            .collect();
+        terms.sort_by_key(|&(_, _, len)| Reverse(len));
+
+        let mut matches = Vec::new();
+        let mut position = 0;
+        while position < text.len() {
+            let rest = &text[position..];
+            let previous = text[..position].chars().next_back();
+            let matched = terms.iter().find_map(|&(term, translation, len)| {
+                let end = rest
+                    .char_indices()
+                    .nth(len)
+                    .map_or(text.len(), |(offset, _)| position + offset);
+                let candidate = &text[position..end];
+                let is_match = candidate.chars().count() == len
+                    && self.term_eq(candidate, term)
+                    && at_boundary(previous, term.chars().next())
+                    && at_boundary(text[end..].chars().next(), term.chars().next_back());
+                is_match.then_some((end, translation))
+            });
+            match matched {
+                Some((end, translation)) => {
+                    matches.push(TermMatch {
+                        range: position..end,
+                        translation,
+                    });
+                    position = end;
+                }
+                None => position += rest.chars().next().map_or(1, char::len_utf8),
+            }
+        }
+        matches
+    }
+
+    /// 把原文中的术语替换为指定译法
+    pub fn apply(&self, text: &str) -> String {
+        let mut output = String::with_capacity(text.len());
+        let mut last = 0;
+        for term in self.find_terms(text) {
+            output.push_str(&text[last..term.range.start]);
+            output.push_str(term.translation);
+            last = term.range.end;
+        }
+        output.push_str(&text[last..]);
+        output
+    }
+
+    fn term_eq(&self, candidate: &str, term: &str) -> bool {
+        if self.case_insensitive {
+            candidate
+                .chars()
+                .flat_map(char::to_lowercase)
+                .eq(term.chars().flat_map(char::to_lowercase))
+        } else {
+            candidate == term
+        }
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for Glossary {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(Glossary::new(), |glossary, (term, translation)| {
+                glossary.term(term, translation)
+            })
+    }
+}
+
+impl From<HashMap<String, String>> for Glossary {
+    fn from(entries: HashMap<String, String>) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+/// 是否属于单词的一部分（中日韩文字不计入，它们之间没有空格）
+fn is_word_char(c: char) -> bool {
+    (c.is_alphanumeric() || c == '_') && guard::char_weight(c) == 1.0
+}
+
+/// 术语边缘与相邻字符之间是否为单词边界
+fn at_boundary(neighbor: Option<char>, edge: Option<char>) -> bool {
+    !matches!((neighbor, edge), (Some(neighbor), Some(edge)) if is_word_char(neighbor) && is_word_char(edge))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use crate::glossary::Glossary;
+    use crate::options::TranslateOptions;
+    use crate::testing::MockTranslator;
+    use crate::translator::Translator;
+    use std::collections::HashMap;
+    use unic_langid::LanguageIdentifier;
+
+    fn matched<'a>(glossary: &Glossary, text: &'a str) -> Vec<&'a str> {
+        glossary
+            .find_terms(text)
+            .into_iter()
+            .map(|term| &text[term.range])
+            .collect()
+    }
+
+    #[test]
+    fn test_longest_match_first() {
+        let glossary = Glossary::new()
+            .term("New York", "纽约")
+            .term("New York City", "纽约市")
+            .term("York", "约克");
+        assert_eq!(
+            matched(&glossary, "New York City and York, not New York"),
+            vec!["New York City", "York", "New York"]
+        );
+        assert_eq!(glossary.apply("New York City and York"), "纽约市 and 约克");
+    }
+
+    #[test]
+    fn test_word_boundaries() {
+        let glossary = Glossary::new().term("cat", "猫");
+        assert_eq!(
+            matched(&glossary, "cat, category, bobcat, cat_x, cat."),
+            vec!["cat", "cat"]
+        );
+        // 中日韩文字之间没有空格，不要求边界
+        let glossary = Glossary::new().term("机器学习", "machine learning");
+        assert_eq!(
+            matched(&glossary, "我喜欢机器学习和深度学习"),
+            vec!["机器学习"]
+        );
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let glossary = Glossary::new().term("api", "接口");
+        assert!(matched(&glossary, "The API is ready").is_empty());
+        let glossary = glossary.case_insensitive(true);
+        assert_eq!(
+            matched(&glossary, "The API and the Api"),
+            vec!["API", "Api"]
+        );
+    }
+
+    #[test]
+    fn test_regex_metacharacters_are_literal() {
+        let glossary = Glossary::new()
+            .term("C++", "C++ 语言")
+            .term("a.b", "甲乙")
+            .term("(beta)", "（测试版）")
+            .term("$HOME", "主目录");
+        assert_eq!(
+            matched(&glossary, "C++ and a.b (beta) in $HOME, not axb or C+"),
+            vec!["C++", "a.b", "(beta)", "$HOME"]
+        );
+        assert!(matched(&glossary, "aXb").is_empty());
+    }
+
+    #[test]
+    fn test_glossary_from_map() {
+        let map = HashMap::from([
+            ("cloud".to_string(), "云".to_string()),
+            (String::new(), "ignored".to_string()),
+        ]);
+        let glossary = Glossary::from(map);
+        assert_eq!(glossary.len(), 1);
+        assert_eq!(
+            glossary.entries().collect::<Vec<_>>(),
+            vec![("cloud", "云")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_translate_with_glossary_substitutes_terms() {
+        let translator = MockTranslator::new().prefix("T:");
+        let glossary = Glossary::new().term("Rust", "铁锈");
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let result = translator
+            .translate_with_glossary(
+                "I like Rust",
+                &glossary,
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result, "T:I like 铁锈");
+    }
+}
@@ -0,0 +1,90 @@
+//! 译文合理性检查
+//!
+//! 比较译文与原文的长度比例，发现明显不合理的译文（例如代理丢失了系统提示词，
+//! 三段原文只返回一个词）。中日韩文字的信息密度更高，按字符计数时会乘以权重，
+//! 使不同文字之间的长度可以直接比较。
+//!
+//! 另外判断译文是否与原文相同，区分已经是目标语言或翻译服务原样返回的文本。
+
+use crate::options::UnchangedComparison;
+use unicode_normalization::UnicodeNormalization;
+
+/// 字符权重表：（起始字符，结束字符，权重），未列出的非空白字符权重为 1
+const CHAR_WEIGHTS: &[(char, char, f64)] = &[
+    ('\u{1100}', '\u{11FF}', 2.5),   // 韩文字母
+    ('\u{3040}', '\u{30FF}', 2.0),   // 平假名、片假名
+    ('\u{3400}', '\u{4DBF}', 3.0),   // 中日韩统一表意文字扩展 A
+    ('\u{4E00}', '\u{9FFF}', 3.0),   // 中日韩统一表意文字
+    ('\u{AC00}', '\u{D7AF}', 2.5),   // 韩文音节
+    ('\u{F900}', '\u{FAFF}', 3.0),   // 中日韩兼容表意文字
+    ('\u{20000}', '\u{2FA1F}', 3.0), // 中日韩统一表意文字扩展 B 及以后
+];
+
+/// 单个字符的权重，空白字符不计入长度
+pub(crate) fn char_weight(c: char) -> f64 {
+    if c.is_whitespace() {
+        return 0.0;
+    }
+    CHAR_WEIGHTS
+        .iter()
+        .find(|(start, end, _)| (*start..=*end).contains(&c))
+        .map_or(1.0, |(_, _, weight)| *weight)
+}
+
+/// 按文字加权的文本长度
+#[cfg_attr(not(feature = "openai"), allow(dead_code))]
+pub(crate) fn weighted_len(text: &str) -> f64 {
+    text.chars().map(char_weight).sum()
+}
+
+/// 译文与原文的加权长度比例，原文为空时返回 None
+#[cfg_attr(not(feature = "openai"), allow(dead_code))]
+pub(crate) fn length_ratio(input: &str, output: &str) -> Option<f64> {
+    let input_len = weighted_len(input);
+    (input_len > 0.0).then(|| weighted_len(output) / input_len)
+}
+
+/// 比例偏离 `[min_ratio, max_ratio]` 的程度（按对数计算），在范围内时为 0
+#[cfg_attr(not(feature = "openai"), allow(dead_code))]
+pub(crate) fn ratio_deviation(ratio: f64, min_ratio: f64, max_ratio: f64) -> f64 {
+    if ratio < min_ratio {
+        (min_ratio / ratio.max(f64::MIN_POSITIVE)).ln()
+    } else if ratio > max_ratio {
+        (ratio / max_ratio).ln()
+    } else {
+        0.0
+    }
+}
+
+/// 译文在忽略 `comparison` 指定的差异后是否与原文相同
+///
+/// 忽略这些差异后原文为空（例如只有标点）时没有可以比较的内容，视为不同
+pub(crate) fn is_unchanged(input: &str, output: &str, comparison: &UnchangedComparison) -> bool {
+    let mut input = comparable(input, comparison).peekable();
+    input.peek().is_some() && input.eq(comparable(output, comparison))
+}
+
+/// 按 NFKC 规范化后参与比较的字符
+fn comparable<'a>(
+    text: &'a str,
+    comparison: &UnchangedComparison,
+) -> Box<dyn Iterator<Item = char> + 'a> {
+    let ignore_punctuation = comparison.ignore_punctuation;
+    let chars = text
+        .nfkc()
+        .filter(move |&c| !(c.is_whitespace() || ignore_punctuation && is_punctuation(c)));
+    if comparison.ignore_case {
+        Box::new(chars.flat_map(char::to_lowercase))
+    } else {
+        Box::new(chars)
+    }
+}
+
+/// ASCII 标点、通用标点和中日韩标点，全角标点在 NFKC 规范化后已是 ASCII 标点
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(c, '\u{00A1}'..='\u{00BF}' | '\u{2010}'..='\u{205E}' | '\u{3001}'..='\u{303F}')
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use crate::guard::{char_weight, is_unchanged, length_ratio, ratio_deviation, weighted_len};
+    use crate::options::UnchangedComparison;
+
+    #[test]
+    fn test_char_weights() {
+        assert_eq!(char_weight('a'), 1.0);
+        assert_eq!(char_weight('é'), 1.0);
+        assert_eq!(char_weight('，'), 1.0);
+        assert_eq!(char_weight(' '), 0.0);
+        assert_eq!(char_weight('\n'), 0.0);
+        assert_eq!(char_weight('中'), 3.0);
+        assert_eq!(char_weight('㐀'), 3.0);
+        assert_eq!(char_weight('𠀀'), 3.0);
+        assert_eq!(char_weight('あ'), 2.0);
+        assert_eq!(char_weight('カ'), 2.0);
+        assert_eq!(char_weight('한'), 2.5);
+    }
+
+    #[test]
+    fn test_weighted_len() {
+        assert_eq!(weighted_len(""), 0.0);
+        assert_eq!(weighted_len("a b"), 2.0);
+        assert_eq!(weighted_len("你好"), 6.0);
+        assert_eq!(weighted_len("こんにちは"), 10.0);
+    }
+
+    #[test]
+    fn test_cjk_translation_ratio_is_comparable() {
+        // 正常的英译中，按字符数比例约为 0.25，加权后接近 1
+        let ratio = length_ratio(
+            "The weather is nice today, let's go to the park.",
+            "今天天气很好，我们去公园吧。",
+        )
+        .unwrap();
+        assert!((0.7..1.3).contains(&ratio), "{}", ratio);
+
+        let ratio = length_ratio(
+            "Good morning, how are you?",
+            "おはようございます、お元気ですか？",
+        )
+        .unwrap();
+        assert!((0.7..1.6).contains(&ratio), "{}", ratio);
+
+        assert_eq!(length_ratio("   ", "x"), None);
+    }
+
+    #[test]
+    fn test_ratio_deviation() {
+        assert_eq!(ratio_deviation(1.0, 0.5, 2.0), 0.0);
+        assert_eq!(ratio_deviation(0.5, 0.5, 2.0), 0.0);
+        assert!((ratio_deviation(0.25, 0.5, 2.0) - 2f64.ln()).abs() < 1e-9);
+        assert!((ratio_deviation(8.0, 0.5, 2.0) - 4f64.ln()).abs() < 1e-9);
+        assert!(ratio_deviation(0.0, 0.5, 2.0).is_finite());
+    }
+
+    #[test]
+    fn test_unchanged_comparison() {
+        let default = UnchangedComparison::default();
+        let strict = UnchangedComparison {
+            ignore_case: false,
+            ignore_punctuation: false,
+        };
+        // 空白和全角字符总是被忽略或规范化
+        for (input, output) in [
+            ("Hello world", "Hello world"),
+            ("Hello world", "  Hello   world\n"),
+            ("ＡＢＣ 123", "ABC123"),
+        ] {
+            assert!(is_unchanged(input, output, &strict), "{:?}", output);
+        }
+        // 大小写和标点按设置忽略
+        for (input, output) in [
+            ("Hello world", "hello world"),
+            ("Hello world", "Hello world!"),
+            ("你好，世界", "你好世界。"),
+            ("«Bonjour»", "Bonjour"),
+        ] {
+            assert!(is_unchanged(input, output, &default), "{:?}", output);
+            assert!(!is_unchanged(input, output, &strict), "{:?}", output);
+        }
+        let case_only = UnchangedComparison {
+            ignore_case: true,
+            ignore_punctuation: false,
+        };
+        assert!(is_unchanged("STOP", "stop", &case_only));
+        assert!(!is_unchanged("STOP", "stop.", &case_only));
+
+        assert!(!is_unchanged("Hello world", "你好，世界", &default));
+        assert!(!is_unchanged("Hello", "Hello world", &default));
+        // 只有标点或空白的原文没有可比较的内容
+        assert!(!is_unchanged("...", "...", &default));
+        assert!(!is_unchanged("", "", &default));
+        assert!(is_unchanged("...", "...", &strict));
+    }
+}
@@ -0,0 +1,476 @@
+//! HTTP 客户端构造与响应体读取
+//!
+//! 启用 `compression` feature 后，内部客户端会请求 gzip/brotli 压缩传输。
+//! 响应体按块读取，设置了 [`crate::TranslateOptions::max_response_bytes`] 时，
+//! 超出限制会立即中止读取，而不是把全部内容缓冲到内存中。
+
+use crate::{
+    clock::Clock,
+    error::{NetworkErrorKind, TranslationError},
+    options::{EffectiveOptions, PoolOptions, ProxyMode},
+    secret,
+};
+use reqwest::{Client, ClientBuilder, NoProxy, Proxy, Response};
+use serde::{Deserialize, Deserializer, de::DeserializeOwned};
+use serde_json::Value;
+use std::collections::HashMap;
+#[cfg(feature = "microsoft")]
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 默认的主机名解析失败缓存时长
+pub(crate) const DEFAULT_DNS_FAILURE_TTL: Duration = Duration::from_secs(30);
+
+/// 主机名解析失败的负缓存
+///
+/// 拼错的主机名会让每个批量条目的每次重试都等待一次 DNS 解析失败。一个主机名解析失败后，
+/// 在 `ttl` 内发往该主机的请求不再发出，直接返回 `source` 为 None、不可重试的
+/// [`TranslationError::NetworkError`]。TCP 连接和 TLS 错误不记录，照常重试
+#[derive(Debug)]
+pub(crate) struct DnsFailureCache {
+    ttl: Duration,
+    /// 主机名及其失败记录的过期时间
+    hosts: Mutex<HashMap<String, Instant>>,
+}
+
+impl DnsFailureCache {
+    /// `ttl` 为零时不缓存
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 请求地址的主机名最近解析失败过时返回直接失败的错误
+    pub(crate) fn check(&self, url: &str, clock: &dyn Clock) -> Result<(), TranslationError> {
+        let Some(host) = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+        else {
+            return Ok(());
+        };
+        let mut hosts = self.hosts.lock().unwrap();
+        match hosts.get(&host) {
+            Some(&expires_at) if clock.now() < expires_at => Err(TranslationError::NetworkError {
+                kind: NetworkErrorKind::Dns,
+                host: Some(host),
+                source: None,
+                cooldown: None,
+            }),
+            Some(_) => {
+                hosts.remove(&host);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// 记录请求错误中解析失败的主机名，原样返回错误
+    pub(crate) fn record(&self, error: TranslationError, clock: &dyn Clock) -> TranslationError {
+        if let TranslationError::NetworkError {
+            kind: NetworkErrorKind::Dns,
+            host: Some(host),
+            source: Some(_),
+            ..
+        } = &error
+        {
+            if !self.ttl.is_zero() {
+                self.hosts
+                    .lock()
+                    .unwrap()
+                    .insert(host.clone(), clock.now() + self.ttl);
+            }
+        }
+        error
+    }
+}
+
+/// 创建带有统一默认配置的客户端构造器
+pub(crate) fn client_builder() -> ClientBuilder {
+    let builder = Client::builder();
+    #[cfg(feature = "compression")]
+    let builder = builder.gzip(true).brotli(true);
+    builder
+}
+
+#[cfg(test)]
+thread_local! {
+    /// 测试中记录当前线程每次创建客户端时使用的连接池和代理设置
+    pub(crate) static CONSTRUCTED_CLIENTS: std::cell::RefCell<Vec<(PoolOptions, ProxyMode)>> =
+        const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// 在客户端构造器上应用连接池设置
+pub(crate) fn apply_pool_options(builder: ClientBuilder, pool: &PoolOptions) -> ClientBuilder {
+    let builder = builder
+        .pool_max_idle_per_host(pool.max_idle_per_host)
+        .pool_idle_timeout(pool.idle_timeout)
+        .tcp_keepalive(pool.tcp_keepalive);
+    let builder = match pool.connect_timeout {
+        Some(connect_timeout) => builder.connect_timeout(connect_timeout),
+        None => builder,
+    };
+    let builder = match pool.http2_keep_alive_interval {
+        Some(interval) => builder
+            .http2_keep_alive_interval(interval)
+            .http2_keep_alive_while_idle(pool.http2_keep_alive_while_idle),
+        None => builder,
+    };
+    let builder = match pool.http2_keep_alive_timeout {
+        Some(timeout) => builder.http2_keep_alive_timeout(timeout),
+        None => builder,
+    };
+    if pool.http2_prior_knowledge {
+        builder.http2_prior_knowledge()
+    } else if pool.prefer_http2 {
+        builder
+    } else {
+        // 只在 TLS 握手中声明 HTTP/1.1，与未启用 ALPN 时的行为一致
+        builder.http1_only()
+    }
+}
+
+/// 检查连接池设置是否一致
+pub(crate) fn validate_pool_options(pool: &PoolOptions) -> Result<(), TranslationError> {
+    let invalid = |message: &str| Err(TranslationError::ConfigurationError(message.to_string()));
+    if pool.http2_keep_alive_interval == Some(Duration::ZERO) {
+        return invalid("http2_keep_alive_interval must be greater than 0");
+    }
+    if pool.http2_keep_alive_timeout == Some(Duration::ZERO) {
+        return invalid("http2_keep_alive_timeout must be greater than 0");
+    }
+    if pool.http2_keep_alive_interval.is_none()
+        && (pool.http2_keep_alive_timeout.is_some() || pool.http2_keep_alive_while_idle)
+    {
+        return invalid(
+            "http2_keep_alive_timeout and http2_keep_alive_while_idle require http2_keep_alive_interval",
+        );
+    }
+    if pool.http2_keep_alive_interval.is_some() && !pool.prefer_http2 && !pool.http2_prior_knowledge
+    {
+        return invalid("http2_keep_alive_interval requires prefer_http2 or http2_prior_knowledge");
+    }
+    Ok(())
+}
+
+/// 在客户端构造器上应用代理设置，`no_proxy` 中的主机不经过指定的代理
+///
+/// reqwest 默认就会读取代理相关的环境变量，因此 [`ProxyMode::System`] 不做修改
+pub(crate) fn apply_proxy_mode(
+    builder: ClientBuilder,
+    mode: &ProxyMode,
+    no_proxy: Option<NoProxy>,
+) -> ClientBuilder {
+    match mode {
+        ProxyMode::System => builder,
+        ProxyMode::None => builder.no_proxy(),
+        ProxyMode::Explicit(url) => builder.proxy(
+            Proxy::all(url.as_str())
+                .expect("invalid proxy URL")
+                .no_proxy(no_proxy),
+        ),
+    }
+}
+
+/// 创建翻译器共用的客户端
+///
+/// 每个翻译器只创建一次客户端，单次请求的超时通过请求本身设置，以便复用连接池
+pub(crate) fn build_client(pool: &PoolOptions, proxy_mode: &ProxyMode) -> Client {
+    #[cfg(test)]
+    CONSTRUCTED_CLIENTS.with(|constructed| {
+        constructed
+            .borrow_mut()
+            .push((pool.clone(), proxy_mode.clone()))
+    });
+    let builder = apply_pool_options(client_builder(), pool);
+    apply_proxy_mode(builder, proxy_mode, NoProxy::from_env())
+        .build()
+        .expect("failed to build HTTP client")
+}
+
+/// 检查代理设置中的 URL 是否有效
+pub(crate) fn validate_proxy_mode(proxy_mode: &ProxyMode) -> Result<(), TranslationError> {
+    match proxy_mode {
+        ProxyMode::Explicit(url) => validate_url("proxy", url),
+        ProxyMode::System | ProxyMode::None => Ok(()),
+    }
+}
+
+/// 检查配置中的 URL 是否有效（必须是 http 或 https）
+pub(crate) fn validate_url(name: &str, url: &str) -> Result<(), TranslationError> {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+        Ok(parsed) => Err(TranslationError::ConfigurationError(format!(
+            "Invalid {} '{}': unsupported scheme '{}'",
+            name,
+            secret::redact_url(url),
+            parsed.scheme()
+        ))),
+        Err(e) => Err(TranslationError::ConfigurationError(format!(
+            "Invalid {} '{}': {}",
+            name,
+            secret::redact_url(url),
+            e
+        ))),
+    }
+}
+
+/// 检查单次请求覆盖的 URL 是否有效（必须是 http 或 https）
+pub(crate) fn validate_override_url(name: &str, url: &str) -> Result<(), TranslationError> {
+    validate_url(&format!("{} override", name), url)
+}
+
+/// 读取响应体，超出 `max_response_bytes` 时返回 [`TranslationError::ResponseTooLarge`]
+pub(crate) async fn read_bytes(
+    mut response: Response,
+    options: &EffectiveOptions,
+) -> Result<Vec<u8>, TranslationError> {
+    let max = options.max_response_bytes;
+    if let (Some(max), Some(length)) = (max, response.content_length()) {
+        // 未压缩时可以根据 Content-Length 提前判断
+        if length > max as u64 {
+            return Err(TranslationError::ResponseTooLarge { max });
+        }
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if let Some(max) = max {
+            if body.len() + chunk.len() > max {
+                return Err(TranslationError::ResponseTooLarge { max });
+            }
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// 读取响应体并转换为字符串
+pub(crate) async fn read_text(
+    response: Response,
+    options: &EffectiveOptions,
+) -> Result<String, TranslationError> {
+    let body = read_bytes(response, options).await?;
+    // 合法的 UTF-8 直接复用缓冲区
+    Ok(String::from_utf8(body)
+        .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned()))
+}
+
+/// 读取响应体并解析为 JSON
+pub(crate) async fn read_json<T: DeserializeOwned>(
+    response: Response,
+    options: &EffectiveOptions,
+) -> Result<T, TranslationError> {
+    let body = read_bytes(response, options).await?;
+    serde_json::from_slice(&body).map_err(malformed)
+}
+
+/// 逐项读取响应体中的 JSON 数组，内存中只保留尚未解析的数组项
+///
+/// 响应体总大小同样受 `max_response_bytes` 限制
+#[cfg(feature = "microsoft")]
+pub(crate) struct JsonArrayStream {
+    response: Response,
+    scanner: JsonArrayScanner,
+    items: VecDeque<Vec<u8>>,
+    read: usize,
+    max: Option<usize>,
+    finished: bool,
+}
+
+#[cfg(feature = "microsoft")]
+impl JsonArrayStream {
+    /// 开始读取响应体，`Content-Length` 超出限制时立即返回错误
+    pub(crate) fn new(
+        response: Response,
+        options: &EffectiveOptions,
+    ) -> Result<Self, TranslationError> {
+        let max = options.max_response_bytes;
+        if let (Some(max), Some(length)) = (max, response.content_length()) {
+            if length > max as u64 {
+                return Err(TranslationError::ResponseTooLarge { max });
+            }
+        }
+        Ok(Self {
+            response,
+            scanner: JsonArrayScanner::default(),
+            items: VecDeque::new(),
+            read: 0,
+            max,
+            finished: false,
+        })
+    }
+
+    /// 读取下一项，数组结束时返回 `None`
+    pub(crate) async fn next_value(&mut self) -> Result<Option<Value>, TranslationError> {
+        loop {
+            if let Some(item) = self.items.pop_front() {
+                return serde_json::from_slice(&item).map(Some).map_err(malformed);
+            }
+            if self.finished {
+                self.scanner.finish()?;
+                return Ok(None);
+            }
+            match self.response.chunk().await? {
+                Some(chunk) => {
+                    self.read += chunk.len();
+                    if let Some(max) = self.max.filter(|&max| self.read > max) {
+                        return Err(TranslationError::ResponseTooLarge { max });
+                    }
+                    self.scanner.feed(&chunk, &mut self.items)?;
+                }
+                None => self.finished = true,
+            }
+        }
+    }
+}
+
+/// JSON 数组的扫描位置
+#[cfg(feature = "microsoft")]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum ScanState {
+    /// 尚未读到 `[`
+    #[default]
+    BeforeArray,
+    /// 数组中，`after_comma` 表示刚读过分隔项的 `,`
+    InArray { after_comma: bool },
+    /// 已读到与开头匹配的 `]`
+    Done,
+}
+
+/// 把分块到达的 JSON 数组切分成各项的原始字节，不解析项的内容
+///
+/// 只跟踪字符串和嵌套层级，项本身是否是合法的 JSON 由调用方解析时检查
+#[cfg(feature = "microsoft")]
+#[derive(Debug, Default)]
+pub(crate) struct JsonArrayScanner {
+    state: ScanState,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    item: Vec<u8>,
+}
+
+#[cfg(feature = "microsoft")]
+impl JsonArrayScanner {
+    /// 扫描一块数据，完整的项依次放入 `items`
+    pub(crate) fn feed(
+        &mut self,
+        chunk: &[u8],
+        items: &mut VecDeque<Vec<u8>>,
+    ) -> Result<(), TranslationError> {
+        for &byte in chunk {
+            match self.state {
+                ScanState::BeforeArray => match byte {
+                    b'[' => self.state = ScanState::InArray { after_comma: false },
+                    byte if byte.is_ascii_whitespace() => {}
+                    _ => return Err(malformed("expected a JSON array")),
+                },
+                ScanState::Done if byte.is_ascii_whitespace() => {}
+                ScanState::Done => return Err(malformed("trailing characters after JSON array")),
+                ScanState::InArray { after_comma } => {
+                    if self.in_string {
+                        self.item.push(byte);
+                        if self.escaped {
+                            self.escaped = false;
+                        } else if byte == b'\\' {
+                            self.escaped = true;
+                        } else if byte == b'"' {
+                            self.in_string = false;
+                        }
+                        continue;
+                    }
+                    if self.depth == 0 {
+                        match byte {
+                            byte if byte.is_ascii_whitespace() => continue,
+                            b',' if !self.item.is_empty() => {
+                                items.push_back(std::mem::take(&mut self.item));
+                                self.state = ScanState::InArray { after_comma: true };
+                                continue;
+                            }
+                            b']' if self.item.is_empty() && !after_comma => {
+                                self.state = ScanState::Done;
+                                continue;
+                            }
+                            b']' if !self.item.is_empty() => {
+                                items.push_back(std::mem::take(&mut self.item));
+                                self.state = ScanState::Done;
+                                continue;
+                            }
+                            b',' | b']' => {
+                                return Err(malformed("unexpected delimiter in JSON array"));
+                            }
+                            _ => {}
+                        }
+                    }
+                    self.item.push(byte);
+                    self.state = ScanState::InArray { after_comma: false };
+                    match byte {
+                        b'"' => self.in_string = true,
+                        b'{' | b'[' => self.depth += 1,
+                        b'}' | b']' => {
+                            self.depth = self
+                                .depth
+                                .checked_sub(1)
+                                .ok_or_else(|| malformed("unbalanced brackets in JSON array"))?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查数组是否已经完整结束
+    pub(crate) fn finish(&self) -> Result<(), TranslationError> {
+        match self.state {
+            ScanState::Done => Ok(()),
+            _ => Err(malformed("unexpected end of JSON array")),
+        }
+    }
+}
+
+/// 响应体无法解析时的错误
+fn malformed(error: impl std::fmt::Display) -> TranslationError {
+    TranslationError::MalformedResponse(format!("error decoding response body: {}", error))
+}
+
+/// 反序列化时把 `null` 当作默认值，用于服务端可能返回 `null` 而不是省略的字段
+pub(crate) fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// 将已解析的 JSON 转换为具体的响应类型
+pub(crate) fn decode_json<T: DeserializeOwned>(value: &Value) -> Result<T, TranslationError> {
+    T::deserialize(value).map_err(malformed)
+}
+
+/// 按大小限制保留原始响应 JSON，超出限制时只保留序列化文本的前缀并注明截断
+pub(crate) fn capture_raw(value: Value, max_bytes: Option<usize>) -> Value {
+    let Some(max_bytes) = max_bytes else {
+        return value;
+    };
+    let serialized = value.to_string();
+    if serialized.len() <= max_bytes {
+        return value;
+    }
+    let mut end = max_bytes;
+    while !serialized.is_char_boundary(end) {
+        end -= 1;
+    }
+    serde_json::json!({
+        "truncated": true,
+        "bytes": serialized.len(),
+        "prefix": &serialized[..end],
+    })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,436 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::TranslationError;
+    use crate::http::{
+        apply_pool_options, apply_proxy_mode, build_client, capture_raw, read_bytes, read_json,
+        read_text, validate_pool_options,
+    };
+    use crate::options::{EffectiveOptions, PoolOptions, ProxyMode, TranslateOptions};
+    use crate::testing::{DelayedResponse, DelayedServer};
+    use std::time::Duration;
+
+    async fn fetch(uri: String) -> reqwest::Response {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap()
+            .get(uri)
+            .send()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_read_bytes_unlimited() {
+        let body = "x".repeat(100_000);
+        let server = DelayedServer::start(DelayedResponse::new(body.clone())).await;
+        let bytes = read_bytes(
+            fetch(server.uri()).await,
+            &EffectiveOptions::from_call_site(&TranslateOptions::default()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(bytes, body.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_read_bytes_within_limit() {
+        let server = DelayedServer::start(DelayedResponse::new("x".repeat(100))).await;
+        let options = TranslateOptions::default().max_response_bytes(100);
+        let bytes = read_bytes(
+            fetch(server.uri()).await,
+            &EffectiveOptions::from_call_site(&options),
+        )
+        .await
+        .unwrap();
+        assert_eq!(bytes.len(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_content_length_over_limit() {
+        let server = DelayedServer::start(DelayedResponse::new("x".repeat(101))).await;
+        let options = TranslateOptions::default().max_response_bytes(100);
+        match read_bytes(
+            fetch(server.uri()).await,
+            &EffectiveOptions::from_call_site(&options),
+        )
+        .await
+        {
+            Err(TranslationError::ResponseTooLarge { max }) => assert_eq!(max, 100),
+            other => panic!("Expected ResponseTooLarge, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streamed_body_aborts_at_limit() {
+        // 服务端无限发送数据，只有按块检查大小才能及时中止
+        let server =
+            DelayedServer::start(DelayedResponse::new("x".repeat(8192)).endless(true)).await;
+        let options = TranslateOptions::default().max_response_bytes(64 * 1024);
+        match read_bytes(
+            fetch(server.uri()).await,
+            &EffectiveOptions::from_call_site(&options),
+        )
+        .await
+        {
+            Err(TranslationError::ResponseTooLarge { max }) => assert_eq!(max, 64 * 1024),
+            other => panic!("Expected ResponseTooLarge, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_text_replaces_invalid_utf8() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::path("/invalid"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(400).set_body_bytes(b"bad \xff body".to_vec()),
+            )
+            .mount(&server)
+            .await;
+        let options = EffectiveOptions::from_call_site(&TranslateOptions::default());
+        let invalid = read_text(fetch(format!("{}/invalid", server.uri())).await, &options).await;
+        assert_eq!(invalid.unwrap(), "bad \u{FFFD} body");
+
+        let server = DelayedServer::start(DelayedResponse::new("错误：额度不足")).await;
+        let valid = read_text(fetch(server.uri()).await, &options).await;
+        assert_eq!(valid.unwrap(), "错误：额度不足");
+    }
+
+    #[tokio::test]
+    async fn test_read_json_malformed() {
+        let server = DelayedServer::start(DelayedResponse::new("{\"a\":")).await;
+        let result: Result<serde_json::Value, _> = read_json(
+            fetch(server.uri()).await,
+            &EffectiveOptions::from_call_site(&TranslateOptions::default()),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(TranslationError::MalformedResponse(_))
+        ));
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_gzip_response_decoded() {
+        use flate2::{Compression, write::GzEncoder};
+        use std::io::Write;
+        use wiremock::matchers::header_regex;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(br#"{"text":"hello"}"#).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let server = MockServer::start().await;
+        Mock::given(header_regex("accept-encoding", "gzip"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Content-Encoding", "gzip")
+                    .set_body_raw(compressed, "application/json"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let response = crate::http::build_client(&PoolOptions::default(), &ProxyMode::default())
+            .get(server.uri())
+            .send()
+            .await
+            .unwrap();
+        let value: serde_json::Value = read_json(
+            response,
+            &EffectiveOptions::from_call_site(&TranslateOptions::default()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(value["text"], "hello");
+    }
+
+    #[test]
+    fn test_apply_pool_options() {
+        let debug = format!(
+            "{:?}",
+            apply_pool_options(reqwest::Client::builder(), &PoolOptions::default())
+        );
+        assert!(!debug.contains("connect_timeout"), "{}", debug);
+        assert!(!debug.contains("http2_prior_knowledge"), "{}", debug);
+        assert!(debug.contains("http1_only: true"), "{}", debug);
+
+        // 协商 HTTP/2 时不限制为 HTTP/1.1
+        let pool = PoolOptions {
+            prefer_http2: true,
+            ..Default::default()
+        };
+        let debug = format!(
+            "{:?}",
+            apply_pool_options(reqwest::Client::builder(), &pool)
+        );
+        assert!(!debug.contains("http1_only"), "{}", debug);
+        assert!(!debug.contains("http2_prior_knowledge"), "{}", debug);
+
+        let pool = PoolOptions {
+            connect_timeout: Some(Duration::from_secs(3)),
+            http2_prior_knowledge: true,
+            ..Default::default()
+        };
+        let debug = format!(
+            "{:?}",
+            apply_pool_options(reqwest::Client::builder(), &pool)
+        );
+        assert!(debug.contains("connect_timeout: 3s"), "{}", debug);
+        assert!(debug.contains("http2_prior_knowledge: true"), "{}", debug);
+    }
+
+    #[test]
+    fn test_validate_pool_options() {
+        let interval = Some(Duration::from_secs(30));
+        let invalid = [
+            PoolOptions {
+                prefer_http2: true,
+                http2_keep_alive_interval: Some(Duration::ZERO),
+                ..Default::default()
+            },
+            PoolOptions {
+                prefer_http2: true,
+                http2_keep_alive_interval: interval,
+                http2_keep_alive_timeout: Some(Duration::ZERO),
+                ..Default::default()
+            },
+            PoolOptions {
+                prefer_http2: true,
+                http2_keep_alive_timeout: Some(Duration::from_secs(5)),
+                ..Default::default()
+            },
+            PoolOptions {
+                prefer_http2: true,
+                http2_keep_alive_while_idle: true,
+                ..Default::default()
+            },
+            // 只使用 HTTP/1.1 时心跳不会生效
+            PoolOptions {
+                http2_keep_alive_interval: interval,
+                ..Default::default()
+            },
+        ];
+        for pool in &invalid {
+            assert!(
+                matches!(
+                    validate_pool_options(pool),
+                    Err(TranslationError::ConfigurationError(_))
+                ),
+                "{:?}",
+                pool
+            );
+        }
+
+        let valid = [
+            PoolOptions::default(),
+            PoolOptions {
+                prefer_http2: true,
+                ..Default::default()
+            },
+            PoolOptions {
+                prefer_http2: true,
+                http2_keep_alive_interval: interval,
+                http2_keep_alive_timeout: Some(Duration::from_secs(5)),
+                http2_keep_alive_while_idle: true,
+                ..Default::default()
+            },
+            PoolOptions {
+                http2_prior_knowledge: true,
+                http2_keep_alive_interval: interval,
+                ..Default::default()
+            },
+        ];
+        for pool in &valid {
+            assert!(validate_pool_options(pool).is_ok(), "{:?}", pool);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http2_connection_survives_idle_period() {
+        let server = named_server("ok").await;
+        let pool = PoolOptions {
+            http2_prior_knowledge: true,
+            http2_keep_alive_interval: Some(Duration::from_millis(50)),
+            http2_keep_alive_timeout: Some(Duration::from_secs(1)),
+            http2_keep_alive_while_idle: true,
+            ..Default::default()
+        };
+        let client = build_client(&pool, &ProxyMode::None);
+        for _ in 0..2 {
+            let response = client.get(server.uri()).send().await.unwrap();
+            assert_eq!(response.version(), reqwest::Version::HTTP_2);
+            assert_eq!(response.text().await.unwrap(), "ok");
+            // 空闲期间连接靠心跳保持
+            tokio::time::sleep(Duration::from_millis(300)).await;
+        }
+
+        // 明文连接无法通过 ALPN 协商，prefer_http2 回退到 HTTP/1.1
+        let pool = PoolOptions {
+            prefer_http2: true,
+            ..Default::default()
+        };
+        let response = build_client(&pool, &ProxyMode::None)
+            .get(server.uri())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.version(), reqwest::Version::HTTP_11);
+    }
+
+    #[test]
+    fn test_capture_raw_truncates_at_char_boundary() {
+        let value = serde_json::json!({"text": "你好世界"});
+        assert_eq!(capture_raw(value.clone(), None), value);
+        assert_eq!(capture_raw(value.clone(), Some(100)), value);
+
+        // `{"text":"` 占 9 字节，第 10 字节落在“你”的中间
+        let truncated = capture_raw(value, Some(10));
+        assert_eq!(truncated["truncated"], true);
+        assert_eq!(truncated["bytes"], 23);
+        assert_eq!(truncated["prefix"], r#"{"text":""#);
+    }
+
+    /// 启动对所有 GET 请求返回 `body` 的服务器
+    async fn named_server(body: &str) -> wiremock::MockServer {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    /// 按代理设置创建客户端，请求 `uri` 并返回响应体
+    async fn fetch_via(uri: &str, mode: &ProxyMode, no_proxy: Option<&str>) -> String {
+        apply_proxy_mode(
+            reqwest::Client::builder(),
+            mode,
+            no_proxy.and_then(reqwest::NoProxy::from_string),
+        )
+        .build()
+        .unwrap()
+        .get(uri)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_explicit_proxy_respects_no_proxy() {
+        let target = named_server("target").await;
+        let proxy = named_server("proxy").await;
+        let mode = ProxyMode::Explicit(proxy.uri());
+
+        assert_eq!(fetch_via(&target.uri(), &mode, None).await, "proxy");
+        assert_eq!(
+            fetch_via(&target.uri(), &mode, Some("example.com")).await,
+            "proxy"
+        );
+        // NO_PROXY 中列出的主机直接连接
+        assert_eq!(
+            fetch_via(&target.uri(), &mode, Some("example.com, 127.0.0.1")).await,
+            "target"
+        );
+        assert_eq!(
+            fetch_via(&target.uri(), &mode, Some("127.0.0.0/8")).await,
+            "target"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_proxy_mode_connects_directly() {
+        let target = named_server("target").await;
+        assert_eq!(
+            fetch_via(&target.uri(), &ProxyMode::None, None).await,
+            "target"
+        );
+    }
+
+    #[test]
+    fn test_system_proxy_mode_keeps_default_detection() {
+        let debug = |mode: &ProxyMode| {
+            format!(
+                "{:?}",
+                apply_proxy_mode(reqwest::Client::builder(), mode, None)
+            )
+        };
+        // 不显式设置代理，由 reqwest 在构造客户端时读取环境变量
+        assert_eq!(
+            debug(&ProxyMode::System),
+            format!("{:?}", reqwest::Client::builder())
+        );
+        assert!(!debug(&ProxyMode::None).contains("proxies"));
+        assert!(
+            debug(&ProxyMode::Explicit(
+                "http://proxy.example.com:3128".to_string()
+            ))
+            .contains("proxies")
+        );
+    }
+
+    /// 把 `body` 按 `chunk_size` 字节一块送入扫描器，返回切分出的各项
+    #[cfg(feature = "microsoft")]
+    fn scan(body: &str, chunk_size: usize) -> Result<Vec<String>, TranslationError> {
+        let mut scanner = crate::http::JsonArrayScanner::default();
+        let mut items = std::collections::VecDeque::new();
+        for chunk in body.as_bytes().chunks(chunk_size) {
+            scanner.feed(chunk, &mut items)?;
+        }
+        scanner.finish()?;
+        Ok(items
+            .into_iter()
+            .map(|item| String::from_utf8(item).unwrap())
+            .collect())
+    }
+
+    #[cfg(feature = "microsoft")]
+    #[test]
+    fn test_json_array_scanner_splits_items_across_chunks() {
+        let body = r#" [ {"text": "a, [b]} \"c\" 你好"}, [1, {"x": []}] ,"s",  42 ] "#;
+        let expected = vec![
+            r#"{"text": "a, [b]} \"c\" 你好"}"#,
+            r#"[1, {"x": []}]"#,
+            r#""s""#,
+            "42",
+        ];
+        // 任意的分块位置（包括多字节字符和转义符中间）都得到相同的结果
+        for chunk_size in 1..=body.len() {
+            assert_eq!(
+                scan(body, chunk_size).unwrap(),
+                expected,
+                "chunk {}",
+                chunk_size
+            );
+        }
+        assert_eq!(scan("[]", 1).unwrap(), Vec::<String>::new());
+    }
+
+    #[cfg(feature = "microsoft")]
+    #[test]
+    fn test_json_array_scanner_rejects_malformed_arrays() {
+        for body in [
+            r#"{"a": 1}"#,
+            "[1,]",
+            "[,1]",
+            "[1,,2]",
+            "[1] x",
+            "[1, [2]",
+            "[1}]",
+        ] {
+            assert!(
+                matches!(scan(body, 3), Err(TranslationError::MalformedResponse(_))),
+                "{}",
+                body
+            );
+        }
+    }
+}
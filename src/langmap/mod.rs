@@ -0,0 +1,60 @@
+//! 语言标识符到翻译服务语言代码的映射
+//!
+//! 各翻译服务对 BCP 47 标识符的支持并不一致：微软翻译只接受部分带地区或文字的代码
+//! （例如 `zh-Hans`、`sr-Latn`、`pt-PT`），`zh-Hans-CN` 这类组合可能被拒绝，
+//! 旧的语言代码（`iw`、`tl`、`no`）也不一定能被识别。这里的函数把任意标识符
+//! 转换为对应服务使用的代码。
+
+use unic_langid::LanguageIdentifier;
+
+/// 已被取代或有别名的语言代码，映射为微软翻译使用的代码
+const MICROSOFT_LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("tl", "fil"),
+    ("no", "nb"),
+    ("iw", "he"),
+    ("in", "id"),
+    ("ji", "yi"),
+    ("mo", "ro"),
+];
+
+/// 使用繁体中文的地区
+const TRADITIONAL_CHINESE_REGIONS: &[&str] = &["TW", "HK", "MO"];
+
+/// 转换为微软翻译使用的语言代码
+///
+/// - 中文只保留文字子标签：指定了 `Hans`/`Hant` 时使用指定的文字，否则台湾、香港、
+///   澳门映射为 `zh-Hant`，其他映射为 `zh-Hans`
+/// - 塞尔维亚语、蒙古语和因纽特语保留文字子标签，没有文字时使用微软的默认文字
+/// - 只保留微软区分的地区：除巴西以外的葡萄牙语映射为 `pt-PT`，加拿大法语为 `fr-CA`
+/// - 旧的或有别名的语言代码映射为微软使用的代码，例如 `tl` → `fil`、`no` → `nb`、`iw` → `he`
+/// - 其他标识符只保留语言子标签
+pub fn microsoft_code(lang: &LanguageIdentifier) -> String {
+    let language = lang.language.as_str();
+    let language = MICROSOFT_LANGUAGE_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == language)
+        .map_or(language, |(_, code)| code);
+    let script = lang.script.as_ref().map(|script| script.as_str());
+    let region = lang.region.as_ref().map(|region| region.as_str());
+
+    match (language, script, region) {
+        ("zh", Some(script @ ("Hans" | "Hant")), _) => format!("zh-{}", script),
+        ("zh", _, Some(region)) if TRADITIONAL_CHINESE_REGIONS.contains(&region) => {
+            "zh-Hant".to_string()
+        }
+        ("zh", _, _) => "zh-Hans".to_string(),
+        ("sr", Some(script @ ("Latn" | "Cyrl")), _) => format!("sr-{}", script),
+        // 黑山的塞尔维亚语通常使用拉丁字母
+        ("sr", _, Some("ME")) => "sr-Latn".to_string(),
+        ("sr", _, _) => "sr-Cyrl".to_string(),
+        ("mn", Some("Mong"), _) => "mn-Mong".to_string(),
+        ("mn", _, _) => "mn-Cyrl".to_string(),
+        ("iu", Some("Latn"), _) => "iu-Latn".to_string(),
+        ("pt", _, Some(region)) if region != "BR" => "pt-PT".to_string(),
+        ("fr", _, Some("CA")) => "fr-CA".to_string(),
+        (language, _, _) => language.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests;
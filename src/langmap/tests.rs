@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use crate::langmap::microsoft_code;
+    use unic_langid::LanguageIdentifier;
+
+    #[test]
+    fn test_microsoft_code_table() {
+        let cases = [
+            // 中文
+            ("zh", "zh-Hans"),
+            ("zh-CN", "zh-Hans"),
+            ("zh-SG", "zh-Hans"),
+            ("zh-TW", "zh-Hant"),
+            ("zh-HK", "zh-Hant"),
+            ("zh-MO", "zh-Hant"),
+            ("zh-Hans", "zh-Hans"),
+            ("zh-Hant", "zh-Hant"),
+            ("zh-Hans-CN", "zh-Hans"),
+            ("zh-Hant-TW", "zh-Hant"),
+            ("zh-Hans-HK", "zh-Hans"),
+            ("zh-Hant-CN", "zh-Hant"),
+            // 日文和韩文
+            ("ja", "ja"),
+            ("ja-JP", "ja"),
+            ("ko-KR", "ko"),
+            // 塞尔维亚语
+            ("sr", "sr-Cyrl"),
+            ("sr-RS", "sr-Cyrl"),
+            ("sr-Latn", "sr-Latn"),
+            ("sr-Latn-RS", "sr-Latn"),
+            ("sr-Cyrl-RS", "sr-Cyrl"),
+            ("sr-ME", "sr-Latn"),
+            // 挪威语
+            ("no", "nb"),
+            ("nb", "nb"),
+            ("nb-NO", "nb"),
+            ("no-NO", "nb"),
+            // 菲律宾语
+            ("tl", "fil"),
+            ("fil", "fil"),
+            ("fil-PH", "fil"),
+            ("tl-PH", "fil"),
+            // 葡萄牙语
+            ("pt", "pt"),
+            ("pt-BR", "pt"),
+            ("pt-PT", "pt-PT"),
+            ("pt-AO", "pt-PT"),
+            // 其他别名和地区
+            ("iw", "he"),
+            ("he-IL", "he"),
+            ("in", "id"),
+            ("fr-CA", "fr-CA"),
+            ("fr-FR", "fr"),
+            ("en-US", "en"),
+            ("en-Latn-GB", "en"),
+            ("mn", "mn-Cyrl"),
+            ("mn-Mong", "mn-Mong"),
+            ("iu-Latn", "iu-Latn"),
+            ("iu", "iu"),
+        ];
+        for (input, expected) in cases {
+            let lang: LanguageIdentifier = input.parse().unwrap();
+            assert_eq!(microsoft_code(&lang), expected, "mapping {}", input);
+        }
+    }
+}
@@ -15,14 +15,42 @@
 //! - 类型安全的语言标识符支持
 //! - 可配置的超时和重试机制
 //!
+//! ## Cargo features
+//!
+//! - `openai`（默认启用）：OpenAI 翻译器
+//! - `microsoft`（默认启用）：微软翻译器
+//! - `tracing`（默认启用）：根据当前 tracing span 自动生成 traceparent
+//! - `compression`（默认启用）：请求 gzip/brotli 压缩传输
+//! - `test-util`：测试工具和译文回归对比
+//! - `zeroize`：释放 API Key 时清零内存
+//! - `tower`：tower `Service` 适配器和 axum 路由（[`service`]）
+//! - `csv`：翻译 CSV/TSV 表格中的指定列（`formats::csv`）
+//! - `cli`：命令行程序，包括演示和交互模式（[`repl`]）
+//!
+//! 只需要其中一个翻译服务时可以关闭默认 features，例如
+//! `async-translate = { version = "...", default-features = false, features = ["microsoft"] }`。
+//! 不启用任何翻译服务时，[`Translator`] trait 与 [`TranslationManager`] 仍然可用，
+//! 可以注册自定义翻译器。
+//!
 //! ## 使用方法
 //!
-//! ```rust,no_run
+//! 设置环境变量 `ASYNC_TRANSLATE_OFFLINE=1` 时，示例改用返回确定性伪译文的
+//! [`testing::StaticTranslator`]（需要 `test-util` feature），不访问真实的翻译服务；
+//! 文档测试总是以这种方式运行并检查输出。
+//!
+//! ```rust
+//! # #[cfg(all(feature = "openai", feature = "microsoft", feature = "test-util"))]
 //! use async_translate::{TranslationManager, OpenAITranslator, OpenAIConfig, MicrosoftTranslator, MicrosoftConfig, LanguageIdentifier, TranslateOptions};
+//! # #[cfg(all(feature = "openai", feature = "microsoft", feature = "test-util"))]
+//! use async_translate::testing::{self, StaticTranslator};
+//! # #[cfg(all(feature = "openai", feature = "microsoft", feature = "test-util"))]
 //! use std::time::Duration;
 //!
+//! # #[cfg(all(feature = "openai", feature = "microsoft", feature = "test-util"))]
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! #   // 每个文档测试在单独的进程中运行
+//! #   unsafe { std::env::set_var(testing::OFFLINE_ENV, "1") };
 //!     // 创建翻译管理器
 //!     let mut manager = TranslationManager::new();
 //!
@@ -30,22 +58,30 @@
 //!     let openai_config = OpenAIConfig {
 //!         base_url: "https://api.openai.com/v1".to_string(),
 //!         model: "gpt-3.5-turbo".to_string(),
-//!         api_keys: vec!["your-openai-api-key".to_string()],
+//!         api_keys: vec!["your-openai-api-key".into()],
 //!         rpm_limit: 60,
 //!         concurrent_limit: 10,
 //!         system_prompt: None,
+//!         ..Default::default()
 //!     };
 //!     let openai_translator = Box::new(OpenAITranslator::new(openai_config));
-//!     manager.add_translator("openai", openai_translator);
 //!
 //!     // 配置微软翻译器（自动获取认证token）
 //!     let microsoft_config = MicrosoftConfig {
 //!         endpoint: None,  // 使用默认端点
 //!         api_key: None,   // 使用自动认证
 //!         concurrent_limit: 10,
+//!         ..Default::default()
 //!     };
 //!     let microsoft_translator = Box::new(MicrosoftTranslator::new(microsoft_config));
-//!     manager.add_translator("microsoft", microsoft_translator);
+//!
+//!     if testing::offline() {
+//!         manager.add_translator("openai", Box::new(StaticTranslator::new()));
+//!         manager.add_translator("microsoft", Box::new(StaticTranslator::new()));
+//!     } else {
+//!         manager.add_translator("openai", openai_translator);
+//!         manager.add_translator("microsoft", microsoft_translator);
+//!     }
 //!
 //!     // 执行翻译
 //!     let text = "Hello, world!";
@@ -54,6 +90,7 @@
 //!     // 基本翻译（使用默认配置）
 //!     let result = manager.translate("openai", text, &target_lang, None).await?;
 //!     println!("OpenAI Translation: {}", result);
+//! #   assert_eq!(result, "zh「Hello, world!」");
 //!
 //!     // 带配置的翻译
 //!     let options = TranslateOptions::default()
@@ -61,24 +98,129 @@
 //!         .max_retries(5);
 //!     let result = manager.translate_with_options("microsoft", text, &target_lang, None, &options).await?;
 //!     println!("Microsoft Translation: {}", result);
+//! #   assert_eq!(result, "zh「Hello, world!」");
 //!
 //!     Ok(())
 //! }
+//! # #[cfg(not(all(feature = "openai", feature = "microsoft", feature = "test-util")))]
+//! # fn main() {}
 //! ```
 
+#![cfg_attr(test, allow(clippy::module_inception))]
+// 不启用任何翻译服务时，只供翻译器使用的内部工具不会被调用
+#![cfg_attr(not(any(feature = "openai", feature = "microsoft")), allow(dead_code))]
+
+pub mod availability;
+mod batch;
+pub mod cache;
+pub mod clock;
+mod concurrency;
+pub mod convenience;
+pub mod detect;
 pub mod error;
+pub mod error_code;
+#[cfg(feature = "openai")]
+mod extract;
+pub mod formats;
+pub mod glossary;
+mod guard;
+mod http;
+pub mod langmap;
 pub mod manager;
+pub mod metrics;
+#[cfg(feature = "microsoft")]
 pub mod microsoft;
+pub mod normalize;
+#[cfg(feature = "openai")]
 pub mod openai;
 pub mod options;
+pub mod policy;
+pub mod postprocess;
+mod preflight;
+pub mod prelude;
+pub mod preview;
+pub mod protect;
+pub mod quota;
+mod ratelimit;
+pub mod registry;
+#[cfg(any(test, feature = "test-util"))]
+pub mod regression;
+#[cfg(any(test, feature = "cli"))]
+pub mod repl;
+mod retry;
+pub mod secret;
+#[cfg(feature = "tower")]
+pub mod service;
+pub mod stats;
+pub mod stream;
+pub mod tenant;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+pub mod trace;
 pub mod translator;
 
-pub use error::TranslationError;
-pub use manager::TranslationManager;
-pub use microsoft::{MicrosoftConfig, MicrosoftTranslator};
-pub use openai::{OpenAIConfig, OpenAITranslator};
-pub use options::TranslateOptions;
-pub use translator::Translator;
+pub use availability::{DowntimeWindow, Weekday};
+pub use cache::{CacheEntry, CacheKey, CacheKeyInputs, ImportReport, TranslationCache};
+pub use clock::{Clock, TokioClock};
+pub use convenience::ChineseVariant;
+pub use detect::DetectionCacheStats;
+pub use error::{BatchErrorReport, NetworkErrorKind, TranslationError};
+pub use error_code::{ErrorCode, register_messages};
+pub use glossary::Glossary;
+pub use manager::{
+    AdaptiveStats, IntoTranslator, LanguagePair, ManagerConfig, PairStats, ReloadReport,
+    SamplingHook, TranslationManager, TranslationRecord, TranslatorConfig, TranslatorRef,
+};
+pub use metrics::{MetricsRecorder, QueueStats};
+#[cfg(feature = "microsoft")]
+pub use microsoft::{
+    AuthStatus, BatchResults, GeoRegion, LowConfidencePolicy, MicrosoftConfig,
+    MicrosoftConfigBuilder, MicrosoftTranslation, MicrosoftTranslator,
+};
+pub use normalize::{Normalization, NormalizationForm};
+#[cfg(feature = "openai")]
+pub use openai::keys::{ApiKeyConfig, KeyProvider};
+#[cfg(feature = "openai")]
+pub use openai::prompt::{
+    ContextSegment, DefaultPromptStrategy, Message, PromptStrategy, Role, XmlTaggedStrategy,
+};
+#[cfg(feature = "openai")]
+pub use openai::{
+    ApiShape, KeyStrategy, OpenAIConfig, OpenAIConfigBuilder, OpenAITranslator, OverflowPolicy,
+    RateLimitState, TranslationAlternatives,
+};
+pub use options::{
+    Deadline, FailurePolicy, PoolOptions, ProviderOptions, ProxyMode, RetryEvent, RetryHook,
+    TraceContext, TranslateOptions, UnchangedComparison,
+};
+pub use policy::{ContentPolicy, PiiDetector, PolicyDecision};
+pub use postprocess::{CjkPunctuationNormalizer, PostProcessor, WhitespaceNormalizer};
+pub use preview::RequestPreview;
+pub use protect::ProtectPatterns;
+pub use quota::{Quota, QuotaCallback, QuotaEvent, QuotaTracker};
+pub use registry::{TranslatorFactory, TranslatorRegistry};
+pub use secret::SecretString;
+pub use stats::{StatsExport, StatsWindow, WindowPeriod};
+pub use tenant::{SharedRateLimiter, TenantContext};
+pub use trace::RequestTrace;
+pub use translator::{
+    LengthUnit, Provenance, TranslationDetail, TranslationKind, Translator, TranslatorCapabilities,
+};
 
 // 导出语言标识符类型
 pub use unic_langid::LanguageIdentifier;
+
+// 导出保护模式使用的正则表达式类型
+pub use regex::Regex;
+
+/// 解析语言标识符，例如 `lang("zh-Hans")`
+///
+/// 无法解析时返回 [`TranslationError::ConfigurationError`]，便于在返回翻译错误的函数中使用 `?`
+pub fn lang(tag: &str) -> Result<LanguageIdentifier, TranslationError> {
+    tag.parse().map_err(|e| {
+        TranslationError::ConfigurationError(format!(
+            "Invalid language identifier '{}': {}",
+            tag, e
+        ))
+    })
+}
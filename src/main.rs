@@ -1,23 +1,112 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_translate::{
-    LanguageIdentifier, TranslateOptions,
+    LanguageIdentifier, TranslateOptions, TranslationManager,
     microsoft::{MicrosoftConfig, MicrosoftTranslator},
     openai::{OpenAIConfig, OpenAITranslator},
+    repl::ReplSession,
 };
+use clap::{Arg, ArgMatches, Command};
 use std::time::Duration;
 use tracing::{error, info};
 
+fn cli() -> Command {
+    Command::new("async-translate")
+        .about("Asynchronous translation with Microsoft and OpenAI translators")
+        .subcommand(Command::new("demo").about("Run the translator demonstrations (default)"))
+        .subcommand(
+            Command::new("repl")
+                .about("Translate lines read from stdin interactively")
+                .arg(
+                    Arg::new("provider")
+                        .long("provider")
+                        .value_parser(["microsoft", "openai"])
+                        .default_value("microsoft")
+                        .help("Translator to start with"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .required(true)
+                        .help("Target language, e.g. zh"),
+                )
+                .arg(
+                    Arg::new("source")
+                        .long("source")
+                        .help("Source language, detected when omitted"),
+                ),
+        )
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    // 初始化日志
-    tracing_subscriber::fmt::init();
+    let matches = cli().get_matches();
+    match matches.subcommand() {
+        Some(("repl", matches)) => run_repl(matches).await,
+        _ => {
+            // 初始化日志
+            tracing_subscriber::fmt::init();
+
+            // --- Microsoft Translator Demonstration ---
+            run_microsoft_translator_demo().await?;
 
-    // --- Microsoft Translator Demonstration ---
-    run_microsoft_translator_demo().await?;
+            // --- OpenAI Translator Demonstration ---
+            run_openai_translator_demo().await?;
+
+            Ok(())
+        }
+    }
+}
+
+/// 交互模式：微软翻译器使用自动认证，设置了 `OPENAI_API_KEY` 时同时注册 OpenAI 翻译器，
+/// `OPENAI_BASE_URL` 和 `OPENAI_MODEL` 可以覆盖默认的地址和模型
+async fn run_repl(matches: &ArgMatches) -> Result<()> {
+    // 日志写到标准错误，不与译文混在一起
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+
+    let mut manager = TranslationManager::new();
+    manager.add_translator(
+        "microsoft",
+        Box::new(MicrosoftTranslator::new(MicrosoftConfig::default())),
+    );
+    if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
+        let mut config = OpenAIConfig::builder().api_keys(vec![api_key]);
+        if let Ok(base_url) = std::env::var("OPENAI_BASE_URL") {
+            config = config.base_url(base_url);
+        }
+        if let Ok(model) = std::env::var("OPENAI_MODEL") {
+            config = config.model(model);
+        }
+        let config = config.build();
+        config.validate()?;
+        manager.add_translator("openai", Box::new(OpenAITranslator::new(config)));
+    }
 
-    // --- OpenAI Translator Demonstration ---
-    run_openai_translator_demo().await?;
+    let provider = matches
+        .get_one::<String>("provider")
+        .expect("has a default");
+    let target_lang: LanguageIdentifier = matches
+        .get_one::<String>("to")
+        .expect("required")
+        .parse()
+        .context("invalid --to language")?;
+    let source_lang = matches
+        .get_one::<String>("source")
+        .map(|tag| tag.parse::<LanguageIdentifier>())
+        .transpose()
+        .context("invalid --source language")?;
+    let mut session = ReplSession::new(manager, provider, target_lang)
+        .context("set OPENAI_API_KEY to use the openai provider")?
+        .source_lang(source_lang);
 
+    eprintln!("Type text to translate, :help for commands, Ctrl-C or :quit to leave.");
+    let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+    session
+        .run(stdin, &mut tokio::io::stdout(), || async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
     Ok(())
 }
 
@@ -1,13 +1,485 @@
 //! 翻译管理器实现
 
-use crate::{error::TranslationError, options::TranslateOptions, translator::Translator};
+#[cfg(feature = "microsoft")]
+use crate::microsoft::{MicrosoftConfig, MicrosoftTranslator};
+#[cfg(feature = "openai")]
+use crate::openai::{OpenAIConfig, OpenAITranslator};
+use crate::{
+    availability::{self, DowntimeWindow},
+    batch,
+    cache::{CacheEntry, CacheKey, CacheKeyInputs, TranslationCache},
+    clock::{Clock, TokioClock},
+    concurrency::{ConcurrencyLimiter, Permit},
+    error::TranslationError,
+    guard,
+    options::{FailurePolicy, ProviderOptions, TranslateOptions},
+    policy::{self, ContentPolicy},
+    postprocess::{self, PostProcessor},
+    protect::Protected,
+    quota::{Quota, QuotaCallback},
+    registry::TranslatorRegistry,
+    stats::{StatsExport, StatsWindow, WindowExport, WindowPeriod},
+    translator::{TranslationDetail, TranslationKind, Translator},
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::sync::{Notify, mpsc};
 use unic_langid::LanguageIdentifier;
 
+/// 未经流量切分、使用翻译器自身配置的后端名称
+pub const PRIMARY_BACKEND: &str = "primary";
+
+/// 经流量切分、使用覆盖端点的后端名称
+pub const CANARY_BACKEND: &str = "canary";
+
+/// 流量切分配置：按比例为请求应用覆盖的端点
+#[derive(Debug)]
+struct TrafficSplit {
+    /// 应用到被选中请求的覆盖选项
+    overrides: ProviderOptions,
+    /// 被选中请求的百分比（0-100）
+    percentage: f64,
+    rng: Mutex<StdRng>,
+}
+
+impl TrafficSplit {
+    /// 随机决定本次请求是否应用覆盖选项
+    fn pick_canary(&self) -> bool {
+        let roll: f64 = self.rng.lock().unwrap().random();
+        roll * 100.0 < self.percentage
+    }
+}
+
+/// 声明式的翻译器配置
+///
+/// 可用的变体取决于启用的 features；不启用任何翻译服务时没有可用的变体
+#[derive(Debug, Clone, PartialEq)]
+pub enum TranslatorConfig {
+    /// OpenAI 翻译器
+    #[cfg(feature = "openai")]
+    OpenAI(OpenAIConfig),
+    /// 微软翻译器
+    #[cfg(feature = "microsoft")]
+    Microsoft(MicrosoftConfig),
+}
+
+impl TranslatorConfig {
+    /// 检查配置是否可以用于创建翻译器
+    pub fn validate(&self) -> Result<(), TranslationError> {
+        match *self {
+            #[cfg(feature = "openai")]
+            TranslatorConfig::OpenAI(ref config) => config.validate(),
+            #[cfg(feature = "microsoft")]
+            TranslatorConfig::Microsoft(ref config) => config.validate(),
+        }
+    }
+
+    /// 根据配置创建翻译器
+    fn build(&self) -> Arc<dyn Translator> {
+        match *self {
+            #[cfg(feature = "openai")]
+            TranslatorConfig::OpenAI(ref config) => Arc::new(OpenAITranslator::new(config.clone())),
+            #[cfg(feature = "microsoft")]
+            TranslatorConfig::Microsoft(ref config) => {
+                Arc::new(MicrosoftTranslator::new(config.clone()))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "openai")]
+impl From<OpenAIConfig> for TranslatorConfig {
+    fn from(config: OpenAIConfig) -> Self {
+        TranslatorConfig::OpenAI(config)
+    }
+}
+
+#[cfg(feature = "microsoft")]
+impl From<MicrosoftConfig> for TranslatorConfig {
+    fn from(config: MicrosoftConfig) -> Self {
+        TranslatorConfig::Microsoft(config)
+    }
+}
+
+/// 声明式的管理器配置
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ManagerConfig {
+    /// 翻译器配置，键为翻译器名称
+    pub translators: HashMap<String, TranslatorConfig>,
+}
+
+impl ManagerConfig {
+    /// 创建空配置
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一个翻译器配置
+    #[cfg_attr(
+        not(any(feature = "openai", feature = "microsoft")),
+        allow(unreachable_code)
+    )]
+    pub fn translator(mut self, name: &str, config: impl Into<TranslatorConfig>) -> Self {
+        self.translators.insert(name.to_string(), config.into());
+        self
+    }
+}
+
+/// [`TranslationManager::from_config`] 使用的 JSON 配置
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonManagerConfig {
+    translators: HashMap<String, JsonTranslatorConfig>,
+}
+
+/// JSON 配置中的单个翻译器
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonTranslatorConfig {
+    /// 注册表中的翻译器种类
+    kind: String,
+    /// 传给工厂函数的配置
+    #[serde(default)]
+    config: serde_json::Value,
+}
+
+/// [`TranslationManager::apply_config`] 的执行结果，名称均按字母顺序排列
+#[derive(Debug, Default)]
+pub struct ReloadReport {
+    /// 新增的翻译器
+    pub added: Vec<String>,
+    /// 移除的翻译器
+    pub removed: Vec<String>,
+    /// 配置变化后重建的翻译器
+    pub updated: Vec<String>,
+    /// 未通过校验的配置及原因，对应的已有翻译器保持不变
+    pub failed: Vec<(String, TranslationError)>,
+}
+
+/// 一次成功翻译的记录，传给 [`SamplingHook`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranslationRecord {
+    /// 翻译器名称
+    pub provider: String,
+    /// 调用方指定的源语言，None 表示自动检测
+    pub source_lang: Option<LanguageIdentifier>,
+    /// 目标语言
+    pub target_lang: LanguageIdentifier,
+    /// 原文
+    pub input: String,
+    /// 译文
+    pub output: String,
+    /// 翻译耗时
+    pub latency: Duration,
+}
+
+/// 翻译质量抽样钩子，例如把部分译文提交人工审核
+///
+/// 钩子在后台线程中依次调用，耗时较长也不会拖慢翻译；
+/// 待处理的记录超过 [`SAMPLE_QUEUE_CAPACITY`] 时，新的抽样会被丢弃
+pub trait SamplingHook: Send + Sync + 'static {
+    /// 处理一条被抽中的翻译记录
+    fn maybe_sample(&self, record: &TranslationRecord);
+}
+
+/// 等待抽样钩子处理的记录数上限
+pub const SAMPLE_QUEUE_CAPACITY: usize = 1024;
+
+/// 统计的键：翻译器名称与语言对
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LanguagePair {
+    /// 翻译器名称
+    pub provider: String,
+    /// 调用方指定的源语言，None 表示自动检测
+    pub source_lang: Option<LanguageIdentifier>,
+    /// 目标语言
+    pub target_lang: LanguageIdentifier,
+}
+
+/// 单个语言对的翻译统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PairStats {
+    /// 成功的翻译数
+    pub successes: u64,
+    /// 失败的翻译数
+    pub failures: u64,
+    /// 成功翻译的总耗时
+    pub total_latency: Duration,
+}
+
+impl PairStats {
+    /// 成功翻译的平均耗时
+    pub fn average_latency(&self) -> Duration {
+        if self.successes == 0 {
+            Duration::ZERO
+        } else {
+            self.total_latency.div_f64(self.successes as f64)
+        }
+    }
+}
+
+/// [`TranslationManager::translate_adaptive`] 为单个翻译器维护的统计
+///
+/// 耗时和错误率都是指数加权移动平均（EWMA），越新的请求权重越大
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AdaptiveStats {
+    /// 经过该翻译器的请求数
+    pub requests: u64,
+    /// 成功翻译耗时的移动平均，尚无成功翻译时为 None
+    pub latency: Option<Duration>,
+    /// 失败率的移动平均（0.0-1.0）
+    pub error_rate: f64,
+}
+
+impl AdaptiveStats {
+    /// 更新一次请求的结果，`latency` 为 None 表示失败
+    fn update(&mut self, latency: Option<Duration>, smoothing: f64) {
+        self.requests += 1;
+        let failed = if latency.is_some() { 0.0 } else { 1.0 };
+        self.error_rate += smoothing * (failed - self.error_rate);
+        if let Some(sample) = latency {
+            self.latency = Some(match self.latency {
+                Some(average) => average.mul_f64(1.0 - smoothing) + sample.mul_f64(smoothing),
+                None => sample,
+            });
+        }
+    }
+
+    /// 预期代价：平均耗时除以成功率，越小越好；从未成功时为无穷大
+    fn cost(&self) -> f64 {
+        match self.latency {
+            Some(latency) => latency.as_secs_f64() / (1.0 - self.error_rate).max(0.01),
+            None => f64::INFINITY,
+        }
+    }
+}
+
+/// 自适应选择翻译器的配置和统计
+struct AdaptiveRouter {
+    /// 随机选择候选以重新探索的概率
+    epsilon: f64,
+    /// 移动平均中最新一次请求的权重
+    smoothing: f64,
+    rng: Mutex<StdRng>,
+    /// 各翻译器的统计，键为翻译器名称
+    stats: Mutex<HashMap<String, AdaptiveStats>>,
+}
+
+impl AdaptiveRouter {
+    fn new(epsilon: f64, smoothing: f64, rng: StdRng) -> Self {
+        Self {
+            epsilon: epsilon.clamp(0.0, 1.0),
+            smoothing: smoothing.clamp(f64::EPSILON, 1.0),
+            rng: Mutex::new(rng),
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 选择候选：优先尚无统计的候选，否则以 `epsilon` 的概率随机选择，
+    /// 其余时候选择预期代价最小的候选
+    fn pick<'a>(&self, candidates: &[&'a str]) -> &'a str {
+        let stats = self.stats.lock().unwrap();
+        if let Some(unexplored) = candidates.iter().find(|name| !stats.contains_key(**name)) {
+            return unexplored;
+        }
+        let mut rng = self.rng.lock().unwrap();
+        if rng.random::<f64>() < self.epsilon {
+            return candidates[rng.random_range(0..candidates.len())];
+        }
+        candidates
+            .iter()
+            .min_by(|a, b| stats[**a].cost().total_cmp(&stats[**b].cost()))
+            .copied()
+            .unwrap_or(candidates[0])
+    }
+
+    fn update(&self, translator_name: &str, latency: Option<Duration>) {
+        self.stats
+            .lock()
+            .unwrap()
+            .entry(translator_name.to_string())
+            .or_default()
+            .update(latency, self.smoothing);
+    }
+}
+
+/// 带标签的批量翻译中同时进行的翻译数
+pub const TAGGED_BATCH_CONCURRENCY: usize = 16;
+
+/// 自适应选择默认的探索概率
+const DEFAULT_ADAPTIVE_EPSILON: f64 = 0.1;
+
+/// 自适应选择默认的移动平均权重
+const DEFAULT_ADAPTIVE_SMOOTHING: f64 = 0.3;
+
+/// 抽样配置
+struct Sampler {
+    hook: Arc<dyn SamplingHook>,
+    /// 未单独配置的语言对使用的抽样率
+    default_rate: f64,
+    rng: Mutex<StdRng>,
+    /// 首次抽样时启动后台线程
+    sender: OnceLock<mpsc::Sender<TranslationRecord>>,
+}
+
+impl Sampler {
+    /// 把记录交给后台线程，队列已满时丢弃
+    fn submit(&self, record: TranslationRecord) {
+        let sender = self.sender.get_or_init(|| {
+            let (sender, mut receiver) = mpsc::channel(SAMPLE_QUEUE_CAPACITY);
+            let hook = self.hook.clone();
+            // 钩子是同步调用，放在阻塞线程中执行，避免占用异步运行时
+            tokio::task::spawn_blocking(move || {
+                while let Some(record) = receiver.blocking_recv() {
+                    hook.maybe_sample(&record);
+                }
+            });
+            sender
+        });
+        let _ = sender.try_send(record);
+    }
+}
+
+/// 可以注册到 [`TranslationManager`] 的翻译器
+///
+/// `Box` 和 `Arc` 包装的翻译器都可以直接注册；使用 `Arc` 时可以在管理器之外
+/// 保留同一个实例
+pub trait IntoTranslator {
+    /// 转换为共享的翻译器实例
+    fn into_translator(self) -> Arc<dyn Translator>;
+}
+
+impl<T: Translator + 'static> IntoTranslator for Box<T> {
+    fn into_translator(self) -> Arc<dyn Translator> {
+        Arc::from(self as Box<dyn Translator>)
+    }
+}
+
+impl IntoTranslator for Box<dyn Translator> {
+    fn into_translator(self) -> Arc<dyn Translator> {
+        self.into()
+    }
+}
+
+impl<T: Translator + 'static> IntoTranslator for Arc<T> {
+    fn into_translator(self) -> Arc<dyn Translator> {
+        self
+    }
+}
+
+impl IntoTranslator for Arc<dyn Translator> {
+    fn into_translator(self) -> Arc<dyn Translator> {
+        self
+    }
+}
+
+/// 已注册翻译器的具体类型引用，由 [`TranslationManager::get_translator_as`] 返回
+///
+/// 持有翻译器的 `Arc`，之后替换或移除管理器中的同名翻译器不影响已取得的引用
+pub struct TranslatorRef<T> {
+    translator: Arc<dyn Translator>,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static> Deref for TranslatorRef<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.translator
+            .as_any()
+            .downcast_ref()
+            .expect("translator type checked when the reference was created")
+    }
+}
+
+/// 管理器级的并发限制，对所有翻译器共同生效
+struct GlobalConcurrency {
+    limit: usize,
+    limiter: ConcurrencyLimiter,
+}
+
+/// 已注册的翻译器
+struct Registration {
+    translator: Arc<dyn Translator>,
+    /// 通过 [`TranslationManager::apply_config`] 注册时使用的配置
+    config: Option<TranslatorConfig>,
+}
+
 /// 翻译管理器，用于统一管理多个翻译器
+///
+/// 克隆得到的是共享同一份状态的句柄，可以直接放进 axum 的 `State` 等需要 `Clone` 的地方：
+/// 注册的翻译器和别名、统计、关闭状态、正在进行的翻译数和全局并发限制在所有克隆间共享，
+/// 通过任意一个句柄注册的翻译器对其他句柄立即可见。后处理器、流量切分、抽样率和缓存
+/// 命名空间等构造时的设置在克隆时复制，之后对其中一个句柄的修改不影响其他句柄。
+#[derive(Clone)]
 pub struct TranslationManager {
-    /// 翻译器映射表，键为翻译器名称，值为翻译器实例
-    translators: HashMap<String, Box<dyn Translator>>,
+    /// 翻译器映射表，键为翻译器名称
+    ///
+    /// 翻译时只持有锁取出 `Arc`，替换翻译器不会影响正在进行的翻译
+    translators: Arc<RwLock<HashMap<String, Registration>>>,
+    /// 翻译器别名，键为别名，值为对应的翻译器名称
+    aliases: Arc<RwLock<HashMap<String, String>>>,
+    /// 流量切分配置，键为翻译器名称
+    traffic_splits: HashMap<String, Arc<TrafficSplit>>,
+    /// 对所有翻译器生效的后处理器
+    post_processors: Vec<Arc<dyn PostProcessor>>,
+    /// 只对指定翻译器生效的后处理器，键为翻译器名称，先于全局后处理器执行
+    translator_post_processors: HashMap<String, Vec<Arc<dyn PostProcessor>>>,
+    /// 质量抽样配置
+    sampler: Option<Arc<Sampler>>,
+    /// 单独配置的抽样率，键为（源语言，目标语言）
+    sampling_rates: HashMap<(Option<LanguageIdentifier>, LanguageIdentifier), f64>,
+    /// 当前统计窗口中各语言对的翻译统计
+    pair_stats: Arc<StatsWindow<HashMap<LanguagePair, PairStats>>>,
+    /// 自适应选择翻译器的配置和统计
+    adaptive: Arc<AdaptiveRouter>,
+    /// 缓存键的命名空间
+    cache_namespace: Option<String>,
+    /// 译文缓存，克隆时共享
+    cache: Option<TranslationCache>,
+    /// 发送前检查原文的内容策略
+    content_policy: Option<Arc<dyn ContentPolicy>>,
+    /// 带标签的批量翻译的启动延迟
+    start_jitter: Arc<batch::StartJitter>,
+    /// 是否已开始关闭
+    shutting_down: Arc<AtomicBool>,
+    /// 正在进行的翻译数
+    in_flight: Arc<AtomicUsize>,
+    /// 正在进行的翻译数降为零时通知
+    idle: Arc<Notify>,
+    /// 所有翻译器共用的并发限制
+    global_concurrency: Option<Arc<GlobalConcurrency>>,
+    /// 计划的维护窗口，键为翻译器名称
+    availability: HashMap<String, Vec<DowntimeWindow>>,
+    /// 判断是否处于维护窗口的时钟
+    availability_clock: Arc<dyn Clock>,
+    /// 总时限计时、等待全局并发许可和启动延迟使用的时钟
+    clock: Arc<dyn Clock>,
+    /// 各翻译器的用量配额，用量在所有克隆间共享
+    quotas: HashMap<String, Arc<Quota>>,
+    /// 对所有配额生效的阈值回调
+    quota_thresholds: Vec<(f64, QuotaCallback)>,
+}
+
+/// 正在进行的翻译的计数守卫，离开作用域（包括被取消）时减少计数
+struct InFlightGuard<'a> {
+    manager: &'a TranslationManager,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if self.manager.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.manager.idle.notify_waiters();
+        }
+    }
 }
 
 impl TranslationManager {
@@ -18,18 +490,789 @@ impl TranslationManager {
     /// 返回翻译管理器实例
     pub fn new() -> Self {
         Self {
-            translators: HashMap::new(),
+            translators: Arc::new(RwLock::new(HashMap::new())),
+            aliases: Arc::new(RwLock::new(HashMap::new())),
+            traffic_splits: HashMap::new(),
+            post_processors: Vec::new(),
+            translator_post_processors: HashMap::new(),
+            sampler: None,
+            sampling_rates: HashMap::new(),
+            pair_stats: Arc::new(StatsWindow::new()),
+            adaptive: Arc::new(AdaptiveRouter::new(
+                DEFAULT_ADAPTIVE_EPSILON,
+                DEFAULT_ADAPTIVE_SMOOTHING,
+                StdRng::from_os_rng(),
+            )),
+            cache_namespace: None,
+            cache: None,
+            content_policy: None,
+            start_jitter: Arc::new(batch::StartJitter::default()),
+            shutting_down: Arc::new(AtomicBool::new(false)),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            idle: Arc::new(Notify::new()),
+            global_concurrency: None,
+            availability: HashMap::new(),
+            availability_clock: Arc::new(TokioClock),
+            clock: Arc::new(TokioClock),
+            quotas: HashMap::new(),
+            quota_thresholds: Vec::new(),
+        }
+    }
+
+    /// 根据 JSON 配置创建翻译管理器，翻译器由注册表中对应种类的工厂函数创建
+    ///
+    /// 配置格式为 `{"translators": {"名称": {"kind": "种类", "config": {...}}}}`，
+    /// 任何一个翻译器创建失败都会返回错误
+    ///
+    /// # 参数
+    ///
+    /// * `config` - JSON 配置
+    /// * `registry` - 翻译器注册表
+    ///
+    /// # 返回值
+    ///
+    /// 返回翻译管理器实例
+    pub fn from_config(
+        config: serde_json::Value,
+        registry: &TranslatorRegistry,
+    ) -> Result<Self, TranslationError> {
+        let config: JsonManagerConfig = serde_json::from_value(config).map_err(|e| {
+            TranslationError::ConfigurationError(format!("Invalid manager config: {}", e))
+        })?;
+        let mut manager = Self::new();
+        for (name, translator) in config.translators {
+            let created = registry
+                .create(&translator.kind, translator.config)
+                .map_err(|e| match e {
+                    TranslationError::ConfigurationError(msg) => {
+                        TranslationError::ConfigurationError(format!(
+                            "translator '{}': {}",
+                            name, msg
+                        ))
+                    }
+                    e => e,
+                })?;
+            manager.add_translator(&name, created);
         }
+        Ok(manager)
     }
 
     /// 添加翻译器到管理器
     ///
     /// # 参数
     ///
-    /// * `name` - 翻译器名称
-    /// * `translator` - 翻译器实例
-    pub fn add_translator(&mut self, name: &str, translator: Box<dyn Translator>) {
-        self.translators.insert(name.to_string(), translator);
+    /// * `name` - 翻译器名称，与已有的别名同名时替换该别名
+    /// * `translator` - 翻译器实例，可以是 `Box<dyn Translator>` 或 `Arc<dyn Translator>`
+    pub fn add_translator(&mut self, name: &str, translator: impl IntoTranslator) {
+        self.aliases.write().unwrap().remove(name);
+        self.translators.write().unwrap().insert(
+            name.to_string(),
+            Registration {
+                translator: translator.into_translator(),
+                config: None,
+            },
+        );
+    }
+
+    /// 为已注册的翻译器添加别名，通过别名调用时使用同一个翻译器实例
+    ///
+    /// 别名之间不会嵌套：为别名再添加别名时指向其对应的翻译器。后处理器、流量切分等
+    /// 按翻译器名称注册的设置同样对别名生效
+    ///
+    /// # 参数
+    ///
+    /// * `existing_name` - 已注册的翻译器名称或别名
+    /// * `alias` - 别名
+    ///
+    /// # 返回值
+    ///
+    /// 翻译器不存在或别名与已注册的翻译器同名时返回 [`TranslationError::ConfigurationError`]
+    pub fn add_alias(&mut self, existing_name: &str, alias: &str) -> Result<(), TranslationError> {
+        let canonical = self.resolve(existing_name).ok_or_else(|| {
+            TranslationError::ConfigurationError(format!(
+                "Translator '{}' not found",
+                existing_name
+            ))
+        })?;
+        if self.translators.read().unwrap().contains_key(alias) {
+            return Err(TranslationError::ConfigurationError(format!(
+                "Alias '{}' conflicts with a registered translator",
+                alias
+            )));
+        }
+        self.aliases
+            .write()
+            .unwrap()
+            .insert(alias.to_string(), canonical);
+        Ok(())
+    }
+
+    /// 查找名称对应的翻译器名称：翻译器名称返回自身，别名返回其对应的翻译器名称
+    ///
+    /// # 返回值
+    ///
+    /// 名称没有对应已注册的翻译器时返回 None
+    pub fn resolve(&self, name: &str) -> Option<String> {
+        let canonical = self.canonical_name(name);
+        self.translators
+            .read()
+            .unwrap()
+            .contains_key(canonical.as_ref())
+            .then(|| canonical.into_owned())
+    }
+
+    /// 所有别名及其对应的翻译器名称，按别名的字母顺序排列
+    pub fn list_aliases(&self) -> Vec<(String, String)> {
+        let mut aliases: Vec<(String, String)> = self
+            .aliases
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(alias, name)| (alias.clone(), name.clone()))
+            .collect();
+        aliases.sort();
+        aliases
+    }
+
+    /// 将别名替换为对应的翻译器名称，其他名称原样返回
+    fn canonical_name<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        if self.translators.read().unwrap().contains_key(name) {
+            return Cow::Borrowed(name);
+        }
+        match self.aliases.read().unwrap().get(name) {
+            Some(canonical) => Cow::Owned(canonical.clone()),
+            None => Cow::Borrowed(name),
+        }
+    }
+
+    /// 应用新的声明式配置
+    ///
+    /// 与当前由配置注册的翻译器比较：新增的配置创建翻译器，不再出现的翻译器被移除，
+    /// 发生变化的配置重建翻译器并原子替换，正在进行的翻译继续使用旧实例。
+    /// 未通过校验的配置记录在 [`ReloadReport::failed`] 中，不影响已有的翻译器。
+    /// 通过 [`TranslationManager::add_translator`] 添加的翻译器不会被移除，
+    /// 但可以被同名配置替换。
+    ///
+    /// # 参数
+    ///
+    /// * `config` - 新的管理器配置
+    ///
+    /// # 返回值
+    ///
+    /// 返回变更报告，管理器已开始关闭时返回 [`TranslationError::ShuttingDown`]
+    pub fn apply_config(&self, config: ManagerConfig) -> Result<ReloadReport, TranslationError> {
+        if self.is_shutting_down() {
+            return Err(TranslationError::ShuttingDown);
+        }
+
+        let mut report = ReloadReport::default();
+        let mut translators = self.translators.write().unwrap();
+        translators.retain(|name, registration| {
+            let keep = registration.config.is_none() || config.translators.contains_key(name);
+            if !keep {
+                report.removed.push(name.clone());
+            }
+            keep
+        });
+
+        for (name, translator_config) in config.translators {
+            let current = translators
+                .get(&name)
+                .and_then(|registration| registration.config.as_ref());
+            if current == Some(&translator_config) {
+                continue;
+            }
+            if let Err(e) = translator_config.validate() {
+                report.failed.push((name, e));
+                continue;
+            }
+            let registration = Registration {
+                translator: translator_config.build(),
+                config: Some(translator_config),
+            };
+            if translators.insert(name.clone(), registration).is_some() {
+                report.updated.push(name);
+            } else {
+                report.added.push(name);
+            }
+        }
+
+        report.added.sort();
+        report.removed.sort();
+        report.updated.sort();
+        report.failed.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(report)
+    }
+
+    /// 为指定的翻译器配置流量切分
+    ///
+    /// 经过管理器的请求中，约 `percentage`% 会应用 `overrides` 中的端点覆盖
+    /// （调用方在选项中已显式设置的字段优先），其余请求使用翻译器自身的配置。
+    /// 详细结果的 `provider_metadata["backend"]` 记录实际使用的后端：
+    /// [`CANARY_BACKEND`] 或 [`PRIMARY_BACKEND`]。
+    ///
+    /// # 参数
+    ///
+    /// * `translator_name` - 翻译器名称
+    /// * `overrides` - 被选中请求使用的覆盖选项
+    /// * `percentage` - 被选中请求的百分比，超出 0-100 的值会被截断
+    pub fn with_traffic_split(
+        mut self,
+        translator_name: &str,
+        overrides: ProviderOptions,
+        percentage: f64,
+    ) -> Self {
+        self.traffic_splits.insert(
+            translator_name.to_string(),
+            Arc::new(TrafficSplit {
+                overrides,
+                percentage: percentage.clamp(0.0, 100.0),
+                rng: Mutex::new(StdRng::from_os_rng()),
+            }),
+        );
+        self
+    }
+
+    /// 添加对所有翻译器生效的后处理器
+    ///
+    /// 后处理器按添加顺序作用于成功的译文，抽样记录和统计使用处理后的译文
+    ///
+    /// # 参数
+    ///
+    /// * `processor` - 后处理器
+    pub fn add_post_processor(&mut self, processor: Arc<dyn PostProcessor>) -> &mut Self {
+        self.post_processors.push(processor);
+        self
+    }
+
+    /// 添加只对指定翻译器生效的后处理器
+    ///
+    /// 按名称关联，通过 [`TranslationManager::apply_config`] 替换翻译器后仍然生效
+    ///
+    /// # 参数
+    ///
+    /// * `translator_name` - 翻译器名称
+    /// * `processor` - 后处理器
+    pub fn add_translator_post_processor(
+        &mut self,
+        translator_name: &str,
+        processor: Arc<dyn PostProcessor>,
+    ) -> &mut Self {
+        self.translator_post_processors
+            .entry(translator_name.to_string())
+            .or_default()
+            .push(processor);
+        self
+    }
+
+    /// 依次应用指定翻译器的后处理器和全局后处理器
+    fn post_process(
+        &self,
+        translator_name: &str,
+        text: String,
+        target_lang: &LanguageIdentifier,
+    ) -> String {
+        let text = match self.translator_post_processors.get(translator_name) {
+            Some(processors) => postprocess::apply(processors, text, target_lang),
+            None => text,
+        };
+        postprocess::apply(&self.post_processors, text, target_lang)
+    }
+
+    /// 设置翻译质量抽样钩子
+    ///
+    /// 每次成功翻译后按抽样率随机决定是否把记录交给钩子
+    ///
+    /// # 参数
+    ///
+    /// * `hook` - 抽样钩子
+    /// * `rate` - 未单独配置的语言对使用的抽样率，超出 0.0-1.0 的值会被截断
+    pub fn with_sampling_hook(mut self, hook: impl SamplingHook, rate: f64) -> Self {
+        self.sampler = Some(Arc::new(Sampler {
+            hook: Arc::new(hook),
+            default_rate: rate.clamp(0.0, 1.0),
+            rng: Mutex::new(StdRng::from_os_rng()),
+            sender: OnceLock::new(),
+        }));
+        self
+    }
+
+    /// 为指定的语言对单独设置抽样率
+    ///
+    /// # 参数
+    ///
+    /// * `source_lang` - 源语言，None 表示调用时未指定源语言的翻译
+    /// * `target_lang` - 目标语言
+    /// * `rate` - 抽样率，超出 0.0-1.0 的值会被截断
+    pub fn with_sampling_rate(
+        mut self,
+        source_lang: Option<&LanguageIdentifier>,
+        target_lang: &LanguageIdentifier,
+        rate: f64,
+    ) -> Self {
+        self.sampling_rates.insert(
+            (source_lang.cloned(), target_lang.clone()),
+            rate.clamp(0.0, 1.0),
+        );
+        self
+    }
+
+    /// 为指定的翻译器设置计划的维护窗口，替换之前设置的窗口
+    ///
+    /// 维护期间直接调用该翻译器会立即返回 [`TranslationError::TranslatorUnavailable`]，
+    /// [`TranslationManager::translate_adaptive`] 和 [`TranslationManager::translate_with_fallback`]
+    /// 会跳过该翻译器。通过别名调用时按别名指向的翻译器判断
+    ///
+    /// # 参数
+    ///
+    /// * `translator_name` - 翻译器名称
+    /// * `windows` - 维护窗口，为空时取消设置
+    pub fn with_availability(
+        mut self,
+        translator_name: &str,
+        windows: Vec<DowntimeWindow>,
+    ) -> Self {
+        if windows.is_empty() {
+            self.availability.remove(translator_name);
+        } else {
+            self.availability
+                .insert(translator_name.to_string(), windows);
+        }
+        self
+    }
+
+    /// 设置判断维护窗口使用的时钟，测试中可以使用 `testing::MockClock`
+    pub fn with_availability_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.availability_clock = clock;
+        self
+    }
+
+    /// 设置总时限计时、等待全局并发许可和带标签批量翻译的启动延迟使用的时钟
+    ///
+    /// 管理器开始计时的总时限交给翻译器后按翻译器配置的时钟判断，两者应使用同一个时钟，
+    /// 例如测试中把同一个 `testing::MockClock` 同时交给管理器和翻译器
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 为翻译器设置用量配额，用量达到阈值时在单独的任务中调用回调
+    ///
+    /// 用量按成功翻译的原文字符数累加，命中缓存的翻译不计入；配额只用于提醒，
+    /// 超出上限后翻译照常进行。详见 [`crate::quota`]
+    ///
+    /// # 参数
+    ///
+    /// * `translator_name` - 翻译器名称，通过别名调用时计入别名指向的翻译器
+    /// * `quota` - 配额
+    pub fn with_quota(mut self, translator_name: &str, quota: Quota) -> Self {
+        self.quotas
+            .insert(translator_name.to_string(), Arc::new(quota));
+        self
+    }
+
+    /// 注册对所有翻译器的配额生效的阈值回调，与 [`Quota::on_threshold`] 注册的回调同时生效
+    pub fn on_quota_threshold(mut self, thresholds: Vec<(f64, QuotaCallback)>) -> Self {
+        self.quota_thresholds.extend(thresholds);
+        self
+    }
+
+    /// 翻译器的用量配额，未设置时返回 None
+    pub fn quota(&self, translator_name: &str) -> Option<&Quota> {
+        self.quotas
+            .get(&*self.canonical_name(translator_name))
+            .map(Arc::as_ref)
+    }
+
+    /// 翻译器当前处于维护窗口时返回恢复可用的时间，否则返回 None
+    ///
+    /// # 参数
+    ///
+    /// * `translator_name` - 翻译器名称或别名
+    pub fn unavailable_until(&self, translator_name: &str) -> Option<SystemTime> {
+        let windows = self
+            .availability
+            .get(&*self.canonical_name(translator_name))?;
+        availability::unavailable_until(windows, self.availability_clock.system_time())
+    }
+
+    /// 翻译器处于维护窗口时返回 [`TranslationError::TranslatorUnavailable`]
+    fn ensure_available(&self, translator_name: &str) -> Result<(), TranslationError> {
+        match self.unavailable_until(translator_name) {
+            Some(until) => Err(TranslationError::TranslatorUnavailable {
+                translator: translator_name.to_string(),
+                until,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// 获取当前统计窗口中各语言对的翻译统计
+    pub fn pair_stats(&self) -> HashMap<LanguagePair, PairStats> {
+        self.pair_stats.current().stats
+    }
+
+    /// 按日历时间划分统计窗口，已有的统计会被清空
+    ///
+    /// 未设置时统计只在调用 [`TranslationManager::snapshot_and_reset_stats`] 时重置
+    ///
+    /// # 参数
+    ///
+    /// * `period` - 窗口长度，窗口按 UTC 整点或零点等对齐
+    /// * `clock` - 获取日历时间的时钟，测试中可以使用 `testing::MockClock`
+    pub fn with_stats_window(mut self, period: WindowPeriod, clock: Arc<dyn Clock>) -> Self {
+        self.pair_stats = Arc::new(StatsWindow::with_clock(Some(period), clock));
+        self
+    }
+
+    /// 导出当前统计窗口和上一个已结束窗口的统计，不重置统计
+    pub fn export_stats(&self) -> StatsExport {
+        let (current, previous) = self.pair_stats.current_and_previous();
+        StatsExport::new(self.pair_stats.period(), &current, previous.as_ref())
+    }
+
+    /// 结束当前统计窗口并开始新窗口，返回刚结束窗口的统计
+    ///
+    /// 所有统计在同一时刻切换，切换期间完成的翻译计入新窗口
+    pub fn snapshot_and_reset_stats(&self) -> WindowExport {
+        WindowExport::new(&self.pair_stats.snapshot_and_reset())
+    }
+
+    /// 设置 [`TranslationManager::translate_adaptive`] 的探索概率和移动平均权重，
+    /// 已有的自适应统计会被清空
+    ///
+    /// # 参数
+    ///
+    /// * `epsilon` - 随机选择候选的概率，使恢复的翻译器能被重新发现，超出 0.0-1.0 的值会被截断
+    /// * `smoothing` - 移动平均中最新一次请求的权重，越大对变化越敏感，超出 0.0-1.0 的值会被截断
+    pub fn with_adaptive_routing(mut self, epsilon: f64, smoothing: f64) -> Self {
+        self.adaptive = Arc::new(AdaptiveRouter::new(
+            epsilon,
+            smoothing,
+            StdRng::from_os_rng(),
+        ));
+        self
+    }
+
+    /// 固定自适应选择使用的随机数种子，使探索的结果可以复现，已有的自适应统计会被清空
+    pub fn with_adaptive_seed(mut self, seed: u64) -> Self {
+        self.adaptive = Arc::new(AdaptiveRouter::new(
+            self.adaptive.epsilon,
+            self.adaptive.smoothing,
+            StdRng::seed_from_u64(seed),
+        ));
+        self
+    }
+
+    /// 获取 [`TranslationManager::translate_adaptive`] 使用的各翻译器统计，键为翻译器名称
+    pub fn adaptive_stats(&self) -> HashMap<String, AdaptiveStats> {
+        self.adaptive.stats.lock().unwrap().clone()
+    }
+
+    /// 清空自适应统计，之后每个候选都会被重新探索
+    pub fn reset_adaptive_stats(&self) {
+        self.adaptive.stats.lock().unwrap().clear();
+    }
+
+    /// 设置缓存键的命名空间，修改后所有缓存键都会变化，用于手动使缓存失效
+    pub fn with_cache_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.cache_namespace = Some(namespace.into());
+        self
+    }
+
+    /// 缓存键的命名空间
+    pub(crate) fn cache_namespace(&self) -> Option<&str> {
+        self.cache_namespace.as_deref()
+    }
+
+    /// 启用译文缓存
+    ///
+    /// [`TranslationManager::translate_with_options`] 先按 [`TranslationManager::cache_key`]
+    /// 查找缓存，命中时直接返回，否则翻译成功后把后处理过的译文写入缓存。
+    /// 翻译器的 `cache_key_inputs` 返回 None 时不使用缓存。
+    pub fn with_cache(mut self, cache: TranslationCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// 设置发送前检查原文的内容策略
+    ///
+    /// 所有经过管理器的翻译（包括批量翻译中的每一项）在发送给翻译器之前都要经过策略检查：
+    /// 被禁止的文本直接返回 [`TranslationError::PolicyViolation`]，不发出任何请求，也不按
+    /// `on_failure` 返回原文；需要遮盖的片段替换为占位符后翻译，译文经过后处理后再还原为
+    /// 原文。见 [`crate::policy`]。
+    pub fn with_content_policy(mut self, policy: impl ContentPolicy + 'static) -> Self {
+        self.content_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// 按内容策略检查原文，需要遮盖时返回遮盖后的文本
+    fn redact(&self, text: &str) -> Result<Option<Protected>, TranslationError> {
+        match &self.content_policy {
+            Some(policy) => policy::apply(policy.as_ref(), text),
+            None => Ok(None),
+        }
+    }
+
+    /// 获取译文缓存，未启用时返回 None
+    pub fn cache(&self) -> Option<&TranslationCache> {
+        self.cache.as_ref()
+    }
+
+    /// 指定翻译器翻译该语言对时提供的缓存键输入，翻译器不存在或不应缓存时返回 None
+    pub(crate) fn cache_key_inputs(
+        &self,
+        translator_name: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Option<CacheKeyInputs> {
+        self.get_translator(&self.canonical_name(translator_name))
+            .ok()?
+            .cache_key_inputs(target_lang, source_lang, options)
+    }
+
+    /// 计算使用指定翻译器翻译文本时的缓存键
+    ///
+    /// 键由管理器的命名空间、翻译器提供的 [`crate::CacheKeyInputs`]、原文和语言对共同决定
+    ///
+    /// # 参数
+    ///
+    /// * `translator_name` - 翻译器名称
+    /// * `text` - 需要翻译的文本
+    /// * `target_lang` - 目标语言标识符
+    /// * `source_lang` - 源语言标识符 (None表示自动检测)
+    /// * `options` - 翻译配置选项
+    ///
+    /// # 返回值
+    ///
+    /// 返回缓存键，翻译器的译文不应被缓存时返回 None，翻译器不存在时返回错误
+    pub fn cache_key(
+        &self,
+        translator_name: &str,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<Option<CacheKey>, TranslationError> {
+        let translator = self.get_translator(&self.canonical_name(translator_name))?;
+        Ok(translator
+            .cache_key_inputs(target_lang, source_lang, options)
+            .map(|inputs| {
+                CacheKey::compute(
+                    self.cache_namespace.as_deref(),
+                    &inputs,
+                    text,
+                    target_lang,
+                    source_lang,
+                )
+            }))
+    }
+
+    /// 更新统计，成功时按抽样率提交给抽样钩子
+    fn record(
+        &self,
+        translator_name: &str,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        output: Option<&str>,
+        latency: Duration,
+    ) {
+        let pair = LanguagePair {
+            provider: translator_name.to_string(),
+            source_lang: source_lang.cloned(),
+            target_lang: target_lang.clone(),
+        };
+        self.pair_stats.update(|pair_stats| {
+            let stats = pair_stats.entry(pair.clone()).or_default();
+            if output.is_some() {
+                stats.successes += 1;
+                stats.total_latency += latency;
+            } else {
+                stats.failures += 1;
+            }
+        });
+
+        if let (Some(quota), Some(_)) = (self.quotas.get(translator_name), output) {
+            quota.record(
+                translator_name,
+                text.chars().count() as u64,
+                &self.quota_thresholds,
+            );
+        }
+
+        let (Some(sampler), Some(output)) = (&self.sampler, output) else {
+            return;
+        };
+        let rate = self
+            .sampling_rates
+            .get(&(pair.source_lang.clone(), pair.target_lang.clone()))
+            .copied()
+            .unwrap_or(sampler.default_rate);
+        let roll: f64 = sampler.rng.lock().unwrap().random();
+        if roll < rate {
+            sampler.submit(TranslationRecord {
+                provider: pair.provider,
+                source_lang: pair.source_lang,
+                target_lang: pair.target_lang,
+                input: text.to_string(),
+                output: output.to_string(),
+                latency,
+            });
+        }
+    }
+
+    /// 开始关闭：之后的翻译请求都会返回 [`TranslationError::ShuttingDown`]
+    ///
+    /// 已经开始的翻译不受影响，可以配合 [`TranslationManager::drain`] 等待它们完成
+    pub fn begin_shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    /// 是否已开始关闭
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// 当前正在进行的翻译数，包括正在等待全局并发许可的翻译
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// 等待正在进行的翻译全部完成
+    ///
+    /// # 参数
+    ///
+    /// * `timeout` - 最长等待时间
+    ///
+    /// # 返回值
+    ///
+    /// 返回超时时仍未完成的翻译数，全部完成时返回 0
+    pub async fn drain(&self, timeout: Duration) -> usize {
+        let wait = async {
+            loop {
+                // 先注册通知再检查计数，避免错过计数归零
+                let notified = self.idle.notified();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        let _ = tokio::time::timeout(timeout, wait).await;
+        self.in_flight()
+    }
+
+    /// 限制通过管理器同时进行的翻译数，不论使用哪个翻译器
+    ///
+    /// 每次翻译先获取全局许可，再交给翻译器获取它自己的并发许可；等待全局许可的时间同样受
+    /// `max_queue_wait` 限制。一次逻辑请求只占用一个许可：`translate_adaptive` 只占用所选
+    /// 候选的一个许可，带标签的批量翻译中每条文本各占用一个。`limit` 为 0 时按 1 处理
+    pub fn with_global_concurrency(mut self, limit: usize) -> Self {
+        let limit = limit.max(1);
+        self.global_concurrency = Some(Arc::new(GlobalConcurrency {
+            limit,
+            limiter: ConcurrencyLimiter::new(limit, None),
+        }));
+        self
+    }
+
+    /// 当前持有全局并发许可的翻译数，未设置全局并发限制时为 0
+    pub fn global_in_flight(&self) -> usize {
+        self.global_concurrency.as_ref().map_or(0, |global| {
+            global.limit - global.limiter.available_permits()
+        })
+    }
+
+    /// 获取全局并发许可，等待时间不超过 `max_queue_wait` 和总时限的剩余时间，
+    /// 未设置全局并发限制时返回 None
+    async fn acquire_global(
+        &self,
+        options: &TranslateOptions,
+    ) -> Result<Option<Permit<'_>>, TranslationError> {
+        match &self.global_concurrency {
+            Some(global) => global
+                .limiter
+                .acquire_within(
+                    options.get_max_queue_wait(),
+                    options.get_deadline(),
+                    self.clock.as_ref(),
+                )
+                .await
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// 开始调用方设置的总时限的计时，之后等待全局许可和调用的翻译器共用同一个时限
+    fn start_deadline<'a>(&self, options: Cow<'a, TranslateOptions>) -> Cow<'a, TranslateOptions> {
+        match options.deadline {
+            Some(Some(_)) => Cow::Owned(options.into_owned().start_deadline(self.clock.as_ref())),
+            _ => options,
+        }
+    }
+
+    /// 登记一次翻译，已开始关闭时返回错误
+    fn enter(&self) -> Result<InFlightGuard<'_>, TranslationError> {
+        // 先增加计数再检查标志，保证 drain 不会漏掉刚开始的翻译
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = InFlightGuard { manager: self };
+        if self.is_shutting_down() {
+            return Err(TranslationError::ShuttingDown);
+        }
+        Ok(guard)
+    }
+
+    /// 根据流量切分配置确定本次请求的选项和后端名称
+    fn route<'a>(
+        &self,
+        translator_name: &str,
+        options: &'a TranslateOptions,
+    ) -> (Cow<'a, TranslateOptions>, Option<&'static str>) {
+        let Some(split) = self.traffic_splits.get(translator_name) else {
+            return (Cow::Borrowed(options), None);
+        };
+        if !split.pick_canary() {
+            return (Cow::Borrowed(options), Some(PRIMARY_BACKEND));
+        }
+        // 调用方显式设置的选项优先于切分配置
+        let overrides = TranslateOptions::default().provider(split.overrides.clone());
+        (Cow::Owned(options.or(&overrides)), Some(CANARY_BACKEND))
+    }
+
+    /// 取出调用方设置的 `on_failure`，翻译器只返回错误，由管理器在最后按策略处理，
+    /// 使没有实现该选项的翻译器同样生效
+    fn take_failure_policy(
+        options: Cow<'_, TranslateOptions>,
+    ) -> (Cow<'_, TranslateOptions>, Option<FailurePolicy>) {
+        match options.on_failure {
+            Some(policy) => (
+                Cow::Owned(options.into_owned().on_failure(FailurePolicy::Error)),
+                Some(policy),
+            ),
+            None => (options, None),
+        }
+    }
+
+    /// 按 `unchanged_comparison` 判断译文是否与原文相同
+    ///
+    /// 相同且启用了 `error_on_unchanged` 时返回 [`TranslationError::NotTranslated`]
+    fn result_kind(
+        translator_name: &str,
+        text: &str,
+        translated: &str,
+        options: &TranslateOptions,
+    ) -> Result<TranslationKind, TranslationError> {
+        if !guard::is_unchanged(text, translated, &options.get_unchanged_comparison()) {
+            return Ok(TranslationKind::Translated);
+        }
+        if options.get_error_on_unchanged() {
+            return Err(TranslationError::NotTranslated {
+                translator: translator_name.to_string(),
+            });
+        }
+        Ok(TranslationKind::Unchanged)
     }
 
     /// 使用指定的翻译器翻译文本（带配置选项）
@@ -53,16 +1296,385 @@ impl TranslationManager {
         source_lang: Option<&LanguageIdentifier>,
         options: &TranslateOptions,
     ) -> Result<String, TranslationError> {
-        let translator = self.translators.get(translator_name).ok_or_else(|| {
-            TranslationError::ConfigurationError(format!(
-                "Translator '{}' not found",
-                translator_name
-            ))
-        })?;
+        let _guard = self.enter()?;
+        let translator_name = &*self.canonical_name(translator_name);
+        let translator = self.get_translator(translator_name)?;
+        self.ensure_available(translator_name)?;
+        let redacted = self.redact(text)?;
+        let (options, _) = self.route(translator_name, options);
+        let (options, policy) = Self::take_failure_policy(self.start_deadline(options));
+        let cached = self.cache.as_ref().and_then(|cache| {
+            let inputs = translator.cache_key_inputs(target_lang, source_lang, &options)?;
+            let key = CacheKey::compute(
+                self.cache_namespace.as_deref(),
+                &inputs,
+                text,
+                target_lang,
+                source_lang,
+            );
+            Some((cache, inputs, key))
+        });
+        if let Some(translation) = cached.as_ref().and_then(|(cache, _, key)| cache.get(key)) {
+            return Ok(translation);
+        }
+        // 等待全局许可超时同样按 `on_failure` 处理
+        let permit = self.acquire_global(&options).await;
+        let start = tokio::time::Instant::now();
+        let result = match permit {
+            Ok(_permit) => translator
+                .translate_with_options(
+                    redacted.as_ref().map_or(text, Protected::text),
+                    target_lang,
+                    source_lang,
+                    &options,
+                )
+                .await
+                .map(|translated| self.post_process(translator_name, translated, target_lang))
+                .and_then(|translated| match &redacted {
+                    Some(redacted) => policy::restore(redacted, &translated),
+                    None => Ok(translated),
+                })
+                .and_then(|translated| {
+                    Self::result_kind(translator_name, text, &translated, &options)?;
+                    Ok(translated)
+                }),
+            Err(error) => Err(error),
+        };
+        if let (Ok(translation), Some((cache, inputs, key))) = (&result, cached) {
+            cache.insert(CacheEntry {
+                key,
+                translator: translator_name.to_string(),
+                namespace: self.cache_namespace.clone(),
+                inputs,
+                target_lang: target_lang.to_string(),
+                source_lang: source_lang.map(|lang| lang.to_string()),
+                translation: translation.clone(),
+            });
+        }
+        self.record(
+            translator_name,
+            text,
+            target_lang,
+            source_lang,
+            result.as_deref().ok(),
+            start.elapsed(),
+        );
+        match result {
+            Err(error) if policy.is_some_and(|policy| policy.returns_original(&error)) => {
+                Ok(text.to_string())
+            }
+            result => result,
+        }
+    }
+
+    /// 使用指定的翻译器翻译文本并返回详细结果（带配置选项）
+    ///
+    /// # 参数
+    ///
+    /// * `translator_name` - 翻译器名称
+    /// * `text` - 需要翻译的文本
+    /// * `target_lang` - 目标语言标识符
+    /// * `source_lang` - 源语言标识符 (None表示自动检测)
+    /// * `options` - 翻译配置选项
+    ///
+    /// # 返回值
+    ///
+    /// 返回带详细信息的翻译结果，如果出错则返回错误信息
+    pub async fn translate_detailed_with_options(
+        &self,
+        translator_name: &str,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<TranslationDetail, TranslationError> {
+        let _guard = self.enter()?;
+        let translator_name = &*self.canonical_name(translator_name);
+        let translator = self.get_translator(translator_name)?;
+        self.ensure_available(translator_name)?;
+        let redacted = self.redact(text)?;
+        let (options, backend) = self.route(translator_name, options);
+        let (options, policy) = Self::take_failure_policy(self.start_deadline(options));
+        let permit = self.acquire_global(&options).await;
+        let start = tokio::time::Instant::now();
+        let result = match permit {
+            Ok(_permit) => translator
+                .translate_detailed_with_options(
+                    redacted.as_ref().map_or(text, Protected::text),
+                    target_lang,
+                    source_lang,
+                    &options,
+                )
+                .await
+                .map(|mut detail| {
+                    // 原样返回的原文不是译文，不做后处理
+                    if !detail.passthrough {
+                        detail.text = self.post_process(translator_name, detail.text, target_lang);
+                    }
+                    detail
+                })
+                .and_then(|mut detail| {
+                    if let Some(redacted) = &redacted {
+                        // 原样返回的原文和中断后的部分译文不要求包含所有占位符
+                        detail.text = if detail.passthrough || detail.partial {
+                            redacted.restore_partial(&detail.text)
+                        } else {
+                            policy::restore(redacted, &detail.text)?
+                        };
+                    }
+                    if !detail.passthrough {
+                        detail.result_kind =
+                            Self::result_kind(translator_name, text, &detail.text, &options)?;
+                    }
+                    Ok(detail)
+                }),
+            Err(error) => Err(error),
+        };
+        // 原样返回的原文按失败统计，也不会被采样
+        self.record(
+            translator_name,
+            text,
+            target_lang,
+            source_lang,
+            result
+                .as_ref()
+                .ok()
+                .filter(|detail| !detail.passthrough)
+                .map(|detail| detail.text.as_str()),
+            start.elapsed(),
+        );
+        let mut detail = match result {
+            Err(error) if policy.is_some_and(|policy| policy.returns_original(&error)) => {
+                TranslationDetail::pass_through(text, &error)
+            }
+            result => result?,
+        };
+        if let Some(backend) = backend {
+            if !detail.provider_metadata.is_object() {
+                detail.provider_metadata = serde_json::json!({});
+            }
+            detail.provider_metadata["backend"] = backend.into();
+        }
+        Ok(detail)
+    }
+
+    /// 在多个候选翻译器中选择当前表现最好的一个翻译文本
+    ///
+    /// 管理器为每个候选维护成功耗时和失败率的移动平均（见 [`TranslationManager::adaptive_stats`]），
+    /// 尚无统计的候选优先使用；之后按 [`TranslationManager::with_adaptive_routing`] 设置的概率
+    /// 随机选择候选，其余时候选择平均耗时除以成功率最小的候选。别名按其指向的翻译器统计。
+    /// 处于维护窗口的候选不参与选择，所有候选都在维护时返回最早恢复的候选的
+    /// [`TranslationError::TranslatorUnavailable`]。
+    /// 调用方设置的 `on_failure` 在更新统计之后处理，原样返回的原文按失败统计。
+    ///
+    /// # 参数
+    ///
+    /// * `candidates` - 候选翻译器名称
+    /// * `text` - 需要翻译的文本
+    /// * `target_lang` - 目标语言标识符
+    /// * `source_lang` - 源语言标识符 (None表示自动检测)
+    /// * `options` - 翻译配置选项
+    ///
+    /// # 返回值
+    ///
+    /// 返回翻译后的文本，没有候选时返回 [`TranslationError::ConfigurationError`]
+    pub async fn translate_adaptive(
+        &self,
+        candidates: &[&str],
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<String, TranslationError> {
+        if candidates.is_empty() {
+            return Err(TranslationError::ConfigurationError(
+                "No candidate translators".to_string(),
+            ));
+        }
+        if self.is_shutting_down() {
+            return Err(TranslationError::ShuttingDown);
+        }
+        let candidates: Vec<Cow<str>> = candidates
+            .iter()
+            .map(|name| self.canonical_name(name))
+            .collect();
+        let candidates: Vec<&str> = candidates.iter().map(AsRef::as_ref).collect();
+        let candidates = self.available_candidates(&candidates)?;
+        let translator_name = self.adaptive.pick(&candidates);
+        let (options, policy) =
+            Self::take_failure_policy(self.start_deadline(Cow::Borrowed(options)));
+        let start = tokio::time::Instant::now();
+        let result = self
+            .translate_with_options(translator_name, text, target_lang, source_lang, &options)
+            .await;
+        self.adaptive
+            .update(translator_name, result.is_ok().then(|| start.elapsed()));
+        match result {
+            Err(error) if policy.is_some_and(|policy| policy.returns_original(&error)) => {
+                Ok(text.to_string())
+            }
+            result => result,
+        }
+    }
+
+    /// 按顺序尝试多个翻译器，返回第一个成功的译文
+    ///
+    /// 处于维护窗口的翻译器直接跳过，不计为失败。每个翻译器按
+    /// [`TranslationManager::translate_with_options`] 调用；调用方设置的 `on_failure`
+    /// 只在所有翻译器都失败后处理。
+    ///
+    /// # 参数
+    ///
+    /// * `chain` - 按优先级排列的翻译器名称
+    /// * `text` - 需要翻译的文本
+    /// * `target_lang` - 目标语言标识符
+    /// * `source_lang` - 源语言标识符 (None表示自动检测)
+    /// * `options` - 翻译配置选项
+    ///
+    /// # 返回值
+    ///
+    /// 返回第一个成功的译文；都失败时返回最后一个翻译器的错误，
+    /// 都在维护时返回最早恢复的翻译器的 [`TranslationError::TranslatorUnavailable`]
+    pub async fn translate_with_fallback(
+        &self,
+        chain: &[&str],
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<String, TranslationError> {
+        if chain.is_empty() {
+            return Err(TranslationError::ConfigurationError(
+                "No fallback translators".to_string(),
+            ));
+        }
+        if self.is_shutting_down() {
+            return Err(TranslationError::ShuttingDown);
+        }
+        // 所有翻译器共用同一个总时限
+        let (options, policy) =
+            Self::take_failure_policy(self.start_deadline(Cow::Borrowed(options)));
+        let mut last_error = None;
+        for translator_name in self.available_candidates(chain)? {
+            match self
+                .translate_with_options(translator_name, text, target_lang, source_lang, &options)
+                .await
+            {
+                Ok(translated) => return Ok(translated),
+                // 总时限已到，不再尝试后面的翻译器
+                Err(error @ TranslationError::DeadlineExceeded { .. }) => {
+                    last_error = Some(error);
+                    break;
+                }
+                Err(error) => last_error = Some(error),
+            }
+        }
+        match last_error {
+            Some(error) if policy.is_some_and(|policy| policy.returns_original(&error)) => {
+                Ok(text.to_string())
+            }
+            Some(error) => Err(error),
+            None => unreachable!("available_candidates returns at least one candidate"),
+        }
+    }
+
+    /// 去掉处于维护窗口的候选，保持原有顺序
+    ///
+    /// 所有候选都在维护时返回最早恢复的候选的 [`TranslationError::TranslatorUnavailable`]
+    fn available_candidates<'a>(
+        &self,
+        candidates: &[&'a str],
+    ) -> Result<Vec<&'a str>, TranslationError> {
+        let mut earliest: Option<(&str, SystemTime)> = None;
+        let mut available = Vec::with_capacity(candidates.len());
+        for &candidate in candidates {
+            match self.unavailable_until(candidate) {
+                Some(until) => {
+                    if earliest.is_none_or(|(_, earliest)| until < earliest) {
+                        earliest = Some((candidate, until));
+                    }
+                }
+                None => available.push(candidate),
+            }
+        }
+        match earliest {
+            Some((translator, until)) if available.is_empty() => {
+                Err(TranslationError::TranslatorUnavailable {
+                    translator: translator.to_string(),
+                    until,
+                })
+            }
+            _ => Ok(available),
+        }
+    }
+
+    /// 使用指定的翻译器批量翻译带标签的文本，每个结果附带对应输入的标签
+    ///
+    /// 每条文本按 [`TranslationManager::translate_with_options`] 单独翻译（包括后处理和统计），
+    /// 同时进行的翻译数不超过 [`TAGGED_BATCH_CONCURRENCY`]。标签始终和对应的文本一起传递，
+    /// 不依赖位置；设置 `dedup` 时相同的文本只翻译一次。结果按输入顺序排列
+    ///
+    /// # 参数
+    ///
+    /// * `translator_name` - 翻译器名称
+    /// * `items` - 标签和需要翻译的文本
+    /// * `target_lang` - 目标语言标识符
+    /// * `source_lang` - 源语言标识符 (None表示自动检测)
+    /// * `options` - 翻译配置选项
+    ///
+    /// # 返回值
+    ///
+    /// 返回每个标签及其翻译结果
+    pub async fn translate_batch_tagged<T: Send>(
+        &self,
+        translator_name: &str,
+        items: Vec<(T, String)>,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Vec<(T, Result<String, TranslationError>)> {
+        let options = &*self.start_deadline(Cow::Borrowed(options));
+        batch::translate_tagged(
+            items,
+            options.get_dedup(),
+            TAGGED_BATCH_CONCURRENCY,
+            batch::CharBudget::new(
+                options.get_max_batch_chars(),
+                options.get_truncate_to_budget(),
+            ),
+            |text| async move {
+                self.start_jitter
+                    .wait(options.get_start_jitter(), self.clock.as_ref())
+                    .await;
+                self.translate_with_options(
+                    translator_name,
+                    &text,
+                    target_lang,
+                    source_lang,
+                    options,
+                )
+                .await
+            },
+        )
+        .await
+    }
 
-        translator
-            .translate_with_options(text, target_lang, source_lang, options)
-            .await
+    /// 按名称查找翻译器
+    fn get_translator(
+        &self,
+        translator_name: &str,
+    ) -> Result<Arc<dyn Translator>, TranslationError> {
+        self.translators
+            .read()
+            .unwrap()
+            .get(translator_name)
+            .map(|registration| registration.translator.clone())
+            .ok_or_else(|| {
+                TranslationError::ConfigurationError(format!(
+                    "Translator '{}' not found",
+                    translator_name
+                ))
+            })
     }
 
     /// 使用指定的翻译器翻译文本（使用默认选项）
@@ -102,18 +1714,50 @@ impl TranslationManager {
     ///
     /// # 返回值
     ///
-    /// 如果翻译器存在返回true，否则返回false
+    /// 如果翻译器或指向已注册翻译器的别名存在返回true，否则返回false
     pub fn has_translator(&self, translator_name: &str) -> bool {
-        self.translators.contains_key(translator_name)
+        self.resolve(translator_name).is_some()
+    }
+
+    /// 以具体类型取得已注册的翻译器，用于调用翻译器特有的方法
+    ///
+    /// 这是绕过统一接口的后门：例如通过管理器调用微软翻译器的 `translate_batch`
+    /// 获取检测结果。经过这个引用的调用不会应用管理器的后处理、统计和关闭检查。
+    /// 翻译器需要实现 [`Translator::as_any`]，内置的翻译器都已实现。
+    ///
+    /// # 参数
+    ///
+    /// * `translator_name` - 翻译器名称或别名
+    ///
+    /// # 返回值
+    ///
+    /// 翻译器不存在、类型不是 `T` 或不支持向下转型时返回 None
+    pub fn get_translator_as<T: Translator + 'static>(
+        &self,
+        translator_name: &str,
+    ) -> Option<TranslatorRef<T>> {
+        let translator = self
+            .get_translator(&self.canonical_name(translator_name))
+            .ok()?;
+        translator.as_any().is::<T>().then_some(TranslatorRef {
+            translator,
+            _type: PhantomData,
+        })
     }
 
     /// 获取所有翻译器名称
     ///
     /// # 返回值
     ///
-    /// 返回所有翻译器名称的向量
+    /// 返回所有翻译器名称的向量，不包括别名（见 [`TranslationManager::list_aliases`]）
     pub fn list_translators(&self) -> Vec<String> {
-        self.translators.keys().cloned().collect()
+        self.translators.read().unwrap().keys().cloned().collect()
+    }
+}
+
+impl Default for TranslationManager {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -1,10 +1,82 @@
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "microsoft")]
+    use crate::microsoft::MicrosoftConfig;
+    #[cfg(feature = "openai")]
+    use crate::openai::{OpenAIConfig, OpenAITranslator};
+    use crate::testing::MockTranslator;
+    #[cfg(feature = "openai")]
+    use crate::testing::{DelayedResponse, DelayedServer};
     use crate::{
-        error::TranslationError, manager::TranslationManager, options::TranslateOptions,
-        translator::Translator,
+        error::TranslationError,
+        manager::{
+            CANARY_BACKEND, LanguagePair, ManagerConfig, PRIMARY_BACKEND, SamplingHook,
+            TranslationManager, TranslationRecord,
+        },
+        options::{FailurePolicy, ProviderOptions, TranslateOptions, UnchangedComparison},
+        postprocess::{CjkPunctuationNormalizer, PostProcessor},
+        translator::{TranslationDetail, TranslationKind, Translator},
     };
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
     use unic_langid::LanguageIdentifier;
+    #[cfg(feature = "openai")]
+    use wiremock::matchers::{method, path};
+    #[cfg(feature = "openai")]
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// 返回本次请求使用的 base URL 的模拟翻译器
+    struct BaseUrlTranslator;
+
+    #[async_trait::async_trait]
+    impl Translator for BaseUrlTranslator {
+        async fn translate_with_options(
+            &self,
+            _text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            Ok(options
+                .get_base_url()
+                .unwrap_or_else(|| "default".to_string()))
+        }
+    }
+
+    /// 把原文转为小写返回的模拟翻译器
+    struct LowercaseTranslator;
+
+    #[async_trait::async_trait]
+    impl Translator for LowercaseTranslator {
+        async fn translate_with_options(
+            &self,
+            text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            Ok(text.to_lowercase())
+        }
+    }
+
+    fn split_manager(percentage: f64, seed: u64) -> TranslationManager {
+        let mut manager = TranslationManager::new();
+        manager.add_translator("echo", Box::new(BaseUrlTranslator));
+        let manager = manager.with_traffic_split(
+            "echo",
+            ProviderOptions {
+                base_url: Some("http://canary".to_string()),
+                ..Default::default()
+            },
+            percentage,
+        );
+        // 固定随机数种子，保证结果可重复
+        *manager.traffic_splits["echo"].rng.lock().unwrap() = StdRng::seed_from_u64(seed);
+        manager
+    }
 
     #[tokio::test]
     async fn test_translation_manager_creation() {
@@ -76,4 +148,1752 @@ mod tests {
             .await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_translation_manager_translate_detailed_default_impl() {
+        let mut manager = TranslationManager::new();
+
+        // 只实现了 translate_with_options 的模拟翻译器，使用默认的详细结果实现
+        struct MockTranslator;
+        #[async_trait::async_trait]
+        impl Translator for MockTranslator {
+            async fn translate_with_options(
+                &self,
+                text: &str,
+                _target_lang: &LanguageIdentifier,
+                _source_lang: Option<&LanguageIdentifier>,
+                _options: &TranslateOptions,
+            ) -> Result<String, TranslationError> {
+                Ok(format!("Translated: {}", text))
+            }
+        }
+
+        manager.add_translator("mock", Box::new(MockTranslator));
+
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = manager
+            .translate_detailed_with_options(
+                "mock",
+                "test",
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "Translated: test");
+        assert_eq!(detail.detected_source, None);
+        assert!(detail.provider_metadata.is_null());
+
+        let result = manager
+            .translate_detailed_with_options(
+                "nonexistent",
+                "test",
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(TranslationError::ConfigurationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_translation_manager_translate_detailed_override() {
+        let mut manager = TranslationManager::new();
+
+        // 覆盖了详细结果方法的模拟翻译器
+        struct DetectingTranslator;
+        #[async_trait::async_trait]
+        impl Translator for DetectingTranslator {
+            async fn translate_with_options(
+                &self,
+                text: &str,
+                _target_lang: &LanguageIdentifier,
+                _source_lang: Option<&LanguageIdentifier>,
+                _options: &TranslateOptions,
+            ) -> Result<String, TranslationError> {
+                Ok(text.to_string())
+            }
+
+            async fn translate_detailed_with_options(
+                &self,
+                text: &str,
+                _target_lang: &LanguageIdentifier,
+                _source_lang: Option<&LanguageIdentifier>,
+                _options: &TranslateOptions,
+            ) -> Result<TranslationDetail, TranslationError> {
+                Ok(TranslationDetail {
+                    text: text.to_string(),
+                    detected_source: Some(("fr".parse().unwrap(), 0.75)),
+                    provider_metadata: serde_json::json!({"provider": "detecting"}),
+                    partial: false,
+                    suspicious: false,
+                    raw: None,
+                    passthrough: false,
+                    provenance: None,
+                    result_kind: TranslationKind::Translated,
+                })
+            }
+        }
+
+        manager.add_translator("detecting", Box::new(DetectingTranslator));
+
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = manager
+            .translate_detailed_with_options(
+                "detecting",
+                "bonjour",
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "bonjour");
+        assert_eq!(detail.detected_source, Some(("fr".parse().unwrap(), 0.75)));
+        assert_eq!(detail.provider_metadata["provider"], "detecting");
+    }
+
+    #[tokio::test]
+    async fn test_traffic_split_ratio() {
+        let manager = split_manager(25.0, 42);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+
+        let mut canary = 0;
+        for _ in 0..1000 {
+            let detail = manager
+                .translate_detailed_with_options("echo", "test", &target_lang, None, &options)
+                .await
+                .unwrap();
+            let backend = detail.provider_metadata["backend"].as_str().unwrap();
+            if detail.text == "http://canary" {
+                assert_eq!(backend, CANARY_BACKEND);
+                canary += 1;
+            } else {
+                assert_eq!(detail.text, "default");
+                assert_eq!(backend, PRIMARY_BACKEND);
+            }
+        }
+        assert!((200..=300).contains(&canary), "canary requests: {}", canary);
+    }
+
+    #[tokio::test]
+    async fn test_traffic_split_bounds_and_explicit_override() {
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+
+        let never = split_manager(0.0, 1);
+        let always = split_manager(150.0, 1);
+        for _ in 0..100 {
+            assert_eq!(
+                never
+                    .translate("echo", "test", &target_lang, None)
+                    .await
+                    .unwrap(),
+                "default"
+            );
+            assert_eq!(
+                always
+                    .translate_with_options("echo", "test", &target_lang, None, &options)
+                    .await
+                    .unwrap(),
+                "http://canary"
+            );
+        }
+
+        // 调用方显式设置的覆盖优先于流量切分
+        let explicit = options.clone().base_url("http://explicit");
+        assert_eq!(
+            always
+                .translate_with_options("echo", "test", &target_lang, None, &explicit)
+                .await
+                .unwrap(),
+            "http://explicit"
+        );
+    }
+
+    #[cfg(feature = "openai")]
+    #[tokio::test]
+    async fn test_traffic_split_reaches_mock_servers() {
+        let primary = MockServer::start().await;
+        let canary = MockServer::start().await;
+        for (server, content) in [(&primary, "primary"), (&canary, "canary")] {
+            Mock::given(method("POST"))
+                .and(path("/chat/completions"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "choices": [{"message": {"role": "assistant", "content": content}}]
+                })))
+                .mount(server)
+                .await;
+        }
+
+        let mut manager = TranslationManager::new();
+        manager.add_translator(
+            "openai",
+            Box::new(OpenAITranslator::new(
+                OpenAIConfig::builder()
+                    .base_url(primary.uri())
+                    .api_keys(vec!["test-key"])
+                    .rpm_limit(0)
+                    .build(),
+            )),
+        );
+        let manager = manager.with_traffic_split(
+            "openai",
+            ProviderOptions {
+                base_url: Some(canary.uri()),
+                ..Default::default()
+            },
+            50.0,
+        );
+        *manager.traffic_splits["openai"].rng.lock().unwrap() = StdRng::seed_from_u64(7);
+
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().no_timeout();
+        let mut canary_count = 0;
+        for _ in 0..40 {
+            let detail = manager
+                .translate_detailed_with_options("openai", "Hello", &target_lang, None, &options)
+                .await
+                .unwrap();
+            // 译文来自实际处理请求的服务器，与记录的后端一致
+            assert_eq!(detail.provider_metadata["backend"], detail.text);
+            if detail.text == "canary" {
+                canary_count += 1;
+            }
+        }
+        assert!(canary_count > 0 && canary_count < 40);
+        assert_eq!(
+            canary.received_requests().await.unwrap().len(),
+            canary_count
+        );
+        assert_eq!(
+            primary.received_requests().await.unwrap().len(),
+            40 - canary_count
+        );
+    }
+
+    fn slow_manager(latency: Duration) -> TranslationManager {
+        let mut manager = TranslationManager::new();
+        manager.add_translator("slow", Box::new(MockTranslator::new().latency(latency)));
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_rejects_new_calls() {
+        let manager = slow_manager(Duration::ZERO);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        assert!(!manager.is_shutting_down());
+        assert!(
+            manager
+                .translate("slow", "a", &target_lang, None)
+                .await
+                .is_ok()
+        );
+
+        manager.begin_shutdown();
+        assert!(manager.is_shutting_down());
+        assert!(matches!(
+            manager.translate("slow", "a", &target_lang, None).await,
+            Err(TranslationError::ShuttingDown)
+        ));
+        assert!(matches!(
+            manager
+                .translate_detailed_with_options(
+                    "slow",
+                    "a",
+                    &target_lang,
+                    None,
+                    &TranslateOptions::default()
+                )
+                .await,
+            Err(TranslationError::ShuttingDown)
+        ));
+        // 被拒绝的调用不会留下计数
+        assert_eq!(manager.in_flight(), 0);
+        assert_eq!(manager.drain(Duration::from_secs(1)).await, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_waits_for_in_flight() {
+        let manager = slow_manager(Duration::from_secs(2));
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let started = tokio::time::Instant::now();
+
+        let in_flight = manager.translate("slow", "a", &target_lang, None);
+        let shutdown = async {
+            while manager.in_flight() == 0 {
+                tokio::task::yield_now().await;
+            }
+            manager.begin_shutdown();
+            assert!(matches!(
+                manager.translate("slow", "b", &target_lang, None).await,
+                Err(TranslationError::ShuttingDown)
+            ));
+            manager.drain(Duration::from_secs(10)).await
+        };
+        let (result, outstanding) = tokio::join!(in_flight, shutdown);
+
+        assert_eq!(result.unwrap(), "Translated: a");
+        assert_eq!(outstanding, 0);
+        assert_eq!(started.elapsed(), Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_timeout_reports_outstanding() {
+        let manager = slow_manager(Duration::from_secs(10));
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let first = manager.translate("slow", "a", &target_lang, None);
+        let second = manager.translate("slow", "b", &target_lang, None);
+        let shutdown = async {
+            while manager.in_flight() < 2 {
+                tokio::task::yield_now().await;
+            }
+            manager.begin_shutdown();
+            let started = tokio::time::Instant::now();
+            let outstanding = manager.drain(Duration::from_secs(1)).await;
+            assert_eq!(started.elapsed(), Duration::from_secs(1));
+            outstanding
+        };
+        let (first, second, outstanding) = tokio::join!(first, second, shutdown);
+
+        assert_eq!(outstanding, 2);
+        // 超时后正在进行的翻译仍会正常完成
+        assert!(first.is_ok() && second.is_ok());
+        assert_eq!(manager.in_flight(), 0);
+    }
+
+    /// 记录同时进行的翻译数的翻译器，多个实例可以共用同一组计数
+    #[derive(Clone, Default)]
+    struct GaugeTranslator {
+        current: Arc<std::sync::atomic::AtomicUsize>,
+        max: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Translator for GaugeTranslator {
+        async fn translate_with_options(
+            &self,
+            text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            use std::sync::atomic::Ordering;
+            let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max.fetch_max(current, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            self.current.fetch_sub(1, Ordering::SeqCst);
+            Ok(text.to_string())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_global_concurrency_bounds_all_translators() {
+        let gauge = GaugeTranslator::default();
+        let mut manager = TranslationManager::new().with_global_concurrency(3);
+        manager.add_translator("a", Box::new(gauge.clone()));
+        manager.add_translator("b", Box::new(gauge.clone()));
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let started = tokio::time::Instant::now();
+
+        let requests = (0..10).map(|i| {
+            let name = if i % 2 == 0 { "a" } else { "b" };
+            let options = TranslateOptions::default();
+            let manager = &manager;
+            let target_lang = &target_lang;
+            async move {
+                if i % 3 == 0 {
+                    manager
+                        .translate_detailed_with_options(name, "x", target_lang, None, &options)
+                        .await
+                        .map(|detail| detail.text)
+                } else {
+                    manager.translate(name, "x", target_lang, None).await
+                }
+            }
+        });
+        let watch = async {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            (manager.global_in_flight(), manager.in_flight())
+        };
+        let (results, (global_in_flight, in_flight)) =
+            tokio::join!(futures::future::join_all(requests), watch);
+
+        assert!(results.iter().all(|result| result.is_ok()));
+        // 两个翻译器合计同时进行的翻译数不超过全局限制，等待许可的翻译也计入 in_flight
+        assert_eq!(gauge.max.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert_eq!((global_in_flight, in_flight), (3, 10));
+        assert_eq!(started.elapsed(), Duration::from_secs(4));
+        assert_eq!(manager.global_in_flight(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_global_concurrency_queue_wait() {
+        let mut manager = TranslationManager::new().with_global_concurrency(1);
+        manager.add_translator("a", Box::new(GaugeTranslator::default()));
+        manager.add_translator("b", Box::new(GaugeTranslator::default()));
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let options = TranslateOptions::default();
+        let first = manager.translate_adaptive(&["a", "b"], "first", &target_lang, None, &options);
+        let second = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            let wait = TranslateOptions::default().max_queue_wait(Duration::from_millis(100));
+            let overloaded = manager
+                .translate_with_options("b", "second", &target_lang, None, &wait)
+                .await;
+            let original = manager
+                .translate_with_options(
+                    "b",
+                    "third",
+                    &target_lang,
+                    None,
+                    &wait.on_failure(FailurePolicy::ReturnOriginal),
+                )
+                .await;
+            (overloaded, original)
+        };
+        let (first, (overloaded, original)) = tokio::join!(first, second);
+
+        // 自适应选择只占用所选候选的一个许可，不会因为嵌套调用而死锁
+        assert_eq!(first.unwrap(), "first");
+        assert!(matches!(
+            overloaded,
+            Err(TranslationError::Overloaded { .. })
+        ));
+        assert_eq!(original.unwrap(), "third");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_global_concurrency_respects_deadline() {
+        let mut manager = TranslationManager::new().with_global_concurrency(1);
+        manager.add_translator("a", Box::new(GaugeTranslator::default()));
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let first = manager.translate("a", "first", &target_lang, None);
+        let second = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            // 排队等待的上限比总时限长，总时限先到期
+            let options = TranslateOptions::default()
+                .max_queue_wait(Duration::from_secs(5))
+                .deadline(Duration::from_millis(200));
+            let start = tokio::time::Instant::now();
+            let result = manager
+                .translate_with_options("a", "second", &target_lang, None, &options)
+                .await;
+            (result, start.elapsed())
+        };
+        let (first, (second, waited)) = tokio::join!(first, second);
+
+        assert_eq!(first.unwrap(), "first");
+        match second {
+            Err(TranslationError::DeadlineExceeded { budget }) => {
+                assert_eq!(budget, Duration::from_millis(200));
+            }
+            other => panic!("Expected DeadlineExceeded, got {:?}", other),
+        }
+        assert_eq!(waited, Duration::from_millis(200));
+    }
+
+    /// 记录收到的总时限剩余时间的翻译器
+    #[derive(Clone, Default)]
+    struct DeadlineRecorder {
+        remaining: Arc<Mutex<Option<Duration>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Translator for DeadlineRecorder {
+        async fn translate_with_options(
+            &self,
+            text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            let now = tokio::time::Instant::now().into_std();
+            *self.remaining.lock().unwrap() = options
+                .get_deadline()
+                .map(|deadline| deadline.remaining(now));
+            Ok(text.to_string())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fallback_shares_deadline() {
+        let failing = ScriptedTranslator::new("failing", 600);
+        failing
+            .failing
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+        let recorder = DeadlineRecorder::default();
+        let mut manager = TranslationManager::new().with_global_concurrency(1);
+        manager.add_translator("failing", Box::new(failing));
+        manager.add_translator("recorder", Box::new(recorder.clone()));
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().deadline(Duration::from_secs(1));
+
+        // 第二个翻译器只剩下第一个翻译器用掉之后的时间
+        let result = manager
+            .translate_with_fallback(&["failing", "recorder"], "hi", &target_lang, None, &options)
+            .await;
+        assert_eq!(result.unwrap(), "hi");
+        assert_eq!(
+            *recorder.remaining.lock().unwrap(),
+            Some(Duration::from_millis(400))
+        );
+    }
+
+    #[cfg(feature = "openai")]
+    #[tokio::test]
+    async fn test_deadline_follows_manager_clock() {
+        let server = completion_server("你好").await;
+        let clock = crate::testing::MockClock::new();
+        // 模拟时钟与真实时间相差一小时，总时限只按模拟时钟计时
+        clock.advance(Duration::from_secs(3600));
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(1)
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        let mut manager = TranslationManager::new()
+            .with_clock(Arc::new(clock.clone()))
+            .with_global_concurrency(1);
+        manager.add_translator("openai", Box::new(translator));
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        // 用完 RPM 额度
+        manager
+            .translate("openai", "Hello", &target_lang, None)
+            .await
+            .unwrap();
+
+        let options = TranslateOptions::default().deadline(Duration::from_secs(90));
+        let queued_options = TranslateOptions::default().deadline(Duration::from_secs(10));
+        let queued = async {
+            // 第一个请求持有全局许可并等待 RPM 额度，第二个请求按模拟时钟等待全局许可
+            while clock.sleeps().is_empty() {
+                tokio::task::yield_now().await;
+            }
+            let rate_limit_wait = clock.sleeps()[0];
+            let (result, ()) = tokio::join!(
+                manager.translate_with_options(
+                    "openai",
+                    "World",
+                    &target_lang,
+                    None,
+                    &queued_options
+                ),
+                async {
+                    while clock.sleeps().len() < 2 {
+                        tokio::task::yield_now().await;
+                    }
+                    assert_eq!(clock.sleeps()[1], Duration::from_secs(10));
+                    clock.advance(Duration::from_secs(10));
+                },
+            );
+            clock.advance(rate_limit_wait - Duration::from_secs(10));
+            result
+        };
+        let (result, queued_result) = tokio::join!(
+            manager.translate_with_options("openai", "Hello", &target_lang, None, &options),
+            queued,
+        );
+        assert_eq!(result.unwrap(), "你好");
+        assert!(matches!(
+            queued_result,
+            Err(TranslationError::DeadlineExceeded { budget }) if budget == Duration::from_secs(10)
+        ));
+        assert_eq!(requests(&server).await, 2);
+    }
+
+    #[cfg(feature = "openai")]
+    /// 启动固定返回 `content` 的 OpenAI 模拟服务器
+    async fn completion_server(content: &str) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{"message": {"role": "assistant", "content": content}}]
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[cfg(feature = "openai")]
+    fn openai_config(base_url: String) -> OpenAIConfig {
+        OpenAIConfig::builder()
+            .base_url(base_url)
+            .api_keys(vec!["test-key"])
+            .rpm_limit(0)
+            .build()
+    }
+
+    #[cfg(feature = "openai")]
+    async fn requests(server: &MockServer) -> usize {
+        server.received_requests().await.unwrap().len()
+    }
+
+    #[cfg(feature = "openai")]
+    #[tokio::test]
+    async fn test_apply_config_successive_reloads() {
+        let a1 = completion_server("a1").await;
+        let a2 = completion_server("a2").await;
+        let b = completion_server("b").await;
+        let c = completion_server("c").await;
+        let manager = TranslationManager::new();
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let report = manager
+            .apply_config(
+                ManagerConfig::new()
+                    .translator("a", openai_config(a1.uri()))
+                    .translator("b", openai_config(b.uri())),
+            )
+            .unwrap();
+        assert_eq!(report.added, vec!["a", "b"]);
+        assert!(report.removed.is_empty() && report.updated.is_empty());
+        assert!(report.failed.is_empty());
+        assert_eq!(
+            manager
+                .translate("a", "x", &target_lang, None)
+                .await
+                .unwrap(),
+            "a1"
+        );
+        assert_eq!(
+            manager
+                .translate("b", "x", &target_lang, None)
+                .await
+                .unwrap(),
+            "b"
+        );
+
+        // a 变更，b 移除，新增 c，d 未通过校验
+        let second = ManagerConfig::new()
+            .translator("a", openai_config(a2.uri()))
+            .translator("c", openai_config(c.uri()))
+            .translator("d", OpenAIConfig::builder().base_url(c.uri()).build());
+        let report = manager.apply_config(second.clone()).unwrap();
+        assert_eq!(report.added, vec!["c"]);
+        assert_eq!(report.removed, vec!["b"]);
+        assert_eq!(report.updated, vec!["a"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, "d");
+        assert!(matches!(
+            report.failed[0].1,
+            TranslationError::ConfigurationError(_)
+        ));
+
+        assert_eq!(
+            manager
+                .translate("a", "x", &target_lang, None)
+                .await
+                .unwrap(),
+            "a2"
+        );
+        assert_eq!(
+            manager
+                .translate("c", "x", &target_lang, None)
+                .await
+                .unwrap(),
+            "c"
+        );
+        assert!(!manager.has_translator("b"));
+        assert!(!manager.has_translator("d"));
+        assert_eq!(requests(&a1).await, 1);
+        assert_eq!(requests(&a2).await, 1);
+        assert_eq!(requests(&b).await, 1);
+
+        // 相同的配置不会重建任何翻译器
+        let report = manager.apply_config(second).unwrap();
+        assert!(report.added.is_empty() && report.removed.is_empty());
+        assert!(report.updated.is_empty());
+        assert_eq!(report.failed.len(), 1);
+    }
+
+    #[cfg(feature = "openai")]
+    #[tokio::test]
+    async fn test_apply_config_invalid_update_keeps_existing() {
+        let server = completion_server("ok").await;
+        let mut manager = TranslationManager::new();
+        manager.add_translator("manual", Box::new(MockTranslator::new()));
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        manager
+            .apply_config(ManagerConfig::new().translator("a", openai_config(server.uri())))
+            .unwrap();
+
+        let report = manager
+            .apply_config(
+                ManagerConfig::new().translator("a", openai_config("ftp://example.com".into())),
+            )
+            .unwrap();
+        assert!(report.updated.is_empty());
+        assert_eq!(report.failed[0].0, "a");
+        assert_eq!(
+            manager
+                .translate("a", "x", &target_lang, None)
+                .await
+                .unwrap(),
+            "ok"
+        );
+        // 手动添加的翻译器不受配置影响
+        assert!(manager.has_translator("manual"));
+
+        manager.begin_shutdown();
+        assert!(matches!(
+            manager.apply_config(ManagerConfig::new()),
+            Err(TranslationError::ShuttingDown)
+        ));
+    }
+
+    #[cfg(feature = "openai")]
+    #[tokio::test]
+    async fn test_apply_config_in_flight_uses_old_instance() {
+        let old = DelayedServer::start(
+            DelayedResponse::new(
+                serde_json::json!({
+                    "choices": [{"message": {"role": "assistant", "content": "old"}}]
+                })
+                .to_string(),
+            )
+            .header_delay(Duration::from_millis(200)),
+        )
+        .await;
+        let new = completion_server("new").await;
+        let manager = TranslationManager::new();
+        manager
+            .apply_config(ManagerConfig::new().translator("a", openai_config(old.uri())))
+            .unwrap();
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let in_flight = manager.translate("a", "x", &target_lang, None);
+        let reload = async {
+            while old.requests() == 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            manager
+                .apply_config(ManagerConfig::new().translator("a", openai_config(new.uri())))
+                .unwrap()
+        };
+        let (result, report) = tokio::join!(in_flight, reload);
+        assert_eq!(report.updated, vec!["a"]);
+        assert_eq!(result.unwrap(), "old");
+
+        assert_eq!(
+            manager
+                .translate("a", "x", &target_lang, None)
+                .await
+                .unwrap(),
+            "new"
+        );
+        assert_eq!(old.requests(), 1);
+        assert_eq!(requests(&new).await, 1);
+    }
+
+    // 以下测试按启用的 features 编译，用于检查各种 feature 组合下管理器都能正常工作：
+    // cargo test --no-default-features [--features openai|microsoft]
+
+    #[cfg(feature = "openai")]
+    #[test]
+    fn test_openai_feature_config() {
+        let config = openai_config("http://localhost".to_string());
+        assert!(matches!(
+            crate::TranslatorConfig::from(config.clone()),
+            crate::TranslatorConfig::OpenAI(_)
+        ));
+        let report = TranslationManager::new()
+            .apply_config(ManagerConfig::new().translator("openai", config))
+            .unwrap();
+        assert_eq!(report.added, vec!["openai"]);
+    }
+
+    #[cfg(feature = "microsoft")]
+    #[test]
+    fn test_microsoft_feature_config() {
+        let config = MicrosoftConfig::default();
+        assert!(matches!(
+            crate::TranslatorConfig::from(config.clone()),
+            crate::TranslatorConfig::Microsoft(_)
+        ));
+        let report = TranslationManager::new()
+            .apply_config(ManagerConfig::new().translator("microsoft", config))
+            .unwrap();
+        assert_eq!(report.added, vec!["microsoft"]);
+    }
+
+    #[cfg(not(any(feature = "openai", feature = "microsoft")))]
+    #[tokio::test]
+    async fn test_manager_without_providers() {
+        let mut manager = TranslationManager::new();
+        let report = manager.apply_config(ManagerConfig::new()).unwrap();
+        assert!(report.added.is_empty() && report.failed.is_empty());
+
+        manager.add_translator("mock", Box::new(MockTranslator::new()));
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        assert!(
+            manager
+                .translate("mock", "Hello", &target_lang, None)
+                .await
+                .is_ok()
+        );
+    }
+
+    /// 记录所有被抽中的翻译，可选地在每条记录上阻塞一段时间
+    #[derive(Clone, Default)]
+    struct CollectingHook {
+        records: Arc<Mutex<Vec<TranslationRecord>>>,
+        delay: Duration,
+    }
+
+    impl SamplingHook for CollectingHook {
+        fn maybe_sample(&self, record: &TranslationRecord) {
+            std::thread::sleep(self.delay);
+            self.records.lock().unwrap().push(record.clone());
+        }
+    }
+
+    impl CollectingHook {
+        async fn wait_for(&self, count: usize) -> Vec<TranslationRecord> {
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while self.records.lock().unwrap().len() < count {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            })
+            .await
+            .expect("hook did not receive the expected records");
+            self.records.lock().unwrap().clone()
+        }
+    }
+
+    /// 总是失败的翻译器
+    struct FailingTranslator;
+
+    #[async_trait::async_trait]
+    impl Translator for FailingTranslator {
+        async fn translate_with_options(
+            &self,
+            _text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            Err(TranslationError::ServiceError("unavailable".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pair_stats() {
+        let mut manager = TranslationManager::new();
+        manager.add_translator(
+            "mock",
+            Box::new(MockTranslator::new().latency(Duration::from_millis(10))),
+        );
+        manager.add_translator("failing", Box::new(FailingTranslator));
+        let en: LanguageIdentifier = "en".parse().unwrap();
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+
+        for _ in 0..3 {
+            manager
+                .translate("mock", "a", &zh, Some(&en))
+                .await
+                .unwrap();
+        }
+        manager.translate("mock", "a", &en, None).await.unwrap();
+        assert!(
+            manager
+                .translate("failing", "a", &zh, Some(&en))
+                .await
+                .is_err()
+        );
+
+        let stats = manager.pair_stats();
+        assert_eq!(stats.len(), 3);
+        let pair =
+            |provider: &str, source: Option<&LanguageIdentifier>, target: &LanguageIdentifier| {
+                LanguagePair {
+                    provider: provider.to_string(),
+                    source_lang: source.cloned(),
+                    target_lang: target.clone(),
+                }
+            };
+        let en_zh = stats[&pair("mock", Some(&en), &zh)];
+        assert_eq!(en_zh.successes, 3);
+        assert_eq!(en_zh.failures, 0);
+        assert!(en_zh.average_latency() >= Duration::from_millis(10));
+        assert_eq!(stats[&pair("mock", None, &en)].successes, 1);
+        let failing = stats[&pair("failing", Some(&en), &zh)];
+        assert_eq!((failing.successes, failing.failures), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_quota_counts_successful_translations() {
+        use crate::quota::{Quota, QuotaCallback};
+        use crate::stats::WindowPeriod;
+        use futures::FutureExt;
+
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let callback: QuotaCallback = Arc::new(move |event| {
+            let sender = sender.clone();
+            async move { sender.send(event).unwrap() }.boxed()
+        });
+        let mut manager = TranslationManager::new()
+            .with_quota("mock", Quota::new(10, WindowPeriod::Daily))
+            .on_quota_threshold(vec![(0.8, callback)]);
+        manager.add_translator("mock", Box::new(MockTranslator::new()));
+        manager.add_translator("failing", Box::new(FailingTranslator));
+        manager.add_alias("mock", "alias").unwrap();
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+
+        manager
+            .translate("mock", "你好世界", &zh, None)
+            .await
+            .unwrap();
+        manager.translate("alias", "abcd", &zh, None).await.unwrap();
+        assert!(
+            manager
+                .translate("failing", "abcd", &zh, None)
+                .await
+                .is_err()
+        );
+        assert_eq!(manager.quota("alias").unwrap().used(), 8);
+        assert!(manager.quota("failing").is_none());
+
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(
+            (event.provider.as_str(), event.used, event.limit),
+            ("mock", 8, 10)
+        );
+        manager.translate("mock", "ab", &zh, None).await.unwrap();
+        tokio::task::yield_now().await;
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sampling_rates_per_pair() {
+        let hook = CollectingHook::default();
+        let en: LanguageIdentifier = "en".parse().unwrap();
+        let de: LanguageIdentifier = "de".parse().unwrap();
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+        let fr: LanguageIdentifier = "fr".parse().unwrap();
+
+        let mut manager = TranslationManager::new();
+        manager.add_translator("mock", Box::new(MockTranslator::new().prefix("T:")));
+        let manager = manager
+            .with_sampling_hook(hook.clone(), 0.0)
+            .with_sampling_rate(Some(&en), &zh, 0.25)
+            .with_sampling_rate(None, &fr, 1.0);
+        *manager.sampler.as_ref().unwrap().rng.lock().unwrap() = StdRng::seed_from_u64(42);
+
+        for _ in 0..400 {
+            manager
+                .translate("mock", "hello", &zh, Some(&en))
+                .await
+                .unwrap();
+        }
+        for _ in 0..10 {
+            manager
+                .translate("mock", "hallo", &zh, Some(&de))
+                .await
+                .unwrap();
+            manager
+                .translate("mock", "bonjour", &fr, None)
+                .await
+                .unwrap();
+        }
+
+        // 使用同一种子重放随机数，得到 en→zh 应被抽中的次数
+        let mut rng = StdRng::seed_from_u64(42);
+        let expected_en_zh = (0..400).filter(|_| rng.random::<f64>() < 0.25).count();
+        assert!((60..140).contains(&expected_en_zh));
+
+        let records = hook.wait_for(expected_en_zh + 10).await;
+        let sampled = |source: Option<&LanguageIdentifier>, target: &LanguageIdentifier| {
+            records
+                .iter()
+                .filter(|r| r.source_lang.as_ref() == source && &r.target_lang == target)
+                .count()
+        };
+        assert_eq!(sampled(Some(&en), &zh), expected_en_zh);
+        assert_eq!(sampled(None, &fr), 10);
+        assert_eq!(sampled(Some(&de), &zh), 0);
+
+        let record = records.iter().find(|r| r.target_lang == fr).unwrap();
+        assert_eq!(record.provider, "mock");
+        assert_eq!(record.input, "bonjour");
+        assert_eq!(record.output, "T:bonjour");
+    }
+
+    #[tokio::test]
+    async fn test_slow_sampling_hook_does_not_delay_translation() {
+        let hook = CollectingHook {
+            delay: Duration::from_millis(500),
+            ..Default::default()
+        };
+        let mut manager = TranslationManager::new();
+        manager.add_translator("mock", Box::new(MockTranslator::new()));
+        let manager = manager.with_sampling_hook(hook.clone(), 1.0);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let start = std::time::Instant::now();
+        for _ in 0..3 {
+            manager
+                .translate("mock", "a", &target_lang, None)
+                .await
+                .unwrap();
+        }
+        assert!(
+            start.elapsed() < Duration::from_millis(250),
+            "{:?}",
+            start.elapsed()
+        );
+        assert_eq!(hook.wait_for(3).await.len(), 3);
+    }
+
+    /// 在译文末尾追加标记并统计调用次数的后处理器
+    #[derive(Default)]
+    struct AppendProcessor {
+        suffix: &'static str,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl PostProcessor for AppendProcessor {
+        fn process(&self, text: String, _target: &LanguageIdentifier) -> String {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            format!("{}{}", text, self.suffix)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_post_processors_applied_in_order() {
+        let global = Arc::new(AppendProcessor {
+            suffix: "|global",
+            ..Default::default()
+        });
+        let local = Arc::new(AppendProcessor {
+            suffix: "|local",
+            ..Default::default()
+        });
+        let mut manager = TranslationManager::new();
+        manager.add_translator("a", Box::new(MockTranslator::new().prefix("A:")));
+        manager.add_translator("b", Box::new(MockTranslator::new().prefix("B:")));
+        manager
+            .add_post_processor(global.clone())
+            .add_translator_post_processor("a", local.clone());
+
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+        let result = manager.translate("a", "hi", &zh, None).await.unwrap();
+        assert_eq!(result, "A:hi|local|global");
+        let detail = manager
+            .translate_detailed_with_options("a", "hi", &zh, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "A:hi|local|global");
+        let result = manager.translate("b", "hi", &zh, None).await.unwrap();
+        assert_eq!(result, "B:hi|global");
+
+        // 每次翻译只处理一次，失败的翻译不处理
+        assert!(manager.translate("missing", "hi", &zh, None).await.is_err());
+        assert_eq!(local.calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(global.calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_builtin_post_processor_respects_target() {
+        let mut manager = TranslationManager::new();
+        manager.add_translator("mock", Box::new(MockTranslator::new().prefix("你好")));
+        manager.add_post_processor(Arc::new(CjkPunctuationNormalizer));
+
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+        let result = manager
+            .translate("mock", ", 世界!", &zh, None)
+            .await
+            .unwrap();
+        assert_eq!(result, "你好，世界！");
+        let en: LanguageIdentifier = "en".parse().unwrap();
+        let result = manager
+            .translate("mock", ", 世界!", &en, None)
+            .await
+            .unwrap();
+        assert_eq!(result, "你好, 世界!");
+    }
+
+    #[tokio::test]
+    async fn test_alias_shares_translator_instance() {
+        let mock = Arc::new(MockTranslator::new());
+        let mut manager = TranslationManager::new();
+        manager.add_translator("default", mock.clone());
+        manager.add_alias("default", "fast").unwrap();
+        // 为别名再添加别名时指向同一个翻译器
+        manager.add_alias("fast", "quick").unwrap();
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        for name in ["default", "fast", "quick"] {
+            manager
+                .translate(name, "Hello", &target_lang, None)
+                .await
+                .unwrap();
+        }
+        assert_eq!(mock.calls(), 3);
+
+        assert_eq!(manager.resolve("fast").as_deref(), Some("default"));
+        assert_eq!(manager.resolve("quick").as_deref(), Some("default"));
+        assert_eq!(manager.resolve("default").as_deref(), Some("default"));
+        assert_eq!(manager.resolve("missing"), None);
+        assert!(manager.has_translator("fast"));
+        assert_eq!(manager.list_translators(), vec!["default"]);
+        assert_eq!(
+            manager.list_aliases(),
+            vec![
+                ("fast".to_string(), "default".to_string()),
+                ("quick".to_string(), "default".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_alias_errors_and_replacement() {
+        let mut manager = TranslationManager::new();
+        manager.add_translator("a", Box::new(MockTranslator::new().prefix("a:")));
+        manager.add_translator("b", Box::new(MockTranslator::new().prefix("b:")));
+        assert!(matches!(
+            manager.add_alias("missing", "x"),
+            Err(TranslationError::ConfigurationError(_))
+        ));
+        assert!(matches!(
+            manager.add_alias("a", "b"),
+            Err(TranslationError::ConfigurationError(_))
+        ));
+
+        // 注册同名翻译器会替换别名
+        manager.add_alias("a", "x").unwrap();
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let via_alias = manager
+            .translate("x", "Hi", &target_lang, None)
+            .await
+            .unwrap();
+        assert!(via_alias.starts_with("a:"), "{}", via_alias);
+        manager.add_translator("x", Box::new(MockTranslator::new().prefix("x:")));
+        let direct = manager
+            .translate("x", "Hi", &target_lang, None)
+            .await
+            .unwrap();
+        assert!(direct.starts_with("x:"), "{}", direct);
+        assert!(manager.list_aliases().is_empty());
+    }
+
+    /// 自身按 `on_failure` 原样返回原文的翻译器
+    struct PassThroughTranslator;
+
+    #[async_trait::async_trait]
+    impl Translator for PassThroughTranslator {
+        async fn translate_with_options(
+            &self,
+            text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            Ok(text.to_string())
+        }
+
+        async fn translate_detailed_with_options(
+            &self,
+            text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<TranslationDetail, TranslationError> {
+            Ok(TranslationDetail::pass_through(
+                text,
+                &TranslationError::ServiceError("unavailable".to_string()),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_on_failure_is_last_resort_in_manager() {
+        let hook = CollectingHook::default();
+        let processor = Arc::new(AppendProcessor {
+            suffix: "|processed",
+            ..Default::default()
+        });
+        let mut manager = TranslationManager::new().with_sampling_hook(hook.clone(), 1.0);
+        manager.add_translator("failing", Box::new(FailingTranslator));
+        manager.add_translator("passthrough", Box::new(PassThroughTranslator));
+        manager.add_translator("mock", Box::new(MockTranslator::new()));
+        manager.add_post_processor(processor.clone());
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+
+        let options = TranslateOptions::default().on_failure(FailurePolicy::ReturnOriginal);
+        assert_eq!(
+            manager
+                .translate_with_options("failing", "hi", &zh, None, &options)
+                .await
+                .unwrap(),
+            "hi"
+        );
+        let detail = manager
+            .translate_detailed_with_options("failing", "hi", &zh, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "hi");
+        assert!(detail.passthrough);
+        assert_eq!(
+            detail.provider_metadata["error"],
+            "Service error: unavailable"
+        );
+
+        // 条件不满足时仍然返回错误
+        let options =
+            TranslateOptions::default().on_failure(FailurePolicy::ReturnOriginalIf(|error| {
+                matches!(error, TranslationError::TimeoutError)
+            }));
+        assert!(
+            manager
+                .translate_with_options("failing", "hi", &zh, None, &options)
+                .await
+                .is_err()
+        );
+
+        // 翻译器自己原样返回的原文同样不做后处理
+        let detail = manager
+            .translate_detailed_with_options(
+                "passthrough",
+                "hi",
+                &zh,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "hi");
+        assert!(detail.passthrough);
+        assert_eq!(processor.calls.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        // 原样返回的原文按失败统计，只有真正的译文会被采样
+        manager.translate("mock", "hi", &zh, None).await.unwrap();
+        let records = hook.wait_for(1).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(hook.records.lock().unwrap().len(), 1);
+        assert_eq!(records[0].provider, "mock");
+        let stats = manager.pair_stats();
+        let failures = |provider: &str| {
+            stats[&LanguagePair {
+                provider: provider.to_string(),
+                source_lang: None,
+                target_lang: zh.clone(),
+            }]
+                .failures
+        };
+        assert_eq!(failures("failing"), 3);
+        assert_eq!(failures("passthrough"), 1);
+    }
+
+    /// 延迟和是否失败可以在测试中途修改的模拟翻译器
+    #[derive(Clone)]
+    struct ScriptedTranslator {
+        name: &'static str,
+        latency: Arc<Mutex<Duration>>,
+        failing: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    impl ScriptedTranslator {
+        fn new(name: &'static str, latency_ms: u64) -> Self {
+            Self {
+                name,
+                latency: Arc::new(Mutex::new(Duration::from_millis(latency_ms))),
+                failing: Arc::default(),
+            }
+        }
+
+        fn set_latency(&self, latency_ms: u64) {
+            *self.latency.lock().unwrap() = Duration::from_millis(latency_ms);
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Translator for ScriptedTranslator {
+        async fn translate_with_options(
+            &self,
+            _text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            let latency = *self.latency.lock().unwrap();
+            tokio::time::sleep(latency).await;
+            if self.failing.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err(TranslationError::ServiceError("unavailable".to_string()));
+            }
+            Ok(self.name.to_string())
+        }
+    }
+
+    async fn adaptive_counts(
+        manager: &TranslationManager,
+        candidates: &[&str],
+        requests: usize,
+    ) -> HashMap<String, usize> {
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().on_failure(FailurePolicy::ReturnOriginal);
+        let mut counts = HashMap::new();
+        for _ in 0..requests {
+            let result = manager
+                .translate_adaptive(candidates, "hi", &zh, None, &options)
+                .await
+                .unwrap();
+            *counts.entry(result).or_default() += 1;
+        }
+        counts
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_adaptive_routing_follows_latency() {
+        let fast = ScriptedTranslator::new("fast", 10);
+        let slow = ScriptedTranslator::new("slow", 200);
+        let mut manager = TranslationManager::new()
+            .with_adaptive_routing(0.1, 0.5)
+            .with_adaptive_seed(3);
+        manager.add_translator("fast", Box::new(fast.clone()));
+        manager.add_translator("slow", Box::new(slow.clone()));
+        let candidates = ["slow", "fast"];
+
+        // 尚无统计的候选优先使用
+        let counts = adaptive_counts(&manager, &candidates, 2).await;
+        assert_eq!((counts["slow"], counts["fast"]), (1, 1));
+
+        let counts = adaptive_counts(&manager, &candidates, 200).await;
+        assert!(counts["fast"] > 170, "{:?}", counts);
+        assert!(counts.get("slow").copied().unwrap_or_default() > 0);
+
+        // 延迟变化后，探索让流量转向恢复的翻译器
+        fast.set_latency(300);
+        slow.set_latency(20);
+        let _ = adaptive_counts(&manager, &candidates, 100).await;
+        let counts = adaptive_counts(&manager, &candidates, 200).await;
+        assert!(counts["slow"] > 170, "{:?}", counts);
+
+        let stats = manager.adaptive_stats();
+        assert_eq!(stats["fast"].requests + stats["slow"].requests, 502);
+        let latency = stats["slow"].latency.unwrap();
+        assert!(latency < Duration::from_millis(50), "{:?}", latency);
+        assert_eq!(stats["slow"].error_rate, 0.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_adaptive_routing_avoids_failing_translator() {
+        let flaky = ScriptedTranslator::new("flaky", 10);
+        let steady = ScriptedTranslator::new("steady", 50);
+        let mut manager = TranslationManager::new()
+            .with_adaptive_routing(0.0, 0.5)
+            .with_adaptive_seed(5);
+        manager.add_translator("flaky", Box::new(flaky.clone()));
+        manager.add_translator("steady", Box::new(steady));
+        manager.add_alias("flaky", "primary").unwrap();
+        let candidates = ["primary", "steady"];
+
+        let counts = adaptive_counts(&manager, &candidates, 20).await;
+        assert_eq!(counts["flaky"], 19);
+
+        // 失败按原样返回处理，但仍然计入统计
+        flaky
+            .failing
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+        let counts = adaptive_counts(&manager, &candidates, 20).await;
+        assert!(counts["hi"] <= 3, "{:?}", counts);
+        assert!(counts["steady"] >= 17, "{:?}", counts);
+        let stats = manager.adaptive_stats();
+        assert!(!stats.contains_key("primary"));
+        assert!(stats["flaky"].error_rate > 0.5);
+
+        // 重置后每个候选都会被重新探索
+        flaky
+            .failing
+            .store(false, std::sync::atomic::Ordering::SeqCst);
+        manager.reset_adaptive_stats();
+        assert!(manager.adaptive_stats().is_empty());
+        let counts = adaptive_counts(&manager, &candidates, 20).await;
+        assert_eq!(counts["flaky"], 19);
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_routing_errors() {
+        let manager = TranslationManager::new();
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        assert!(matches!(
+            manager
+                .translate_adaptive(&[], "hi", &zh, None, &options)
+                .await,
+            Err(TranslationError::ConfigurationError(_))
+        ));
+        assert!(matches!(
+            manager
+                .translate_adaptive(&["missing"], "hi", &zh, None, &options)
+                .await,
+            Err(TranslationError::ConfigurationError(_))
+        ));
+        assert_eq!(manager.adaptive_stats()["missing"].error_rate, 0.3);
+
+        manager.begin_shutdown();
+        assert!(matches!(
+            manager
+                .translate_adaptive(&["missing"], "hi", &zh, None, &options)
+                .await,
+            Err(TranslationError::ShuttingDown)
+        ));
+        assert_eq!(manager.adaptive_stats()["missing"].requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_translate_batch_tagged_keeps_tags() {
+        let mock = MockTranslator::new().prefix("zh:");
+        let mut manager = TranslationManager::new();
+        manager.add_translator("mock", Box::new(mock.clone()));
+        manager.add_translator("failing", Box::new(FailingTranslator));
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+
+        // 标签是不可复制的行记录，只能随文本移动
+        #[derive(Debug, PartialEq)]
+        struct Row {
+            id: u32,
+        }
+        let texts = ["apple", "pear", "apple", "plum", "pear", "apple"];
+        let items: Vec<(Row, String)> = texts
+            .iter()
+            .enumerate()
+            .map(|(id, text)| (Row { id: id as u32 }, text.to_string()))
+            .collect();
+        let options = TranslateOptions::default().dedup(true);
+        let results = manager
+            .translate_batch_tagged("mock", items, &zh, None, &options)
+            .await;
+        assert_eq!(mock.calls(), 3);
+        assert_eq!(results.len(), texts.len());
+        for (row, result) in results {
+            assert_eq!(
+                result.unwrap(),
+                format!("zh:{}", texts[row.id as usize]),
+                "row {}",
+                row.id
+            );
+        }
+
+        // 失败时每个标签都会得到错误，统计按实际翻译次数计算
+        let items = vec![("a", "x".to_string()), ("b", "x".to_string())];
+        let results = manager
+            .translate_batch_tagged("failing", items, &zh, None, &options)
+            .await;
+        assert_eq!(
+            results.iter().map(|(tag, _)| *tag).collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+        assert!(results.iter().all(|(_, result)| result.is_err()));
+        let stats = manager.pair_stats();
+        let failing = LanguagePair {
+            provider: "failing".to_string(),
+            source_lang: None,
+            target_lang: zh.clone(),
+        };
+        assert_eq!(stats[&failing].failures, 1);
+
+        // 不去重时每条都会翻译
+        let items = vec![(1, "apple".to_string()), (2, "apple".to_string())];
+        manager
+            .translate_batch_tagged("mock", items, &zh, None, &TranslateOptions::default())
+            .await;
+        assert_eq!(mock.calls(), 5);
+    }
+
+    #[cfg(feature = "openai")]
+    #[test]
+    fn test_cache_key_namespace() {
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+        let build = |namespace: Option<&str>| {
+            let mut manager = TranslationManager::new();
+            if let Some(namespace) = namespace {
+                manager = manager.with_cache_namespace(namespace);
+            }
+            manager.add_translator(
+                "openai",
+                Box::new(OpenAITranslator::new(
+                    OpenAIConfig::builder().api_keys(vec!["test-key"]).build(),
+                )),
+            );
+            manager.add_translator("mock", Box::new(MockTranslator::new()));
+            manager.add_alias("openai", "llm").unwrap();
+            manager
+        };
+        let options = TranslateOptions::default();
+        let key = |manager: &TranslationManager, name: &str, text: &str| {
+            manager
+                .cache_key(name, text, &zh, None, &options)
+                .unwrap()
+                .unwrap()
+        };
+
+        let manager = build(None);
+        assert_eq!(
+            key(&manager, "openai", "Hello"),
+            key(&build(None), "openai", "Hello")
+        );
+        assert_eq!(
+            key(&manager, "llm", "Hello"),
+            key(&manager, "openai", "Hello")
+        );
+        assert_ne!(
+            key(&manager, "openai", "Hello"),
+            key(&manager, "openai", "Bye")
+        );
+        let v2 = build(Some("v2"));
+        assert_ne!(
+            key(&v2, "openai", "Hello"),
+            key(&manager, "openai", "Hello")
+        );
+        assert_ne!(
+            key(&v2, "openai", "Hello"),
+            key(&build(Some("v3")), "openai", "Hello")
+        );
+
+        // 没有提供缓存输入的翻译器不缓存，不存在的翻译器返回错误
+        assert_eq!(
+            manager
+                .cache_key("mock", "Hello", &zh, None, &options)
+                .unwrap(),
+            None
+        );
+        assert!(matches!(
+            manager.cache_key("missing", "Hello", &zh, None, &options),
+            Err(TranslationError::ConfigurationError(_))
+        ));
+    }
+
+    #[cfg(all(feature = "openai", feature = "microsoft"))]
+    #[test]
+    fn test_get_translator_as_downcasts_builtin_translators() {
+        use crate::microsoft::MicrosoftTranslator;
+
+        let mut manager = TranslationManager::new();
+        manager.add_translator(
+            "openai",
+            Box::new(OpenAITranslator::new(
+                OpenAIConfig::builder()
+                    .model("gpt-4o")
+                    .api_keys(vec!["test-key"])
+                    .build(),
+            )),
+        );
+        manager.add_translator(
+            "microsoft",
+            Arc::new(MicrosoftTranslator::new(
+                MicrosoftConfig::builder().category("custom").build(),
+            )),
+        );
+        manager.add_alias("microsoft", "ms").unwrap();
+
+        // 通过引用调用翻译器特有的方法
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        let openai = manager
+            .get_translator_as::<OpenAITranslator>("openai")
+            .unwrap();
+        let preview = openai
+            .build_request_preview("Hello", &target_lang, None, &options)
+            .unwrap();
+        assert_eq!(preview.body_json["model"], "gpt-4o");
+        let microsoft = manager
+            .get_translator_as::<MicrosoftTranslator>("ms")
+            .unwrap();
+        let preview = microsoft
+            .build_request_preview(["Hello"], &target_lang, None, &options)
+            .unwrap();
+        assert!(
+            preview
+                .query
+                .contains(&("category".to_string(), "custom".to_string()))
+        );
+
+        // 类型不符或名称不存在时返回 None
+        assert!(
+            manager
+                .get_translator_as::<MicrosoftTranslator>("openai")
+                .is_none()
+        );
+        assert!(
+            manager
+                .get_translator_as::<OpenAITranslator>("microsoft")
+                .is_none()
+        );
+        assert!(
+            manager
+                .get_translator_as::<OpenAITranslator>("missing")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_get_translator_as_without_as_any() {
+        let mut manager = TranslationManager::new();
+        manager.add_translator("mock", Box::new(MockTranslator::new()));
+        manager.add_translator("base_url", Box::new(BaseUrlTranslator));
+
+        assert_eq!(
+            manager
+                .get_translator_as::<MockTranslator>("mock")
+                .unwrap()
+                .calls(),
+            0
+        );
+        // 没有实现 as_any 的翻译器不支持向下转型
+        assert!(
+            manager
+                .get_translator_as::<BaseUrlTranslator>("base_url")
+                .is_none()
+        );
+
+        // 取得的引用不受之后替换翻译器的影响
+        let mock = manager.get_translator_as::<MockTranslator>("mock").unwrap();
+        manager.add_translator("mock", Box::new(BaseUrlTranslator));
+        assert_eq!(mock.calls(), 0);
+        assert!(
+            manager
+                .get_translator_as::<MockTranslator>("mock")
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cloned_manager_shares_registrations() {
+        fn assert_shareable<T: Clone + Default + Send + Sync + 'static>() {}
+        assert_shareable::<TranslationManager>();
+
+        let mut manager = TranslationManager::new();
+        let mut handle = manager.clone();
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        // 通过克隆注册的翻译器和别名对原句柄可见，反之亦然
+        handle.add_translator("mock", Box::new(MockTranslator::new().prefix("A:")));
+        handle.add_alias("mock", "default").unwrap();
+        assert!(manager.has_translator("mock"));
+        assert_eq!(manager.resolve("default").as_deref(), Some("mock"));
+        manager.add_translator("other", Box::new(MockTranslator::new().prefix("B:")));
+        assert_eq!(handle.list_translators().len(), 2);
+        assert_eq!(
+            handle
+                .translate("other", "hi", &target_lang, None)
+                .await
+                .unwrap(),
+            "B:hi"
+        );
+
+        // 关闭状态和统计同样共享
+        let stats = manager.pair_stats();
+        assert_eq!(stats.values().map(|stats| stats.successes).sum::<u64>(), 1);
+        handle.begin_shutdown();
+        assert!(manager.is_shutting_down());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cloned_handles_translate_concurrently() {
+        let latency = Duration::from_millis(100);
+        let mut manager = TranslationManager::new();
+        manager.add_translator(
+            "mock",
+            Box::new(MockTranslator::new().prefix("").latency(latency)),
+        );
+        let handle = manager.clone();
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let start = tokio::time::Instant::now();
+        let task = tokio::spawn({
+            let handle = handle.clone();
+            let target_lang = target_lang.clone();
+            async move { handle.translate("mock", "a", &target_lang, None).await }
+        });
+        let local = manager.translate("mock", "b", &target_lang, None);
+        let (local, remote) = tokio::join!(local, task);
+        assert_eq!(local.unwrap(), "b");
+        assert_eq!(remote.unwrap().unwrap(), "a");
+        assert!(start.elapsed() < latency * 2);
+        assert_eq!(handle.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_result_and_fallback() {
+        let mut manager = TranslationManager::new();
+        manager.add_translator("echo", Box::new(MockTranslator::new().prefix("")));
+        manager.add_translator("backup", Box::new(MockTranslator::new().prefix("译: ")));
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+
+        let detail = manager
+            .translate_detailed_with_options(
+                "echo",
+                "Hello",
+                &zh,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(detail.result_kind, TranslationKind::Unchanged);
+        let detail = manager
+            .translate_detailed_with_options(
+                "backup",
+                "Hello",
+                &zh,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(detail.result_kind, TranslationKind::Translated);
+
+        // 默认只标记，启用后返回错误，回退链尝试下一个翻译器
+        let chain = ["echo", "backup"];
+        let translated = manager
+            .translate_with_fallback(&chain, "Hello", &zh, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(translated, "Hello");
+        let options = TranslateOptions::default().error_on_unchanged(true);
+        let translated = manager
+            .translate_with_fallback(&chain, "Hello", &zh, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(translated, "译: Hello");
+        assert!(matches!(
+            manager
+                .translate_with_fallback(&["echo"], "Hello", &zh, None, &options)
+                .await,
+            Err(TranslationError::NotTranslated { translator }) if translator == "echo"
+        ));
+
+        // 严格比较时大小写不同不算相同
+        manager.add_translator("lower", Box::new(LowercaseTranslator));
+        assert!(
+            manager
+                .translate_with_options("lower", "Hello", &zh, None, &options)
+                .await
+                .is_err()
+        );
+        let strict = options.unchanged_comparison(UnchangedComparison {
+            ignore_case: false,
+            ignore_punctuation: true,
+        });
+        assert_eq!(
+            manager
+                .translate_with_options("lower", "Hello", &zh, None, &strict)
+                .await
+                .unwrap(),
+            "hello"
+        );
+    }
 }
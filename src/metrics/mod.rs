@@ -0,0 +1,175 @@
+//! 并发许可的排队指标
+//!
+//! 两个翻译器在获取并发许可时记录等待时长和排队的请求数，用于调整 `concurrent_limit`：
+//!
+//! * 通过配置中的 [`MetricsRecorder`] 把每次等待和排队深度的变化转发给外部的指标系统；
+//! * 通过翻译器的 `queue_stats()` 查看最近 [`QUEUE_STATS_WINDOW`] 内的平均值和最大值。
+//!
+//! 有空闲许可、无需排队的请求按等待零秒计入。
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// `queue_stats()` 统计的时间范围
+pub const QUEUE_STATS_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// 统计按分钟分桶，过期的桶整体丢弃
+const BUCKET: Duration = Duration::from_secs(60);
+
+/// 接收排队指标的接口，例如转发到 Prometheus 或 StatsD
+///
+/// 方法在获取许可的路径上同步调用，应尽快返回
+pub trait MetricsRecorder: fmt::Debug + Send + Sync {
+    /// 一个请求拿到并发许可，`wait` 为排队等待的时长
+    fn record_queue_wait(&self, provider: &str, wait: Duration) {
+        let _ = (provider, wait);
+    }
+
+    /// 等待许可的请求数发生变化，`depth` 为变化后的值
+    fn record_queue_depth(&self, provider: &str, depth: usize) {
+        let _ = (provider, depth);
+    }
+}
+
+/// 最近一段时间内获取并发许可的统计
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QueueStats {
+    /// 拿到许可的请求数
+    pub requests: u64,
+    /// 平均等待时长
+    pub average_wait: Duration,
+    /// 最长等待时长
+    pub max_wait: Duration,
+    /// 请求开始排队时（包括它自己）的平均排队请求数，无需排队的请求按 0 计入
+    pub average_depth: f64,
+    /// 排队请求数的最大值
+    pub max_depth: usize,
+    /// 当前正在等待许可的请求数
+    pub current_depth: usize,
+}
+
+/// 一分钟内的统计
+#[derive(Debug, Default)]
+struct Bucket {
+    /// 桶的序号：从创建起经过的分钟数
+    index: u64,
+    requests: u64,
+    total_wait: Duration,
+    max_wait: Duration,
+    total_depth: u64,
+    max_depth: usize,
+}
+
+/// 一个翻译器的排队指标，由它的各个 [`crate::concurrency::ConcurrencyLimiter`] 共享
+#[derive(Debug)]
+pub(crate) struct QueueMetrics {
+    provider: &'static str,
+    recorder: Option<Arc<dyn MetricsRecorder>>,
+    /// 所有共享该指标的限制器中正在等待许可的请求数
+    waiting: AtomicUsize,
+    created: Instant,
+    buckets: Mutex<VecDeque<Bucket>>,
+}
+
+impl QueueMetrics {
+    pub(crate) fn new(provider: &'static str, recorder: Option<Arc<dyn MetricsRecorder>>) -> Self {
+        Self {
+            provider,
+            recorder,
+            waiting: AtomicUsize::new(0),
+            created: Instant::now(),
+            buckets: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// 一个请求开始排队，返回包括它在内的排队请求数
+    pub(crate) fn enter(&self) -> usize {
+        let depth = self.waiting.fetch_add(1, Ordering::AcqRel) + 1;
+        self.update(|bucket| bucket.max_depth = bucket.max_depth.max(depth));
+        if let Some(recorder) = &self.recorder {
+            recorder.record_queue_depth(self.provider, depth);
+        }
+        depth
+    }
+
+    /// 一个请求离开队列（拿到许可、被拒绝或取消）
+    pub(crate) fn leave(&self) {
+        let depth = self.waiting.fetch_sub(1, Ordering::AcqRel) - 1;
+        if let Some(recorder) = &self.recorder {
+            recorder.record_queue_depth(self.provider, depth);
+        }
+    }
+
+    /// 一个请求拿到许可，`depth` 为它开始排队时的排队请求数，无需排队时为 0
+    pub(crate) fn granted(&self, wait: Duration, depth: usize) {
+        self.update(|bucket| {
+            bucket.requests += 1;
+            bucket.total_wait += wait;
+            bucket.max_wait = bucket.max_wait.max(wait);
+            bucket.total_depth += depth as u64;
+            bucket.max_depth = bucket.max_depth.max(depth);
+        });
+        if let Some(recorder) = &self.recorder {
+            recorder.record_queue_wait(self.provider, wait);
+        }
+    }
+
+    /// 更新当前分钟的桶，同时丢弃过期的桶
+    fn update(&self, update: impl FnOnce(&mut Bucket)) {
+        let index = self.bucket_index();
+        let mut buckets = self.buckets.lock().unwrap();
+        Self::expire(&mut buckets, index);
+        if buckets.back().is_none_or(|bucket| bucket.index != index) {
+            buckets.push_back(Bucket {
+                index,
+                ..Default::default()
+            });
+        }
+        update(buckets.back_mut().unwrap());
+    }
+
+    fn bucket_index(&self) -> u64 {
+        self.created.elapsed().as_secs() / BUCKET.as_secs()
+    }
+
+    fn expire(buckets: &mut VecDeque<Bucket>, index: u64) {
+        let kept = QUEUE_STATS_WINDOW.as_secs() / BUCKET.as_secs();
+        while buckets
+            .front()
+            .is_some_and(|bucket| bucket.index + kept <= index)
+        {
+            buckets.pop_front();
+        }
+    }
+
+    /// 最近 [`QUEUE_STATS_WINDOW`] 内的统计
+    pub(crate) fn stats(&self) -> QueueStats {
+        let mut buckets = self.buckets.lock().unwrap();
+        Self::expire(&mut buckets, self.bucket_index());
+        let mut stats = QueueStats {
+            current_depth: self.waiting.load(Ordering::Acquire),
+            ..Default::default()
+        };
+        let mut total_wait = Duration::ZERO;
+        let mut total_depth = 0;
+        for bucket in buckets.iter() {
+            stats.requests += bucket.requests;
+            total_wait += bucket.total_wait;
+            total_depth += bucket.total_depth;
+            stats.max_wait = stats.max_wait.max(bucket.max_wait);
+            stats.max_depth = stats.max_depth.max(bucket.max_depth);
+        }
+        if stats.requests > 0 {
+            stats.average_wait = total_wait.div_f64(stats.requests as f64);
+            stats.average_depth = total_depth as f64 / stats.requests as f64;
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests;
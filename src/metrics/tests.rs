@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use crate::concurrency::ConcurrencyLimiter;
+    use crate::metrics::{MetricsRecorder, QUEUE_STATS_WINDOW, QueueMetrics, QueueStats};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// 记录收到的所有事件
+    #[derive(Debug, Default)]
+    struct Events {
+        waits: Mutex<Vec<(String, Duration)>>,
+        depths: Mutex<Vec<usize>>,
+    }
+
+    impl MetricsRecorder for Events {
+        fn record_queue_wait(&self, provider: &str, wait: Duration) {
+            self.waits
+                .lock()
+                .unwrap()
+                .push((provider.to_string(), wait));
+        }
+
+        fn record_queue_depth(&self, _provider: &str, depth: usize) {
+            self.depths.lock().unwrap().push(depth);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rolling_window() {
+        let metrics = QueueMetrics::new("test", None);
+        assert_eq!(metrics.stats(), QueueStats::default());
+
+        metrics.granted(Duration::ZERO, 0);
+        assert_eq!(metrics.enter(), 1);
+        assert_eq!(metrics.enter(), 2);
+        assert_eq!(metrics.stats().current_depth, 2);
+        metrics.leave();
+        metrics.granted(Duration::from_millis(300), 1);
+        tokio::time::advance(Duration::from_secs(90)).await;
+        metrics.leave();
+        metrics.granted(Duration::from_millis(600), 2);
+
+        assert_eq!(
+            metrics.stats(),
+            QueueStats {
+                requests: 3,
+                average_wait: Duration::from_millis(300),
+                max_wait: Duration::from_millis(600),
+                average_depth: 1.0,
+                max_depth: 2,
+                current_depth: 0,
+            }
+        );
+
+        // 第一分钟的桶过期后只剩后面的记录
+        tokio::time::advance(QUEUE_STATS_WINDOW - Duration::from_secs(60)).await;
+        let stats = metrics.stats();
+        assert_eq!(stats.requests, 1);
+        assert_eq!(stats.max_wait, Duration::from_millis(600));
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!(metrics.stats(), QueueStats::default());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_limiter_reports_waits_and_depth() {
+        let events = Arc::new(Events::default());
+        let metrics = Arc::new(QueueMetrics::new("test", Some(events.clone())));
+        let limiter = ConcurrencyLimiter::new(1, None).metrics(metrics.clone());
+
+        let held = limiter.acquire(None).await.unwrap();
+        // 拿到许可后立即归还，让下一个排队的请求继续
+        let acquire = || async { limiter.acquire(None).await.map(drop).unwrap() };
+        let queued = async {
+            tokio::join!(acquire(), acquire());
+        };
+        let release = async {
+            while metrics.stats().current_depth < 2 {
+                tokio::task::yield_now().await;
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            drop(held);
+        };
+        tokio::join!(queued, release);
+
+        let waits: Vec<Duration> = events.waits.lock().unwrap().iter().map(|w| w.1).collect();
+        assert_eq!(waits.len(), 3);
+        assert_eq!(waits[0], Duration::ZERO);
+        assert!(
+            waits[1..]
+                .iter()
+                .all(|wait| *wait >= Duration::from_secs(1))
+        );
+        assert_eq!(*events.depths.lock().unwrap(), [1, 2, 1, 0]);
+        assert!(events.waits.lock().unwrap().iter().all(|w| w.0 == "test"));
+
+        let stats = metrics.stats();
+        assert_eq!(
+            (stats.requests, stats.max_depth, stats.current_depth),
+            (3, 2, 0)
+        );
+        assert_eq!(stats.average_depth, 1.0);
+    }
+}
@@ -4,36 +4,325 @@
 //! 1. 自动认证：通过临时token，无需配置API密钥
 //! 2. API Key认证：使用用户提供的API密钥
 
-use crate::{error::TranslationError, options::TranslateOptions, translator::Translator};
+use crate::{
+    batch,
+    cache::CacheKeyInputs,
+    clock::{Clock, TokioClock},
+    concurrency::{ConcurrencyLimiter, Permit},
+    detect::{self, DetectionCache, DetectionCacheStats},
+    error::{self, BatchErrorReport, TranslationError},
+    glossary::Glossary,
+    http, langmap,
+    metrics::{MetricsRecorder, QueueMetrics, QueueStats},
+    normalize,
+    options::{EffectiveOptions, PoolOptions, ProxyMode, TranslateOptions},
+    preflight,
+    preview::{PreparedRequest, RequestPreview},
+    protect::Protected,
+    ratelimit::RateLimiter,
+    retry,
+    secret::SecretString,
+    trace,
+    translator::{
+        LengthUnit, Provenance, TranslationDetail, TranslationKind, Translator,
+        TranslatorCapabilities,
+    },
+};
+use futures::future::{join_all, select_ok};
+use rand::Rng;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use tokio::sync::{Mutex, Semaphore};
-use tokio::time::sleep;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{Mutex, OnceCell};
 use unic_langid::LanguageIdentifier;
 
+/// 单次请求允许的最大字符数（所有文本合计）
+pub const MAX_REQUEST_CHARS: usize = 50_000;
+
+/// 单次请求允许的最大文本条数
+pub const MAX_REQUEST_ITEMS: usize = 1_000;
+
+/// 未配置端点和地理区域时使用的默认端点
+pub const DEFAULT_ENDPOINT: &str = "https://api-edge.cognitive.microsofttranslator.com";
+
+/// 自动认证时默认的临时 token 获取地址
+pub const DEFAULT_AUTH_ENDPOINT: &str = "https://edge.microsoft.com/translate/auth";
+
+/// 未配置 `api_version` 时使用的 API 版本
+pub const DEFAULT_API_VERSION: &str = "3.0";
+
+/// 微软翻译服务的地理区域
+///
+/// 各区域端点需要使用 API Key 认证
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GeoRegion {
+    /// 全球端点，由微软就近路由
+    Global,
+    /// 美洲
+    Americas,
+    /// 欧洲
+    Europe,
+    /// 亚太
+    AsiaPacific,
+}
+
+impl GeoRegion {
+    /// 所有地理区域
+    pub const ALL: [GeoRegion; 4] = [
+        GeoRegion::Global,
+        GeoRegion::Americas,
+        GeoRegion::Europe,
+        GeoRegion::AsiaPacific,
+    ];
+
+    /// 区域对应的端点
+    pub fn endpoint(self) -> &'static str {
+        match self {
+            GeoRegion::Global => "https://api.cognitive.microsofttranslator.com",
+            GeoRegion::Americas => "https://api-nam.cognitive.microsofttranslator.com",
+            GeoRegion::Europe => "https://api-eur.cognitive.microsofttranslator.com",
+            GeoRegion::AsiaPacific => "https://api-apc.cognitive.microsofttranslator.com",
+        }
+    }
+}
+
+/// 定位批次中出错条目时默认最多额外发送的请求数
+const DEFAULT_MAX_BISECT_REQUESTS: usize = 16;
+
+/// 自动选择端点时每个候选端点的测速超时
+const ENDPOINT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 自动检测源语言的置信度低于 `min_detection_score` 时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LowConfidencePolicy {
+    /// 以 `suggested_from` 作为源语言重新翻译这些文本
+    #[default]
+    UseSuggestedFrom,
+    /// 返回 [`TranslationError::LowConfidenceDetection`] 错误
+    Error,
+    /// 原样返回原文
+    PassThrough,
+}
+
 /// 微软翻译器配置
 #[derive(Debug, Clone)]
 pub struct MicrosoftConfig {
-    /// 微软翻译服务的端点
+    /// 微软翻译服务的端点，设置后优先于 `region` 和 `auto_select_endpoint`
     pub endpoint: Option<String>,
+    /// 地理区域，未设置 `endpoint` 时使用该区域的端点
+    pub region: Option<GeoRegion>,
+    /// 首次使用时是否对 `endpoint_candidates` 测速并缓存最快的端点
+    pub auto_select_endpoint: bool,
+    /// 自动选择端点时参与测速的端点，默认为所有地理区域的端点
+    pub endpoint_candidates: Vec<String>,
+    /// 所有接口请求的 `api-version` 参数，None 表示使用 [`DEFAULT_API_VERSION`]；
+    /// 部分主权云端点需要不同的版本
+    pub api_version: Option<String>,
     /// API Key（可选），如果未设置则使用自动认证
-    pub api_key: Option<String>,
+    pub api_key: Option<SecretString>,
+    /// 自动认证时获取临时 token 的地址，None 表示使用 [`DEFAULT_AUTH_ENDPOINT`]
+    pub auth_endpoint: Option<String>,
+    /// 自动认证时减轻对 token 地址的压力：重试之间加入随机间隔，连续收到 429 后
+    /// 按指数退避暂停获取 token，暂停期间直接返回 `AuthenticationError`。配置了 API Key 时不生效
+    pub free_tier_hardening: bool,
     /// 并发请求数限制
     pub concurrent_limit: usize,
+    /// 允许同时等待并发许可的最大请求数，超出时返回 `Overloaded` 错误，None 表示不限制
+    pub max_queue_depth: Option<usize>,
+    /// 请求体（序列化后的 JSON）的最大字节数，None 表示不限制。超出时批量请求按大小
+    /// 拆分为多个请求，单条文本的请求体仍然超出时返回 `TextTooLong`
+    pub max_request_bytes: Option<usize>,
+    /// 是否在收到 429/503 响应时自动降低并发数，持续成功后逐步恢复到 `concurrent_limit`
+    pub adaptive_concurrency: bool,
+    /// 相邻两次请求之间的最小间隔，None 表示不限制
+    pub min_request_interval: Option<Duration>,
+    /// Custom Translator 自定义模型的类别 ID，作为 `category` 参数发送
+    pub category: Option<String>,
+    /// 自定义模型不可用时是否允许回退到通用模型，作为 `allowFallback` 参数发送；
+    /// None 表示使用服务端默认值（允许回退）
+    pub allow_fallback: Option<bool>,
+    /// 无法识别源语言时使用的语言，作为 `suggestedFrom` 参数发送
+    pub suggested_from: Option<LanguageIdentifier>,
+    /// 自动检测源语言的最低置信度（0.0 ~ 1.0），None 表示不检查
+    pub min_detection_score: Option<f64>,
+    /// 检测置信度低于 `min_detection_score` 时的处理方式
+    pub on_low_confidence: LowConfidencePolicy,
+    /// HTTP 连接池设置
+    pub pool: PoolOptions,
+    /// HTTP 代理设置，微软翻译获取 token 的请求同样使用该代理
+    pub proxy_mode: ProxyMode,
+    /// 主机名解析失败后直接失败的时长，期间发往该主机的请求不再发出，返回不可重试的
+    /// `NetworkError`；默认 30 秒，`Duration::ZERO` 表示关闭
+    pub dns_failure_ttl: Duration,
+    /// 语言检测缓存的最大条目数，0 表示不缓存
+    pub detection_cache_size: usize,
+    /// [`MicrosoftTranslator::translate_batch_isolated`] 定位出错条目时最多额外发送的请求数
+    pub max_bisect_requests: usize,
+    /// 默认的翻译选项，调用时未设置的选项使用这里的值
+    pub default_options: TranslateOptions,
+    /// token 过期判断和重试退避使用的时钟
+    pub clock: Arc<dyn Clock>,
+    /// 接收并发许可排队指标的记录器，None 表示只在 `queue_stats()` 中统计
+    pub metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
+}
+
+impl MicrosoftConfig {
+    /// 检查配置是否可以用于创建翻译器
+    pub fn validate(&self) -> Result<(), TranslationError> {
+        if self.concurrent_limit == 0 {
+            return Err(TranslationError::ConfigurationError(
+                "concurrent_limit must be greater than 0".to_string(),
+            ));
+        }
+        if let Some(endpoint) = &self.endpoint {
+            http::validate_url("endpoint", endpoint)?;
+        }
+        if let Some(auth_endpoint) = &self.auth_endpoint {
+            http::validate_url("auth_endpoint", auth_endpoint)?;
+        }
+        if self
+            .api_version
+            .as_deref()
+            .is_some_and(|version| version.trim().is_empty())
+        {
+            return Err(TranslationError::ConfigurationError(
+                "api_version must not be empty".to_string(),
+            ));
+        }
+        if self.allow_fallback.is_some() && self.category.is_none() {
+            return Err(TranslationError::ConfigurationError(
+                "allow_fallback requires category".to_string(),
+            ));
+        }
+        if self.min_detection_score.is_some()
+            && self.on_low_confidence == LowConfidencePolicy::UseSuggestedFrom
+            && self.suggested_from.is_none()
+        {
+            return Err(TranslationError::ConfigurationError(
+                "on_low_confidence UseSuggestedFrom requires suggested_from".to_string(),
+            ));
+        }
+        if self.auto_select_endpoint && self.endpoint.is_none() {
+            if self.endpoint_candidates.is_empty() {
+                return Err(TranslationError::ConfigurationError(
+                    "auto_select_endpoint requires at least one endpoint candidate".to_string(),
+                ));
+            }
+            for candidate in &self.endpoint_candidates {
+                http::validate_url("endpoint candidate", candidate)?;
+            }
+        }
+        http::validate_pool_options(&self.pool)?;
+        http::validate_proxy_mode(&self.proxy_mode)
+    }
+}
+
+/// 时钟按实例比较
+impl PartialEq for MicrosoftConfig {
+    fn eq(&self, other: &Self) -> bool {
+        // 解构全部字段，新增字段时编译器会提示更新这里
+        let MicrosoftConfig {
+            endpoint,
+            region,
+            auto_select_endpoint,
+            endpoint_candidates,
+            api_version,
+            api_key,
+            auth_endpoint,
+            free_tier_hardening,
+            concurrent_limit,
+            max_queue_depth,
+            max_request_bytes,
+            adaptive_concurrency,
+            min_request_interval,
+            category,
+            allow_fallback,
+            suggested_from,
+            min_detection_score,
+            on_low_confidence,
+            pool,
+            proxy_mode,
+            dns_failure_ttl,
+            detection_cache_size,
+            max_bisect_requests,
+            default_options,
+            clock,
+            metrics_recorder,
+        } = self;
+        *endpoint == other.endpoint
+            && *region == other.region
+            && *auto_select_endpoint == other.auto_select_endpoint
+            && *endpoint_candidates == other.endpoint_candidates
+            && *api_version == other.api_version
+            && *api_key == other.api_key
+            && *auth_endpoint == other.auth_endpoint
+            && *free_tier_hardening == other.free_tier_hardening
+            && *concurrent_limit == other.concurrent_limit
+            && *max_queue_depth == other.max_queue_depth
+            && *max_request_bytes == other.max_request_bytes
+            && *adaptive_concurrency == other.adaptive_concurrency
+            && *min_request_interval == other.min_request_interval
+            && *category == other.category
+            && *allow_fallback == other.allow_fallback
+            && *suggested_from == other.suggested_from
+            && *min_detection_score == other.min_detection_score
+            && *on_low_confidence == other.on_low_confidence
+            && *pool == other.pool
+            && *proxy_mode == other.proxy_mode
+            && *dns_failure_ttl == other.dns_failure_ttl
+            && *detection_cache_size == other.detection_cache_size
+            && *max_bisect_requests == other.max_bisect_requests
+            && *default_options == other.default_options
+            && Arc::ptr_eq(clock, &other.clock)
+            && match (metrics_recorder, &other.metrics_recorder) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    }
 }
 
 impl Default for MicrosoftConfig {
     fn default() -> Self {
         Self {
             endpoint: None, // 使用默认端点
-            api_key: None,  // 使用自动认证
+            region: None,
+            auto_select_endpoint: false,
+            endpoint_candidates: default_endpoint_candidates(),
+            api_version: None,
+            api_key: None, // 使用自动认证
+            auth_endpoint: None,
+            free_tier_hardening: false,
             concurrent_limit: 10,
+            max_queue_depth: None,
+            max_request_bytes: None,
+            adaptive_concurrency: false,
+            min_request_interval: None,
+            category: None,
+            allow_fallback: None,
+            suggested_from: None,
+            min_detection_score: None,
+            on_low_confidence: LowConfidencePolicy::default(),
+            pool: PoolOptions::default(),
+            proxy_mode: ProxyMode::default(),
+            dns_failure_ttl: http::DEFAULT_DNS_FAILURE_TTL,
+            detection_cache_size: detect::DEFAULT_DETECTION_CACHE_SIZE,
+            max_bisect_requests: DEFAULT_MAX_BISECT_REQUESTS,
+            default_options: TranslateOptions::default(),
+            clock: Arc::new(TokioClock),
+            metrics_recorder: None,
         }
     }
 }
 
+/// 所有地理区域的端点
+fn default_endpoint_candidates() -> Vec<String> {
+    GeoRegion::ALL
+        .iter()
+        .map(|region| region.endpoint().to_string())
+        .collect()
+}
+
 impl MicrosoftConfig {
     pub fn builder() -> MicrosoftConfigBuilder {
         MicrosoftConfigBuilder::default()
@@ -43,8 +332,31 @@ impl MicrosoftConfig {
 #[derive(Debug, Default)]
 pub struct MicrosoftConfigBuilder {
     endpoint: Option<String>,
-    api_key: Option<String>,
+    region: Option<GeoRegion>,
+    auto_select_endpoint: Option<bool>,
+    endpoint_candidates: Option<Vec<String>>,
+    api_version: Option<String>,
+    api_key: Option<SecretString>,
+    auth_endpoint: Option<String>,
+    free_tier_hardening: Option<bool>,
     concurrent_limit: Option<usize>,
+    max_queue_depth: Option<usize>,
+    max_request_bytes: Option<usize>,
+    adaptive_concurrency: bool,
+    min_request_interval: Option<Duration>,
+    category: Option<String>,
+    allow_fallback: Option<bool>,
+    suggested_from: Option<LanguageIdentifier>,
+    min_detection_score: Option<f64>,
+    on_low_confidence: Option<LowConfidencePolicy>,
+    pool: PoolOptions,
+    proxy_mode: ProxyMode,
+    dns_failure_ttl: Option<Duration>,
+    detection_cache_size: Option<usize>,
+    max_bisect_requests: Option<usize>,
+    default_options: TranslateOptions,
+    clock: Option<Arc<dyn Clock>>,
+    metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
 }
 
 impl MicrosoftConfigBuilder {
@@ -53,23 +365,432 @@ impl MicrosoftConfigBuilder {
         self
     }
 
-    pub fn api_key(mut self, api_key: Option<impl Into<String>>) -> Self {
+    pub fn region(mut self, region: GeoRegion) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    pub fn auto_select_endpoint(mut self, auto_select_endpoint: bool) -> Self {
+        self.auto_select_endpoint = Some(auto_select_endpoint);
+        self
+    }
+
+    pub fn endpoint_candidates(mut self, endpoint_candidates: Vec<impl Into<String>>) -> Self {
+        self.endpoint_candidates =
+            Some(endpoint_candidates.into_iter().map(|s| s.into()).collect());
+        self
+    }
+
+    pub fn api_version(mut self, api_version: impl Into<String>) -> Self {
+        self.api_version = Some(api_version.into());
+        self
+    }
+
+    pub fn api_key(mut self, api_key: Option<impl Into<SecretString>>) -> Self {
         self.api_key = api_key.map(|s| s.into());
         self
     }
 
+    pub fn auth_endpoint(mut self, auth_endpoint: impl Into<String>) -> Self {
+        self.auth_endpoint = Some(auth_endpoint.into());
+        self
+    }
+
+    pub fn free_tier_hardening(mut self, free_tier_hardening: bool) -> Self {
+        self.free_tier_hardening = Some(free_tier_hardening);
+        self
+    }
+
     pub fn concurrent_limit(mut self, concurrent_limit: usize) -> Self {
         self.concurrent_limit = Some(concurrent_limit);
         self
     }
 
+    pub fn max_queue_depth(mut self, max_queue_depth: usize) -> Self {
+        self.max_queue_depth = Some(max_queue_depth);
+        self
+    }
+
+    pub fn max_request_bytes(mut self, max_request_bytes: usize) -> Self {
+        self.max_request_bytes = Some(max_request_bytes);
+        self
+    }
+
+    pub fn adaptive_concurrency(mut self, adaptive_concurrency: bool) -> Self {
+        self.adaptive_concurrency = adaptive_concurrency;
+        self
+    }
+
+    pub fn min_request_interval(mut self, min_request_interval: Duration) -> Self {
+        self.min_request_interval = Some(min_request_interval);
+        self
+    }
+
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    pub fn allow_fallback(mut self, allow_fallback: bool) -> Self {
+        self.allow_fallback = Some(allow_fallback);
+        self
+    }
+
+    pub fn suggested_from(mut self, suggested_from: LanguageIdentifier) -> Self {
+        self.suggested_from = Some(suggested_from);
+        self
+    }
+
+    pub fn min_detection_score(mut self, min_detection_score: f64) -> Self {
+        self.min_detection_score = Some(min_detection_score);
+        self
+    }
+
+    pub fn on_low_confidence(mut self, on_low_confidence: LowConfidencePolicy) -> Self {
+        self.on_low_confidence = Some(on_low_confidence);
+        self
+    }
+
+    pub fn pool_max_idle_per_host(mut self, pool_max_idle_per_host: usize) -> Self {
+        self.pool.max_idle_per_host = pool_max_idle_per_host;
+        self
+    }
+
+    pub fn pool_idle_timeout(mut self, pool_idle_timeout: Option<Duration>) -> Self {
+        self.pool.idle_timeout = pool_idle_timeout;
+        self
+    }
+
+    pub fn tcp_keepalive(mut self, tcp_keepalive: Option<Duration>) -> Self {
+        self.pool.tcp_keepalive = tcp_keepalive;
+        self
+    }
+
+    pub fn http2_prior_knowledge(mut self, http2_prior_knowledge: bool) -> Self {
+        self.pool.http2_prior_knowledge = http2_prior_knowledge;
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.pool.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    pub fn prefer_http2(mut self, prefer_http2: bool) -> Self {
+        self.pool.prefer_http2 = prefer_http2;
+        self
+    }
+
+    pub fn http2_keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.pool.http2_keep_alive_interval = Some(interval);
+        self
+    }
+
+    pub fn http2_keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.pool.http2_keep_alive_timeout = Some(timeout);
+        self
+    }
+
+    pub fn http2_keep_alive_while_idle(mut self, while_idle: bool) -> Self {
+        self.pool.http2_keep_alive_while_idle = while_idle;
+        self
+    }
+
+    pub fn proxy_mode(mut self, proxy_mode: ProxyMode) -> Self {
+        self.proxy_mode = proxy_mode;
+        self
+    }
+
+    pub fn dns_failure_ttl(mut self, dns_failure_ttl: Duration) -> Self {
+        self.dns_failure_ttl = Some(dns_failure_ttl);
+        self
+    }
+
+    pub fn detection_cache_size(mut self, detection_cache_size: usize) -> Self {
+        self.detection_cache_size = Some(detection_cache_size);
+        self
+    }
+
+    pub fn max_bisect_requests(mut self, max_bisect_requests: usize) -> Self {
+        self.max_bisect_requests = Some(max_bisect_requests);
+        self
+    }
+
+    pub fn default_options(mut self, default_options: TranslateOptions) -> Self {
+        self.default_options = default_options;
+        self
+    }
+
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn metrics_recorder(mut self, metrics_recorder: Arc<dyn MetricsRecorder>) -> Self {
+        self.metrics_recorder = Some(metrics_recorder);
+        self
+    }
+
     pub fn build(self) -> MicrosoftConfig {
         MicrosoftConfig {
             endpoint: self.endpoint,
+            region: self.region,
+            auto_select_endpoint: self.auto_select_endpoint.unwrap_or(false),
+            endpoint_candidates: self
+                .endpoint_candidates
+                .unwrap_or_else(default_endpoint_candidates),
+            api_version: self.api_version,
             api_key: self.api_key,
+            auth_endpoint: self.auth_endpoint,
+            free_tier_hardening: self.free_tier_hardening.unwrap_or(false),
             concurrent_limit: self.concurrent_limit.unwrap_or(10),
+            max_queue_depth: self.max_queue_depth,
+            max_request_bytes: self.max_request_bytes,
+            adaptive_concurrency: self.adaptive_concurrency,
+            min_request_interval: self.min_request_interval,
+            category: self.category,
+            allow_fallback: self.allow_fallback,
+            suggested_from: self.suggested_from,
+            min_detection_score: self.min_detection_score,
+            on_low_confidence: self.on_low_confidence.unwrap_or_default(),
+            pool: self.pool,
+            proxy_mode: self.proxy_mode,
+            dns_failure_ttl: self
+                .dns_failure_ttl
+                .unwrap_or(http::DEFAULT_DNS_FAILURE_TTL),
+            detection_cache_size: self
+                .detection_cache_size
+                .unwrap_or(detect::DEFAULT_DETECTION_CACHE_SIZE),
+            max_bisect_requests: self
+                .max_bisect_requests
+                .unwrap_or(DEFAULT_MAX_BISECT_REQUESTS),
+            default_options: self.default_options,
+            clock: self.clock.unwrap_or_else(|| Arc::new(TokioClock)),
+            metrics_recorder: self.metrics_recorder,
+        }
+    }
+}
+
+/// 计算缓存过期时间时从 token 的 `exp` 中扣除的时长，用于容忍本地与服务端的时钟偏差
+const TOKEN_EXPIRY_SAFETY_MARGIN: Duration = Duration::from_secs(30);
+
+/// token 剩余有效期不足该时长时提前刷新
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// 获取 token 的最大尝试次数（包括返回无效 token 的情况）
+const AUTH_ATTEMPTS: u32 = 3;
+
+/// 获取 token 失败后重试前的等待时长
+const AUTH_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// 启用 `free_tier_hardening` 时在重试等待中额外加入的随机时长上限
+const AUTH_RETRY_JITTER: Duration = Duration::from_secs(1);
+
+/// 启用 `free_tier_hardening` 时，token 地址连续返回 429 达到该次数后暂停获取 token
+const AUTH_BREAKER_THRESHOLD: u32 = 3;
+
+/// 首次暂停获取 token 的时长，之后每次仍收到 429 时加倍
+const AUTH_BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// 暂停获取 token 的最长时长
+const AUTH_BREAKER_MAX_COOLDOWN: Duration = Duration::from_secs(600);
+
+/// 获取 token 失败后直接返回错误的时长，之后每次仍然失败时加倍
+const AUTH_FAILURE_COOLDOWN: Duration = Duration::from_secs(5);
+
+/// 获取 token 失败后直接返回错误的最长时长
+const AUTH_FAILURE_MAX_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// 自动认证的当前状态，由 [`MicrosoftTranslator::auth_status`] 返回
+#[derive(Debug)]
+pub struct AuthStatus {
+    /// 是否使用配置的 API Key，为 true 时不请求 token 地址，其余字段均为空
+    pub uses_api_key: bool,
+    /// 缓存的 token 的过期时间，None 表示还没有获取过 token 或已被清除
+    pub token_expires_at: Option<Instant>,
+    /// 连续获取 token 失败的次数，成功后清零
+    pub consecutive_failures: u32,
+    /// 最近一次获取 token 失败的错误，成功后清除
+    pub last_error: Option<TranslationError>,
+    /// 距离允许再次请求 token 地址的时长，None 表示现在就可以请求
+    pub retry_after: Option<Duration>,
+}
+
+impl Clone for AuthStatus {
+    fn clone(&self) -> Self {
+        Self {
+            uses_api_key: self.uses_api_key,
+            token_expires_at: self.token_expires_at,
+            consecutive_failures: self.consecutive_failures,
+            last_error: self.last_error.as_ref().map(TranslationError::duplicate),
+            retry_after: self.retry_after,
+        }
+    }
+}
+
+/// 获取 token 失败的状态，所有请求共享
+///
+/// 一次获取（包括其中的重试）失败后，冷却期内的请求直接返回错误，不再请求 token 地址；
+/// 冷却期结束后允许一次获取，仍然失败时冷却期加倍
+#[derive(Debug, Default)]
+struct AuthFailures {
+    /// 连续失败的次数，成功获取 token 后清零
+    consecutive: u32,
+    /// 最近一次失败的错误
+    last_error: Option<TranslationError>,
+    /// 冷却期的结束时间
+    retry_at: Option<Instant>,
+}
+
+impl AuthFailures {
+    /// 冷却期内返回不可重试的错误：最近一次是网络错误时返回类别和主机名相同、记录了冷却期
+    /// 剩余时长的网络错误，否则返回包含最近一次错误的 `AuthenticationError`
+    fn check(&self, now: Instant) -> Result<(), TranslationError> {
+        let (Some(error), Some(remaining)) = (&self.last_error, self.remaining(now)) else {
+            return Ok(());
+        };
+        if let TranslationError::NetworkError { kind, host, .. } = error {
+            return Err(TranslationError::NetworkError {
+                kind: *kind,
+                host: host.clone(),
+                source: None,
+                cooldown: Some(remaining),
+            });
+        }
+        let failures = match self.consecutive {
+            1 => "once".to_string(),
+            n => format!("{} consecutive times", n),
+        };
+        Err(TranslationError::AuthenticationError(format!(
+            "Microsoft Translator authorization failed {}; not retrying for {}s: {}",
+            failures,
+            remaining.as_secs_f64().ceil(),
+            error
+        )))
+    }
+
+    /// 冷却期的剩余时长
+    fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.retry_at
+            .filter(|retry_at| *retry_at > now)
+            .map(|retry_at| retry_at - now)
+    }
+
+    /// 记录一次失败并开始冷却
+    fn record(&mut self, error: &TranslationError, now: Instant) {
+        self.consecutive += 1;
+        let cooldown = AUTH_FAILURE_COOLDOWN
+            .saturating_mul(2u32.saturating_pow(self.consecutive - 1))
+            .min(AUTH_FAILURE_MAX_COOLDOWN);
+        self.last_error = Some(error.duplicate());
+        self.retry_at = Some(now + cooldown);
+    }
+}
+
+/// token 地址的限流状态，仅在启用 `free_tier_hardening` 时使用
+#[derive(Debug, Default)]
+struct AuthBreaker {
+    /// 连续收到 429 的次数，成功获取 token 后清零
+    consecutive_throttles: u32,
+    /// 暂停获取 token 直到该时间
+    open_until: Option<Instant>,
+}
+
+impl AuthBreaker {
+    /// 暂停期间返回错误；暂停结束后允许一次尝试，仍收到 429 时以加倍的时长再次暂停
+    fn check(&self, now: Instant) -> Result<(), TranslationError> {
+        match self.open_until {
+            Some(until) if until > now => Err(self.open_error(now)),
+            _ => Ok(()),
         }
     }
+
+    /// 暂停期间返回的错误
+    fn open_error(&self, now: Instant) -> TranslationError {
+        let remaining = self
+            .open_until
+            .map_or(Duration::ZERO, |until| until.saturating_duration_since(now));
+        TranslationError::AuthenticationError(format!(
+            "Microsoft Translator auth endpoint throttled {} consecutive requests; not retrying for {}s",
+            self.consecutive_throttles,
+            remaining.as_secs_f64().ceil()
+        ))
+    }
+
+    /// 记录一次 429，达到阈值时开始暂停并返回 true
+    fn record_throttle(&mut self, now: Instant) -> bool {
+        self.consecutive_throttles += 1;
+        let Some(trips) = self
+            .consecutive_throttles
+            .checked_sub(AUTH_BREAKER_THRESHOLD)
+        else {
+            return false;
+        };
+        let cooldown = AUTH_BREAKER_COOLDOWN
+            .saturating_mul(2u32.saturating_pow(trips))
+            .min(AUTH_BREAKER_MAX_COOLDOWN);
+        self.open_until = Some(now + cooldown);
+        true
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenClaims {
+    exp: f64,
+}
+
+/// 校验认证接口返回的 JWT，返回其 `exp` 声明（Unix 时间戳，秒）
+///
+/// token 必须非空，由三段 base64url 编码的内容组成，且负载中带有未过期的 `exp`
+fn validate_token(token: &str, unix_now: u64) -> Result<u64, String> {
+    use base64::Engine;
+
+    if token.is_empty() {
+        return Err("token is empty".to_string());
+    }
+    let segments: Vec<&str> = token.split('.').collect();
+    if segments.len() != 3 {
+        return Err(format!(
+            "expected 3 dot-separated segments, got {}",
+            segments.len()
+        ));
+    }
+    let is_base64url = |segment: &str| {
+        !segment.is_empty()
+            && segment
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    };
+    if !segments.iter().all(|segment| is_base64url(segment)) {
+        return Err("segments are not base64url encoded".to_string());
+    }
+    let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(segments[1])
+        .map_err(|e| format!("payload is not base64url encoded: {}", e))?;
+    let claims: TokenClaims = serde_json::from_slice(&payload)
+        .map_err(|e| format!("payload has no valid exp claim: {}", e))?;
+    if !claims.exp.is_finite() || claims.exp <= unix_now as f64 {
+        return Err(format!("token expired at {}", claims.exp));
+    }
+    Ok(claims.exp as u64)
+}
+
+/// 根据 token 的 `exp` 计算缓存过期时间
+fn token_expiry(exp: u64, unix_now: u64, now: Instant) -> Instant {
+    let remaining = Duration::from_secs(exp.saturating_sub(unix_now));
+    now + remaining.saturating_sub(TOKEN_EXPIRY_SAFETY_MARGIN)
+}
+
+/// 当前的 Unix 时间戳（秒）
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// 判断缓存的 token 在 `now` 时是否仍可使用
+fn token_is_fresh(expiry: Instant, now: Instant) -> bool {
+    expiry.saturating_duration_since(now) > TOKEN_REFRESH_MARGIN
 }
 
 /// 微软翻译器错误响应
@@ -84,8 +805,173 @@ struct MicrosoftErrorDetails {
     message: String,
 }
 
+/// 将微软翻译的错误响应解析为 [`TranslationError::Provider`]
+///
+/// 错误码为 6 位数字，前 3 位与 HTTP 状态码一致。限流（429xxx）、
+/// 请求超时（408xxx）和服务端错误（5xxxxx）可以重试；配额耗尽（403001）
+/// 等其他错误不可重试。无法解析时返回 None。
+pub(crate) fn parse_error_body(
+    status: reqwest::StatusCode,
+    body: &str,
+) -> Option<TranslationError> {
+    let error = serde_json::from_str::<MicrosoftErrorResponse>(body)
+        .ok()?
+        .error;
+    let retryable = matches!(error.code / 1000, 408 | 429 | 500..=599);
+    Some(TranslationError::Provider {
+        provider: "microsoft".to_string(),
+        code: error.code.to_string(),
+        message: error.message,
+        http_status: Some(status),
+        retryable,
+        trace: None,
+    })
+}
+
+/// 由单条文本引起、拆分批次可以定位的错误：请求中有无效的输入（400000）
+/// 或文本过长（400050）
+const ITEM_ERROR_CODES: [&str; 2] = ["400000", "400050"];
+
+/// 错误是否可能只由批次中的部分文本引起
+fn is_item_error(error: &TranslationError) -> bool {
+    matches!(
+        error,
+        TranslationError::Provider { code, .. } if ITEM_ERROR_CODES.contains(&code.as_str())
+    )
+}
+
+/// 目标类别下的自定义系统不存在（400079），或语言对与类别的组合无效（400075）
+const CUSTOM_MODEL_ERROR_CODES: [&str; 2] = ["400075", "400079"];
+
+/// 禁止回退时，将自定义模型相关的错误转换为 [`TranslationError::CustomModelUnavailable`]
+fn custom_model_error(error: TranslationError, config: &MicrosoftConfig) -> TranslationError {
+    let (Some(category), Some(false)) = (&config.category, config.allow_fallback) else {
+        return error;
+    };
+    match error {
+        TranslationError::Provider { code, message, .. }
+            if CUSTOM_MODEL_ERROR_CODES.contains(&code.as_str()) =>
+        {
+            TranslationError::CustomModelUnavailable {
+                provider: "microsoft".to_string(),
+                category: category.clone(),
+                message,
+            }
+        }
+        error => error,
+    }
+}
+
+/// API 版本参数缺失或无效
+const API_VERSION_ERROR_CODE: &str = "400021";
+
+/// 将服务端不支持 API 版本的错误转换为指明所配置版本的 [`TranslationError::ConfigurationError`]
+fn api_version_error(error: TranslationError, api_version: &str) -> TranslationError {
+    match error {
+        TranslationError::Provider { code, message, .. } if code == API_VERSION_ERROR_CODE => {
+            TranslationError::ConfigurationError(format!(
+                "Microsoft Translator does not support api_version '{}': {}",
+                api_version, message
+            ))
+        }
+        error => error,
+    }
+}
+
+/// 请求中被改写的语言代码，形如 `{"to": {"requested": "zh-CN", "sent": "zh-Hans"}}`，
+/// 没有改写时返回 None
+fn language_mapping(
+    target_lang: &LanguageIdentifier,
+    source_lang: Option<&LanguageIdentifier>,
+) -> Option<serde_json::Value> {
+    let mut mapping = serde_json::Map::new();
+    for (param, lang) in [("to", Some(target_lang)), ("from", source_lang)] {
+        let Some(lang) = lang else { continue };
+        let (requested, sent) = (lang.to_string(), langmap::microsoft_code(lang));
+        if requested != sent {
+            mapping.insert(
+                param.to_string(),
+                serde_json::json!({"requested": requested, "sent": sent}),
+            );
+        }
+    }
+    (!mapping.is_empty()).then_some(serde_json::Value::Object(mapping))
+}
+
+/// 响应头 `X-MT-System` 标明的翻译系统
+fn translation_system(response: &reqwest::Response) -> Option<String> {
+    header_value(response, "X-MT-System")
+}
+
+/// 响应头 `X-RequestId`：服务为请求分配的标识，向微软报告问题时需要提供
+fn request_id(response: &reqwest::Response) -> Option<String> {
+    header_value(response, "X-RequestId")
+}
+
+fn header_value(response: &reqwest::Response, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// 按单次请求的字符数、条数和请求体字节数限制把文本依次分组
+///
+/// 单条文本的请求体就超出 `max_bytes` 时返回 [`TranslationError::TextTooLong`]
+fn request_groups<'a>(
+    texts: impl IntoIterator<Item = &'a str>,
+    max_bytes: Option<usize>,
+) -> Result<Vec<Vec<&'a str>>, TranslationError> {
+    let max_bytes = max_bytes.unwrap_or(usize::MAX);
+    let mut groups: Vec<Vec<&str>> = Vec::new();
+    let (mut group_len, mut group_bytes) = (0, 0);
+    for text in texts {
+        let len = preflight::char_len(text);
+        let bytes = item_bytes(text);
+        preflight::check_length(bytes + 2, Some(max_bytes))?;
+        match groups.last_mut() {
+            Some(group)
+                if group_len + len <= MAX_REQUEST_CHARS
+                    && group.len() < MAX_REQUEST_ITEMS
+                    && group_bytes + 1 + bytes <= max_bytes =>
+            {
+                group.push(text);
+                group_len += len;
+                group_bytes += 1 + bytes;
+            }
+            _ => {
+                groups.push(vec![text]);
+                group_len = len;
+                group_bytes = bytes + 2;
+            }
+        }
+    }
+    Ok(groups)
+}
+
+/// 一条文本在请求体数组中占用的字节数，不含分隔的逗号
+fn item_bytes(text: &str) -> usize {
+    preflight::json_len(&BatchTranslationRequest { text })
+}
+
+/// 批量请求的请求体字节数，与 `build_request` 序列化的结果一致
+fn request_bytes(texts: &[&str]) -> usize {
+    2 + texts.iter().map(|text| item_bytes(text)).sum::<usize>() + texts.len().saturating_sub(1)
+}
+
+/// 检查每一项都至少包含一条译文，否则批量结果会与输入错位
+fn ensure_translated(translation: &MicrosoftTranslation) -> Result<(), TranslationError> {
+    if translation.translations.is_empty() {
+        return Err(TranslationError::ServiceError(
+            "No translation results returned".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 /// 微软翻译检测到的语言信息
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct DetectedLanguage {
     pub language: String,
     pub score: f64,
@@ -96,20 +982,81 @@ pub struct DetectedLanguage {
 pub struct MicrosoftTranslation {
     #[serde(rename = "detectedLanguage")]
     pub detected_language: Option<DetectedLanguage>,
+    /// 服务端偶尔返回空数组或 `null`，解析时视为空数组，随后按无结果报错
+    #[serde(default, deserialize_with = "http::null_as_default")]
     pub translations: Vec<TranslationResult>,
+    /// 该结果对应的原始响应项，仅在启用 `include_raw_response` 时提供；
+    /// 文本被拆分翻译时为各片段响应项组成的数组
+    #[serde(skip)]
+    pub raw: Option<serde_json::Value>,
+    /// 响应头 `X-MT-System` 标明的翻译系统：`Custom` 表示使用了自定义模型，
+    /// `Team` 表示通用模型
+    #[serde(skip)]
+    pub system: Option<String>,
+    /// 响应头 `X-RequestId` 中服务为该请求分配的标识
+    #[serde(skip)]
+    pub request_id: Option<String>,
+    /// 收到响应的时间
+    #[serde(skip)]
+    pub received_at: Option<SystemTime>,
+    /// 是否为翻译失败后按 `on_failure` 原样返回的原文，这类结果不应被缓存；
+    /// 批量翻译中因 `max_batch_chars` 预算用完而跳过的条目同样以原文返回
+    #[serde(skip)]
+    pub passthrough: bool,
+    /// 是否只翻译了按 `auto_split` 拆分出的前几个片段：批量翻译的预算在这一条中间用完，
+    /// 译文只包含预算内的片段
+    #[serde(skip)]
+    pub partial: bool,
+    /// 原样返回或只翻译了一部分的原因
+    #[serde(skip)]
+    pub error: Option<String>,
+}
+
+/// [`MicrosoftTranslator::translate_batch_isolated`] 的结果
+#[derive(Debug)]
+pub struct BatchResults {
+    /// 按输入顺序排列的翻译结果，失败的条目为 None
+    pub translations: Vec<Option<MicrosoftTranslation>>,
+    /// 失败的条目及其错误
+    pub report: BatchErrorReport,
+}
+
+impl MicrosoftTranslation {
+    /// 翻译失败后原样返回的原文
+    fn pass_through(
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        error: &TranslationError,
+    ) -> Self {
+        Self {
+            detected_language: None,
+            translations: vec![TranslationResult {
+                text: text.to_string(),
+                to: langmap::microsoft_code(target_lang),
+            }],
+            raw: None,
+            system: None,
+            request_id: None,
+            received_at: None,
+            passthrough: true,
+            partial: false,
+            error: Some(error.to_string()),
+        }
+    }
 }
 
 /// 翻译结果
 #[derive(Debug, Deserialize)]
 pub struct TranslationResult {
     pub text: String,
+    #[serde(default)]
     pub to: String,
 }
 
 /// 用于批量文本翻译的请求
 #[derive(Serialize)]
-struct BatchTranslationRequest {
-    text: String,
+struct BatchTranslationRequest<'a> {
+    text: &'a str,
 }
 
 /// 微软翻译器实现
@@ -119,48 +1066,232 @@ struct BatchTranslationRequest {
 /// 2. API Key认证：使用用户提供的API密钥
 pub struct MicrosoftTranslator {
     client: Client,
+    /// 最近解析失败的主机名
+    dns_failures: http::DnsFailureCache,
     config: MicrosoftConfig,
-    semaphore: Arc<Semaphore>,
+    concurrency: ConcurrencyLimiter,
+    /// 并发许可的排队指标
+    queue_metrics: Arc<QueueMetrics>,
+    /// 相邻请求之间的最小间隔（整个翻译器共用）
+    rate_limiter: RateLimiter,
     cached_token: Arc<Mutex<Option<String>>>,
     token_expiry: Arc<Mutex<Option<Instant>>>,
+    /// token 地址的限流状态
+    auth_breaker: std::sync::Mutex<AuthBreaker>,
+    /// 获取 token 失败后的冷却状态
+    auth_failures: std::sync::Mutex<AuthFailures>,
+    /// 自动选择的端点，首次测速成功后缓存
+    selected_endpoint: OnceCell<String>,
+    /// 批量翻译的启动延迟
+    start_jitter: batch::StartJitter,
+    /// `/detect` 接口的检测结果
+    detection_cache: DetectionCache<DetectedLanguage>,
 }
 
 impl MicrosoftTranslator {
     /// 创建新的微软翻译器实例
     pub fn new(config: MicrosoftConfig) -> Self {
-        let concurrent_limit = config.concurrent_limit;
+        let queue_metrics = Arc::new(QueueMetrics::new(
+            "microsoft",
+            config.metrics_recorder.clone(),
+        ));
+        let concurrency = ConcurrencyLimiter::new(config.concurrent_limit, config.max_queue_depth)
+            .adaptive(config.adaptive_concurrency)
+            .metrics(queue_metrics.clone());
+        let rate_limiter = RateLimiter::new(0, None, config.min_request_interval);
+        let detection_cache = DetectionCache::new(config.detection_cache_size);
         Self {
-            client: Client::new(),
+            client: http::build_client(&config.pool, &config.proxy_mode),
+            dns_failures: http::DnsFailureCache::new(config.dns_failure_ttl),
             config,
-            semaphore: Arc::new(Semaphore::new(concurrent_limit)),
+            concurrency,
+            queue_metrics,
+            rate_limiter,
             cached_token: Arc::new(Mutex::new(None)),
             token_expiry: Arc::new(Mutex::new(None)),
+            auth_breaker: std::sync::Mutex::new(AuthBreaker::default()),
+            auth_failures: std::sync::Mutex::new(AuthFailures::default()),
+            selected_endpoint: OnceCell::new(),
+            start_jitter: batch::StartJitter::default(),
+            detection_cache,
         }
     }
 
-    /// 获取认证token，带缓存和过期处理
-    async fn get_auth_token(&self) -> Result<String, TranslationError> {
-        // 如果配置了API Key，直接使用
-        if let Some(api_key) = &self.config.api_key {
-            return Ok(api_key.clone());
-        }
+    /// 合并调用方的选项与配置中的默认选项
+    fn effective_options(&self, options: &TranslateOptions) -> EffectiveOptions {
+        EffectiveOptions::resolve(
+            options,
+            &self.config.default_options,
+            &EffectiveOptions::default(),
+        )
+        .start_deadline(self.config.clock.as_ref())
+    }
 
-        let mut token_guard = self.cached_token.lock().await;
-        let mut expiry_guard = self.token_expiry.lock().await;
+    /// 影响译文的输入，用于缓存键和译文的来源信息
+    fn key_inputs(
+        &self,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+    ) -> CacheKeyInputs {
+        let mut inputs = CacheKeyInputs::new("microsoft").option("html", options.html);
+        if let Some(category) = &self.config.category {
+            inputs = inputs.model(category);
+        }
+        if let Some(allow_fallback) = self.config.allow_fallback {
+            inputs = inputs.option("allow_fallback", allow_fallback);
+        }
+        if let Some(normalization) = options.normalize_input {
+            inputs = inputs.option("normalize_input", format!("{:?}", normalization));
+        }
+        if options.protects() {
+            inputs = inputs
+                .option("protect_numbers", options.protect_numbers)
+                .option(
+                    "protect_patterns",
+                    format!("{:?}", options.protect_patterns),
+                );
+        }
+        // 自动检测源语言时，建议的源语言和低置信度的处理方式会影响译文
+        if source_lang.is_none() {
+            if let Some(suggested_from) = &self.config.suggested_from {
+                inputs = inputs.option("suggested_from", langmap::microsoft_code(suggested_from));
+            }
+            if let Some(min_score) = self.config.min_detection_score {
+                inputs = inputs.option("min_detection_score", min_score).option(
+                    "on_low_confidence",
+                    format!("{:?}", self.config.on_low_confidence),
+                );
+            }
+        }
+        inputs
+    }
+
+    /// 确定使用的端点：`endpoint` > 自动选择 > `region` > 默认端点
+    async fn configured_endpoint(&self) -> &str {
+        if self.config.endpoint.is_none() && self.config.auto_select_endpoint {
+            // 探测失败时下次请求重新探测
+            let _ = self
+                .selected_endpoint
+                .get_or_try_init(|| self.probe_endpoints())
+                .await;
+        }
+        self.endpoint_without_probe()
+    }
+
+    /// 不探测端点时使用的端点：`endpoint` > 已选出的端点 > `region` > 默认端点
+    fn endpoint_without_probe(&self) -> &str {
+        if let Some(endpoint) = &self.config.endpoint {
+            return endpoint;
+        }
+        if let Some(endpoint) = self.selected_endpoint.get() {
+            return endpoint;
+        }
+        match self.config.region {
+            Some(region) => region.endpoint(),
+            None => DEFAULT_ENDPOINT,
+        }
+    }
+
+    /// 同时请求各候选端点的语言列表，返回最先成功响应的端点
+    async fn probe_endpoints(&self) -> Result<String, TranslationError> {
+        if self.config.endpoint_candidates.is_empty() {
+            return Err(TranslationError::ConfigurationError(
+                "No endpoint candidates configured".to_string(),
+            ));
+        }
+        let probes = self
+            .config
+            .endpoint_candidates
+            .iter()
+            .map(|endpoint| Box::pin(self.probe_endpoint(endpoint)));
+        let (endpoint, _) = select_ok(probes).await?;
+        Ok(endpoint.to_string())
+    }
+
+    /// 请求端点的语言列表，成功时返回该端点
+    async fn probe_endpoint<'a>(&self, endpoint: &'a str) -> Result<&'a str, TranslationError> {
+        let response = self
+            .client
+            .get(format!("{}/languages", endpoint))
+            .query(&[
+                ("api-version", self.api_version()),
+                ("scope", "translation"),
+            ])
+            .timeout(ENDPOINT_PROBE_TIMEOUT)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(TranslationError::HttpError {
+                status,
+                body: String::new(),
+                trace: None,
+            });
+        }
+        Ok(endpoint)
+    }
+
+    /// 获取认证token，带缓存和过期处理
+    async fn get_auth_token(&self) -> Result<String, TranslationError> {
+        // 如果配置了API Key，直接使用
+        if let Some(api_key) = &self.config.api_key {
+            return Ok(api_key.expose_secret().to_string());
+        }
+
+        let mut token_guard = self.cached_token.lock().await;
+        let mut expiry_guard = self.token_expiry.lock().await;
 
-        // 检查缓存的token是否仍然有效（有效期通常为10分钟，我们提前1分钟刷新）
+        // 检查缓存的token是否仍然有效（距离过期不足1分钟时提前刷新）
         if let (Some(token), Some(expiry)) = (token_guard.as_ref(), expiry_guard.as_ref()) {
-            if expiry.saturating_duration_since(Instant::now()) > Duration::from_secs(60) {
+            if token_is_fresh(*expiry, self.config.clock.now()) {
                 return Ok(token.clone());
             }
         }
 
-        // 获取新的token
-        let mut auth_attempts = 3;
-        while auth_attempts > 0 {
-            auth_attempts -= 1;
+        // 同一时间只有一个请求获取 token，其余请求等待锁后使用它的结果：成功时使用缓存的
+        // token，失败时在冷却期内直接返回错误
+        let auth_endpoint = self
+            .config
+            .auth_endpoint
+            .as_deref()
+            .unwrap_or(DEFAULT_AUTH_ENDPOINT);
+        let hardening = self.config.free_tier_hardening;
+        let now = self.config.clock.now();
+        self.auth_failures.lock().unwrap().check(now)?;
+        if hardening {
+            self.auth_breaker.lock().unwrap().check(now)?;
+        }
+        match self.request_auth_token(auth_endpoint, hardening).await {
+            Ok((token, expiry)) => {
+                *self.auth_failures.lock().unwrap() = AuthFailures::default();
+                *token_guard = Some(token.clone());
+                *expiry_guard = Some(expiry);
+                Ok(token)
+            }
+            Err(error) => {
+                let now = self.config.clock.now();
+                // 限流暂停期间由 AuthBreaker 决定何时再次请求
+                let throttled = hardening && self.auth_breaker.lock().unwrap().check(now).is_err();
+                if !throttled {
+                    self.auth_failures.lock().unwrap().record(&error, now);
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// 请求 token 地址，失败时重试，返回 token 和缓存的过期时间
+    async fn request_auth_token(
+        &self,
+        auth_endpoint: &str,
+        hardening: bool,
+    ) -> Result<(String, Instant), TranslationError> {
+        // 返回的token无效时同样重新获取。与翻译请求共用同一个客户端，因此使用相同的代理设置
+        let mut invalid_reason = None;
+        for attempt in 1..=AUTH_ATTEMPTS {
+            let last_attempt = attempt == AUTH_ATTEMPTS;
             match self.client
-                .get("https://edge.microsoft.com/translate/auth")
+                .get(auth_endpoint)
                 .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36")
                 .send()
                 .await
@@ -168,14 +1299,37 @@ impl MicrosoftTranslator {
                 Ok(response) => {
                     if response.status().is_success() {
                         let token = response.text().await.map_err(|e| {
-                            TranslationError::AuthenticationError(format!("Failed to read auth response: {}", e))
+                            if error::is_timeout(&e) {
+                                TranslationError::TimeoutError
+                            } else {
+                                TranslationError::AuthenticationError(format!("Failed to read auth response: {}", e))
+                            }
                         })?;
-                        // 缓存新的token和过期时间
-                        *token_guard = Some(token.clone());
-                        *expiry_guard = Some(Instant::now() + Duration::from_secs(540)); // 9分钟后过期
-                        return Ok(token);
+                        let token = token.trim();
+                        let now = unix_now();
+                        match validate_token(token, now) {
+                            Ok(exp) => {
+                                if hardening {
+                                    *self.auth_breaker.lock().unwrap() = AuthBreaker::default();
+                                }
+                                // 按token自身的过期时间计算缓存有效期
+                                return Ok((
+                                    token.to_string(),
+                                    token_expiry(exp, now, self.config.clock.now()),
+                                ));
+                            }
+                            Err(reason) => invalid_reason = Some(reason),
+                        }
                     } else {
-                        if auth_attempts <= 0 {
+                        if hardening && response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                            let now = self.config.clock.now();
+                            let mut breaker = self.auth_breaker.lock().unwrap();
+                            if breaker.record_throttle(now) {
+                                // 不再重试，之后的调用在暂停结束前直接失败
+                                return Err(breaker.open_error(now));
+                            }
+                        }
+                        if last_attempt {
                             return Err(TranslationError::AuthenticationError(
                                 format!("Failed to authenticate with Microsoft Translator: HTTP {}", response.status())
                             ));
@@ -183,15 +1337,30 @@ impl MicrosoftTranslator {
                     }
                 }
                 Err(e) => {
-                    if auth_attempts <= 0 {
-                        return Err(TranslationError::NetworkError(e));
+                    if last_attempt {
+                        return Err(e.into());
                     }
                 }
             }
-            sleep(Duration::from_secs(1)).await;
+            if !last_attempt {
+                let jitter = if hardening {
+                    rand::rng().random_range(Duration::ZERO..AUTH_RETRY_JITTER)
+                } else {
+                    Duration::ZERO
+                };
+                self.config.clock.sleep(AUTH_RETRY_DELAY + jitter).await;
+            }
         }
         Err(TranslationError::AuthenticationError(
-            "Failed to get Microsoft Translator authorization after retries".to_string(),
+            match invalid_reason {
+                Some(reason) => format!(
+                    "invalid token received from Microsoft Translator auth endpoint after {} attempts: {}",
+                    AUTH_ATTEMPTS, reason
+                ),
+                None => {
+                    "Failed to get Microsoft Translator authorization after retries".to_string()
+                }
+            },
         ))
     }
 
@@ -205,154 +1374,976 @@ impl MicrosoftTranslator {
     ///
     /// # 参数
     ///
-    /// * `texts` - 需要翻译的文本数组
+    /// * `texts` - 需要翻译的文本，例如 `&[&str]`、`Vec<String>` 或产生字符串的迭代器
     /// * `target_lang` - 目标语言标识符
     /// * `source_lang` - 源语言标识符 (None表示自动检测)
     /// * `options` - 翻译配置选项
     ///
     /// # 返回值
     ///
-    /// 返回翻译结果数组
-    pub async fn translate_batch(
+    /// 返回翻译结果数组，顺序与输入一致。整批翻译失败且 `on_failure` 要求原样返回时，
+    /// 每一项都是标记为 [`MicrosoftTranslation::passthrough`] 的原文。启用
+    /// `truncate_to_budget` 时只发送 `max_batch_chars` 预算内的前几条，不论 `on_failure`
+    /// 如何，其余条目都以标记为 `passthrough` 的原文返回，[`MicrosoftTranslation::error`]
+    /// 为 [`TranslationError::BudgetExceeded`] 的错误信息；同时启用 `auto_split` 时，预算在
+    /// 其中间用完的那一条只翻译预算内的片段，结果标记为 [`MicrosoftTranslation::partial`]
+    pub async fn translate_batch<I>(
+        &self,
+        texts: I,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<Vec<MicrosoftTranslation>, TranslationError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let options = &self.effective_options(options);
+        let texts: Vec<I::Item> = texts.into_iter().collect();
+        let texts: Vec<&str> = texts.iter().map(|text| text.as_ref()).collect();
+        let (translations, _) = self
+            .translate_batch_budgeted(&texts, target_lang, source_lang, options, true)
+            .await?;
+        Ok(translations)
+    }
+
+    /// 按 `max_batch_chars` 截断后批量翻译，返回所有条目的结果和完整翻译的前几条的数量
+    ///
+    /// `split` 为 true 且启用 `auto_split` 时按片段截断，见 [`Self::translate_batch`]
+    async fn translate_batch_budgeted(
         &self,
         texts: &[&str],
         target_lang: &LanguageIdentifier,
         source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+        split: bool,
+    ) -> Result<(Vec<MicrosoftTranslation>, usize), TranslationError> {
+        let truncation = self.truncate_batch(texts, options, split)?;
+        let sent = truncation.sent(texts);
+        let mut translations = match self
+            .translate_batch_effective(&sent, target_lang, source_lang, options)
+            .await
+        {
+            Err(error) if options.on_failure.returns_original(&error) => texts[..sent.len()]
+                .iter()
+                .map(|text| MicrosoftTranslation::pass_through(text, target_lang, &error))
+                .collect(),
+            result => result?,
+        };
+        if let Some(error) = &truncation.error {
+            if truncation.prefix.is_some() {
+                mark_partial(&mut translations[truncation.fit], error);
+            }
+            translations.extend(
+                texts[truncation.skipped_from()..]
+                    .iter()
+                    .map(|text| MicrosoftTranslation::pass_through(text, target_lang, error)),
+            );
+        }
+        Ok((translations, truncation.fit))
+    }
+
+    /// 按 `max_batch_chars` 截断批次，`split` 为 true 且启用 `auto_split` 时按与拆分翻译相同的
+    /// 长度限制拆分预算在其中间用完的那一条
+    fn truncate_batch(
+        &self,
+        texts: &[&str],
+        options: &EffectiveOptions,
+        split: bool,
+    ) -> Result<batch::Truncation, TranslationError> {
+        let budget = batch::CharBudget::of(options);
+        if split && options.auto_split {
+            budget.apply_split(texts, MAX_REQUEST_CHARS, preflight::char_len)
+        } else {
+            budget.apply(texts)
+        }
+    }
+
+    /// 批量翻译文本，个别文本导致整批失败时定位出这些文本，其余文本照常返回译文
+    ///
+    /// 整批请求因单条文本引起的错误（无效输入或文本过长）被拒绝时，把批次对半拆分后
+    /// 分别重新发送，继续拆分失败的一半，直到找出出错的条目。额外发送的请求数不超过
+    /// `max_bisect_requests`，达到上限时尚未定位的子批次中的条目都记为失败。
+    /// 其他错误（例如认证失败）直接返回；`on_failure` 不适用于此方法。启用
+    /// `truncate_to_budget` 时超出 `max_batch_chars` 的条目不发送，记为失败；同时启用
+    /// `auto_split` 时与 [`Self::translate_batch`] 相同，预算在其中间用完的那一条只翻译预算内的片段。
+    ///
+    /// # 参数
+    ///
+    /// * `texts` - 需要翻译的文本
+    /// * `target_lang` - 目标语言标识符
+    /// * `source_lang` - 源语言标识符 (None表示自动检测)
+    /// * `options` - 翻译配置选项
+    ///
+    /// # 返回值
+    ///
+    /// 返回与输入顺序一致的结果，失败条目的结果为 None，其错误记录在
+    /// [`BatchResults::report`] 中
+    pub async fn translate_batch_isolated<I>(
+        &self,
+        texts: I,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
         options: &TranslateOptions,
-    ) -> Result<Vec<MicrosoftTranslation>, TranslationError> {
-        let mut errors = Vec::new();
-        for attempt in 0..=options.max_retries {
-            if attempt > 0 {
-                let delay = Duration::from_millis(100 * 2u64.pow(attempt - 1));
-                sleep(delay).await;
+    ) -> Result<BatchResults, TranslationError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let options = &self.effective_options(options);
+        let texts: Vec<I::Item> = texts.into_iter().collect();
+        let texts: Vec<&str> = texts.iter().map(|text| text.as_ref()).collect();
+        let mut results = BatchResults {
+            translations: Vec::with_capacity(texts.len()),
+            report: BatchErrorReport::default(),
+        };
+        results.translations.resize_with(texts.len(), || None);
+        let truncation = self.truncate_batch(&texts, options, true)?;
+        if let Some(error) = &truncation.error {
+            results.report.failed.extend(
+                (truncation.skipped_from()..texts.len()).map(|index| (index, error.duplicate())),
+            );
+        }
+        let texts = &truncation.sent(&texts);
+
+        // 待拆分的子批次及其失败的原因
+        let mut failing = match self
+            .translate_batch_effective(texts, target_lang, source_lang, options)
+            .await
+        {
+            Ok(translations) => {
+                for (slot, translation) in results.translations.iter_mut().zip(translations) {
+                    *slot = Some(translation);
+                }
+                Vec::new()
             }
+            Err(error) if is_item_error(&error) => vec![(0..texts.len(), error)],
+            Err(error) => return Err(error),
+        };
+        let mut budget = self.config.max_bisect_requests;
+        while let Some((range, error)) = failing.pop() {
+            if range.len() == 1 || budget == 0 {
+                results
+                    .report
+                    .failed
+                    .extend(range.map(|index| (index, error.duplicate())));
+                continue;
+            }
+            let middle = range.start + range.len() / 2;
+            for half in [range.start..middle, middle..range.end] {
+                if budget == 0 {
+                    failing.push((half, error.duplicate()));
+                    continue;
+                }
+                budget -= 1;
+                match self
+                    .translate_batch_effective(
+                        &texts[half.clone()],
+                        target_lang,
+                        source_lang,
+                        options,
+                    )
+                    .await
+                {
+                    Ok(translations) => {
+                        for (index, translation) in half.zip(translations) {
+                            results.translations[index] = Some(translation);
+                        }
+                    }
+                    Err(error) if is_item_error(&error) => failing.push((half, error)),
+                    Err(error) => return Err(error),
+                }
+            }
+        }
+        results.report.failed.sort_by_key(|(index, _)| *index);
+        if let (Some(error), Some(_)) = (&truncation.error, truncation.prefix)
+            && let Some(translation) = &mut results.translations[truncation.fit]
+        {
+            mark_partial(translation, error);
+        }
+        Ok(results)
+    }
 
-            match self
-                .try_translate_batch(texts, target_lang, source_lang, options)
-                .await
-            {
-                Ok(result) => return Ok(result),
-                Err(e) => {
-                    // 只在可重试的错误上继续
-                    if e.is_retryable() {
-                        errors.push(e);
-                    } else {
-                        return Err(e);
+    /// 流式批量翻译，每解析出一条结果就按输入顺序交给 `sink`
+    ///
+    /// 与 [`Self::translate_batch`] 不同，响应体按块读取并逐项解析，内存中不会同时保留
+    /// 整批结果，适合把译文直接写入文件的场景。超出单次请求长度或条数限制的批次会按顺序
+    /// 分成多个请求依次发送。
+    ///
+    /// 重试只发生在开始读取响应体之前；已经交给 `sink` 的结果不会重复。某个请求失败且
+    /// `on_failure` 要求原样返回时，该请求中尚未交付的文本以原文交付。不支持 `auto_split`，
+    /// 单条文本超出长度限制时在发送任何请求之前返回 [`TranslationError::TextTooLong`]，
+    /// 超出 `max_batch_chars` 时即使启用了 `truncate_to_budget` 也整批拒绝
+    ///
+    /// # 参数
+    ///
+    /// * `texts` - 需要翻译的文本
+    /// * `target_lang` - 目标语言标识符
+    /// * `source_lang` - 源语言标识符 (None表示自动检测)
+    /// * `options` - 翻译配置选项
+    /// * `sink` - 接收 (输入中的下标, 翻译结果) 的回调
+    ///
+    /// # 返回值
+    ///
+    /// 返回交给 `sink` 的结果数量，成功时等于输入的文本数量
+    pub async fn translate_batch_streaming<I, F>(
+        &self,
+        texts: I,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+        mut sink: F,
+    ) -> Result<usize, TranslationError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+        F: FnMut(usize, MicrosoftTranslation),
+    {
+        let options = &self.effective_options(options);
+        let texts: Vec<I::Item> = texts.into_iter().collect();
+        let texts: Vec<&str> = texts.iter().map(|text| text.as_ref()).collect();
+        batch::CharBudget::of(options).check(&texts)?;
+        let protected: Vec<Protected> = texts
+            .iter()
+            .map(|text| options.protect(&normalize::apply(text, options.normalize_input.as_ref())))
+            .collect();
+        for text in &protected {
+            preflight::check_length(preflight::char_len(text.text()), Some(MAX_REQUEST_CHARS))?;
+        }
+
+        let mut delivered = 0;
+        let groups = request_groups(
+            protected.iter().map(Protected::text),
+            self.config.max_request_bytes,
+        )?;
+        for group in groups {
+            let offset = delivered;
+            let result = self
+                .stream_group(
+                    &group,
+                    &protected[offset..offset + group.len()],
+                    offset,
+                    target_lang,
+                    source_lang,
+                    options,
+                    &mut sink,
+                    &mut delivered,
+                )
+                .await;
+            match result {
+                Err(error) if options.on_failure.returns_original(&error) => {
+                    for (index, text) in texts
+                        .iter()
+                        .enumerate()
+                        .take(offset + group.len())
+                        .skip(delivered)
+                    {
+                        sink(
+                            index,
+                            MicrosoftTranslation::pass_through(text, target_lang, &error),
+                        );
+                        delivered += 1;
                     }
                 }
+                result => result?,
+            }
+        }
+        Ok(delivered)
+    }
+
+    /// 发送一组文本并逐项交付还原了占位符的结果，`delivered` 记录已交付的总数
+    #[allow(clippy::too_many_arguments)]
+    async fn stream_group<F>(
+        &self,
+        texts: &[&str],
+        protected: &[Protected],
+        offset: usize,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+        sink: &mut F,
+        delivered: &mut usize,
+    ) -> Result<(), TranslationError>
+    where
+        F: FnMut(usize, MicrosoftTranslation),
+    {
+        let (response, permit) = retry::retry(
+            self.config.clock.as_ref(),
+            options.max_retries,
+            options.deadline,
+            "microsoft",
+            options.on_retry.as_ref(),
+            || self.try_send_batch(texts, target_lang, source_lang, options),
+        )
+        .await?;
+        let system = translation_system(&response);
+        let request_id = request_id(&response);
+        let received_at = self.config.clock.system_time();
+        let mut stream = http::JsonArrayStream::new(response, options)?;
+        // 需要以 suggested_from 重新翻译时，重新翻译的请求要等当前响应读完、释放许可后
+        // 才能发送，从第一条需要重新翻译的结果起暂存到本组结束
+        let defer_low_confidence = source_lang.is_none()
+            && self.config.on_low_confidence == LowConfidencePolicy::UseSuggestedFrom;
+        let mut pending: Vec<MicrosoftTranslation> = Vec::new();
+        let mut received = 0;
+        while let Some(item) = stream.next_value().await? {
+            let Some(text) = texts.get(received) else {
+                return Err(TranslationError::MalformedResponse(format!(
+                    "expected {} results, got more",
+                    texts.len()
+                )));
+            };
+            let mut translation: MicrosoftTranslation = http::decode_json(&item)?;
+            ensure_translated(&translation)?;
+            translation.system = system.clone();
+            translation.request_id = request_id.clone();
+            translation.received_at = Some(received_at);
+            if options.include_raw_response {
+                translation.raw = Some(http::capture_raw(item, options.max_raw_response_bytes));
+            }
+            received += 1;
+            if defer_low_confidence {
+                if !pending.is_empty() || self.is_low_confidence(&translation) {
+                    pending.push(translation);
+                    continue;
+                }
+            } else if source_lang.is_none() {
+                self.handle_low_confidence(
+                    &[text],
+                    std::slice::from_mut(&mut translation),
+                    target_lang,
+                    options,
+                )
+                .await?;
             }
+            restore_placeholders(&protected[received - 1], &mut translation)?;
+            sink(offset + received - 1, translation);
+            *delivered += 1;
+        }
+        if received < texts.len() {
+            return Err(TranslationError::MalformedResponse(format!(
+                "expected {} results, got {}",
+                texts.len(),
+                received
+            )));
+        }
+        drop(stream);
+        drop(permit);
+
+        let start = received - pending.len();
+        self.handle_low_confidence(&texts[start..], &mut pending, target_lang, options)
+            .await?;
+        for (index, mut translation) in pending.into_iter().enumerate() {
+            restore_placeholders(&protected[start + index], &mut translation)?;
+            sink(offset + start + index, translation);
+            *delivered += 1;
         }
-        Err(TranslationError::MaxRetriesExceeded {
-            attempts: options.max_retries + 1,
-            errors,
+        Ok(())
+    }
+
+    /// 自动检测源语言的置信度是否低于 `min_detection_score`
+    fn is_low_confidence(&self, translation: &MicrosoftTranslation) -> bool {
+        self.config.min_detection_score.is_some_and(|min_score| {
+            translation
+                .detected_language
+                .as_ref()
+                .is_some_and(|detected| detected.score < min_score)
         })
     }
 
-    /// 尝试批量翻译文本（无重试）
-    async fn try_translate_batch(
+    /// 规范化输入后批量翻译，并按 `on_low_confidence` 处理检测结果
+    async fn translate_batch_effective(
         &self,
         texts: &[&str],
         target_lang: &LanguageIdentifier,
         source_lang: Option<&LanguageIdentifier>,
-        options: &TranslateOptions,
+        options: &EffectiveOptions,
     ) -> Result<Vec<MicrosoftTranslation>, TranslationError> {
-        // 获取并发许可
-        let _permit =
-            self.semaphore.acquire().await.map_err(|e| {
-                TranslationError::Other(format!("Failed to acquire semaphore: {}", e))
-            })?;
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let protected: Vec<Protected> = texts
+            .iter()
+            .map(|text| options.protect(&normalize::apply(text, options.normalize_input.as_ref())))
+            .collect();
+        let texts: Vec<&str> = protected.iter().map(Protected::text).collect();
+        let mut results = self
+            .request_batch(&texts, target_lang, source_lang, options)
+            .await?;
+        if source_lang.is_none() {
+            self.handle_low_confidence(&texts, &mut results, target_lang, options)
+                .await?;
+        }
+        for (protected, translation) in protected.iter().zip(&mut results) {
+            restore_placeholders(protected, translation)?;
+        }
+        Ok(results)
+    }
 
-        // 获取认证token
-        let token = self.get_auth_token().await?;
+    /// 按 `on_low_confidence` 处理检测置信度过低的结果，只有这些文本会被重新翻译
+    async fn handle_low_confidence(
+        &self,
+        texts: &[&str],
+        results: &mut [MicrosoftTranslation],
+        target_lang: &LanguageIdentifier,
+        options: &EffectiveOptions,
+    ) -> Result<(), TranslationError> {
+        let Some(min_score) = self.config.min_detection_score else {
+            return Ok(());
+        };
+        let Some(first) = results.iter().find_map(|result| {
+            result
+                .detected_language
+                .as_ref()
+                .filter(|detected| detected.score < min_score)
+        }) else {
+            return Ok(());
+        };
+        let low_confidence: Vec<usize> = results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| {
+                result
+                    .detected_language
+                    .as_ref()
+                    .is_some_and(|detected| detected.score < min_score)
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        match self.config.on_low_confidence {
+            LowConfidencePolicy::Error => Err(TranslationError::LowConfidenceDetection {
+                language: first.language.clone(),
+                score: first.score,
+                min_score,
+            }),
+            LowConfidencePolicy::PassThrough => {
+                for &index in &low_confidence {
+                    for translation in &mut results[index].translations {
+                        translation.text = texts[index].to_string();
+                    }
+                }
+                Ok(())
+            }
+            LowConfidencePolicy::UseSuggestedFrom => {
+                let suggested_from = self.config.suggested_from.as_ref().ok_or_else(|| {
+                    TranslationError::ConfigurationError(
+                        "on_low_confidence UseSuggestedFrom requires suggested_from".to_string(),
+                    )
+                })?;
+                let retry_texts: Vec<&str> =
+                    low_confidence.iter().map(|&index| texts[index]).collect();
+                let retried = self
+                    .request_batch(&retry_texts, target_lang, Some(suggested_from), options)
+                    .await?;
+                for (index, result) in low_confidence.into_iter().zip(retried) {
+                    results[index] = result;
+                }
+                Ok(())
+            }
+        }
+    }
 
-        // 确定使用哪个端点
-        let endpoint = self
+    /// 发送批量翻译请求，超出长度限制时按 `auto_split` 拆分，超出 `max_request_bytes`
+    /// 时按请求体大小分组发送
+    async fn request_batch(
+        &self,
+        texts: &[&str],
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+    ) -> Result<Vec<MicrosoftTranslation>, TranslationError> {
+        let total_len = texts.iter().map(|text| preflight::char_len(text)).sum();
+        let fits_bytes = self
             .config
-            .endpoint
-            .as_deref()
-            .unwrap_or("https://api-edge.cognitive.microsofttranslator.com");
-
-        // 根据超时设置创建客户端
-        let client = if let Some(timeout) = options.timeout {
-            Client::builder()
-                .timeout(timeout)
-                .build()
-                .map_err(|e| TranslationError::NetworkError(e))?
-        } else {
-            self.client.clone()
-        };
+            .max_request_bytes
+            .is_none_or(|max| request_bytes(texts) <= max);
+        match preflight::check_length(total_len, Some(MAX_REQUEST_CHARS)) {
+            Ok(()) if fits_bytes => {
+                self.translate_batch_with_retry(texts, target_lang, source_lang, options)
+                    .await
+            }
+            // 每条文本都不超过字符数限制，拆分时只分组
+            Ok(()) => {
+                self.translate_batch_split(texts, target_lang, source_lang, options)
+                    .await
+            }
+            Err(e) if !options.auto_split => Err(e),
+            Err(_) => {
+                self.translate_batch_split(texts, target_lang, source_lang, options)
+                    .await
+            }
+        }
+    }
 
-        // 构造请求
+    /// 拆分超长批次：超长文本拆成片段，按长度限制分组发送，再按原顺序合并
+    async fn translate_batch_split(
+        &self,
+        texts: &[&str],
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+    ) -> Result<Vec<MicrosoftTranslation>, TranslationError> {
+        // 每个片段记录其所属原文的下标
+        let mut pieces = Vec::new();
+        for (index, text) in texts.iter().enumerate() {
+            for segment in preflight::split_text(text, MAX_REQUEST_CHARS, preflight::char_len) {
+                pieces.push((index, segment));
+            }
+        }
+
+        let groups = request_groups(
+            pieces.iter().map(|(_, segment)| segment.text),
+            self.config.max_request_bytes,
+        )?;
+
+        // 只有拆分成多个请求时才同时发出，需要错开
+        let jitter = options.start_jitter.filter(|_| groups.len() > 1);
+        let futures = groups.iter().map(|group| async move {
+            self.start_jitter
+                .wait(jitter, self.config.clock.as_ref())
+                .await;
+            self.translate_batch_with_retry(group, target_lang, source_lang, options)
+                .await
+        });
+        let mut translated = Vec::with_capacity(pieces.len());
+        for result in join_all(futures).await {
+            translated.extend(result?);
+        }
+
+        let mut merged: Vec<MicrosoftTranslation> = Vec::with_capacity(texts.len());
+        for ((index, segment), mut translation) in pieces.into_iter().zip(translated) {
+            if merged.len() == index {
+                for result in &mut translation.translations {
+                    result.text.push_str(segment.separator);
+                }
+                merged.push(translation);
+            } else {
+                let target = &mut merged[index];
+                for (merged, result) in target.translations.iter_mut().zip(translation.translations)
+                {
+                    merged.text.push_str(&result.text);
+                    merged.text.push_str(segment.separator);
+                }
+                target.raw = match (target.raw.take(), translation.raw) {
+                    (Some(serde_json::Value::Array(mut items)), Some(item)) => {
+                        items.push(item);
+                        Some(serde_json::Value::Array(items))
+                    }
+                    (Some(first), Some(item)) => Some(serde_json::Value::Array(vec![first, item])),
+                    (raw, _) => raw,
+                };
+            }
+        }
+        Ok(merged)
+    }
+
+    /// 使用重试逻辑批量翻译文本
+    async fn translate_batch_with_retry(
+        &self,
+        texts: &[&str],
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+    ) -> Result<Vec<MicrosoftTranslation>, TranslationError> {
+        retry::retry(
+            self.config.clock.as_ref(),
+            options.max_retries,
+            options.deadline,
+            "microsoft",
+            options.on_retry.as_ref(),
+            || self.try_translate_batch(texts, target_lang, source_lang, options),
+        )
+        .await
+    }
+
+    /// 构造批量翻译请求，不进行任何网络请求
+    fn build_request(
+        &self,
+        endpoint: &str,
+        texts: &[&str],
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+        auth_header: String,
+    ) -> PreparedRequest {
         let requests: Vec<BatchTranslationRequest> = texts
             .iter()
-            .map(|text| BatchTranslationRequest {
-                text: text.to_string(),
-            })
+            .map(|&text| BatchTranslationRequest { text })
             .collect();
 
         // 构造查询参数
-        let target_lang_str = target_lang.to_string();
-        let source_lang_str = source_lang.map(|s| s.to_string());
-        let mut params = vec![
-            ("api-version", "3.0"),
-            ("to", target_lang_str.as_str()),
-            ("includeSentenceLength", "true"),
+        let mut query = vec![
+            ("api-version", self.api_version().to_string()),
+            ("to", langmap::microsoft_code(target_lang)),
+            ("includeSentenceLength", "true".to_string()),
         ];
+        if let Some(source_lang) = source_lang {
+            query.push(("from", langmap::microsoft_code(source_lang)));
+        } else if let Some(suggested_from) = &self.config.suggested_from {
+            query.push(("suggestedFrom", langmap::microsoft_code(suggested_from)));
+        }
+        if options.html {
+            query.push(("textType", "html".to_string()));
+        }
+        if let Some(category) = &self.config.category {
+            query.push(("category", category.clone()));
+        }
+        if let Some(allow_fallback) = self.config.allow_fallback {
+            query.push(("allowFallback", allow_fallback.to_string()));
+        }
 
-        if let Some(ref source_str) = source_lang_str {
-            params.push(("from", source_str.as_str()));
+        let mut headers = vec![
+            ("Authorization".to_string(), auth_header),
+            ("Content-Type".to_string(), "application/json".to_string()),
+        ];
+        headers.extend(trace::headers(options));
+        PreparedRequest {
+            url: format!("{}/translate", endpoint),
+            query: query
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+            headers,
+            body: serde_json::to_vec(&requests).expect("request body is serializable"),
         }
+    }
 
-        // 确定认证头
-        let auth_header = if self.config.api_key.is_some() {
+    /// 构造批量翻译请求但不发送，用于在不产生费用的情况下排查参数问题
+    ///
+    /// 返回的请求与 [`Self::translate_batch`] 发送的请求一致。启用了
+    /// `auto_select_endpoint` 但尚未选出端点时使用 `region` 或默认端点；未配置
+    /// API Key 时认证头为 `Bearer ***`，token 在发送时才获取。超出长度限制需要
+    /// 拆分的批次不做拆分，预览的是整批文本的请求。
+    pub fn build_request_preview<I>(
+        &self,
+        texts: I,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<RequestPreview, TranslationError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let options = &self.effective_options(options);
+        let endpoint = match &options.endpoint {
+            Some(endpoint) => {
+                http::validate_override_url("endpoint", endpoint)?;
+                endpoint.as_str()
+            }
+            None => self.endpoint_without_probe(),
+        };
+        let texts: Vec<I::Item> = texts.into_iter().collect();
+        let protected: Vec<Protected> = texts
+            .iter()
+            .map(|text| {
+                options.protect(&normalize::apply(
+                    text.as_ref(),
+                    options.normalize_input.as_ref(),
+                ))
+            })
+            .collect();
+        let texts: Vec<&str> = protected.iter().map(Protected::text).collect();
+        let auth_header = match &self.config.api_key {
+            Some(api_key) => format!("Ocp-Apim-Subscription-Key {}", api_key.expose_secret()),
+            None => "Bearer ***".to_string(),
+        };
+        let request = self.build_request(
+            endpoint,
+            &texts,
+            target_lang,
+            source_lang,
+            options,
+            auth_header,
+        );
+        Ok(request.preview(self.config.api_key.as_ref()))
+    }
+
+    /// 确定本次请求使用的端点，单次请求覆盖的端点在认证之前校验
+    async fn request_endpoint<'a>(
+        &'a self,
+        options: &'a EffectiveOptions,
+    ) -> Result<&'a str, TranslationError> {
+        match &options.endpoint {
+            Some(endpoint) => {
+                http::validate_override_url("endpoint", endpoint)?;
+                Ok(endpoint.as_str())
+            }
+            None => Ok(self.configured_endpoint().await),
+        }
+    }
+
+    /// 获取认证token并构造认证头
+    async fn auth_header(&self) -> Result<String, TranslationError> {
+        let token = self.get_auth_token().await?;
+        Ok(if self.config.api_key.is_some() {
             format!("Ocp-Apim-Subscription-Key {}", token)
         } else {
             format!("Bearer {}", token)
-        };
+        })
+    }
 
-        // 发送请求
-        let response = client
-            .post(&format!("{}/translate", endpoint))
-            .header("Authorization", auth_header)
-            .header("Content-Type", "application/json")
-            .query(&params)
-            .json(&requests)
+    /// 发送请求，HTTP 状态码表示失败时解析错误响应
+    async fn send(
+        &self,
+        request: PreparedRequest,
+        options: &EffectiveOptions,
+    ) -> Result<reqwest::Response, TranslationError> {
+        let clock = self.config.clock.as_ref();
+        self.dns_failures.check(&request.url, clock)?;
+        let body_bytes = request.body.len();
+        let mut request_builder = request.into_builder(&self.client);
+        if let Some(timeout) = options.attempt_timeout(clock) {
+            request_builder = request_builder.timeout(timeout);
+        }
+        let response = request_builder
             .send()
+            .await
+            .map_err(|error| self.dns_failures.record(error.into(), clock))?;
+        self.concurrency.observe(response.status());
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        if response.status() == reqwest::StatusCode::PAYLOAD_TOO_LARGE {
+            return Err(TranslationError::RequestTooLarge { bytes: body_bytes });
+        }
+
+        let status = response.status();
+        let error_text = match http::read_text(response, options).await {
+            Ok(error_text) => error_text,
+            Err(
+                e @ (TranslationError::TimeoutError | TranslationError::ResponseTooLarge { .. }),
+            ) => {
+                return Err(e);
+            }
+            Err(_) => "Unknown error".to_string(),
+        };
+        // 服务端可能在错误信息中回显 Key
+        let error_text = match &self.config.api_key {
+            Some(api_key) => api_key.redact_owned(error_text),
+            None => error_text,
+        };
+
+        // 如果是401未授权错误，则清除缓存的token
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            self.clear_cached_token().await;
+        }
+
+        let error = parse_error_body(status, &error_text).unwrap_or(TranslationError::HttpError {
+            status,
+            body: error_text,
+            trace: None,
+        });
+        let error = trace::annotate_error(error, options);
+        Err(api_version_error(error, self.api_version()))
+    }
+
+    /// 请求使用的 API 版本
+    fn api_version(&self) -> &str {
+        self.config
+            .api_version
+            .as_deref()
+            .unwrap_or(DEFAULT_API_VERSION)
+    }
+
+    /// 检测文本的语言（`/detect` 接口）
+    ///
+    /// 不超过 256 个字符的文本按合并空白、转为小写后的内容缓存检测结果，
+    /// 缓存大小由 `detection_cache_size` 设置，重复检测同一短文本时不再发送请求
+    ///
+    /// # 参数
+    ///
+    /// * `text` - 需要检测的文本
+    /// * `options` - 翻译配置选项，使用其中的超时、重试和端点覆盖等设置
+    ///
+    /// # 返回值
+    ///
+    /// 返回检测到的语言代码和置信度
+    pub async fn detect(
+        &self,
+        text: &str,
+        options: &TranslateOptions,
+    ) -> Result<DetectedLanguage, TranslationError> {
+        if let Some(detected) = self.detection_cache.get(text) {
+            return Ok(detected);
+        }
+        let options = &self.effective_options(options);
+        let detected = retry::retry(
+            self.config.clock.as_ref(),
+            options.max_retries,
+            options.deadline,
+            "microsoft",
+            options.on_retry.as_ref(),
+            || self.try_detect(text, options),
+        )
+        .await?;
+        self.detection_cache.insert(text, detected.clone());
+        Ok(detected)
+    }
+
+    /// 尝试检测文本的语言（无重试）
+    async fn try_detect(
+        &self,
+        text: &str,
+        options: &EffectiveOptions,
+    ) -> Result<DetectedLanguage, TranslationError> {
+        let endpoint = self.request_endpoint(options).await?;
+        let _permit = self
+            .concurrency
+            .acquire_within(
+                options.max_queue_wait,
+                options.deadline,
+                self.config.clock.as_ref(),
+            )
+            .await?;
+        self.rate_limiter
+            .acquire(self.config.clock.as_ref(), options.deadline)
             .await?;
+        let auth_header = self.auth_header().await?;
 
-        // 检查HTTP状态码
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
+        let mut headers = vec![
+            ("Authorization".to_string(), auth_header),
+            ("Content-Type".to_string(), "application/json".to_string()),
+        ];
+        headers.extend(trace::headers(options));
+        let request = PreparedRequest {
+            url: format!("{}/detect", endpoint),
+            query: vec![("api-version".to_string(), self.api_version().to_string())],
+            headers,
+            body: serde_json::to_vec(&[BatchTranslationRequest { text }])
+                .expect("request body is serializable"),
+        };
+        let response = self.send(request, options).await?;
+        let results: Vec<DetectedLanguage> = http::read_json(response, options).await?;
+        results.into_iter().next().ok_or_else(|| {
+            TranslationError::ServiceError("No detection results returned".to_string())
+        })
+    }
 
-            // 如果是401未授权错误，则清除缓存的token
-            if status == reqwest::StatusCode::UNAUTHORIZED {
-                self.clear_cached_token().await;
-            }
+    /// 关闭翻译器：之后的请求和仍在等待并发许可的请求返回
+    /// [`TranslationError::TranslatorClosed`]，等待已经开始的请求全部完成后返回
+    ///
+    /// 直接丢弃翻译器不会阻塞，也不需要先调用 `close`：正在进行的请求持有翻译器的引用
+    /// （例如 [`crate::TranslationManager`] 中的 `Arc`），翻译器在它们完成后才会真正释放
+    pub async fn close(&self) {
+        self.concurrency.close().await;
+    }
 
-            if let Ok(error_response) = serde_json::from_str::<MicrosoftErrorResponse>(&error_text)
-            {
-                return Err(TranslationError::HttpError {
-                    status,
-                    body: format!(
-                        "Error {}: {}",
-                        error_response.error.code, error_response.error.message
-                    ),
-                });
-            }
+    /// 语言检测缓存的命中统计
+    pub fn detection_cache_stats(&self) -> DetectionCacheStats {
+        self.detection_cache.stats()
+    }
 
-            return Err(TranslationError::HttpError {
-                status,
-                body: error_text,
-            });
+    /// 当前生效的并发数，未启用 `adaptive_concurrency` 时等于 `concurrent_limit`
+    pub fn effective_concurrency_limit(&self) -> usize {
+        self.concurrency.effective_limit()
+    }
+
+    /// 最近 [`crate::metrics::QUEUE_STATS_WINDOW`] 内等待并发许可的统计
+    pub fn queue_stats(&self) -> QueueStats {
+        self.queue_metrics.stats()
+    }
+
+    /// 自动认证的当前状态，用于监控 token 地址是否可用
+    pub async fn auth_status(&self) -> AuthStatus {
+        if self.config.api_key.is_some() {
+            return AuthStatus {
+                uses_api_key: true,
+                token_expires_at: None,
+                consecutive_failures: 0,
+                last_error: None,
+                retry_after: None,
+            };
+        }
+        let token_expires_at = *self.token_expiry.lock().await;
+        let now = self.config.clock.now();
+        let failures = self.auth_failures.lock().unwrap();
+        let breaker = self.auth_breaker.lock().unwrap();
+        let throttled = breaker
+            .open_until
+            .filter(|until| *until > now)
+            .map(|until| until - now);
+        AuthStatus {
+            uses_api_key: false,
+            token_expires_at,
+            consecutive_failures: failures.consecutive,
+            last_error: failures
+                .last_error
+                .as_ref()
+                .map(TranslationError::duplicate),
+            retry_after: failures.remaining(now).max(throttled),
         }
+    }
 
-        // 解析响应
-        let response_body: Vec<MicrosoftTranslation> = response.json().await?;
-        Ok(response_body)
+    /// 发送批量翻译请求（无重试），返回成功的响应和读取响应体期间需要持有的并发许可
+    async fn try_send_batch(
+        &self,
+        texts: &[&str],
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+    ) -> Result<(reqwest::Response, Permit<'_>), TranslationError> {
+        let endpoint = self.request_endpoint(options).await?;
+        let permit = self
+            .concurrency
+            .acquire_within(
+                options.max_queue_wait,
+                options.deadline,
+                self.config.clock.as_ref(),
+            )
+            .await?;
+        self.rate_limiter
+            .acquire(self.config.clock.as_ref(), options.deadline)
+            .await?;
+        let auth_header = self.auth_header().await?;
+
+        let request = self.build_request(
+            endpoint,
+            texts,
+            target_lang,
+            source_lang,
+            options,
+            auth_header,
+        );
+        preflight::check_length(request.body.len(), self.config.max_request_bytes)?;
+        let response = self
+            .send(request, options)
+            .await
+            .map_err(|error| custom_model_error(error, &self.config))?;
+        Ok((response, permit))
+    }
+
+    /// 尝试批量翻译文本（无重试）
+    async fn try_translate_batch(
+        &self,
+        texts: &[&str],
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+    ) -> Result<Vec<MicrosoftTranslation>, TranslationError> {
+        let (response, _permit) = self
+            .try_send_batch(texts, target_lang, source_lang, options)
+            .await?;
+
+        // 解析响应，需要时保留每一项的原始 JSON
+        let system = translation_system(&response);
+        let request_id = request_id(&response);
+        let received_at = self.config.clock.system_time();
+        let body: serde_json::Value = http::read_json(response, options).await?;
+        let mut results: Vec<MicrosoftTranslation> = http::decode_json(&body)?;
+        for result in &mut results {
+            ensure_translated(result)?;
+            result.system = system.clone();
+            result.request_id = request_id.clone();
+            result.received_at = Some(received_at);
+        }
+        if let (true, serde_json::Value::Array(items)) = (options.include_raw_response, body) {
+            for (result, item) in results.iter_mut().zip(items) {
+                result.raw = Some(http::capture_raw(item, options.max_raw_response_bytes));
+            }
+        }
+        Ok(results)
     }
 
     /// 翻译单个文本（公共方法）
@@ -363,7 +2354,7 @@ impl MicrosoftTranslator {
         source_lang: Option<&LanguageIdentifier>,
         options: &TranslateOptions,
     ) -> Result<String, TranslationError> {
-        let results = self
+        let mut results = self
             .translate_batch(&[text], target_lang, source_lang, options)
             .await?;
 
@@ -373,27 +2364,186 @@ impl MicrosoftTranslator {
             ));
         }
 
-        Ok(results[0].translations[0].text.clone())
+        Ok(results.swap_remove(0).translations.swap_remove(0).text)
     }
 
-    /// 批量翻译文本并返回字符串数组
-    pub async fn translate_batch_to_strings(
+    /// 翻译单个文本并返回包含检测语言的详细结果
+    ///
+    /// 按 `on_failure` 原样返回原文时，结果标记为 [`TranslationDetail::passthrough`]
+    pub async fn translate_detailed(
         &self,
-        texts: &[&str],
+        text: &str,
         target_lang: &LanguageIdentifier,
         source_lang: Option<&LanguageIdentifier>,
         options: &TranslateOptions,
-    ) -> Result<Vec<String>, TranslationError> {
+    ) -> Result<TranslationDetail, TranslationError> {
+        let options = &self.effective_options(options);
+        let result = self
+            .translate_detailed_effective(text, target_lang, source_lang, options)
+            .await;
+        options.on_failure.apply(text, result)
+    }
+
+    /// 翻译单个文本并整理为详细结果
+    async fn translate_detailed_effective(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+    ) -> Result<TranslationDetail, TranslationError> {
         let results = self
-            .translate_batch(texts, target_lang, source_lang, options)
+            .translate_batch_effective(&[text], target_lang, source_lang, options)
+            .await?;
+        let Some(translation) = results.into_iter().next() else {
+            return Err(TranslationError::ServiceError(
+                "No translation results returned".to_string(),
+            ));
+        };
+        let Some(result) = translation.translations.into_iter().next() else {
+            return Err(TranslationError::ServiceError(
+                "No translation results returned".to_string(),
+            ));
+        };
+
+        // 原始 JSON 还原为该请求的响应数组
+        let raw = translation.raw.map(|raw| match raw {
+            serde_json::Value::Array(_) => raw,
+            item => serde_json::Value::Array(vec![item]),
+        });
+        let detected_language = translation
+            .detected_language
+            .as_ref()
+            .map(|detected| detected.language.clone());
+        let detected_source = translation.detected_language.and_then(|detected| {
+            detected
+                .language
+                .parse::<LanguageIdentifier>()
+                .ok()
+                .map(|lang| (lang, detected.score))
+        });
+        let mut provider_metadata = serde_json::json!({
+            "provider": "microsoft",
+            "to": result.to,
+            "detected_language": detected_language,
+        });
+        if let Some(category) = &self.config.category {
+            provider_metadata["category"] = serde_json::json!(category);
+        }
+        if let Some(system) = &translation.system {
+            provider_metadata["system"] = serde_json::json!(system);
+        }
+        if let Some(mapping) = language_mapping(target_lang, source_lang) {
+            provider_metadata["language_mapping"] = mapping;
+        }
+        trace::annotate_metadata(&mut provider_metadata, options);
+        let provenance = Provenance {
+            provider: "microsoft".to_string(),
+            model: self.config.category.clone(),
+            prompt_fingerprint: None,
+            request_id: translation.request_id,
+            system_fingerprint: None,
+            timestamp: translation
+                .received_at
+                .unwrap_or_else(|| self.config.clock.system_time()),
+            options_fingerprint: self.key_inputs(source_lang, options).fingerprint(),
+        };
+
+        Ok(TranslationDetail {
+            text: result.text,
+            detected_source,
+            provider_metadata,
+            partial: false,
+            suspicious: false,
+            raw,
+            passthrough: false,
+            provenance: Some(provenance),
+            result_kind: TranslationKind::Translated,
+        })
+    }
+
+    /// 按术语表翻译单个文本
+    ///
+    /// 原文中的术语使用动态词典标记 `<mstrans:dictionary>` 指定译法，
+    /// 请求以 `textType=html` 提交，译文中残留的标记和 HTML 实体会被去除
+    pub async fn translate_with_glossary(
+        &self,
+        text: &str,
+        glossary: &Glossary,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<String, TranslationError> {
+        let options = options.clone().html(true);
+        let translated = self
+            .translate_text(
+                &dictionary_markup(text, glossary),
+                target_lang,
+                source_lang,
+                &options,
+            )
+            .await?;
+        Ok(strip_dictionary_markup(&translated))
+    }
+
+    /// 批量翻译文本并返回字符串数组
+    ///
+    /// 启用 `truncate_to_budget` 且超出 `max_batch_chars` 时只返回预算内完整翻译的前几条，
+    /// 需要逐条知道哪些条目被跳过时使用 [`Self::translate_batch`]
+    pub async fn translate_batch_to_strings<I>(
+        &self,
+        texts: I,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<Vec<String>, TranslationError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let options = &self.effective_options(options);
+        let texts: Vec<I::Item> = texts.into_iter().collect();
+        let texts: Vec<&str> = texts.iter().map(|text| text.as_ref()).collect();
+        let (results, fit) = self
+            .translate_batch_budgeted(&texts, target_lang, source_lang, options, false)
             .await?;
         let translated_texts = results
             .into_iter()
+            .take(fit)
             .filter_map(|res| res.translations.into_iter().next())
             .map(|trans_result| trans_result.text)
             .collect();
         Ok(translated_texts)
     }
+
+    /// 批量翻译带标签的文本，每个结果附带对应输入的标签
+    ///
+    /// 标签始终和对应的文本一起传递，不依赖位置；设置 `dedup` 时相同的文本只翻译一次。
+    /// 每条文本单独请求，同时进行的请求数不超过 `concurrent_limit`，
+    /// 一条失败不影响其他结果，结果按输入顺序排列
+    pub async fn translate_batch_tagged<T: Send>(
+        &self,
+        items: Vec<(T, String)>,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Vec<(T, Result<String, TranslationError>)> {
+        let effective = self.effective_options(options);
+        batch::translate_tagged(
+            items,
+            effective.dedup,
+            self.config.concurrent_limit,
+            batch::CharBudget::of(&effective),
+            |text| async move {
+                self.start_jitter
+                    .wait(effective.start_jitter, self.config.clock.as_ref())
+                    .await;
+                self.translate_text(&text, target_lang, source_lang, options)
+                    .await
+            },
+        )
+        .await
+    }
 }
 
 #[async_trait::async_trait]
@@ -408,6 +2558,152 @@ impl Translator for MicrosoftTranslator {
         self.translate_text(text, target_lang, source_lang, options)
             .await
     }
+
+    async fn translate_batch_with_options(
+        &self,
+        texts: &[&str],
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<Vec<String>, TranslationError> {
+        self.translate_batch_to_strings(texts, target_lang, source_lang, options)
+            .await
+    }
+
+    async fn translate_detailed_with_options(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<TranslationDetail, TranslationError> {
+        self.translate_detailed(text, target_lang, source_lang, options)
+            .await
+    }
+
+    async fn translate_with_glossary(
+        &self,
+        text: &str,
+        glossary: &Glossary,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<String, TranslationError> {
+        MicrosoftTranslator::translate_with_glossary(
+            self,
+            text,
+            glossary,
+            target_lang,
+            source_lang,
+            options,
+        )
+        .await
+    }
+
+    fn cache_key_inputs(
+        &self,
+        _target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Option<CacheKeyInputs> {
+        Some(self.key_inputs(source_lang, &self.effective_options(options)))
+    }
+
+    fn capabilities(&self) -> TranslatorCapabilities {
+        TranslatorCapabilities {
+            max_text_len: Some(MAX_REQUEST_CHARS),
+            length_unit: LengthUnit::Chars,
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+const DICTIONARY_OPEN: &str = "<mstrans:dictionary";
+const DICTIONARY_CLOSE: &str = "</mstrans:dictionary>";
+
+/// 转义 HTML 特殊字符
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 还原 [`escape_html`] 转义的字符
+fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 标记只翻译了预算内前几个片段的结果
+fn mark_partial(translation: &mut MicrosoftTranslation, error: &TranslationError) {
+    if !translation.passthrough {
+        translation.partial = true;
+        translation.error = Some(error.to_string());
+    }
+}
+
+/// 把结果中各目标语言译文的占位符还原为原文
+fn restore_placeholders(
+    protected: &Protected,
+    translation: &mut MicrosoftTranslation,
+) -> Result<(), TranslationError> {
+    for result in &mut translation.translations {
+        result.text = protected.restore(&result.text)?;
+    }
+    Ok(())
+}
+
+/// 把原文中的术语包裹为动态词典标记，其余文本按 HTML 转义
+pub(crate) fn dictionary_markup(text: &str, glossary: &Glossary) -> String {
+    let mut markup = String::with_capacity(text.len());
+    let mut last = 0;
+    for term in glossary.find_terms(text) {
+        markup.push_str(&escape_html(&text[last..term.range.start]));
+        markup.push_str(&format!(
+            "{} translation=\"{}\">{}{}",
+            DICTIONARY_OPEN,
+            escape_html(term.translation),
+            escape_html(&text[term.range.clone()]),
+            DICTIONARY_CLOSE
+        ));
+        last = term.range.end;
+    }
+    markup.push_str(&escape_html(&text[last..]));
+    markup
+}
+
+/// 去除译文中残留的动态词典标记并还原 HTML 实体
+pub(crate) fn strip_dictionary_markup(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(DICTIONARY_OPEN) {
+        stripped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match rest.find('>') {
+            Some(end) => rest = &rest[end + 1..],
+            None => {
+                rest = "";
+                break;
+            }
+        }
+    }
+    stripped.push_str(rest);
+    unescape_html(&stripped.replace(DICTIONARY_CLOSE, ""))
 }
 
 #[cfg(test)]
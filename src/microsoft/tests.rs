@@ -1,10 +1,73 @@
 #[cfg(test)]
 mod tests {
-    use crate::error::TranslationError;
-    use crate::microsoft::{MicrosoftConfig, MicrosoftTranslator};
-    use crate::options::TranslateOptions;
+    use crate::clock::Clock;
+    use crate::error::{NetworkErrorKind, TranslationError};
+    use crate::glossary::Glossary;
+    use crate::microsoft::{
+        AUTH_FAILURE_COOLDOWN, AUTH_FAILURE_MAX_COOLDOWN, AUTH_RETRY_DELAY, AUTH_RETRY_JITTER,
+        AuthFailures, DEFAULT_ENDPOINT, GeoRegion, LowConfidencePolicy, MAX_REQUEST_CHARS,
+        MicrosoftConfig, MicrosoftTranslation, MicrosoftTranslator, TOKEN_EXPIRY_SAFETY_MARGIN,
+        TOKEN_REFRESH_MARGIN, dictionary_markup, parse_error_body, strip_dictionary_markup,
+        token_expiry, token_is_fresh, unix_now, validate_token,
+    };
+    use crate::normalize::{Normalization, NormalizationForm};
+    use crate::options::{ProxyMode, TraceContext, TranslateOptions};
+    use crate::testing::{DelayedResponse, DelayedServer, MockClock};
+    use crate::trace::RequestTrace;
     use crate::translator::Translator;
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::time::Duration;
     use unic_langid::LanguageIdentifier;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+    /// 测试中缓存 token 的有效期
+    const TOKEN_TTL: Duration = Duration::from_secs(540);
+
+    /// 构造负载为 `payload` 的 JWT
+    fn jwt(payload: &str) -> String {
+        use base64::Engine;
+        let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        format!(
+            "{}.{}.{}",
+            engine.encode(r#"{"alg":"HS256","typ":"JWT"}"#),
+            engine.encode(payload),
+            engine.encode("signature")
+        )
+    }
+
+    /// 将每条文本加上 "T:" 前缀原样返回的模拟响应
+    struct EchoResponder;
+
+    impl Respond for EchoResponder {
+        fn respond(&self, request: &Request) -> ResponseTemplate {
+            let body: Vec<serde_json::Value> = serde_json::from_slice(&request.body).unwrap();
+            let results: Vec<serde_json::Value> = body
+                .iter()
+                .map(|item| {
+                    json!({
+                        "detectedLanguage": {"language": "en", "score": 1.0},
+                        "translations": [{
+                            "text": format!("T:{}", item["text"].as_str().unwrap()),
+                            "to": "zh"
+                        }]
+                    })
+                })
+                .collect();
+            ResponseTemplate::new(200).set_body_json(results)
+        }
+    }
+
+    async fn mock_translator(endpoint: String) -> MicrosoftTranslator {
+        MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(endpoint)
+                .api_key(Some("test-key"))
+                .build(),
+        )
+    }
 
     #[tokio::test]
     async fn test_microsoft_config_default() {
@@ -20,11 +83,11 @@ mod tests {
             endpoint: None,
             api_key: None,
             concurrent_limit: 10,
+            ..Default::default()
         };
 
         let _translator = MicrosoftTranslator::new(config);
         // 这里我们只测试创建是否成功，不测试实际的API调用
-        assert!(true); // 如果创建成功，这行代码会执行
     }
 
     #[tokio::test]
@@ -33,6 +96,7 @@ mod tests {
             endpoint: None,
             api_key: None,
             concurrent_limit: 10,
+            ..Default::default()
         };
 
         let translator = MicrosoftTranslator::new(config);
@@ -52,8 +116,8 @@ mod tests {
                 println!("Network error (expected in test): {}", e);
                 // 验证错误信息包含网络相关内容
                 match e {
-                    TranslationError::NetworkError(_) => assert!(true),
-                    _ => assert!(false, "Expected NetworkError"),
+                    TranslationError::NetworkError { .. } => {}
+                    _ => panic!("Expected NetworkError"),
                 }
             }
         }
@@ -65,6 +129,7 @@ mod tests {
             endpoint: None,
             api_key: None,
             concurrent_limit: 10,
+            ..Default::default()
         };
 
         let translator = MicrosoftTranslator::new(config);
@@ -86,8 +151,8 @@ mod tests {
                 // 在测试环境中可能无法访问网络，这是正常的
                 println!("Network error (expected in test): {}", e);
                 match e {
-                    TranslationError::NetworkError(_) => assert!(true),
-                    _ => assert!(false, "Expected NetworkError"),
+                    TranslationError::NetworkError { .. } => {}
+                    _ => panic!("Expected NetworkError"),
                 }
             }
         }
@@ -110,8 +175,8 @@ mod tests {
                 // 在测试环境中可能无法访问网络，这是正常的
                 println!("Network error (expected in test): {}", e);
                 match e {
-                    TranslationError::NetworkError(_) => assert!(true),
-                    _ => assert!(false, "Expected NetworkError"),
+                    TranslationError::NetworkError { .. } => {}
+                    _ => panic!("Expected NetworkError"),
                 }
             }
         }
@@ -123,6 +188,7 @@ mod tests {
             endpoint: None,
             api_key: None,
             concurrent_limit: 10,
+            ..Default::default()
         };
 
         let translator = MicrosoftTranslator::new(config);
@@ -139,14 +205,13 @@ mod tests {
         {
             Ok(results) => {
                 println!("Batch translation results: {:?}", results);
-                assert!(true);
             }
             Err(e) => {
                 // 在测试环境中可能无法访问网络，这是正常的
                 println!("Network error (expected in test): {}", e);
                 match e {
-                    TranslationError::NetworkError(_) => assert!(true),
-                    _ => assert!(false, "Expected NetworkError"),
+                    TranslationError::NetworkError { .. } => {}
+                    _ => panic!("Expected NetworkError"),
                 }
             }
         }
@@ -158,6 +223,7 @@ mod tests {
             endpoint: None,
             api_key: None,
             concurrent_limit: 10,
+            ..Default::default()
         };
 
         let translator = MicrosoftTranslator::new(config);
@@ -173,8 +239,8 @@ mod tests {
                 // 在测试环境中可能无法访问网络，这是正常的
                 println!("Network error (expected in test): {}", e);
                 match e {
-                    TranslationError::NetworkError(_) => assert!(true),
-                    _ => assert!(false, "Expected NetworkError"),
+                    TranslationError::NetworkError { .. } => {}
+                    _ => panic!("Expected NetworkError"),
                 }
             }
         }
@@ -204,4 +270,2923 @@ mod tests {
         let token3 = translator.get_auth_token().await;
         assert!(token3.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_request_id_and_trace_headers_sent() {
+        let server = MockServer::start().await;
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(query_param("to", "zh-Hans"))
+            .and(header("X-Request-Id", "req-456"))
+            .and(header("traceparent", traceparent))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {
+                    "detectedLanguage": {"language": "en", "score": 1.0},
+                    "translations": [{"text": "你好", "to": "zh"}]
+                }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = MicrosoftConfig::builder()
+            .endpoint(server.uri())
+            .api_key(Some("test-key"))
+            .build();
+        let translator = MicrosoftTranslator::new(config);
+        let options = TranslateOptions::default()
+            .request_id("req-456")
+            .trace_context(TraceContext {
+                traceparent: traceparent.to_string(),
+                tracestate: None,
+            });
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let result = translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(result, "你好");
+    }
+
+    #[tokio::test]
+    async fn test_request_id_and_trace_in_errors() {
+        let server = MockServer::start().await;
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "error": {"code": 400036, "message": "The target language is not valid."}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .build(),
+        );
+        let options = TranslateOptions::default()
+            .request_id("req-456")
+            .trace_context(TraceContext {
+                traceparent: traceparent.to_string(),
+                tracestate: None,
+            });
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        match translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::Provider { code, trace, .. }) => {
+                assert_eq!(code, "400036");
+                assert_eq!(
+                    trace,
+                    Some(Box::new(RequestTrace {
+                        request_id: Some("req-456".to_string()),
+                        traceparent: Some(traceparent.to_string()),
+                    }))
+                );
+            }
+            other => panic!("Expected Provider error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preflight_char_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(EchoResponder)
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+
+        let under = "a".repeat(MAX_REQUEST_CHARS - 1);
+        assert!(
+            translator
+                .translate_text(&under, &target_lang, None, &options)
+                .await
+                .is_ok()
+        );
+
+        let at = "a".repeat(MAX_REQUEST_CHARS);
+        assert!(
+            translator
+                .translate_text(&at, &target_lang, None, &options)
+                .await
+                .is_ok()
+        );
+
+        // 超出限制时不会发送请求
+        let over = "a".repeat(MAX_REQUEST_CHARS + 1);
+        match translator
+            .translate_text(&over, &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::TextTooLong { length, max }) => {
+                assert_eq!(length, MAX_REQUEST_CHARS + 1);
+                assert_eq!(max, MAX_REQUEST_CHARS);
+            }
+            other => panic!("Expected TextTooLong, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preflight_batch_total_limit() {
+        let server = MockServer::start().await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        // 每条文本都未超限，但合计超出
+        let half = "a".repeat(MAX_REQUEST_CHARS / 2 + 1);
+        let result = translator
+            .translate_batch(
+                &[&half, &half],
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(TranslationError::TextTooLong { length, .. }) if length == MAX_REQUEST_CHARS + 2
+        ));
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_preflight_auto_split_batch() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(EchoResponder)
+            .expect(4)
+            .mount(&server)
+            .await;
+
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().auto_split(true);
+
+        // 前两条合计超限需分到两个请求，第三条本身超长需拆成两个片段
+        let sentence = "word ".repeat(MAX_REQUEST_CHARS / 5 - 1) + "end.";
+        let long = format!("{} {}", sentence, sentence);
+        let short = "Hello";
+        let results = translator
+            .translate_batch_to_strings(&[short, &sentence, &long], &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], "T:Hello");
+        assert_eq!(results[1], format!("T:{}", sentence));
+        assert_eq!(results[2], format!("T:{} T:{}", sentence, sentence));
+    }
+
+    #[tokio::test]
+    async fn test_max_request_bytes_splits_batch() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(EchoResponder)
+            .expect(2)
+            .mount(&server)
+            .await;
+        // `[{"text":"Hello"},{"text":"World"}]` 正好 35 字节
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .max_request_bytes(35)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let results = translator
+            .translate_batch_to_strings(
+                &["Hello", "World", "Again"],
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results, ["T:Hello", "T:World", "T:Again"]);
+        let sizes: Vec<usize> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .map(|request| request.body.len())
+            .collect();
+        assert_eq!(sizes, [35, 18]);
+
+        // 单条文本的请求体就超出限制时不发送请求
+        let result = translator
+            .translate_text(
+                &"a".repeat(30),
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(TranslationError::TextTooLong {
+                length: 43,
+                max: 35
+            })
+        ));
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_payload_too_large_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(413).set_body_string("Request Entity Too Large"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let error = translator
+            .translate_text(
+                "Hello",
+                &target_lang,
+                None,
+                &TranslateOptions::default().max_retries(2),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            TranslationError::RequestTooLarge { bytes: 18 }
+        ));
+        assert_eq!(error.code(), crate::ErrorCode::TextTooLong);
+    }
+
+    #[tokio::test]
+    async fn test_translate_detailed_includes_detection() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {
+                    "detectedLanguage": {"language": "en", "score": 0.98},
+                    "translations": [{"text": "你好", "to": "zh-Hans"}]
+                }
+            ])))
+            .mount(&server)
+            .await;
+
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh-Hans".parse().unwrap();
+        let options = TranslateOptions::default().request_id("req-789");
+        let detail = translator
+            .translate_detailed_with_options("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "你好");
+        assert_eq!(detail.detected_source, Some(("en".parse().unwrap(), 0.98)));
+        assert_eq!(detail.provider_metadata["provider"], "microsoft");
+        assert_eq!(detail.provider_metadata["to"], "zh-Hans");
+        assert_eq!(detail.provider_metadata["request_id"], "req-789");
+    }
+
+    #[tokio::test]
+    async fn test_translate_detailed_without_detection() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"translations": [{"text": "你好", "to": "zh"}]}
+            ])))
+            .mount(&server)
+            .await;
+
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let source_lang: LanguageIdentifier = "en".parse().unwrap();
+        let detail = translator
+            .translate_detailed(
+                "Hello",
+                &target_lang,
+                Some(&source_lang),
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "你好");
+        assert_eq!(detail.detected_source, None);
+    }
+
+    #[tokio::test]
+    async fn test_queue_stats_with_saturated_server() {
+        let body = json!([{"translations": [{"text": "你好", "to": "zh"}]}]).to_string();
+        let server = DelayedServer::start(
+            DelayedResponse::new(body).header_delay(Duration::from_millis(100)),
+        )
+        .await;
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .concurrent_limit(1)
+                .build(),
+        );
+        assert_eq!(translator.queue_stats().requests, 0);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        let results = futures::future::join_all(
+            (0..3).map(|_| translator.translate_text("Hello", &target_lang, None, &options)),
+        )
+        .await;
+        assert!(results.iter().all(|result| result.is_ok()));
+
+        // 第一个请求不排队，第三个请求等待前两个请求
+        let stats = translator.queue_stats();
+        assert_eq!(stats.requests, 3);
+        assert_eq!(stats.max_depth, 2);
+        assert_eq!(stats.current_depth, 0);
+        assert!(stats.max_wait >= Duration::from_millis(150), "{:?}", stats);
+        assert!((stats.average_depth - 1.0).abs() < 1e-9, "{:?}", stats);
+    }
+
+    #[tokio::test]
+    async fn test_body_timeout_is_retryable_timeout() {
+        let body = json!([{"translations": [{"text": "你好", "to": "zh"}]}]).to_string();
+        let server =
+            DelayedServer::start(DelayedResponse::new(body).body_delay(Duration::from_secs(2)))
+                .await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default()
+            .timeout(Duration::from_millis(200))
+            .max_retries(1);
+
+        match translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::MaxRetriesExceeded { attempts, errors }) => {
+                assert_eq!(attempts, 2);
+                assert!(
+                    errors
+                        .iter()
+                        .all(|e| matches!(e, TranslationError::TimeoutError))
+                );
+            }
+            other => panic!("Expected MaxRetriesExceeded, got {:?}", other),
+        }
+        assert_eq!(server.requests(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_response_not_retried() {
+        let server = DelayedServer::start(DelayedResponse::new("not json")).await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().max_retries(2);
+
+        match translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::MalformedResponse(_)) => {}
+            other => panic!("Expected MalformedResponse, got {:?}", other),
+        }
+        assert_eq!(server.requests(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_response_too_large_not_retried() {
+        let server =
+            DelayedServer::start(DelayedResponse::new("x".repeat(4096)).endless(true)).await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default()
+            .max_response_bytes(16 * 1024)
+            .max_retries(2);
+
+        match translator
+            .translate_batch(&["Hello"], &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::ResponseTooLarge { max }) => assert_eq!(max, 16 * 1024),
+            other => panic!("Expected ResponseTooLarge, got {:?}", other),
+        }
+        assert_eq!(server.requests(), 1);
+    }
+
+    #[test]
+    fn test_token_freshness_at_refresh_margin() {
+        let clock = MockClock::new();
+        let expiry = clock.now() + TOKEN_TTL;
+
+        assert!(token_is_fresh(expiry, clock.now()));
+        clock.advance(TOKEN_TTL - TOKEN_REFRESH_MARGIN - Duration::from_secs(1));
+        assert!(token_is_fresh(expiry, clock.now()));
+        // 剩余有效期恰好等于提前刷新的时长时需要刷新
+        clock.advance(Duration::from_secs(1));
+        assert!(!token_is_fresh(expiry, clock.now()));
+        // 时钟跳过过期时间后同样需要刷新
+        clock.advance(Duration::from_secs(3600));
+        assert!(!token_is_fresh(expiry, clock.now()));
+    }
+
+    #[tokio::test]
+    async fn test_cached_token_used_until_margin() {
+        let clock = MockClock::new();
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        *translator.cached_token.lock().await = Some("cached-token".to_string());
+        *translator.token_expiry.lock().await = Some(clock.now() + TOKEN_TTL);
+
+        clock.advance(TOKEN_TTL - TOKEN_REFRESH_MARGIN - Duration::from_millis(1));
+        // 仍在有效期内，不会发起认证请求
+        assert_eq!(translator.get_auth_token().await.unwrap(), "cached-token");
+    }
+
+    #[tokio::test]
+    async fn test_batch_retry_backoff_uses_clock() {
+        let server = DelayedServer::start(DelayedResponse::new("overloaded").status(503)).await;
+        let clock = MockClock::new().auto_advance(true);
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().max_retries(3);
+
+        let result = translator
+            .translate_batch(&["Hello"], &target_lang, None, &options)
+            .await;
+        assert!(matches!(
+            result,
+            Err(TranslationError::MaxRetriesExceeded { attempts: 4, .. })
+        ));
+        assert_eq!(server.requests(), 4);
+        assert_eq!(
+            clock.sleeps(),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(400)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_override() {
+        let primary = MockServer::start().await;
+        let canary = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(EchoResponder)
+            .expect(1)
+            .mount(&canary)
+            .await;
+
+        let translator = mock_translator(primary.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().endpoint(canary.uri());
+        let result = translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(result, "T:Hello");
+        assert!(primary.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_endpoint_override_fails_before_auth() {
+        // 未配置 API Key，如果发起认证请求会访问真实网络
+        let translator = MicrosoftTranslator::new(MicrosoftConfig::default());
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().endpoint("://missing-scheme");
+        match translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::ConfigurationError(msg)) => {
+                assert!(msg.contains("endpoint"), "{}", msg)
+            }
+            other => panic!("Expected ConfigurationError, got {:?}", other),
+        }
+        assert!(translator.cached_token.lock().await.is_none());
+    }
+
+    #[test]
+    fn test_parse_error_bodies() {
+        // (HTTP 状态码, 响应体, 期望的错误码, 是否可重试)
+        let cases: &[(u16, &str, &str, bool)] = &[
+            (
+                400,
+                r#"{"error":{"code":400000,"message":"One of the request inputs is not valid."}}"#,
+                "400000",
+                false,
+            ),
+            (
+                400,
+                r#"{"error":{"code":400036,"message":"The target language \"To\" field is missing or invalid."}}"#,
+                "400036",
+                false,
+            ),
+            (
+                400,
+                r#"{"error":{"code":400050,"message":"The input text is too long."}}"#,
+                "400050",
+                false,
+            ),
+            (
+                401,
+                r#"{"error":{"code":401000,"message":"The request is not authorized because credentials are missing or invalid."}}"#,
+                "401000",
+                false,
+            ),
+            (
+                401,
+                r#"{"error":{"code":401015,"message":"The credentials provided are for the Speech API."}}"#,
+                "401015",
+                false,
+            ),
+            (
+                403,
+                r#"{"error":{"code":403001,"message":"The operation is not allowed because the subscription has exceeded its free quota."}}"#,
+                "403001",
+                false,
+            ),
+            (
+                405,
+                r#"{"error":{"code":405000,"message":"The request method is not supported for the requested resource."}}"#,
+                "405000",
+                false,
+            ),
+            (
+                408,
+                r#"{"error":{"code":408001,"message":"The translation system requested is being prepared."}}"#,
+                "408001",
+                true,
+            ),
+            (
+                429,
+                r#"{"error":{"code":429000,"message":"The server rejected the request because the client has exceeded request limits."}}"#,
+                "429000",
+                true,
+            ),
+            (
+                429,
+                r#"{"error":{"code":429001,"message":"The server rejected the request because the client has exceeded request limits."}}"#,
+                "429001",
+                true,
+            ),
+            (
+                500,
+                r#"{"error":{"code":500000,"message":"An unexpected error occurred."}}"#,
+                "500000",
+                true,
+            ),
+            (
+                503,
+                r#"{"error":{"code":503000,"message":"Service is temporarily unavailable."}}"#,
+                "503000",
+                true,
+            ),
+        ];
+
+        for &(status, body, expected_code, expected_retryable) in cases {
+            let status = StatusCode::from_u16(status).unwrap();
+            let error = parse_error_body(status, body)
+                .unwrap_or_else(|| panic!("Expected Provider error for {}", body));
+            assert_eq!(error.is_retryable(), expected_retryable, "{}", body);
+            match error {
+                TranslationError::Provider {
+                    provider,
+                    code,
+                    message,
+                    http_status,
+                    retryable,
+                    ..
+                } => {
+                    assert_eq!(provider, "microsoft");
+                    assert_eq!(code, expected_code, "{}", body);
+                    assert!(!message.is_empty());
+                    assert_eq!(http_status, Some(status));
+                    assert_eq!(retryable, expected_retryable);
+                }
+                other => panic!("Expected Provider error, got {:?}", other),
+            }
+        }
+
+        // 无法解析的响应体
+        for body in ["Service Unavailable", r#"{"message":"oops"}"#, ""] {
+            assert!(parse_error_body(StatusCode::SERVICE_UNAVAILABLE, body).is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_provider_error_returned_from_batch() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "error": {"code": 400036, "message": "The target language is not valid."}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        match translator
+            .translate_batch(&["Hello"], &target_lang, None, &TranslateOptions::default())
+            .await
+        {
+            Err(TranslationError::Provider {
+                code, http_status, ..
+            }) => {
+                assert_eq!(code, "400036");
+                assert_eq!(http_status, Some(StatusCode::BAD_REQUEST));
+            }
+            other => panic!("Expected Provider error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unparseable_error_body_kept_as_http_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(502).set_body_string("Bad Gateway"))
+            .mount(&server)
+            .await;
+
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().no_retries();
+        match translator
+            .translate_batch(&["Hello"], &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::MaxRetriesExceeded { errors, .. }) => match &errors[..] {
+                [TranslationError::HttpError { status, body, .. }] => {
+                    assert_eq!(*status, StatusCode::BAD_GATEWAY);
+                    assert_eq!(body, "Bad Gateway");
+                }
+                other => panic!("Expected HttpError, got {:?}", other),
+            },
+            other => panic!("Expected MaxRetriesExceeded, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_geo_region_endpoints() {
+        let cases = [
+            (
+                GeoRegion::Global,
+                "https://api.cognitive.microsofttranslator.com",
+            ),
+            (
+                GeoRegion::Americas,
+                "https://api-nam.cognitive.microsofttranslator.com",
+            ),
+            (
+                GeoRegion::Europe,
+                "https://api-eur.cognitive.microsofttranslator.com",
+            ),
+            (
+                GeoRegion::AsiaPacific,
+                "https://api-apc.cognitive.microsofttranslator.com",
+            ),
+        ];
+        for (region, endpoint) in cases {
+            assert_eq!(region.endpoint(), endpoint);
+        }
+        assert_eq!(
+            MicrosoftConfig::default().endpoint_candidates,
+            cases.map(|(_, endpoint)| endpoint.to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_resolution_order() {
+        let default = MicrosoftTranslator::new(MicrosoftConfig::default());
+        assert_eq!(default.configured_endpoint().await, DEFAULT_ENDPOINT);
+
+        let regional = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .region(GeoRegion::AsiaPacific)
+                .build(),
+        );
+        assert_eq!(
+            regional.configured_endpoint().await,
+            GeoRegion::AsiaPacific.endpoint()
+        );
+
+        // 显式配置的端点优先于区域和自动选择，不会发起测速请求
+        let server = MockServer::start().await;
+        let explicit = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint("https://example.com")
+                .region(GeoRegion::Europe)
+                .auto_select_endpoint(true)
+                .endpoint_candidates(vec![server.uri()])
+                .build(),
+        );
+        assert_eq!(explicit.configured_endpoint().await, "https://example.com");
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_auto_select_fastest_endpoint() {
+        let mut servers = Vec::new();
+        for delay_ms in [300, 10, 150] {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/languages"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(json!({"translation": {}}))
+                        .set_delay(Duration::from_millis(delay_ms)),
+                )
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/translate"))
+                .respond_with(EchoResponder)
+                .mount(&server)
+                .await;
+            servers.push(server);
+        }
+
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .api_key(Some("test-key"))
+                .region(GeoRegion::Europe)
+                .auto_select_endpoint(true)
+                .endpoint_candidates(servers.iter().map(|s| s.uri()).collect())
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        for _ in 0..3 {
+            let result = translator
+                .translate_text("Hello", &target_lang, None, &TranslateOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(result, "T:Hello");
+        }
+
+        let mut language_hits = Vec::new();
+        let mut translate_hits = Vec::new();
+        for server in &servers {
+            let requests = server.received_requests().await.unwrap();
+            let count = |p: &str| requests.iter().filter(|r| r.url.path() == p).count();
+            language_hits.push(count("/languages"));
+            translate_hits.push(count("/translate"));
+        }
+        // 只测速一次，之后的请求都发往最快的端点
+        assert!(language_hits.iter().all(|&hits| hits <= 1));
+        assert_eq!(language_hits[1], 1);
+        assert_eq!(translate_hits, vec![0, 3, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_auto_select_falls_back_when_all_probes_fail() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/languages"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .region(GeoRegion::Americas)
+                .auto_select_endpoint(true)
+                .endpoint_candidates(vec![server.uri()])
+                .build(),
+        );
+        assert_eq!(
+            translator.configured_endpoint().await,
+            GeoRegion::Americas.endpoint()
+        );
+        // 测速失败时不缓存结果，下次使用时重新测速
+        assert!(translator.selected_endpoint.get().is_none());
+        translator.configured_endpoint().await;
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    /// 按文本返回预设检测结果的模拟响应，指定了 `from` 时不返回检测结果
+    struct ScriptedDetectionResponder;
+
+    impl Respond for ScriptedDetectionResponder {
+        fn respond(&self, request: &Request) -> ResponseTemplate {
+            let from = request
+                .url
+                .query_pairs()
+                .find(|(name, _)| name == "from")
+                .map(|(_, value)| value.into_owned());
+            let body: Vec<serde_json::Value> = serde_json::from_slice(&request.body).unwrap();
+            let results: Vec<serde_json::Value> = body
+                .iter()
+                .map(|item| {
+                    let text = item["text"].as_str().unwrap();
+                    match &from {
+                        Some(from) => json!({
+                            "translations": [{"text": format!("T[{}]:{}", from, text), "to": "zh"}]
+                        }),
+                        None => {
+                            let (language, score) = match text {
+                                "chat" => ("fr", 0.3),
+                                _ => ("en", 0.98),
+                            };
+                            json!({
+                                "detectedLanguage": {"language": language, "score": score},
+                                "translations": [{"text": format!("T:{}", text), "to": "zh"}]
+                            })
+                        }
+                    }
+                })
+                .collect();
+            ResponseTemplate::new(200).set_body_json(results)
+        }
+    }
+
+    async fn low_confidence_translator(
+        policy: LowConfidencePolicy,
+    ) -> (MockServer, MicrosoftTranslator) {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ScriptedDetectionResponder)
+            .mount(&server)
+            .await;
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .suggested_from("en".parse().unwrap())
+                .min_detection_score(0.5)
+                .on_low_confidence(policy)
+                .build(),
+        );
+        (server, translator)
+    }
+
+    fn texts_of(request: &Request) -> Vec<String> {
+        let body: Vec<serde_json::Value> = serde_json::from_slice(&request.body).unwrap();
+        body.iter()
+            .map(|item| item["text"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_low_confidence_uses_suggested_from() {
+        let (server, translator) =
+            low_confidence_translator(LowConfidencePolicy::UseSuggestedFrom).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let results = translator
+            .translate_batch_to_strings(
+                &["Hello world", "chat", "Good morning"],
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            results,
+            vec!["T:Hello world", "T[en]:chat", "T:Good morning"]
+        );
+
+        // 只有置信度低的文本被重新请求
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+        let suggested = requests[0]
+            .url
+            .query_pairs()
+            .find(|(name, _)| name == "suggestedFrom")
+            .map(|(_, value)| value.into_owned());
+        assert_eq!(suggested.as_deref(), Some("en"));
+        assert_eq!(texts_of(&requests[1]), vec!["chat"]);
+    }
+
+    #[tokio::test]
+    async fn test_low_confidence_error() {
+        let (server, translator) = low_confidence_translator(LowConfidencePolicy::Error).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        match translator
+            .translate_text("chat", &target_lang, None, &TranslateOptions::default())
+            .await
+        {
+            Err(TranslationError::LowConfidenceDetection {
+                language,
+                score,
+                min_score,
+            }) => {
+                assert_eq!(language, "fr");
+                assert_eq!(score, 0.3);
+                assert_eq!(min_score, 0.5);
+            }
+            other => panic!("Expected LowConfidenceDetection, got {:?}", other),
+        }
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_low_confidence_pass_through() {
+        let (server, translator) =
+            low_confidence_translator(LowConfidencePolicy::PassThrough).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let results = translator
+            .translate_batch_to_strings(
+                &["Hello world", "chat"],
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results, vec!["T:Hello world", "chat"]);
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+        // 指定源语言时不检查检测置信度
+        let source_lang: LanguageIdentifier = "fr".parse().unwrap();
+        let result = translator
+            .translate_text(
+                "chat",
+                &target_lang,
+                Some(&source_lang),
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result, "T[fr]:chat");
+    }
+
+    #[tokio::test]
+    async fn test_translate_batch_streaming_delivers_in_order() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(EchoResponder)
+            .mount(&server)
+            .await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let texts: Vec<String> = (0..2500).map(|i| format!("item {}", i)).collect();
+
+        let mut received = Vec::new();
+        let delivered = translator
+            .translate_batch_streaming(
+                &texts,
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+                |index, translation| {
+                    received.push((index, translation.translations[0].text.clone()))
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(delivered, 2500);
+        let expected: Vec<(usize, String)> =
+            (0..2500).map(|i| (i, format!("T:item {}", i))).collect();
+        assert_eq!(received, expected);
+        // 超出单次请求的条数限制时按顺序分成多个请求
+        let requests = server.received_requests().await.unwrap();
+        let sizes: Vec<usize> = requests.iter().map(|r| texts_of(r).len()).collect();
+        assert_eq!(sizes, vec![1000, 1000, 500]);
+    }
+
+    #[tokio::test]
+    async fn test_translate_batch_streaming_low_confidence_with_single_permit() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ScriptedDetectionResponder)
+            .mount(&server)
+            .await;
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .suggested_from("en".parse().unwrap())
+                .min_detection_score(0.5)
+                .concurrent_limit(1)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let options = TranslateOptions::default();
+        let mut received = Vec::new();
+        let streaming = translator.translate_batch_streaming(
+            ["Hello world", "chat", "Good morning"],
+            &target_lang,
+            None,
+            &options,
+            |index, translation| received.push((index, translation.translations[0].text.clone())),
+        );
+        // 重新翻译的请求在释放当前请求的许可之后发送，不会死锁
+        let delivered = tokio::time::timeout(Duration::from_secs(5), streaming)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(delivered, 3);
+        assert_eq!(
+            received,
+            vec![
+                (0, "T:Hello world".to_string()),
+                (1, "T[en]:chat".to_string()),
+                (2, "T:Good morning".to_string()),
+            ]
+        );
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(texts_of(&requests[1]), vec!["chat"]);
+    }
+
+    #[tokio::test]
+    async fn test_translate_batch_streaming_truncated_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"[{"translations": [{"text": "T:a", "to": "zh"}]}, {"translations": ["#,
+            ))
+            .mount(&server)
+            .await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let mut received = Vec::new();
+        let result = translator
+            .translate_batch_streaming(
+                ["a", "b"],
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+                |index, _| received.push(index),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(TranslationError::MalformedResponse(_))
+        ));
+        assert_eq!(received, vec![0]);
+
+        // 要求原样返回时，尚未交付的文本以原文交付
+        let mut received = Vec::new();
+        let delivered = translator
+            .translate_batch_streaming(
+                ["a", "b"],
+                &target_lang,
+                None,
+                &TranslateOptions::default().on_failure(crate::FailurePolicy::ReturnOriginal),
+                |index, translation| {
+                    received.push((
+                        index,
+                        translation.translations[0].text.clone(),
+                        translation.passthrough,
+                    ))
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(delivered, 2);
+        assert_eq!(
+            received,
+            vec![(0, "T:a".to_string(), false), (1, "b".to_string(), true)]
+        );
+    }
+
+    #[test]
+    fn test_suggested_from_required_for_fallback() {
+        let config = MicrosoftConfig::builder()
+            .api_key(Some("test-key"))
+            .min_detection_score(0.5)
+            .build();
+        assert!(matches!(
+            config.validate(),
+            Err(TranslationError::ConfigurationError(_))
+        ));
+        let config = MicrosoftConfig::builder()
+            .api_key(Some("test-key"))
+            .min_detection_score(0.5)
+            .on_low_confidence(LowConfidencePolicy::PassThrough)
+            .build();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_pool_options_builder() {
+        let config = MicrosoftConfig::builder()
+            .pool_max_idle_per_host(32)
+            .pool_idle_timeout(Some(Duration::from_secs(10)))
+            .tcp_keepalive(Some(Duration::from_secs(60)))
+            .connect_timeout(Duration::from_secs(1))
+            .build();
+        assert_eq!(config.pool.max_idle_per_host, 32);
+        assert_eq!(config.pool.idle_timeout, Some(Duration::from_secs(10)));
+        assert_eq!(config.pool.tcp_keepalive, Some(Duration::from_secs(60)));
+        assert!(!config.pool.http2_prior_knowledge);
+        assert_eq!(config.pool.connect_timeout, Some(Duration::from_secs(1)));
+        assert_eq!(MicrosoftConfig::default().pool, Default::default());
+        assert!(config.validate().is_ok());
+
+        let config = MicrosoftConfig::builder()
+            .prefer_http2(true)
+            .http2_keep_alive_interval(Duration::from_secs(30))
+            .http2_keep_alive_timeout(Duration::from_secs(5))
+            .http2_keep_alive_while_idle(true)
+            .build();
+        assert!(config.pool.prefer_http2);
+        assert_eq!(
+            config.pool.http2_keep_alive_interval,
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            config.pool.http2_keep_alive_timeout,
+            Some(Duration::from_secs(5))
+        );
+        assert!(config.pool.http2_keep_alive_while_idle);
+        assert!(config.validate().is_ok());
+
+        // 只使用 HTTP/1.1 时心跳不会生效
+        let config = MicrosoftConfig::builder()
+            .http2_keep_alive_interval(Duration::from_secs(30))
+            .build();
+        assert!(matches!(
+            config.validate(),
+            Err(TranslationError::ConfigurationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_dictionary_markup_overlapping_terms() {
+        let glossary = Glossary::new()
+            .term("New York", "纽约")
+            .term("New York City", "纽约市");
+        assert_eq!(
+            dictionary_markup("New York City & New York", &glossary),
+            "<mstrans:dictionary translation=\"纽约市\">New York City</mstrans:dictionary> &amp; \
+             <mstrans:dictionary translation=\"纽约\">New York</mstrans:dictionary>"
+        );
+    }
+
+    #[test]
+    fn test_dictionary_markup_metacharacters_escaped() {
+        let glossary = Glossary::new()
+            .term("C++", "C++")
+            .term("<T>", "\"泛型\"")
+            .term("a.b", "甲乙");
+        assert_eq!(
+            dictionary_markup("Use C++ <T> with a.b, not axb", &glossary),
+            "Use <mstrans:dictionary translation=\"C++\">C++</mstrans:dictionary> \
+             <mstrans:dictionary translation=\"&quot;泛型&quot;\">&lt;T&gt;</mstrans:dictionary> \
+             with <mstrans:dictionary translation=\"甲乙\">a.b</mstrans:dictionary>, not axb"
+        );
+    }
+
+    #[test]
+    fn test_strip_dictionary_markup() {
+        assert_eq!(
+            strip_dictionary_markup(
+                "<mstrans:dictionary translation=\"纽约\">纽约</mstrans:dictionary> &amp; &lt;T&gt; &amp;lt;"
+            ),
+            "纽约 & <T> &lt;"
+        );
+        assert_eq!(strip_dictionary_markup("纽约 <mstrans:dictionary"), "纽约 ");
+        assert_eq!(strip_dictionary_markup("没有标记"), "没有标记");
+    }
+
+    #[tokio::test]
+    async fn test_translate_with_glossary_uses_dictionary_markup() {
+        let server = MockServer::start().await;
+        // 服务端把标记原样留在了译文中
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(query_param("textType", "html"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "translations": [{
+                    "text": "我住在<mstrans:dictionary translation=\"纽约市\">纽约市</mstrans:dictionary> &amp; 约克",
+                    "to": "zh"
+                }]
+            }])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = mock_translator(server.uri()).await;
+        let glossary = Glossary::new()
+            .term("New York City", "纽约市")
+            .term("York", "约克");
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let result = Translator::translate_with_glossary(
+            &translator,
+            "I live in New York City & York",
+            &glossary,
+            &target_lang,
+            None,
+            &TranslateOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, "我住在纽约市 & 约克");
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(
+            body[0]["text"],
+            "I live in <mstrans:dictionary translation=\"纽约市\">New York City</mstrans:dictionary> \
+             &amp; <mstrans:dictionary translation=\"约克\">York</mstrans:dictionary>"
+        );
+    }
+
+    #[test]
+    fn test_config_debug_redacts_key() {
+        let config = MicrosoftConfig::builder()
+            .api_key(Some("ms-secret-key"))
+            .build();
+        let debug = format!("{:?}", config);
+        assert!(!debug.contains("ms-secret-key"), "{}", debug);
+        assert!(debug.contains("api_key: Some(***)"), "{}", debug);
+    }
+
+    #[tokio::test]
+    async fn test_error_body_redacts_key() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "error": {"code": 401000, "message": "Invalid key ms-secret-key"}
+            })))
+            .mount(&server)
+            .await;
+
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("ms-secret-key"))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().max_retries(0);
+        let error = translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+            .unwrap_err();
+        let output = format!("{:?} {}", error, error);
+        assert!(!output.contains("ms-secret-key"), "{}", output);
+        assert!(output.contains("Invalid key ***"), "{}", output);
+    }
+
+    #[test]
+    fn test_validate_token() {
+        let now = 1_700_000_000;
+        let token = jwt(&format!(r#"{{"region":"global","exp":{}}}"#, now + 600));
+        assert_eq!(validate_token(&token, now), Ok(now + 600));
+
+        for (token, reason) in [
+            (String::new(), "empty"),
+            ("not-a-jwt".to_string(), "3 dot-separated segments"),
+            ("a.b.c.d".to_string(), "3 dot-separated segments"),
+            ("a..c".to_string(), "base64url"),
+            ("<html>.oops.</html>".to_string(), "base64url"),
+            ("abc.e30.ghi".to_string(), "exp"),
+            ("abc.def.ghi".to_string(), "base64url"),
+            (jwt(r#"{"region":"global"}"#), "exp"),
+            (jwt(&format!(r#"{{"exp":{}}}"#, now - 1)), "expired"),
+        ] {
+            match validate_token(&token, now) {
+                Err(message) => assert!(message.contains(reason), "{}: {}", token, message),
+                Ok(exp) => panic!("Expected {} to be rejected, got exp {}", token, exp),
+            }
+        }
+    }
+
+    #[test]
+    fn test_token_expiry_from_exp_claim() {
+        let clock = MockClock::new();
+        let now = 1_700_000_000;
+
+        let expiry = token_expiry(now + 600, now, clock.now());
+        assert_eq!(
+            expiry,
+            clock.now() + Duration::from_secs(600) - TOKEN_EXPIRY_SAFETY_MARGIN
+        );
+        assert!(token_is_fresh(expiry, clock.now()));
+
+        // 有效期很短的 token 可以使用一次，但不会被当作新鲜的缓存
+        let expiry = token_expiry(now + 20, now, clock.now());
+        assert_eq!(expiry, clock.now());
+        assert!(!token_is_fresh(expiry, clock.now()));
+        let expiry = token_expiry(now + 90, now, clock.now());
+        assert!(!token_is_fresh(expiry, clock.now()));
+    }
+
+    /// 认证地址和端点都指向模拟服务器、使用自动认证的翻译器
+    fn auto_auth_translator(server: &MockServer, clock: &MockClock) -> MicrosoftTranslator {
+        MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .auth_endpoint(format!("{}/translate/auth", server.uri()))
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_auth_endpoint_success() {
+        let server = MockServer::start().await;
+        let token = jwt(&format!(r#"{{"exp":{}}}"#, unix_now() + 600));
+        Mock::given(method("GET"))
+            .and(path("/translate/auth"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!("{}\n", token)))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(header("Authorization", format!("Bearer {}", token)))
+            .respond_with(EchoResponder)
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let clock = MockClock::new();
+        let translator = auto_auth_translator(&server, &clock);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        assert_eq!(
+            translator
+                .translate("Hello", &target_lang, None)
+                .await
+                .unwrap(),
+            "T:Hello"
+        );
+        // 缓存的 token 在清除之前一直复用
+        assert_eq!(translator.get_auth_token().await.unwrap(), token);
+        translator.clear_cached_token().await;
+        assert_eq!(translator.get_auth_token().await.unwrap(), token);
+    }
+
+    #[tokio::test]
+    async fn test_auth_endpoint_forbidden() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/translate/auth"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("Forbidden"))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let clock = MockClock::new().auto_advance(true);
+        let translator = auto_auth_translator(&server, &clock);
+        match translator.get_auth_token().await {
+            Err(TranslationError::AuthenticationError(msg)) => {
+                assert!(msg.contains("HTTP 403"), "{}", msg)
+            }
+            other => panic!("Expected AuthenticationError, got {:?}", other),
+        }
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(1); 2]);
+        assert!(translator.cached_token.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_free_tier_hardening_breaker() {
+        let server = MockServer::start().await;
+        let throttled = Mock::given(method("GET"))
+            .and(path("/translate/auth"))
+            .respond_with(ResponseTemplate::new(429))
+            .mount_as_scoped(&server)
+            .await;
+        let clock = MockClock::new().auto_advance(true);
+        let hardened = |hardening| {
+            MicrosoftTranslator::new(
+                MicrosoftConfig::builder()
+                    .endpoint(server.uri())
+                    .auth_endpoint(format!("{}/translate/auth", server.uri()))
+                    .free_tier_hardening(hardening)
+                    .clock(Arc::new(clock.clone()))
+                    .build(),
+            )
+        };
+
+        // 未启用时冷却期过后的每次调用都重试 3 次
+        let plain = hardened(false);
+        for _ in 0..2 {
+            assert!(plain.get_auth_token().await.is_err());
+            clock.advance(AUTH_FAILURE_MAX_COOLDOWN);
+        }
+        assert_eq!(throttled.received_requests().await.len(), 6);
+        let start = clock.sleeps().len();
+
+        // 连续 3 次 429 后暂停获取 token，重试间隔带有随机部分
+        let translator = hardened(true);
+        let expect_paused = |result: Result<String, TranslationError>, seconds: &str| match result {
+            Err(TranslationError::AuthenticationError(msg)) => {
+                assert!(
+                    msg.contains(&format!("not retrying for {}s", seconds)),
+                    "{}",
+                    msg
+                )
+            }
+            other => panic!("Expected AuthenticationError, got {:?}", other),
+        };
+        expect_paused(translator.get_auth_token().await, "30");
+        assert_eq!(throttled.received_requests().await.len(), 9);
+        let retries = &clock.sleeps()[start..];
+        assert_eq!(retries.len(), 2);
+        assert!(
+            retries
+                .iter()
+                .all(|&sleep| sleep >= AUTH_RETRY_DELAY
+                    && sleep < AUTH_RETRY_DELAY + AUTH_RETRY_JITTER),
+            "{:?}",
+            retries
+        );
+
+        // 暂停期间不发送请求
+        clock.advance(Duration::from_secs(20));
+        expect_paused(translator.get_auth_token().await, "10");
+        assert_eq!(throttled.received_requests().await.len(), 9);
+
+        // 暂停结束后只尝试一次，仍然限流时暂停时长加倍
+        clock.advance(Duration::from_secs(10));
+        expect_paused(translator.get_auth_token().await, "60");
+        assert_eq!(throttled.received_requests().await.len(), 10);
+        drop(throttled);
+
+        // 恢复后成功获取 token 并清除限流状态
+        let token = jwt(&format!(r#"{{"exp":{}}}"#, unix_now() + 600));
+        Mock::given(method("GET"))
+            .and(path("/translate/auth"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(token.clone()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(translator.get_auth_token().await.unwrap(), token);
+        assert_eq!(
+            translator
+                .auth_breaker
+                .lock()
+                .unwrap()
+                .consecutive_throttles,
+            0
+        );
+
+        // 配置了 API Key 时不请求 token 地址
+        let keyed = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .api_key(Some("key"))
+                .auth_endpoint(format!("{}/translate/auth", server.uri()))
+                .free_tier_hardening(true)
+                .build(),
+        );
+        assert_eq!(keyed.get_auth_token().await.unwrap(), "key");
+    }
+
+    #[test]
+    fn test_auth_failure_cooldown_doubles_up_to_cap() {
+        let now = std::time::Instant::now();
+        let error = TranslationError::AuthenticationError("down".to_string());
+        let mut failures = AuthFailures::default();
+        assert!(failures.check(now).is_ok());
+        let mut cooldowns = Vec::new();
+        for _ in 0..8 {
+            failures.record(&error, now);
+            cooldowns.push(failures.remaining(now).unwrap());
+        }
+        assert_eq!(cooldowns[0], AUTH_FAILURE_COOLDOWN);
+        assert_eq!(cooldowns[1], AUTH_FAILURE_COOLDOWN * 2);
+        assert_eq!(cooldowns[7], AUTH_FAILURE_MAX_COOLDOWN);
+        assert!(failures.check(now + AUTH_FAILURE_MAX_COOLDOWN).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_auth_failure_cooldown_keeps_network_error() {
+        // 连接已关闭的端口得到连接被拒绝的错误
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/translate/auth", listener.local_addr().unwrap());
+        drop(listener);
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let error: TranslationError = client.get(&url).send().await.unwrap_err().into();
+        assert!(matches!(
+            error,
+            TranslationError::NetworkError {
+                kind: NetworkErrorKind::Connect,
+                source: Some(_),
+                ..
+            }
+        ));
+
+        let now = std::time::Instant::now();
+        let mut failures = AuthFailures::default();
+        failures.record(&error, now);
+        match failures.check(now) {
+            Err(cached @ TranslationError::NetworkError { .. }) => {
+                assert!(!cached.is_retryable());
+                assert!(matches!(
+                    &cached,
+                    TranslationError::NetworkError {
+                        kind: NetworkErrorKind::Connect,
+                        host: Some(host),
+                        source: None,
+                        cooldown: Some(_),
+                    } if host == "127.0.0.1"
+                ));
+                assert_eq!(
+                    cached.to_string(),
+                    "Network error: connection failed for host '127.0.0.1', not retrying for 5s"
+                );
+            }
+            other => panic!("Expected NetworkError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auth_failure_cooldown_skips_retries() {
+        let server = MockServer::start().await;
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let auth_endpoint = format!("http://{}/translate/auth", listener.local_addr().unwrap());
+        drop(listener);
+        let clock = MockClock::new().auto_advance(true);
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .auth_endpoint(auth_endpoint)
+                .proxy_mode(ProxyMode::None)
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        // 连接被拒绝，获取 token 失败后开始冷却
+        let error = translator.get_auth_token().await.unwrap_err();
+        assert!(matches!(
+            error,
+            TranslationError::NetworkError {
+                kind: NetworkErrorKind::Connect,
+                ..
+            }
+        ));
+
+        // 冷却期内的翻译只尝试一次，直接返回冷却期的错误，不经过重试退避
+        let sleeps = clock.sleeps().len();
+        let options = TranslateOptions::default().max_retries(3);
+        match translator
+            .translate_with_options("Hello", &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::NetworkError {
+                kind: NetworkErrorKind::Connect,
+                host: Some(host),
+                cooldown: Some(_),
+                ..
+            }) => assert_eq!(host, "127.0.0.1"),
+            other => panic!("Expected cooldown NetworkError, got {:?}", other),
+        }
+        assert_eq!(clock.sleeps().len(), sleeps);
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_auth_failure_cooldown_under_concurrent_load() {
+        let server = MockServer::start().await;
+        let failing = Mock::given(method("GET"))
+            .and(path("/translate/auth"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount_as_scoped(&server)
+            .await;
+        let clock = MockClock::new().auto_advance(true);
+        let translator = auto_auth_translator(&server, &clock);
+        let concurrent_attempts =
+            || futures::future::join_all((0..20).map(|_| translator.get_auth_token()));
+
+        // 并发的请求中只有第一个请求 token 地址，其余直接返回它的错误
+        let results = concurrent_attempts().await;
+        assert!(results.iter().all(Result::is_err));
+        assert_eq!(failing.received_requests().await.len(), 3);
+        match &results[19] {
+            Err(TranslationError::AuthenticationError(msg)) => {
+                assert!(msg.contains("failed once; not retrying for 5s"), "{}", msg);
+                assert!(msg.contains("HTTP 500"), "{}", msg);
+            }
+            other => panic!("Expected AuthenticationError, got {:?}", other),
+        }
+        let status = translator.auth_status().await;
+        assert!(!status.uses_api_key);
+        assert_eq!(status.consecutive_failures, 1);
+        assert_eq!(status.retry_after, Some(AUTH_FAILURE_COOLDOWN));
+        assert!(matches!(
+            status.last_error,
+            Some(TranslationError::AuthenticationError(_))
+        ));
+
+        // 冷却期内不请求，结束后只允许一次获取，仍然失败时冷却期加倍
+        clock.advance(AUTH_FAILURE_COOLDOWN - Duration::from_secs(1));
+        assert!(concurrent_attempts().await.iter().all(Result::is_err));
+        assert_eq!(failing.received_requests().await.len(), 3);
+        clock.advance(Duration::from_secs(1));
+        assert!(concurrent_attempts().await.iter().all(Result::is_err));
+        assert_eq!(failing.received_requests().await.len(), 6);
+        let status = translator.auth_status().await;
+        assert_eq!(status.consecutive_failures, 2);
+        assert_eq!(status.retry_after, Some(AUTH_FAILURE_COOLDOWN * 2));
+        drop(failing);
+
+        // 成功后清除失败状态，所有请求共用同一个 token
+        let token = jwt(&format!(r#"{{"exp":{}}}"#, unix_now() + 600));
+        Mock::given(method("GET"))
+            .and(path("/translate/auth"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(token.clone()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        clock.advance(AUTH_FAILURE_COOLDOWN * 2);
+        let results = concurrent_attempts().await;
+        assert!(
+            results
+                .iter()
+                .all(|result| result.as_ref().is_ok_and(|t| *t == token))
+        );
+        let status = translator.auth_status().await;
+        assert_eq!(status.consecutive_failures, 0);
+        assert!(status.last_error.is_none());
+        assert_eq!(status.retry_after, None);
+        assert!(status.token_expires_at.is_some());
+
+        let keyed =
+            MicrosoftTranslator::new(MicrosoftConfig::builder().api_key(Some("key")).build());
+        assert!(keyed.auth_status().await.uses_api_key);
+    }
+
+    #[tokio::test]
+    async fn test_auth_endpoint_malformed_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/translate/auth"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<html>sign in</html>"))
+            .expect(3)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(EchoResponder)
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let clock = MockClock::new().auto_advance(true);
+        let translator = auto_auth_translator(&server, &clock);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        match translator
+            .translate_with_options(
+                "Hello",
+                &target_lang,
+                None,
+                &TranslateOptions::default().no_retries(),
+            )
+            .await
+        {
+            Err(TranslationError::AuthenticationError(msg)) => {
+                assert!(msg.contains("invalid token"), "{}", msg);
+                assert!(msg.contains("after 3 attempts"), "{}", msg);
+            }
+            other => panic!("Expected AuthenticationError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_auth_endpoint_rejected() {
+        let config = MicrosoftConfig::builder()
+            .auth_endpoint("edge.microsoft.com/translate/auth")
+            .build();
+        assert!(matches!(
+            config.validate(),
+            Err(TranslationError::ConfigurationError(_))
+        ));
+        assert!(
+            MicrosoftConfig::builder()
+                .auth_endpoint("https://auth.internal.example/translate/auth")
+                .build()
+                .validate()
+                .is_ok()
+        );
+    }
+
+    /// 按模拟时钟记录每个请求到达时间的模拟响应
+    struct RecordingResponder {
+        clock: MockClock,
+        times: Arc<std::sync::Mutex<Vec<std::time::Instant>>>,
+    }
+
+    impl Respond for RecordingResponder {
+        fn respond(&self, _request: &Request) -> ResponseTemplate {
+            self.times.lock().unwrap().push(self.clock.now());
+            ResponseTemplate::new(200)
+                .set_body_json(json!([{"translations": [{"text": "ok", "to": "zh"}]}]))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_min_request_interval_spaces_burst() {
+        let clock = MockClock::new();
+        let times = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(RecordingResponder {
+                clock: clock.clone(),
+                times: times.clone(),
+            })
+            .mount(&server)
+            .await;
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .min_request_interval(Duration::from_millis(250))
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+
+        let burst = futures::future::join_all(
+            (0..10).map(|_| translator.translate_text("Hello", &target_lang, None, &options)),
+        );
+        let driver = async {
+            for received in 1..10 {
+                // 上一个请求到达服务端、下一个请求等待间隔后再推进时钟
+                clock.wait_for_sleepers(1).await;
+                while times.lock().unwrap().len() < received {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+                clock.advance(Duration::from_millis(250));
+            }
+        };
+        let (results, _) = tokio::join!(burst, driver);
+        assert!(results.iter().all(Result::is_ok), "{:?}", results);
+
+        let times = times.lock().unwrap().clone();
+        assert_eq!(times.len(), 10);
+        // 每次只等待维持间隔所需的时长
+        for pair in times.windows(2) {
+            assert_eq!(pair[1] - pair[0], Duration::from_millis(250));
+        }
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(250); 9]);
+    }
+
+    #[tokio::test]
+    async fn test_default_options_apply_unless_overridden() {
+        let server = MockServer::start().await;
+        for request_id in ["from-config", "from-call-site"] {
+            Mock::given(method("POST"))
+                .and(path("/translate"))
+                .and(header("X-Request-Id", request_id))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                    {"translations": [{"text": request_id, "to": "zh"}]}
+                ])))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let config = MicrosoftConfig::builder()
+            .endpoint(server.uri())
+            .api_key(Some("test-key"))
+            .default_options(TranslateOptions::default().request_id("from-config"))
+            .build();
+        let translator = MicrosoftTranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let result = translator
+            .translate("Hello", &target_lang, None)
+            .await
+            .unwrap();
+        assert_eq!(result, "from-config");
+
+        let options = TranslateOptions::default().request_id("from-call-site");
+        let result = translator
+            .translate_with_options("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(result, "from-call-site");
+    }
+
+    #[tokio::test]
+    async fn test_raw_response_included_when_requested() {
+        let server = MockServer::start().await;
+        let body = json!([
+            {
+                "detectedLanguage": {"language": "en", "score": 1.0},
+                "translations": [{"text": "你好", "to": "zh", "sentLen": {"srcSentLen": [5]}}]
+            }
+        ]);
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body.clone()))
+            .mount(&server)
+            .await;
+
+        let config = MicrosoftConfig::builder()
+            .endpoint(server.uri())
+            .api_key(Some("test-key"))
+            .build();
+        let translator = MicrosoftTranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(detail.raw, None);
+
+        let options = TranslateOptions::default().include_raw_response(true);
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "你好");
+        assert_eq!(detail.raw, Some(body.clone()));
+
+        let results = translator
+            .translate_batch(&["Hello"], &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(results[0].raw.as_ref(), Some(&body[0]));
+    }
+
+    #[tokio::test]
+    async fn test_normalize_input_before_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(wiremock::matchers::body_json(json!([
+                {"text": "Caf\u{E9} ABC"},
+                {"text": "ok"}
+            ])))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"translations": [{"text": "咖啡馆 ABC", "to": "zh"}]},
+                {"translations": [{"text": "好", "to": "zh"}]}
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = MicrosoftConfig::builder()
+            .endpoint(server.uri())
+            .api_key(Some("test-key"))
+            .build();
+        let translator = MicrosoftTranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().normalize_input(Normalization {
+            form: NormalizationForm::Nfkc,
+            collapse_whitespace: true,
+            ..Default::default()
+        });
+        let results = translator
+            .translate_batch_to_strings(
+                &["Cafe\u{301}  \u{FF21}\u{FF22}\u{FF23}", "ok"],
+                &target_lang,
+                None,
+                &options,
+            )
+            .await
+            .unwrap();
+        assert_eq!(results, vec!["咖啡馆 ABC", "好"]);
+    }
+
+    #[tokio::test]
+    async fn test_protect_numbers_restores_placeholders() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(wiremock::matchers::body_json(json!([
+                {"text": "Version ⟦A⟧ ships ⟦B⟧"},
+                {"text": "no numbers"}
+            ])))
+            .respond_with(EchoResponder)
+            .expect(1)
+            .mount(&server)
+            .await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().protect_numbers(true);
+
+        let results = translator
+            .translate_batch_to_strings(
+                &["Version 1.2.3 ships 2024-05-03", "no numbers"],
+                &target_lang,
+                None,
+                &options,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            results,
+            vec!["T:Version 1.2.3 ships 2024-05-03", "T:no numbers"]
+        );
+    }
+
+    /// 批次中包含 "BAD" 时以文本过长拒绝整批，否则与 [`EchoResponder`] 相同
+    struct PoisonedBatchResponder;
+
+    impl Respond for PoisonedBatchResponder {
+        fn respond(&self, request: &Request) -> ResponseTemplate {
+            let body: Vec<serde_json::Value> = serde_json::from_slice(&request.body).unwrap();
+            if body
+                .iter()
+                .any(|item| item["text"].as_str().unwrap().contains("BAD"))
+            {
+                return ResponseTemplate::new(400).set_body_json(json!({
+                    "error": {"code": 400050, "message": "The input text is too long."}
+                }));
+            }
+            EchoResponder.respond(request)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_isolated_bisects_to_offending_items() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(PoisonedBatchResponder)
+            .mount(&server)
+            .await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let texts = ["a", "b", "c", "d", "e", "BAD f", "g", "h"];
+
+        let results = translator
+            .translate_batch_isolated(texts, &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        // 整批 1 次，[0, 4) [4, 8) [4, 6) [6, 8) [4] [5] 共 6 次
+        assert_eq!(server.received_requests().await.unwrap().len(), 7);
+        assert_eq!(results.report.indices(), vec![5]);
+        assert!(matches!(
+            &results.report.failed[0].1,
+            TranslationError::Provider { code, .. } if code == "400050"
+        ));
+        let translated: Vec<Option<String>> = results
+            .translations
+            .iter()
+            .map(|result| result.as_ref().map(|r| r.translations[0].text.clone()))
+            .collect();
+        assert_eq!(translated[4].as_deref(), Some("T:e"));
+        assert_eq!(translated[5], None);
+        assert_eq!(translated[7].as_deref(), Some("T:h"));
+
+        // 没有出错的条目时只发送一次请求
+        server.reset().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(PoisonedBatchResponder)
+            .expect(1)
+            .mount(&server)
+            .await;
+        let results = translator
+            .translate_batch_isolated(["a", "b"], &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert!(results.report.is_empty());
+        assert!(results.translations.iter().all(Option::is_some));
+    }
+
+    #[tokio::test]
+    async fn test_batch_budget_truncates_or_rejects() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(PoisonedBatchResponder)
+            .mount(&server)
+            .await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let texts = ["aaaa", "bbb", "cc", "d"];
+
+        let options = TranslateOptions::default().max_batch_chars(9);
+        let error = translator
+            .translate_batch(texts, &target_lang, None, &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            TranslationError::BudgetExceeded { total: 10, max: 9 }
+        ));
+        assert!(server.received_requests().await.unwrap().is_empty());
+
+        let truncating = options.truncate_to_budget(true);
+        let results = translator
+            .translate_batch_isolated(texts, &target_lang, None, &truncating)
+            .await
+            .unwrap();
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(
+            body,
+            json!([{"text": "aaaa"}, {"text": "bbb"}, {"text": "cc"}])
+        );
+        assert_eq!(results.report.indices(), vec![3]);
+        assert!(matches!(
+            results.report.failed[0].1,
+            TranslationError::BudgetExceeded { total: 10, max: 9 }
+        ));
+        assert_eq!(
+            results.translations[2].as_ref().unwrap().translations[0].text,
+            "T:cc"
+        );
+        assert!(results.translations[3].is_none());
+
+        // 不论 on_failure 如何，预算内的条目照常返回，跳过的条目逐条标记
+        let translations = translator
+            .translate_batch(texts, &target_lang, None, &truncating)
+            .await
+            .unwrap();
+        let texts_of = |translations: &[MicrosoftTranslation]| -> Vec<String> {
+            translations
+                .iter()
+                .map(|translation| translation.translations[0].text.clone())
+                .collect()
+        };
+        assert_eq!(texts_of(&translations), ["T:aaaa", "T:bbb", "T:cc", "d"]);
+        assert!(
+            translations[..3]
+                .iter()
+                .all(|t| !t.passthrough && t.error.is_none())
+        );
+        assert!(translations[3].passthrough && !translations[3].partial);
+        assert_eq!(
+            translations[3].error.as_deref(),
+            Some("Batch of 10 characters exceeds the budget of 9 characters")
+        );
+        // 只返回字符串时只包含预算内的条目
+        let strings = translator
+            .translate_batch_to_strings(texts, &target_lang, None, &truncating)
+            .await
+            .unwrap();
+        assert_eq!(strings, ["T:aaaa", "T:bbb", "T:cc"]);
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_batch_budget_truncates_split_chunks() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(EchoResponder)
+            .mount(&server)
+            .await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        // 第二条超出单次请求的长度限制，按段落拆分为两个片段
+        let head = "x".repeat(30_000);
+        let long = format!("{}\n\n{}", head, "y".repeat(30_000));
+        let texts = ["ab", long.as_str(), "cd"];
+        let options = |max: usize| {
+            TranslateOptions::default()
+                .max_batch_chars(max)
+                .truncate_to_budget(true)
+                .auto_split(true)
+        };
+
+        // 预算恰好容纳第一个片段时只发送该片段，这一条标记为部分翻译
+        let translations = translator
+            .translate_batch(texts, &target_lang, None, &options(2 + 30_000))
+            .await
+            .unwrap();
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body, json!([{"text": "ab"}, {"text": head}]));
+        assert_eq!(translations.len(), 3);
+        assert_eq!(translations[1].translations[0].text, format!("T:{}", head));
+        assert!(translations[1].partial && !translations[1].passthrough);
+        assert!(translations[1].error.is_some());
+        assert!(translations[2].passthrough);
+        assert_eq!(translations[2].translations[0].text, "cd");
+
+        // 少一个字符时整条跳过，不发送任何片段
+        let translations = translator
+            .translate_batch(texts, &target_lang, None, &options(2 + 29_999))
+            .await
+            .unwrap();
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+        let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(body, json!([{"text": "ab"}]));
+        assert!(translations[1].passthrough && !translations[1].partial);
+        assert_eq!(translations[1].translations[0].text, long);
+        assert!(translations[2].passthrough);
+    }
+
+    #[tokio::test]
+    async fn test_batch_isolated_respects_request_bound() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(PoisonedBatchResponder)
+            .mount(&server)
+            .await;
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .max_bisect_requests(3)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let texts = ["BAD a", "b", "c", "d", "e", "f", "g", "BAD h"];
+
+        let results = translator
+            .translate_batch_isolated(texts, &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(server.received_requests().await.unwrap().len(), 4);
+        // [0, 4) 和 [4, 8) 都失败，只剩一次请求：[4, 8) 拆出的 [4, 6) 成功，
+        // 其余尚未定位的条目都记为失败
+        assert_eq!(results.report.indices(), vec![0, 1, 2, 3, 6, 7]);
+        assert!(results.translations[4].is_some() && results.translations[5].is_some());
+
+        // 与单条文本无关的错误直接返回
+        server.reset().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "error": {"code": 401000, "message": "Invalid credentials."}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        assert!(
+            translator
+                .translate_batch_isolated(texts, &target_lang, None, &TranslateOptions::default())
+                .await
+                .is_err()
+        );
+    }
+
+    fn custom_model_translator(
+        endpoint: String,
+        allow_fallback: Option<bool>,
+    ) -> MicrosoftTranslator {
+        let builder = MicrosoftConfig::builder()
+            .endpoint(endpoint)
+            .api_key(Some("test-key"))
+            .category("my-category");
+        let builder = match allow_fallback {
+            Some(allow_fallback) => builder.allow_fallback(allow_fallback),
+            None => builder,
+        };
+        MicrosoftTranslator::new(builder.build())
+    }
+
+    #[tokio::test]
+    async fn test_category_and_allow_fallback_sent() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(query_param("category", "my-category"))
+            .and(query_param("allowFallback", "false"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("X-MT-System", "Custom")
+                    .set_body_json(json!([
+                        {"translations": [{"text": "你好", "to": "zh"}]}
+                    ])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = custom_model_translator(server.uri(), Some(false));
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "你好");
+        assert_eq!(detail.provider_metadata["category"], "my-category");
+        assert_eq!(detail.provider_metadata["system"], "Custom");
+    }
+
+    #[tokio::test]
+    async fn test_provenance_from_response_headers() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("X-RequestId", "EUR0:2f0c1a7e")
+                    .set_body_json(json!([
+                        {"translations": [{"text": "你好", "to": "zh"}]}
+                    ])),
+            )
+            .mount(&server)
+            .await;
+
+        let timestamp = std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .category("my-category")
+                .clock(Arc::new(MockClock::new().wall_clock(timestamp)))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        let provenance = detail.provenance.unwrap();
+        assert_eq!(provenance.provider, "microsoft");
+        assert_eq!(provenance.model.as_deref(), Some("my-category"));
+        assert_eq!(provenance.request_id.as_deref(), Some("EUR0:2f0c1a7e"));
+        assert_eq!(provenance.prompt_fingerprint, None);
+        assert_eq!(provenance.system_fingerprint, None);
+        assert_eq!(provenance.timestamp, timestamp);
+        let inputs = translator
+            .cache_key_inputs(&target_lang, None, &TranslateOptions::default())
+            .unwrap();
+        assert_eq!(provenance.options_fingerprint, inputs.fingerprint());
+
+        let html = translator
+            .translate_detailed(
+                "Hello",
+                &target_lang,
+                None,
+                &TranslateOptions::default().html(true),
+            )
+            .await
+            .unwrap()
+            .provenance
+            .unwrap();
+        assert_ne!(html.options_fingerprint, provenance.options_fingerprint);
+
+        let json = provenance.to_json();
+        assert_eq!(json["request_id"], "EUR0:2f0c1a7e");
+        assert_eq!(json["timestamp_ms"], 1_700_000_000_000u64);
+        assert_eq!(json["prompt_fingerprint"], serde_json::Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_language_codes_mapped_for_microsoft() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(query_param("to", "zh-Hant"))
+            .and(query_param("from", "fil"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"translations": [{"text": "你好", "to": "zh-Hant"}]}
+            ])))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh-Hant-TW".parse().unwrap();
+        let source_lang: LanguageIdentifier = "tl".parse().unwrap();
+        let detail = translator
+            .translate_detailed(
+                "Kumusta",
+                &target_lang,
+                Some(&source_lang),
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            detail.provider_metadata["language_mapping"],
+            json!({
+                "to": {"requested": "zh-Hant-TW", "sent": "zh-Hant"},
+                "from": {"requested": "tl", "sent": "fil"}
+            })
+        );
+
+        // 已经是微软使用的代码时不记录映射
+        let target_lang: LanguageIdentifier = "zh-Hant".parse().unwrap();
+        let source_lang: LanguageIdentifier = "fil".parse().unwrap();
+        let detail = translator
+            .translate_detailed(
+                "Kumusta",
+                &target_lang,
+                Some(&source_lang),
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert!(detail.provider_metadata.get("language_mapping").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_allow_fallback_omitted_by_default() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"translations": [{"text": "你好", "to": "zh"}]}
+            ])))
+            .mount(&server)
+            .await;
+
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert!(detail.provider_metadata.get("category").is_none());
+        assert!(detail.provider_metadata.get("system").is_none());
+
+        let requests = server.received_requests().await.unwrap();
+        let query = requests[0].url.query().unwrap();
+        assert!(!query.contains("category") && !query.contains("allowFallback"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_model_unavailable_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "error": {
+                    "code": 400079,
+                    "message": "The custom system requested for translation between from and to language does not exist."
+                }
+            })))
+            .mount(&server)
+            .await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let translator = custom_model_translator(server.uri(), Some(false));
+        match translator
+            .translate_batch(&["Hello"], &target_lang, None, &TranslateOptions::default())
+            .await
+        {
+            Err(TranslationError::CustomModelUnavailable {
+                provider, category, ..
+            }) => {
+                assert_eq!(provider, "microsoft");
+                assert_eq!(category, "my-category");
+            }
+            other => panic!("Expected CustomModelUnavailable, got {:?}", other),
+        }
+
+        // 允许回退时保留原始的服务端错误
+        let translator = custom_model_translator(server.uri(), None);
+        match translator
+            .translate_batch(&["Hello"], &target_lang, None, &TranslateOptions::default())
+            .await
+        {
+            Err(TranslationError::Provider { code, .. }) => assert_eq!(code, "400079"),
+            other => panic!("Expected Provider error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_allow_fallback_requires_category() {
+        let config = MicrosoftConfig::builder().allow_fallback(false).build();
+        assert!(matches!(
+            config.validate(),
+            Err(TranslationError::ConfigurationError(_))
+        ));
+        let config = MicrosoftConfig::builder()
+            .category("my-category")
+            .allow_fallback(false)
+            .build();
+        assert!(config.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_batch_accepts_any_string_collection() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(EchoResponder)
+            .mount(&server)
+            .await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        let expected = vec!["T:a".to_string(), "T:b".to_string()];
+
+        let strs: &[&str] = &["a", "b"];
+        let owned: Vec<String> = vec!["a".to_string(), "b".to_string()];
+        let borrowed: &[String] = &owned;
+        assert_eq!(
+            translator
+                .translate_batch_to_strings(strs, &target_lang, None, &options)
+                .await
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            translator
+                .translate_batch_to_strings(borrowed, &target_lang, None, &options)
+                .await
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            translator
+                .translate_batch_to_strings(&owned, &target_lang, None, &options)
+                .await
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            translator
+                .translate_batch_to_strings(owned, &target_lang, None, &options)
+                .await
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            translator
+                .translate_batch_to_strings("a\nb".lines(), &target_lang, None, &options)
+                .await
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_output_follows_iterator_order() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(EchoResponder)
+            .mount(&server)
+            .await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let results = translator
+            .translate_batch_to_strings(
+                (0..50).rev().map(|i| format!("text-{}", i)),
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        let expected: Vec<String> = (0..50).rev().map(|i| format!("T:text-{}", i)).collect();
+        assert_eq!(results, expected);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_queue_depth_rejects_when_saturated() {
+        let body = json!([{"translations": [{"text": "你好", "to": "zh"}]}]).to_string();
+        let server =
+            DelayedServer::start(DelayedResponse::new(body).header_delay(Duration::from_secs(10)))
+                .await;
+        let translator = Arc::new(MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .concurrent_limit(1)
+                .max_queue_depth(0)
+                .build(),
+        ));
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let busy = tokio::spawn({
+            let translator = translator.clone();
+            let target_lang = target_lang.clone();
+            async move { translator.translate("Hello", &target_lang, None).await }
+        });
+        while translator.concurrency.available_permits() > 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let start = tokio::time::Instant::now();
+        match translator.translate("Hello", &target_lang, None).await {
+            Err(TranslationError::Overloaded { waited }) => assert_eq!(waited, Duration::ZERO),
+            other => panic!("Expected Overloaded, got {:?}", other),
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        assert_eq!(busy.await.unwrap().unwrap(), "你好");
+        assert_eq!(translator.concurrency.available_permits(), 1);
+        assert_eq!(translator.concurrency.waiters(), 0);
+        assert_eq!(server.requests(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_on_failure_policies_with_failing_server() {
+        use crate::options::FailurePolicy;
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("unavailable"))
+            .mount(&server)
+            .await;
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .clock(Arc::new(MockClock::new().auto_advance(true)))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh-Hans".parse().unwrap();
+        let options = TranslateOptions::default().max_retries(1);
+
+        assert!(matches!(
+            translator
+                .translate_batch(["Hello"], &target_lang, None, &options)
+                .await,
+            Err(TranslationError::MaxRetriesExceeded { attempts: 2, .. })
+        ));
+
+        for policy in [
+            FailurePolicy::ReturnOriginal,
+            FailurePolicy::ReturnOriginalIf(|error| {
+                matches!(error, TranslationError::MaxRetriesExceeded { .. })
+            }),
+        ] {
+            let options = options.clone().on_failure(policy);
+            let results = translator
+                .translate_batch(["one", "two"], &target_lang, None, &options)
+                .await
+                .unwrap();
+            assert_eq!(results.len(), 2);
+            assert!(results.iter().all(|result| result.passthrough));
+            assert_eq!(results[1].translations[0].text, "two");
+            assert_eq!(results[1].translations[0].to, "zh-Hans");
+
+            let detail = translator
+                .translate_detailed("Hello", &target_lang, None, &options)
+                .await
+                .unwrap();
+            assert_eq!(detail.text, "Hello");
+            assert!(detail.passthrough);
+            assert_eq!(detail.detected_source, None);
+            assert_eq!(
+                translator
+                    .translate_with_options("Hello", &target_lang, None, &options)
+                    .await
+                    .unwrap(),
+                "Hello"
+            );
+        }
+
+        let options = options.on_failure(FailurePolicy::ReturnOriginalIf(|error| {
+            matches!(error, TranslationError::TimeoutError)
+        }));
+        assert!(matches!(
+            translator
+                .translate_detailed("Hello", &target_lang, None, &options)
+                .await,
+            Err(TranslationError::MaxRetriesExceeded { .. })
+        ));
+        assert_eq!(server.received_requests().await.unwrap().len(), 2 * 8);
+    }
+
+    #[test]
+    fn test_request_preview_with_api_key() {
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint("https://translator.example.com")
+                .api_key(Some("ms-secret"))
+                .category("my-category")
+                .allow_fallback(true)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh-CN".parse().unwrap();
+        let source_lang: LanguageIdentifier = "en".parse().unwrap();
+        let options = TranslateOptions::default().html(true).request_id("req-1");
+        let preview = translator
+            .build_request_preview(
+                ["<b>Hello</b>", "World"],
+                &target_lang,
+                Some(&source_lang),
+                &options,
+            )
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&preview).unwrap(),
+            json!({
+                "url": "https://translator.example.com/translate",
+                "query": [
+                    ["api-version", "3.0"],
+                    ["to", "zh-Hans"],
+                    ["includeSentenceLength", "true"],
+                    ["from", "en"],
+                    ["textType", "html"],
+                    ["category", "my-category"],
+                    ["allowFallback", "true"]
+                ],
+                "headers_redacted": [
+                    ["Authorization", "Ocp-Apim-Subscription-Key ***"],
+                    ["Content-Type", "application/json"],
+                    ["X-Request-Id", "req-1"]
+                ],
+                "body_json": [{"text": "<b>Hello</b>"}, {"text": "World"}]
+            })
+        );
+        assert!(
+            !serde_json::to_string(&preview)
+                .unwrap()
+                .contains("ms-secret")
+        );
+    }
+
+    #[test]
+    fn test_request_preview_without_api_key() {
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .suggested_from("pt-PT".parse().unwrap())
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "sr-Latn".parse().unwrap();
+        let preview = translator
+            .build_request_preview(["Olá"], &target_lang, None, &TranslateOptions::default())
+            .unwrap();
+        assert_eq!(preview.url, format!("{}/translate", DEFAULT_ENDPOINT));
+        assert_eq!(
+            preview.query,
+            [
+                ("api-version", "3.0"),
+                ("to", "sr-Latn"),
+                ("includeSentenceLength", "true"),
+                ("suggestedFrom", "pt-PT"),
+            ]
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+        );
+        // token 在发送时才获取，预览中只显示认证方案
+        assert_eq!(
+            preview.headers_redacted[0],
+            ("Authorization".to_string(), "Bearer ***".to_string())
+        );
+        assert_eq!(preview.body_json, json!([{"text": "Olá"}]));
+
+        let invalid = TranslateOptions::default().endpoint("not a url");
+        assert!(matches!(
+            translator.build_request_preview(["Olá"], &target_lang, None, &invalid),
+            Err(TranslationError::ConfigurationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_request_preview_matches_sent_request() {
+        let server = MockServer::start().await;
+        let translator = mock_translator(server.uri()).await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        let preview = translator
+            .build_request_preview(["Hello"], &target_lang, None, &options)
+            .unwrap();
+        let mut mock = Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(wiremock::matchers::body_json(&preview.body_json));
+        for (name, value) in &preview.query {
+            mock = mock.and(query_param(name.as_str(), value.as_str()));
+        }
+        mock.respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"translations": [{"text": "你好", "to": "zh-Hans"}]}
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+        assert_eq!(preview.url, format!("{}/translate", server.uri()));
+        let results = translator
+            .translate_batch(["Hello"], &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(results[0].translations[0].text, "你好");
+    }
+
+    #[tokio::test]
+    async fn test_auth_and_translate_use_explicit_proxy() {
+        // 请求的地址是 target，但都应该发到代理
+        let target = MockServer::start().await;
+        let proxy = MockServer::start().await;
+        let token = jwt(&format!(r#"{{"exp":{}}}"#, unix_now() + 600));
+        Mock::given(method("GET"))
+            .and(path("/translate/auth"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(token.clone()))
+            .expect(1)
+            .mount(&proxy)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(header("Authorization", format!("Bearer {}", token)))
+            .respond_with(EchoResponder)
+            .expect(1)
+            .mount(&proxy)
+            .await;
+
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(target.uri())
+                .auth_endpoint(format!("{}/translate/auth", target.uri()))
+                .proxy_mode(ProxyMode::Explicit(proxy.uri()))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        assert_eq!(
+            translator
+                .translate("Hello", &target_lang, None)
+                .await
+                .unwrap(),
+            "T:Hello"
+        );
+        assert!(target.received_requests().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_validate_proxy_mode() {
+        let config = |proxy_mode| MicrosoftConfig::builder().proxy_mode(proxy_mode).build();
+        assert_eq!(MicrosoftConfig::default().proxy_mode, ProxyMode::System);
+        assert!(config(ProxyMode::None).validate().is_ok());
+        assert!(
+            config(ProxyMode::Explicit(
+                "http://proxy.example.com:3128".to_string()
+            ))
+            .validate()
+            .is_ok()
+        );
+        match config(ProxyMode::Explicit("not a url".to_string())).validate() {
+            Err(TranslationError::ConfigurationError(msg)) => {
+                assert!(msg.starts_with("Invalid proxy 'not a url'"), "{}", msg)
+            }
+            other => panic!("Expected ConfigurationError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_version_override() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(query_param("api-version", "3.1-preview"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"translations": [{"text": "你好", "to": "zh"}]}
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/detect"))
+            .and(query_param("api-version", "3.1-preview"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!([{"language": "en", "score": 1.0}])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .api_version("3.1-preview")
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        assert_eq!(
+            translator
+                .translate_text("Hello", &target_lang, None, &options)
+                .await
+                .unwrap(),
+            "你好"
+        );
+        translator.detect("Hello", &options).await.unwrap();
+
+        match MicrosoftConfig::builder()
+            .api_version(" ")
+            .build()
+            .validate()
+        {
+            Err(TranslationError::ConfigurationError(msg)) => {
+                assert_eq!(msg, "api_version must not be empty")
+            }
+            other => panic!("Expected ConfigurationError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_api_version_is_configuration_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "error": {
+                    "code": 400021,
+                    "message": "The API version parameter is missing or invalid."
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .api_version("2.0")
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        match translator
+            .translate_text("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+        {
+            Err(TranslationError::ConfigurationError(msg)) => assert_eq!(
+                msg,
+                "Microsoft Translator does not support api_version '2.0': \
+                 The API version parameter is missing or invalid."
+            ),
+            other => panic!("Expected ConfigurationError, got {:?}", other),
+        }
+        // 配置错误不会重试
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_detect_uses_cache() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/detect"))
+            .and(query_param("api-version", "3.0"))
+            .and(wiremock::matchers::body_json(json!([{"text": "Hello"}])))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"language": "en", "score": 0.97, "isTranslationSupported": true}
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/detect"))
+            .and(wiremock::matchers::body_json(json!([{"text": "Bonjour"}])))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!([{"language": "fr", "score": 1.0}])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = mock_translator(server.uri()).await;
+        let options = TranslateOptions::default();
+        // 规范化后相同的文本只请求一次
+        for (text, language) in [
+            ("Hello", "en"),
+            ("hello", "en"),
+            ("  Hello ", "en"),
+            ("Bonjour", "fr"),
+            ("Bonjour", "fr"),
+        ] {
+            let detected = translator.detect(text, &options).await.unwrap();
+            assert_eq!(detected.language, language);
+        }
+        let stats = translator.detection_cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.len), (3, 2, 2));
+    }
+
+    #[tokio::test]
+    async fn test_detect_cache_disabled() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/detect"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!([{"language": "en", "score": 1.0}])),
+            )
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .detection_cache_size(0)
+                .build(),
+        );
+        for _ in 0..3 {
+            translator
+                .detect("Hello", &TranslateOptions::default())
+                .await
+                .unwrap();
+        }
+        assert_eq!(translator.detection_cache_stats().hits, 0);
+    }
 }
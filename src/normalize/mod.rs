@@ -0,0 +1,88 @@
+//! 输入文本规范化
+//!
+//! 从 PDF 等来源复制的文本常带有软连字符、零宽字符和组合字符序列，既会干扰
+//! 翻译服务，也会增加按字符计费的长度。设置
+//! [`crate::TranslateOptions::normalize_input`] 后，翻译器在发送请求之前按
+//! [`Normalization`] 处理文本。处理结果是新的字符串，调用方的原文保持不变。
+
+use std::borrow::Cow;
+use unicode_normalization::UnicodeNormalization;
+
+/// Unicode 规范化形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NormalizationForm {
+    /// 标准组合形式，只合并组合字符序列
+    #[default]
+    Nfc,
+    /// 兼容组合形式，同时把全角字母、连字等兼容字符替换为标准字符
+    Nfkc,
+}
+
+/// 输入文本的规范化设置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Normalization {
+    /// Unicode 规范化形式
+    pub form: NormalizationForm,
+    /// 是否去掉零宽空格（U+200B）、字连接符（U+2060）和 BOM（U+FEFF）
+    ///
+    /// 零宽连接符和零宽不连接符（U+200D、U+200C）会影响 emoji 和部分文字的显示，不会被去掉
+    pub strip_zero_width: bool,
+    /// 是否去掉软连字符（U+00AD）
+    pub strip_soft_hyphens: bool,
+    /// 是否把行内连续的空白合并为一个空格，换行保持不变
+    pub collapse_whitespace: bool,
+}
+
+/// 按设置规范化文本，没有变化时返回原文的引用
+pub(crate) fn normalize<'a>(text: &'a str, normalization: &Normalization) -> Cow<'a, str> {
+    let stripped: String = text
+        .chars()
+        .filter(|&c| {
+            !(normalization.strip_zero_width && matches!(c, '\u{200B}' | '\u{2060}' | '\u{FEFF}')
+                || normalization.strip_soft_hyphens && c == '\u{00AD}')
+        })
+        .collect();
+    let normalized: String = match normalization.form {
+        NormalizationForm::Nfc => stripped.nfc().collect(),
+        NormalizationForm::Nfkc => stripped.nfkc().collect(),
+    };
+    let normalized = if normalization.collapse_whitespace {
+        collapse_whitespace(&normalized)
+    } else {
+        normalized
+    };
+    if normalized == text {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(normalized)
+    }
+}
+
+/// 按可选的设置规范化文本，未设置时返回原文的引用
+pub(crate) fn apply<'a>(text: &'a str, normalization: Option<&Normalization>) -> Cow<'a, str> {
+    match normalization {
+        Some(normalization) => normalize(text, normalization),
+        None => Cow::Borrowed(text),
+    }
+}
+
+/// 把行内连续的空白合并为一个空格
+fn collapse_whitespace(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() && !matches!(c, '\n' | '\r') {
+            if !in_space {
+                output.push(' ');
+            }
+            in_space = true;
+        } else {
+            output.push(c);
+            in_space = false;
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests;
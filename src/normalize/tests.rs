@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use crate::normalize::{Normalization, NormalizationForm, apply, normalize};
+    use std::borrow::Cow;
+
+    /// 依次为：NFKC、去掉零宽字符、去掉软连字符、合并空白
+    fn settings(
+        nfkc: bool,
+        zero_width: bool,
+        soft_hyphens: bool,
+        whitespace: bool,
+    ) -> Normalization {
+        Normalization {
+            form: if nfkc {
+                NormalizationForm::Nfkc
+            } else {
+                NormalizationForm::Nfc
+            },
+            strip_zero_width: zero_width,
+            strip_soft_hyphens: soft_hyphens,
+            collapse_whitespace: whitespace,
+        }
+    }
+
+    #[test]
+    fn test_normalization_table() {
+        // “e” 加组合重音符、全角字母、零宽空格、软连字符和连续空白
+        let input =
+            "Cafe\u{301} \u{FF21}\u{FF22}\u{200B}C  trans\u{00AD}lation\t\tok\n\nnext\u{FEFF}";
+        let cases = [
+            (
+                settings(false, false, false, false),
+                "Caf\u{E9} \u{FF21}\u{FF22}\u{200B}C  trans\u{00AD}lation\t\tok\n\nnext\u{FEFF}",
+            ),
+            (
+                settings(true, false, false, false),
+                "Caf\u{E9} AB\u{200B}C  trans\u{00AD}lation\t\tok\n\nnext\u{FEFF}",
+            ),
+            (
+                settings(false, true, false, false),
+                "Caf\u{E9} \u{FF21}\u{FF22}C  trans\u{00AD}lation\t\tok\n\nnext",
+            ),
+            (
+                settings(false, false, true, false),
+                "Caf\u{E9} \u{FF21}\u{FF22}\u{200B}C  translation\t\tok\n\nnext\u{FEFF}",
+            ),
+            (
+                settings(false, false, false, true),
+                "Caf\u{E9} \u{FF21}\u{FF22}\u{200B}C trans\u{00AD}lation ok\n\nnext\u{FEFF}",
+            ),
+            (
+                settings(true, true, false, false),
+                "Caf\u{E9} ABC  trans\u{00AD}lation\t\tok\n\nnext",
+            ),
+            (
+                settings(false, true, true, true),
+                "Caf\u{E9} \u{FF21}\u{FF22}C translation ok\n\nnext",
+            ),
+            (
+                settings(true, true, true, true),
+                "Caf\u{E9} ABC translation ok\n\nnext",
+            ),
+        ];
+        for (normalization, expected) in cases {
+            assert_eq!(
+                normalize(input, &normalization),
+                expected,
+                "{:?}",
+                normalization
+            );
+        }
+    }
+
+    #[test]
+    fn test_nfkc_whitespace_interaction() {
+        // NFKC 把不换行空格替换为普通空格，之后才合并空白
+        let input = "a\u{00A0} b";
+        assert_eq!(
+            normalize(input, &settings(false, false, false, true)),
+            "a b"
+        );
+        assert_eq!(
+            normalize(input, &settings(true, false, false, false)),
+            "a  b"
+        );
+        assert_eq!(normalize(input, &settings(true, false, false, true)), "a b");
+    }
+
+    #[test]
+    fn test_joiners_are_kept() {
+        // 零宽连接符组成的 emoji 序列和波斯语中的零宽不连接符保持不变
+        let input = "\u{1F469}\u{200D}\u{1F4BB} \u{0645}\u{06CC}\u{200C}\u{062E}\u{0648}\u{0627}\u{0647}\u{0645}";
+        assert_eq!(normalize(input, &settings(false, true, true, true)), input);
+    }
+
+    #[test]
+    fn test_unchanged_text_is_borrowed() {
+        let input = "already clean";
+        assert!(matches!(
+            normalize(input, &settings(true, true, true, true)),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(apply("Cafe\u{301}", None), Cow::Borrowed(_)));
+        assert!(matches!(
+            apply("Cafe\u{301}", Some(&Normalization::default())),
+            Cow::Owned(_)
+        ));
+    }
+}
@@ -0,0 +1,68 @@
+//! 运行时更换 OpenAI 翻译器的 API Key
+//!
+//! 配置 [`crate::OpenAIConfigBuilder::key_provider`] 后，翻译器在请求前按
+//! `key_refresh_interval` 调用 [`KeyProvider::fetch_keys`]，Key 有变化时整体替换 Key 集合，
+//! 为新的 Key 重新创建并发许可、RPM 限制和服务端额度的跟踪状态。已经开始的请求继续使用
+//! 替换前的 Key 直到完成。当前所有 Key 都被服务端以 401 拒绝时（例如 Key 已被轮换），
+//! 下一个请求不等间隔结束就重新获取，但两次获取之间至少间隔 [`MIN_KEY_REFRESH_INTERVAL`]。
+//!
+//! 获取失败或返回空列表时保留当前的 Key；还没有任何 Key 时请求返回获取的错误。
+//! 未配置时使用配置中固定的 `api_keys` 和 `key_weights`。
+
+use crate::{error::TranslationError, secret::SecretString};
+use std::fmt;
+use std::time::Duration;
+
+/// 默认的 Key 刷新间隔
+pub const DEFAULT_KEY_REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// 两次获取 Key 之间的最小间隔，包括所有 Key 都被拒绝时的立即刷新
+pub const MIN_KEY_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 一个 API Key 及其权重
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKeyConfig {
+    /// API Key
+    pub api_key: SecretString,
+    /// `WeightedRandom` 策略使用的权重，默认为 1；全部为 0 时均匀随机选择
+    pub weight: u32,
+}
+
+impl ApiKeyConfig {
+    pub fn new(api_key: impl Into<SecretString>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            weight: 1,
+        }
+    }
+
+    pub fn weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+impl From<&str> for ApiKeyConfig {
+    fn from(api_key: &str) -> Self {
+        Self::new(api_key)
+    }
+}
+
+impl From<String> for ApiKeyConfig {
+    fn from(api_key: String) -> Self {
+        Self::new(api_key)
+    }
+}
+
+impl From<SecretString> for ApiKeyConfig {
+    fn from(api_key: SecretString) -> Self {
+        Self::new(api_key)
+    }
+}
+
+/// 提供当前可用的 API Key，例如从密钥管理服务读取
+#[async_trait::async_trait]
+pub trait KeyProvider: fmt::Debug + Send + Sync {
+    /// 返回当前应使用的全部 Key，顺序决定 `RoundRobin` 的轮换顺序
+    async fn fetch_keys(&self) -> Result<Vec<ApiKeyConfig>, TranslationError>;
+}
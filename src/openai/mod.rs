@@ -1,15 +1,194 @@
 //! OpenAI 翻译器实现
 
-use crate::{error::TranslationError, options::TranslateOptions, translator::Translator};
+pub mod keys;
+pub mod prompt;
+
+use crate::{
+    batch,
+    cache::{self, CacheKeyInputs},
+    clock::{Clock, TokioClock},
+    concurrency::ConcurrencyLimiter,
+    detect::{self, DetectionCache, DetectionCacheStats},
+    error::{self, TranslationError},
+    extract, guard, http,
+    metrics::{MetricsRecorder, QueueMetrics, QueueStats},
+    normalize,
+    options::{Deadline, EffectiveOptions, PoolOptions, ProxyMode, TranslateOptions},
+    preflight,
+    preview::{PreparedRequest, RequestPreview},
+    protect,
+    ratelimit::RateLimiter,
+    retry,
+    secret::{self, SecretString},
+    trace,
+    translator::{
+        LengthUnit, Provenance, TranslationDetail, TranslationKind, Translator,
+        TranslatorCapabilities,
+    },
+};
+use futures::StreamExt;
 use futures::future::join_all;
+use keys::{ApiKeyConfig, KeyProvider, MIN_KEY_REFRESH_INTERVAL};
+use prompt::{DefaultPromptStrategy, Message, PromptStrategy};
+use rand::distr::Distribution;
+use rand::distr::weighted::WeightedIndex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, Instant};
-use tokio::sync::{Mutex, Semaphore};
-use tokio::time::sleep;
 use unic_langid::LanguageIdentifier;
 
+/// OpenAI 兼容服务的接口形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ApiShape {
+    /// `/chat/completions` 接口
+    #[default]
+    ChatCompletions,
+    /// `/responses` 接口，适用于只实现了该接口的网关
+    Responses,
+}
+
+impl ApiShape {
+    /// 接口相对于 base URL 的路径
+    const fn path(self) -> &'static str {
+        match self {
+            ApiShape::ChatCompletions => "/chat/completions",
+            ApiShape::Responses => "/responses",
+        }
+    }
+}
+
+/// 所有接口形式的路径，误写入 base URL 时去掉
+const ENDPOINT_PATHS: [&str; 2] = [ApiShape::ChatCompletions.path(), ApiShape::Responses.path()];
+
+/// 把 base URL 拆分为路径部分和查询部分（包括 `?` 或 `#`）
+fn split_query(base_url: &str) -> (&str, &str) {
+    base_url.split_at(base_url.find(['?', '#']).unwrap_or(base_url.len()))
+}
+
+/// 规范化 base URL：去掉末尾的斜杠和误加的接口路径，例如
+/// `https://api.openai.com/v1/chat/completions` 变为 `https://api.openai.com/v1`
+pub(crate) fn normalize_base_url(base_url: &str) -> String {
+    let (path, query) = split_query(base_url);
+    let mut path = path.trim_end_matches('/');
+    if let Some(stripped) = ENDPOINT_PATHS
+        .iter()
+        .find_map(|endpoint| path.strip_suffix(endpoint))
+    {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            base_url = %secret::redact_url(base_url),
+            "base_url includes an endpoint path, stripping it"
+        );
+        path = stripped.trim_end_matches('/');
+    }
+    format!("{}{}", path, query)
+}
+
+/// 检查 base URL：必须是 http 或 https，路径为空或包含 `v1` 这样的版本段，
+/// 且不能以接口路径结尾
+fn validate_base_url(base_url: &str) -> Result<(), TranslationError> {
+    http::validate_url("base_url", base_url)?;
+    let (path, _) = split_query(base_url);
+    let path = path.trim_end_matches('/');
+    let invalid = |reason: String| {
+        Err(TranslationError::ConfigurationError(format!(
+            "Invalid base_url '{}': {}",
+            secret::redact_url(base_url),
+            reason
+        )))
+    };
+    if let Some(endpoint) = ENDPOINT_PATHS
+        .iter()
+        .find(|endpoint| path.ends_with(*endpoint))
+    {
+        return invalid(format!(
+            "it should not include the {} endpoint, use the API root such as https://api.openai.com/v1",
+            endpoint
+        ));
+    }
+    let path = path.split_once("://").map_or(path, |(_, rest)| rest);
+    let segments: Vec<&str> = path.split('/').skip(1).filter(|s| !s.is_empty()).collect();
+    if !segments.is_empty() && !segments.iter().any(|segment| is_version_segment(segment)) {
+        return invalid(format!(
+            "path '/{}' has no API version segment such as /v1",
+            segments.join("/")
+        ));
+    }
+    Ok(())
+}
+
+/// `v1`、`v4`、`v1beta` 这样的版本段
+fn is_version_segment(segment: &str) -> bool {
+    let Some(rest) = segment.strip_prefix(['v', 'V']) else {
+        return false;
+    };
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    digits > 0 && rest[digits..].chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// 拼接 base URL 和接口路径，base URL 末尾的斜杠和查询参数都能正确处理
+fn endpoint_url(base_url: &str, endpoint: &str) -> String {
+    let (path, query) = split_query(base_url);
+    format!("{}{}{}", path.trim_end_matches('/'), endpoint, query)
+}
+
+/// 配置了多个 API Key 时，选择每次请求所用 Key 的策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyStrategy {
+    /// 依次轮流使用
+    #[default]
+    RoundRobin,
+    /// 均匀随机选择
+    Random,
+    /// 选择空闲并发许可最多的 Key，并列时随机选择
+    LeastLoaded,
+    /// 按 `key_weights` 加权随机选择
+    WeightedRandom,
+}
+
+/// 输入文本加上系统提示词超出 token 预算时的处理方式
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// 返回 `TextTooLong` 错误
+    #[default]
+    Reject,
+    /// 拆分为多个片段分别翻译，与 `auto_split` 选项相同
+    Split,
+    /// 只翻译预算内的开头部分，并在译文末尾追加指定标记
+    TruncateWithMarker(String),
+}
+
+/// 常见模型的上下文窗口大小（token），按前缀匹配，更具体的前缀在前
+const MODEL_CONTEXT_WINDOWS: &[(&str, u32)] = &[
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4-32k", 32_768),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo-instruct", 4_096),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1-mini", 128_000),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4-mini", 200_000),
+];
+
+/// 未知模型使用的保守上下文窗口大小
+const DEFAULT_CONTEXT_WINDOW: u32 = 4_096;
+
+/// 查询模型的上下文窗口大小，未知模型返回保守的默认值
+pub(crate) fn context_window_for_model(model: &str) -> u32 {
+    MODEL_CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map_or(DEFAULT_CONTEXT_WINDOW, |&(_, window)| window)
+}
+
 /// OpenAI翻译器配置
 #[derive(Debug, Clone)]
 pub struct OpenAIConfig {
@@ -18,13 +197,299 @@ pub struct OpenAIConfig {
     /// 使用的模型名称
     pub model: String,
     /// API Key 列表，支持多个 Key
-    pub api_keys: Vec<String>,
+    pub api_keys: Vec<SecretString>,
     /// 每分钟请求数限制，设为0表示不限制
     pub rpm_limit: u32,
+    /// RPM 限制允许的突发请求数，None 表示等于 `rpm_limit`，即一次可以用完整分钟的配额
+    ///
+    /// 小于 `rpm_limit` 时请求按 `60 秒 / rpm_limit` 的间隔均匀发送，空闲后最多连续发送
+    /// `burst` 个；为 1 时完全均匀。任意 60 秒内的请求数仍不超过 `rpm_limit`
+    pub burst: Option<u32>,
+    /// 同一个 Key 相邻两次请求之间的最小间隔，None 表示不限制
+    pub min_request_interval: Option<Duration>,
     /// 并发请求数限制
     pub concurrent_limit: usize,
+    /// 允许同时等待并发许可的最大请求数，超出时返回 `Overloaded` 错误，None 表示不限制
+    pub max_queue_depth: Option<usize>,
+    /// 请求体（序列化后的 JSON，含提示词）的最大字节数，None 表示不限制，
+    /// 超出时不发送请求并返回 `TextTooLong`
+    pub max_request_bytes: Option<usize>,
+    /// 是否在收到 429/503 响应时自动降低并发数，持续成功后逐步恢复到 `concurrent_limit`
+    pub adaptive_concurrency: bool,
+    /// 是否优先按响应头报告的剩余请求数决定是否等待，而不是本地的 RPM 时间窗口
+    ///
+    /// 没有收到过响应头或已过恢复时间时仍使用本地限速，最小请求间隔始终生效
+    pub honor_server_rate_limits: bool,
     /// 自定义系统提示词，如果为None则使用默认提示词
+    ///
+    /// 提示词中的 `{source_lang}` 和 `{target_lang}` 会被替换为实际的语言，
+    /// 未指定源语言时 `{source_lang}` 替换为 `auto`；`{target_lang_name}` 替换为
+    /// 目标语言的英文名称（见 `locale_names`），没有名称时同 `{target_lang}`
     pub system_prompt: Option<String>,
+    /// 按语言对指定的系统提示词，键为（源语言或 `"auto"`，目标语言）的主语言子标签，
+    /// 优先于 `system_prompt`
+    pub system_prompts: HashMap<(String, String), String>,
+    /// 带地区或书写系统子标签的目标语言在提示词中使用的英文名称（例如 `pt-BR` 为
+    /// `"Brazilian Portuguese"`），键不区分大小写，优先于内置的名称表
+    pub locale_names: HashMap<String, String>,
+    /// 单条输入允许的最大（估算）token 数，None 表示只受模型上下文窗口的限制
+    pub max_input_tokens: Option<usize>,
+    /// token 数估算函数，默认按每 4 个字符一个 token 估算
+    pub token_estimator: fn(&str) -> usize,
+    /// 模型的上下文窗口大小（token），None 表示按模型名称查表
+    pub context_window: Option<u32>,
+    /// 输入文本加上系统提示词超出上下文窗口或 `max_input_tokens` 时的处理方式
+    pub overflow_policy: OverflowPolicy,
+    /// 未指定源语言时，是否要求模型在译文前输出检测到的源语言
+    pub detect_source_language: bool,
+    /// 未指定源语言时，是否先在本地检测源语言并写入提示词
+    pub detect_before_translate: bool,
+    /// 幂等键请求头名称，例如 `"Idempotency-Key"`，None 表示不发送
+    ///
+    /// 每个待翻译文本生成一个 UUID，同一文本的所有重试使用同一个键
+    pub idempotency_header: Option<String>,
+    /// 是否以流式响应（SSE）接收译文
+    pub stream: bool,
+    /// 使用的接口形式
+    pub api_shape: ApiShape,
+    /// 选择 API Key 的策略
+    pub key_strategy: KeyStrategy,
+    /// 每个 API Key 的权重，与 `api_keys` 一一对应，仅 `WeightedRandom` 策略使用；
+    /// 为空时所有 Key 的权重相同
+    pub key_weights: Vec<u32>,
+    /// 运行时提供 API Key 的来源，设置后 `api_keys` 和 `key_weights` 不再使用，见 [`keys`]
+    pub key_provider: Option<Arc<dyn KeyProvider>>,
+    /// 向 `key_provider` 重新获取 Key 的间隔
+    pub key_refresh_interval: Duration,
+    /// HTTP 连接池设置
+    pub pool: PoolOptions,
+    /// HTTP 代理设置
+    pub proxy_mode: ProxyMode,
+    /// 主机名解析失败后直接失败的时长，期间发往该主机的请求不再发出，返回不可重试的
+    /// `NetworkError`；默认 30 秒，`Duration::ZERO` 表示关闭
+    pub dns_failure_ttl: Duration,
+    /// 语言检测缓存的最大条目数，0 表示不缓存
+    pub detection_cache_size: usize,
+    /// 默认的翻译选项，调用时未设置的选项使用这里的值
+    pub default_options: TranslateOptions,
+    /// RPM 限制和重试退避使用的时钟
+    pub clock: Arc<dyn Clock>,
+    /// 接收并发许可排队指标的记录器，None 表示只在 `queue_stats()` 中统计
+    pub metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
+    /// 构造消息和提取译文的提示词策略，None 表示按 `system_prompt`、`system_prompts`
+    /// 和 `locale_names` 使用 [`DefaultPromptStrategy`]
+    pub prompt_strategy: Option<Arc<dyn PromptStrategy>>,
+}
+
+impl OpenAIConfig {
+    /// 检查配置是否可以用于创建翻译器
+    pub fn validate(&self) -> Result<(), TranslationError> {
+        if self.api_keys.is_empty() && self.key_provider.is_none() {
+            return Err(TranslationError::ConfigurationError(
+                "No API keys configured".to_string(),
+            ));
+        }
+        if self.key_provider.is_some() && self.key_refresh_interval < MIN_KEY_REFRESH_INTERVAL {
+            return Err(TranslationError::ConfigurationError(format!(
+                "key_refresh_interval must be at least {}s",
+                MIN_KEY_REFRESH_INTERVAL.as_secs()
+            )));
+        }
+        if self.concurrent_limit == 0 {
+            return Err(TranslationError::ConfigurationError(
+                "concurrent_limit must be greater than 0".to_string(),
+            ));
+        }
+        if self.burst == Some(0) {
+            return Err(TranslationError::ConfigurationError(
+                "burst must be greater than 0".to_string(),
+            ));
+        }
+        if !self.key_weights.is_empty() {
+            if self.key_weights.len() != self.api_keys.len() {
+                return Err(TranslationError::ConfigurationError(format!(
+                    "key_weights has {} weights for {} API keys",
+                    self.key_weights.len(),
+                    self.api_keys.len()
+                )));
+            }
+            if self.key_weights.iter().all(|&weight| weight == 0) {
+                return Err(TranslationError::ConfigurationError(
+                    "key_weights must contain at least one positive weight".to_string(),
+                ));
+            }
+        }
+        http::validate_pool_options(&self.pool)?;
+        http::validate_proxy_mode(&self.proxy_mode)?;
+        validate_base_url(&self.base_url)
+    }
+}
+
+/// 时钟按实例比较，估算函数按地址比较
+impl PartialEq for OpenAIConfig {
+    fn eq(&self, other: &Self) -> bool {
+        // 解构全部字段，新增字段时编译器会提示更新这里
+        let OpenAIConfig {
+            base_url,
+            model,
+            api_keys,
+            rpm_limit,
+            burst,
+            min_request_interval,
+            concurrent_limit,
+            max_queue_depth,
+            max_request_bytes,
+            adaptive_concurrency,
+            honor_server_rate_limits,
+            system_prompt,
+            system_prompts,
+            locale_names,
+            max_input_tokens,
+            token_estimator,
+            context_window,
+            overflow_policy,
+            detect_source_language,
+            detect_before_translate,
+            idempotency_header,
+            stream,
+            api_shape,
+            key_strategy,
+            key_weights,
+            key_provider,
+            key_refresh_interval,
+            pool,
+            proxy_mode,
+            dns_failure_ttl,
+            detection_cache_size,
+            default_options,
+            clock,
+            metrics_recorder,
+            prompt_strategy,
+        } = self;
+        *base_url == other.base_url
+            && *model == other.model
+            && *api_keys == other.api_keys
+            && *rpm_limit == other.rpm_limit
+            && *burst == other.burst
+            && *min_request_interval == other.min_request_interval
+            && *concurrent_limit == other.concurrent_limit
+            && *max_queue_depth == other.max_queue_depth
+            && *max_request_bytes == other.max_request_bytes
+            && *adaptive_concurrency == other.adaptive_concurrency
+            && *honor_server_rate_limits == other.honor_server_rate_limits
+            && *system_prompt == other.system_prompt
+            && *system_prompts == other.system_prompts
+            && *locale_names == other.locale_names
+            && *max_input_tokens == other.max_input_tokens
+            && std::ptr::fn_addr_eq(*token_estimator, other.token_estimator)
+            && *context_window == other.context_window
+            && *overflow_policy == other.overflow_policy
+            && *detect_source_language == other.detect_source_language
+            && *detect_before_translate == other.detect_before_translate
+            && *idempotency_header == other.idempotency_header
+            && *stream == other.stream
+            && *api_shape == other.api_shape
+            && *key_strategy == other.key_strategy
+            && *key_weights == other.key_weights
+            && match (key_provider, &other.key_provider) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+            && *key_refresh_interval == other.key_refresh_interval
+            && *pool == other.pool
+            && *proxy_mode == other.proxy_mode
+            && *dns_failure_ttl == other.dns_failure_ttl
+            && *detection_cache_size == other.detection_cache_size
+            && *default_options == other.default_options
+            && Arc::ptr_eq(clock, &other.clock)
+            && match (metrics_recorder, &other.metrics_recorder) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+            && match (prompt_strategy, &other.prompt_strategy) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    }
+}
+
+/// 要求模型输出检测到的源语言的附加提示词
+const DETECT_LANGUAGE_INSTRUCTION: &str = "\n\nBefore the translation, output the detected source language of the user's text as a BCP 47 tag on its own first line, in the form [lang: <tag>]. Then output the translated text on the following lines.";
+
+/// 要求模型按 JSON 数组逐条翻译的附加提示词
+const JSON_ARRAY_INSTRUCTION: &str = "\n\nThe user message is a JSON array of strings. Translate every string and reply with only a JSON array of the translations, in the same order and with the same number of elements. Do not add any explanation or code fences.";
+
+/// 本地检测结果的置信度低于该值时，提示词中的源语言仍使用 "auto"
+const DETECT_BEFORE_TRANSLATE_THRESHOLD: f64 = 0.5;
+
+/// 取语言标签的主语言子标签（小写），例如 `"zh-Hans"` 取 `"zh"`
+fn primary_subtag(lang: &str) -> String {
+    lang.split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// 带地区或书写系统子标签的常见语言变体在提示词中使用的英文名称
+///
+/// 模型常常忽略原始的语言标签（例如把 `pt-BR` 译成欧洲葡萄牙语），写明名称更可靠
+const LOCALE_NAMES: &[(&str, &str)] = &[
+    ("zh-Hans", "Simplified Chinese"),
+    ("zh-Hant", "Traditional Chinese"),
+    ("zh-CN", "Simplified Chinese (Mainland China)"),
+    ("zh-SG", "Simplified Chinese (Singapore)"),
+    ("zh-TW", "Traditional Chinese (Taiwan)"),
+    ("zh-HK", "Traditional Chinese (Hong Kong)"),
+    ("zh-MO", "Traditional Chinese (Macau)"),
+    ("zh-Hans-CN", "Simplified Chinese (Mainland China)"),
+    ("zh-Hans-SG", "Simplified Chinese (Singapore)"),
+    ("zh-Hant-TW", "Traditional Chinese (Taiwan)"),
+    ("zh-Hant-HK", "Traditional Chinese (Hong Kong)"),
+    ("zh-Hant-MO", "Traditional Chinese (Macau)"),
+    ("pt-BR", "Brazilian Portuguese"),
+    ("pt-PT", "European Portuguese"),
+    ("en-US", "American English"),
+    ("en-GB", "British English"),
+    ("en-AU", "Australian English"),
+    ("en-CA", "Canadian English"),
+    ("en-IN", "Indian English"),
+    ("en-NZ", "New Zealand English"),
+    ("en-IE", "Irish English"),
+    ("es-ES", "European Spanish (Spain)"),
+    ("es-MX", "Mexican Spanish"),
+    ("es-419", "Latin American Spanish"),
+    ("es-AR", "Argentine Spanish"),
+    ("es-CO", "Colombian Spanish"),
+    ("es-US", "US Spanish"),
+    ("fr-FR", "French (France)"),
+    ("fr-CA", "Canadian French"),
+    ("fr-BE", "Belgian French"),
+    ("fr-CH", "Swiss French"),
+    ("de-DE", "German (Germany)"),
+    ("de-AT", "Austrian German"),
+    ("de-CH", "Swiss Standard German"),
+    ("nl-NL", "Dutch (Netherlands)"),
+    ("nl-BE", "Flemish (Belgian Dutch)"),
+    ("ar-EG", "Egyptian Arabic"),
+    ("ar-SA", "Arabic (Saudi Arabia)"),
+    ("sr-Latn", "Serbian (Latin script)"),
+    ("sr-Cyrl", "Serbian (Cyrillic script)"),
+    ("uz-Latn", "Uzbek (Latin script)"),
+    ("uz-Cyrl", "Uzbek (Cyrillic script)"),
+    ("az-Latn", "Azerbaijani (Latin script)"),
+    ("mn-Cyrl", "Mongolian (Cyrillic script)"),
+    ("pa-Guru", "Punjabi (Gurmukhi script)"),
+    ("pa-Arab", "Punjabi (Shahmukhi script)"),
+];
+
+/// 统一语言标签的分隔符和大小写，用于查找名称
+fn normalize_tag(lang: &str) -> String {
+    lang.replace('_', "-").to_ascii_lowercase()
+}
+
+/// 默认的 token 估算：每 4 个字符约为一个 token
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
 }
 
 impl Default for OpenAIConfig {
@@ -34,8 +499,37 @@ impl Default for OpenAIConfig {
             model: "gpt-3.5-turbo".to_string(),
             api_keys: vec![],
             rpm_limit: 60,
+            burst: None,
+            min_request_interval: None,
             concurrent_limit: 10,
+            max_queue_depth: None,
+            max_request_bytes: None,
+            adaptive_concurrency: false,
+            honor_server_rate_limits: false,
             system_prompt: None,
+            system_prompts: HashMap::new(),
+            locale_names: HashMap::new(),
+            max_input_tokens: None,
+            token_estimator: estimate_tokens,
+            context_window: None,
+            overflow_policy: OverflowPolicy::default(),
+            detect_source_language: false,
+            detect_before_translate: false,
+            idempotency_header: None,
+            stream: false,
+            api_shape: ApiShape::default(),
+            key_strategy: KeyStrategy::default(),
+            key_weights: Vec::new(),
+            key_provider: None,
+            key_refresh_interval: keys::DEFAULT_KEY_REFRESH_INTERVAL,
+            pool: PoolOptions::default(),
+            proxy_mode: ProxyMode::default(),
+            dns_failure_ttl: http::DEFAULT_DNS_FAILURE_TTL,
+            detection_cache_size: detect::DEFAULT_DETECTION_CACHE_SIZE,
+            default_options: TranslateOptions::default(),
+            clock: Arc::new(TokioClock),
+            metrics_recorder: None,
+            prompt_strategy: None,
         }
     }
 }
@@ -50,10 +544,39 @@ impl OpenAIConfig {
 pub struct OpenAIConfigBuilder {
     base_url: Option<String>,
     model: Option<String>,
-    api_keys: Option<Vec<String>>,
+    api_keys: Option<Vec<SecretString>>,
     rpm_limit: Option<u32>,
+    burst: Option<u32>,
+    min_request_interval: Option<Duration>,
     concurrent_limit: Option<usize>,
+    max_queue_depth: Option<usize>,
+    max_request_bytes: Option<usize>,
+    adaptive_concurrency: bool,
+    honor_server_rate_limits: bool,
     system_prompt: Option<String>,
+    system_prompts: HashMap<(String, String), String>,
+    locale_names: HashMap<String, String>,
+    max_input_tokens: Option<Option<usize>>,
+    token_estimator: Option<fn(&str) -> usize>,
+    context_window: Option<u32>,
+    overflow_policy: Option<OverflowPolicy>,
+    detect_source_language: Option<bool>,
+    detect_before_translate: Option<bool>,
+    idempotency_header: Option<String>,
+    stream: Option<bool>,
+    api_shape: Option<ApiShape>,
+    key_strategy: Option<KeyStrategy>,
+    key_weights: Vec<u32>,
+    key_provider: Option<Arc<dyn KeyProvider>>,
+    key_refresh_interval: Option<Duration>,
+    pool: PoolOptions,
+    proxy_mode: ProxyMode,
+    dns_failure_ttl: Option<Duration>,
+    detection_cache_size: Option<usize>,
+    default_options: TranslateOptions,
+    clock: Option<Arc<dyn Clock>>,
+    metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
+    prompt_strategy: Option<Arc<dyn PromptStrategy>>,
 }
 
 impl OpenAIConfigBuilder {
@@ -67,7 +590,7 @@ impl OpenAIConfigBuilder {
         self
     }
 
-    pub fn api_keys(mut self, api_keys: Vec<impl Into<String>>) -> Self {
+    pub fn api_keys(mut self, api_keys: Vec<impl Into<SecretString>>) -> Self {
         self.api_keys = Some(api_keys.into_iter().map(|s| s.into()).collect());
         self
     }
@@ -77,26 +600,259 @@ impl OpenAIConfigBuilder {
         self
     }
 
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = Some(burst);
+        self
+    }
+
+    pub fn min_request_interval(mut self, min_request_interval: Duration) -> Self {
+        self.min_request_interval = Some(min_request_interval);
+        self
+    }
+
     pub fn concurrent_limit(mut self, concurrent_limit: usize) -> Self {
         self.concurrent_limit = Some(concurrent_limit);
         self
     }
 
+    pub fn max_queue_depth(mut self, max_queue_depth: usize) -> Self {
+        self.max_queue_depth = Some(max_queue_depth);
+        self
+    }
+
+    pub fn max_request_bytes(mut self, max_request_bytes: usize) -> Self {
+        self.max_request_bytes = Some(max_request_bytes);
+        self
+    }
+
+    pub fn adaptive_concurrency(mut self, adaptive_concurrency: bool) -> Self {
+        self.adaptive_concurrency = adaptive_concurrency;
+        self
+    }
+
+    pub fn honor_server_rate_limits(mut self, honor_server_rate_limits: bool) -> Self {
+        self.honor_server_rate_limits = honor_server_rate_limits;
+        self
+    }
+
     pub fn system_prompt(mut self, system_prompt: impl Into<String>) -> Self {
         self.system_prompt = Some(system_prompt.into());
         self
     }
 
+    /// 为指定语言对设置系统提示词，`source_lang` 为 `"auto"` 时匹配未指定源语言的请求
+    /// 以及没有更精确匹配的语言对
+    pub fn system_prompt_for(
+        mut self,
+        source_lang: impl Into<String>,
+        target_lang: impl Into<String>,
+        system_prompt: impl Into<String>,
+    ) -> Self {
+        let key = (
+            primary_subtag(&source_lang.into()),
+            primary_subtag(&target_lang.into()),
+        );
+        self.system_prompts.insert(key, system_prompt.into());
+        self
+    }
+
+    /// 设置目标语言在提示词中使用的英文名称，覆盖内置的名称表
+    pub fn locale_name(mut self, lang: impl Into<String>, name: impl Into<String>) -> Self {
+        self.locale_names.insert(lang.into(), name.into());
+        self
+    }
+
+    pub fn max_input_tokens(mut self, max_input_tokens: Option<usize>) -> Self {
+        self.max_input_tokens = Some(max_input_tokens);
+        self
+    }
+
+    pub fn token_estimator(mut self, token_estimator: fn(&str) -> usize) -> Self {
+        self.token_estimator = Some(token_estimator);
+        self
+    }
+
+    pub fn context_window(mut self, context_window: u32) -> Self {
+        self.context_window = Some(context_window);
+        self
+    }
+
+    pub fn overflow_policy(mut self, overflow_policy: OverflowPolicy) -> Self {
+        self.overflow_policy = Some(overflow_policy);
+        self
+    }
+
+    pub fn detect_source_language(mut self, detect_source_language: bool) -> Self {
+        self.detect_source_language = Some(detect_source_language);
+        self
+    }
+
+    pub fn detect_before_translate(mut self, detect_before_translate: bool) -> Self {
+        self.detect_before_translate = Some(detect_before_translate);
+        self
+    }
+
+    pub fn idempotency_header(mut self, idempotency_header: impl Into<String>) -> Self {
+        self.idempotency_header = Some(idempotency_header.into());
+        self
+    }
+
+    pub fn stream(mut self, stream: bool) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    pub fn api_shape(mut self, api_shape: ApiShape) -> Self {
+        self.api_shape = Some(api_shape);
+        self
+    }
+
+    pub fn key_strategy(mut self, key_strategy: KeyStrategy) -> Self {
+        self.key_strategy = Some(key_strategy);
+        self
+    }
+
+    pub fn key_weights(mut self, key_weights: Vec<u32>) -> Self {
+        self.key_weights = key_weights;
+        self
+    }
+
+    pub fn key_provider(mut self, key_provider: Arc<dyn KeyProvider>) -> Self {
+        self.key_provider = Some(key_provider);
+        self
+    }
+
+    pub fn key_refresh_interval(mut self, key_refresh_interval: Duration) -> Self {
+        self.key_refresh_interval = Some(key_refresh_interval);
+        self
+    }
+
+    pub fn pool_max_idle_per_host(mut self, pool_max_idle_per_host: usize) -> Self {
+        self.pool.max_idle_per_host = pool_max_idle_per_host;
+        self
+    }
+
+    pub fn pool_idle_timeout(mut self, pool_idle_timeout: Option<Duration>) -> Self {
+        self.pool.idle_timeout = pool_idle_timeout;
+        self
+    }
+
+    pub fn tcp_keepalive(mut self, tcp_keepalive: Option<Duration>) -> Self {
+        self.pool.tcp_keepalive = tcp_keepalive;
+        self
+    }
+
+    pub fn http2_prior_knowledge(mut self, http2_prior_knowledge: bool) -> Self {
+        self.pool.http2_prior_knowledge = http2_prior_knowledge;
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.pool.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    pub fn prefer_http2(mut self, prefer_http2: bool) -> Self {
+        self.pool.prefer_http2 = prefer_http2;
+        self
+    }
+
+    pub fn http2_keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.pool.http2_keep_alive_interval = Some(interval);
+        self
+    }
+
+    pub fn http2_keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.pool.http2_keep_alive_timeout = Some(timeout);
+        self
+    }
+
+    pub fn http2_keep_alive_while_idle(mut self, while_idle: bool) -> Self {
+        self.pool.http2_keep_alive_while_idle = while_idle;
+        self
+    }
+
+    pub fn proxy_mode(mut self, proxy_mode: ProxyMode) -> Self {
+        self.proxy_mode = proxy_mode;
+        self
+    }
+
+    pub fn dns_failure_ttl(mut self, dns_failure_ttl: Duration) -> Self {
+        self.dns_failure_ttl = Some(dns_failure_ttl);
+        self
+    }
+
+    pub fn detection_cache_size(mut self, detection_cache_size: usize) -> Self {
+        self.detection_cache_size = Some(detection_cache_size);
+        self
+    }
+
+    pub fn default_options(mut self, default_options: TranslateOptions) -> Self {
+        self.default_options = default_options;
+        self
+    }
+
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn metrics_recorder(mut self, metrics_recorder: Arc<dyn MetricsRecorder>) -> Self {
+        self.metrics_recorder = Some(metrics_recorder);
+        self
+    }
+
+    pub fn prompt_strategy(mut self, prompt_strategy: Arc<dyn PromptStrategy>) -> Self {
+        self.prompt_strategy = Some(prompt_strategy);
+        self
+    }
+
     pub fn build(self) -> OpenAIConfig {
         OpenAIConfig {
-            base_url: self
-                .base_url
-                .unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
+            base_url: self.base_url.map_or_else(
+                || "https://api.openai.com/v1".to_string(),
+                |base_url| normalize_base_url(&base_url),
+            ),
             model: self.model.unwrap_or_else(|| "gpt-3.5-turbo".to_string()),
-            api_keys: self.api_keys.unwrap_or_else(Vec::new),
+            api_keys: self.api_keys.unwrap_or_default(),
             rpm_limit: self.rpm_limit.unwrap_or(60),
+            burst: self.burst,
+            min_request_interval: self.min_request_interval,
             concurrent_limit: self.concurrent_limit.unwrap_or(10),
+            max_queue_depth: self.max_queue_depth,
+            max_request_bytes: self.max_request_bytes,
+            adaptive_concurrency: self.adaptive_concurrency,
+            honor_server_rate_limits: self.honor_server_rate_limits,
             system_prompt: self.system_prompt,
+            system_prompts: self.system_prompts,
+            locale_names: self.locale_names,
+            max_input_tokens: self.max_input_tokens.unwrap_or_default(),
+            token_estimator: self.token_estimator.unwrap_or(estimate_tokens),
+            context_window: self.context_window,
+            overflow_policy: self.overflow_policy.unwrap_or_default(),
+            detect_source_language: self.detect_source_language.unwrap_or(false),
+            detect_before_translate: self.detect_before_translate.unwrap_or(false),
+            idempotency_header: self.idempotency_header,
+            stream: self.stream.unwrap_or(false),
+            api_shape: self.api_shape.unwrap_or_default(),
+            key_strategy: self.key_strategy.unwrap_or_default(),
+            key_weights: self.key_weights,
+            key_provider: self.key_provider,
+            key_refresh_interval: self
+                .key_refresh_interval
+                .unwrap_or(keys::DEFAULT_KEY_REFRESH_INTERVAL),
+            pool: self.pool,
+            proxy_mode: self.proxy_mode,
+            dns_failure_ttl: self
+                .dns_failure_ttl
+                .unwrap_or(http::DEFAULT_DNS_FAILURE_TTL),
+            detection_cache_size: self
+                .detection_cache_size
+                .unwrap_or(detect::DEFAULT_DETECTION_CACHE_SIZE),
+            default_options: self.default_options,
+            clock: self.clock.unwrap_or_else(|| Arc::new(TokioClock)),
+            metrics_recorder: self.metrics_recorder,
+            prompt_strategy: self.prompt_strategy,
         }
     }
 }
@@ -104,235 +860,1787 @@ impl OpenAIConfigBuilder {
 /// 用于跟踪每个API Key的使用情况
 #[derive(Debug)]
 struct KeyTracker {
-    /// 控制并发数的信号量
-    semaphore: Arc<Semaphore>,
-    /// 跟踪最近的请求时间，用于RPM限制（仅在需要时使用）
-    request_times: Option<Arc<Mutex<Vec<Instant>>>>,
+    /// API Key
+    api_key: SecretString,
+    /// `WeightedRandom` 策略使用的权重
+    weight: u32,
+    /// 是否收到过 401 响应，当前所有 Key 都被拒绝时立即向 `key_provider` 重新获取
+    rejected: AtomicBool,
+    /// 该 Key 的并发许可
+    concurrency: ConcurrencyLimiter,
+    /// 该 Key 的 RPM 限制和最小请求间隔
+    rate_limiter: RateLimiter,
+    /// 最近一次响应头中报告的剩余额度
+    server_limits: Mutex<RateLimitState>,
 }
 
-#[derive(Serialize, Deserialize)]
-struct Message {
-    role: String,
-    content: String,
+impl KeyTracker {
+    fn new(key: ApiKeyConfig, config: &OpenAIConfig, queue_metrics: &Arc<QueueMetrics>) -> Self {
+        Self {
+            api_key: key.api_key,
+            weight: key.weight,
+            rejected: AtomicBool::new(false),
+            concurrency: ConcurrencyLimiter::new(config.concurrent_limit, config.max_queue_depth)
+                .adaptive(config.adaptive_concurrency)
+                .metrics(queue_metrics.clone()),
+            rate_limiter: RateLimiter::new(
+                config.rpm_limit,
+                config.burst,
+                config.min_request_interval,
+            ),
+            server_limits: Mutex::new(RateLimitState::default()),
+        }
+    }
 }
 
-#[derive(Serialize)]
-struct Request {
-    model: String,
-    messages: Vec<Message>,
-    temperature: f32,
+/// 服务端在 `x-ratelimit-*` 响应头中报告的某个 Key 的剩余额度
+///
+/// 每次收到响应后更新，缺失或无法解析的响应头保留之前的值，从未收到时各字段为 None
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitState {
+    /// 当前周期内剩余的请求数
+    pub remaining_requests: Option<u64>,
+    /// 当前周期内剩余的 token 数
+    pub remaining_tokens: Option<u64>,
+    /// 请求数额度恢复的时间
+    pub reset_requests_at: Option<Instant>,
+    /// token 额度恢复的时间
+    pub reset_tokens_at: Option<Instant>,
 }
 
-#[derive(Deserialize)]
-struct Choice {
-    message: Message,
+impl RateLimitState {
+    /// 按响应头更新，`now` 为收到响应的时间
+    fn update(&mut self, headers: &reqwest::header::HeaderMap, now: Instant) {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        let count = |name: &str| header(name).and_then(|value| value.trim().parse::<u64>().ok());
+        let reset = |name: &str| header(name).and_then(parse_reset).map(|wait| now + wait);
+        if let Some(remaining) = count("x-ratelimit-remaining-requests") {
+            self.remaining_requests = Some(remaining);
+        }
+        if let Some(remaining) = count("x-ratelimit-remaining-tokens") {
+            self.remaining_tokens = Some(remaining);
+        }
+        if let Some(at) = reset("x-ratelimit-reset-requests") {
+            self.reset_requests_at = Some(at);
+        }
+        if let Some(at) = reset("x-ratelimit-reset-tokens") {
+            self.reset_tokens_at = Some(at);
+        }
+    }
+
+    /// 按服务端报告的额度为下一个请求预留一次请求数
+    ///
+    /// 返回 None 表示没有可用的额度信息（从未收到或已过恢复时间），应使用本地限速；
+    /// 返回零表示额度充足，否则为需要等待到额度恢复的时长
+    fn reserve(&mut self, now: Instant) -> Option<Duration> {
+        let fresh = |at: Option<Instant>| at.filter(|&at| at > now);
+        let remaining = self.remaining_requests?;
+        let reset_at = fresh(self.reset_requests_at)?;
+        let mut wait = Duration::ZERO;
+        if remaining == 0 {
+            wait = reset_at - now;
+        }
+        if let (Some(0), Some(tokens_at)) = (self.remaining_tokens, fresh(self.reset_tokens_at)) {
+            wait = wait.max(tokens_at - now);
+        }
+        if wait.is_zero() {
+            self.remaining_requests = Some(remaining - 1);
+        }
+        Some(wait)
+    }
 }
 
-#[derive(Deserialize)]
-struct Response {
-    choices: Vec<Choice>,
+/// 解析恢复时间响应头，支持 `6m0s`、`1.5s`、`20ms` 形式的时长和纯秒数
+fn parse_reset(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(seconds).ok();
+    }
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .filter(|&split| split > 0)?;
+        let number: f64 = rest[..split].parse().ok()?;
+        rest = &rest[split..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 1e-3,
+            "us" | "µs" => 1e-6,
+            "ns" => 1e-9,
+            _ => return None,
+        };
+        total += number * scale;
+        rest = &rest[unit_len..];
+    }
+    Duration::try_from_secs_f64(total).ok()
 }
 
-/// OpenAI翻译器实现
-pub struct OpenAITranslator {
-    client: Client,
-    config: OpenAIConfig,
-    /// 每个API Key对应的跟踪器
-    key_trackers: Arc<Vec<KeyTracker>>,
-    /// 用于轮询选择API Key的索引
-    current_key_index: Arc<Mutex<usize>>,
+/// 从多个 Key 中选择本次请求所用的一个，`keys` 总是非空
+trait KeySelector: Send + Sync {
+    fn select(&self, keys: &[KeyTracker]) -> usize;
 }
 
-impl OpenAITranslator {
-    /// 创建新的OpenAI翻译器实例
-    pub fn new(config: OpenAIConfig) -> Self {
-        let mut key_trackers = Vec::new();
-        for _ in &config.api_keys {
-            let request_times = if config.rpm_limit > 0 {
-                Some(Arc::new(Mutex::new(Vec::new())))
-            } else {
-                None
-            };
-            key_trackers.push(KeyTracker {
-                semaphore: Arc::new(Semaphore::new(config.concurrent_limit)),
-                request_times,
-            });
-        }
-        Self {
-            client: Client::new(),
-            config,
-            key_trackers: Arc::new(key_trackers),
-            current_key_index: Arc::new(Mutex::new(0)),
-        }
+/// 依次轮流使用，计数器溢出时回绕到 0
+#[derive(Default)]
+struct RoundRobinSelector {
+    next: AtomicUsize,
+}
+
+impl KeySelector for RoundRobinSelector {
+    fn select(&self, keys: &[KeyTracker]) -> usize {
+        self.next.fetch_add(1, Ordering::Relaxed) % keys.len()
     }
+}
 
-    /// 轮询选择下一个可用的API Key索引
-    async fn get_next_key_index(&self) -> usize {
-        let mut index = self.current_key_index.lock().await;
-        let current = *index;
-        *index = (*index + 1) % self.config.api_keys.len();
-        current
+/// 均匀随机选择
+struct RandomSelector {
+    rng: Mutex<StdRng>,
+}
+
+impl KeySelector for RandomSelector {
+    fn select(&self, keys: &[KeyTracker]) -> usize {
+        self.rng.lock().unwrap().random_range(0..keys.len())
     }
+}
 
-    /// 获取系统提示词
-    fn get_system_prompt(&self, target_lang: &str, source_lang: Option<&str>) -> String {
-        if let Some(prompt) = &self.config.system_prompt {
-            return prompt.clone();
-        }
-        let source_lang_str = source_lang.unwrap_or("auto");
-        format!(
-            "You are a raw translation engine. You are not an AI assistant. Your only function is to translate the user's text. Translate from {} to {}. Do not, under any circumstances, write anything other than the translated text. Do not apologize. Do not explain. Do not add any extra text. If you cannot translate the text, repeat the original text.\n\nExamples:\n\nUser: Hello\nAssistant: 你好\n\nUser: World\nAssistant: 世界\n\nUser: xyzabc\nAssistant: xyzabc",
-            source_lang_str, target_lang
-        )
+/// 选择空闲并发许可最多的 Key，并列时随机选择
+struct LeastLoadedSelector {
+    rng: Mutex<StdRng>,
+}
+
+impl KeySelector for LeastLoadedSelector {
+    fn select(&self, keys: &[KeyTracker]) -> usize {
+        let available: Vec<usize> = keys
+            .iter()
+            .map(|key| key.concurrency.available_permits())
+            .collect();
+        let most = available.iter().copied().max().unwrap_or_default();
+        let candidates: Vec<usize> = (0..keys.len())
+            .filter(|&index| available[index] == most)
+            .collect();
+        candidates[self.rng.lock().unwrap().random_range(0..candidates.len())]
     }
+}
 
-    /// 检查并等待直到可以发送请求（遵守RPM限制）
-    async fn wait_for_rate_limit(&self, tracker: &KeyTracker) {
-        if let Some(request_times) = &tracker.request_times {
-            let mut times = request_times.lock().await;
-            let now = Instant::now();
-            times.retain(|&time| now.duration_since(time) < Duration::from_secs(60));
-            if self.config.rpm_limit > 0 && times.len() >= self.config.rpm_limit as usize {
-                if let Some(oldest) = times.first() {
-                    let elapsed = now.duration_since(*oldest);
-                    if elapsed < Duration::from_secs(60) {
-                        sleep(Duration::from_secs(60) - elapsed).await;
-                    }
-                }
-            }
-            times.push(now);
+/// 按各 Key 的权重随机选择，权重全部为 0 时退化为均匀随机
+struct WeightedRandomSelector {
+    rng: Mutex<StdRng>,
+}
+
+impl KeySelector for WeightedRandomSelector {
+    fn select(&self, keys: &[KeyTracker]) -> usize {
+        let mut rng = self.rng.lock().unwrap();
+        match WeightedIndex::new(keys.iter().map(|key| key.weight)) {
+            Ok(weights) => weights.sample(&mut *rng),
+            Err(_) => rng.random_range(0..keys.len()),
         }
     }
+}
 
-    /// 批量翻译文本
-    pub async fn translate_batch(
-        &self,
-        texts: &[&str],
-        target_lang: &LanguageIdentifier,
-        source_lang: Option<&LanguageIdentifier>,
-        options: &TranslateOptions,
-    ) -> Result<Vec<String>, TranslationError> {
-        let mut futures = Vec::new();
-        for &text in texts {
-            let future = self.translate_text_with_retry(text, target_lang, source_lang, options);
-            futures.push(future);
-        }
-        let results: Vec<_> = join_all(futures).await;
-        results.into_iter().collect()
+/// 根据配置创建 Key 选择器
+fn key_selector(config: &OpenAIConfig, rng: StdRng) -> Box<dyn KeySelector> {
+    let rng = Mutex::new(rng);
+    match config.key_strategy {
+        KeyStrategy::RoundRobin => Box::new(RoundRobinSelector::default()),
+        KeyStrategy::Random => Box::new(RandomSelector { rng }),
+        KeyStrategy::LeastLoaded => Box::new(LeastLoadedSelector { rng }),
+        KeyStrategy::WeightedRandom => Box::new(WeightedRandomSelector { rng }),
     }
+}
 
-    /// 使用重试逻辑翻译单个文本
-    async fn translate_text_with_retry(
-        &self,
-        text: &str,
-        target_lang: &LanguageIdentifier,
-        source_lang: Option<&LanguageIdentifier>,
-        options: &TranslateOptions,
-    ) -> Result<String, TranslationError> {
-        let mut errors = Vec::new();
-        for attempt in 0..=options.max_retries {
-            if attempt > 0 {
-                let delay = Duration::from_millis(100 * 2u64.pow(attempt - 1));
-                sleep(delay).await;
-            }
-            match self
-                .try_translate_single(text, target_lang, source_lang, options)
-                .await
-            {
-                Ok(result) => return Ok(result),
-                Err(e) => {
-                    if e.is_retryable() {
-                        errors.push(e);
-                    } else {
-                        return Err(e);
-                    }
-                }
-            }
+#[derive(Serialize)]
+struct Request<'a> {
+    model: &'a str,
+    messages: &'a [Message],
+    temperature: f32,
+    /// 候选译文数，只在大于 1 时发送
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+/// 对冲的两个请求共享的状态
+#[derive(Default)]
+struct Hedge {
+    /// 先选择 Key 的请求使用的 Key 下标
+    primary_key: OnceLock<usize>,
+    /// 请求取得并发许可和速率限制额度后通知，对冲从此时开始计时
+    admitted: tokio::sync::Notify,
+}
+
+/// 请求的采样参数和输出格式
+#[derive(Debug, Clone, Copy)]
+struct Sampling {
+    temperature: f32,
+    /// 候选译文数，大于 1 时不使用流式响应
+    n: u32,
+    /// 用户消息是待翻译文本组成的 JSON 数组，要求模型以 JSON 数组回复
+    json_array: bool,
+}
+
+impl Sampling {
+    /// 普通翻译使用的参数：温度为 0，只要一个候选
+    const DEFAULT: Sampling = Sampling {
+        temperature: 0.0,
+        n: 1,
+        json_array: false,
+    };
+
+    /// 是否使用流式响应
+    fn stream(self, config: &OpenAIConfig) -> bool {
+        config.stream && self.n <= 1
+    }
+}
+
+/// Chat Completions 接口的响应体，未知字段会被忽略
+///
+/// 部分兼容网关出错时仍返回 200，`choices` 为 `null` 并附带 `error` 对象
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default, deserialize_with = "http::null_as_default")]
+    choices: Vec<Choice>,
+    /// 只取其中的 `message`，结构不规范时忽略
+    #[serde(default)]
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    #[serde(default, deserialize_with = "http::null_as_default")]
+    message: ChoiceMessage,
+}
+
+/// 响应中的消息，拒绝回答或只调用工具时 `content` 为 `null`
+#[derive(Deserialize, Default)]
+struct ChoiceMessage {
+    content: Option<String>,
+}
+
+impl Response {
+    /// 按服务返回的顺序取所有候选的文本，跳过没有文本的候选
+    ///
+    /// 没有任何文本时返回带有网关错误信息（如果有）的 [`TranslationError::ServiceError`]
+    fn into_contents(self) -> Result<Vec<String>, TranslationError> {
+        let contents: Vec<String> = self
+            .choices
+            .into_iter()
+            .filter_map(|choice| choice.message.content)
+            .collect();
+        if !contents.is_empty() {
+            return Ok(contents);
         }
-        Err(TranslationError::MaxRetriesExceeded {
-            attempts: options.max_retries + 1,
-            errors,
+        let error = self
+            .error
+            .as_ref()
+            .and_then(|error| error.get("message").or(Some(error)))
+            .and_then(|message| message.as_str());
+        Err(match error {
+            Some(message) => TranslationError::ServiceError(format!(
+                "No translation results returned: {}",
+                message
+            )),
+            None => TranslationError::ServiceError("No translation results returned".to_string()),
         })
     }
+}
 
-    /// 尝试翻译单个文本（无重试）
-    async fn try_translate_single(
+/// `/responses` 接口的请求体
+#[derive(Serialize)]
+struct ResponsesRequest<'a> {
+    model: &'a str,
+    input: &'a [Message],
+    temperature: f32,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+/// `/responses` 接口的响应体，未知字段会被忽略
+#[derive(Deserialize)]
+struct ResponsesResponse {
+    #[serde(default, deserialize_with = "http::null_as_default")]
+    output: Vec<OutputItem>,
+}
+
+#[derive(Deserialize)]
+struct OutputItem {
+    #[serde(default, deserialize_with = "http::null_as_default")]
+    content: Vec<OutputContent>,
+}
+
+#[derive(Deserialize)]
+struct OutputContent {
+    text: Option<String>,
+}
+
+impl ResponsesResponse {
+    /// 取第一段输出文本，跳过推理等不含文本的输出项
+    fn output_text(self) -> Option<String> {
+        self.output
+            .into_iter()
+            .flat_map(|item| item.content)
+            .find_map(|content| content.text)
+    }
+}
+
+/// `/responses` 接口流式响应中的一个事件
+#[derive(Deserialize)]
+struct ResponsesStreamEvent {
+    #[serde(rename = "type")]
+    event_type: String,
+    delta: Option<String>,
+    /// `response.created` 等事件附带的响应对象
+    response: Option<ResponseIdentity>,
+}
+
+/// 流式响应中的一个事件
+#[derive(Deserialize)]
+struct StreamChunk {
+    #[serde(default, deserialize_with = "http::null_as_default")]
+    choices: Vec<StreamChoice>,
+    #[serde(flatten)]
+    identity: ResponseIdentity,
+}
+
+/// 响应中标识这次生成的字段，两种接口的响应体和流式事件都可能包含
+#[derive(Deserialize, Default)]
+struct ResponseIdentity {
+    id: Option<String>,
+    model: Option<String>,
+    system_fingerprint: Option<String>,
+}
+
+impl ResponseIdentity {
+    /// 流式响应取第一个带有标识的事件
+    fn fill(&mut self, other: ResponseIdentity) {
+        if self.id.is_none() {
+            *self = other;
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct StreamChoice {
+    #[serde(default, deserialize_with = "http::null_as_default")]
+    delta: Delta,
+}
+
+#[derive(Deserialize, Default)]
+struct Delta {
+    content: Option<String>,
+}
+
+/// 构造好的请求，以及解析响应时需要的信息
+struct OpenAIRequest {
+    request: PreparedRequest,
+    /// 是否要求模型在译文前输出检测到的源语言
+    detect_language: bool,
+    /// 发送前本地检测的源语言及置信度
+    source_hint: Option<(LanguageIdentifier, f64)>,
+    /// 实际发送的系统提示词的哈希
+    prompt_fingerprint: String,
+}
+
+/// 多候选翻译使用的最高温度，温度为 0 时多个候选通常完全相同
+const ALTERNATIVES_TEMPERATURE: f32 = 0.8;
+
+/// [`OpenAITranslator::translate_alternatives`] 返回的候选译文
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranslationAlternatives {
+    /// 去重后的候选译文，按服务返回的顺序排列
+    pub candidates: Vec<String>,
+    /// 请求的候选数
+    pub requested: usize,
+}
+
+impl TranslationAlternatives {
+    /// 去重后的候选是否少于请求的候选数
+    pub fn is_short(&self) -> bool {
+        self.candidates.len() < self.requested
+    }
+}
+
+/// 请求预览中代替幂等键的占位符，实际的幂等键在发送时为每个文本生成
+const IDEMPOTENCY_KEY_PLACEHOLDER: &str = "<generated>";
+
+/// OpenAI 错误响应
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ErrorDetails,
+}
+
+#[derive(Deserialize)]
+struct ErrorDetails {
+    message: String,
+    #[serde(rename = "type")]
+    error_type: Option<String>,
+    /// 官方接口为字符串，部分兼容网关返回数字
+    code: Option<serde_json::Value>,
+}
+
+/// 不可重试的 OpenAI 错误码（即使 HTTP 状态码为 429 或 5xx）
+const NON_RETRYABLE_CODES: &[&str] = &["insufficient_quota", "billing_hard_limit_reached"];
+
+/// 可以重试的 OpenAI 错误码或错误类型
+const RETRYABLE_CODES: &[&str] = &[
+    "rate_limit_exceeded",
+    "requests",
+    "tokens",
+    "server_error",
+    "service_unavailable",
+    "engine_overloaded",
+    "overloaded_error",
+    "api_error",
+    "timeout",
+];
+
+/// 将 OpenAI 的错误响应解析为 [`TranslationError::Provider`]
+///
+/// 错误码优先取 `error.code`，缺失时取 `error.type`。是否可重试先查错误码表，
+/// 表中没有的错误码按 HTTP 状态码判断（429 和 5xx 可以重试）。无法解析时返回 None。
+pub(crate) fn parse_error_body(
+    status: reqwest::StatusCode,
+    body: &str,
+) -> Option<TranslationError> {
+    let error = serde_json::from_str::<ErrorResponse>(body).ok()?.error;
+    let code = match error.code {
+        Some(serde_json::Value::String(code)) => Some(code),
+        Some(serde_json::Value::Number(code)) => Some(code.to_string()),
+        _ => None,
+    };
+    let code = code
+        .or(error.error_type.clone())
+        .unwrap_or_else(|| "unknown".to_string());
+    let classify = |key: &str| {
+        if NON_RETRYABLE_CODES.contains(&key) {
+            Some(false)
+        } else if RETRYABLE_CODES.contains(&key) {
+            Some(true)
+        } else {
+            None
+        }
+    };
+    let retryable = classify(&code)
+        .or_else(|| error.error_type.as_deref().and_then(classify))
+        .unwrap_or(status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error());
+    Some(TranslationError::Provider {
+        provider: "openai".to_string(),
+        code,
+        message: error.message,
+        http_status: Some(status),
+        retryable,
+        trace: None,
+    })
+}
+
+/// 接口返回 404 时在错误信息中提示检查 base URL，模型不存在的 404 除外
+fn with_base_url_hint(error: TranslationError, base_url: &str) -> TranslationError {
+    let hint = format!(
+        "check that base_url '{}' points at the API root such as https://api.openai.com/v1",
+        secret::redact_url(base_url)
+    );
+    match error {
+        TranslationError::Provider {
+            provider,
+            code,
+            message,
+            http_status,
+            retryable,
+            trace,
+        } if code != "model_not_found" => TranslationError::Provider {
+            provider,
+            code,
+            message: format!("{} ({})", message, hint),
+            http_status,
+            retryable,
+            trace,
+        },
+        TranslationError::HttpError {
+            status,
+            body,
+            trace,
+        } => TranslationError::HttpError {
+            status,
+            body: format!("{} ({})", body, hint),
+            trace,
+        },
+        error => error,
+    }
+}
+
+/// OpenAI翻译器实现
+pub struct OpenAITranslator {
+    client: Client,
+    /// 最近解析失败的主机名
+    dns_failures: http::DnsFailureCache,
+    config: OpenAIConfig,
+    /// 当前的 Key 集合，`key_provider` 返回新的 Key 时整体替换；请求持有开始时的集合直到完成
+    key_trackers: std::sync::RwLock<Arc<[KeyTracker]>>,
+    /// 同一时间只有一个请求向 `key_provider` 获取 Key
+    key_refresh: tokio::sync::Mutex<()>,
+    /// 最近一次向 `key_provider` 获取 Key 的时间
+    last_key_refresh: Mutex<Option<Instant>>,
+    /// 按 `key_strategy` 选择API Key
+    key_selector: Box<dyn KeySelector>,
+    /// 使用租户自己的 Key 的请求共用的并发许可
+    tenant_concurrency: ConcurrencyLimiter,
+    /// 所有 Key 和租户 Key 共享的排队指标
+    queue_metrics: Arc<QueueMetrics>,
+    /// 配置的提示词策略，未配置时为按配置创建的 [`DefaultPromptStrategy`]
+    prompt_strategy: Arc<dyn PromptStrategy>,
+    /// 批量翻译的启动延迟
+    start_jitter: batch::StartJitter,
+    /// `detect_before_translate` 的本地检测结果
+    detection_cache: DetectionCache<(LanguageIdentifier, f64)>,
+}
+
+impl OpenAITranslator {
+    /// 创建新的OpenAI翻译器实例
+    pub fn new(config: OpenAIConfig) -> Self {
+        let queue_metrics = Arc::new(QueueMetrics::new("openai", config.metrics_recorder.clone()));
+        // 使用 `key_provider` 时第一个请求获取 Key
+        let weighted = config.key_weights.len() == config.api_keys.len();
+        let key_trackers = match config.key_provider {
+            Some(_) => Arc::from([]),
+            None => config
+                .api_keys
+                .iter()
+                .enumerate()
+                .map(|(index, api_key)| {
+                    let weight = if weighted {
+                        config.key_weights[index]
+                    } else {
+                        1
+                    };
+                    let key = ApiKeyConfig::new(api_key.clone()).weight(weight);
+                    KeyTracker::new(key, &config, &queue_metrics)
+                })
+                .collect(),
+        };
+        Self {
+            client: http::build_client(&config.pool, &config.proxy_mode),
+            dns_failures: http::DnsFailureCache::new(config.dns_failure_ttl),
+            key_selector: key_selector(&config, StdRng::from_os_rng()),
+            tenant_concurrency: ConcurrencyLimiter::new(
+                config.concurrent_limit,
+                config.max_queue_depth,
+            )
+            .adaptive(config.adaptive_concurrency)
+            .metrics(queue_metrics.clone()),
+            queue_metrics,
+            prompt_strategy: config.prompt_strategy.clone().unwrap_or_else(|| {
+                Arc::new(DefaultPromptStrategy::from_config(&config)) as Arc<dyn PromptStrategy>
+            }),
+            start_jitter: batch::StartJitter::default(),
+            detection_cache: DetectionCache::new(config.detection_cache_size),
+            config,
+            key_trackers: std::sync::RwLock::new(key_trackers),
+            key_refresh: tokio::sync::Mutex::new(()),
+            last_key_refresh: Mutex::new(None),
+        }
+    }
+
+    /// 合并调用方的选项与配置中的默认选项
+    fn effective_options(&self, options: &TranslateOptions) -> EffectiveOptions {
+        EffectiveOptions::resolve(
+            options,
+            &self.config.default_options,
+            &EffectiveOptions::default(),
+        )
+        .start_deadline(self.config.clock.as_ref())
+    }
+
+    /// 影响译文的输入，用于缓存键和译文的来源信息
+    fn key_inputs(
         &self,
-        text: &str,
         target_lang: &LanguageIdentifier,
         source_lang: Option<&LanguageIdentifier>,
-        options: &TranslateOptions,
-    ) -> Result<String, TranslationError> {
-        if self.config.api_keys.is_empty() {
+        options: &EffectiveOptions,
+    ) -> CacheKeyInputs {
+        let messages = self.template_messages(target_lang, source_lang);
+        let mut inputs = CacheKeyInputs::new("openai")
+            .model(&self.config.model)
+            .prompt(prompt::system_content(&messages))
+            .option(
+                "base_url",
+                options.base_url.as_deref().unwrap_or(&self.config.base_url),
+            )
+            .option("api_shape", format!("{:?}", self.config.api_shape))
+            .option(
+                "detect_before_translate",
+                self.config.detect_before_translate,
+            );
+        // 自定义策略可能把指令写在系统消息以外
+        if self.config.prompt_strategy.is_some() {
+            inputs = inputs.option(
+                "prompt_messages",
+                serde_json::to_string(&messages).expect("messages are serializable"),
+            );
+        }
+        if let Some(normalization) = options.normalize_input {
+            inputs = inputs.option("normalize_input", format!("{:?}", normalization));
+        }
+        if options.protects() {
+            inputs = inputs
+                .option("protect_numbers", options.protect_numbers)
+                .option(
+                    "protect_patterns",
+                    format!("{:?}", options.protect_patterns),
+                );
+        }
+        inputs
+    }
+
+    /// 当前的 Key 集合
+    fn key_trackers(&self) -> Arc<[KeyTracker]> {
+        self.key_trackers.read().unwrap().clone()
+    }
+
+    /// 按 `key_strategy` 从当前的 Key 集合中选择下一个 Key，返回集合和 Key 的索引
+    ///
+    /// 没有配置 Key 时返回配置错误
+    fn select_key(&self) -> Result<(Arc<[KeyTracker]>, usize), TranslationError> {
+        let trackers = self.key_trackers();
+        if trackers.is_empty() {
             return Err(TranslationError::ConfigurationError(
                 "No API keys configured".to_string(),
             ));
         }
+        let index = self.key_selector.select(&trackers);
+        Ok((trackers, index))
+    }
 
-        let key_index = self.get_next_key_index().await;
-        let selected_key = &self.config.api_keys[key_index];
-        let tracker = &self.key_trackers[key_index];
+    /// 按 `key_strategy` 选择下一个API Key索引
+    #[cfg(test)]
+    fn get_next_key_index(&self) -> Result<usize, TranslationError> {
+        self.select_key().map(|(_, index)| index)
+    }
 
-        let _permit =
-            tracker.semaphore.acquire().await.map_err(|e| {
-                TranslationError::Other(format!("Failed to acquire semaphore: {}", e))
-            })?;
-        self.wait_for_rate_limit(tracker).await;
+    /// 需要时向 `key_provider` 获取 Key：距上次获取超过 `key_refresh_interval`，
+    /// 或当前没有可用的 Key 且距上次获取超过 [`MIN_KEY_REFRESH_INTERVAL`]
+    ///
+    /// 获取失败时保留当前的 Key，只在还没有任何 Key 时返回错误
+    async fn refresh_keys(&self) -> Result<(), TranslationError> {
+        let Some(provider) = &self.config.key_provider else {
+            return Ok(());
+        };
+        if self.tenant_concurrency.is_closed() || !self.key_refresh_due() {
+            return Ok(());
+        }
+        // 已有 Key 时不等待正在进行的获取
+        let _guard = match self.key_refresh.try_lock() {
+            Ok(guard) => guard,
+            Err(_) if !self.key_trackers().is_empty() => return Ok(()),
+            Err(_) => self.key_refresh.lock().await,
+        };
+        if !self.key_refresh_due() {
+            return Ok(());
+        }
+        *self.last_key_refresh.lock().unwrap() = Some(self.config.clock.now());
+        let fetched = provider.fetch_keys().await.and_then(|keys| {
+            if keys.is_empty() {
+                Err(TranslationError::ConfigurationError(
+                    "Key provider returned no API keys".to_string(),
+                ))
+            } else {
+                Ok(keys)
+            }
+        });
+        match fetched {
+            Ok(keys) => {
+                self.replace_keys(keys);
+                Ok(())
+            }
+            Err(error) if self.key_trackers().is_empty() => Err(error),
+            Err(_error) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %_error, "failed to refresh OpenAI API keys; keeping the current keys");
+                Ok(())
+            }
+        }
+    }
+
+    /// 是否需要向 `key_provider` 获取 Key
+    fn key_refresh_due(&self) -> bool {
+        let Some(last) = *self.last_key_refresh.lock().unwrap() else {
+            return true;
+        };
+        let elapsed = self.config.clock.now().saturating_duration_since(last);
+        if elapsed >= self.config.key_refresh_interval {
+            return true;
+        }
+        elapsed >= MIN_KEY_REFRESH_INTERVAL
+            && self
+                .key_trackers()
+                .iter()
+                .all(|tracker| tracker.rejected.load(Ordering::Relaxed))
+    }
+
+    /// 替换当前的 Key 集合，Key 和权重都没有变化时保留原有的跟踪状态
+    fn replace_keys(&self, keys: Vec<ApiKeyConfig>) {
+        let mut current = self.key_trackers.write().unwrap();
+        let unchanged = current.len() == keys.len()
+            && current.iter().zip(&keys).all(|(tracker, key)| {
+                tracker.api_key == key.api_key && tracker.weight == key.weight
+            });
+        if unchanged {
+            return;
+        }
+        *current = keys
+            .into_iter()
+            .map(|key| KeyTracker::new(key, &self.config, &self.queue_metrics))
+            .collect();
+    }
+
+    /// 获取内置策略的系统提示词
+    #[cfg(test)]
+    fn get_system_prompt(&self, target_lang: &str, source_lang: Option<&str>) -> String {
+        DefaultPromptStrategy::from_config(&self.config).system_prompt(target_lang, source_lang)
+    }
+
+    /// 提示词策略为空文本构造的消息，加上检测语言的说明，用于缓存键和 token 预算
+    fn template_messages(
+        &self,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+    ) -> Vec<Message> {
+        let mut messages =
+            self.prompt_strategy
+                .build_messages("", target_lang, source_lang, None, &[]);
+        if self.config.detect_source_language && source_lang.is_none() {
+            prompt::push_instructions(&mut messages, DETECT_LANGUAGE_INSTRUCTION);
+        }
+        messages
+    }
+
+    /// 检查并等待直到可以发送请求（遵守RPM限制）
+    ///
+    /// 启用 `honor_server_rate_limits` 且服务端报告的额度仍有效时按该额度决定是否等待，
+    /// 只保留最小请求间隔。等待会超出总时限时返回 [`TranslationError::DeadlineExceeded`]
+    async fn wait_for_rate_limit(
+        &self,
+        tracker: &KeyTracker,
+        deadline: Option<Deadline>,
+    ) -> Result<(), TranslationError> {
+        let clock = self.config.clock.as_ref();
+        if !self.config.honor_server_rate_limits {
+            return tracker.rate_limiter.acquire(clock, deadline).await;
+        }
+        loop {
+            let now = clock.now();
+            let reserved = tracker.server_limits.lock().unwrap().reserve(now);
+            match reserved {
+                None => return tracker.rate_limiter.acquire(clock, deadline).await,
+                Some(wait) if wait.is_zero() => {
+                    return tracker.rate_limiter.acquire_interval(clock, deadline).await;
+                }
+                Some(wait) => {
+                    if let Some(deadline) = deadline {
+                        deadline.check(now, wait)?;
+                    }
+                    clock.sleep(wait).await;
+                }
+            }
+        }
+    }
+
+    /// 各 Key 最近一次响应报告的剩余额度，顺序与当前的 Key 集合（配置中的 `api_keys`
+    /// 或 `key_provider` 最近返回的 Key）一致
+    pub fn rate_limit_state(&self) -> Vec<RateLimitState> {
+        self.key_trackers()
+            .iter()
+            .map(|tracker| *tracker.server_limits.lock().unwrap())
+            .collect()
+    }
+
+    /// 批量翻译文本
+    ///
+    /// `texts` 可以是 `&[&str]`、`Vec<String>` 或产生字符串的迭代器，结果顺序与输入一致。
+    /// 每个条目单独按 `on_failure` 处理（条目的 panic 视为失败），仍然失败时返回输入顺序中的
+    /// 第一个错误。启用 `truncate_to_budget` 且超出 `max_batch_chars` 时只翻译并返回预算内的
+    /// 前几条，需要逐条知道哪些条目被跳过时使用 [`Self::translate_batch_detailed`]
+    pub async fn translate_batch<I>(
+        &self,
+        texts: I,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<Vec<String>, TranslationError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let options = &self.effective_options(options);
+        let texts: Vec<I::Item> = texts.into_iter().collect();
+        let (details, fit) = self
+            .translate_batch_budgeted(&texts, target_lang, source_lang, options, false)
+            .await?;
+        Ok(details
+            .into_iter()
+            .take(fit)
+            .map(|detail| detail.text)
+            .collect())
+    }
+
+    /// 批量翻译文本，返回每条的详细结果
+    ///
+    /// 与 [`Self::translate_batch`] 相同，但启用 `truncate_to_budget` 时返回所有条目：不论
+    /// `on_failure` 如何，超出 `max_batch_chars` 预算的条目都不发送，以标记为
+    /// [`TranslationDetail::passthrough`] 的原文返回，`provider_metadata` 的 `error` 为
+    /// [`TranslationError::BudgetExceeded`] 的错误信息。同时启用 `auto_split` 时，预算在其中间
+    /// 用完的那一条按拆分翻译的长度限制拆分，只翻译预算内的片段，结果标记为
+    /// [`TranslationDetail::partial`]
+    pub async fn translate_batch_detailed<I>(
+        &self,
+        texts: I,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<Vec<TranslationDetail>, TranslationError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let options = &self.effective_options(options);
+        let texts: Vec<I::Item> = texts.into_iter().collect();
+        let (details, _) = self
+            .translate_batch_budgeted(&texts, target_lang, source_lang, options, true)
+            .await?;
+        Ok(details)
+    }
 
-        let client = if let Some(timeout) = options.timeout {
-            Client::builder()
-                .timeout(timeout)
-                .build()
-                .map_err(|e| TranslationError::NetworkError(e))?
+    /// 按 `max_batch_chars` 截断后批量翻译，返回所有条目的结果和完整翻译的前几条的数量
+    ///
+    /// `split` 为 true 且启用 `auto_split` 时按片段截断，见 [`Self::translate_batch_detailed`]
+    async fn translate_batch_budgeted<S: AsRef<str>>(
+        &self,
+        texts: &[S],
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+        split: bool,
+    ) -> Result<(Vec<TranslationDetail>, usize), TranslationError> {
+        let budget = batch::CharBudget::of(options);
+        let truncation = if split && options.auto_split {
+            let max_tokens = self.input_token_budget(target_lang, source_lang);
+            budget.apply_split(texts, max_tokens, self.config.token_estimator)?
         } else {
-            self.client.clone()
+            budget.apply(texts)?
         };
+        let sent = &truncation.sent(texts);
+        // 同时进行的翻译数不超过所有 Key 的并发数之和，内存占用与批次大小无关
+        let mut results: Vec<Option<Result<TranslationDetail, TranslationError>>> =
+            std::iter::repeat_with(|| None).take(sent.len()).collect();
+        // 按下标遍历，闭包参数不带生命周期，返回的 future 可以在 async trait 中使用
+        let mut translated = futures::stream::iter(0..sent.len())
+            .map(|index| async move {
+                let text = sent[index];
+                self.start_jitter
+                    .wait(options.start_jitter, self.config.clock.as_ref())
+                    .await;
+                let result = batch::catch_panic(self.translate_checked(
+                    text,
+                    target_lang,
+                    source_lang,
+                    options,
+                ))
+                .await;
+                (
+                    index,
+                    options.on_failure.apply(texts[index].as_ref(), result),
+                )
+            })
+            .buffer_unordered(self.batch_concurrency());
+        while let Some((index, result)) = translated.next().await {
+            results[index] = Some(result);
+        }
+        let mut details = results
+            .into_iter()
+            .map(|result| result.expect("every batch item is translated"))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(error) = &truncation.error {
+            if truncation.prefix.is_some() {
+                let detail = &mut details[truncation.fit];
+                if !detail.passthrough {
+                    detail.partial = true;
+                    detail.provider_metadata["error"] = error.to_string().into();
+                }
+            }
+            details.extend(
+                texts[truncation.skipped_from()..]
+                    .iter()
+                    .map(|text| TranslationDetail::pass_through(text.as_ref(), error)),
+            );
+        }
+        Ok((details, truncation.fit))
+    }
 
-        let source_lang_str = source_lang.map(|s| s.to_string());
-        let system_prompt =
-            self.get_system_prompt(&target_lang.to_string(), source_lang_str.as_deref());
+    /// 批量翻译带标签的文本，每个结果附带对应输入的标签
+    ///
+    /// 标签始终和对应的文本一起传递，不依赖位置；设置 `dedup` 时相同的文本只翻译一次。
+    /// 每条文本单独翻译，一条失败不影响其他结果，结果按输入顺序排列
+    pub async fn translate_batch_tagged<T: Send>(
+        &self,
+        items: Vec<(T, String)>,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Vec<(T, Result<String, TranslationError>)> {
+        let effective = self.effective_options(options);
+        batch::translate_tagged(
+            items,
+            effective.dedup,
+            self.batch_concurrency(),
+            batch::CharBudget::of(&effective),
+            |text| async move {
+                self.start_jitter
+                    .wait(effective.start_jitter, self.config.clock.as_ref())
+                    .await;
+                self.translate_text(&text, target_lang, source_lang, options)
+                    .await
+            },
+        )
+        .await
+    }
 
-        let request = Request {
-            model: self.config.model.clone(),
-            messages: vec![
-                Message {
-                    role: "system".to_string(),
-                    content: system_prompt,
-                },
-                Message {
-                    role: "user".to_string(),
-                    content: text.to_string(),
-                },
-            ],
-            temperature: 0.0,
+    /// 在一个请求中批量翻译文本，文本以 JSON 数组发送，模型以 JSON 数组回复
+    ///
+    /// 适合大量短文本（例如界面文案），请求数和重复发送的系统提示词都比逐条翻译少。
+    /// 回复包在代码块中或带有说明文字时，取其中第一个字符串数组；找不到或条数与输入不一致时
+    /// 返回 [`TranslationError::MalformedResponse`]。输入会按 `normalize_input` 规范化，
+    /// 但不替换受保护的片段，也不检测源语言；整个数组超出长度限制时直接返回错误，不做拆分。
+    /// 超出 `max_batch_chars` 时即使启用了 `truncate_to_budget` 也整批拒绝
+    pub async fn translate_batch_json<I>(
+        &self,
+        texts: I,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<Vec<String>, TranslationError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let options = &self.effective_options(options);
+        let texts: Vec<String> = texts
+            .into_iter()
+            .map(|text| {
+                normalize::apply(text.as_ref(), options.normalize_input.as_ref()).into_owned()
+            })
+            .collect();
+        batch::CharBudget::of(options).check(&texts)?;
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let input = serde_json::to_string(&texts).expect("strings are serializable");
+        preflight::check_length(
+            (self.config.token_estimator)(&input),
+            Some(self.input_token_budget(target_lang, source_lang)),
+        )?;
+        let sampling = Sampling {
+            json_array: true,
+            ..Sampling::DEFAULT
         };
+        let content = self
+            .translate_choices_with_retry(&input, target_lang, source_lang, options, sampling)
+            .await?
+            .into_iter()
+            .next()
+            .map(|detail| detail.text)
+            .unwrap_or_default();
+        let translations: Vec<String> = extract::json(&content).map_err(|e| {
+            TranslationError::MalformedResponse(format!("expected a JSON array of strings: {}", e))
+        })?;
+        if translations.len() != texts.len() {
+            return Err(TranslationError::MalformedResponse(format!(
+                "expected {} translations, got {}",
+                texts.len(),
+                translations.len()
+            )));
+        }
+        Ok(translations)
+    }
 
-        let response = client
-            .post(&format!("{}/chat/completions", self.config.base_url))
-            .header("Authorization", format!("Bearer {}", selected_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
+    /// 批量翻译时同时进行的翻译数：所有 Key 的并发数之和
+    fn batch_concurrency(&self) -> usize {
+        (self.config.concurrent_limit * self.key_trackers().len()).max(1)
+    }
+
+    /// 单条输入允许的 token 数：`max_input_tokens` 与上下文窗口扣除系统提示词后的较小值
+    fn input_token_budget(
+        &self,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+    ) -> usize {
+        let estimator = self.config.token_estimator;
+        let prompt: String = self
+            .template_messages(target_lang, source_lang)
+            .iter()
+            .map(|message| message.content.as_str())
+            .collect();
+        let context_window = self
+            .config
+            .context_window
+            .unwrap_or_else(|| context_window_for_model(&self.config.model));
+        let context_budget = (context_window as usize).saturating_sub(estimator(&prompt));
+        self.config
+            .max_input_tokens
+            .map_or(context_budget, |max| max.min(context_budget))
+    }
+
+    /// 规范化并替换受保护的片段后翻译单个文本，译文中的占位符还原为原文
+    async fn translate_checked(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+    ) -> Result<TranslationDetail, TranslationError> {
+        let text = normalize::apply(text, options.normalize_input.as_ref());
+        let protected = options.protect(&text);
+        let mut detail = self
+            .translate_within_budget(protected.text(), target_lang, source_lang, options)
+            .await?;
+        // 截断或中断的译文只包含部分占位符
+        detail.text = if detail.partial {
+            protected.restore_partial(&detail.text)
+        } else {
+            protected.restore(&detail.text)?
+        };
+        Ok(detail)
+    }
+
+    /// 长度预检后翻译单个文本，超长时按 `overflow_policy` 处理，
+    /// 启用 `auto_split` 选项时总是拆分翻译
+    async fn translate_within_budget(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+    ) -> Result<TranslationDetail, TranslationError> {
+        let estimator = self.config.token_estimator;
+        let max_tokens = self.input_token_budget(target_lang, source_lang);
+        let policy = match preflight::check_length(estimator(text), Some(max_tokens)) {
+            Ok(()) => {
+                return self
+                    .translate_text_with_retry(text, target_lang, source_lang, options)
+                    .await;
+            }
+            Err(_) if options.auto_split => &OverflowPolicy::Split,
+            Err(e) => match &self.config.overflow_policy {
+                OverflowPolicy::Reject => return Err(e),
+                policy => policy,
+            },
+        };
+        match policy {
+            OverflowPolicy::TruncateWithMarker(marker) => {
+                // 只翻译第一个片段，片段在段落、句子等自然边界处截断
+                let segments = preflight::split_text(text, max_tokens, estimator);
+                let head = segments.first().map_or("", |segment| segment.text);
+                let mut detail = self
+                    .translate_text_with_retry(head, target_lang, source_lang, options)
+                    .await?;
+                detail.text.push_str(marker);
+                detail.partial = true;
+                Ok(detail)
+            }
+            _ => {
+                let segments = preflight::split_text(text, max_tokens, estimator);
+                let futures = segments.iter().map(|segment| async move {
+                    if segment.text.is_empty() {
+                        return Ok(None);
+                    }
+                    self.translate_text_with_retry(segment.text, target_lang, source_lang, options)
+                        .await
+                        .map(Some)
+                });
+                let translated = join_all(futures).await;
+                let mut result: Option<TranslationDetail> = None;
+                let mut text = String::new();
+                for (segment, translation) in segments.iter().zip(translated) {
+                    if let Some(detail) = translation? {
+                        text.push_str(&detail.text);
+                        match &mut result {
+                            Some(result) if result.detected_source.is_none() => {
+                                result.detected_source = detail.detected_source;
+                            }
+                            Some(_) => {}
+                            None => result = Some(detail),
+                        }
+                    }
+                    text.push_str(segment.separator);
+                }
+                let mut result = result.unwrap_or_default();
+                result.text = text;
+                Ok(result)
+            }
+        }
+    }
+
+    /// 使用重试逻辑翻译单个文本
+    async fn translate_text_with_retry(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+    ) -> Result<TranslationDetail, TranslationError> {
+        // 同一文本的所有重试共用一个幂等键
+        let idempotency_key = self
+            .config
+            .idempotency_header
+            .as_ref()
+            .map(|_| uuid::Uuid::new_v4().to_string());
+        let mut first_attempt = true;
+        let result = retry::retry(
+            self.config.clock.as_ref(),
+            options.max_retries,
+            options.deadline,
+            "openai",
+            options.on_retry.as_ref(),
+            || {
+                self.try_translate_single(
+                    text,
+                    target_lang,
+                    source_lang,
+                    options,
+                    idempotency_key.as_deref(),
+                    std::mem::take(&mut first_attempt),
+                )
+            },
+        )
+        .await;
+        match result {
+            Err(TranslationError::MaxRetriesExceeded {
+                attempts,
+                mut errors,
+            }) => take_best_candidate(&mut errors)
+                .ok_or(TranslationError::MaxRetriesExceeded { attempts, errors }),
+            result => result,
+        }
+    }
+
+    /// 使用重试逻辑翻译单个文本，按服务返回的顺序返回所有候选
+    async fn translate_choices_with_retry(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+        sampling: Sampling,
+    ) -> Result<Vec<TranslationDetail>, TranslationError> {
+        let idempotency_key = self
+            .config
+            .idempotency_header
+            .as_ref()
+            .map(|_| uuid::Uuid::new_v4().to_string());
+        let mut first_attempt = true;
+        retry::retry(
+            self.config.clock.as_ref(),
+            options.max_retries,
+            options.deadline,
+            "openai",
+            options.on_retry.as_ref(),
+            || {
+                self.try_translate_hedged(
+                    text,
+                    target_lang,
+                    source_lang,
+                    options,
+                    idempotency_key.as_deref(),
+                    sampling,
+                    std::mem::take(&mut first_attempt),
+                )
+            },
+        )
+        .await
+    }
+
+    /// 本地检测文本的语言，短文本的结果会被缓存
+    fn detect_cached(&self, text: &str) -> Option<(LanguageIdentifier, f64)> {
+        if let Some(detected) = self.detection_cache.get(text) {
+            return Some(detected);
+        }
+        let detected = detect::detect(text)?;
+        self.detection_cache.insert(text, detected.clone());
+        Some(detected)
+    }
+
+    /// 关闭翻译器：之后的请求和仍在等待并发许可的请求返回
+    /// [`TranslationError::TranslatorClosed`]，等待已经开始的请求全部完成后返回
+    ///
+    /// 直接丢弃翻译器不会阻塞，也不需要先调用 `close`：正在进行的请求持有翻译器的引用
+    /// （例如 [`crate::TranslationManager`] 中的 `Arc`），翻译器在它们完成后才会真正释放
+    pub async fn close(&self) {
+        let key_trackers = self.key_trackers();
+        join_all(
+            key_trackers
+                .iter()
+                .map(|tracker| &tracker.concurrency)
+                .chain([&self.tenant_concurrency])
+                .map(ConcurrencyLimiter::close),
+        )
+        .await;
+    }
+
+    /// 语言检测缓存的命中统计
+    pub fn detection_cache_stats(&self) -> DetectionCacheStats {
+        self.detection_cache.stats()
+    }
+
+    /// 当前生效的并发数之和（不含使用租户 Key 的请求）
+    ///
+    /// 未启用 `adaptive_concurrency` 时等于 `concurrent_limit` 乘以 Key 的数量
+    pub fn effective_concurrency_limit(&self) -> usize {
+        self.key_trackers()
+            .iter()
+            .map(|tracker| tracker.concurrency.effective_limit())
+            .sum()
+    }
+
+    /// 最近 [`crate::metrics::QUEUE_STATS_WINDOW`] 内等待并发许可的统计，包括所有 Key 和租户 Key
+    pub fn queue_stats(&self) -> QueueStats {
+        self.queue_metrics.stats()
+    }
+
+    /// 构造单个文本的翻译请求，不进行任何网络请求
+    #[allow(clippy::too_many_arguments)]
+    fn build_request(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+        base_url: &str,
+        api_key: &SecretString,
+        idempotency_key: Option<&str>,
+        sampling: Sampling,
+    ) -> OpenAIRequest {
+        // 本地检测的结果足够可信时，作为源语言写入提示词
+        let source_hint = if self.config.detect_before_translate && source_lang.is_none() {
+            self.detect_cached(text)
+        } else {
+            None
+        };
+        let hinted_source = source_hint
+            .as_ref()
+            .filter(|(_, confidence)| *confidence >= DETECT_BEFORE_TRANSLATE_THRESHOLD);
+        let prompt_source = source_lang.or(hinted_source.map(|(lang, _)| lang));
+        let mut messages =
+            self.prompt_strategy
+                .build_messages(text, target_lang, prompt_source, None, &[]);
+        // JSON 数组的回复中不能有检测语言的前导行
+        let detect_language =
+            self.config.detect_source_language && prompt_source.is_none() && !sampling.json_array;
+        if detect_language {
+            prompt::push_instructions(&mut messages, DETECT_LANGUAGE_INSTRUCTION);
+        }
+        if sampling.json_array {
+            prompt::push_instructions(&mut messages, JSON_ARRAY_INSTRUCTION);
+        }
+        if options.protects() && protect::contains_placeholder(text) {
+            prompt::push_instructions(&mut messages, protect::PLACEHOLDER_INSTRUCTION);
+        }
+
+        let mut headers = vec![
+            (
+                "Authorization".to_string(),
+                format!("Bearer {}", api_key.expose_secret()),
+            ),
+            ("Content-Type".to_string(), "application/json".to_string()),
+        ];
+        if let (Some(header), Some(key)) = (&self.config.idempotency_header, idempotency_key) {
+            headers.push((header.clone(), key.to_string()));
+        }
+        headers.extend(trace::headers(options));
+        let body = match self.config.api_shape {
+            ApiShape::ChatCompletions => serde_json::to_vec(&Request {
+                model: &self.config.model,
+                messages: &messages,
+                temperature: sampling.temperature,
+                n: (sampling.n > 1).then_some(sampling.n),
+                stream: sampling.stream(&self.config),
+            }),
+            ApiShape::Responses => serde_json::to_vec(&ResponsesRequest {
+                model: &self.config.model,
+                input: &messages,
+                temperature: sampling.temperature,
+                stream: sampling.stream(&self.config),
+            }),
+        }
+        .expect("request body is serializable");
+        OpenAIRequest {
+            request: PreparedRequest {
+                url: endpoint_url(base_url, self.config.api_shape.path()),
+                query: Vec::new(),
+                headers,
+                body,
+            },
+            detect_language,
+            source_hint,
+            prompt_fingerprint: cache::prompt_hash(prompt::system_content(&messages)),
+        }
+    }
+
+    /// 构造翻译请求但不发送，用于在不产生费用的情况下排查提示词和参数问题
+    ///
+    /// 返回的请求与 [`Translator::translate_with_options`] 为该文本发送的请求一致，
+    /// 使用第一个 API Key，幂等键显示为 `<generated>`。超出长度限制需要拆分的文本
+    /// 不做拆分，预览的是整段文本的请求。
+    pub fn build_request_preview(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<RequestPreview, TranslationError> {
+        let options = &self.effective_options(options);
+        let text = normalize::apply(text, options.normalize_input.as_ref());
+        let protected = options.protect(&text);
+        let base_url = match &options.base_url {
+            Some(base_url) => {
+                http::validate_override_url("base_url", base_url)?;
+                base_url.as_str()
+            }
+            None => self.config.base_url.as_str(),
+        };
+        let key_trackers = self.key_trackers();
+        let tracker = key_trackers.first().ok_or_else(|| {
+            TranslationError::ConfigurationError("No API keys configured".to_string())
+        })?;
+        let idempotency_key = self
+            .config
+            .idempotency_header
+            .as_ref()
+            .map(|_| IDEMPOTENCY_KEY_PLACEHOLDER);
+        let request = self.build_request(
+            protected.text(),
+            target_lang,
+            source_lang,
+            options,
+            base_url,
+            &tracker.api_key,
+            idempotency_key,
+            Sampling::DEFAULT,
+        );
+        Ok(request.request.preview(Some(&tracker.api_key)))
+    }
+
+    /// 尝试翻译单个文本（无重试），`first_attempt` 为 true 时可以对冲
+    async fn try_translate_single(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+        idempotency_key: Option<&str>,
+        first_attempt: bool,
+    ) -> Result<TranslationDetail, TranslationError> {
+        let mut details = self
+            .try_translate_hedged(
+                text,
+                target_lang,
+                source_lang,
+                options,
+                idempotency_key,
+                Sampling::DEFAULT,
+                first_attempt,
+            )
             .await?;
+        Ok(details.swap_remove(0))
+    }
 
+    /// 尝试翻译（无重试），按 `hedge_after` 对冲
+    ///
+    /// 只对冲重试中的第一次尝试，且只对冲温度为 0、只要一个候选的请求：采样的请求两次结果
+    /// 不同，不能互相代替。`hedge_after` 从第一个请求取得并发许可和速率限制额度之后开始计时，
+    /// 仍在排队的请求不对冲，避免在本地限制或服务端已经饱和时再增加请求。超过 `hedge_after`
+    /// 第一个请求仍未完成时发出第二个请求，取先成功的结果，另一个请求随之取消；一个请求失败时
+    /// 等待另一个，都失败时返回第一个请求的错误
+    #[allow(clippy::too_many_arguments)]
+    async fn try_translate_hedged(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+        idempotency_key: Option<&str>,
+        sampling: Sampling,
+        first_attempt: bool,
+    ) -> Result<Vec<TranslationDetail>, TranslationError> {
+        let hedge_state = Hedge::default();
+        let attempt = || {
+            self.try_translate_choices(
+                text,
+                target_lang,
+                source_lang,
+                options,
+                idempotency_key,
+                sampling,
+                &hedge_state,
+            )
+        };
+        let hedge_after = options
+            .hedge_after
+            .filter(|_| first_attempt && sampling.temperature == 0.0 && sampling.n <= 1);
+        let Some(hedge_after) = hedge_after else {
+            return attempt().await;
+        };
+
+        let primary = attempt();
+        tokio::pin!(primary);
+        let timer = async {
+            hedge_state.admitted.notified().await;
+            self.config.clock.sleep(hedge_after).await;
+        };
+        tokio::select! {
+            result = &mut primary => return result,
+            () = timer => {}
+        }
+        let hedge = attempt();
+        tokio::pin!(hedge);
+        tokio::select! {
+            result = &mut primary => match result {
+                Ok(details) => Ok(details),
+                Err(error) => hedge.await.map_err(|_| error),
+            },
+            result = &mut hedge => match result {
+                Ok(details) => Ok(details),
+                Err(_) => primary.await,
+            },
+        }
+    }
+
+    /// 尝试翻译单个文本（无重试），按服务返回的顺序返回所有候选，至少有一个
+    ///
+    /// 不是目标语言（`assert_target_language`）或长度比例超出 `length_ratio_guard` 的候选被丢弃，
+    /// 所有候选都被丢弃时返回第一个被丢弃候选的错误。对冲的两个请求共用 `hedge`：
+    /// 先选择 Key 的请求记录下标，另一个请求在有其他 Key 时避开它
+    #[allow(clippy::too_many_arguments)]
+    async fn try_translate_choices(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &EffectiveOptions,
+        idempotency_key: Option<&str>,
+        sampling: Sampling,
+        hedge: &Hedge,
+    ) -> Result<Vec<TranslationDetail>, TranslationError> {
+        // 单次请求覆盖的 base URL 在占用 Key 之前校验
+        let base_url = match &options.base_url {
+            Some(base_url) => {
+                http::validate_override_url("base_url", base_url)?;
+                base_url.as_str()
+            }
+            None => self.config.base_url.as_str(),
+        };
+
+        let clock = self.config.clock.as_ref();
+        let tenant = options.tenant.as_ref();
+        // 租户自己的 Key 不参与轮换，也不受配置中 Key 的 RPM 限制。请求持有选择时的
+        // Key 集合，期间替换 Key 不影响这个请求
+        let key_trackers;
+        let (selected_key, concurrency, tracker) =
+            match tenant.and_then(|t| t.api_key_override.as_ref()) {
+                Some(api_key) => (api_key, &self.tenant_concurrency, None),
+                None => {
+                    self.refresh_keys().await?;
+                    let mut index;
+                    (key_trackers, index) = self.select_key()?;
+                    match hedge.primary_key.get() {
+                        Some(&primary) if primary == index && key_trackers.len() > 1 => {
+                            index = (index + 1) % key_trackers.len();
+                        }
+                        Some(_) => {}
+                        None => {
+                            let _ = hedge.primary_key.set(index);
+                        }
+                    }
+                    let tracker = &key_trackers[index];
+                    (&tracker.api_key, &tracker.concurrency, Some(tracker))
+                }
+            };
+
+        // 请求体只序列化一次，超出大小限制时不占用并发许可和 RPM 额度
+        let OpenAIRequest {
+            request,
+            detect_language,
+            source_hint,
+            prompt_fingerprint,
+        } = self.build_request(
+            text,
+            target_lang,
+            source_lang,
+            options,
+            base_url,
+            selected_key,
+            idempotency_key,
+            sampling,
+        );
+        let body_bytes = request.body.len();
+        preflight::check_length(body_bytes, self.config.max_request_bytes)?;
+
+        let _permit = concurrency
+            .acquire_within(options.max_queue_wait, options.deadline, clock)
+            .await?;
+        if let Some(tracker) = tracker {
+            self.wait_for_rate_limit(tracker, options.deadline).await?;
+        }
+        if let Some(rate_limiter) = tenant.and_then(|t| t.rate_limiter.as_ref()) {
+            rate_limiter.acquire(clock, options.deadline).await?;
+        }
+        hedge.admitted.notify_one();
+
+        let hinted_source = source_hint
+            .as_ref()
+            .filter(|(_, confidence)| *confidence >= DETECT_BEFORE_TRANSLATE_THRESHOLD);
+        self.dns_failures.check(&request.url, clock)?;
+        let mut request_builder = request.into_builder(&self.client);
+        if let Some(timeout) = options.attempt_timeout(self.config.clock.as_ref()) {
+            request_builder = request_builder.timeout(timeout);
+        }
+        let response = request_builder
+            .send()
+            .await
+            .map_err(|error| self.dns_failures.record(error.into(), clock))?;
+        concurrency.observe(response.status());
+        if let Some(tracker) = tracker {
+            if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+                tracker.rejected.store(true, Ordering::Relaxed);
+            }
+            tracker
+                .server_limits
+                .lock()
+                .unwrap()
+                .update(response.headers(), clock.now());
+        }
+
+        if response.status() == reqwest::StatusCode::PAYLOAD_TOO_LARGE {
+            return Err(TranslationError::RequestTooLarge { bytes: body_bytes });
+        }
         if !response.status().is_success() {
             let status = response.status();
-            let body = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(TranslationError::HttpError { status, body });
+            let body = match http::read_text(response, options).await {
+                Ok(body) => body,
+                Err(
+                    e
+                    @ (TranslationError::TimeoutError | TranslationError::ResponseTooLarge { .. }),
+                ) => return Err(e),
+                Err(_) => "Unknown error".to_string(),
+            };
+            // 服务端可能在错误信息中回显 Key
+            let body = selected_key.redact_owned(body);
+            let error = parse_error_body(status, &body).unwrap_or(TranslationError::HttpError {
+                status,
+                body,
+                trace: None,
+            });
+            let error = trace::annotate_error(error, options);
+            return Err(if status == reqwest::StatusCode::NOT_FOUND {
+                with_base_url_hint(error, base_url)
+            } else {
+                error
+            });
         }
 
-        let response_body: Response = response.json().await?;
-        response_body
-            .choices
-            .into_iter()
-            .next()
-            .map(|c| c.message.content)
-            .ok_or_else(|| {
-                TranslationError::ServiceError("No translation results returned".to_string())
-            })
+        let mut identity = ResponseIdentity::default();
+        let (contents, partial, mut raw) = if sampling.stream(&self.config) {
+            let (content, partial) = self.read_stream(response, options, &mut identity).await?;
+            (vec![content], partial, None)
+        } else {
+            // 先解析为通用 JSON，需要时原样保留给调用方
+            let body: serde_json::Value = http::read_json(response, options).await?;
+            // 标识字段类型不符时不影响译文
+            identity = ResponseIdentity::deserialize(&body).unwrap_or_default();
+            let contents = match self.config.api_shape {
+                ApiShape::ChatCompletions => {
+                    http::decode_json::<Response>(&body)?.into_contents()?
+                }
+                ApiShape::Responses => vec![
+                    http::decode_json::<ResponsesResponse>(&body)?
+                        .output_text()
+                        .ok_or_else(|| {
+                            TranslationError::ServiceError(
+                                "No translation results returned".to_string(),
+                            )
+                        })?,
+                ],
+            };
+            let raw = options
+                .include_raw_response
+                .then(|| http::capture_raw(body, options.max_raw_response_bytes));
+            (contents, false, raw)
+        };
+
+        let mut provider_metadata = serde_json::json!({
+            "provider": "openai",
+            "model": self.config.model,
+        });
+        if let Some(key) = idempotency_key {
+            provider_metadata["idempotency_key"] = key.into();
+        }
+        if let Some((lang, confidence)) = &source_hint {
+            provider_metadata["source_hint"] = serde_json::json!({
+                "language": lang.to_string(),
+                "confidence": confidence,
+            });
+        }
+        trace::annotate_metadata(&mut provider_metadata, options);
+        let provenance = Provenance {
+            provider: "openai".to_string(),
+            model: Some(identity.model.unwrap_or_else(|| self.config.model.clone())),
+            prompt_fingerprint: Some(prompt_fingerprint),
+            request_id: identity.id,
+            system_fingerprint: identity.system_fingerprint,
+            timestamp: self.config.clock.system_time(),
+            options_fingerprint: self
+                .key_inputs(target_lang, source_lang, options)
+                .fingerprint(),
+        };
+
+        let mut details = Vec::with_capacity(contents.len());
+        let mut rejected = None;
+        for content in contents {
+            let (translated, detected_source) = if detect_language {
+                parse_language_preamble(&content)
+            } else {
+                (content, hinted_source.cloned())
+            };
+            let translated = self.prompt_strategy.extract_translation(&translated)?;
+            let detail = TranslationDetail {
+                text: translated,
+                detected_source,
+                provider_metadata: provider_metadata.clone(),
+                partial,
+                suspicious: false,
+                // 原始响应只附在第一个候选上
+                raw: raw.take(),
+                passthrough: false,
+                provenance: Some(provenance.clone()),
+                result_kind: TranslationKind::Translated,
+            };
+
+            // 部分译文本来就不完整，不做语言和长度检查
+            if options.assert_target_language && !partial {
+                if let Some(detected) = detect::output_language_mismatch(&detail.text, target_lang)
+                {
+                    rejected.get_or_insert(TranslationError::WrongOutputLanguage {
+                        expected: target_lang.to_string(),
+                        detected,
+                    });
+                    continue;
+                }
+            }
+            if let (Some((min_ratio, max_ratio)), false) = (options.length_ratio_guard, partial) {
+                if let Some(ratio) = guard::length_ratio(text, &detail.text) {
+                    if !(min_ratio..=max_ratio).contains(&ratio) {
+                        rejected.get_or_insert(TranslationError::SuspiciousOutput {
+                            ratio,
+                            min_ratio,
+                            max_ratio,
+                            candidate: Box::new(detail),
+                        });
+                        continue;
+                    }
+                }
+            }
+            details.push(detail);
+        }
+        match rejected {
+            Some(error) if details.is_empty() => Err(error),
+            _ => {
+                if let Some(quota) = tenant.and_then(|t| t.quota_handle.as_ref()) {
+                    quota.record("openai", text.chars().count() as u64, &[]);
+                }
+                Ok(details)
+            }
+        }
+    }
+
+    /// 读取流式响应，返回累积的译文以及是否为中断后保留的部分结果
+    async fn read_stream(
+        &self,
+        mut response: reqwest::Response,
+        options: &EffectiveOptions,
+        identity: &mut ResponseIdentity,
+    ) -> Result<(String, bool), TranslationError> {
+        let mut content = String::new();
+        let mut buffer = Vec::new();
+        let mut received = 0;
+        loop {
+            let chunk = match options.stall_timeout {
+                Some(stall_timeout) => tokio::select! {
+                    chunk = response.chunk() => chunk,
+                    _ = self.config.clock.sleep(stall_timeout) => {
+                        return salvage_partial(content, options);
+                    }
+                },
+                None => response.chunk().await,
+            };
+            let chunk = match chunk {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => return Ok((content, false)),
+                Err(e) if error::is_timeout(&e) => return salvage_partial(content, options),
+                Err(e) => return Err(e.into()),
+            };
+
+            received += chunk.len();
+            if let Some(max) = options.max_response_bytes {
+                if received > max {
+                    return Err(TranslationError::ResponseTooLarge { max });
+                }
+            }
+            buffer.extend_from_slice(&chunk);
+
+            while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=pos).collect();
+                let line = String::from_utf8_lossy(&line);
+                let Some(data) = line.trim().strip_prefix("data:") else {
+                    continue;
+                };
+                let data = data.trim();
+                let delta = match self.config.api_shape {
+                    ApiShape::ChatCompletions => {
+                        if data == "[DONE]" {
+                            return Ok((content, false));
+                        }
+                        let event: StreamChunk = parse_stream_event(data)?;
+                        identity.fill(event.identity);
+                        event
+                            .choices
+                            .into_iter()
+                            .next()
+                            .and_then(|c| c.delta.content)
+                    }
+                    ApiShape::Responses => {
+                        let event: ResponsesStreamEvent = parse_stream_event(data)?;
+                        if let Some(response) = event.response {
+                            identity.fill(response);
+                        }
+                        match event.event_type.as_str() {
+                            "response.output_text.delta" => event.delta,
+                            "response.completed" => return Ok((content, false)),
+                            _ => None,
+                        }
+                    }
+                };
+                if let Some(delta) = delta {
+                    content.push_str(&delta);
+                }
+            }
+        }
     }
 
     /// 翻译单个文本
@@ -343,11 +2651,210 @@ impl OpenAITranslator {
         source_lang: Option<&LanguageIdentifier>,
         options: &TranslateOptions,
     ) -> Result<String, TranslationError> {
-        self.translate_text_with_retry(text, target_lang, source_lang, options)
+        self.translate_detailed(text, target_lang, source_lang, options)
             .await
+            .map(|detail| detail.text)
+    }
+
+    /// 翻译单个文本并返回详细结果
+    ///
+    /// 启用 `detect_source_language` 且未指定源语言时，结果中包含模型检测到的源语言；
+    /// 按 `on_failure` 原样返回原文时，结果标记为 [`TranslationDetail::passthrough`]
+    pub async fn translate_detailed(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<TranslationDetail, TranslationError> {
+        let options = &self.effective_options(options);
+        let result = self
+            .translate_checked(text, target_lang, source_lang, options)
+            .await;
+        options.on_failure.apply(text, result)
+    }
+
+    /// 翻译单个文本并返回最多 `n` 个不同的候选译文，供人工挑选
+    ///
+    /// Chat Completions 接口通过请求的 `n` 参数一次取得所有候选，温度提高到 0.8。
+    /// 服务以 HTTP 400 或 422 拒绝该请求、忽略 `n` 只返回一个候选，或使用 `/responses`
+    /// 接口时，改为依次发送 `n` 个单候选请求，温度从 0 逐步提高到 0.8。
+    /// 只有首尾空白不同的候选视为相同，去重后不足 `n` 个时
+    /// [`TranslationAlternatives::is_short`] 返回 true。
+    ///
+    /// 超出长度限制的文本不做拆分，直接返回错误；`on_failure` 不适用于此方法。
+    ///
+    /// # 参数
+    ///
+    /// * `text` - 需要翻译的文本
+    /// * `target_lang` - 目标语言标识符
+    /// * `source_lang` - 源语言标识符 (None表示自动检测)
+    /// * `n` - 需要的候选数，至少为 1
+    /// * `options` - 翻译配置选项
+    pub async fn translate_alternatives(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        n: usize,
+        options: &TranslateOptions,
+    ) -> Result<TranslationAlternatives, TranslationError> {
+        if n == 0 {
+            return Err(TranslationError::ConfigurationError(
+                "n must be at least 1".to_string(),
+            ));
+        }
+        let options = &self.effective_options(options);
+        let text = normalize::apply(text, options.normalize_input.as_ref());
+        let protected = options.protect(&text);
+        let text = protected.text();
+        let max_tokens = self.input_token_budget(target_lang, source_lang);
+        preflight::check_length((self.config.token_estimator)(text), Some(max_tokens))?;
+
+        let mut alternatives = TranslationAlternatives {
+            candidates: Vec::with_capacity(n),
+            requested: n,
+        };
+        // 丢失占位符的候选被丢弃，所有候选都丢失时返回第一个错误
+        let mut lost = None;
+        let mut push = |detail: TranslationDetail| match protected.restore(&detail.text) {
+            Ok(restored) => {
+                let candidates = &mut alternatives.candidates;
+                if !candidates.iter().any(|c| c.trim() == restored.trim()) {
+                    candidates.push(restored);
+                }
+            }
+            Err(e) => {
+                lost.get_or_insert(e);
+            }
+        };
+        let mut sent = 0;
+        if n > 1 && self.config.api_shape == ApiShape::ChatCompletions {
+            let sampling = Sampling {
+                temperature: ALTERNATIVES_TEMPERATURE,
+                n: n as u32,
+                ..Sampling::DEFAULT
+            };
+            match self
+                .translate_choices_with_retry(text, target_lang, source_lang, options, sampling)
+                .await
+            {
+                // 只返回一个候选说明服务忽略了 `n`，其余候选逐个请求
+                Ok(details) => {
+                    sent = if details.len() == 1 { 1 } else { n };
+                    details.into_iter().for_each(&mut push);
+                }
+                Err(e) if rejects_parameters(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        for i in sent..n {
+            let temperature = match n {
+                1 => 0.0,
+                n => ALTERNATIVES_TEMPERATURE * i as f32 / (n - 1) as f32,
+            };
+            let sampling = Sampling {
+                temperature,
+                ..Sampling::DEFAULT
+            };
+            self.translate_choices_with_retry(text, target_lang, source_lang, options, sampling)
+                .await?
+                .into_iter()
+                .for_each(&mut push);
+        }
+        match lost {
+            Some(e) if alternatives.candidates.is_empty() => Err(e),
+            _ => Ok(alternatives),
+        }
     }
 }
 
+/// 请求参数被服务拒绝（HTTP 400 或 422），用于判断兼容网关是否不支持 `n`
+fn rejects_parameters(error: &TranslationError) -> bool {
+    let status = match error {
+        TranslationError::HttpError { status, .. } => Some(*status),
+        TranslationError::Provider { http_status, .. } => *http_status,
+        _ => None,
+    };
+    status.is_some_and(|status| {
+        status == reqwest::StatusCode::BAD_REQUEST
+            || status == reqwest::StatusCode::UNPROCESSABLE_ENTITY
+    })
+}
+
+/// 重试耗尽且曾收到过长度异常的译文时，取出比例最接近允许范围的一个
+fn take_best_candidate(errors: &mut Vec<TranslationError>) -> Option<TranslationDetail> {
+    let best = errors
+        .iter()
+        .enumerate()
+        .filter_map(|(index, error)| match error {
+            TranslationError::SuspiciousOutput {
+                ratio,
+                min_ratio,
+                max_ratio,
+                ..
+            } => Some((
+                index,
+                guard::ratio_deviation(*ratio, *min_ratio, *max_ratio),
+            )),
+            _ => None,
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))?
+        .0;
+    match errors.remove(best) {
+        TranslationError::SuspiciousOutput {
+            ratio, candidate, ..
+        } => {
+            let mut detail = *candidate;
+            detail.suspicious = true;
+            if detail.provider_metadata.is_object() {
+                detail.provider_metadata["length_ratio"] = ratio.into();
+            }
+            Some(detail)
+        }
+        _ => None,
+    }
+}
+
+/// 解析流式响应中的一个事件
+fn parse_stream_event<T: serde::de::DeserializeOwned>(data: &str) -> Result<T, TranslationError> {
+    serde_json::from_str(data).map_err(|e| {
+        TranslationError::MalformedResponse(format!("error decoding stream event: {}", e))
+    })
+}
+
+/// 流式响应停顿或超时时，按 `salvage_partial` 选项保留部分译文或返回超时错误
+fn salvage_partial(
+    content: String,
+    options: &EffectiveOptions,
+) -> Result<(String, bool), TranslationError> {
+    if options.salvage_partial && !content.is_empty() {
+        Ok((content, true))
+    } else {
+        Err(TranslationError::TimeoutError)
+    }
+}
+
+/// 解析模型输出开头的 `[lang: <tag>]` 行
+///
+/// 模型不提供置信度，检测成功时置信度固定为 1.0；没有前导行时原样返回
+fn parse_language_preamble(content: &str) -> (String, Option<(LanguageIdentifier, f64)>) {
+    let trimmed = content.trim_start();
+    let Some(rest) = trimmed.strip_prefix("[lang:") else {
+        return (content.to_string(), None);
+    };
+    let Some((tag, text)) = rest.split_once(']') else {
+        return (content.to_string(), None);
+    };
+    let detected = tag
+        .trim()
+        .parse::<LanguageIdentifier>()
+        .ok()
+        .map(|lang| (lang, 1.0));
+    let text = text.trim_start_matches([' ', '\r', '\n']);
+    (text.to_string(), detected)
+}
+
 #[async_trait::async_trait]
 impl Translator for OpenAITranslator {
     async fn translate_with_options(
@@ -360,6 +2867,53 @@ impl Translator for OpenAITranslator {
         self.translate_text(text, target_lang, source_lang, options)
             .await
     }
+
+    async fn translate_batch_with_options(
+        &self,
+        texts: &[&str],
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<Vec<String>, TranslationError> {
+        self.translate_batch(texts, target_lang, source_lang, options)
+            .await
+    }
+
+    async fn translate_detailed_with_options(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<TranslationDetail, TranslationError> {
+        self.translate_detailed(text, target_lang, source_lang, options)
+            .await
+    }
+
+    fn cache_key_inputs(
+        &self,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Option<CacheKeyInputs> {
+        Some(self.key_inputs(target_lang, source_lang, &self.effective_options(options)))
+    }
+
+    fn capabilities(&self) -> TranslatorCapabilities {
+        TranslatorCapabilities {
+            max_text_len: Some(self.config.max_input_tokens.unwrap_or_else(|| {
+                self.config
+                    .context_window
+                    .unwrap_or_else(|| context_window_for_model(&self.config.model))
+                    as usize
+            })),
+            length_unit: LengthUnit::Tokens,
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,327 @@
+//! OpenAI 翻译器的提示词策略
+//!
+//! [`PromptStrategy`] 同时负责两个方向：按原文构造发送给模型的消息，以及从模型的回复中
+//! 取出译文。通过 [`crate::OpenAIConfigBuilder::prompt_strategy`] 替换后，翻译器的其余
+//! 功能保持不变：检测语言、JSON 数组和占位符的附加说明仍由翻译器追加到第一条系统消息
+//! 的末尾（没有系统消息时插入一条），回复中检测语言的前导行在提取译文之前解析。
+//!
+//! 内置两种策略：
+//!
+//! * [`DefaultPromptStrategy`]：一条系统提示词加原文，回复原样作为译文，未设置策略时使用；
+//! * [`XmlTaggedStrategy`]：原文放在 `<source>` 元素中，从回复的 `<translation>` 元素中取出译文，
+//!   模型在译文前后添加说明时也能得到干净的译文。
+//!
+//! 翻译器目前不向策略传入术语表和上下文片段，两个参数留给直接调用策略的代码使用。
+
+use super::{LOCALE_NAMES, OpenAIConfig, normalize_tag, primary_subtag};
+use crate::{error::TranslationError, glossary::Glossary};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use unic_langid::LanguageIdentifier;
+
+/// 消息的角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+}
+
+/// 发送给模型的一条消息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Message {
+    pub role: Role,
+    pub content: String,
+}
+
+impl Message {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::System,
+            content: content.into(),
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::User,
+            content: content.into(),
+        }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::Assistant,
+            content: content.into(),
+        }
+    }
+}
+
+/// 原文前后的一段上下文，例如同一文档中相邻的句子
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextSegment {
+    /// 原文
+    pub source: String,
+    /// 已有的译文，None 表示只作为参考的原文
+    pub translation: Option<String>,
+}
+
+/// 提示词策略：构造消息并从回复中取出译文
+pub trait PromptStrategy: fmt::Debug + Send + Sync {
+    /// 构造翻译 `text` 的消息
+    ///
+    /// # 参数
+    ///
+    /// * `text` - 原文，JSON 数组批量翻译时为字符串数组的 JSON
+    /// * `target_lang` - 目标语言标识符
+    /// * `source_lang` - 源语言标识符 (None表示自动检测)
+    /// * `glossary` - 需要按指定译法翻译的术语
+    /// * `context` - 原文的上下文片段
+    fn build_messages(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        glossary: Option<&Glossary>,
+        context: &[ContextSegment],
+    ) -> Vec<Message>;
+
+    /// 从模型回复（已去掉检测语言的前导行）中取出译文，默认原样返回
+    fn extract_translation(&self, content: &str) -> Result<String, TranslationError> {
+        Ok(content.to_string())
+    }
+}
+
+/// 把附加说明追加到第一条系统消息的末尾，没有系统消息时在开头插入一条
+pub(crate) fn push_instructions(messages: &mut Vec<Message>, instructions: &str) {
+    if instructions.is_empty() {
+        return;
+    }
+    match messages
+        .iter_mut()
+        .find(|message| message.role == Role::System)
+    {
+        Some(message) => message.content.push_str(instructions),
+        None => messages.insert(0, Message::system(instructions.trim_start())),
+    }
+}
+
+/// 第一条系统消息的内容，没有时为空字符串
+pub(crate) fn system_content(messages: &[Message]) -> &str {
+    messages
+        .iter()
+        .find(|message| message.role == Role::System)
+        .map_or("", |message| message.content.as_str())
+}
+
+/// 目标语言的英文名称，只用于带地区或书写系统子标签的语言
+///
+/// 依次查找 `overrides` 和内置名称表，找不到时去掉最后一个子标签再查，
+/// 例如 `sr-Latn-RS` 匹配 `sr-Latn`
+fn locale_name<'a>(overrides: &'a HashMap<String, String>, target_lang: &str) -> Option<&'a str> {
+    let mut tag = normalize_tag(target_lang);
+    while tag.contains('-') {
+        if let Some((_, name)) = overrides
+            .iter()
+            .find(|(known, _)| normalize_tag(known) == tag)
+        {
+            return Some(name);
+        }
+        if let Some((_, name)) = LOCALE_NAMES
+            .iter()
+            .find(|(known, _)| normalize_tag(known) == tag)
+        {
+            return Some(name);
+        }
+        tag.truncate(tag.rfind('-').unwrap_or_default());
+    }
+    None
+}
+
+/// 提示词中的目标语言：有英文名称时为 `名称 (标签)`
+fn describe_target(overrides: &HashMap<String, String>, target_lang: &str) -> String {
+    match locale_name(overrides, target_lang) {
+        Some(name) => format!("{} ({})", name, target_lang),
+        None => target_lang.to_string(),
+    }
+}
+
+/// 内置的提示词：一条系统提示词加原文，回复原样作为译文
+///
+/// 系统提示词依次匹配精确的语言对、（auto，目标语言）和 `system_prompt`，都没有时使用内置提示词，
+/// 与 [`OpenAIConfig`] 中对应字段的含义相同。术语表和上下文片段不写入消息
+#[derive(Debug, Clone, Default)]
+pub struct DefaultPromptStrategy {
+    system_prompt: Option<String>,
+    system_prompts: HashMap<(String, String), String>,
+    locale_names: HashMap<String, String>,
+}
+
+impl DefaultPromptStrategy {
+    /// 只使用内置提示词和内置语言名称的策略
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用配置中的 `system_prompt`、`system_prompts` 和 `locale_names`，未设置策略时翻译器即使用它
+    pub fn from_config(config: &OpenAIConfig) -> Self {
+        Self {
+            system_prompt: config.system_prompt.clone(),
+            system_prompts: config.system_prompts.clone(),
+            locale_names: config.locale_names.clone(),
+        }
+    }
+
+    /// 语言对的系统提示词
+    pub fn system_prompt(&self, target_lang: &str, source_lang: Option<&str>) -> String {
+        let source_lang_str = source_lang.unwrap_or("auto");
+        let target = primary_subtag(target_lang);
+        let prompt = self
+            .system_prompts
+            .get(&(primary_subtag(source_lang_str), target.clone()))
+            .or_else(|| self.system_prompts.get(&("auto".to_string(), target)))
+            .or(self.system_prompt.as_ref());
+        let target_name = locale_name(&self.locale_names, target_lang);
+        if let Some(prompt) = prompt {
+            return prompt
+                .replace("{source_lang}", source_lang_str)
+                .replace("{target_lang_name}", target_name.unwrap_or(target_lang))
+                .replace("{target_lang}", target_lang);
+        }
+        format!(
+            "You are a raw translation engine. You are not an AI assistant. Your only function is to translate the user's text. Translate from {} to {}. Do not, under any circumstances, write anything other than the translated text. Do not apologize. Do not explain. Do not add any extra text. If you cannot translate the text, repeat the original text.\n\nExamples:\n\nUser: Hello\nAssistant: 你好\n\nUser: World\nAssistant: 世界\n\nUser: xyzabc\nAssistant: xyzabc",
+            source_lang_str,
+            describe_target(&self.locale_names, target_lang)
+        )
+    }
+}
+
+impl PromptStrategy for DefaultPromptStrategy {
+    fn build_messages(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        _glossary: Option<&Glossary>,
+        _context: &[ContextSegment],
+    ) -> Vec<Message> {
+        let source_lang = source_lang.map(|lang| lang.to_string());
+        vec![
+            Message::system(self.system_prompt(&target_lang.to_string(), source_lang.as_deref())),
+            Message::user(text),
+        ]
+    }
+}
+
+/// 用 XML 元素分隔原文和译文的提示词
+///
+/// 用户消息中依次是可选的 `<context>`（上下文片段）、`<glossary>`（术语表）和
+/// `<source>`（原文），文本中的 `&`、`<`、`>` 按 XML 转义；回复中第一个
+/// `<translation>` 元素的内容（去掉首尾空白并反转义）作为译文，回复被截断、
+/// 缺少结束标签时取到末尾。回复中没有 `<translation>` 时返回
+/// [`TranslationError::MalformedResponse`]。流式响应的增量回调收到的是带标签的原始内容
+#[derive(Debug, Clone, Default)]
+pub struct XmlTaggedStrategy {
+    locale_names: HashMap<String, String>,
+}
+
+impl XmlTaggedStrategy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置目标语言在提示词中使用的英文名称，覆盖内置的名称表
+    pub fn locale_name(mut self, lang: impl Into<String>, name: impl Into<String>) -> Self {
+        self.locale_names.insert(lang.into(), name.into());
+        self
+    }
+}
+
+/// 转义 XML 文本中的 `&`、`<`、`>`
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// [`escape`] 的逆操作，同时接受 `&quot;` 和 `&apos;`
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+impl PromptStrategy for XmlTaggedStrategy {
+    fn build_messages(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        glossary: Option<&Glossary>,
+        context: &[ContextSegment],
+    ) -> Vec<Message> {
+        let source = match source_lang {
+            Some(lang) => format!("from {} ", lang),
+            None => String::new(),
+        };
+        let mut system = format!(
+            "You are a translation engine. Translate the text inside the <source> element {}to {}. The text is XML-escaped; keep any markup in it as text.",
+            source,
+            describe_target(&self.locale_names, &target_lang.to_string())
+        );
+        let mut user = String::new();
+        if !context.is_empty() {
+            system.push_str(" The <context> element contains neighbouring segments for reference only; do not translate them.");
+            user.push_str("<context>\n");
+            for segment in context {
+                user.push_str("<segment><source>");
+                user.push_str(&escape(&segment.source));
+                user.push_str("</source>");
+                if let Some(translation) = &segment.translation {
+                    user.push_str("<translation>");
+                    user.push_str(&escape(translation));
+                    user.push_str("</translation>");
+                }
+                user.push_str("</segment>\n");
+            }
+            user.push_str("</context>\n");
+        }
+        if let Some(glossary) = glossary.filter(|glossary| !glossary.is_empty()) {
+            system.push_str(
+                " Translate every term listed in the <glossary> element exactly as given.",
+            );
+            user.push_str("<glossary>\n");
+            for (term, translation) in glossary.entries() {
+                user.push_str("<term><source>");
+                user.push_str(&escape(term));
+                user.push_str("</source><translation>");
+                user.push_str(&escape(translation));
+                user.push_str("</translation></term>\n");
+            }
+            user.push_str("</glossary>\n");
+        }
+        system.push_str(" Reply with only the translation inside a single <translation> element, escaped the same way, and nothing else.");
+        user.push_str("<source>");
+        user.push_str(&escape(text));
+        user.push_str("</source>");
+        vec![Message::system(system), Message::user(user)]
+    }
+
+    fn extract_translation(&self, content: &str) -> Result<String, TranslationError> {
+        const OPEN: &str = "<translation>";
+        const CLOSE: &str = "</translation>";
+        let start = content.find(OPEN).ok_or_else(|| {
+            TranslationError::MalformedResponse("Response has no <translation> element".to_string())
+        })?;
+        let rest = &content[start + OPEN.len()..];
+        let inner = rest.find(CLOSE).map_or(rest, |end| &rest[..end]);
+        Ok(unescape(inner.trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::TranslationError;
+    use crate::glossary::Glossary;
+    use crate::openai::OpenAIConfig;
+    use crate::openai::prompt::{
+        ContextSegment, DefaultPromptStrategy, Message, PromptStrategy, Role, XmlTaggedStrategy,
+        push_instructions, system_content,
+    };
+    use std::collections::HashMap;
+    use unic_langid::LanguageIdentifier;
+
+    fn lang(tag: &str) -> LanguageIdentifier {
+        tag.parse().unwrap()
+    }
+
+    #[test]
+    fn test_push_instructions() {
+        let mut messages = vec![Message::user("text"), Message::system("prompt")];
+        push_instructions(&mut messages, "\n\nMore.");
+        push_instructions(&mut messages, "");
+        assert_eq!(messages[1].content, "prompt\n\nMore.");
+        assert_eq!(system_content(&messages), "prompt\n\nMore.");
+
+        // 没有系统消息时插入到开头
+        let mut messages = vec![Message::user("text")];
+        assert_eq!(system_content(&messages), "");
+        push_instructions(&mut messages, "\n\nMore.");
+        assert_eq!(messages[0], Message::system("More."));
+        assert_eq!(messages[1].role, Role::User);
+        assert_eq!(
+            serde_json::to_value(&messages[1]).unwrap(),
+            serde_json::json!({"role": "user", "content": "text"})
+        );
+    }
+
+    #[test]
+    fn test_default_strategy_matches_config() {
+        let mut system_prompts = HashMap::new();
+        system_prompts.insert(
+            ("en".to_string(), "zh".to_string()),
+            "{source_lang} -> {target_lang_name}".to_string(),
+        );
+        let config = OpenAIConfig {
+            system_prompts,
+            ..Default::default()
+        };
+        let strategy = DefaultPromptStrategy::from_config(&config);
+        let messages = strategy.build_messages(
+            "Hello",
+            &lang("zh-TW"),
+            Some(&lang("en")),
+            Some(&Glossary::new()),
+            &[],
+        );
+        assert_eq!(
+            messages,
+            vec![
+                Message::system("en -> Traditional Chinese (Taiwan)"),
+                Message::user("Hello")
+            ]
+        );
+
+        let prompt = DefaultPromptStrategy::new().system_prompt("pt-BR", None);
+        assert!(prompt.contains("Translate from auto to Brazilian Portuguese (pt-BR)."));
+        assert_eq!(
+            DefaultPromptStrategy::new()
+                .extract_translation("  你好 ")
+                .unwrap(),
+            "  你好 "
+        );
+    }
+
+    #[test]
+    fn test_xml_tagged_messages() {
+        let strategy = XmlTaggedStrategy::new();
+        let messages = strategy.build_messages("a < b & c", &lang("zh"), None, None, &[]);
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].content.contains("to zh."));
+        assert!(!messages[0].content.contains("<glossary>"));
+        assert_eq!(messages[1].content, "<source>a &lt; b &amp; c</source>");
+
+        let glossary = Glossary::new().term("Rust", "Rust");
+        let context = [ContextSegment {
+            source: "Intro".to_string(),
+            translation: Some("介绍".to_string()),
+        }];
+        let messages = XmlTaggedStrategy::new()
+            .locale_name("zh-Hant", "Chinese (Hant)")
+            .build_messages(
+                "Rust",
+                &lang("zh-Hant"),
+                Some(&lang("en")),
+                Some(&glossary),
+                &context,
+            );
+        assert!(
+            messages[0]
+                .content
+                .contains("from en to Chinese (Hant) (zh-Hant).")
+        );
+        assert!(messages[0].content.contains("<glossary>"));
+        assert_eq!(
+            messages[1].content,
+            "<context>\n\
+             <segment><source>Intro</source><translation>介绍</translation></segment>\n\
+             </context>\n\
+             <glossary>\n\
+             <term><source>Rust</source><translation>Rust</translation></term>\n\
+             </glossary>\n\
+             <source>Rust</source>"
+        );
+    }
+
+    #[test]
+    fn test_xml_tagged_extraction() {
+        let strategy = XmlTaggedStrategy::new();
+        for (content, expected) in [
+            ("<translation>你好</translation>", "你好"),
+            (
+                "Here you go:\n<translation>\n鱼 &amp; &lt;薯条&gt;\n</translation>\nEnjoy!",
+                "鱼 & <薯条>",
+            ),
+            // 被截断的回复取到末尾
+            ("<translation>半句", "半句"),
+            ("<translation>&amp;lt;</translation>", "&lt;"),
+        ] {
+            assert_eq!(strategy.extract_translation(content).unwrap(), expected);
+        }
+        assert!(matches!(
+            strategy.extract_translation("你好"),
+            Err(TranslationError::MalformedResponse(_))
+        ));
+    }
+}
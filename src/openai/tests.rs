@@ -1,6 +1,48 @@
 #[cfg(test)]
 mod tests {
-    use crate::openai::{OpenAIConfig, OpenAITranslator};
+    use crate::batch::StartJitter;
+    use crate::clock::Clock;
+    use crate::error::{NetworkErrorKind, TranslationError};
+    use crate::http::CONSTRUCTED_CLIENTS;
+    use crate::metrics::MetricsRecorder;
+    use crate::normalize::Normalization;
+    use crate::openai::{
+        ApiShape, DETECT_LANGUAGE_INSTRUCTION, KeyStrategy, OpenAIConfig, OpenAITranslator,
+        OverflowPolicy, RateLimitState, RoundRobinSelector, context_window_for_model,
+        estimate_tokens, key_selector,
+        keys::{ApiKeyConfig, KeyProvider, MIN_KEY_REFRESH_INTERVAL},
+        normalize_base_url, parse_error_body, parse_language_preamble, parse_reset,
+        prompt::XmlTaggedStrategy,
+    };
+    use crate::options::{PoolOptions, ProxyMode, TraceContext, TranslateOptions};
+    use crate::quota::Quota;
+    use crate::stats::WindowPeriod;
+    use crate::tenant::{SharedRateLimiter, TenantContext};
+    use crate::testing::{DelayedResponse, DelayedServer, MockClock};
+    use crate::trace::RequestTrace;
+    use crate::translator::Translator;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use unic_langid::LanguageIdentifier;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+    /// 将用户消息加上 "T:" 前缀原样返回的模拟响应
+    struct EchoResponder;
+
+    impl Respond for EchoResponder {
+        fn respond(&self, request: &Request) -> ResponseTemplate {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            let content = body["messages"][1]["content"].as_str().unwrap();
+            ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"message": {"role": "assistant", "content": format!("T:{}", content)}}]
+            }))
+        }
+    }
 
     #[tokio::test]
     async fn test_openai_config_default() {
@@ -9,8 +51,10 @@ mod tests {
         assert_eq!(config.model, "gpt-3.5-turbo");
         assert_eq!(config.api_keys.len(), 0);
         assert_eq!(config.rpm_limit, 60);
+        assert_eq!(config.burst, None);
         assert_eq!(config.concurrent_limit, 10);
         assert_eq!(config.system_prompt, None);
+        assert_eq!(config.max_input_tokens, None);
     }
 
     #[tokio::test]
@@ -18,15 +62,15 @@ mod tests {
         let config = OpenAIConfig {
             base_url: "https://api.openai.com/v1".to_string(),
             model: "gpt-3.5-turbo".to_string(),
-            api_keys: vec!["test-key".to_string()],
+            api_keys: vec!["test-key".into()],
             rpm_limit: 60,
             concurrent_limit: 10,
             system_prompt: None,
+            ..Default::default()
         };
 
         let _translator = OpenAITranslator::new(config);
         // 这里我们只测试创建是否成功，不测试实际的API调用
-        assert!(true); // 如果创建成功，这行代码会执行
     }
 
     #[tokio::test]
@@ -35,18 +79,18 @@ mod tests {
             base_url: "https://api.openai.com/v1".to_string(),
             model: "gpt-3.5-turbo".to_string(),
             api_keys: vec![
-                "test-key-1".to_string(),
-                "test-key-2".to_string(),
-                "test-key-3".to_string(),
+                "test-key-1".into(),
+                "test-key-2".into(),
+                "test-key-3".into(),
             ],
             rpm_limit: 60,
             concurrent_limit: 10,
             system_prompt: None,
+            ..Default::default()
         };
 
         let _translator = OpenAITranslator::new(config);
         // 测试创建是否成功
-        assert!(true);
     }
 
     #[tokio::test]
@@ -54,15 +98,15 @@ mod tests {
         let config = OpenAIConfig {
             base_url: "https://api.openai.com/v1".to_string(),
             model: "gpt-3.5-turbo".to_string(),
-            api_keys: vec!["test-key".to_string()],
+            api_keys: vec!["test-key".into()],
             rpm_limit: 0, // 不限制RPM
             concurrent_limit: 10,
             system_prompt: None,
+            ..Default::default()
         };
 
         let _translator = OpenAITranslator::new(config);
         // 测试创建是否成功
-        assert!(true);
     }
 
     #[tokio::test]
@@ -71,15 +115,19 @@ mod tests {
         let config = OpenAIConfig {
             base_url: "https://api.openai.com/v1".to_string(),
             model: "gpt-3.5-turbo".to_string(),
-            api_keys: vec!["test-key".to_string()],
+            api_keys: vec!["test-key".into()],
             rpm_limit: 60,
             concurrent_limit: 10,
             system_prompt: Some(custom_prompt.clone()),
+            ..Default::default()
         };
 
         let translator = OpenAITranslator::new(config);
         let generated_prompt = translator.get_system_prompt("zh", None);
-        assert_eq!(generated_prompt, custom_prompt);
+        assert_eq!(
+            generated_prompt,
+            "You are a professional translator. Please translate the following text to high-quality zh.."
+        );
     }
 
     #[tokio::test]
@@ -94,4 +142,4705 @@ mod tests {
         let prompt_no_source = translator.get_system_prompt("fr", None);
         assert!(prompt_no_source.contains("Translate from auto to fr"));
     }
+
+    #[test]
+    fn test_default_prompt_names_locale_variants() {
+        let translator = OpenAITranslator::new(OpenAIConfig::default());
+        let cases = [
+            ("pt-BR", "to Brazilian Portuguese (pt-BR)."),
+            ("pt-PT", "to European Portuguese (pt-PT)."),
+            ("zh-Hant", "to Traditional Chinese (zh-Hant)."),
+            (
+                "zh-Hant-TW",
+                "to Traditional Chinese (Taiwan) (zh-Hant-TW).",
+            ),
+            ("zh-TW", "to Traditional Chinese (Taiwan) (zh-TW)."),
+            ("en-GB", "to British English (en-GB)."),
+            ("es-419", "to Latin American Spanish (es-419)."),
+            // 没有单独名称时去掉最后一个子标签再查
+            ("sr-Latn-RS", "to Serbian (Latin script) (sr-Latn-RS)."),
+            // 不带子标签或不在表中的语言使用原始标签
+            ("pt", "to pt."),
+            ("ja-JP", "to ja-JP."),
+        ];
+        for (tag, expected) in cases {
+            let lang: LanguageIdentifier = tag.parse().unwrap();
+            let prompt = translator.get_system_prompt(&lang.to_string(), Some("en"));
+            assert!(
+                prompt.contains(&format!("Translate from en {}", expected)),
+                "{}: {}",
+                tag,
+                prompt
+            );
+        }
+    }
+
+    #[test]
+    fn test_locale_names_override_and_custom_prompt() {
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .locale_name("pt_br", "Portuguese as spoken in Brazil")
+                .locale_name("ja-JP", "Japanese")
+                .system_prompt("Translate into {target_lang_name} [{target_lang}].")
+                .build(),
+        );
+        assert_eq!(
+            translator.get_system_prompt("pt-BR", None),
+            "Translate into Portuguese as spoken in Brazil [pt-BR]."
+        );
+        assert_eq!(
+            translator.get_system_prompt("ja-JP", None),
+            "Translate into Japanese [ja-JP]."
+        );
+        assert_eq!(
+            translator.get_system_prompt("zh-Hant", None),
+            "Translate into Traditional Chinese [zh-Hant]."
+        );
+        assert_eq!(
+            translator.get_system_prompt("fr", None),
+            "Translate into fr [fr]."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_id_and_trace_headers_sent() {
+        let server = MockServer::start().await;
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("X-Request-Id", "req-123"))
+            .and(header("traceparent", traceparent))
+            .and(header("tracestate", "vendor=value"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"message": {"role": "assistant", "content": "你好"}}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let options = TranslateOptions::default()
+            .request_id("req-123")
+            .trace_context(TraceContext {
+                traceparent: traceparent.to_string(),
+                tracestate: Some("vendor=value".to_string()),
+            });
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let result = translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(result, "你好");
+    }
+
+    #[tokio::test]
+    async fn test_request_id_and_trace_in_errors() {
+        let server = MockServer::start().await;
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("X-Request-Id", "req-400"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "error": {"message": "Bad request", "type": "invalid_request_error", "code": "bad"}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("X-Request-Id", "req-502"))
+            .respond_with(ResponseTemplate::new(502).set_body_string("Bad Gateway"))
+            .mount(&server)
+            .await;
+
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = |request_id: &str| {
+            TranslateOptions::default()
+                .request_id(request_id)
+                .trace_context(TraceContext {
+                    traceparent: traceparent.to_string(),
+                    tracestate: None,
+                })
+                .no_retries()
+        };
+        let expected = |request_id: &str| {
+            Some(Box::new(RequestTrace {
+                request_id: Some(request_id.to_string()),
+                traceparent: Some(traceparent.to_string()),
+            }))
+        };
+
+        let error = translator
+            .translate_text("Hello", &target_lang, None, &options("req-400"))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&error, TranslationError::Provider { trace, .. } if *trace == expected("req-400")),
+            "{:?}",
+            error
+        );
+        assert!(
+            error.to_string().ends_with(&format!(
+                "[request_id=req-400, traceparent={}]",
+                traceparent
+            )),
+            "{}",
+            error
+        );
+
+        // 可重试的错误在重试记录中同样带有请求ID
+        match translator
+            .translate_text("Hello", &target_lang, None, &options("req-502"))
+            .await
+        {
+            Err(TranslationError::MaxRetriesExceeded { errors, .. }) => match &errors[..] {
+                [TranslationError::HttpError { status, trace, .. }] => {
+                    assert_eq!(*status, StatusCode::BAD_GATEWAY);
+                    assert_eq!(*trace, expected("req-502"));
+                }
+                other => panic!("Expected HttpError, got {:?}", other),
+            },
+            other => panic!("Expected MaxRetriesExceeded, got {:?}", other),
+        }
+
+        // 没有设置请求ID和链路追踪上下文时为 None
+        match translator
+            .translate_text(
+                "Hello",
+                &target_lang,
+                None,
+                &TranslateOptions::default().request_id("req-400"),
+            )
+            .await
+        {
+            Err(TranslationError::Provider { trace, .. }) => assert_eq!(
+                trace,
+                Some(Box::new(RequestTrace {
+                    request_id: Some("req-400".to_string()),
+                    traceparent: None,
+                }))
+            ),
+            other => panic!("Expected Provider error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abc"), 1);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        assert_eq!(estimate_tokens("你好世界"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_preflight_token_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .max_input_tokens(Some(10))
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+
+        // 36 个字符约 9 个 token，刚好低于限制
+        let under = "a".repeat(36);
+        assert!(
+            translator
+                .translate_text(&under, &target_lang, None, &options)
+                .await
+                .is_ok()
+        );
+
+        // 40 个字符约 10 个 token，恰好等于限制
+        let at = "a".repeat(40);
+        assert!(
+            translator
+                .translate_text(&at, &target_lang, None, &options)
+                .await
+                .is_ok()
+        );
+
+        // 41 个字符约 11 个 token，超出限制，不会发送请求
+        let over = "a".repeat(41);
+        match translator
+            .translate_text(&over, &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::TextTooLong { length, max }) => {
+                assert_eq!(length, 11);
+                assert_eq!(max, 10);
+            }
+            other => panic!("Expected TextTooLong, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preflight_custom_estimator() {
+        let config = OpenAIConfig::builder()
+            .api_keys(vec!["test-key"])
+            .max_input_tokens(Some(3))
+            .token_estimator(|text| text.split_whitespace().count())
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let result = translator
+            .translate_text(
+                "one two three four",
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(TranslationError::TextTooLong { length: 4, max: 3 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_max_request_bytes() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(completion_body("你好")))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .max_request_bytes(2_000)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        assert_eq!(
+            translator
+                .translate_text("Hello", &target_lang, None, &options)
+                .await
+                .unwrap(),
+            "你好"
+        );
+
+        // 超出限制时不发送请求；请求体中的原文经过 JSON 转义
+        let result = translator
+            .translate_text(&"\n".repeat(1_000), &target_lang, None, &options)
+            .await;
+        match result {
+            Err(TranslationError::TextTooLong { length, max }) => {
+                assert!(length > 2_000);
+                assert_eq!(max, 2_000);
+            }
+            other => panic!("Expected TextTooLong, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_payload_too_large_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(413).set_body_string("Payload Too Large"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let error = translator
+            .translate_text(
+                "Hello",
+                &target_lang,
+                None,
+                &TranslateOptions::default().max_retries(2),
+            )
+            .await
+            .unwrap_err();
+        let TranslationError::RequestTooLarge { bytes } = error else {
+            panic!("Expected RequestTooLarge, got {:?}", error);
+        };
+        let request = &server.received_requests().await.unwrap()[0];
+        assert_eq!(bytes, request.body.len());
+    }
+
+    #[tokio::test]
+    async fn test_unresolvable_host_fails_fast() {
+        let clock = MockClock::new().auto_advance(true);
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url("http://translate.invalid/v1")
+                .api_keys(vec!["test-key"])
+                .dns_failure_ttl(Duration::from_secs(60))
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        // 记录每次重试前的错误是否来自真正的 DNS 解析
+        let lookups = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = lookups.clone();
+        let options = TranslateOptions::default()
+            .max_retries(3)
+            .on_retry(Arc::new(move |event: crate::RetryEvent<'_>| {
+                recorded.lock().unwrap().push(matches!(
+                    event.error,
+                    TranslationError::NetworkError {
+                        kind: NetworkErrorKind::Dns,
+                        source: Some(_),
+                        ..
+                    }
+                ));
+            }));
+
+        // 第一次解析失败可以重试，重试时主机名已在负缓存中，直接失败且不再重试
+        match translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+        {
+            Err(
+                ref error @ TranslationError::NetworkError {
+                    kind: NetworkErrorKind::Dns,
+                    ref host,
+                    source: None,
+                    cooldown: None,
+                },
+            ) => {
+                assert_eq!(host.as_deref(), Some("translate.invalid"));
+                assert!(!error.is_retryable());
+            }
+            other => panic!("Expected cached DNS failure, got {:?}", other),
+        }
+        assert_eq!(*lookups.lock().unwrap(), [true]);
+
+        // 缓存期内的请求不再解析
+        let error = translator
+            .translate_text("World", &target_lang, None, &options)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("translate.invalid"));
+        assert_eq!(lookups.lock().unwrap().len(), 1);
+
+        // 过期后重新解析一次
+        clock.advance(Duration::from_secs(60));
+        translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+            .unwrap_err();
+        assert_eq!(*lookups.lock().unwrap(), [true, true]);
+    }
+
+    #[tokio::test]
+    async fn test_refused_connection_is_retried() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(format!("http://127.0.0.1:{}/v1", port))
+                .api_keys(vec!["test-key"])
+                .clock(Arc::new(MockClock::new().auto_advance(true)))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().max_retries(2);
+        for _ in 0..2 {
+            match translator
+                .translate_text("Hello", &target_lang, None, &options)
+                .await
+            {
+                Err(TranslationError::MaxRetriesExceeded {
+                    attempts: 3,
+                    errors,
+                }) => assert!(errors.iter().all(|error| matches!(
+                    error,
+                    TranslationError::NetworkError {
+                        kind: NetworkErrorKind::Connect,
+                        source: Some(_),
+                        ..
+                    }
+                ))),
+                other => panic!("Expected MaxRetriesExceeded, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preflight_auto_split() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .max_input_tokens(Some(5))
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().auto_split(true);
+
+        let result = translator
+            .translate_text("First sentence. Second one.", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(result, "T:First sentence. T:Second one.");
+    }
+
+    #[test]
+    fn test_parse_language_preamble() {
+        let (text, detected) = parse_language_preamble("[lang: en]\n你好");
+        assert_eq!(text, "你好");
+        assert_eq!(detected, Some(("en".parse().unwrap(), 1.0)));
+
+        let (text, detected) = parse_language_preamble("[lang: zh-Hant]\r\n\r\nHello");
+        assert_eq!(text, "Hello");
+        assert_eq!(detected, Some(("zh-Hant".parse().unwrap(), 1.0)));
+
+        // 没有前导行时原样返回
+        let (text, detected) = parse_language_preamble("[not a preamble] text");
+        assert_eq!(text, "[not a preamble] text");
+        assert_eq!(detected, None);
+    }
+
+    #[tokio::test]
+    async fn test_translate_detailed_detects_source_language() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"message": {"role": "assistant", "content": "[lang: en]\n你好"}}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .detect_source_language(true)
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = translator
+            .translate_detailed_with_options(
+                "Hello",
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "你好");
+        assert_eq!(detail.detected_source, Some(("en".parse().unwrap(), 1.0)));
+        assert_eq!(detail.provider_metadata["provider"], "openai");
+        assert_eq!(detail.provider_metadata["model"], "gpt-3.5-turbo");
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        let system_prompt = body["messages"][0]["content"].as_str().unwrap();
+        assert!(system_prompt.contains("[lang: <tag>]"));
+    }
+
+    #[tokio::test]
+    async fn test_translate_detailed_without_detection_flag() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"message": {"role": "assistant", "content": "你好"}}]
+            })))
+            .mount(&server)
+            .await;
+
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "你好");
+        assert_eq!(detail.detected_source, None);
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        let system_prompt = body["messages"][0]["content"].as_str().unwrap();
+        assert!(!system_prompt.contains("[lang: <tag>]"));
+    }
+
+    #[tokio::test]
+    async fn test_xml_tagged_prompt_strategy() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"message": {
+                    "role": "assistant",
+                    "content": "[lang: en]\nSure! <translation>\n鱼 &amp; 薯条\n</translation>"
+                }}]
+            })))
+            .mount(&server)
+            .await;
+
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .detect_source_language(true)
+            .prompt_strategy(Arc::new(XmlTaggedStrategy::new()))
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = translator
+            .translate_detailed(
+                "Fish & <b>chips</b>",
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "鱼 & 薯条");
+        assert_eq!(detail.detected_source, Some(("en".parse().unwrap(), 1.0)));
+
+        // 检测语言的说明追加在策略的系统消息之后
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        let system_prompt = body["messages"][0]["content"].as_str().unwrap();
+        assert!(system_prompt.contains("<translation>"));
+        assert!(system_prompt.ends_with(DETECT_LANGUAGE_INSTRUCTION));
+        assert_eq!(
+            body["messages"][1]["content"],
+            "<source>Fish &amp; &lt;b&gt;chips&lt;/b&gt;</source>"
+        );
+
+        // 回复中没有译文元素时报告格式错误
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(completion_body("鱼和薯条")))
+            .mount(&server)
+            .await;
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .prompt_strategy(Arc::new(XmlTaggedStrategy::new()))
+            .build();
+        let result = OpenAITranslator::new(config)
+            .translate_detailed("Fish", &target_lang, None, &TranslateOptions::default())
+            .await;
+        assert!(matches!(
+            result,
+            Err(TranslationError::MalformedResponse(_))
+        ));
+    }
+
+    fn completion_body(content: &str) -> String {
+        json!({
+            "choices": [{"message": {"role": "assistant", "content": content}}]
+        })
+        .to_string()
+    }
+
+    async fn translate_with_server(
+        server: &DelayedServer,
+        options: &TranslateOptions,
+    ) -> Result<String, TranslationError> {
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        translator
+            .translate_text("Hello", &target_lang, None, options)
+            .await
+    }
+
+    /// 记录排队等待时长和深度的记录器
+    #[derive(Debug, Default)]
+    struct QueueEvents {
+        waits: std::sync::Mutex<Vec<Duration>>,
+        depths: std::sync::Mutex<Vec<usize>>,
+    }
+
+    impl MetricsRecorder for QueueEvents {
+        fn record_queue_wait(&self, provider: &str, wait: Duration) {
+            assert_eq!(provider, "openai");
+            self.waits.lock().unwrap().push(wait);
+        }
+
+        fn record_queue_depth(&self, _provider: &str, depth: usize) {
+            self.depths.lock().unwrap().push(depth);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_queue_stats_with_saturated_server() {
+        let server = DelayedServer::start(
+            DelayedResponse::new(completion_body("你好")).header_delay(Duration::from_millis(100)),
+        )
+        .await;
+        let events = Arc::new(QueueEvents::default());
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["key-a", "key-b"])
+                .concurrent_limit(1)
+                .metrics_recorder(events.clone())
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        let results = futures::future::join_all(
+            (0..6).map(|_| translator.translate_text("Hello", &target_lang, None, &options)),
+        )
+        .await;
+        assert!(results.iter().all(|result| result.is_ok()));
+
+        // 两个 Key 各一个许可，每个 Key 上的请求依次排队
+        let stats = translator.queue_stats();
+        assert_eq!(stats.requests, 6);
+        assert_eq!(stats.current_depth, 0);
+        assert!(stats.max_wait >= Duration::from_millis(150), "{:?}", stats);
+        assert!(stats.average_wait > Duration::ZERO);
+        assert!((2..=4).contains(&stats.max_depth), "{:?}", stats);
+        assert!(stats.average_depth > 0.0);
+
+        let waits = events.waits.lock().unwrap();
+        assert_eq!(waits.len(), 6);
+        assert_eq!(waits.iter().max(), Some(&stats.max_wait));
+        let depths = events.depths.lock().unwrap();
+        assert_eq!(depths.len(), 8);
+        assert_eq!(depths.last(), Some(&0));
+        assert_eq!(depths.iter().max(), Some(&stats.max_depth));
+    }
+
+    #[tokio::test]
+    async fn test_close_finishes_in_flight_requests() {
+        let server = DelayedServer::start(
+            DelayedResponse::new(completion_body("你好")).header_delay(Duration::from_millis(300)),
+        )
+        .await;
+        let translator = Arc::new(OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .concurrent_limit(1)
+                .build(),
+        ));
+        let translate = |translator: Arc<OpenAITranslator>| async move {
+            let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+            translator
+                .translate_text("Hello", &target_lang, None, &TranslateOptions::default())
+                .await
+        };
+        let in_flight = tokio::spawn(translate(translator.clone()));
+        while server.requests() == 0 {
+            tokio::task::yield_now().await;
+        }
+        let queued = tokio::spawn(translate(translator.clone()));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        translator.close().await;
+        assert_eq!(in_flight.await.unwrap().unwrap(), "你好");
+        assert!(matches!(
+            queued.await.unwrap(),
+            Err(TranslationError::TranslatorClosed)
+        ));
+        assert!(matches!(
+            translate(translator.clone()).await,
+            Err(TranslationError::TranslatorClosed)
+        ));
+        assert_eq!(server.requests(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_header_timeout() {
+        let server = DelayedServer::start(
+            DelayedResponse::new(completion_body("你好")).header_delay(Duration::from_secs(2)),
+        )
+        .await;
+        let options = TranslateOptions::default()
+            .timeout(Duration::from_millis(200))
+            .no_retries();
+
+        match translate_with_server(&server, &options).await {
+            Err(TranslationError::MaxRetriesExceeded { errors, .. }) => {
+                assert!(matches!(errors[..], [TranslationError::TimeoutError]));
+            }
+            other => panic!("Expected MaxRetriesExceeded, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_body_timeout_is_retryable_timeout() {
+        let server = DelayedServer::start(
+            DelayedResponse::new(completion_body("你好")).body_delay(Duration::from_secs(2)),
+        )
+        .await;
+        let options = TranslateOptions::default()
+            .timeout(Duration::from_millis(200))
+            .max_retries(1);
+
+        match translate_with_server(&server, &options).await {
+            Err(TranslationError::MaxRetriesExceeded { attempts, errors }) => {
+                assert_eq!(attempts, 2);
+                assert!(
+                    errors
+                        .iter()
+                        .all(|e| matches!(e, TranslationError::TimeoutError))
+                );
+            }
+            other => panic!("Expected MaxRetriesExceeded, got {:?}", other),
+        }
+        assert_eq!(server.requests(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_error_body_timeout() {
+        let server = DelayedServer::start(
+            DelayedResponse::new("overloaded")
+                .status(503)
+                .body_delay(Duration::from_secs(2)),
+        )
+        .await;
+        let options = TranslateOptions::default()
+            .timeout(Duration::from_millis(200))
+            .no_retries();
+
+        match translate_with_server(&server, &options).await {
+            Err(TranslationError::MaxRetriesExceeded { errors, .. }) => {
+                assert!(matches!(errors[..], [TranslationError::TimeoutError]));
+            }
+            other => panic!("Expected MaxRetriesExceeded, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_body_within_timeout() {
+        let server = DelayedServer::start(
+            DelayedResponse::new(completion_body("你好"))
+                .header_delay(Duration::from_millis(50))
+                .body_delay(Duration::from_millis(50)),
+        )
+        .await;
+        let options = TranslateOptions::default().timeout(Duration::from_secs(5));
+
+        assert_eq!(
+            translate_with_server(&server, &options).await.unwrap(),
+            "你好"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_response_not_retried() {
+        let server = DelayedServer::start(DelayedResponse::new("{\"choices\": ")).await;
+        let options = TranslateOptions::default().max_retries(2);
+
+        match translate_with_server(&server, &options).await {
+            Err(TranslationError::MalformedResponse(_)) => {}
+            other => panic!("Expected MalformedResponse, got {:?}", other),
+        }
+        assert_eq!(server.requests(), 1);
+    }
+
+    fn rate_limited_translator(rpm_limit: u32, clock: &MockClock) -> OpenAITranslator {
+        OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .api_keys(vec!["test-key"])
+                .rpm_limit(rpm_limit)
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_rpm_limit_waits_for_window() {
+        let clock = MockClock::new();
+        let translator = rate_limited_translator(2, &clock);
+        let key_trackers = translator.key_trackers();
+        let tracker = &key_trackers[0];
+
+        translator.wait_for_rate_limit(tracker, None).await.unwrap();
+        clock.advance(Duration::from_secs(10));
+        translator.wait_for_rate_limit(tracker, None).await.unwrap();
+        assert!(clock.sleeps().is_empty());
+
+        // 第三个请求需要等待第一个请求移出 60 秒窗口
+        let third = async { translator.wait_for_rate_limit(tracker, None).await.unwrap() };
+        let driver = async {
+            clock.wait_for_sleepers(1).await;
+            assert_eq!(clock.sleeps(), vec![Duration::from_secs(50)]);
+            clock.advance(Duration::from_secs(49));
+            tokio::task::yield_now().await;
+            assert_eq!(clock.sleepers(), 1);
+            clock.advance(Duration::from_secs(1));
+        };
+        tokio::join!(third, driver);
+        assert_eq!(clock.sleepers(), 0);
+        assert_eq!(clock.elapsed(), Duration::from_secs(60));
+
+        let times = tracker.rate_limiter.request_times().await;
+        assert_eq!(times.len(), 2);
+        assert_eq!(*times.last().unwrap(), clock.now());
+    }
+
+    #[tokio::test]
+    async fn test_rpm_limit_boundary_exactly_at_window() {
+        let clock = MockClock::new().auto_advance(true);
+        let translator = rate_limited_translator(1, &clock);
+        let key_trackers = translator.key_trackers();
+        let tracker = &key_trackers[0];
+
+        translator.wait_for_rate_limit(tracker, None).await.unwrap();
+        // 恰好 60 秒后，旧请求已不在窗口内
+        clock.advance(Duration::from_secs(60));
+        translator.wait_for_rate_limit(tracker, None).await.unwrap();
+        assert!(clock.sleeps().is_empty());
+
+        // 紧接着的请求需要等待完整的窗口
+        translator.wait_for_rate_limit(tracker, None).await.unwrap();
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(60)]);
+    }
+
+    #[tokio::test]
+    async fn test_rpm_limit_after_clock_jump() {
+        let clock = MockClock::new().auto_advance(true);
+        let translator = rate_limited_translator(3, &clock);
+        let key_trackers = translator.key_trackers();
+        let tracker = &key_trackers[0];
+
+        for _ in 0..3 {
+            translator.wait_for_rate_limit(tracker, None).await.unwrap();
+        }
+        // 时钟跳过很长一段时间后，窗口内的记录全部过期
+        clock.advance(Duration::from_secs(3600));
+        translator.wait_for_rate_limit(tracker, None).await.unwrap();
+        assert!(clock.sleeps().is_empty());
+        assert_eq!(tracker.rate_limiter.request_times().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_burst_paces_requests() {
+        let clock = MockClock::new().auto_advance(true);
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .api_keys(vec!["test-key"])
+                .rpm_limit(30)
+                .burst(1)
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        let key_trackers = translator.key_trackers();
+        let tracker = &key_trackers[0];
+        for _ in 0..3 {
+            translator.wait_for_rate_limit(tracker, None).await.unwrap();
+        }
+        // 每 2 秒发送一个，而不是先发送 30 个再停顿
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(2); 2]);
+
+        let config = OpenAIConfig::builder()
+            .api_keys(vec!["test-key"])
+            .burst(0)
+            .build();
+        assert!(matches!(
+            config.validate(),
+            Err(TranslationError::ConfigurationError(msg)) if msg.contains("burst")
+        ));
+    }
+
+    #[test]
+    fn test_parse_reset() {
+        let cases = [
+            ("1s", Some(Duration::from_secs(1))),
+            ("6m0s", Some(Duration::from_secs(360))),
+            ("1h2m3s", Some(Duration::from_secs(3723))),
+            ("1.5s", Some(Duration::from_millis(1500))),
+            ("20ms", Some(Duration::from_millis(20))),
+            ("250us", Some(Duration::from_micros(250))),
+            (" 12 ", Some(Duration::from_secs(12))),
+            ("0.5", Some(Duration::from_millis(500))),
+            ("", None),
+            ("soon", None),
+            ("10x", None),
+            ("s", None),
+            ("-1s", None),
+            ("-1", None),
+            ("NaN", None),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(parse_reset(value), expected, "{:?}", value);
+        }
+    }
+
+    /// 依次返回带有指定 `x-ratelimit-*` 响应头的成功响应，用完后重复最后一个
+    struct RateLimitHeaders {
+        responses: Vec<Vec<(&'static str, &'static str)>>,
+        served: std::sync::atomic::AtomicUsize,
+    }
+
+    impl RateLimitHeaders {
+        fn new(responses: Vec<Vec<(&'static str, &'static str)>>) -> Self {
+            Self {
+                responses,
+                served: Default::default(),
+            }
+        }
+    }
+
+    impl Respond for RateLimitHeaders {
+        fn respond(&self, _request: &Request) -> ResponseTemplate {
+            let index = self
+                .served
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                .min(self.responses.len() - 1);
+            self.responses[index].iter().fold(
+                ResponseTemplate::new(200).set_body_string(completion_body("你好")),
+                |response, (name, value)| response.insert_header(*name, *value),
+            )
+        }
+    }
+
+    async fn rate_limit_server(responses: Vec<Vec<(&'static str, &'static str)>>) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(RateLimitHeaders::new(responses))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn server_limited_translator(
+        server: &MockServer,
+        clock: &MockClock,
+        honor: bool,
+    ) -> OpenAITranslator {
+        OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(1)
+                .honor_server_rate_limits(honor)
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_state_from_headers() {
+        let server = rate_limit_server(vec![
+            vec![
+                ("x-ratelimit-remaining-requests", "59"),
+                ("x-ratelimit-remaining-tokens", "149000"),
+                ("x-ratelimit-reset-requests", "1s"),
+                ("x-ratelimit-reset-tokens", "6m0s"),
+            ],
+            // 无法解析的值保留之前的状态
+            vec![
+                ("x-ratelimit-remaining-requests", "many"),
+                ("x-ratelimit-remaining-tokens", "148000"),
+                ("x-ratelimit-reset-requests", "later"),
+            ],
+        ])
+        .await;
+        let clock = MockClock::new();
+        let translator = server_limited_translator(&server, &clock, false);
+        assert_eq!(
+            translator.rate_limit_state(),
+            vec![RateLimitState::default()]
+        );
+
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        let start = clock.now();
+        translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        let expected = RateLimitState {
+            remaining_requests: Some(59),
+            remaining_tokens: Some(149000),
+            reset_requests_at: Some(start + Duration::from_secs(1)),
+            reset_tokens_at: Some(start + Duration::from_secs(360)),
+        };
+        assert_eq!(translator.rate_limit_state(), vec![expected]);
+
+        clock.advance(Duration::from_secs(60));
+        translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            translator.rate_limit_state(),
+            vec![RateLimitState {
+                remaining_tokens: Some(148000),
+                ..expected
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_honor_server_rate_limits() {
+        let responses = vec![
+            vec![
+                ("x-ratelimit-remaining-requests", "1"),
+                ("x-ratelimit-reset-requests", "60s"),
+            ],
+            vec![
+                ("x-ratelimit-remaining-requests", "0"),
+                ("x-ratelimit-reset-requests", "20s"),
+            ],
+        ];
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+
+        let server = rate_limit_server(responses.clone()).await;
+        let clock = MockClock::new().auto_advance(true);
+        let translator = server_limited_translator(&server, &clock, true);
+        for _ in 0..2 {
+            translator
+                .translate_text("Hello", &target_lang, None, &options)
+                .await
+                .unwrap();
+        }
+        // 服务端还有剩余额度，第二个请求不按本地的 RPM 窗口等待
+        assert!(clock.sleeps().is_empty());
+
+        // 额度用完后等待到服务端报告的恢复时间，之后没有新的额度信息，回到本地限速
+        translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            clock.sleeps(),
+            vec![Duration::from_secs(20), Duration::from_secs(40)]
+        );
+
+        // 未启用时只按本地的 RPM 窗口等待
+        let server = rate_limit_server(responses).await;
+        let clock = MockClock::new().auto_advance(true);
+        let translator = server_limited_translator(&server, &clock, false);
+        for _ in 0..2 {
+            translator
+                .translate_text("Hello", &target_lang, None, &options)
+                .await
+                .unwrap();
+        }
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(60)]);
+    }
+
+    #[tokio::test]
+    async fn test_deadline_fails_before_rpm_wait() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(1)
+            .mount(&server)
+            .await;
+        let clock = MockClock::new();
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(1)
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        translator
+            .translate_text("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        clock.advance(Duration::from_secs(10));
+
+        // RPM 限制需要等待 50 秒，超出 10 秒的总时限，不等待直接失败
+        let options = TranslateOptions::default()
+            .timeout(Duration::from_secs(30))
+            .deadline(Duration::from_secs(10));
+        match translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::DeadlineExceeded { budget }) => {
+                assert_eq!(budget, Duration::from_secs(10));
+            }
+            other => panic!("Expected DeadlineExceeded, got {:?}", other),
+        }
+        assert!(clock.sleeps().is_empty());
+        assert_eq!(clock.elapsed(), Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_no_rpm_limit_never_waits() {
+        let clock = MockClock::new();
+        let translator = rate_limited_translator(0, &clock);
+        let key_trackers = translator.key_trackers();
+        let tracker = &key_trackers[0];
+        assert!(tracker.rate_limiter.is_unlimited());
+        for _ in 0..100 {
+            translator.wait_for_rate_limit(tracker, None).await.unwrap();
+        }
+        assert!(clock.sleeps().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retry_backoff_uses_clock() {
+        let server = DelayedServer::start(DelayedResponse::new("overloaded").status(503)).await;
+        let clock = MockClock::new().auto_advance(true);
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().max_retries(2);
+
+        let result = translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await;
+        assert!(matches!(
+            result,
+            Err(TranslationError::MaxRetriesExceeded { attempts: 3, .. })
+        ));
+        assert_eq!(server.requests(), 3);
+        assert_eq!(
+            clock.sleeps(),
+            vec![Duration::from_millis(100), Duration::from_millis(200)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_on_retry_reports_openai_retries() {
+        let server = DelayedServer::start(DelayedResponse::new("overloaded").status(503)).await;
+        let clock = MockClock::new().auto_advance(true);
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let options = TranslateOptions::default()
+            .max_retries(2)
+            .on_retry(Arc::new(move |event: crate::RetryEvent<'_>| {
+                let status = match event.error {
+                    TranslationError::HttpError { status, .. } => Some(status.as_u16()),
+                    _ => None,
+                };
+                recorded.lock().unwrap().push((
+                    event.attempt,
+                    event.provider.to_string(),
+                    event.next_delay,
+                    status,
+                ));
+            }));
+
+        let result = translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await;
+        assert!(matches!(
+            result,
+            Err(TranslationError::MaxRetriesExceeded { attempts: 3, .. })
+        ));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                (
+                    1,
+                    "openai".to_string(),
+                    Duration::from_millis(100),
+                    Some(503)
+                ),
+                (
+                    2,
+                    "openai".to_string(),
+                    Duration::from_millis(200),
+                    Some(503)
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_base_url_override() {
+        let primary = MockServer::start().await;
+        let canary = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(1)
+            .mount(&canary)
+            .await;
+
+        let config = OpenAIConfig::builder()
+            .base_url(primary.uri())
+            .api_keys(vec!["test-key"])
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().base_url(canary.uri());
+        let result = translator
+            .translate_text("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(result, "T:Hello");
+        assert!(primary.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_base_url_override() {
+        let server = MockServer::start().await;
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        for invalid in ["not a url", "ftp://example.com"] {
+            let options = TranslateOptions::default().base_url(invalid);
+            match translator
+                .translate_text("Hello", &target_lang, None, &options)
+                .await
+            {
+                Err(TranslationError::ConfigurationError(msg)) => {
+                    assert!(msg.contains("base_url"), "{}", msg)
+                }
+                other => panic!("Expected ConfigurationError, got {:?}", other),
+            }
+        }
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_base_url_normalization_and_validation() {
+        // (配置的 base_url, 规范化的结果, 校验错误中应包含的内容)
+        let cases: &[(&str, &str, Option<&str>)] = &[
+            (
+                "https://api.openai.com/v1",
+                "https://api.openai.com/v1",
+                None,
+            ),
+            (
+                "https://api.openai.com/v1/",
+                "https://api.openai.com/v1",
+                None,
+            ),
+            ("https://api.openai.com", "https://api.openai.com", None),
+            ("https://api.openai.com/", "https://api.openai.com", None),
+            (
+                "https://api.openai.com/v1/chat/completions",
+                "https://api.openai.com/v1",
+                None,
+            ),
+            (
+                "https://api.openai.com/v1/chat/completions/",
+                "https://api.openai.com/v1",
+                None,
+            ),
+            (
+                "https://gateway.example/v1/responses",
+                "https://gateway.example/v1",
+                None,
+            ),
+            (
+                "https://openrouter.ai/api/v1",
+                "https://openrouter.ai/api/v1",
+                None,
+            ),
+            (
+                "https://generativelanguage.googleapis.com/v1beta/openai/",
+                "https://generativelanguage.googleapis.com/v1beta/openai",
+                None,
+            ),
+            (
+                "https://proxy.example/v1/?api-version=2024-06-01",
+                "https://proxy.example/v1?api-version=2024-06-01",
+                None,
+            ),
+            (
+                "https://api.openai.com/chat/completions",
+                "https://api.openai.com",
+                None,
+            ),
+            (
+                "https://api.openai.com/api",
+                "https://api.openai.com/api",
+                Some("/api"),
+            ),
+            (
+                "https://api.openai.com/v1/chat",
+                "https://api.openai.com/v1/chat",
+                None,
+            ),
+            (
+                "https://api.openai.com/completions",
+                "https://api.openai.com/completions",
+                Some("version"),
+            ),
+            (
+                "ftp://api.openai.com/v1",
+                "ftp://api.openai.com/v1",
+                Some("scheme"),
+            ),
+        ];
+        for (base_url, normalized, error) in cases {
+            let config = OpenAIConfig::builder()
+                .base_url(*base_url)
+                .api_keys(vec!["test-key"])
+                .build();
+            assert_eq!(config.base_url, *normalized, "normalizing {}", base_url);
+            match (config.validate(), error) {
+                (Ok(()), None) => {}
+                (Err(TranslationError::ConfigurationError(msg)), Some(expected)) => {
+                    assert!(msg.contains(expected), "{}: {}", base_url, msg)
+                }
+                (result, _) => panic!("validating {}: unexpected {:?}", base_url, result),
+            }
+        }
+
+        // 不经过 builder 构造的配置不做规范化，校验时拒绝接口路径
+        let mut config = OpenAIConfig::builder().api_keys(vec!["test-key"]).build();
+        config.base_url = "https://api.openai.com/v1/chat/completions".to_string();
+        match config.validate() {
+            Err(TranslationError::ConfigurationError(msg)) => {
+                assert!(msg.contains("/chat/completions endpoint"), "{}", msg)
+            }
+            other => panic!("Expected ConfigurationError, got {:?}", other),
+        }
+        assert_eq!(
+            normalize_base_url("http://localhost:11434/v1//"),
+            "http://localhost:11434/v1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_not_found_hints_at_base_url() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "error": {
+                    "message": "The model `gpt-5-nano` does not exist",
+                    "type": "invalid_request_error",
+                    "code": "model_not_found"
+                }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/chat/completions"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("404 page not found"))
+            .mount(&server)
+            .await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().no_retries();
+        let translate = |base_url: String| {
+            let translator = OpenAITranslator::new(
+                OpenAIConfig::builder()
+                    .base_url(base_url)
+                    .api_keys(vec!["test-key"])
+                    .rpm_limit(0)
+                    .build(),
+            );
+            let (target_lang, options) = (target_lang.clone(), options.clone());
+            async move {
+                translator
+                    .translate_text("Hello", &target_lang, None, &options)
+                    .await
+            }
+        };
+
+        // 路径不存在时提示检查 base_url
+        match translate(format!("{}/api", server.uri())).await {
+            Err(TranslationError::HttpError { status, body, .. }) => {
+                assert_eq!(status, StatusCode::NOT_FOUND);
+                assert!(body.starts_with("404 page not found"), "{}", body);
+                assert!(
+                    body.contains(&format!("base_url '{}/api'", server.uri())),
+                    "{}",
+                    body
+                );
+            }
+            other => panic!("Expected HttpError, got {:?}", other),
+        }
+        // 模型不存在的 404 与 base_url 无关
+        match translate(format!("{}/v1", server.uri())).await {
+            Err(TranslationError::Provider { code, message, .. }) => {
+                assert_eq!(code, "model_not_found");
+                assert!(!message.contains("base_url"), "{}", message);
+            }
+            other => panic!("Expected Provider, got {:?}", other),
+        }
+    }
+
+    /// 使用本地检测翻译并返回详细结果和发送的系统提示词
+    async fn translate_with_source_hint(
+        text: &str,
+        target: &str,
+    ) -> (crate::translator::TranslationDetail, String) {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .detect_before_translate(true)
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = target.parse().unwrap();
+        let detail = translator
+            .translate_detailed(text, &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        let system_prompt = body["messages"][0]["content"].as_str().unwrap().to_string();
+        (detail, system_prompt)
+    }
+
+    #[tokio::test]
+    async fn test_detect_before_translate_english() {
+        let (detail, prompt) =
+            translate_with_source_hint("The weather is nice and I want to go to the park.", "zh")
+                .await;
+        assert!(prompt.contains("Translate from en to zh"), "{}", prompt);
+        let (lang, confidence) = detail.detected_source.unwrap();
+        assert_eq!(lang.to_string(), "en");
+        assert!(confidence >= 0.5);
+        assert_eq!(detail.provider_metadata["source_hint"]["language"], "en");
+    }
+
+    #[tokio::test]
+    async fn test_detect_before_translate_chinese() {
+        let (detail, prompt) =
+            translate_with_source_hint("今天天气很好，我们去公园散步吧。", "en").await;
+        assert!(prompt.contains("Translate from zh to en"), "{}", prompt);
+        assert_eq!(detail.detected_source.unwrap().0.to_string(), "zh");
+    }
+
+    #[tokio::test]
+    async fn test_detect_before_translate_ambiguous_falls_back_to_auto() {
+        // 无法识别的短文本
+        let (detail, prompt) = translate_with_source_hint("OK", "zh").await;
+        assert!(prompt.contains("Translate from auto to zh"), "{}", prompt);
+        assert_eq!(detail.detected_source, None);
+        assert!(detail.provider_metadata.get("source_hint").is_none());
+
+        // 能识别但置信度不足的短文本，检测结果仍记录在元数据中
+        let (detail, prompt) = translate_with_source_hint("好", "en").await;
+        assert!(prompt.contains("Translate from auto to en"), "{}", prompt);
+        assert_eq!(detail.detected_source, None);
+        assert_eq!(detail.provider_metadata["source_hint"]["language"], "zh");
+    }
+
+    #[tokio::test]
+    async fn test_detect_before_translate_respects_explicit_source() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .mount(&server)
+            .await;
+
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .detect_before_translate(true)
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let source_lang: LanguageIdentifier = "fr".parse().unwrap();
+        translator
+            .translate_text(
+                "The weather is nice today.",
+                &target_lang,
+                Some(&source_lang),
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        let system_prompt = body["messages"][0]["content"].as_str().unwrap();
+        assert!(system_prompt.contains("Translate from fr to zh"));
+    }
+
+    #[test]
+    fn test_parse_error_bodies() {
+        // (HTTP 状态码, 响应体, 期望的错误码, 是否可重试)
+        let cases: &[(u16, &str, &str, bool)] = &[
+            (
+                401,
+                r#"{"error":{"message":"Incorrect API key provided: sk-abc.","type":"invalid_request_error","param":null,"code":"invalid_api_key"}}"#,
+                "invalid_api_key",
+                false,
+            ),
+            (
+                429,
+                r#"{"error":{"message":"Rate limit reached for requests","type":"requests","param":null,"code":"rate_limit_exceeded"}}"#,
+                "rate_limit_exceeded",
+                true,
+            ),
+            (
+                429,
+                r#"{"error":{"message":"Rate limit reached for tokens","type":"tokens","param":null,"code":"rate_limit_exceeded"}}"#,
+                "rate_limit_exceeded",
+                true,
+            ),
+            (
+                429,
+                r#"{"error":{"message":"You exceeded your current quota.","type":"insufficient_quota","param":null,"code":"insufficient_quota"}}"#,
+                "insufficient_quota",
+                false,
+            ),
+            (
+                400,
+                r#"{"error":{"message":"This model's maximum context length is 4097 tokens.","type":"invalid_request_error","param":"messages","code":"context_length_exceeded"}}"#,
+                "context_length_exceeded",
+                false,
+            ),
+            (
+                404,
+                r#"{"error":{"message":"The model `gpt-5` does not exist","type":"invalid_request_error","param":null,"code":"model_not_found"}}"#,
+                "model_not_found",
+                false,
+            ),
+            (
+                403,
+                r#"{"error":{"message":"Country, region, or territory not supported","type":"request_forbidden","param":null,"code":"unsupported_country_region_territory"}}"#,
+                "unsupported_country_region_territory",
+                false,
+            ),
+            (
+                500,
+                r#"{"error":{"message":"The server had an error while processing your request.","type":"server_error","param":null,"code":null}}"#,
+                "server_error",
+                true,
+            ),
+            (
+                503,
+                r#"{"error":{"message":"That model is currently overloaded with other requests.","type":"server_error","param":null,"code":null}}"#,
+                "server_error",
+                true,
+            ),
+            (
+                529,
+                r#"{"error":{"message":"Overloaded","type":"overloaded_error"}}"#,
+                "overloaded_error",
+                true,
+            ),
+            (
+                400,
+                r#"{"error":{"message":"Invalid parameter","code":400}}"#,
+                "400",
+                false,
+            ),
+            (
+                502,
+                r#"{"error":{"message":"Bad gateway"}}"#,
+                "unknown",
+                true,
+            ),
+        ];
+
+        for &(status, body, expected_code, expected_retryable) in cases {
+            let status = StatusCode::from_u16(status).unwrap();
+            let error = parse_error_body(status, body)
+                .unwrap_or_else(|| panic!("Expected Provider error for {}", body));
+            assert_eq!(error.is_retryable(), expected_retryable, "{}", body);
+            match error {
+                TranslationError::Provider {
+                    provider,
+                    code,
+                    message,
+                    http_status,
+                    retryable,
+                    ..
+                } => {
+                    assert_eq!(provider, "openai");
+                    assert_eq!(code, expected_code, "{}", body);
+                    assert!(!message.is_empty());
+                    assert_eq!(http_status, Some(status));
+                    assert_eq!(retryable, expected_retryable);
+                }
+                other => panic!("Expected Provider error, got {:?}", other),
+            }
+        }
+
+        // 无法解析的响应体
+        for body in ["<html>Bad Gateway</html>", r#"{"detail":"Not Found"}"#, ""] {
+            assert!(parse_error_body(StatusCode::BAD_GATEWAY, body).is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_provider_error_retry_classification() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(429).set_body_json(json!({
+                "error": {"message": "You exceeded your current quota.", "type": "insufficient_quota", "code": "insufficient_quota"}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        // 配额耗尽不可重试，只会发送一次请求
+        match translator
+            .translate_text("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+        {
+            Err(TranslationError::Provider { code, .. }) => assert_eq!(code, "insufficient_quota"),
+            other => panic!("Expected Provider error, got {:?}", other),
+        }
+    }
+
+    fn idempotency_keys(requests: &[Request]) -> Vec<String> {
+        requests
+            .iter()
+            .map(|request| {
+                request.headers["Idempotency-Key"]
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_constant_across_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("overloaded"))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["key-a", "key-b"])
+                .idempotency_header("Idempotency-Key")
+                .clock(Arc::new(MockClock::new().auto_advance(true)))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "T:Hello");
+
+        let keys = idempotency_keys(&server.received_requests().await.unwrap());
+        assert_eq!(keys.len(), 3);
+        assert!(keys.iter().all(|key| key == &keys[0]));
+        assert!(uuid::Uuid::parse_str(&keys[0]).is_ok());
+        assert_eq!(
+            detail.provider_metadata["idempotency_key"],
+            keys[0].as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_per_batch_item() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .idempotency_header("Idempotency-Key")
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        translator
+            .translate_batch(
+                &["one", "two", "three"],
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        let mut keys = idempotency_keys(&server.received_requests().await.unwrap());
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_header_disabled_by_default() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests[0].headers.get("Idempotency-Key").is_none());
+        assert!(detail.provider_metadata.get("idempotency_key").is_none());
+    }
+
+    /// 构造由若干增量组成的 SSE 响应体
+    fn sse_body(deltas: &[&str], done: bool) -> String {
+        let mut body: String = deltas
+            .iter()
+            .map(|delta| {
+                format!(
+                    "data: {}\n\n",
+                    json!({"choices": [{"delta": {"content": delta}}]})
+                )
+            })
+            .collect();
+        if done {
+            body.push_str("data: [DONE]\n\n");
+        }
+        body
+    }
+
+    fn streaming_translator(base_url: String) -> OpenAITranslator {
+        OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(base_url)
+                .api_keys(vec!["test-key"])
+                .stream(true)
+                .build(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_streaming_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(sse_body(&["你", "好", "世界"], true)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = streaming_translator(server.uri());
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = translator
+            .translate_detailed(
+                "Hello world",
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "你好世界");
+        assert!(!detail.partial);
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["stream"], true);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_tolerates_null_choices_and_delta() {
+        // Azure 的第一个事件只有内容过滤结果，部分网关在末尾发送 `delta: null`
+        let body = concat!(
+            "data: {\"choices\":[],\"prompt_filter_results\":[]}\n\n",
+            "data: {\"choices\":null}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\",\"content\":\"你好\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":null,\"finish_reason\":\"stop\"}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = streaming_translator(server.uri());
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let result = translator
+            .translate("Hello", &target_lang, None)
+            .await
+            .unwrap();
+        assert_eq!(result, "你好");
+    }
+
+    #[tokio::test]
+    async fn test_translate_batch_json_extracts_array() {
+        let content = "Here are the translations:\n\n```json\n[\"你好\", \"{名字}，欢迎\"]\n```";
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"message": {"role": "assistant", "content": content}}]
+            })))
+            .mount(&server)
+            .await;
+
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .detect_source_language(true)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        assert_eq!(
+            translator
+                .translate_batch_json(["Hello", "{name}, welcome"], &target_lang, None, &options)
+                .await
+                .unwrap(),
+            ["你好", "{名字}，欢迎"]
+        );
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(
+            body["messages"][1]["content"],
+            r#"["Hello","{name}, welcome"]"#
+        );
+        let system_prompt = body["messages"][0]["content"].as_str().unwrap();
+        assert!(system_prompt.contains("JSON array"));
+        assert!(!system_prompt.contains("[lang: <tag>]"));
+
+        // 条数不一致
+        match translator
+            .translate_batch_json(["Hello", "a", "b"], &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::MalformedResponse(msg)) => {
+                assert_eq!(msg, "expected 3 translations, got 2")
+            }
+            other => panic!("Expected MalformedResponse, got {:?}", other),
+        }
+        assert!(
+            translator
+                .translate_batch_json(Vec::<String>::new(), &target_lang, None, &options)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // 普通翻译原样返回模型输出，不提取其中的 JSON
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .build(),
+        );
+        assert_eq!(
+            translator
+                .translate_text("Hello", &target_lang, None, &options)
+                .await
+                .unwrap(),
+            content
+        );
+    }
+
+    #[tokio::test]
+    async fn test_provenance_from_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "chatcmpl-123",
+                "model": "gpt-4o-2024-08-06",
+                "system_fingerprint": "fp_abc",
+                "choices": [{"message": {"role": "assistant", "content": "你好"}}]
+            })))
+            .mount(&server)
+            .await;
+
+        let timestamp = std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .model("gpt-4o")
+                .clock(Arc::new(MockClock::new().wall_clock(timestamp)))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        let provenance = detail.provenance.unwrap();
+        assert_eq!(provenance.provider, "openai");
+        assert_eq!(provenance.model.as_deref(), Some("gpt-4o-2024-08-06"));
+        assert_eq!(provenance.request_id.as_deref(), Some("chatcmpl-123"));
+        assert_eq!(provenance.system_fingerprint.as_deref(), Some("fp_abc"));
+        assert_eq!(provenance.timestamp, timestamp);
+
+        // 指纹与缓存键使用相同的输入
+        let inputs = translator
+            .cache_key_inputs(&target_lang, None, &TranslateOptions::default())
+            .unwrap();
+        assert_eq!(provenance.prompt_fingerprint, inputs.prompt_hash);
+        assert_eq!(provenance.options_fingerprint, inputs.fingerprint());
+
+        // 影响译文的选项改变选项指纹，目标语言只改变提示词指纹
+        let normalized = translator
+            .translate_detailed(
+                "Hello",
+                &target_lang,
+                None,
+                &TranslateOptions::default().normalize_input(Normalization::default()),
+            )
+            .await
+            .unwrap()
+            .provenance
+            .unwrap();
+        assert_ne!(
+            normalized.options_fingerprint,
+            provenance.options_fingerprint
+        );
+        let japanese = translator
+            .translate_detailed(
+                "Hello",
+                &"ja".parse().unwrap(),
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap()
+            .provenance
+            .unwrap();
+        assert_eq!(japanese.options_fingerprint, provenance.options_fingerprint);
+        assert_ne!(japanese.prompt_fingerprint, provenance.prompt_fingerprint);
+
+        assert_eq!(
+            provenance.to_json(),
+            json!({
+                "provider": "openai",
+                "model": "gpt-4o-2024-08-06",
+                "prompt_fingerprint": provenance.prompt_fingerprint,
+                "request_id": "chatcmpl-123",
+                "system_fingerprint": "fp_abc",
+                "timestamp_ms": 1_700_000_000_000u64,
+                "options_fingerprint": provenance.options_fingerprint,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_concurrency_drops_on_429_and_recovers() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(429).set_body_string("slow down"))
+            .up_to_n_times(3)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .concurrent_limit(8)
+                .adaptive_concurrency(true)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().max_retries(0);
+        assert_eq!(translator.effective_concurrency_limit(), 8);
+
+        for expected in [4, 2, 1] {
+            assert!(
+                translator
+                    .translate_with_options("Hello", &target_lang, None, &options)
+                    .await
+                    .is_err()
+            );
+            assert_eq!(translator.effective_concurrency_limit(), expected);
+        }
+
+        // 每连续成功与当前并发数相同的次数后加 1
+        let mut limits = Vec::new();
+        for _ in 0..28 {
+            translator
+                .translate_with_options("Hello", &target_lang, None, &options)
+                .await
+                .unwrap();
+            limits.push(translator.effective_concurrency_limit());
+        }
+        let mut expected: Vec<usize> = (1..8).flat_map(|limit| vec![limit; limit]).collect();
+        expected.remove(0);
+        expected.push(8);
+        assert_eq!(limits, expected);
+
+        // 未启用时不调整
+        let fixed = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key", "other-key"])
+                .concurrent_limit(8)
+                .build(),
+        );
+        assert_eq!(fixed.effective_concurrency_limit(), 16);
+    }
+
+    #[tokio::test]
+    async fn test_tenants_use_own_keys_and_quotas() {
+        let server = MockServer::start().await;
+        for key in ["shared-key", "tenant-a-key", "tenant-b-key"] {
+            Mock::given(method("POST"))
+                .and(path("/chat/completions"))
+                .and(header("Authorization", format!("Bearer {}", key).as_str()))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(json!({
+                            "choices": [{"message": {"role": "assistant", "content": key}}]
+                        }))
+                        .set_delay(Duration::from_millis(20)),
+                )
+                .expect(if key == "tenant-a-key" { 2 } else { 1 })
+                .mount(&server)
+                .await;
+        }
+
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["shared-key"])
+                .concurrent_limit(4)
+                .build(),
+        );
+        let quota_a = Arc::new(Quota::new(1000, WindowPeriod::Daily));
+        let quota_b = Arc::new(Quota::new(1000, WindowPeriod::Daily));
+        let tenant_a = TenantContext::new("tenant-a")
+            .api_key("tenant-a-key")
+            .quota(quota_a.clone());
+        let tenant_b = TenantContext::new("tenant-b")
+            .api_key("tenant-b-key")
+            .quota(quota_b.clone())
+            .rate_limiter(SharedRateLimiter::new(10, None));
+        let options_a = TranslateOptions::default().tenant(tenant_a.clone());
+        let options_b = TranslateOptions::default().tenant(tenant_b);
+        let untenanted = TranslateOptions::default();
+
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let (a1, a2, b, shared) = tokio::join!(
+            translator.translate_with_options("Hello", &target_lang, None, &options_a),
+            translator.translate_with_options("World!", &target_lang, None, &options_a),
+            translator.translate_with_options("Goodbye", &target_lang, None, &options_b),
+            translator.translate_with_options("Hi", &target_lang, None, &untenanted),
+        );
+        assert_eq!(a1.unwrap(), "tenant-a-key");
+        assert_eq!(a2.unwrap(), "tenant-a-key");
+        assert_eq!(b.unwrap(), "tenant-b-key");
+        assert_eq!(shared.unwrap(), "shared-key");
+        assert_eq!(quota_a.used(), 11);
+        assert_eq!(quota_b.used(), 7);
+
+        // 租户标识和 Key 不出现在调试输出中
+        let debug = format!("{:?}", options_a);
+        assert!(!debug.contains("tenant-a"), "{}", debug);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_provenance_from_first_chunk() {
+        let body = concat!(
+            "data: {\"id\":\"chatcmpl-1\",\"model\":\"gpt-4o-mini\",\"system_fingerprint\":\"fp_1\",\"choices\":[{\"delta\":{\"content\":\"你\"}}]}\n\n",
+            "data: {\"id\":\"chatcmpl-1\",\"choices\":[{\"delta\":{\"content\":\"好\"}}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = streaming_translator(server.uri());
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "你好");
+        let provenance = detail.provenance.unwrap();
+        assert_eq!(provenance.model.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(provenance.request_id.as_deref(), Some("chatcmpl-1"));
+        assert_eq!(provenance.system_fingerprint.as_deref(), Some("fp_1"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_stall_salvages_partial() {
+        let server = DelayedServer::start(
+            DelayedResponse::new(sse_body(&["你好", "世"], false)).stall(true),
+        )
+        .await;
+        let translator = streaming_translator(server.uri());
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default()
+            .stall_timeout(Duration::from_millis(100))
+            .salvage_partial(true);
+
+        let detail = translator
+            .translate_detailed("Hello world", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "你好世");
+        assert!(detail.partial);
+        // 保留部分结果时不会重试
+        assert_eq!(server.requests(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stream_stall_without_salvage_times_out() {
+        let server = DelayedServer::start(
+            DelayedResponse::new(sse_body(&["你好", "世"], false)).stall(true),
+        )
+        .await;
+        let translator = streaming_translator(server.uri());
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default()
+            .stall_timeout(Duration::from_millis(100))
+            .max_retries(1);
+
+        match translator
+            .translate_detailed("Hello world", &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::MaxRetriesExceeded { attempts, errors }) => {
+                assert_eq!(attempts, 2);
+                assert!(
+                    errors
+                        .iter()
+                        .all(|e| matches!(e, TranslationError::TimeoutError))
+                );
+            }
+            other => panic!("Expected MaxRetriesExceeded, got {:?}", other),
+        }
+        // 停顿视为超时，会重试
+        assert_eq!(server.requests(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stall_options_ignored_without_streaming() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default()
+            .stall_timeout(Duration::from_millis(1))
+            .salvage_partial(true);
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "T:Hello");
+        assert!(!detail.partial);
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert!(body.get("stream").is_none());
+    }
+
+    fn shaped_translator(base_url: String, api_shape: ApiShape, stream: bool) -> OpenAITranslator {
+        OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(base_url)
+                .api_keys(vec!["test-key"])
+                .model("gpt-test")
+                .api_shape(api_shape)
+                .stream(stream)
+                .build(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_chat_completions_request_shape() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = shaped_translator(server.uri(), ApiShape::ChatCompletions, false);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        translator
+            .translate_text("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["model"], "gpt-test");
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(
+            body["messages"][1],
+            json!({"role": "user", "content": "Hello"})
+        );
+        assert!(body.get("input").is_none());
+        assert!(body.get("n").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_responses_api_shape() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/responses"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "resp_123",
+                "object": "response",
+                "status": "completed",
+                "output": [
+                    {"type": "reasoning", "id": "rs_1", "summary": []},
+                    {
+                        "type": "message",
+                        "id": "msg_1",
+                        "role": "assistant",
+                        "content": [
+                            {"type": "output_text", "text": "你好", "annotations": []}
+                        ]
+                    }
+                ],
+                "usage": {"input_tokens": 12, "output_tokens": 2}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = shaped_translator(server.uri(), ApiShape::Responses, false);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let result = translator
+            .translate_text("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(result, "你好");
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["model"], "gpt-test");
+        assert_eq!(body["input"][0]["role"], "system");
+        assert!(
+            body["input"][0]["content"]
+                .as_str()
+                .unwrap()
+                .contains("Translate from auto to zh")
+        );
+        assert_eq!(
+            body["input"][1],
+            json!({"role": "user", "content": "Hello"})
+        );
+        assert!(body.get("messages").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_responses_api_without_output_text() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/responses"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "output": [{"type": "reasoning", "summary": []}]
+            })))
+            .mount(&server)
+            .await;
+
+        let translator = shaped_translator(server.uri(), ApiShape::Responses, false);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let result = translator
+            .translate_text("Hello", &target_lang, None, &TranslateOptions::default())
+            .await;
+        assert!(matches!(result, Err(TranslationError::ServiceError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_responses_api_streaming() {
+        let events = [
+            json!({"type": "response.created", "response": {"id": "resp_123"}}),
+            json!({"type": "response.output_text.delta", "item_id": "msg_1", "delta": "你"}),
+            json!({"type": "response.output_text.delta", "item_id": "msg_1", "delta": "好"}),
+            json!({"type": "response.output_text.done", "text": "你好"}),
+            json!({"type": "response.completed", "response": {"id": "resp_123"}}),
+        ];
+        let body: String = events
+            .iter()
+            .map(|event| {
+                format!(
+                    "event: {}\ndata: {}\n\n",
+                    event["type"].as_str().unwrap(),
+                    event
+                )
+            })
+            .collect();
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/responses"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(body))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = shaped_translator(server.uri(), ApiShape::Responses, true);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "你好");
+        assert!(!detail.partial);
+    }
+
+    #[test]
+    fn test_pool_options_applied_to_client() {
+        CONSTRUCTED_CLIENTS.with(|constructed| constructed.borrow_mut().clear());
+        let config = OpenAIConfig::builder()
+            .api_keys(vec!["test-key"])
+            .pool_max_idle_per_host(200)
+            .pool_idle_timeout(None)
+            .tcp_keepalive(Some(Duration::from_secs(30)))
+            .http2_prior_knowledge(true)
+            .connect_timeout(Duration::from_secs(2))
+            .build();
+        let expected = PoolOptions {
+            max_idle_per_host: 200,
+            idle_timeout: None,
+            tcp_keepalive: Some(Duration::from_secs(30)),
+            http2_prior_knowledge: true,
+            connect_timeout: Some(Duration::from_secs(2)),
+            prefer_http2: false,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
+            http2_keep_alive_while_idle: false,
+        };
+        assert_eq!(config.pool, expected);
+        assert_eq!(OpenAIConfig::default().pool, PoolOptions::default());
+
+        let _translator = OpenAITranslator::new(config);
+        let constructed = CONSTRUCTED_CLIENTS.with(|constructed| constructed.borrow().clone());
+        assert_eq!(constructed, vec![(expected, ProxyMode::System)]);
+    }
+
+    #[tokio::test]
+    async fn test_high_concurrency_shares_one_client() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(200)
+            .mount(&server)
+            .await;
+
+        CONSTRUCTED_CLIENTS.with(|constructed| constructed.borrow_mut().clear());
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .concurrent_limit(200)
+                .pool_max_idle_per_host(200)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let texts: Vec<String> = (0..200).map(|i| format!("text {}", i)).collect();
+        let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+        // 默认选项带有超时设置，也不应为每个请求创建客户端
+        let results = translator
+            .translate_batch(&texts, &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 200);
+        assert_eq!(results[7], "T:text 7");
+        assert_eq!(
+            CONSTRUCTED_CLIENTS.with(|constructed| constructed.borrow().len()),
+            1
+        );
+    }
+
+    /// 依次挂载只响应一次的模拟响应，最后一个一直生效
+    async fn scripted_completions(server: &MockServer, contents: &[&str]) {
+        for (index, content) in contents.iter().enumerate() {
+            let mock = Mock::given(method("POST"))
+                .and(path("/chat/completions"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "choices": [{"message": {"role": "assistant", "content": content}}]
+                })));
+            let mock = if index + 1 < contents.len() {
+                mock.up_to_n_times(1)
+            } else {
+                mock
+            };
+            mock.mount(server).await;
+        }
+    }
+
+    const LONG_INPUT: &str = "The committee met on Tuesday to review the annual budget. \
+        After a long discussion, the members agreed to increase funding for public libraries \
+        and to postpone the decision on the new sports center until next spring.";
+
+    fn guarded_translator(server: &MockServer) -> OpenAITranslator {
+        OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .clock(Arc::new(MockClock::new().auto_advance(true)))
+                .build(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_length_ratio_guard_retries_suspicious_output() {
+        let server = MockServer::start().await;
+        let good = "委员会周二开会审议年度预算。经过长时间讨论，成员们同意增加公共图书馆的经费，并将新体育中心的决定推迟到明年春天。";
+        scripted_completions(&server, &["好", good]).await;
+
+        let translator = guarded_translator(&server);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().length_ratio_guard(0.5, 2.0);
+        let detail = translator
+            .translate_detailed(LONG_INPUT, &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(detail.text, good);
+        assert!(!detail.suspicious);
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_length_ratio_guard_returns_best_candidate() {
+        let server = MockServer::start().await;
+        scripted_completions(&server, &["好", "委员会开会审议预算。", "好的"]).await;
+
+        let translator = guarded_translator(&server);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default()
+            .length_ratio_guard(0.5, 2.0)
+            .max_retries(2);
+        let detail = translator
+            .translate_detailed(LONG_INPUT, &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "委员会开会审议预算。");
+        assert!(detail.suspicious);
+        assert!(detail.provider_metadata["length_ratio"].as_f64().unwrap() < 0.5);
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_length_ratio_guard_disabled_by_default() {
+        let server = MockServer::start().await;
+        scripted_completions(&server, &["好"]).await;
+
+        let translator = guarded_translator(&server);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let detail = translator
+            .translate_detailed(LONG_INPUT, &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "好");
+        assert!(!detail.suspicious);
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_assert_target_language_retries_wrong_language() {
+        let server = MockServer::start().await;
+        scripted_completions(&server, &["Cat", "猫"]).await;
+        let translator = guarded_translator(&server);
+        let target_lang: LanguageIdentifier = "ja".parse().unwrap();
+        let options = TranslateOptions::default().assert_target_language(true);
+        assert_eq!(
+            translator
+                .translate_text("Cat", &target_lang, None, &options)
+                .await
+                .unwrap(),
+            "猫"
+        );
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+        // 一直返回错误语言时重试次数受 max_retries 限制
+        let server = MockServer::start().await;
+        scripted_completions(&server, &["Cat"]).await;
+        let translator = guarded_translator(&server);
+        match translator
+            .translate_text("Cat", &target_lang, None, &options.clone().max_retries(2))
+            .await
+        {
+            Err(TranslationError::MaxRetriesExceeded { attempts, errors }) => {
+                assert_eq!(attempts, 3);
+                assert!(matches!(
+                    &errors[0],
+                    TranslationError::WrongOutputLanguage { expected, detected }
+                        if expected == "ja" && detected == "und"
+                ));
+            }
+            other => panic!("Expected MaxRetriesExceeded, got {:?}", other),
+        }
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+
+        // 未启用时不检查
+        assert_eq!(
+            translator
+                .translate_text("Cat", &target_lang, None, &TranslateOptions::default())
+                .await
+                .unwrap(),
+            "Cat"
+        );
+    }
+
+    #[test]
+    fn test_config_debug_redacts_keys() {
+        let config = OpenAIConfig::builder()
+            .api_keys(vec!["sk-secret-1", "sk-secret-2"])
+            .build();
+        let debug = format!("{:?}", config);
+        assert!(!debug.contains("sk-secret"), "{}", debug);
+        assert!(debug.contains("api_keys: [***, ***]"), "{}", debug);
+        assert_eq!(config.api_keys[0].expose_secret(), "sk-secret-1");
+    }
+
+    #[tokio::test]
+    async fn test_error_body_redacts_key() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("Authorization", "Bearer sk-secret-1"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "error": {
+                    "message": "Incorrect API key provided: sk-secret-1.",
+                    "type": "invalid_request_error",
+                    "code": "invalid_api_key"
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["sk-secret-1"])
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let error = translator
+            .translate_with_options("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap_err();
+        let output = format!("{:?} {}", error, error);
+        assert!(!output.contains("sk-secret-1"), "{}", output);
+        assert!(
+            output.contains("Incorrect API key provided: ***."),
+            "{}",
+            output
+        );
+    }
+
+    #[tokio::test]
+    async fn test_network_error_redacts_key_in_url() {
+        // 获取一个没有监听的端口
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(format!("http://127.0.0.1:{}/v1?key=sk-secret-1", port))
+                .api_keys(vec!["sk-secret-1"])
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().max_retries(0);
+        let error = translator
+            .translate_with_options("Hello", &target_lang, None, &options)
+            .await
+            .unwrap_err();
+        let output = format!("{:?} {}", error, error);
+        assert!(!output.contains("sk-secret-1"), "{}", output);
+
+        let options = options.base_url("ftp://127.0.0.1/v1?key=sk-secret-1");
+        let error = translator
+            .translate_with_options("Hello", &target_lang, None, &options)
+            .await
+            .unwrap_err();
+        let output = format!("{:?} {}", error, error);
+        assert!(!output.contains("sk-secret-1"), "{}", output);
+        assert!(matches!(error, TranslationError::ConfigurationError(_)));
+    }
+
+    /// 按模拟时钟记录每个请求到达时间的模拟响应
+    struct RecordingResponder {
+        clock: MockClock,
+        times: Arc<std::sync::Mutex<Vec<std::time::Instant>>>,
+    }
+
+    impl Respond for RecordingResponder {
+        fn respond(&self, _request: &Request) -> ResponseTemplate {
+            self.times.lock().unwrap().push(self.clock.now());
+            ResponseTemplate::new(200).set_body_json(
+                json!({"choices": [{"message": {"role": "assistant", "content": "ok"}}]}),
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_min_request_interval_spaces_burst() {
+        let clock = MockClock::new();
+        let times = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(RecordingResponder {
+                clock: clock.clone(),
+                times: times.clone(),
+            })
+            .mount(&server)
+            .await;
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .min_request_interval(Duration::from_millis(250))
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+
+        let burst = futures::future::join_all(
+            (0..10)
+                .map(|_| translator.translate_with_options("Hello", &target_lang, None, &options)),
+        );
+        let driver = async {
+            for received in 1..10 {
+                // 上一个请求到达服务端、下一个请求等待间隔后再推进时钟
+                clock.wait_for_sleepers(1).await;
+                while times.lock().unwrap().len() < received {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+                clock.advance(Duration::from_millis(250));
+            }
+        };
+        let (results, _) = tokio::join!(burst, driver);
+        assert!(results.iter().all(Result::is_ok), "{:?}", results);
+
+        let times = times.lock().unwrap().clone();
+        assert_eq!(times.len(), 10);
+        // 每次只等待维持间隔所需的时长
+        for pair in times.windows(2) {
+            assert_eq!(pair[1] - pair[0], Duration::from_millis(250));
+        }
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(250); 9]);
+    }
+
+    #[test]
+    fn test_min_request_interval_builder() {
+        let config = OpenAIConfig::builder()
+            .min_request_interval(Duration::from_millis(250))
+            .build();
+        assert_eq!(
+            config.min_request_interval,
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(OpenAIConfig::default().min_request_interval, None);
+    }
+
+    #[tokio::test]
+    async fn test_default_options_apply_unless_overridden() {
+        let server = MockServer::start().await;
+        for request_id in ["from-config", "from-call-site"] {
+            Mock::given(method("POST"))
+                .and(path("/chat/completions"))
+                .and(header("X-Request-Id", request_id))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "choices": [{"message": {"role": "assistant", "content": request_id}}]
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .default_options(TranslateOptions::default().request_id("from-config"))
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let result = translator
+            .translate("Hello", &target_lang, None)
+            .await
+            .unwrap();
+        assert_eq!(result, "from-config");
+
+        let options = TranslateOptions::default().request_id("from-call-site");
+        let result = translator
+            .translate_with_options("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(result, "from-call-site");
+    }
+
+    /// 依次返回预先设定的结果，用完后重复最后一个
+    #[derive(Debug)]
+    struct ScriptedKeyProvider {
+        responses: std::sync::Mutex<Vec<Result<Vec<ApiKeyConfig>, TranslationError>>>,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl ScriptedKeyProvider {
+        fn new(responses: Vec<Result<Vec<ApiKeyConfig>, TranslationError>>) -> Arc<Self> {
+            Arc::new(Self {
+                responses: std::sync::Mutex::new(responses),
+                calls: std::sync::atomic::AtomicUsize::new(0),
+            })
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl KeyProvider for ScriptedKeyProvider {
+        async fn fetch_keys(&self) -> Result<Vec<ApiKeyConfig>, TranslationError> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let mut responses = self.responses.lock().unwrap();
+            if responses.len() > 1 {
+                responses.remove(0)
+            } else {
+                responses[0]
+                    .as_ref()
+                    .map(Clone::clone)
+                    .map_err(TranslationError::duplicate)
+            }
+        }
+    }
+
+    /// 以请求使用的 Key 作为译文，已吊销的 Key 返回 401，`old` 的响应延迟发送
+    struct KeyEchoResponder {
+        revoked: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl Respond for KeyEchoResponder {
+        fn respond(&self, request: &Request) -> ResponseTemplate {
+            let key = request.headers["authorization"]
+                .to_str()
+                .unwrap()
+                .trim_start_matches("Bearer ")
+                .to_string();
+            if self.revoked.lock().unwrap().contains(&key) {
+                return ResponseTemplate::new(401).set_body_json(json!({
+                    "error": {"message": "Incorrect API key provided", "code": "invalid_api_key"}
+                }));
+            }
+            let delay = if key == "old" { 300 } else { 0 };
+            ResponseTemplate::new(200)
+                .set_body_string(completion_body(&key))
+                .set_delay(Duration::from_millis(delay))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_key_provider_swaps_keys() {
+        let server = MockServer::start().await;
+        let revoked = Arc::new(std::sync::Mutex::new(Vec::new()));
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(KeyEchoResponder {
+                revoked: revoked.clone(),
+            })
+            .mount(&server)
+            .await;
+        let provider = ScriptedKeyProvider::new(vec![
+            Ok(vec!["old".into()]),
+            Ok(vec!["new-a".into(), ApiKeyConfig::new("new-b").weight(3)]),
+            Ok(vec!["new-c".into()]),
+            Err(TranslationError::ServiceError(
+                "secrets service down".to_string(),
+            )),
+        ]);
+        let clock = MockClock::new();
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .key_provider(provider.clone())
+            .key_refresh_interval(Duration::from_secs(60))
+            .rpm_limit(0)
+            .clock(Arc::new(clock.clone()))
+            .build();
+        assert!(config.validate().is_ok());
+        let translator = Arc::new(OpenAITranslator::new(config));
+        assert!(translator.rate_limit_state().is_empty());
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().no_retries();
+        let translate = || {
+            let translator = translator.clone();
+            let target_lang = target_lang.clone();
+            let options = options.clone();
+            async move {
+                translator
+                    .translate_with_options("Hello", &target_lang, None, &options)
+                    .await
+            }
+        };
+
+        // 第一个请求获取 Key
+        assert_eq!(translate().await.unwrap(), "old");
+        assert_eq!(provider.calls(), 1);
+
+        // 替换前开始的请求继续使用旧的 Key 并正常完成
+        let in_flight = tokio::spawn(translate());
+        while server.received_requests().await.unwrap().len() < 2 {
+            tokio::task::yield_now().await;
+        }
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(translate().await.unwrap(), "new-a");
+        assert_eq!(provider.calls(), 2);
+        assert_eq!(translator.rate_limit_state().len(), 2);
+        assert_eq!(translator.key_trackers()[1].weight, 3);
+        assert_eq!(translate().await.unwrap(), "new-b");
+        assert_eq!(in_flight.await.unwrap().unwrap(), "old");
+
+        // 所有 Key 都被拒绝后，最小间隔过后立即重新获取
+        revoked
+            .lock()
+            .unwrap()
+            .extend(["new-a".to_string(), "new-b".to_string()]);
+        for _ in 0..3 {
+            assert!(translate().await.is_err());
+        }
+        assert_eq!(provider.calls(), 2);
+        clock.advance(MIN_KEY_REFRESH_INTERVAL);
+        assert_eq!(translate().await.unwrap(), "new-c");
+        assert_eq!(provider.calls(), 3);
+
+        // 获取失败时保留当前的 Key
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(translate().await.unwrap(), "new-c");
+        assert_eq!(provider.calls(), 4);
+        assert_eq!(translator.rate_limit_state().len(), 1);
+
+        // 还没有任何 Key 时返回获取的错误
+        let failing = ScriptedKeyProvider::new(vec![Err(TranslationError::ServiceError(
+            "secrets service down".to_string(),
+        ))]);
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .key_provider(failing)
+                .build(),
+        );
+        assert!(matches!(
+            translator
+                .translate_with_options("Hello", &target_lang, None, &options)
+                .await,
+            Err(TranslationError::ServiceError(_))
+        ));
+
+        let config = OpenAIConfig::builder()
+            .key_provider(provider)
+            .key_refresh_interval(Duration::from_secs(1))
+            .build();
+        assert!(matches!(
+            config.validate(),
+            Err(TranslationError::ConfigurationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_key_rotation_even_under_concurrency() {
+        let config = OpenAIConfig::builder()
+            .api_keys(vec!["key-a", "key-b", "key-c"])
+            .build();
+        let translator = Arc::new(OpenAITranslator::new(config));
+
+        let tasks: Vec<_> = (0..64)
+            .map(|_| {
+                let translator = translator.clone();
+                tokio::spawn(async move {
+                    (0..30)
+                        .map(|_| translator.get_next_key_index().unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let mut counts = [0usize; 3];
+        for task in tasks {
+            for index in task.await.unwrap() {
+                counts[index] += 1;
+            }
+        }
+        assert_eq!(counts, [640, 640, 640]);
+    }
+
+    #[test]
+    fn test_key_rotation_wraps_on_overflow() {
+        let config = OpenAIConfig::builder()
+            .api_keys(vec!["key-a", "key-b"])
+            .build();
+        let mut translator = OpenAITranslator::new(config);
+        translator.key_selector = Box::new(RoundRobinSelector {
+            next: std::sync::atomic::AtomicUsize::new(usize::MAX),
+        });
+        assert_eq!(translator.get_next_key_index().unwrap(), 1);
+        assert_eq!(translator.get_next_key_index().unwrap(), 0);
+        assert_eq!(translator.get_next_key_index().unwrap(), 1);
+    }
+
+    /// 使用固定种子的随机数生成器创建翻译器，使随机策略的选择结果可复现
+    fn seeded_translator(config: OpenAIConfig, seed: u64) -> OpenAITranslator {
+        let mut translator = OpenAITranslator::new(config);
+        translator.key_selector = key_selector(&translator.config, StdRng::seed_from_u64(seed));
+        translator
+    }
+
+    fn key_counts(translator: &OpenAITranslator, draws: usize) -> Vec<usize> {
+        let mut counts = vec![0; translator.key_trackers().len()];
+        for _ in 0..draws {
+            counts[translator.get_next_key_index().unwrap()] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_random_key_strategy_is_reproducible_with_seed() {
+        let config = OpenAIConfig::builder()
+            .api_keys(vec!["key-a", "key-b", "key-c"])
+            .key_strategy(KeyStrategy::Random)
+            .build();
+        let draw = |seed| {
+            let translator = seeded_translator(config.clone(), seed);
+            (0..32)
+                .map(|_| translator.get_next_key_index().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(draw(42), draw(42));
+        assert_ne!(draw(42), draw(43));
+
+        let counts = key_counts(&seeded_translator(config, 42), 3000);
+        assert!(counts.iter().all(|&count| count > 800), "{:?}", counts);
+    }
+
+    #[tokio::test]
+    async fn test_least_loaded_key_strategy_prefers_free_keys() {
+        let config = OpenAIConfig::builder()
+            .api_keys(vec!["key-a", "key-b", "key-c"])
+            .concurrent_limit(2)
+            .key_strategy(KeyStrategy::LeastLoaded)
+            .build();
+        let translator = seeded_translator(config, 7);
+        let trackers = translator.key_trackers();
+
+        // key-a 和 key-c 各占用一个许可，只有 key-b 完全空闲
+        let _a = trackers[0].concurrency.acquire(None).await.unwrap();
+        let _c = trackers[2].concurrency.acquire(None).await.unwrap();
+        assert_eq!(key_counts(&translator, 20), [0, 20, 0]);
+
+        // key-b 也占用一个许可后三者并列，随机选择
+        let _b = trackers[1].concurrency.acquire(None).await.unwrap();
+        let counts = key_counts(&translator, 300);
+        assert!(counts.iter().all(|&count| count > 50), "{:?}", counts);
+
+        // key-a 的许可全部被占用，只在 key-b 和 key-c 之间选择
+        let _a2 = trackers[0].concurrency.acquire(None).await.unwrap();
+        let counts = key_counts(&translator, 300);
+        assert_eq!(counts[0], 0);
+        assert!(counts[1] > 100 && counts[2] > 100, "{:?}", counts);
+    }
+
+    #[test]
+    fn test_weighted_random_key_strategy_follows_weights() {
+        let config = OpenAIConfig::builder()
+            .api_keys(vec!["key-a", "key-b", "key-c"])
+            .key_strategy(KeyStrategy::WeightedRandom)
+            .key_weights(vec![3, 1, 0])
+            .build();
+        assert!(config.validate().is_ok());
+        let counts = key_counts(&seeded_translator(config, 11), 4000);
+        assert_eq!(counts[2], 0);
+        assert!((2800..3200).contains(&counts[0]), "{:?}", counts);
+        assert!((800..1200).contains(&counts[1]), "{:?}", counts);
+
+        // 没有配置权重时所有 Key 的权重相同
+        let config = OpenAIConfig::builder()
+            .api_keys(vec!["key-a", "key-b"])
+            .key_strategy(KeyStrategy::WeightedRandom)
+            .build();
+        let counts = key_counts(&seeded_translator(config, 11), 2000);
+        assert!(counts.iter().all(|&count| count > 800), "{:?}", counts);
+    }
+
+    #[test]
+    fn test_invalid_key_weights_rejected() {
+        let builder = || {
+            OpenAIConfig::builder()
+                .api_keys(vec!["key-a", "key-b"])
+                .key_strategy(KeyStrategy::WeightedRandom)
+        };
+        for (weights, expected) in [
+            (vec![1], "key_weights has 1 weights for 2 API keys"),
+            (
+                vec![0, 0],
+                "key_weights must contain at least one positive weight",
+            ),
+        ] {
+            match builder().key_weights(weights).build().validate() {
+                Err(TranslationError::ConfigurationError(message)) => assert_eq!(message, expected),
+                other => panic!("Expected ConfigurationError, got {:?}", other),
+            }
+        }
+        assert_eq!(
+            OpenAIConfig::default().key_strategy,
+            KeyStrategy::RoundRobin
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_keys_is_configuration_error() {
+        let translator = OpenAITranslator::new(OpenAIConfig::default());
+        assert!(matches!(
+            translator.get_next_key_index(),
+            Err(TranslationError::ConfigurationError(_))
+        ));
+
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        match translator.translate("Hello", &target_lang, None).await {
+            Err(TranslationError::ConfigurationError(message)) => {
+                assert_eq!(message, "No API keys configured")
+            }
+            other => panic!("Expected ConfigurationError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_raw_response_included_when_requested() {
+        let server = MockServer::start().await;
+        let body = json!({
+            "id": "chatcmpl-1",
+            "system_fingerprint": "fp_123",
+            "choices": [{"message": {"role": "assistant", "content": "你好"}}]
+        });
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body.clone()))
+            .mount(&server)
+            .await;
+
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(detail.raw, None);
+
+        let options = TranslateOptions::default().include_raw_response(true);
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "你好");
+        assert_eq!(detail.raw, Some(body));
+
+        let options = options.max_raw_response_bytes(16);
+        let detail = translator
+            .translate_detailed("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        let raw = detail.raw.unwrap();
+        assert_eq!(raw["truncated"], true);
+        assert_eq!(raw["prefix"], r#"{"choices":[{"me"#);
+    }
+
+    #[test]
+    fn test_system_prompt_lookup_precedence() {
+        let config = OpenAIConfig::builder()
+            .system_prompt("generic {source_lang}->{target_lang}")
+            .system_prompt_for("en", "ja", "keigo {source_lang}->{target_lang}")
+            .system_prompt_for("auto", "ja", "any->ja from {source_lang}")
+            .build();
+        let translator = OpenAITranslator::new(config);
+
+        // 精确的语言对，按主语言子标签匹配
+        assert_eq!(
+            translator.get_system_prompt("ja-JP", Some("en-US")),
+            "keigo en-US->ja-JP"
+        );
+        // 源语言不匹配或未指定时使用 (auto, 目标语言)
+        assert_eq!(
+            translator.get_system_prompt("ja", Some("de")),
+            "any->ja from de"
+        );
+        assert_eq!(
+            translator.get_system_prompt("ja", None),
+            "any->ja from auto"
+        );
+        // 目标语言不匹配时使用 system_prompt
+        assert_eq!(
+            translator.get_system_prompt("de", Some("en")),
+            "generic en->de"
+        );
+
+        // 没有 system_prompt 时使用内置提示词
+        let config = OpenAIConfig::builder()
+            .system_prompt_for("en", "ja", "keigo")
+            .build();
+        let translator = OpenAITranslator::new(config);
+        assert!(
+            translator
+                .get_system_prompt("de", Some("en"))
+                .contains("Translate from en to de")
+        );
+    }
+
+    #[test]
+    fn test_system_prompt_for_builder() {
+        let config = OpenAIConfig::builder()
+            .system_prompt_for("EN-us", "ja", "first")
+            .system_prompt_for("en", "ja", "second")
+            .system_prompt_for("auto", "zh-Hans", "third")
+            .build();
+        assert_eq!(config.system_prompts.len(), 2);
+        assert_eq!(
+            config.system_prompts[&("en".to_string(), "ja".to_string())],
+            "second"
+        );
+        assert_eq!(
+            config.system_prompts[&("auto".to_string(), "zh".to_string())],
+            "third"
+        );
+        assert!(OpenAIConfig::default().system_prompts.is_empty());
+    }
+
+    /// 按单词计数估算 token，系统提示词为一个单词、上下文窗口为 10 时输入预算为 9
+    fn overflow_translator(policy: OverflowPolicy, server: &MockServer) -> OpenAITranslator {
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .system_prompt("sys")
+            .token_estimator(|text| text.split_whitespace().count())
+            .context_window(10)
+            .overflow_policy(policy)
+            .build();
+        OpenAITranslator::new(config)
+    }
+
+    const TWELVE_WORDS: &str = "a b c d e f g h i j k l";
+
+    #[tokio::test]
+    async fn test_overflow_policy_reject() {
+        let server = MockServer::start().await;
+        let translator = overflow_translator(OverflowPolicy::Reject, &server);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let result = translator
+            .translate_text(
+                TWELVE_WORDS,
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(TranslationError::TextTooLong { length: 12, max: 9 })
+        ));
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_overflow_policy_split() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(2)
+            .mount(&server)
+            .await;
+        let translator = overflow_translator(OverflowPolicy::Split, &server);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let result = translator
+            .translate_text(
+                TWELVE_WORDS,
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result, "T:a b c d e f g h i T:j k l");
+    }
+
+    #[tokio::test]
+    async fn test_overflow_policy_truncate_with_marker() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(2)
+            .mount(&server)
+            .await;
+        let policy = OverflowPolicy::TruncateWithMarker(" [truncated]".to_string());
+        let translator = overflow_translator(policy, &server);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let detail = translator
+            .translate_detailed(
+                TWELVE_WORDS,
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "T:a b c d e f g h i [truncated]");
+        assert!(detail.partial);
+
+        // 预算内的文本不受影响
+        let detail = translator
+            .translate_detailed("a b c", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(detail.text, "T:a b c");
+        assert!(!detail.partial);
+    }
+
+    #[tokio::test]
+    async fn test_auto_split_option_overrides_overflow_policy() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(2)
+            .mount(&server)
+            .await;
+        let translator = overflow_translator(OverflowPolicy::Reject, &server);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().auto_split(true);
+        let result = translator
+            .translate_text(TWELVE_WORDS, &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(result, "T:a b c d e f g h i T:j k l");
+    }
+
+    #[test]
+    fn test_context_window_for_model() {
+        assert_eq!(context_window_for_model("gpt-3.5-turbo"), 16_385);
+        assert_eq!(context_window_for_model("gpt-4"), 8_192);
+        assert_eq!(context_window_for_model("gpt-4-0613"), 8_192);
+        assert_eq!(context_window_for_model("gpt-4o-mini"), 128_000);
+        assert_eq!(context_window_for_model("o1-mini"), 128_000);
+        assert_eq!(context_window_for_model("o1-preview"), 200_000);
+        assert_eq!(context_window_for_model("my-local-llama"), 4_096);
+        assert_eq!(
+            OpenAIConfig::default().overflow_policy,
+            OverflowPolicy::Reject
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_input_limit_follows_context_window() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"message": {"role": "assistant", "content": "你好"}}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        // 约 25000 个 token，远超 4096，但在 gpt-4o 的上下文窗口之内
+        let text = "a".repeat(100_000);
+
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .model("gpt-4o")
+                .build(),
+        );
+        assert_eq!(translator.capabilities().max_text_len, Some(128_000));
+        assert!(
+            translator
+                .translate_text(&text, &target_lang, None, &options)
+                .await
+                .is_ok()
+        );
+
+        // 上下文窗口较小的模型仍按窗口拒绝
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .model("gpt-4")
+                .build(),
+        );
+        match translator
+            .translate_text(&text, &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::TextTooLong { length, max }) => {
+                assert_eq!(length, 25_000);
+                assert!(max < 8_192);
+            }
+            other => panic!("Expected TextTooLong, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_normalize_input_before_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .mount(&server)
+            .await;
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let text = "Cafe\u{301}\u{200B} trans\u{00AD}lation".to_string();
+        let options = TranslateOptions::default().normalize_input(Normalization {
+            strip_zero_width: true,
+            strip_soft_hyphens: true,
+            ..Default::default()
+        });
+        let result = translator
+            .translate_text(&text, &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(result, "T:Caf\u{E9} translation");
+        assert_eq!(text, "Cafe\u{301}\u{200B} trans\u{00AD}lation");
+
+        // 默认不做任何处理
+        let result = translator
+            .translate_text(&text, &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(result, format!("T:{}", text));
+    }
+
+    #[tokio::test]
+    async fn test_protect_numbers_restores_placeholders() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(|request: &Request| {
+                let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                let prompt = body["messages"][0]["content"].as_str().unwrap();
+                let content = body["messages"][1]["content"].as_str().unwrap();
+                let translated = match content {
+                    _ if !prompt.contains("placeholders such as ⟦A⟧") => "missing instruction",
+                    "Released on ⟦A⟧, costs ⟦B⟧" => "售价 ⟦B⟧，于 ⟦A⟧ 发布",
+                    _ => "于 ⟦A⟧ 发布",
+                };
+                ResponseTemplate::new(200).set_body_json(json!({
+                    "choices": [{"message": {"role": "assistant", "content": translated}}]
+                }))
+            })
+            .expect(2)
+            .mount(&server)
+            .await;
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().protect_numbers(true);
+
+        let result = translator
+            .translate_text(
+                "Released on 2024-05-03, costs $1,299.99",
+                &target_lang,
+                None,
+                &options,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result, "售价 $1,299.99，于 2024-05-03 发布");
+
+        // 译文丢失占位符时不返回被改写的译文
+        match translator
+            .translate_text("Shipped 2024-05-03 for €5", &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::ProtectedTextLost { text }) => assert_eq!(text, "€5"),
+            other => panic!("Expected ProtectedTextLost, got {:?}", other),
+        }
+    }
+
+    /// 已开始翻译的批次条目数
+    static BATCH_STARTED: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    /// 模拟服务器已响应的条目数
+    static BATCH_COMPLETED: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    /// 同时进行中的条目数峰值
+    static BATCH_PEAK: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    /// 记录批次条目开始翻译的 token 估算函数，每个条目开始时会对原文估算一次
+    fn counting_estimator(text: &str) -> usize {
+        use std::sync::atomic::Ordering;
+        if text.starts_with("item-") {
+            let started = BATCH_STARTED.fetch_add(1, Ordering::SeqCst) + 1;
+            let outstanding = started - BATCH_COMPLETED.load(Ordering::SeqCst);
+            BATCH_PEAK.fetch_max(outstanding, Ordering::SeqCst);
+        }
+        estimate_tokens(text)
+    }
+
+    /// 原样返回用户消息并记录响应数的模拟响应
+    struct CountingResponder;
+
+    impl Respond for CountingResponder {
+        fn respond(&self, request: &Request) -> ResponseTemplate {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            let content = body["messages"][1]["content"].as_str().unwrap();
+            BATCH_COMPLETED.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"message": {"role": "assistant", "content": format!("T:{}", content)}}]
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_outstanding_futures_bounded() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(CountingResponder)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["key-1", "key-2"])
+                .rpm_limit(0)
+                .concurrent_limit(3)
+                .token_estimator(counting_estimator)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let inputs: Vec<String> = (0..2000).map(|i| format!("item-{}", i)).collect();
+        let texts: Vec<&str> = inputs.iter().map(String::as_str).collect();
+
+        let results = translator
+            .translate_batch(&texts, &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        let expected: Vec<String> = inputs.iter().map(|text| format!("T:{}", text)).collect();
+        assert_eq!(results, expected);
+
+        // 两个 Key 各 3 个并发，同时进行的条目不超过 6 个
+        let peak = BATCH_PEAK.load(std::sync::atomic::Ordering::SeqCst);
+        assert!((1..=6).contains(&peak), "peak outstanding: {}", peak);
+    }
+
+    #[tokio::test]
+    async fn test_batch_returns_first_error_in_input_order() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(wiremock::matchers::body_string_contains("bad"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("bad input"))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .concurrent_limit(2)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let texts = ["one", "bad-1", "two", "bad-2", "three"];
+        let result = translator
+            .translate_batch(&texts, &target_lang, None, &TranslateOptions::default())
+            .await;
+        match result {
+            Err(TranslationError::HttpError { status, .. }) => {
+                assert_eq!(status, StatusCode::BAD_REQUEST)
+            }
+            other => panic!("Expected HttpError, got {:?}", other),
+        }
+        // 出错时其余条目仍会全部翻译
+        assert_eq!(server.received_requests().await.unwrap().len(), texts.len());
+    }
+
+    #[tokio::test]
+    async fn test_batch_accepts_any_string_collection() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .mount(&server)
+            .await;
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        let expected = vec!["T:a".to_string(), "T:b".to_string()];
+
+        let strs: &[&str] = &["a", "b"];
+        let owned: Vec<String> = vec!["a".to_string(), "b".to_string()];
+        let borrowed: &[String] = &owned;
+        assert_eq!(
+            translator
+                .translate_batch(strs, &target_lang, None, &options)
+                .await
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            translator
+                .translate_batch(borrowed, &target_lang, None, &options)
+                .await
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            translator
+                .translate_batch(&owned, &target_lang, None, &options)
+                .await
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            translator
+                .translate_batch(owned, &target_lang, None, &options)
+                .await
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            translator
+                .translate_batch("a\nb".lines(), &target_lang, None, &options)
+                .await
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_output_follows_iterator_order() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .mount(&server)
+            .await;
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let results = translator
+            .translate_batch(
+                (0..50).rev().map(|i| format!("text-{}", i)),
+                &target_lang,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        let expected: Vec<String> = (0..50).rev().map(|i| format!("T:text-{}", i)).collect();
+        assert_eq!(results, expected);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_queue_wait_rejects_when_saturated() {
+        let body = json!({"choices": [{"message": {"role": "assistant", "content": "你好"}}]});
+        let server = DelayedServer::start(
+            DelayedResponse::new(body.to_string()).header_delay(Duration::from_secs(10)),
+        )
+        .await;
+        let translator = Arc::new(OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .concurrent_limit(1)
+                .build(),
+        ));
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let key_trackers = translator.key_trackers();
+        let concurrency = &key_trackers[0].concurrency;
+
+        let busy = tokio::spawn({
+            let translator = translator.clone();
+            let target_lang = target_lang.clone();
+            async move { translator.translate("Hello", &target_lang, None).await }
+        });
+        while concurrency.available_permits() > 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let start = tokio::time::Instant::now();
+        let options = TranslateOptions::default().max_queue_wait(Duration::from_millis(500));
+        match translator
+            .translate_with_options("Hello", &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::Overloaded { waited }) => {
+                assert_eq!(waited, Duration::from_millis(500))
+            }
+            other => panic!("Expected Overloaded, got {:?}", other),
+        }
+        // 在占用许可的请求完成之前就被拒绝，且不会重试
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(!busy.is_finished());
+
+        assert_eq!(busy.await.unwrap().unwrap(), "你好");
+        assert_eq!(concurrency.available_permits(), 1);
+        assert_eq!(concurrency.waiters(), 0);
+        assert_eq!(server.requests(), 1);
+    }
+
+    /// 等待 `clock` 上出现时长为 `duration` 的 `sleep`，然后推进时钟
+    async fn advance_after_sleep(clock: &MockClock, duration: Duration) {
+        while !clock.sleeps().contains(&duration) {
+            tokio::task::yield_now().await;
+        }
+        clock.advance(duration);
+    }
+
+    #[tokio::test]
+    async fn test_hedged_request_wins() {
+        let server = DelayedServer::start_sequence(vec![
+            DelayedResponse::new(completion_body("慢")).header_delay(Duration::from_secs(5)),
+            DelayedResponse::new(completion_body("快")),
+        ])
+        .await;
+        let clock = MockClock::new();
+        // 随机选择 Key 时对冲请求也会避开第一个请求的 Key
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["key-a", "key-b"])
+                .key_strategy(KeyStrategy::Random)
+                .idempotency_header("Idempotency-Key")
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let hedge_after = Duration::from_millis(200);
+        let options = TranslateOptions::default().hedge_after(hedge_after);
+
+        let start = std::time::Instant::now();
+        let (result, ()) = tokio::join!(
+            translator.translate_with_options("Hello", &target_lang, None, &options),
+            advance_after_sleep(&clock, hedge_after),
+        );
+        assert_eq!(result.unwrap(), "快");
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(server.requests(), 2);
+        let keys = server.request_headers("authorization");
+        assert_ne!(keys[0], keys[1]);
+        let idempotency_keys = server.request_headers("idempotency-key");
+        assert!(idempotency_keys[0].is_some());
+        assert_eq!(idempotency_keys[0], idempotency_keys[1]);
+        // 慢的请求已经取消并释放了许可
+        for tracker in translator.key_trackers().iter() {
+            assert_eq!(tracker.concurrency.available_permits(), 10);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hedge_skips_fast_and_retried_requests() {
+        let server = DelayedServer::start_sequence(vec![
+            DelayedResponse::new(completion_body("你好")),
+            DelayedResponse::new("unavailable").status(503),
+            DelayedResponse::new(completion_body("世界")).header_delay(Duration::from_millis(500)),
+        ])
+        .await;
+        let clock = MockClock::new();
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let hedge_after = Duration::from_millis(250);
+        let options = TranslateOptions::default()
+            .hedge_after(hedge_after)
+            .max_retries(1);
+
+        // 在等待时长内完成的请求不对冲
+        let result = translator
+            .translate_with_options("Hello", &target_lang, None, &options)
+            .await
+            .unwrap();
+        assert_eq!(result, "你好");
+        assert_eq!(server.requests(), 1);
+
+        // 第一次尝试失败后的重试不再等待对冲
+        let backoff = Duration::from_millis(100);
+        let (result, ()) = tokio::join!(
+            translator.translate_with_options("World", &target_lang, None, &options),
+            advance_after_sleep(&clock, backoff),
+        );
+        assert_eq!(result.unwrap(), "世界");
+        assert_eq!(server.requests(), 3);
+        assert_eq!(clock.sleeps(), [hedge_after, hedge_after, backoff]);
+    }
+
+    #[tokio::test]
+    async fn test_hedge_waits_for_rate_limit() {
+        let server = DelayedServer::start(DelayedResponse::new(completion_body("你好"))).await;
+        let clock = MockClock::new();
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(1)
+                .clock(Arc::new(clock.clone()))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        // 第一个请求用完 RPM 额度
+        translator
+            .translate("Hello", &target_lang, None)
+            .await
+            .unwrap();
+
+        let hedge_after = Duration::from_millis(100);
+        let options = TranslateOptions::default().hedge_after(hedge_after);
+        let drive = async {
+            while clock.sleeps().is_empty() {
+                tokio::task::yield_now().await;
+            }
+            let rate_limit_wait = clock.sleeps()[0];
+            assert!(rate_limit_wait > hedge_after);
+            // 排队超过 hedge_after 也不发出对冲请求
+            clock.advance(hedge_after);
+            for _ in 0..10 {
+                tokio::task::yield_now().await;
+            }
+            assert_eq!(clock.sleeps(), [rate_limit_wait]);
+            assert_eq!(server.requests(), 1);
+            clock.advance(rate_limit_wait - hedge_after);
+        };
+        let (result, ()) = tokio::join!(
+            translator.translate_with_options("Hello", &target_lang, None, &options),
+            drive,
+        );
+        assert_eq!(result.unwrap(), "你好");
+        assert_eq!(server.requests(), 2);
+        // 取得额度之后才开始计时
+        assert_eq!(clock.sleeps().last(), Some(&hedge_after));
+    }
+
+    fn is_max_retries(error: &TranslationError) -> bool {
+        matches!(error, TranslationError::MaxRetriesExceeded { .. })
+    }
+
+    #[tokio::test]
+    async fn test_on_failure_policies_with_failing_server() {
+        use crate::options::FailurePolicy;
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("unavailable"))
+            .mount(&server)
+            .await;
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .clock(Arc::new(MockClock::new().auto_advance(true)))
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().max_retries(1);
+
+        match translator
+            .translate_detailed("Hello", &target_lang, None, &options)
+            .await
+        {
+            Err(TranslationError::MaxRetriesExceeded { attempts: 2, .. }) => {}
+            other => panic!("Expected MaxRetriesExceeded, got {:?}", other),
+        }
+
+        for policy in [
+            FailurePolicy::ReturnOriginal,
+            FailurePolicy::ReturnOriginalIf(is_max_retries),
+        ] {
+            let options = options.clone().on_failure(policy);
+            let detail = translator
+                .translate_detailed("Hello", &target_lang, None, &options)
+                .await
+                .unwrap();
+            assert_eq!(detail.text, "Hello");
+            assert!(detail.passthrough);
+            assert!(
+                detail.provider_metadata["error"]
+                    .as_str()
+                    .unwrap()
+                    .starts_with("Max retries exceeded after 2 attempts")
+            );
+            assert_eq!(
+                translator
+                    .translate_batch(["one", "two"], &target_lang, None, &options)
+                    .await
+                    .unwrap(),
+                vec!["one", "two"]
+            );
+        }
+
+        // 条件不满足时仍然返回错误
+        let options = options.on_failure(FailurePolicy::ReturnOriginalIf(|error| {
+            matches!(error, TranslationError::TimeoutError)
+        }));
+        assert!(matches!(
+            translator
+                .translate_with_options("Hello", &target_lang, None, &options)
+                .await,
+            Err(TranslationError::MaxRetriesExceeded { .. })
+        ));
+        // 每次翻译都完成了全部重试之后才原样返回
+        assert_eq!(server.received_requests().await.unwrap().len(), 2 * 8);
+    }
+
+    #[tokio::test]
+    async fn test_translate_batch_tagged_dedup() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .rpm_limit(0)
+            .default_options(TranslateOptions::default().dedup(true))
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let rows: Vec<(u64, String)> = [(7, "Hello"), (3, "World"), (9, "Hello"), (1, "Bye")]
+            .into_iter()
+            .map(|(id, text)| (id, text.to_string()))
+            .collect();
+        let results = translator
+            .translate_batch_tagged(rows, &target_lang, None, &TranslateOptions::default())
+            .await;
+        let results: Vec<(u64, String)> = results
+            .into_iter()
+            .map(|(id, result)| (id, result.unwrap()))
+            .collect();
+        assert_eq!(
+            results,
+            [
+                (7, "T:Hello".to_string()),
+                (3, "T:World".to_string()),
+                (9, "T:Hello".to_string()),
+                (1, "T:Bye".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_budget_rejects_before_sending() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .rpm_limit(0)
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let texts = ["Hello", "World", "Bye"];
+        let options = TranslateOptions::default().max_batch_chars(12);
+
+        for error in [
+            translator
+                .translate_batch(texts, &target_lang, None, &options)
+                .await
+                .unwrap_err(),
+            translator
+                .translate_batch_json(
+                    texts,
+                    &target_lang,
+                    None,
+                    &options.clone().truncate_to_budget(true),
+                )
+                .await
+                .unwrap_err(),
+        ] {
+            assert!(matches!(
+                error,
+                TranslationError::BudgetExceeded { total: 13, max: 12 }
+            ));
+        }
+        // 恰好等于预算时整批翻译
+        assert_eq!(
+            translator
+                .translate_batch(
+                    texts,
+                    &target_lang,
+                    None,
+                    &TranslateOptions::default().max_batch_chars(13)
+                )
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+        server.verify().await;
+
+        // 截断时只有预算内的条目发送请求
+        server.reset().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(6)
+            .mount(&server)
+            .await;
+        let rows: Vec<(u64, String)> = texts
+            .iter()
+            .enumerate()
+            .map(|(id, text)| (id as u64, text.to_string()))
+            .collect();
+        let truncating = options.truncate_to_budget(true);
+        let results = translator
+            .translate_batch_tagged(rows, &target_lang, None, &truncating)
+            .await;
+        assert_eq!(results[0].1.as_deref().unwrap(), "T:Hello");
+        assert_eq!(results[1].1.as_deref().unwrap(), "T:World");
+        assert!(matches!(
+            results[2],
+            (
+                2,
+                Err(TranslationError::BudgetExceeded { total: 13, max: 12 })
+            )
+        ));
+
+        // 不论 on_failure 如何都返回预算内的译文：只返回字符串时只有这些条目，
+        // 详细结果中跳过的条目是标记为 passthrough 的原文
+        assert_eq!(
+            translator
+                .translate_batch(texts, &target_lang, None, &truncating)
+                .await
+                .unwrap(),
+            ["T:Hello", "T:World"]
+        );
+        let details = translator
+            .translate_batch_detailed(texts, &target_lang, None, &truncating)
+            .await
+            .unwrap();
+        assert_eq!(details.len(), 3);
+        assert!(!details[1].passthrough);
+        assert!(details[2].passthrough && !details[2].partial);
+        assert_eq!(details[2].text, "Bye");
+        assert_eq!(
+            details[2].provider_metadata["error"],
+            "Batch of 13 characters exceeds the budget of 12 characters"
+        );
+        server.verify().await;
+    }
+
+    #[tokio::test]
+    async fn test_batch_budget_truncates_split_chunks() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(7)
+            .mount(&server)
+            .await;
+        // 每个字符计为一个 token，超过 4 个字符的文本拆分翻译
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .max_input_tokens(Some(4))
+                .token_estimator(|text| text.chars().count())
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let texts = ["ab", "one two six", "cd"];
+        let options = |max: usize| {
+            TranslateOptions::default()
+                .max_batch_chars(max)
+                .truncate_to_budget(true)
+                .auto_split(true)
+        };
+        let translate = |max: usize| {
+            let options = options(max);
+            let translator = &translator;
+            let target_lang = &target_lang;
+            async move {
+                translator
+                    .translate_batch_detailed(texts, target_lang, None, &options)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // 第二条拆分为 "one"、"two"、"six"，预算恰好容纳前两个片段
+        let details = translate(9).await;
+        let translated: Vec<&str> = details.iter().map(|detail| detail.text.as_str()).collect();
+        assert_eq!(translated, ["T:ab", "T:one T:two", "cd"]);
+        assert!(details[1].partial && !details[1].passthrough);
+        assert_eq!(
+            details[1].provider_metadata["error"],
+            "Batch of 15 characters exceeds the budget of 9 characters"
+        );
+        assert!(details[2].passthrough);
+
+        // 少一个字符时只翻译第一个片段
+        assert_eq!(translate(8).await[1].text, "T:one");
+        // 一个片段也容纳不下时整条跳过
+        let details = translate(4).await;
+        assert!(details[1].passthrough && !details[1].partial);
+        assert_eq!(details[1].text, "one two six");
+
+        // 只返回字符串时按整条截断，不发送部分片段
+        assert_eq!(
+            translator
+                .translate_batch(texts, &target_lang, None, &options(9))
+                .await
+                .unwrap(),
+            ["T:ab"]
+        );
+        server.verify().await;
+    }
+
+    #[test]
+    fn test_cache_key_inputs_follow_prompt_and_model() {
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let inputs = |config: OpenAIConfig, options: &TranslateOptions| {
+            OpenAITranslator::new(config)
+                .cache_key_inputs(&target_lang, None, options)
+                .unwrap()
+        };
+        let builder = || OpenAIConfig::builder().api_keys(vec!["test-key"]);
+        let options = TranslateOptions::default();
+        let base = inputs(builder().build(), &options);
+        assert_eq!(base.provider, "openai");
+        assert_eq!(base.model.as_deref(), Some("gpt-3.5-turbo"));
+
+        // 与译文无关的选项不影响输入
+        assert_eq!(
+            inputs(
+                builder().build(),
+                &TranslateOptions::default().max_retries(0)
+            ),
+            base
+        );
+        for changed in [
+            inputs(builder().model("gpt-4").build(), &options),
+            inputs(
+                builder()
+                    .system_prompt("Translate to {target_lang}.")
+                    .build(),
+                &options,
+            ),
+            inputs(
+                builder()
+                    .system_prompt_for("auto", "zh", "Use 简体.")
+                    .build(),
+                &options,
+            ),
+            inputs(builder().detect_source_language(true).build(), &options),
+            inputs(
+                builder().build(),
+                &TranslateOptions::default().base_url("http://other"),
+            ),
+            inputs(
+                builder().build(),
+                &TranslateOptions::default().normalize_input(Normalization::default()),
+            ),
+        ] {
+            assert_ne!(changed, base);
+        }
+    }
+
+    /// 等待服务器收到 `count` 个请求
+    async fn wait_for_requests(server: &DelayedServer, count: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while server.requests() < count {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("expected {} requests, got {}", count, server.requests()));
+    }
+
+    #[tokio::test]
+    async fn test_start_jitter_spreads_first_requests() {
+        let server = DelayedServer::start(DelayedResponse::new(completion_body("你好"))).await;
+        let clock = MockClock::new();
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .rpm_limit(0)
+            .concurrent_limit(50)
+            .clock(Arc::new(clock.clone()))
+            .build();
+        let mut translator = OpenAITranslator::new(config);
+        translator.start_jitter = StartJitter::new(StdRng::seed_from_u64(9));
+        let max = Duration::from_secs(1);
+        let texts: Vec<String> = (0..20).map(|i| format!("text {}", i)).collect();
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().start_jitter(max);
+        let batch = translator.translate_batch(&texts, &target_lang, None, &options);
+
+        let drive = async {
+            // 每条文本的延迟来自固定种子，且都在 [0, max) 内
+            clock.wait_for_sleepers(20).await;
+            let mut rng = StdRng::seed_from_u64(9);
+            let mut expected: Vec<Duration> = (0..20)
+                .map(|_| rand::Rng::random_range(&mut rng, Duration::ZERO..max))
+                .collect();
+            expected.sort();
+            let mut delays = clock.sleeps();
+            delays.sort();
+            assert_eq!(delays, expected);
+            assert_eq!(server.requests(), 0);
+
+            // 推进时钟时，只有延迟已到的文本发出请求
+            let mut busy_steps = 0;
+            for _ in 0..10 {
+                let before = server.requests();
+                clock.advance(Duration::from_millis(100));
+                let due = delays
+                    .iter()
+                    .filter(|&&delay| delay <= clock.elapsed())
+                    .count();
+                wait_for_requests(&server, due).await;
+                assert_eq!(server.requests(), due);
+                if due > before {
+                    busy_steps += 1;
+                }
+            }
+            assert!(
+                busy_steps >= 5,
+                "requests arrived in only {} steps",
+                busy_steps
+            );
+        };
+        let (results, ()) = tokio::join!(batch, drive);
+        assert_eq!(results.unwrap().len(), 20);
+    }
+
+    #[tokio::test]
+    async fn test_no_start_jitter_by_default() {
+        let server = DelayedServer::start(DelayedResponse::new(completion_body("你好"))).await;
+        let clock = MockClock::new();
+        let config = OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec!["test-key"])
+            .rpm_limit(0)
+            .clock(Arc::new(clock.clone()))
+            .build();
+        let translator = OpenAITranslator::new(config);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let texts = ["a", "b", "c"];
+        let results = translator
+            .translate_batch(&texts, &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(clock.sleeps().is_empty());
+        assert_eq!(server.requests(), 3);
+    }
+
+    #[test]
+    fn test_request_preview_chat_completions() {
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url("https://api.example.com/v1")
+                .model("gpt-4o-mini")
+                .api_keys(vec!["sk-secret"])
+                .system_prompt("Translate {source_lang} to {target_lang}.")
+                .idempotency_header("Idempotency-Key")
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let source_lang: LanguageIdentifier = "en".parse().unwrap();
+        let options = TranslateOptions::default().request_id("req-1");
+        let preview = translator
+            .build_request_preview("Hello", &target_lang, Some(&source_lang), &options)
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&preview).unwrap(),
+            json!({
+                "url": "https://api.example.com/v1/chat/completions",
+                "query": [],
+                "headers_redacted": [
+                    ["Authorization", "Bearer ***"],
+                    ["Content-Type", "application/json"],
+                    ["Idempotency-Key", "<generated>"],
+                    ["X-Request-Id", "req-1"]
+                ],
+                "body_json": {
+                    "model": "gpt-4o-mini",
+                    "messages": [
+                        {"role": "system", "content": "Translate en to zh."},
+                        {"role": "user", "content": "Hello"}
+                    ],
+                    "temperature": 0.0
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_request_preview_responses_with_detection() {
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .api_keys(vec!["sk-secret"])
+                .model("gpt-4o")
+                .system_prompt("To {target_lang}.")
+                .api_shape(ApiShape::Responses)
+                .detect_source_language(true)
+                .stream(true)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "ja".parse().unwrap();
+        let options = TranslateOptions::default()
+            .base_url("https://gateway.example.com/v1")
+            .normalize_input(Normalization {
+                collapse_whitespace: true,
+                ..Default::default()
+            });
+        let preview = translator
+            .build_request_preview("Hello   world", &target_lang, None, &options)
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&preview).unwrap(),
+            json!({
+                "url": "https://gateway.example.com/v1/responses",
+                "query": [],
+                "headers_redacted": [
+                    ["Authorization", "Bearer ***"],
+                    ["Content-Type", "application/json"]
+                ],
+                "body_json": {
+                    "model": "gpt-4o",
+                    "input": [
+                        {"role": "system", "content": format!("To ja.{}", DETECT_LANGUAGE_INSTRUCTION)},
+                        {"role": "user", "content": "Hello world"}
+                    ],
+                    "temperature": 0.0,
+                    "stream": true
+                }
+            })
+        );
+
+        // 预览中不会出现 Key 原文，没有 Key 或覆盖的 URL 无效时返回配置错误
+        let text = serde_json::to_string(&preview).unwrap();
+        assert!(!text.contains("sk-secret"));
+        let invalid = TranslateOptions::default().base_url("ftp://gateway.example.com");
+        assert!(matches!(
+            translator.build_request_preview("Hello", &target_lang, None, &invalid),
+            Err(TranslationError::ConfigurationError(_))
+        ));
+        let keyless = OpenAITranslator::new(OpenAIConfig::default());
+        assert!(matches!(
+            keyless.build_request_preview(
+                "Hello",
+                &target_lang,
+                None,
+                &TranslateOptions::default()
+            ),
+            Err(TranslationError::ConfigurationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_request_preview_matches_sent_request() {
+        let server = MockServer::start().await;
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().request_id("req-2");
+        let preview = translator
+            .build_request_preview("Hello", &target_lang, None, &options)
+            .unwrap();
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("Authorization", "Bearer test-key"))
+            .and(header("X-Request-Id", "req-2"))
+            .and(wiremock::matchers::body_json(&preview.body_json))
+            .respond_with(ResponseTemplate::new(200).set_body_string(completion_body("你好")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        assert_eq!(preview.url, format!("{}/chat/completions", server.uri()));
+        assert_eq!(
+            translator
+                .translate_with_options("Hello", &target_lang, None, &options)
+                .await
+                .unwrap(),
+            "你好"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_proxy_mode_explicit_and_none() {
+        let target = MockServer::start().await;
+        let proxy = MockServer::start().await;
+        for server in [&target, &proxy] {
+            Mock::given(method("POST"))
+                .and(path("/chat/completions"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "choices": [{"message": {"role": "assistant", "content": "你好"}}]
+                })))
+                .expect(1)
+                .mount(server)
+                .await;
+        }
+        let translator = |proxy_mode| {
+            OpenAITranslator::new(
+                OpenAIConfig::builder()
+                    .base_url(target.uri())
+                    .api_keys(vec!["test-key"])
+                    .rpm_limit(0)
+                    .proxy_mode(proxy_mode)
+                    .build(),
+            )
+        };
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        // 指定代理时请求发到代理，不使用代理时直接发到 base URL
+        for proxy_mode in [ProxyMode::Explicit(proxy.uri()), ProxyMode::None] {
+            assert_eq!(
+                translator(proxy_mode)
+                    .translate("Hello", &target_lang, None)
+                    .await
+                    .unwrap(),
+                "你好"
+            );
+        }
+        assert!(
+            OpenAIConfig::builder()
+                .api_keys(vec!["test-key"])
+                .proxy_mode(ProxyMode::Explicit("ftp://proxy.example.com".to_string()))
+                .build()
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_detect_before_translate_caches_detection() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(4)
+            .mount(&server)
+            .await;
+        let translator = OpenAITranslator::new(
+            OpenAIConfig::builder()
+                .base_url(server.uri())
+                .api_keys(vec!["test-key"])
+                .rpm_limit(0)
+                .detect_before_translate(true)
+                .build(),
+        );
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let source_lang: LanguageIdentifier = "en".parse().unwrap();
+        let text = "The weather is nice and the sky is blue";
+        for _ in 0..3 {
+            translator
+                .translate(text, &target_lang, None)
+                .await
+                .unwrap();
+        }
+        // 指定了源语言时不检测
+        translator
+            .translate(text, &target_lang, Some(&source_lang))
+            .await
+            .unwrap();
+        let stats = translator.detection_cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.len), (2, 1, 1));
+    }
+
+    /// 请求带 `n` 时以 400 拒绝，否则依次返回预设的译文
+    struct RejectsNResponder {
+        contents: &'static [&'static str],
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl Respond for RejectsNResponder {
+        fn respond(&self, request: &Request) -> ResponseTemplate {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            if body.get("n").is_some() {
+                return ResponseTemplate::new(400).set_body_json(json!({
+                    "error": {
+                        "message": "Unrecognized request argument supplied: n",
+                        "type": "invalid_request_error",
+                        "param": "n",
+                        "code": null
+                    }
+                }));
+            }
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"message": {"role": "assistant", "content": self.contents[call]}}]
+            }))
+        }
+    }
+
+    fn request_bodies(requests: &[Request]) -> Vec<serde_json::Value> {
+        requests
+            .iter()
+            .map(|request| serde_json::from_slice(&request.body).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_alternatives_use_n_parameter() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [
+                    {"index": 0, "message": {"role": "assistant", "content": "你好"}},
+                    {"index": 1, "message": {"role": "assistant", "content": "您好"}},
+                    {"index": 2, "message": {"role": "assistant", "content": " 你好 "}}
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // 多个候选时不使用流式响应
+        let translator = shaped_translator(server.uri(), ApiShape::ChatCompletions, true);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let alternatives = translator
+            .translate_alternatives("Hello", &target_lang, None, 3, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(alternatives.candidates, vec!["你好", "您好"]);
+        assert_eq!(alternatives.requested, 3);
+        assert!(alternatives.is_short());
+
+        let bodies = request_bodies(&server.received_requests().await.unwrap());
+        assert_eq!(bodies[0]["n"], 3);
+        assert!((bodies[0]["temperature"].as_f64().unwrap() - 0.8).abs() < 1e-6);
+        assert!(bodies[0].get("stream").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_alternatives_fall_back_to_sequential_calls() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(RejectsNResponder {
+                contents: &["你好", "您好", "哈喽"],
+                calls: Default::default(),
+            })
+            .expect(4)
+            .mount(&server)
+            .await;
+
+        let translator = shaped_translator(server.uri(), ApiShape::ChatCompletions, false);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let alternatives = translator
+            .translate_alternatives("Hello", &target_lang, None, 3, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(alternatives.candidates, vec!["你好", "您好", "哈喽"]);
+        assert!(!alternatives.is_short());
+
+        // 被拒绝的第一个请求带 `n`，之后的请求逐步提高温度
+        let bodies = request_bodies(&server.received_requests().await.unwrap());
+        assert_eq!(bodies[0]["n"], 3);
+        let temperatures: Vec<f64> = bodies[1..]
+            .iter()
+            .map(|body| {
+                assert!(body.get("n").is_none());
+                body["temperature"].as_f64().unwrap()
+            })
+            .collect();
+        for (actual, expected) in temperatures.iter().zip([0.0, 0.4, 0.8]) {
+            assert!((actual - expected).abs() < 1e-6, "{:?}", temperatures);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_single_alternative_omits_n() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(EchoResponder)
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = shaped_translator(server.uri(), ApiShape::ChatCompletions, false);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let alternatives = translator
+            .translate_alternatives("Hello", &target_lang, None, 1, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(alternatives.candidates, vec!["T:Hello"]);
+
+        let bodies = request_bodies(&server.received_requests().await.unwrap());
+        assert!(bodies[0].get("n").is_none());
+        assert_eq!(bodies[0]["temperature"], 0.0);
+        assert!(matches!(
+            translator
+                .translate_alternatives(
+                    "Hello",
+                    &target_lang,
+                    None,
+                    0,
+                    &TranslateOptions::default()
+                )
+                .await,
+            Err(TranslationError::ConfigurationError(_))
+        ));
+    }
 }
@@ -0,0 +1,743 @@
+//! 翻译配置选项
+//!
+//! [`TranslateOptions`] 中的每个字段都可以不设置。翻译时按
+//! 调用方的选项 > 翻译器配置中的默认选项 > 库的默认值 的顺序合并为
+//! `EffectiveOptions`，合并规则只在本模块中实现一次。
+
+use crate::clock::Clock;
+use crate::error::TranslationError;
+use crate::normalize::Normalization;
+use crate::protect::{self, ProtectPatterns, Protected};
+use crate::tenant::TenantContext;
+use crate::translator::TranslationDetail;
+use regex::Regex;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 原始响应 JSON 默认允许的最大字节数
+const DEFAULT_MAX_RAW_RESPONSE_BYTES: usize = 64 * 1024;
+
+/// W3C Trace Context 信息，用于分布式链路追踪
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    /// `traceparent` 请求头的值
+    pub traceparent: String,
+    /// `tracestate` 请求头的值（可选）
+    pub tracestate: Option<String>,
+}
+
+/// 一次翻译调用的总时限
+///
+/// 与只限制单次 HTTP 尝试的 `timeout` 不同，总时限覆盖等待并发许可、速率限制等待、
+/// 所有重试及其退避。从翻译器开始处理调用时计时，通过 [`crate::TranslationManager`] 调用时
+/// 从管理器开始处理时计时，因此也覆盖等待全局并发许可和 `translate_with_fallback` 依次尝试的
+/// 所有翻译器。批量翻译中的所有条目共用同一个时限。
+///
+/// 开始计时和判断是否到期使用同一个 [`Clock`]：翻译器使用配置的 `clock`，管理器使用
+/// [`crate::TranslationManager::with_clock`] 设置的时钟，两者应设置为同一个时钟。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadline {
+    budget: Duration,
+    /// 到期的时间，开始计时前为 None
+    expires_at: Option<Instant>,
+}
+
+impl Deadline {
+    /// 创建尚未开始计时的总时限
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            expires_at: None,
+        }
+    }
+
+    /// 总时限的长度
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// 从 `now` 开始计时，已开始计时的总时限保持不变
+    pub(crate) fn start(self, now: Instant) -> Self {
+        Self {
+            expires_at: self.expires_at.or_else(|| now.checked_add(self.budget)),
+            ..self
+        }
+    }
+
+    /// 距离到期的剩余时间，尚未开始计时时为完整时限
+    pub(crate) fn remaining(&self, now: Instant) -> Duration {
+        match self.expires_at {
+            Some(expires_at) => expires_at.saturating_duration_since(now),
+            None => self.budget,
+        }
+    }
+
+    /// 从 `now` 再等待 `wait` 会超出时限时返回 [`TranslationError::DeadlineExceeded`]
+    ///
+    /// 到期时刻本身视为已超出
+    pub(crate) fn check(&self, now: Instant, wait: Duration) -> Result<(), TranslationError> {
+        if self.remaining(now) <= wait {
+            return Err(TranslationError::DeadlineExceeded {
+                budget: self.budget,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// HTTP 连接池设置，默认值与 reqwest 的默认值一致
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolOptions {
+    /// 每个主机保留的最大空闲连接数
+    pub max_idle_per_host: usize,
+    /// 空闲连接的保留时间，None 表示一直保留
+    pub idle_timeout: Option<Duration>,
+    /// TCP keepalive 间隔，None 表示不启用
+    pub tcp_keepalive: Option<Duration>,
+    /// 是否不经协商直接使用 HTTP/2
+    pub http2_prior_knowledge: bool,
+    /// 建立连接的超时时间，None 表示不限制
+    pub connect_timeout: Option<Duration>,
+    /// 是否在 TLS 握手时优先协商 HTTP/2，服务端不支持时使用 HTTP/1.1；false 时只使用 HTTP/1.1
+    pub prefer_http2: bool,
+    /// HTTP/2 连接发送 keep-alive ping 的间隔，None 表示不发送
+    pub http2_keep_alive_interval: Option<Duration>,
+    /// 等待 keep-alive ping 响应的超时时间，超时后关闭连接，None 表示使用 reqwest 的默认值
+    pub http2_keep_alive_timeout: Option<Duration>,
+    /// 连接上没有进行中的请求时是否也发送 keep-alive ping
+    pub http2_keep_alive_while_idle: bool,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: usize::MAX,
+            idle_timeout: Some(Duration::from_secs(90)),
+            tcp_keepalive: None,
+            http2_prior_knowledge: false,
+            connect_timeout: None,
+            prefer_http2: false,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
+            http2_keep_alive_while_idle: false,
+        }
+    }
+}
+
+/// HTTP 代理设置
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ProxyMode {
+    /// 使用 `HTTP_PROXY`、`HTTPS_PROXY` 和 `NO_PROXY` 等环境变量中的代理
+    #[default]
+    System,
+    /// 不使用代理，忽略环境变量
+    None,
+    /// 使用指定的代理，`NO_PROXY` 中列出的主机仍然直接连接
+    Explicit(String),
+}
+
+/// 各翻译服务特有的单次请求选项
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProviderOptions {
+    /// 覆盖 OpenAI 翻译器配置中的 base URL
+    pub base_url: Option<String>,
+    /// 覆盖微软翻译器配置中的端点
+    pub endpoint: Option<String>,
+    /// 以 HTML 格式提交文本（微软翻译的 `textType=html`），译文中的标签会被保留
+    pub html: bool,
+}
+
+/// 判断译文是否与原文相同时忽略的差异
+///
+/// 比较前两段文本都按 NFKC 规范化，并去掉所有空白字符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnchangedComparison {
+    /// 是否忽略大小写
+    pub ignore_case: bool,
+    /// 是否忽略标点符号
+    pub ignore_punctuation: bool,
+}
+
+impl Default for UnchangedComparison {
+    fn default() -> Self {
+        Self {
+            ignore_case: true,
+            ignore_punctuation: true,
+        }
+    }
+}
+
+/// 重试耗尽后翻译仍然失败时的处理方式
+#[derive(Debug, Clone, Copy, Default)]
+pub enum FailurePolicy {
+    /// 返回错误
+    #[default]
+    Error,
+    /// 原样返回原文
+    ReturnOriginal,
+    /// 错误满足条件时原样返回原文，否则返回错误
+    ///
+    /// 条件函数收到的是最终的错误，重试耗尽时为 [`TranslationError::MaxRetriesExceeded`]
+    ReturnOriginalIf(fn(&TranslationError) -> bool),
+}
+
+/// 条件函数按地址比较
+impl PartialEq for FailurePolicy {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (FailurePolicy::Error, FailurePolicy::Error) => true,
+            (FailurePolicy::ReturnOriginal, FailurePolicy::ReturnOriginal) => true,
+            (FailurePolicy::ReturnOriginalIf(a), FailurePolicy::ReturnOriginalIf(b)) => {
+                std::ptr::fn_addr_eq(*a, *b)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FailurePolicy {
+    /// 判断翻译因 `error` 失败时是否原样返回原文
+    pub fn returns_original(&self, error: &TranslationError) -> bool {
+        match self {
+            FailurePolicy::Error => false,
+            FailurePolicy::ReturnOriginal => true,
+            FailurePolicy::ReturnOriginalIf(predicate) => predicate(error),
+        }
+    }
+
+    /// 按策略处理翻译结果，需要原样返回时将错误替换为原文透传的结果
+    pub(crate) fn apply(
+        &self,
+        text: &str,
+        result: Result<TranslationDetail, TranslationError>,
+    ) -> Result<TranslationDetail, TranslationError> {
+        match result {
+            Err(error) if self.returns_original(&error) => {
+                Ok(TranslationDetail::pass_through(text, &error))
+            }
+            result => result,
+        }
+    }
+}
+
+/// 一次重试的信息，在每次退避等待之前传给 [`TranslateOptions::on_retry`] 设置的回调
+#[derive(Debug, Clone, Copy)]
+pub struct RetryEvent<'a> {
+    /// 即将进行的是第几次重试，从 1 开始
+    pub attempt: u32,
+    /// 上一次请求的错误
+    pub error: &'a TranslationError,
+    /// 本次重试前的退避时长
+    pub next_delay: Duration,
+    /// 翻译服务名称，例如 `"openai"`、`"microsoft"`
+    pub provider: &'a str,
+}
+
+/// 重试回调，按地址比较
+#[derive(Clone)]
+pub struct RetryHook(Arc<dyn Fn(RetryEvent<'_>) + Send + Sync>);
+
+impl RetryHook {
+    /// 调用回调，回调中的 panic 会被捕获并忽略，不影响重试
+    pub(crate) fn notify(&self, event: RetryEvent<'_>) {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| (self.0)(event)));
+    }
+}
+
+impl std::fmt::Debug for RetryHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RetryHook(..)")
+    }
+}
+
+impl PartialEq for RetryHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// 声明选项字段，同时生成可部分设置的选项、合并后的完整选项和合并逻辑
+///
+/// 每个字段写作 `名称: 类型 = 库的默认值, get 读取方法名;`，新增字段只需在这里加一行
+macro_rules! translate_options {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident => $effective:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field:ident: $ty:ty = $default:expr, get $getter:ident;
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Default, PartialEq)]
+        #[non_exhaustive]
+        pub struct $name {
+            $(
+                $(#[$field_meta])*
+                pub(crate) $field: Option<$ty>,
+            )*
+        }
+
+        /// 按优先级合并后的完整选项，翻译器内部只读取这个类型
+        #[derive(Debug, Clone, PartialEq)]
+        pub(crate) struct $effective {
+            $(
+                $(#[$field_meta])*
+                pub(crate) $field: $ty,
+            )*
+        }
+
+        impl Default for $effective {
+            fn default() -> Self {
+                Self {
+                    $($field: $default,)*
+                }
+            }
+        }
+
+        impl $name {
+            $(
+                /// 读取该选项，未设置时返回库的默认值
+                pub fn $getter(&self) -> $ty {
+                    self.$field.clone().unwrap_or_else(|| $default)
+                }
+            )*
+
+            /// 未设置的字段取 `fallback` 中的值
+            pub(crate) fn or(&self, fallback: &Self) -> Self {
+                Self {
+                    $($field: self.$field.clone().or_else(|| fallback.$field.clone()),)*
+                }
+            }
+        }
+
+        impl $effective {
+            /// 按 调用方 > 翻译器默认 > 库默认 的优先级合并选项
+            pub(crate) fn resolve(
+                call_site: &$name,
+                translator_default: &$name,
+                crate_default: &$effective,
+            ) -> Self {
+                let merged = call_site.or(translator_default);
+                Self {
+                    $($field: merged.$field.unwrap_or_else(|| crate_default.$field.clone()),)*
+                }
+            }
+        }
+    };
+}
+
+translate_options! {
+    /// 翻译配置选项
+    ///
+    /// 通过构造方法设置需要的字段，未设置的字段使用翻译器配置中的默认选项或库的默认值
+    pub struct TranslateOptions => EffectiveOptions {
+        /// 单次 HTTP 尝试的超时时间，不包括排队、速率限制等待和重试，None 表示不超时
+        timeout: Option<Duration> = Some(Duration::from_secs(30)), get get_timeout;
+        /// 最大重试次数
+        max_retries: u32 = 3, get get_max_retries;
+        /// 请求ID，作为 `X-Request-Id` 请求头发送，并记录在详细结果和服务端返回的错误中
+        request_id: Option<String> = None, get get_request_id;
+        /// 链路追踪上下文，作为 `traceparent`/`tracestate` 请求头发送
+        trace_context: Option<TraceContext> = None, get get_trace_context;
+        /// 文本超出翻译服务长度限制时是否自动拆分翻译，false 时直接返回错误
+        auto_split: bool = false, get get_auto_split;
+        /// 响应体允许的最大字节数（解压后），None 表示不限制
+        max_response_bytes: Option<usize> = None, get get_max_response_bytes;
+        /// 流式响应中两个数据块之间允许的最大间隔，None 表示不限制（仅流式请求生效）
+        stall_timeout: Option<Duration> = None, get get_stall_timeout;
+        /// 流式响应停顿或超时时，是否返回已生成的部分译文而不是超时错误（仅流式请求生效）
+        salvage_partial: bool = false, get get_salvage_partial;
+        /// 译文与原文加权长度比例的允许范围（最小值，最大值），None 表示不检查
+        length_ratio_guard: Option<(f64, f64)> = None, get get_length_ratio_guard;
+        /// 是否检查译文为目标语言
+        assert_target_language: bool = false, get get_assert_target_language;
+        /// 覆盖 OpenAI 翻译器配置中的 base URL
+        base_url: Option<String> = None, get get_base_url;
+        /// 覆盖微软翻译器配置中的端点
+        endpoint: Option<String> = None, get get_endpoint;
+        /// 以 HTML 格式提交文本（微软翻译的 `textType=html`），译文中的标签会被保留
+        html: bool = false, get get_html;
+        /// 是否在详细结果中附带翻译服务返回的原始 JSON
+        include_raw_response: bool = false, get get_include_raw_response;
+        /// 原始 JSON 序列化后允许的最大字节数，超出时截断，None 表示不限制
+        max_raw_response_bytes: Option<usize> = Some(DEFAULT_MAX_RAW_RESPONSE_BYTES),
+            get get_max_raw_response_bytes;
+        /// 发送请求前对输入文本的规范化设置，None 表示不处理
+        normalize_input: Option<Normalization> = None, get get_normalize_input;
+        /// 等待并发许可的最长时间，超出时返回 `Overloaded` 错误，None 表示一直等待
+        max_queue_wait: Option<Duration> = None, get get_max_queue_wait;
+        /// 重试耗尽后翻译仍然失败时的处理方式
+        on_failure: FailurePolicy = FailurePolicy::Error, get get_on_failure;
+        /// 带标签的批量翻译中，相同的文本是否只翻译一次
+        dedup: bool = false, get get_dedup;
+        /// 批量翻译中每条文本首次请求前随机等待的上限，None 表示不等待
+        start_jitter: Option<Duration> = None, get get_start_jitter;
+        /// 第一次尝试超过该时长仍未完成时发出一个对冲请求，None 表示不对冲（仅 OpenAI 翻译器生效）
+        hedge_after: Option<Duration> = None, get get_hedge_after;
+        /// 每次重试的退避等待之前调用的回调，None 表示不调用
+        on_retry: Option<RetryHook> = None, get get_on_retry;
+        /// 一次调用的总时限，包括排队、速率限制等待和所有重试，None 表示不限制
+        deadline: Option<Deadline> = None, get get_deadline;
+        /// 翻译期间是否把日期、时间、版本号、货币金额和带分隔符的数字替换为占位符
+        protect_numbers: bool = false, get get_protect_numbers;
+        /// 调用方添加的保护模式，匹配的片段在翻译期间替换为占位符
+        protect_patterns: ProtectPatterns = ProtectPatterns::default(), get get_protect_patterns;
+        /// 调用所属的租户，None 表示使用翻译器配置中的 Key 和限制
+        tenant: Option<TenantContext> = None, get get_tenant;
+        /// 译文与原文相同时是否返回 `NotTranslated` 错误
+        error_on_unchanged: bool = false, get get_error_on_unchanged;
+        /// 判断译文是否与原文相同时忽略的差异
+        unchanged_comparison: UnchangedComparison = UnchangedComparison::default(),
+            get get_unchanged_comparison;
+        /// 一次批量调用允许的输入字符总数，None 表示不限制
+        max_batch_chars: Option<usize> = None, get get_max_batch_chars;
+        /// 批量调用超出 `max_batch_chars` 时是否只翻译预算内的前几条，false 时整批拒绝
+        truncate_to_budget: bool = false, get get_truncate_to_budget;
+    }
+}
+
+impl TranslateOptions {
+    /// 设置超时时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(Some(timeout));
+        self
+    }
+
+    /// 禁用超时
+    pub fn no_timeout(mut self) -> Self {
+        self.timeout = Some(None);
+        self
+    }
+
+    /// 设置最大重试次数
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    /// 禁用重试
+    pub fn no_retries(mut self) -> Self {
+        self.max_retries = Some(0);
+        self
+    }
+
+    /// 设置请求ID
+    pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(Some(request_id.into()));
+        self
+    }
+
+    /// 设置链路追踪上下文
+    ///
+    /// 未设置时，如果启用了 `tracing` feature 且存在活动的 span，
+    /// 将根据当前 span 自动生成 `traceparent`
+    pub fn trace_context(mut self, trace_context: TraceContext) -> Self {
+        self.trace_context = Some(Some(trace_context));
+        self
+    }
+
+    /// 设置是否自动拆分超长文本
+    pub fn auto_split(mut self, auto_split: bool) -> Self {
+        self.auto_split = Some(auto_split);
+        self
+    }
+
+    /// 设置响应体允许的最大字节数
+    ///
+    /// 响应体按块读取，超出限制时立即中止并返回 `ResponseTooLarge` 错误
+    pub fn max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = Some(Some(max_response_bytes));
+        self
+    }
+
+    /// 设置流式响应允许的最大停顿时间
+    ///
+    /// 与 `timeout` 组成软/硬超时：`timeout` 限制整个请求的耗时，
+    /// `stall_timeout` 限制两个数据块之间的间隔
+    pub fn stall_timeout(mut self, stall_timeout: Duration) -> Self {
+        self.stall_timeout = Some(Some(stall_timeout));
+        self
+    }
+
+    /// 设置流式响应中断时是否保留已生成的部分译文
+    ///
+    /// 启用后，停顿或超时时返回 `partial` 为 true 的详细结果；
+    /// 尚未收到任何内容时仍返回 `TimeoutError`
+    pub fn salvage_partial(mut self, salvage_partial: bool) -> Self {
+        self.salvage_partial = Some(salvage_partial);
+        self
+    }
+
+    /// 设置译文长度比例检查
+    ///
+    /// 译文与原文的长度比例（中日韩文字按权重计数）超出范围时视为可重试的
+    /// `SuspiciousOutput` 错误；重试耗尽后返回最接近范围的译文，并在详细结果中
+    /// 标记 `suspicious`。目前仅 OpenAI 翻译器支持
+    pub fn length_ratio_guard(mut self, min_ratio: f64, max_ratio: f64) -> Self {
+        self.length_ratio_guard = Some(Some((min_ratio, max_ratio)));
+        self
+    }
+
+    /// 设置是否检查译文为目标语言
+    ///
+    /// 启用后按文字系统（以及拉丁文字语言的常用词）检查译文，不是目标语言时视为可重试的
+    /// `WrongOutputLanguage` 错误，重试次数仍受 `max_retries` 限制。无法判断的译文
+    /// （例如只有数字，或拉丁文字之间的单词）视为通过。目前仅 OpenAI 翻译器支持
+    pub fn assert_target_language(mut self, assert_target_language: bool) -> Self {
+        self.assert_target_language = Some(assert_target_language);
+        self
+    }
+
+    /// 覆盖本次请求使用的 OpenAI base URL
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(Some(base_url.into()));
+        self
+    }
+
+    /// 覆盖本次请求使用的微软翻译端点
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(Some(endpoint.into()));
+        self
+    }
+
+    /// 设置是否以 HTML 格式提交文本（仅微软翻译器生效）
+    pub fn html(mut self, html: bool) -> Self {
+        self.html = Some(html);
+        self
+    }
+
+    /// 设置是否在详细结果中附带原始响应 JSON
+    ///
+    /// 启用后 [`crate::TranslationDetail::raw`] 包含翻译服务返回的完整 JSON，
+    /// 可用于读取本库尚未建模的字段。流式响应没有完整的 JSON，不会附带
+    pub fn include_raw_response(mut self, include_raw_response: bool) -> Self {
+        self.include_raw_response = Some(include_raw_response);
+        self
+    }
+
+    /// 设置原始响应 JSON 允许的最大字节数
+    ///
+    /// 超出限制时 `raw` 替换为 `{"truncated": true, "bytes": 原始大小, "prefix": 截断后的 JSON 文本}`
+    pub fn max_raw_response_bytes(mut self, max_raw_response_bytes: usize) -> Self {
+        self.max_raw_response_bytes = Some(Some(max_raw_response_bytes));
+        self
+    }
+
+    /// 不限制原始响应 JSON 的大小
+    pub fn no_raw_response_limit(mut self) -> Self {
+        self.max_raw_response_bytes = Some(None);
+        self
+    }
+
+    /// 设置发送请求前对输入文本的规范化
+    ///
+    /// 规范化结果只用于请求，调用方传入的原文不会被修改
+    pub fn normalize_input(mut self, normalization: Normalization) -> Self {
+        self.normalize_input = Some(Some(normalization));
+        self
+    }
+
+    /// 设置等待并发许可的最长时间，超出时返回 `Overloaded` 错误
+    pub fn max_queue_wait(mut self, max_queue_wait: Duration) -> Self {
+        self.max_queue_wait = Some(Some(max_queue_wait));
+        self
+    }
+
+    /// 设置重试耗尽后翻译仍然失败时的处理方式
+    ///
+    /// 原样返回的原文在详细结果中标记为 [`TranslationDetail::passthrough`]
+    pub fn on_failure(mut self, policy: FailurePolicy) -> Self {
+        self.on_failure = Some(policy);
+        self
+    }
+
+    /// 设置带标签的批量翻译中相同的文本是否只翻译一次
+    pub fn dedup(mut self, dedup: bool) -> Self {
+        self.dedup = Some(dedup);
+        self
+    }
+
+    /// 设置批量翻译中每条文本首次请求前随机等待的上限
+    ///
+    /// 每条文本在争用并发许可之前等待 `[0, max)` 内均匀分布的时长，避免大批量请求
+    /// 在同一时刻发出；RPM 限制和请求间隔仍然照常生效
+    pub fn start_jitter(mut self, max: Duration) -> Self {
+        self.start_jitter = Some(Some(max));
+        self
+    }
+
+    /// 设置对冲请求的等待时长，用于降低偶发慢请求造成的尾延迟
+    ///
+    /// 第一次尝试超过 `hedge_after` 仍未完成时，再用另一个 Key（只有一个 Key 时用同一个）
+    /// 发出同样的请求，取先成功的结果并取消另一个；两个请求使用同一个幂等键，都占用并发许可
+    /// 和 RPM 额度。最多额外发出一个请求，重试和多候选等采样请求不对冲；第一次尝试在
+    /// 等待期间失败时照常进入重试，不发出对冲请求
+    pub fn hedge_after(mut self, hedge_after: Duration) -> Self {
+        self.hedge_after = Some(Some(hedge_after));
+        self
+    }
+
+    /// 设置每次重试的退避等待之前调用的回调
+    ///
+    /// 回调只用于观察重试过程（例如记录日志或更新进度），收到的是即将进行的重试次数、
+    /// 上一次的错误和退避时长；回调无法改变重试行为，其中的 panic 会被捕获并忽略
+    pub fn on_retry(mut self, callback: Arc<dyn Fn(RetryEvent<'_>) + Send + Sync>) -> Self {
+        self.on_retry = Some(Some(RetryHook(callback)));
+        self
+    }
+
+    /// 设置一次调用的总时限，包括等待并发许可、速率限制等待、所有重试及其退避
+    ///
+    /// 等待会超出时限时立即返回 [`TranslationError::DeadlineExceeded`]，
+    /// 单次 HTTP 尝试的超时也不会超过剩余的时限
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(Some(Deadline::new(deadline)));
+        self
+    }
+
+    /// 设置翻译期间是否保护数字格式
+    ///
+    /// 启用后日期、时间、版本号、IPv4 地址、货币金额和带分隔符或小数点的数字在发送前
+    /// 替换为占位符，收到译文后还原；译文缺少占位符时返回
+    /// [`TranslationError::ProtectedTextLost`]。详见 [`crate::protect`]
+    pub fn protect_numbers(mut self, protect_numbers: bool) -> Self {
+        self.protect_numbers = Some(protect_numbers);
+        self
+    }
+
+    /// 添加一个保护模式，匹配的片段与 `protect_numbers` 一样在翻译期间替换为占位符
+    ///
+    /// 添加的模式不受 `protect_numbers` 开关影响；设置了该选项的调用会整体替换
+    /// 翻译器默认选项中的模式列表
+    pub fn protect_pattern(mut self, pattern: Regex) -> Self {
+        self.protect_patterns
+            .get_or_insert_with(ProtectPatterns::default)
+            .push(pattern);
+        self
+    }
+
+    /// 设置译文与原文相同时是否返回 [`TranslationError::NotTranslated`] 错误
+    ///
+    /// 未启用时只在详细结果中标记为 [`crate::TranslationKind::Unchanged`]。
+    /// 由 [`crate::TranslationManager`] 检查，`translate_with_fallback` 收到该错误后
+    /// 尝试下一个翻译器
+    pub fn error_on_unchanged(mut self, error_on_unchanged: bool) -> Self {
+        self.error_on_unchanged = Some(error_on_unchanged);
+        self
+    }
+
+    /// 设置判断译文是否与原文相同时忽略的差异，默认忽略大小写和标点符号
+    pub fn unchanged_comparison(mut self, comparison: UnchangedComparison) -> Self {
+        self.unchanged_comparison = Some(comparison);
+        self
+    }
+
+    /// 设置一次批量调用允许的输入字符总数（按 Unicode 字符计）
+    ///
+    /// 批量方法在发出任何请求之前累加所有输入的字符数，超出时返回
+    /// [`TranslationError::BudgetExceeded`]，单条翻译不受影响
+    pub fn max_batch_chars(mut self, max_batch_chars: usize) -> Self {
+        self.max_batch_chars = Some(Some(max_batch_chars));
+        self
+    }
+
+    /// 设置批量调用超出 `max_batch_chars` 时是否只翻译预算内的前几条
+    ///
+    /// 启用后按输入顺序翻译累计字符数不超过预算的条目，之后的条目不再发送，不论 `on_failure`
+    /// 如何都逐条报告为 [`TranslationError::BudgetExceeded`]：逐条的结果中记为错误，详细结果中
+    /// 记为带错误信息的 passthrough，只返回字符串的方法只返回预算内的前几条。同时启用
+    /// `auto_split` 时预算作用于拆分出的片段，跨越预算的条目只翻译预算内的片段并标记为部分翻译。
+    /// 只能整批返回结果的方法（例如 `translate_batch_json`）仍然整批拒绝
+    pub fn truncate_to_budget(mut self, truncate_to_budget: bool) -> Self {
+        self.truncate_to_budget = Some(truncate_to_budget);
+        self
+    }
+
+    /// 设置调用所属的租户
+    ///
+    /// OpenAI 翻译器使用租户的 API Key、速率限制和配额，详见 [`crate::tenant`]；
+    /// 其他翻译器忽略该选项
+    pub fn tenant(mut self, tenant: TenantContext) -> Self {
+        self.tenant = Some(Some(tenant));
+        self
+    }
+
+    /// 设置各翻译服务特有的选项，`provider` 中未设置的字段保持不变
+    pub fn provider(mut self, provider: ProviderOptions) -> Self {
+        if let Some(base_url) = provider.base_url {
+            self = self.base_url(base_url);
+        }
+        if let Some(endpoint) = provider.endpoint {
+            self = self.endpoint(endpoint);
+        }
+        if provider.html {
+            self = self.html(true);
+        }
+        self
+    }
+
+    /// 读取各翻译服务特有的选项
+    pub fn get_provider(&self) -> ProviderOptions {
+        ProviderOptions {
+            base_url: self.get_base_url(),
+            endpoint: self.get_endpoint(),
+            html: self.get_html(),
+        }
+    }
+
+    /// 按 `clock` 的当前时间开始调用方设置的总时限的计时，未设置或已开始计时时原样返回
+    pub(crate) fn start_deadline(mut self, clock: &dyn Clock) -> Self {
+        if let Some(Some(deadline)) = &mut self.deadline {
+            *deadline = deadline.start(clock.now());
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+impl EffectiveOptions {
+    /// 只与库的默认值合并调用方的选项
+    pub(crate) fn from_call_site(options: &TranslateOptions) -> Self {
+        Self::resolve(options, &TranslateOptions::default(), &Self::default())
+    }
+}
+
+impl EffectiveOptions {
+    /// 按 `clock` 的当前时间开始总时限的计时，未设置总时限时原样返回
+    pub(crate) fn start_deadline(self, clock: &dyn Clock) -> Self {
+        match self.deadline {
+            Some(deadline) => Self {
+                deadline: Some(deadline.start(clock.now())),
+                ..self
+            },
+            None => self,
+        }
+    }
+
+    /// 单次 HTTP 尝试的超时：`timeout` 和剩余总时限中较短的一个
+    pub(crate) fn attempt_timeout(&self, clock: &dyn Clock) -> Option<Duration> {
+        let remaining = self
+            .deadline
+            .map(|deadline| deadline.remaining(clock.now()));
+        match (self.timeout, remaining) {
+            (Some(timeout), Some(remaining)) => Some(timeout.min(remaining)),
+            (timeout, remaining) => timeout.or(remaining),
+        }
+    }
+
+    /// 按 `protect_numbers` 和 `protect_patterns` 把需要保护的片段替换为占位符
+    pub(crate) fn protect(&self, text: &str) -> Protected {
+        protect::protect(text, self.protect_numbers, &self.protect_patterns)
+    }
+
+    /// 是否设置了任何保护
+    pub(crate) fn protects(&self) -> bool {
+        self.protect_numbers || !self.protect_patterns.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,347 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::TranslationError;
+    use crate::normalize::{Normalization, NormalizationForm};
+    use crate::options::{
+        EffectiveOptions, FailurePolicy, ProviderOptions, TraceContext, TranslateOptions,
+    };
+    use std::time::Duration;
+
+    fn trace_context(traceparent: &str) -> TraceContext {
+        TraceContext {
+            traceparent: traceparent.to_string(),
+            tracestate: None,
+        }
+    }
+
+    fn is_timeout(error: &TranslationError) -> bool {
+        matches!(error, TranslationError::TimeoutError)
+    }
+
+    fn nfkc() -> Normalization {
+        Normalization {
+            form: NormalizationForm::Nfkc,
+            ..Default::default()
+        }
+    }
+
+    /// 设置了全部字段的调用方选项
+    fn call_site() -> TranslateOptions {
+        TranslateOptions::default()
+            .timeout(Duration::from_secs(5))
+            .max_retries(1)
+            .request_id("call-site")
+            .trace_context(trace_context("call-site"))
+            .auto_split(true)
+            .max_response_bytes(100)
+            .stall_timeout(Duration::from_secs(1))
+            .salvage_partial(true)
+            .length_ratio_guard(0.5, 2.0)
+            .assert_target_language(true)
+            .base_url("http://call-site")
+            .endpoint("http://call-site-endpoint")
+            .html(true)
+            .include_raw_response(true)
+            .max_raw_response_bytes(10)
+            .normalize_input(nfkc())
+            .max_queue_wait(Duration::from_millis(100))
+            .on_failure(FailurePolicy::ReturnOriginal)
+            .dedup(true)
+            .start_jitter(Duration::from_millis(300))
+    }
+
+    /// 设置了全部字段的翻译器默认选项，取值均与调用方不同
+    fn translator_default() -> TranslateOptions {
+        TranslateOptions::default()
+            .no_timeout()
+            .max_retries(7)
+            .request_id("translator")
+            .trace_context(trace_context("translator"))
+            .auto_split(false)
+            .max_response_bytes(200)
+            .stall_timeout(Duration::from_secs(2))
+            .salvage_partial(false)
+            .length_ratio_guard(0.1, 10.0)
+            .assert_target_language(false)
+            .base_url("http://translator")
+            .endpoint("http://translator-endpoint")
+            .html(false)
+            .include_raw_response(false)
+            .no_raw_response_limit()
+            .normalize_input(Normalization::default())
+            .max_queue_wait(Duration::from_millis(200))
+            .on_failure(FailurePolicy::ReturnOriginalIf(is_timeout))
+            .dedup(false)
+            .start_jitter(Duration::from_millis(600))
+    }
+
+    #[test]
+    fn test_call_site_wins() {
+        let resolved = EffectiveOptions::resolve(
+            &call_site(),
+            &translator_default(),
+            &EffectiveOptions::default(),
+        );
+        assert_eq!(
+            resolved,
+            EffectiveOptions {
+                timeout: Some(Duration::from_secs(5)),
+                max_retries: 1,
+                request_id: Some("call-site".to_string()),
+                trace_context: Some(trace_context("call-site")),
+                auto_split: true,
+                max_response_bytes: Some(100),
+                stall_timeout: Some(Duration::from_secs(1)),
+                salvage_partial: true,
+                length_ratio_guard: Some((0.5, 2.0)),
+                assert_target_language: true,
+                base_url: Some("http://call-site".to_string()),
+                endpoint: Some("http://call-site-endpoint".to_string()),
+                html: true,
+                include_raw_response: true,
+                max_raw_response_bytes: Some(10),
+                normalize_input: Some(nfkc()),
+                max_queue_wait: Some(Duration::from_millis(100)),
+                on_failure: FailurePolicy::ReturnOriginal,
+                dedup: true,
+                start_jitter: Some(Duration::from_millis(300)),
+                hedge_after: None,
+                on_retry: None,
+                deadline: None,
+                protect_numbers: false,
+                protect_patterns: Default::default(),
+                tenant: None,
+                error_on_unchanged: false,
+                unchanged_comparison: Default::default(),
+                max_batch_chars: None,
+                truncate_to_budget: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_translator_default_used_when_unset() {
+        let resolved = EffectiveOptions::resolve(
+            &TranslateOptions::default(),
+            &translator_default(),
+            &EffectiveOptions::default(),
+        );
+        assert_eq!(
+            resolved,
+            EffectiveOptions {
+                timeout: None,
+                max_retries: 7,
+                request_id: Some("translator".to_string()),
+                trace_context: Some(trace_context("translator")),
+                auto_split: false,
+                max_response_bytes: Some(200),
+                stall_timeout: Some(Duration::from_secs(2)),
+                salvage_partial: false,
+                length_ratio_guard: Some((0.1, 10.0)),
+                assert_target_language: false,
+                base_url: Some("http://translator".to_string()),
+                endpoint: Some("http://translator-endpoint".to_string()),
+                html: false,
+                include_raw_response: false,
+                max_raw_response_bytes: None,
+                normalize_input: Some(Normalization::default()),
+                max_queue_wait: Some(Duration::from_millis(200)),
+                on_failure: FailurePolicy::ReturnOriginalIf(is_timeout),
+                dedup: false,
+                start_jitter: Some(Duration::from_millis(600)),
+                hedge_after: None,
+                on_retry: None,
+                deadline: None,
+                protect_numbers: false,
+                protect_patterns: Default::default(),
+                tenant: None,
+                error_on_unchanged: false,
+                unchanged_comparison: Default::default(),
+                max_batch_chars: None,
+                truncate_to_budget: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_crate_default_used_when_nothing_set() {
+        let unset = TranslateOptions::default();
+        let resolved = EffectiveOptions::resolve(&unset, &unset, &EffectiveOptions::default());
+        assert_eq!(
+            resolved,
+            EffectiveOptions {
+                timeout: Some(Duration::from_secs(30)),
+                max_retries: 3,
+                request_id: None,
+                trace_context: None,
+                auto_split: false,
+                max_response_bytes: None,
+                stall_timeout: None,
+                salvage_partial: false,
+                length_ratio_guard: None,
+                assert_target_language: false,
+                base_url: None,
+                endpoint: None,
+                html: false,
+                include_raw_response: false,
+                max_raw_response_bytes: Some(64 * 1024),
+                normalize_input: None,
+                max_queue_wait: None,
+                on_failure: FailurePolicy::Error,
+                dedup: false,
+                start_jitter: None,
+                hedge_after: None,
+                on_retry: None,
+                deadline: None,
+                protect_numbers: false,
+                protect_patterns: Default::default(),
+                tenant: None,
+                error_on_unchanged: false,
+                unchanged_comparison: Default::default(),
+                max_batch_chars: None,
+                truncate_to_budget: false,
+            }
+        );
+
+        // 库的默认值同样可以替换
+        let crate_default = EffectiveOptions {
+            max_retries: 9,
+            ..Default::default()
+        };
+        assert_eq!(
+            EffectiveOptions::resolve(&unset, &unset, &crate_default).max_retries,
+            9
+        );
+    }
+
+    #[test]
+    fn test_fields_resolved_independently() {
+        // 调用方只设置部分字段，其余字段继续取翻译器默认值
+        let call_site = TranslateOptions::default()
+            .max_retries(0)
+            .base_url("http://call-site");
+        let resolved = EffectiveOptions::resolve(
+            &call_site,
+            &translator_default(),
+            &EffectiveOptions::default(),
+        );
+        assert_eq!(resolved.max_retries, 0);
+        assert_eq!(resolved.base_url.as_deref(), Some("http://call-site"));
+        assert_eq!(
+            resolved.endpoint.as_deref(),
+            Some("http://translator-endpoint")
+        );
+        assert_eq!(resolved.timeout, None);
+        assert_eq!(resolved.request_id.as_deref(), Some("translator"));
+
+        // 显式禁用超时也算设置过，不会被默认值覆盖
+        let resolved = EffectiveOptions::resolve(
+            &TranslateOptions::default().no_timeout(),
+            &TranslateOptions::default().timeout(Duration::from_secs(9)),
+            &EffectiveOptions::default(),
+        );
+        assert_eq!(resolved.timeout, None);
+    }
+
+    #[test]
+    fn test_getters_fall_back_to_crate_default() {
+        let options = TranslateOptions::default();
+        assert_eq!(options.get_timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(options.get_max_retries(), 3);
+        assert!(!options.get_auto_split());
+        assert_eq!(options.get_provider(), ProviderOptions::default());
+
+        let options = call_site();
+        assert_eq!(options.get_timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(options.get_request_id().as_deref(), Some("call-site"));
+        assert_eq!(options.get_length_ratio_guard(), Some((0.5, 2.0)));
+        assert_eq!(
+            options.get_provider(),
+            ProviderOptions {
+                base_url: Some("http://call-site".to_string()),
+                endpoint: Some("http://call-site-endpoint".to_string()),
+                html: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_failure_policy_returns_original() {
+        let timeout = TranslationError::TimeoutError;
+        let auth = TranslationError::AuthenticationError("denied".to_string());
+        assert!(!FailurePolicy::Error.returns_original(&timeout));
+        assert!(FailurePolicy::ReturnOriginal.returns_original(&auth));
+        let policy = FailurePolicy::ReturnOriginalIf(is_timeout);
+        assert!(policy.returns_original(&timeout));
+        assert!(!policy.returns_original(&auth));
+
+        let detail = policy.apply("Hello", Err(timeout)).unwrap();
+        assert_eq!(detail.text, "Hello");
+        assert!(detail.passthrough);
+        assert_eq!(detail.provider_metadata["error"], "Request timeout");
+        assert!(matches!(
+            policy.apply("Hello", Err(auth)),
+            Err(TranslationError::AuthenticationError(_))
+        ));
+        assert_eq!(
+            TranslateOptions::default().get_on_failure(),
+            FailurePolicy::Error
+        );
+    }
+
+    #[test]
+    fn test_provider_sets_only_given_fields() {
+        let options = TranslateOptions::default()
+            .endpoint("http://explicit")
+            .provider(ProviderOptions {
+                base_url: Some("http://canary".to_string()),
+                ..Default::default()
+            });
+        assert_eq!(options.get_base_url().as_deref(), Some("http://canary"));
+        assert_eq!(options.get_endpoint().as_deref(), Some("http://explicit"));
+        assert_eq!(options.html, None);
+    }
+
+    translate_options! {
+        /// 模拟将来新增了一个字段的选项
+        pub struct FutureOptions => FutureEffectiveOptions {
+            /// 已有字段
+            max_retries: u32 = 3, get get_max_retries;
+            /// 新增字段
+            quality_tier: Option<u8> = Some(1), get get_quality_tier;
+        }
+    }
+
+    #[test]
+    fn test_new_field_resolved_by_same_rules() {
+        let crate_default = FutureEffectiveOptions::default();
+        let call_site = FutureOptions {
+            quality_tier: Some(Some(3)),
+            ..Default::default()
+        };
+        let translator_default = FutureOptions {
+            max_retries: Some(5),
+            quality_tier: Some(None),
+        };
+
+        let resolved =
+            FutureEffectiveOptions::resolve(&call_site, &translator_default, &crate_default);
+        assert_eq!(resolved.quality_tier, Some(3));
+        assert_eq!(resolved.max_retries, 5);
+
+        let resolved = FutureEffectiveOptions::resolve(
+            &FutureOptions::default(),
+            &translator_default,
+            &crate_default,
+        );
+        assert_eq!(resolved.quality_tier, None);
+
+        let unset = FutureOptions::default();
+        let resolved = FutureEffectiveOptions::resolve(&unset, &unset, &crate_default);
+        assert_eq!(resolved.quality_tier, Some(1));
+        assert_eq!(resolved.max_retries, 3);
+        assert_eq!(unset.get_quality_tier(), Some(1));
+        assert_eq!(call_site.get_quality_tier(), Some(3));
+        assert_eq!(translator_default.get_max_retries(), 5);
+    }
+}
@@ -0,0 +1,202 @@
+//! 发送前的内容策略
+//!
+//! 通过 [`crate::TranslationManager::with_content_policy`] 设置的 [`ContentPolicy`]
+//! 在每次翻译（包括批量翻译中的每一项）发送给翻译器之前检查原文：
+//!
+//! * [`PolicyDecision::Allow`]：原样翻译；
+//! * [`PolicyDecision::Redact`]：把指定的片段替换为 `⟪A⟫` 形式的占位符后翻译，
+//!   收到译文后把占位符还原为未经翻译的原文，被遮盖的内容不会离开本机；
+//! * [`PolicyDecision::Block`]：不发送任何请求，返回 [`crate::TranslationError::PolicyViolation`]。
+//!
+//! [`PiiDetector`] 是基于正则表达式的默认实现，识别电子邮件地址、电话号码和证件号码，
+//! 可以添加模式，也可以用自己的 [`ContentPolicy`] 完全替代。
+
+use crate::error::TranslationError;
+use crate::protect::{self, Protected};
+use regex::Regex;
+use std::cmp::Reverse;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// 遮盖片段的占位符的起始字符，与 `protect_numbers` 的占位符不同，两者可以同时使用
+const REDACTION_OPEN: char = '⟪';
+/// 遮盖片段的占位符的结束字符
+const REDACTION_CLOSE: char = '⟫';
+
+/// 内容策略对一段原文的决定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyDecision {
+    /// 允许发送
+    Allow,
+    /// 遮盖这些字节范围后发送，范围必须位于字符边界上，可以重叠
+    Redact(Vec<Range<usize>>),
+    /// 禁止发送，附带原因
+    Block(String),
+}
+
+/// 发送给翻译器之前检查原文的策略
+pub trait ContentPolicy: Send + Sync {
+    /// 检查一段原文
+    fn check(&self, text: &str) -> PolicyDecision;
+}
+
+/// 电子邮件地址、电话号码和证件号码的内置模式
+const BUILTIN_PATTERNS: &[(&str, &str)] = &[
+    (
+        "email address",
+        r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}",
+    ),
+    // 带国家代码的号码、北美格式的号码和中国大陆的手机号
+    (
+        "phone number",
+        r"\+\d{1,3}[\s.-]?(?:\(\d{1,4}\)|\d{1,4})(?:[\s.-]?\d{2,4}){2,4}(?-u:\b)",
+    ),
+    (
+        "phone number",
+        r"(?:\(\d{3}\)\s?|(?-u:\b)\d{3}[\s.-])\d{3}[\s.-]\d{4}(?-u:\b)",
+    ),
+    ("phone number", r"(?-u:\b)1[3-9]\d{9}(?-u:\b)"),
+    // 中国居民身份证号和美国社会安全号
+    ("ID number", r"(?-u:\b)\d{17}[\dXx](?-u:\b)"),
+    ("ID number", r"(?-u:\b)\d{3}-\d{2}-\d{4}(?-u:\b)"),
+];
+
+fn builtin_patterns() -> &'static [(String, Regex)] {
+    static PATTERNS: OnceLock<Vec<(String, Regex)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        BUILTIN_PATTERNS
+            .iter()
+            .map(|(kind, pattern)| {
+                (
+                    kind.to_string(),
+                    Regex::new(pattern).expect("builtin pattern is valid"),
+                )
+            })
+            .collect()
+    })
+}
+
+/// 基于正则表达式识别个人信息的内容策略
+///
+/// 默认遮盖匹配的片段，[`PiiDetector::block`] 改为禁止发送包含个人信息的文本
+#[derive(Debug, Clone)]
+pub struct PiiDetector {
+    /// （信息类别，模式）
+    patterns: Vec<(String, Regex)>,
+    block: bool,
+}
+
+impl PiiDetector {
+    /// 使用内置的电子邮件地址、电话号码和证件号码模式
+    pub fn new() -> Self {
+        Self {
+            patterns: builtin_patterns().to_vec(),
+            block: false,
+        }
+    }
+
+    /// 不带任何模式，只使用 [`PiiDetector::pattern`] 添加的模式
+    pub fn empty() -> Self {
+        Self {
+            patterns: Vec::new(),
+            block: false,
+        }
+    }
+
+    /// 添加一个模式，`kind` 是禁止发送时原因中的信息类别，例如 `"employee ID"`
+    pub fn pattern(mut self, kind: impl Into<String>, pattern: Regex) -> Self {
+        self.patterns.push((kind.into(), pattern));
+        self
+    }
+
+    /// 为 true 时禁止发送包含个人信息的文本，而不是遮盖后发送
+    pub fn block(mut self, block: bool) -> Self {
+        self.block = block;
+        self
+    }
+}
+
+impl Default for PiiDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContentPolicy for PiiDetector {
+    fn check(&self, text: &str) -> PolicyDecision {
+        let mut ranges = Vec::new();
+        for (kind, pattern) in &self.patterns {
+            let mut found = pattern
+                .find_iter(text)
+                .filter(|found| !found.is_empty())
+                .peekable();
+            if self.block && found.peek().is_some() {
+                return PolicyDecision::Block(format!("{} detected", kind));
+            }
+            ranges.extend(found.map(|found| found.range()));
+        }
+        if ranges.is_empty() {
+            return PolicyDecision::Allow;
+        }
+        // 从左到右，同一位置开始的匹配中较长的优先，去掉与已选匹配重叠的部分
+        ranges.sort_by_key(|range| (range.start, Reverse(range.end)));
+        let mut last = 0;
+        ranges.retain(|range| {
+            let keep = range.start >= last;
+            if keep {
+                last = range.end;
+            }
+            keep
+        });
+        PolicyDecision::Redact(ranges)
+    }
+}
+
+/// 按策略检查原文，允许发送时返回 None，需要遮盖时返回遮盖后的文本
+pub(crate) fn apply(
+    policy: &dyn ContentPolicy,
+    text: &str,
+) -> Result<Option<Protected>, TranslationError> {
+    match policy.check(text) {
+        PolicyDecision::Allow => Ok(None),
+        PolicyDecision::Block(reason) => Err(TranslationError::PolicyViolation { reason }),
+        PolicyDecision::Redact(ranges) => {
+            if let Some(range) = ranges.iter().find(|range| {
+                range.start > range.end
+                    || !text.is_char_boundary(range.start)
+                    || !text.is_char_boundary(range.end)
+            }) {
+                // 无法确定要遮盖的内容时不发送
+                return Err(TranslationError::ConfigurationError(format!(
+                    "Content policy returned an invalid range {:?} for a text of {} bytes",
+                    range,
+                    text.len()
+                )));
+            }
+            let ranges = ranges
+                .into_iter()
+                .filter(|range| !range.is_empty())
+                .collect();
+            Ok(Some(protect::mask(
+                text,
+                ranges,
+                REDACTION_OPEN,
+                REDACTION_CLOSE,
+            )))
+        }
+    }
+}
+
+/// 把译文中的占位符还原为被遮盖的原文
+///
+/// 缺少占位符时返回 [`TranslationError::ProtectedTextLost`]，错误中不包含被遮盖的原文
+pub(crate) fn restore(redacted: &Protected, translated: &str) -> Result<String, TranslationError> {
+    redacted
+        .restore(translated)
+        .map_err(|_| TranslationError::ProtectedTextLost {
+            text: "<redacted>".to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests;
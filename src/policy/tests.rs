@@ -0,0 +1,227 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::TranslationError;
+    use crate::manager::TranslationManager;
+    use crate::options::{FailurePolicy, TranslateOptions};
+    use crate::policy::{ContentPolicy, PiiDetector, PolicyDecision};
+    use crate::testing::MockTranslator;
+    use crate::translator::Translator;
+    use regex::Regex;
+    use std::sync::{Arc, Mutex};
+    use unic_langid::LanguageIdentifier;
+
+    /// 记录收到的文本，译文为 `译：` 加原文；`drop_placeholders` 时去掉所有占位符
+    #[derive(Clone, Default)]
+    struct RecordingTranslator {
+        received: Arc<Mutex<Vec<String>>>,
+        drop_placeholders: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl Translator for RecordingTranslator {
+        async fn translate_with_options(
+            &self,
+            text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            self.received.lock().unwrap().push(text.to_string());
+            let translated = format!("译：{}", text);
+            if self.drop_placeholders {
+                let placeholder = Regex::new("⟪[A-Z]+⟫").unwrap();
+                return Ok(placeholder.replace_all(&translated, "").into_owned());
+            }
+            Ok(translated)
+        }
+    }
+
+    /// 把固定的字节范围交给管理器的策略
+    struct FixedRanges(Vec<std::ops::Range<usize>>);
+
+    impl ContentPolicy for FixedRanges {
+        fn check(&self, _text: &str) -> PolicyDecision {
+            PolicyDecision::Redact(self.0.clone())
+        }
+    }
+
+    fn redacted_parts(text: &str) -> Vec<&str> {
+        match PiiDetector::new().check(text) {
+            PolicyDecision::Redact(ranges) => {
+                ranges.into_iter().map(|range| &text[range]).collect()
+            }
+            PolicyDecision::Allow => Vec::new(),
+            PolicyDecision::Block(reason) => panic!("unexpected block: {}", reason),
+        }
+    }
+
+    #[test]
+    fn test_pii_detector() {
+        let cases: &[(&str, &[&str])] = &[
+            (
+                "Mail jane.doe+tag@example.co.uk now",
+                &["jane.doe+tag@example.co.uk"],
+            ),
+            (
+                "Call +1 (415) 555-0132 or +86 138 1234 5678",
+                &["+1 (415) 555-0132", "+86 138 1234 5678"],
+            ),
+            (
+                "Office: (212) 555-0100, fax 212-555-0199",
+                &["(212) 555-0100", "212-555-0199"],
+            ),
+            (
+                "手机13812345678，身份证11010519491231002X",
+                &["13812345678", "11010519491231002X"],
+            ),
+            ("SSN 123-45-6789", &["123-45-6789"]),
+            // 日期、版本号和普通数字不视为个人信息
+            ("Released 2024-05-03, v1.2.3, 1,234,567 users", &[]),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(&redacted_parts(text), expected, "checking {:?}", text);
+        }
+
+        let detector =
+            PiiDetector::empty().pattern("employee ID", Regex::new(r"EMP-\d{6}").unwrap());
+        assert_eq!(
+            detector.check("Ask EMP-004217 or EMP-000001, not bob@example.com"),
+            PolicyDecision::Redact(vec![4..14, 18..28])
+        );
+        assert_eq!(
+            detector.block(true).check("Ask EMP-004217"),
+            PolicyDecision::Block("employee ID detected".into())
+        );
+        assert_eq!(
+            PiiDetector::new()
+                .block(true)
+                .check("no personal data here"),
+            PolicyDecision::Allow
+        );
+    }
+
+    #[tokio::test]
+    async fn test_manager_applies_each_decision() {
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+        let translator = RecordingTranslator::default();
+        let mut manager = TranslationManager::new();
+        manager.add_translator("recording", Box::new(translator.clone()));
+        let manager = manager.with_content_policy(PiiDetector::new());
+
+        // 遮盖后发送，多字节文本中的原文在译文中原样还原
+        let text = "请联系张伟：zhang.wei@example.cn，手机 13812345678。";
+        assert_eq!(
+            manager
+                .translate("recording", text, &zh, None)
+                .await
+                .unwrap(),
+            format!("译：{}", text)
+        );
+        let detail = manager
+            .translate_detailed_with_options(
+                "recording",
+                text,
+                &zh,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(detail.text, format!("译：{}", text));
+        assert_eq!(
+            translator.received.lock().unwrap().clone(),
+            vec!["请联系张伟：⟪A⟫，手机 ⟪B⟫。"; 2]
+        );
+
+        // 不含个人信息的文本原样发送
+        manager
+            .translate("recording", "早上好", &zh, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            translator.received.lock().unwrap().last().unwrap(),
+            "早上好"
+        );
+
+        // 译文丢失占位符时报错，错误中不包含被遮盖的原文
+        let mut manager = TranslationManager::new();
+        manager.add_translator(
+            "lossy",
+            Box::new(RecordingTranslator {
+                drop_placeholders: true,
+                ..Default::default()
+            }),
+        );
+        let manager = manager.with_content_policy(PiiDetector::new());
+        match manager.translate("lossy", text, &zh, None).await {
+            Err(TranslationError::ProtectedTextLost { text }) => assert_eq!(text, "<redacted>"),
+            other => panic!("expected ProtectedTextLost, got {:?}", other),
+        }
+
+        // 禁止发送时不调用翻译器，也不按 on_failure 返回原文
+        let mock = MockTranslator::new();
+        let mut manager = TranslationManager::new();
+        manager.add_translator("mock", Box::new(mock.clone()));
+        let manager = manager.with_content_policy(PiiDetector::new().block(true));
+        let options = TranslateOptions::default().on_failure(FailurePolicy::ReturnOriginal);
+        match manager
+            .translate_with_options("mock", "Mail bob@example.com", &zh, None, &options)
+            .await
+        {
+            Err(TranslationError::PolicyViolation { reason }) => {
+                assert_eq!(reason, "email address detected")
+            }
+            other => panic!("expected PolicyViolation, got {:?}", other),
+        }
+        assert_eq!(mock.calls(), 0);
+
+        // 批量翻译逐项应用策略
+        let results = manager
+            .translate_batch_tagged(
+                "mock",
+                vec![
+                    (1, "Hello".to_string()),
+                    (2, "Call (415) 555-0132".to_string()),
+                ],
+                &zh,
+                None,
+                &TranslateOptions::default(),
+            )
+            .await;
+        assert_eq!(results[0].1.as_deref().unwrap(), "Translated: Hello");
+        assert!(matches!(
+            results[1].1,
+            Err(TranslationError::PolicyViolation { .. })
+        ));
+        assert_eq!(mock.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_custom_policy_ranges() {
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+        let translator = RecordingTranslator::default();
+        let mut manager = TranslationManager::new();
+        manager.add_translator("recording", Box::new(translator.clone()));
+
+        // 重叠的范围合并为一个占位符，空范围被忽略
+        let text = "姓名：王小明，工号 42";
+        let name = "姓名：".len().."姓名：王小明".len();
+        let manager_with = |ranges| manager.clone().with_content_policy(FixedRanges(ranges));
+        let result = manager_with(vec![name.clone(), name.start + 3..name.end, 0..0])
+            .translate("recording", text, &zh, None)
+            .await
+            .unwrap();
+        assert_eq!(result, format!("译：{}", text));
+        assert_eq!(translator.received.lock().unwrap()[0], "姓名：⟪A⟫，工号 42");
+
+        // 不在字符边界上的范围不发送
+        let result = manager_with(vec![0..0, name.start + 1..name.end])
+            .translate("recording", text, &zh, None)
+            .await;
+        assert!(matches!(
+            result,
+            Err(TranslationError::ConfigurationError(_))
+        ));
+        assert_eq!(translator.received.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,235 @@
+//! 译文后处理
+//!
+//! [`PostProcessor`] 在翻译成功后按注册顺序依次处理译文，可以注册到
+//! [`crate::TranslationManager`] 上对所有翻译器生效，也可以只对某个翻译器生效。
+//! 内置了中文标点规范化 [`CjkPunctuationNormalizer`] 和空白规范化
+//! [`WhitespaceNormalizer`]。
+
+use crate::guard;
+use std::sync::Arc;
+use unic_langid::LanguageIdentifier;
+
+/// 译文后处理器
+///
+/// 处理器应当是幂等的：对已经处理过的译文再处理一次不应产生变化。管理器对每次
+/// 成功的翻译只调用一次处理器，但译文可能来自已经处理过的结果（例如调用方自行
+/// 缓存后再次提交），内置的处理器都满足这一要求。
+pub trait PostProcessor: Send + Sync {
+    /// 处理译文
+    ///
+    /// # 参数
+    ///
+    /// * `text` - 译文
+    /// * `target` - 目标语言标识符
+    ///
+    /// # 返回值
+    ///
+    /// 返回处理后的译文
+    fn process(&self, text: String, target: &LanguageIdentifier) -> String;
+}
+
+/// 依次应用多个后处理器
+pub(crate) fn apply(
+    processors: &[Arc<dyn PostProcessor>],
+    text: String,
+    target: &LanguageIdentifier,
+) -> String {
+    processors
+        .iter()
+        .fold(text, |text, processor| processor.process(text, target))
+}
+
+/// 中文标点规范化
+///
+/// 目标语言为中文时，把紧跟在中文后的半角标点替换为全角标点并去掉其后的空格，
+/// `...` 和 `…` 统一为 `……`，直引号按出现顺序配对为弯引号（单词内的撇号保持不变）。
+/// 其他目标语言的译文原样返回。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CjkPunctuationNormalizer;
+
+impl CjkPunctuationNormalizer {
+    /// 规范化中文文本中的标点
+    pub fn normalize(text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut output = String::with_capacity(text.len());
+        let mut double_open = false;
+        let mut single_open = false;
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let prev = output.chars().last();
+            let next = chars.get(i + 1).copied();
+            let after_cjk = prev.is_some_and(is_cjk_context);
+
+            if matches!(c, '.' | '…') {
+                let run = chars[i..]
+                    .iter()
+                    .take_while(|&&c| matches!(c, '.' | '…'))
+                    .count();
+                let ellipsis = run >= 3 || chars[i..i + run].contains(&'…');
+                if after_cjk && ellipsis {
+                    output.push_str("……");
+                    i += run;
+                    continue;
+                }
+                if after_cjk && run == 1 {
+                    output.push('。');
+                    i = skip_spaces(&chars, i + 1);
+                    continue;
+                }
+                output.extend(&chars[i..i + run]);
+                i += run;
+                continue;
+            }
+
+            let converted = match c {
+                ',' if after_cjk => Some('，'),
+                '!' if after_cjk => Some('！'),
+                '?' if after_cjk => Some('？'),
+                ':' if after_cjk => Some('：'),
+                ';' if after_cjk => Some('；'),
+                ')' if after_cjk => Some('）'),
+                '(' if next.is_some_and(is_cjk_context) => {
+                    trim_spaces_after_cjk(&mut output);
+                    Some('（')
+                }
+                '"' => {
+                    double_open = !double_open;
+                    if double_open {
+                        trim_spaces_after_cjk(&mut output);
+                        output.push('“');
+                        i = skip_spaces(&chars, i + 1);
+                        continue;
+                    }
+                    trim_trailing_spaces(&mut output);
+                    Some('”')
+                }
+                '\'' if !(prev.is_some_and(|p| p.is_ascii_alphanumeric())
+                    && next.is_some_and(|n| n.is_ascii_alphanumeric())) =>
+                {
+                    single_open = !single_open;
+                    if single_open {
+                        output.push('‘');
+                        i = skip_spaces(&chars, i + 1);
+                        continue;
+                    }
+                    trim_trailing_spaces(&mut output);
+                    Some('’')
+                }
+                _ => None,
+            };
+            match converted {
+                Some(converted) => {
+                    output.push(converted);
+                    // 全角标点自带间距，去掉其后紧跟中文的空格
+                    let after = skip_spaces(&chars, i + 1);
+                    i = if chars.get(after).is_some_and(|&n| is_cjk_context(n)) {
+                        after
+                    } else {
+                        i + 1
+                    };
+                }
+                None => {
+                    output.push(c);
+                    i += 1;
+                }
+            }
+        }
+        output
+    }
+}
+
+impl PostProcessor for CjkPunctuationNormalizer {
+    fn process(&self, text: String, target: &LanguageIdentifier) -> String {
+        if target.language.as_str() != "zh" {
+            return text;
+        }
+        Self::normalize(&text)
+    }
+}
+
+/// 是否为中日韩文字或全角标点，其后的半角标点需要转换
+fn is_cjk_context(c: char) -> bool {
+    guard::char_weight(c) > 1.0
+        || matches!(c, '\u{3000}'..='\u{303F}' | '\u{FF00}'..='\u{FFEF}' | '“' | '”' | '‘' | '’' | '…')
+}
+
+/// 跳过从 `start` 开始的半角空格，返回第一个非空格字符的位置
+fn skip_spaces(chars: &[char], start: usize) -> usize {
+    start
+        + chars[start.min(chars.len())..]
+            .iter()
+            .take_while(|&&c| c == ' ')
+            .count()
+}
+
+/// 去掉输出末尾的半角空格
+fn trim_trailing_spaces(output: &mut String) {
+    output.truncate(output.trim_end_matches(' ').len());
+}
+
+/// 空格前是中文时去掉输出末尾的半角空格
+fn trim_spaces_after_cjk(output: &mut String) {
+    let trimmed = output.trim_end_matches(' ');
+    if trimmed.chars().last().is_some_and(is_cjk_context) {
+        output.truncate(trimmed.len());
+    }
+}
+
+/// 空白规范化
+///
+/// 行内连续的空白合并为一个，包含不换行空格时保留不换行空格，
+/// 去掉行尾空白，行首缩进、换行和全角空格保持不变。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WhitespaceNormalizer;
+
+impl WhitespaceNormalizer {
+    /// 规范化文本中的空白
+    pub fn normalize(text: &str) -> String {
+        let mut output = String::with_capacity(text.len());
+        for line in text.split_inclusive('\n') {
+            let content = line.trim_end_matches(['\n', '\r']);
+            let ending = &line[content.len()..];
+            let indent_len = content.len() - content.trim_start_matches(is_inline_space).len();
+            output.push_str(&content[..indent_len]);
+
+            let mut run: Option<char> = None;
+            for c in content[indent_len..].chars() {
+                if is_inline_space(c) {
+                    // 整段空白中只要有不换行空格，就保留不换行空格
+                    run = match run {
+                        Some(space) if is_no_break_space(space) => Some(space),
+                        _ if is_no_break_space(c) => Some(c),
+                        _ => Some(' '),
+                    };
+                    continue;
+                }
+                if let Some(space) = run.take() {
+                    output.push(space);
+                }
+                output.push(c);
+            }
+            output.push_str(ending);
+        }
+        output
+    }
+}
+
+impl PostProcessor for WhitespaceNormalizer {
+    fn process(&self, text: String, _target: &LanguageIdentifier) -> String {
+        Self::normalize(&text)
+    }
+}
+
+/// 行内空白（不含换行和全角空格）
+fn is_inline_space(c: char) -> bool {
+    c.is_whitespace() && !matches!(c, '\n' | '\r' | '\u{3000}')
+}
+
+/// 不换行空格
+fn is_no_break_space(c: char) -> bool {
+    matches!(c, '\u{00A0}' | '\u{2007}' | '\u{202F}')
+}
+
+#[cfg(test)]
+mod tests;
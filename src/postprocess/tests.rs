@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod tests {
+    use crate::postprocess::{CjkPunctuationNormalizer, PostProcessor, WhitespaceNormalizer};
+    use unic_langid::LanguageIdentifier;
+
+    fn zh() -> LanguageIdentifier {
+        "zh-Hans".parse().unwrap()
+    }
+
+    #[test]
+    fn test_cjk_punctuation_basic() {
+        let cases = [
+            ("你好, 世界!", "你好，世界！"),
+            ("真的? 是的.", "真的？是的。"),
+            ("注意: 请勿吸烟; 谢谢", "注意：请勿吸烟；谢谢"),
+            ("苹果 (水果) 很甜", "苹果（水果）很甜"),
+            // 数字、网址和英文中的标点保持不变
+            (
+                "价格是3.5元, 访问example.com",
+                "价格是3.5元，访问example.com",
+            ),
+            ("Hello, world.", "Hello, world."),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                CjkPunctuationNormalizer::normalize(input),
+                expected,
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_cjk_punctuation_ellipsis() {
+        assert_eq!(CjkPunctuationNormalizer::normalize("他说..."), "他说……");
+        assert_eq!(
+            CjkPunctuationNormalizer::normalize("他说…然后"),
+            "他说……然后"
+        );
+        assert_eq!(CjkPunctuationNormalizer::normalize("他说……"), "他说……");
+        assert_eq!(CjkPunctuationNormalizer::normalize("wait..."), "wait...");
+    }
+
+    #[test]
+    fn test_cjk_punctuation_quotes() {
+        assert_eq!(
+            CjkPunctuationNormalizer::normalize(r#"他说:"她说'你好'""#),
+            "他说：“她说‘你好’”"
+        );
+        assert_eq!(
+            CjkPunctuationNormalizer::normalize(r#"点击 "Don't save" 按钮"#),
+            "点击“Don't save”按钮"
+        );
+        // 已经是弯引号的文本保持不变
+        assert_eq!(
+            CjkPunctuationNormalizer::normalize("他说：“她说‘你好’”。"),
+            "他说：“她说‘你好’”。"
+        );
+    }
+
+    #[test]
+    fn test_cjk_punctuation_only_for_chinese() {
+        let normalizer = CjkPunctuationNormalizer;
+        let ja: LanguageIdentifier = "ja".parse().unwrap();
+        assert_eq!(
+            normalizer.process("你好, 世界".to_string(), &ja),
+            "你好, 世界"
+        );
+        assert_eq!(
+            normalizer.process("你好, 世界".to_string(), &zh()),
+            "你好，世界"
+        );
+    }
+
+    #[test]
+    fn test_whitespace_normalizer() {
+        let cases = [
+            ("a  b\t\tc", "a b c"),
+            ("trailing   \nnext", "trailing\nnext"),
+            ("  indented  line", "  indented line"),
+            ("windows  \r\nline", "windows\r\nline"),
+            // 不换行空格表示不允许在此处断行，合并时保留
+            ("10 \u{00A0} %", "10\u{00A0}%"),
+            ("prix\u{202F}:", "prix\u{202F}:"),
+            // 全角空格通常是有意的排版
+            ("\u{3000}\u{3000}段落", "\u{3000}\u{3000}段落"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                WhitespaceNormalizer::normalize(input),
+                expected,
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalizers_are_idempotent() {
+        let inputs = [
+            r#"他说:"她说'你好'"... 然后 (笑)."#,
+            "a  b \u{00A0} c  \n  d\t\te",
+            "Don't  stop… 你好, 世界!",
+        ];
+        for input in inputs {
+            let once = CjkPunctuationNormalizer::normalize(input);
+            assert_eq!(
+                CjkPunctuationNormalizer::normalize(&once),
+                once,
+                "{}",
+                input
+            );
+            let once = WhitespaceNormalizer::normalize(input);
+            assert_eq!(WhitespaceNormalizer::normalize(&once), once, "{}", input);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! 请求发送前的长度预检与超长文本拆分
+//!
+//! 在请求发出之前检查文本长度是否超出翻译服务的限制，避免浪费一次请求后
+//! 才收到服务端的错误。启用 `auto_split` 时，超长文本会在段落、行、句子或
+//! 空白处被拆分成多个片段分别翻译。
+
+use crate::error::TranslationError;
+
+/// 拆分后的文本片段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Segment<'a> {
+    /// 需要翻译的内容
+    pub text: &'a str,
+    /// 紧随其后的空白，翻译后原样拼接回去
+    pub separator: &'a str,
+}
+
+/// 检查长度是否超出限制
+pub(crate) fn check_length(length: usize, max: Option<usize>) -> Result<(), TranslationError> {
+    match max {
+        Some(max) if length > max => Err(TranslationError::TextTooLong { length, max }),
+        _ => Ok(()),
+    }
+}
+
+/// 按字符数计量文本长度
+#[cfg_attr(not(feature = "microsoft"), allow(dead_code))]
+pub(crate) fn char_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// 按序列化为 JSON 后的字节数计量，不分配缓冲区
+#[cfg_attr(not(feature = "microsoft"), allow(dead_code))]
+pub(crate) fn json_len(value: &impl serde::Serialize) -> usize {
+    struct Counter(usize);
+
+    impl std::io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    serde_json::to_writer(&mut counter, value).expect("value is serializable");
+    counter.0
+}
+
+/// 将文本拆分为长度不超过 `max` 的片段
+///
+/// 依次尝试在段落、行、句子、空白处拆分，仍然超长的部分按字符硬拆分。
+/// 所有片段按顺序拼接（含分隔空白）后与原文完全一致。
+pub(crate) fn split_text<'a>(
+    text: &'a str,
+    max: usize,
+    measure: impl Fn(&str) -> usize,
+) -> Vec<Segment<'a>> {
+    let mut chunks = Vec::new();
+    split_recursive(text, max.max(1), &measure, 0, &mut chunks);
+    chunks
+        .into_iter()
+        .map(|chunk| {
+            let text = chunk.trim_end();
+            Segment {
+                text,
+                separator: &chunk[text.len()..],
+            }
+        })
+        .collect()
+}
+
+/// 拆分层级：段落、行、句子、空白
+const LEVELS: usize = 4;
+
+fn split_recursive<'a>(
+    text: &'a str,
+    max: usize,
+    measure: &impl Fn(&str) -> usize,
+    level: usize,
+    chunks: &mut Vec<&'a str>,
+) {
+    if measure(text) <= max {
+        chunks.push(text);
+        return;
+    }
+    if level >= LEVELS {
+        hard_split(text, max, measure, chunks);
+        return;
+    }
+
+    let pieces = split_pieces(text, level);
+    if pieces.len() <= 1 {
+        split_recursive(text, max, measure, level + 1, chunks);
+        return;
+    }
+
+    // 贪心合并相邻片段，单个片段仍然超长时进入下一层级拆分
+    let mut start = 0;
+    let mut end = 0;
+    for piece in pieces {
+        let piece_end = end + piece.len();
+        if measure(&text[start..piece_end]) <= max {
+            end = piece_end;
+            continue;
+        }
+        if end > start {
+            chunks.push(&text[start..end]);
+        }
+        start = end;
+        if measure(&text[start..piece_end]) <= max {
+            end = piece_end;
+        } else {
+            split_recursive(&text[start..piece_end], max, measure, level + 1, chunks);
+            start = piece_end;
+            end = piece_end;
+        }
+    }
+    if end > start {
+        chunks.push(&text[start..end]);
+    }
+}
+
+/// 按层级把文本切成连续的小块，分隔符归属于前一块
+fn split_pieces(text: &str, level: usize) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    for (i, &(offset, c)) in chars.iter().enumerate() {
+        let next = chars.get(i + 1).map(|&(_, c)| c);
+        let line_end = c == '\n' && !matches!(next, Some('\n') | Some('\r'));
+        let boundary = match level {
+            // 段落：连续两个以上换行之后
+            0 => line_end && text[start..offset].trim_end_matches('\r').ends_with('\n'),
+            // 行
+            1 => line_end,
+            // 句子：中文标点之后，或西文标点加空白之后
+            2 => {
+                ("。！？；".contains(c) && next.is_some_and(|n| !n.is_whitespace()))
+                    || (c.is_whitespace()
+                        && !next.is_some_and(char::is_whitespace)
+                        && ends_sentence(&text[start..offset]))
+            }
+            // 空白
+            _ => c.is_whitespace() && !next.is_some_and(char::is_whitespace),
+        };
+        if boundary {
+            let end = offset + c.len_utf8();
+            pieces.push(&text[start..end]);
+            start = end;
+        }
+    }
+    if start < text.len() {
+        pieces.push(&text[start..]);
+    }
+    pieces
+}
+
+/// 判断文本（去掉末尾空白后）是否以句末标点结束
+fn ends_sentence(text: &str) -> bool {
+    text.trim_end()
+        .chars()
+        .last()
+        .is_some_and(|c| ".!?。！？；".contains(c))
+}
+
+/// 按字符硬拆分，每段取满足限制的最长前缀
+fn hard_split<'a>(
+    text: &'a str,
+    max: usize,
+    measure: &impl Fn(&str) -> usize,
+    chunks: &mut Vec<&'a str>,
+) {
+    let mut rest = text;
+    while !rest.is_empty() {
+        let boundaries: Vec<usize> = rest.char_indices().map(|(i, c)| i + c.len_utf8()).collect();
+        // 二分查找满足限制的最长前缀（至少一个字符）
+        let (mut lo, mut hi) = (0, boundaries.len() - 1);
+        while lo < hi {
+            let mid = (lo + hi).div_ceil(2);
+            if measure(&rest[..boundaries[mid]]) <= max {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        let end = boundaries[lo];
+        chunks.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::TranslationError;
+    use crate::preflight::{Segment, char_len, check_length, split_text};
+
+    fn rejoin(segments: &[Segment]) -> String {
+        segments
+            .iter()
+            .map(|s| format!("{}{}", s.text, s.separator))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_length_boundaries() {
+        assert!(check_length(9, Some(10)).is_ok());
+        assert!(check_length(10, Some(10)).is_ok());
+        match check_length(11, Some(10)) {
+            Err(TranslationError::TextTooLong { length, max }) => {
+                assert_eq!(length, 11);
+                assert_eq!(max, 10);
+            }
+            other => panic!("Expected TextTooLong, got {:?}", other),
+        }
+        assert!(check_length(usize::MAX, None).is_ok());
+    }
+
+    #[test]
+    fn test_split_short_text_untouched() {
+        let segments = split_text("Hello world.", 100, char_len);
+        assert_eq!(
+            segments,
+            vec![Segment {
+                text: "Hello world.",
+                separator: ""
+            }]
+        );
+    }
+
+    #[test]
+    fn test_split_prefers_paragraphs() {
+        let text = "First paragraph here.\n\nSecond paragraph here.";
+        let segments = split_text(text, 30, char_len);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].text, "First paragraph here.");
+        assert_eq!(segments[0].separator, "\n\n");
+        assert_eq!(segments[1].text, "Second paragraph here.");
+        assert_eq!(rejoin(&segments), text);
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        let text = "One two three. Four five six. Seven eight nine.";
+        let segments = split_text(text, 20, char_len);
+        assert_eq!(
+            segments.iter().map(|s| s.text).collect::<Vec<_>>(),
+            vec!["One two three.", "Four five six.", "Seven eight nine."]
+        );
+        assert_eq!(rejoin(&segments), text);
+    }
+
+    #[test]
+    fn test_split_cjk_sentences() {
+        let text = "今天天气很好。我们去公园散步吧。";
+        let segments = split_text(text, 9, char_len);
+        assert_eq!(
+            segments.iter().map(|s| s.text).collect::<Vec<_>>(),
+            vec!["今天天气很好。", "我们去公园散步吧。"]
+        );
+        assert_eq!(rejoin(&segments), text);
+    }
+
+    #[test]
+    fn test_split_hard_split_without_boundaries() {
+        let text = "a".repeat(25);
+        let segments = split_text(&text, 10, char_len);
+        assert_eq!(
+            segments
+                .iter()
+                .map(|s| char_len(s.text))
+                .collect::<Vec<_>>(),
+            vec![10, 10, 5]
+        );
+        assert_eq!(rejoin(&segments), text);
+    }
+
+    #[test]
+    fn test_split_respects_custom_measure() {
+        // 按 4 个字符一个 token 估算
+        let measure = |s: &str| char_len(s).div_ceil(4);
+        let text = "word ".repeat(40);
+        let segments = split_text(&text, 10, measure);
+        assert!(segments.len() > 1);
+        assert!(segments.iter().all(|s| measure(s.text) <= 10));
+        assert_eq!(rejoin(&segments), text);
+    }
+}
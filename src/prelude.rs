@@ -0,0 +1,75 @@
+//! 常用类型的统一导入
+//!
+//! 只需一行 `use async_translate::prelude::*;` 即可使用翻译器、管理器、选项和错误类型，
+//! 其中包括 [`Translator`] trait，避免在具体翻译器上调用 `.translate(...)` 时因为
+//! 没有导入 trait 而找不到方法。
+//!
+//! ```rust
+//! use async_translate::prelude::*;
+//!
+//! struct UppercaseTranslator;
+//!
+//! #[async_trait]
+//! impl Translator for UppercaseTranslator {
+//!     async fn translate_with_options(
+//!         &self,
+//!         text: &str,
+//!         _target_lang: &LanguageIdentifier,
+//!         _source_lang: Option<&LanguageIdentifier>,
+//!         _options: &TranslateOptions,
+//!     ) -> Result<String, TranslationError> {
+//!         Ok(text.to_uppercase())
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), TranslationError> {
+//!     let mut manager = TranslationManager::new();
+//!     manager.add_translator("upper", Box::new(UppercaseTranslator));
+//!
+//!     let target_lang = lang("zh-Hans")?;
+//!     assert!(lang("not a language!").is_err());
+//!     let result = manager.translate("upper", "hello", &target_lang, None).await?;
+//!     assert_eq!(result, "HELLO");
+//!
+//!     let detail = UppercaseTranslator
+//!         .translate_detailed_with_options("hi", &target_lang, None, &TranslateOptions::default())
+//!         .await?;
+//!     assert_eq!(detail.text, "HI");
+//!     Ok(())
+//! }
+//! ```
+//!
+//! 内置的翻译器同样可以直接调用 trait 方法：
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "openai")]
+//! use async_translate::prelude::*;
+//!
+//! # #[cfg(feature = "openai")]
+//! #[tokio::main]
+//! async fn main() -> Result<(), TranslationError> {
+//!     let translator = OpenAITranslator::new(
+//!         OpenAIConfig::builder().api_keys(vec!["your-api-key"]).build(),
+//!     );
+//!     let result = translator.translate("Hello", &lang("zh")?, None).await?;
+//!     println!("{}", result);
+//!     Ok(())
+//! }
+//! # #[cfg(not(feature = "openai"))]
+//! # fn main() {}
+//! ```
+
+pub use crate::error::TranslationError;
+pub use crate::lang;
+pub use crate::manager::TranslationManager;
+#[cfg(feature = "microsoft")]
+pub use crate::microsoft::{
+    MicrosoftConfig, MicrosoftConfigBuilder, MicrosoftTranslation, MicrosoftTranslator,
+};
+#[cfg(feature = "openai")]
+pub use crate::openai::{OpenAIConfig, OpenAIConfigBuilder, OpenAITranslator};
+pub use crate::options::{FailurePolicy, TranslateOptions};
+pub use crate::translator::{TranslationDetail, Translator};
+pub use async_trait::async_trait;
+pub use unic_langid::LanguageIdentifier;
@@ -0,0 +1,77 @@
+//! 请求预览
+//!
+//! 两个翻译器都先根据文本和选项构造出完整的请求（URL、查询参数、请求头和请求体），
+//! 再交给 HTTP 客户端发送。`build_request_preview` 只做第一步，返回将要发送的请求而
+//! 不进行任何网络请求，便于在不产生费用的情况下排查提示词和参数问题。
+//!
+//! 预览中的凭据按与错误信息相同的规则隐藏：`Authorization` 等请求头只保留认证方案，
+//! URL 中的查询参数和任何位置出现的 API Key 原文都替换为 `***`。
+
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+use crate::secret::{self, SecretString};
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+use reqwest::{Client, RequestBuilder};
+use serde::Serialize;
+use serde_json::Value;
+
+/// 将要发送的请求，凭据已隐藏
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequestPreview {
+    /// 请求地址，不含查询参数
+    pub url: String,
+    /// 查询参数，按发送顺序排列
+    pub query: Vec<(String, String)>,
+    /// 请求头，按发送顺序排列，凭据已隐藏
+    pub headers_redacted: Vec<(String, String)>,
+    /// JSON 请求体
+    pub body_json: Value,
+}
+
+/// 构造完成、尚未发送的 POST 请求
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+pub(crate) struct PreparedRequest {
+    pub(crate) url: String,
+    pub(crate) query: Vec<(String, String)>,
+    pub(crate) headers: Vec<(String, String)>,
+    /// 序列化后的 JSON 请求体，发送时不再经过中间的 [`Value`]
+    pub(crate) body: Vec<u8>,
+}
+
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+impl PreparedRequest {
+    /// 转换为客户端的请求构造器
+    pub(crate) fn into_builder(self, client: &Client) -> RequestBuilder {
+        let mut builder = client.post(self.url);
+        for (name, value) in &self.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        if !self.query.is_empty() {
+            builder = builder.query(&self.query);
+        }
+        // 请求头中已经带有 `Content-Type: application/json`
+        builder.body(self.body)
+    }
+
+    /// 隐藏凭据后转换为预览，`api_key` 的原文在任何位置出现都会被替换
+    pub(crate) fn preview(self, api_key: Option<&SecretString>) -> RequestPreview {
+        let redact = |text: &str| match api_key {
+            Some(api_key) => api_key.redact(text).into_owned(),
+            None => text.to_string(),
+        };
+        let body = redact(&String::from_utf8_lossy(&self.body));
+        RequestPreview {
+            url: redact(&secret::redact_url(&self.url)),
+            query: self
+                .query
+                .iter()
+                .map(|(name, value)| (name.clone(), redact(value)))
+                .collect(),
+            headers_redacted: self
+                .headers
+                .iter()
+                .map(|(name, value)| (name.clone(), redact(&secret::redact_header(name, value))))
+                .collect(),
+            body_json: serde_json::from_str(&body).unwrap_or(Value::String(body)),
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! 翻译期间保护数字、日期和版本号
+//!
+//! 大模型翻译时常会改写其中的数字格式，例如把数字转写为汉字、调换千位分隔符和小数点。
+//! 设置 [`crate::TranslateOptions::protect_numbers`] 后，翻译器在发送请求前把日期、时间、
+//! 版本号（包括 IPv4 地址）、货币金额和带分隔符或小数点的数字替换为 `⟦A⟧` 形式的占位符，
+//! 收到译文后还原。不带分隔符的整数不做替换。
+//!
+//! [`crate::TranslateOptions::protect_pattern`] 添加的正则表达式不受 `protect_numbers`
+//! 开关影响，总是生效。多个模式的匹配重叠时取从左到右、最长的一个。
+//! 译文中缺少任何一个占位符时返回 [`TranslationError::ProtectedTextLost`]。
+//! 原文本身包含 `⟦` 时无法区分占位符，不做替换。
+
+use crate::error::TranslationError;
+use regex::Regex;
+use std::cmp::Reverse;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// 占位符的起始字符
+const PLACEHOLDER_OPEN: char = '⟦';
+/// 占位符的结束字符
+const PLACEHOLDER_CLOSE: char = '⟧';
+
+/// 提示词中要求保留占位符的说明
+#[cfg_attr(not(feature = "openai"), allow(dead_code))]
+pub(crate) const PLACEHOLDER_INSTRUCTION: &str = "\n\nThe text contains placeholders such as ⟦A⟧. Copy every placeholder into the translation exactly as written, without translating or removing it.";
+
+/// 数字格式的内置模式，按日期、时间、版本号、货币金额、数字的顺序排列
+const BUILTIN_PATTERNS: &[&str] = &[
+    // ISO 8601 日期，可带时间和时区
+    r"(?-u:\b)\d{4}-\d{2}-\d{2}(?:[T ]\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?(?:Z|[+-]\d{2}:?\d{2}|\s?(?:UTC|GMT))?)?(?-u:\b)",
+    // 时间，可带上下午和时区
+    r"(?-u:\b)\d{1,2}:\d{2}(?::\d{2})?(?:\s?[AaPp][Mm])?(?:\s?(?:UTC|GMT)(?:[+-]\d{1,2}(?::?\d{2})?)?|Z|[+-]\d{2}:?\d{2}|\s[A-Z]{1,4}T)?(?-u:\b)",
+    // 版本号和 IPv4 地址，可带预发布标识、构建元数据、端口或前缀长度
+    r"(?-u:\b)[vV]?\d+(?:\.\d+){2,}(?::\d{1,5}|/\d{1,2})?(?:-[0-9A-Za-z-]+(?:\.[0-9A-Za-z-]+)*)?(?:\+[0-9A-Za-z-]+(?:\.[0-9A-Za-z-]+)*)?(?-u:\b)",
+    // 货币符号在前的金额
+    r"-?[$€£¥₹₩₽]\s?\d+(?:[,.]\d+)*(?-u:\b)",
+    // 货币代码在前或在后的金额，以及货币符号在后的金额
+    r"(?-u:\b)(?:USD|EUR|GBP|JPY|CNY|RMB|HKD|CHF|CAD|AUD)\s?\d+(?:[,.]\d+)*(?-u:\b)",
+    r"(?-u:\b)\d+(?:[,.]\d+)*(?:\s?(?:USD|EUR|GBP|JPY|CNY|RMB|HKD|CHF|CAD|AUD)(?-u:\b)|\s?[$€£¥₹₩₽])",
+    // 带千位分隔符的数字，或以点分隔千位、以逗号作小数点的数字
+    r"(?-u:\b)\d{1,3}(?:,\d{3})+(?:\.\d+)?(?-u:\b)",
+    r"(?-u:\b)\d{1,3}(?:\.\d{3})+,\d+(?-u:\b)",
+    // 小数
+    r"(?-u:\b)\d+\.\d+(?-u:\b)",
+];
+
+fn builtin_patterns() -> &'static [Regex] {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        BUILTIN_PATTERNS
+            .iter()
+            .map(|pattern| Regex::new(pattern).expect("builtin pattern is valid"))
+            .collect()
+    })
+}
+
+/// 调用方添加的保护模式，按模式字符串比较
+#[derive(Clone, Default)]
+pub struct ProtectPatterns(Vec<Regex>);
+
+impl ProtectPatterns {
+    /// 添加一个模式
+    pub(crate) fn push(&mut self, pattern: Regex) {
+        self.0.push(pattern);
+    }
+
+    /// 所有模式
+    pub fn as_slice(&self) -> &[Regex] {
+        &self.0
+    }
+
+    /// 是否没有任何模式
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl std::fmt::Debug for ProtectPatterns {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(Regex::as_str))
+            .finish()
+    }
+}
+
+impl PartialEq for ProtectPatterns {
+    fn eq(&self, other: &Self) -> bool {
+        self.0
+            .iter()
+            .map(Regex::as_str)
+            .eq(other.0.iter().map(Regex::as_str))
+    }
+}
+
+/// 替换为占位符后的文本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Protected {
+    text: String,
+    /// （占位符，被替换的原文）
+    placeholders: Vec<(String, String)>,
+}
+
+impl Protected {
+    /// 发送给翻译服务的文本
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// 被替换的原文，按出现顺序排列
+    #[cfg(test)]
+    pub(crate) fn originals(&self) -> Vec<&str> {
+        self.placeholders
+            .iter()
+            .map(|(_, original)| original.as_str())
+            .collect()
+    }
+
+    /// 把译文中的占位符还原为原文，缺少任何一个占位符时返回错误
+    pub(crate) fn restore(&self, translated: &str) -> Result<String, TranslationError> {
+        if let Some((_, original)) = self
+            .placeholders
+            .iter()
+            .find(|(placeholder, _)| !translated.contains(placeholder.as_str()))
+        {
+            return Err(TranslationError::ProtectedTextLost {
+                text: original.clone(),
+            });
+        }
+        Ok(self.restore_partial(translated))
+    }
+
+    /// 把译文中出现的占位符还原为原文，缺少的占位符不视为错误，用于截断或中断的译文
+    pub(crate) fn restore_partial(&self, translated: &str) -> String {
+        self.placeholders
+            .iter()
+            .fold(translated.to_string(), |text, (placeholder, original)| {
+                text.replace(placeholder.as_str(), original)
+            })
+    }
+}
+
+/// 文本中是否可能包含占位符
+#[cfg_attr(not(feature = "openai"), allow(dead_code))]
+pub(crate) fn contains_placeholder(text: &str) -> bool {
+    text.contains(PLACEHOLDER_OPEN)
+}
+
+/// 按设置把需要保护的片段替换为占位符
+///
+/// `protect_numbers` 为 false 且没有调用方的模式时原样返回
+pub(crate) fn protect(text: &str, protect_numbers: bool, patterns: &ProtectPatterns) -> Protected {
+    let builtin: &[Regex] = if protect_numbers {
+        builtin_patterns()
+    } else {
+        &[]
+    };
+    let mut ranges = Vec::new();
+    if !text.contains(PLACEHOLDER_OPEN) {
+        for pattern in builtin {
+            ranges.extend(
+                pattern
+                    .find_iter(text)
+                    .filter(|found| !continues_number(&text[..found.start()]))
+                    .map(|found| found.range()),
+            );
+        }
+        for pattern in patterns.as_slice() {
+            ranges.extend(
+                pattern
+                    .find_iter(text)
+                    .filter(|found| !found.is_empty())
+                    .map(|found| found.range()),
+            );
+        }
+    }
+    mask(text, ranges, PLACEHOLDER_OPEN, PLACEHOLDER_CLOSE)
+}
+
+/// 把 `ranges` 指定的片段替换为 `open` 和 `close` 包围的占位符
+///
+/// 从左到右，同一位置开始的片段中较长的优先，跳过与已选片段重叠的部分。
+/// `ranges` 必须位于字符边界上。
+pub(crate) fn mask(
+    text: &str,
+    mut ranges: Vec<Range<usize>>,
+    open: char,
+    close: char,
+) -> Protected {
+    ranges.sort_by_key(|range: &Range<usize>| (range.start, Reverse(range.end)));
+
+    let mut output = String::with_capacity(text.len());
+    let mut placeholders = Vec::new();
+    let mut last = 0;
+    for range in ranges {
+        if range.start < last {
+            continue;
+        }
+        let placeholder = format!("{}{}{}", open, label(placeholders.len()), close);
+        output.push_str(&text[last..range.start]);
+        output.push_str(&placeholder);
+        placeholders.push((placeholder, text[range.clone()].to_string()));
+        last = range.end;
+    }
+    output.push_str(&text[last..]);
+    Protected {
+        text: output,
+        placeholders,
+    }
+}
+
+/// 内置模式的匹配是否紧接在数字和分隔符之后，例如 `a1.2.3` 中的 `2.3`
+///
+/// 这样的匹配只是更长的标识符或数字的一部分，不做替换
+fn continues_number(before: &str) -> bool {
+    let mut chars = before.chars().rev();
+    matches!(
+        (chars.next(), chars.next()),
+        (Some('.' | ',' | ':' | '-' | '/'), Some(c)) if c.is_ascii_digit()
+    )
+}
+
+/// 第 `index` 个占位符的标签：A 到 Z，然后是 AA、AB……
+///
+/// 标签不使用数字，避免被翻译服务转写
+fn label(mut index: usize) -> String {
+    let mut label = Vec::new();
+    loop {
+        label.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    label.reverse();
+    String::from_utf8(label).expect("labels are ASCII")
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::TranslationError;
+    use crate::options::{EffectiveOptions, TranslateOptions};
+    use crate::protect::{ProtectPatterns, label, protect};
+    use regex::Regex;
+
+    fn protected_parts(text: &str) -> Vec<String> {
+        protect(text, true, &ProtectPatterns::default())
+            .originals()
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_builtin_patterns() {
+        let cases: &[(&str, &[&str])] = &[
+            (
+                "Released on 2024-05-03, costs $1,299.99.",
+                &["2024-05-03", "$1,299.99"],
+            ),
+            (
+                "Deployed at 2024-05-03T10:15:00Z",
+                &["2024-05-03T10:15:00Z"],
+            ),
+            (
+                "Window 2024-05-03 22:00+08:00 to 2024-05-04",
+                &["2024-05-03 22:00+08:00", "2024-05-04"],
+            ),
+            // IPv4 地址，可带端口或前缀长度
+            ("Server 192.168.1.10 is down", &["192.168.1.10"]),
+            (
+                "Connect to 10.0.0.1:8080 or 10.0.0.0/8",
+                &["10.0.0.1:8080", "10.0.0.0/8"],
+            ),
+            // 语义化版本号
+            ("Requires 1.75.0 or later.", &["1.75.0"]),
+            ("Upgrade to v2.3.1-rc.1+build.5.", &["v2.3.1-rc.1+build.5"]),
+            ("Bump 0.9.12-beta to 1.0.0", &["0.9.12-beta", "1.0.0"]),
+            // 带时区的时间
+            ("Meeting at 14:30 UTC+8", &["14:30 UTC+8"]),
+            (
+                "Starts 09:00Z, ends 17:45:30+05:30",
+                &["09:00Z", "17:45:30+05:30"],
+            ),
+            ("Call at 3:00 PM PST", &["3:00 PM PST"]),
+            ("Opens 10:00 TODAY", &["10:00"]),
+            // 货币金额
+            ("Preis: 1.299,99 €", &["1.299,99 €"]),
+            ("Total USD 42.50 or 39 EUR", &["USD 42.50", "39 EUR"]),
+            ("2024-05-03发布，售价¥1,299", &["2024-05-03", "¥1,299"]),
+            // 带分隔符的数字和小数
+            ("Population 1,234,567, growth 3.5%", &["1,234,567", "3.5"]),
+            // 不做替换的文本
+            ("I have 3 apples and 12 pears", &[]),
+            ("Build abc1.2.3 and x1.5", &[]),
+            ("item-2024 is 3.5x faster", &[]),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(&protected_parts(text), expected, "protecting {:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_restore_round_trip() {
+        let protected = protect(
+            "Released on 2024-05-03, costs $1,299.99",
+            true,
+            &ProtectPatterns::default(),
+        );
+        assert_eq!(protected.text(), "Released on ⟦A⟧, costs ⟦B⟧");
+        // 占位符的顺序可以改变，也可以出现多次
+        assert_eq!(
+            protected.restore("售价 ⟦B⟧，于 ⟦A⟧ 发布（⟦A⟧）").unwrap(),
+            "售价 $1,299.99，于 2024-05-03 发布（2024-05-03）"
+        );
+        match protected.restore("于 ⟦A⟧ 发布，售价 1299.99 美元") {
+            Err(TranslationError::ProtectedTextLost { text }) => assert_eq!(text, "$1,299.99"),
+            other => panic!("expected ProtectedTextLost, got {:?}", other),
+        }
+        assert_eq!(
+            protected.restore_partial("于 ⟦A⟧ 发布"),
+            "于 2024-05-03 发布"
+        );
+    }
+
+    #[test]
+    fn test_custom_patterns() {
+        let options = EffectiveOptions::from_call_site(
+            &TranslateOptions::default().protect_pattern(Regex::new(r"[A-Z]{2,}-\d+").unwrap()),
+        );
+        // 调用方的模式不受 protect_numbers 开关影响，内置模式不生效
+        let protected = options.protect("Fixed in JIRA-1234, shipped in 1.2.3");
+        assert_eq!(protected.text(), "Fixed in ⟦A⟧, shipped in 1.2.3");
+
+        // 与内置模式重叠时取最长的匹配
+        let options = EffectiveOptions::from_call_site(
+            &TranslateOptions::default()
+                .protect_numbers(true)
+                .protect_pattern(Regex::new(r"build \d+\.\d+\.\d+").unwrap()),
+        );
+        let protected = options.protect("See build 1.2.3 on 2024-05-03");
+        assert_eq!(protected.originals(), vec!["build 1.2.3", "2024-05-03"]);
+
+        let unchanged = options.protect("Keep ⟦raw⟧ 1.2.3");
+        assert_eq!(unchanged.text(), "Keep ⟦raw⟧ 1.2.3");
+        assert_eq!(
+            unchanged.restore("保留 ⟦raw⟧ 1.2.3").unwrap(),
+            "保留 ⟦raw⟧ 1.2.3"
+        );
+    }
+
+    #[test]
+    fn test_labels() {
+        let labels: Vec<String> = [0, 1, 25, 26, 27, 51, 52, 701, 702]
+            .into_iter()
+            .map(label)
+            .collect();
+        assert_eq!(
+            labels,
+            vec!["A", "B", "Z", "AA", "AB", "AZ", "BA", "ZZ", "AAA"]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! 用量配额的阈值提醒
+//!
+//! 通过 [`crate::TranslationManager::with_quota`] 为翻译器设置每个周期的用量上限后，
+//! 管理器在每次向翻译服务成功发出翻译后按原文的字符数累加用量，命中缓存的翻译不计入。
+//! 用量在一个周期内首次达到某个阈值（上限的比例）时，该阈值的回调在单独的任务中调用
+//! 一次，回调中可以执行发送通知等异步操作而不会拖慢翻译。周期按 UTC 日历时间对齐，
+//! 切换到新周期后用量清零，各阈值可以再次触发。
+//!
+//! 配额只用于提醒：用量超出上限后翻译照常进行。
+
+use crate::clock::{Clock, TokioClock};
+use crate::stats::{StatsWindow, WindowPeriod};
+use futures::future::BoxFuture;
+use std::sync::Arc;
+
+/// 用量达到阈值时传给回调的信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuotaEvent {
+    /// 翻译器名称
+    pub provider: String,
+    /// 本周期到目前为止的用量（字符数）
+    pub used: u64,
+    /// 每个周期的用量上限
+    pub limit: u64,
+    /// 用量占上限的比例，可能大于触发的阈值
+    pub fraction: f64,
+}
+
+/// 阈值回调，返回的 future 在单独的任务中执行
+///
+/// ```
+/// use async_translate::quota::{QuotaCallback, QuotaEvent};
+/// use futures::FutureExt;
+/// use std::sync::Arc;
+///
+/// let callback: QuotaCallback = Arc::new(|event: QuotaEvent| {
+///     async move {
+///         eprintln!("{} 已使用 {:.0}% 的配额", event.provider, event.fraction * 100.0);
+///     }
+///     .boxed()
+/// });
+/// ```
+pub type QuotaCallback = Arc<dyn Fn(QuotaEvent) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// 可以在多次调用之间共享的配额，例如 [`crate::tenant::TenantContext`] 中的租户配额
+pub type QuotaTracker = Arc<Quota>;
+
+/// 一个翻译器每个周期的用量上限及阈值回调
+pub struct Quota {
+    limit: u64,
+    usage: StatsWindow<u64>,
+    thresholds: Vec<(f64, QuotaCallback)>,
+}
+
+impl Quota {
+    /// 创建配额，上限为 0 时按 1 处理
+    ///
+    /// # 参数
+    ///
+    /// * `limit` - 每个周期的用量上限（字符数）
+    /// * `period` - 周期长度，例如 [`WindowPeriod::Daily`]
+    pub fn new(limit: u64, period: WindowPeriod) -> Self {
+        Self::with_clock(limit, period, Arc::new(TokioClock))
+    }
+
+    /// 使用指定时钟判断周期切换的配额，测试中可以使用 `testing::MockClock`
+    pub fn with_clock(limit: u64, period: WindowPeriod, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            limit: limit.max(1),
+            usage: StatsWindow::with_clock(Some(period), clock),
+            thresholds: Vec::new(),
+        }
+    }
+
+    /// 注册阈值回调，可以多次调用
+    ///
+    /// # 参数
+    ///
+    /// * `thresholds` - 阈值（用量占上限的比例，例如 0.8）及其回调
+    pub fn on_threshold(mut self, thresholds: Vec<(f64, QuotaCallback)>) -> Self {
+        self.thresholds.extend(thresholds);
+        self
+    }
+
+    /// 每个周期的用量上限
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// 本周期到目前为止的用量
+    pub fn used(&self) -> u64 {
+        self.usage.current().stats
+    }
+
+    /// 累加用量，为本次跨过的阈值启动回调
+    ///
+    /// 用量在周期内只增不减，每个阈值在一个周期内只会被跨过一次
+    pub(crate) fn record(&self, provider: &str, amount: u64, shared: &[(f64, QuotaCallback)]) {
+        let (before, used) = self.usage.update(|used| {
+            let before = *used;
+            *used += amount;
+            (before, *used)
+        });
+        let limit = self.limit as f64;
+        let (before, fraction) = (before as f64 / limit, used as f64 / limit);
+        for (threshold, callback) in self.thresholds.iter().chain(shared) {
+            if before < *threshold && *threshold <= fraction {
+                tokio::spawn(callback(QuotaEvent {
+                    provider: provider.to_string(),
+                    used,
+                    limit: self.limit,
+                    fraction,
+                }));
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Quota {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Quota")
+            .field("limit", &self.limit)
+            .field("period", &self.usage.period())
+            .field(
+                "thresholds",
+                &self.thresholds.iter().map(|(t, _)| t).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use crate::quota::{Quota, QuotaCallback, QuotaEvent};
+    use crate::stats::WindowPeriod;
+    use crate::testing::MockClock;
+    use futures::FutureExt;
+    use std::sync::Arc;
+    use std::time::{Duration, UNIX_EPOCH};
+    use tokio::sync::mpsc;
+
+    /// 把事件连同阈值发送到通道的回调
+    fn recorder(
+        threshold: f64,
+        sender: &mpsc::UnboundedSender<(f64, QuotaEvent)>,
+    ) -> QuotaCallback {
+        let sender = sender.clone();
+        Arc::new(move |event| {
+            let sender = sender.clone();
+            async move {
+                sender.send((threshold, event)).unwrap();
+            }
+            .boxed()
+        })
+    }
+
+    /// 让已启动的回调任务执行完毕，返回收到的阈值
+    async fn fired(receiver: &mut mpsc::UnboundedReceiver<(f64, QuotaEvent)>) -> Vec<f64> {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        let mut thresholds = Vec::new();
+        while let Ok((threshold, _)) = receiver.try_recv() {
+            thresholds.push(threshold);
+        }
+        thresholds
+    }
+
+    #[tokio::test]
+    async fn test_thresholds_fire_once_per_period() {
+        let clock = MockClock::new().wall_clock(UNIX_EPOCH + Duration::from_secs(3600 * 1000));
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let quota = Quota::with_clock(100, WindowPeriod::Hourly, Arc::new(clock.clone()))
+            .on_threshold(vec![
+                (0.8, recorder(0.8, &sender)),
+                (0.95, recorder(0.95, &sender)),
+            ]);
+        let shared = [(0.5, recorder(0.5, &sender))];
+
+        quota.record("openai", 40, &shared);
+        assert!(fired(&mut receiver).await.is_empty());
+        quota.record("openai", 10, &shared);
+        assert_eq!(fired(&mut receiver).await, [0.5]);
+        quota.record("openai", 35, &shared);
+        assert_eq!(fired(&mut receiver).await, [0.8]);
+        // 已触发的阈值在同一周期内不再触发，一次跨过多个阈值时各触发一次
+        quota.record("openai", 5, &shared);
+        quota.record("openai", 20, &shared);
+        assert_eq!(fired(&mut receiver).await, [0.95]);
+        assert_eq!(quota.used(), 110);
+
+        // 新周期用量清零，阈值可以再次触发
+        clock.advance(Duration::from_secs(3600));
+        assert_eq!(quota.used(), 0);
+        quota.record("openai", 96, &shared);
+        assert_eq!(fired(&mut receiver).await, [0.8, 0.95, 0.5]);
+    }
+
+    #[tokio::test]
+    async fn test_event_reports_usage() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let quota =
+            Quota::new(200, WindowPeriod::Daily).on_threshold(vec![(0.8, recorder(0.8, &sender))]);
+        quota.record("microsoft", 170, &[]);
+        let (_, event) = receiver.recv().await.unwrap();
+        assert_eq!(
+            event,
+            QuotaEvent {
+                provider: "microsoft".to_string(),
+                used: 170,
+                limit: 200,
+                fraction: 0.85,
+            }
+        );
+    }
+}
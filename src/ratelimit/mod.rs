@@ -0,0 +1,158 @@
+//! 请求速率限制
+//!
+//! 同时支持每分钟请求数（RPM）限制和相邻请求之间的最小间隔，
+//! 两个条件都满足时才放行，因此较严格的一个生效。等待中的请求按到达顺序依次放行。
+//!
+//! RPM 限制保证任意 60 秒内的请求数不超过 `rpm_limit`。突发请求数 `burst` 小于
+//! `rpm_limit` 时，另外按 GCRA（通用信元速率算法）以 `60 秒 / rpm_limit` 的间隔放行请求，
+//! 空闲时最多累积 `burst` 个可以立即发送的请求，避免先一次用完整分钟的配额、
+//! 再停顿到窗口滚动。`burst` 等于 `rpm_limit` 时 GCRA 不会比窗口更严格，不做计算。
+
+use crate::clock::Clock;
+use crate::error::TranslationError;
+use crate::options::Deadline;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// RPM 限制的时间窗口
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// 请求速率限制器
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    /// 每分钟请求数限制，0 表示不限制
+    rpm_limit: u32,
+    /// 允许的突发请求数，不超过 `rpm_limit`
+    burst: u32,
+    /// 相邻请求之间的最小间隔
+    min_interval: Option<Duration>,
+    state: Mutex<RateState>,
+}
+
+#[derive(Debug, Default)]
+struct RateState {
+    /// 时间窗口内的请求时间（仅在设置了 RPM 限制时记录）
+    request_times: Vec<Instant>,
+    /// 上一个请求的时间
+    last_request: Option<Instant>,
+    /// GCRA 的理论到达时间：按均匀间隔放行时下一个请求应当到达的时间
+    theoretical_arrival: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// `burst` 为 None 时等于 `rpm_limit`，超出 `rpm_limit` 的部分不生效
+    pub(crate) fn new(rpm_limit: u32, burst: Option<u32>, min_interval: Option<Duration>) -> Self {
+        Self {
+            rpm_limit,
+            burst: burst.unwrap_or(rpm_limit).clamp(1, rpm_limit.max(1)),
+            min_interval,
+            state: Mutex::new(RateState::default()),
+        }
+    }
+
+    /// 是否没有任何限制
+    pub(crate) fn is_unlimited(&self) -> bool {
+        self.rpm_limit == 0 && self.min_interval.is_none()
+    }
+
+    /// 等待到可以发送下一个请求，并记录该请求
+    ///
+    /// 需要等待的时长会超出总时限时不再等待，立即返回
+    /// [`TranslationError::DeadlineExceeded`]，也不记录该请求
+    pub(crate) async fn acquire(
+        &self,
+        clock: &dyn Clock,
+        deadline: Option<Deadline>,
+    ) -> Result<(), TranslationError> {
+        self.acquire_with(clock, deadline, true).await
+    }
+
+    /// 只按最小间隔等待，不检查 RPM 限制，但仍记录该请求
+    ///
+    /// 用于服务端已经报告了剩余请求数的情况
+    #[cfg(feature = "openai")]
+    pub(crate) async fn acquire_interval(
+        &self,
+        clock: &dyn Clock,
+        deadline: Option<Deadline>,
+    ) -> Result<(), TranslationError> {
+        self.acquire_with(clock, deadline, false).await
+    }
+
+    async fn acquire_with(
+        &self,
+        clock: &dyn Clock,
+        deadline: Option<Deadline>,
+        check_rpm: bool,
+    ) -> Result<(), TranslationError> {
+        if self.is_unlimited() {
+            return Ok(());
+        }
+        let mut state = self.state.lock().await;
+        loop {
+            let now = clock.now();
+            let wait = self.wait_time(&mut state, now, check_rpm);
+            if wait.is_zero() {
+                if self.rpm_limit > 0 {
+                    state.request_times.push(now);
+                }
+                if self.paces() {
+                    let arrival = state.theoretical_arrival.map_or(now, |tat| tat.max(now));
+                    state.theoretical_arrival = Some(arrival + self.emission_interval());
+                }
+                state.last_request = Some(now);
+                return Ok(());
+            }
+            if let Some(deadline) = deadline {
+                deadline.check(now, wait)?;
+            }
+            clock.sleep(wait).await;
+        }
+    }
+
+    /// 距离下一个请求可以发送还需等待的时长，`check_rpm` 为 false 时只考虑最小间隔
+    fn wait_time(&self, state: &mut RateState, now: Instant, check_rpm: bool) -> Duration {
+        let mut wait = Duration::ZERO;
+        if self.rpm_limit > 0 {
+            state
+                .request_times
+                .retain(|&time| now.duration_since(time) < WINDOW);
+            if check_rpm && state.request_times.len() >= self.rpm_limit as usize {
+                // 等待最早的请求移出时间窗口
+                wait = WINDOW - now.duration_since(state.request_times[0]);
+            }
+        }
+        if let (true, true, Some(arrival)) = (check_rpm, self.paces(), state.theoretical_arrival) {
+            // 理论到达时间最多可以提前 burst - 1 个间隔
+            let tolerance = self.emission_interval() * (self.burst - 1);
+            wait = wait.max(
+                arrival
+                    .saturating_duration_since(now)
+                    .saturating_sub(tolerance),
+            );
+        }
+        if let (Some(interval), Some(last)) = (self.min_interval, state.last_request) {
+            wait = wait.max(interval.saturating_sub(now.duration_since(last)));
+        }
+        wait
+    }
+
+    /// 是否按 GCRA 均匀放行
+    fn paces(&self) -> bool {
+        self.rpm_limit > 0 && self.burst < self.rpm_limit
+    }
+
+    /// 均匀放行时相邻请求的间隔
+    fn emission_interval(&self) -> Duration {
+        WINDOW / self.rpm_limit
+    }
+
+    /// 时间窗口内记录的请求时间
+    #[cfg(test)]
+    pub(crate) async fn request_times(&self) -> Vec<Instant> {
+        self.state.lock().await.request_times.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests;
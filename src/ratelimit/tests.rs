@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod tests {
+    use crate::clock::{Clock, TokioClock};
+    use crate::error::TranslationError;
+    use crate::options::Deadline;
+    use crate::ratelimit::RateLimiter;
+    use crate::testing::MockClock;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_min_interval_sleeps_remaining_time() {
+        let clock = MockClock::new().auto_advance(true);
+        let limiter = RateLimiter::new(0, None, Some(Duration::from_millis(250)));
+
+        limiter.acquire(&clock, None).await.unwrap();
+        clock.advance(Duration::from_millis(100));
+        limiter.acquire(&clock, None).await.unwrap();
+        // 只需等待间隔中剩余的部分
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(150)]);
+
+        clock.advance(Duration::from_secs(1));
+        limiter.acquire(&clock, None).await.unwrap();
+        assert_eq!(clock.sleeps().len(), 1);
+        // 未设置 RPM 限制时不记录请求时间
+        assert!(limiter.request_times().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rpm_stricter_than_interval() {
+        let clock = MockClock::new().auto_advance(true);
+        let limiter = RateLimiter::new(2, None, Some(Duration::from_secs(10)));
+
+        for _ in 0..3 {
+            limiter.acquire(&clock, None).await.unwrap();
+        }
+        // 第二个请求受最小间隔限制，第三个请求需要等待第一个请求移出窗口
+        assert_eq!(
+            clock.sleeps(),
+            vec![Duration::from_secs(10), Duration::from_secs(50)]
+        );
+        assert_eq!(clock.elapsed(), Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn test_interval_stricter_than_rpm() {
+        let clock = MockClock::new().auto_advance(true);
+        let limiter = RateLimiter::new(60, None, Some(Duration::from_secs(5)));
+
+        for _ in 0..4 {
+            limiter.acquire(&clock, None).await.unwrap();
+        }
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(5); 3]);
+        assert_eq!(limiter.request_times().await.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_never_waits() {
+        let clock = MockClock::new();
+        let limiter = RateLimiter::new(0, None, None);
+        assert!(limiter.is_unlimited());
+        for _ in 0..100 {
+            limiter.acquire(&clock, None).await.unwrap();
+        }
+        assert!(clock.sleeps().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_wait_beyond_deadline_fails_without_sleeping() {
+        let clock = MockClock::new().auto_advance(true);
+        let limiter = RateLimiter::new(1, None, None);
+        limiter.acquire(&clock, None).await.unwrap();
+        clock.advance(Duration::from_secs(10));
+
+        // 需要等待 50 秒，总时限只有 10 秒
+        let deadline = Deadline::new(Duration::from_secs(10)).start(clock.now());
+        assert!(matches!(
+            limiter.acquire(&clock, Some(deadline)).await,
+            Err(TranslationError::DeadlineExceeded { budget }) if budget == Duration::from_secs(10)
+        ));
+        assert!(clock.sleeps().is_empty());
+        assert_eq!(limiter.request_times().await.len(), 1);
+
+        let deadline = Deadline::new(Duration::from_secs(60)).start(clock.now());
+        limiter.acquire(&clock, Some(deadline)).await.unwrap();
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(50)]);
+    }
+
+    /// 同时发起 `count` 个请求，返回每个请求获准发送的时刻（相对开始时的秒数）
+    async fn release_times(limiter: &RateLimiter, count: usize) -> Vec<u64> {
+        let start = tokio::time::Instant::now();
+        let mut times: Vec<u64> = futures::future::join_all((0..count).map(|_| async {
+            limiter.acquire(&TokioClock, None).await.unwrap();
+            start.elapsed().as_secs()
+        }))
+        .await;
+        times.sort();
+        times
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_burst_controls_pacing() {
+        // burst 等于 rpm_limit（默认）：一次用完配额，然后停顿到窗口滚动
+        let legacy = RateLimiter::new(5, None, None);
+        assert_eq!(release_times(&legacy, 7).await, vec![0, 0, 0, 0, 0, 60, 60]);
+        let explicit = RateLimiter::new(5, Some(5), None);
+        assert_eq!(
+            release_times(&explicit, 7).await,
+            vec![0, 0, 0, 0, 0, 60, 60]
+        );
+
+        // burst 为 1：每 12 秒放行一个
+        let smooth = RateLimiter::new(5, Some(1), None);
+        assert_eq!(
+            release_times(&smooth, 7).await,
+            vec![0, 12, 24, 36, 48, 60, 72]
+        );
+
+        // burst 为 2：先连续放行两个，之后均匀放行，且任意 60 秒内不超过 rpm_limit
+        let mixed = RateLimiter::new(5, Some(2), None);
+        let times = release_times(&mixed, 8).await;
+        assert_eq!(times, vec![0, 0, 12, 24, 36, 60, 60, 72]);
+        for (i, &time) in times.iter().enumerate() {
+            let in_window = times[i..].iter().filter(|&&t| t < time + 60).count();
+            assert!(
+                in_window <= 5,
+                "{} requests within 60s of {}",
+                in_window,
+                time
+            );
+        }
+
+        // 空闲后重新累积突发配额，但不超过 burst
+        tokio::time::advance(Duration::from_secs(600)).await;
+        assert_eq!(release_times(&smooth, 2).await, vec![0, 12]);
+        assert_eq!(release_times(&mixed, 3).await, vec![0, 0, 12]);
+    }
+}
@@ -0,0 +1,303 @@
+//! 翻译器注册表
+//!
+//! [`TranslatorRegistry`] 按种类名称保存创建翻译器的工厂函数，可以根据运行时的
+//! JSON 配置创建翻译器。内置了 `"openai"` 和 `"microsoft"` 两种（需要启用对应的
+//! feature），第三方翻译器通过 [`TranslatorRegistry::register`] 注册后即可用于
+//! [`crate::TranslationManager::from_config`]。
+//!
+//! 内置种类的 JSON 配置只包含可以用 JSON 表示的常用字段，未出现的字段使用默认值，
+//! 时长以毫秒表示（例如 `min_request_interval_ms`），未知字段会被拒绝。
+
+#[cfg(feature = "microsoft")]
+use crate::microsoft::{MicrosoftConfig, MicrosoftTranslator};
+#[cfg(feature = "openai")]
+use crate::openai::{OpenAIConfig, OpenAITranslator};
+use crate::{error::TranslationError, translator::Translator};
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+use serde::{Deserialize, de::DeserializeOwned};
+use std::collections::HashMap;
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+use std::time::Duration;
+
+/// 根据 JSON 配置创建翻译器的工厂函数
+pub type TranslatorFactory =
+    Box<dyn Fn(serde_json::Value) -> Result<Box<dyn Translator>, TranslationError> + Send + Sync>;
+
+/// 翻译器注册表，键为翻译器种类
+pub struct TranslatorRegistry {
+    factories: HashMap<String, TranslatorFactory>,
+}
+
+impl TranslatorRegistry {
+    /// 创建包含内置翻译器种类的注册表
+    pub fn new() -> Self {
+        #[allow(unused_mut)]
+        let mut registry = Self::empty();
+        #[cfg(feature = "openai")]
+        registry.register(
+            "openai",
+            Box::new(|value| {
+                let config: OpenAIConfig =
+                    parse_config::<OpenAIJsonConfig>("openai", value)?.into();
+                config.validate()?;
+                Ok(Box::new(OpenAITranslator::new(config)))
+            }),
+        );
+        #[cfg(feature = "microsoft")]
+        registry.register(
+            "microsoft",
+            Box::new(|value| {
+                let config: MicrosoftConfig =
+                    parse_config::<MicrosoftJsonConfig>("microsoft", value)?.try_into()?;
+                config.validate()?;
+                Ok(Box::new(MicrosoftTranslator::new(config)))
+            }),
+        );
+        registry
+    }
+
+    /// 创建不包含任何翻译器种类的注册表
+    pub fn empty() -> Self {
+        Self {
+            factories: HashMap::new(),
+        }
+    }
+
+    /// 注册翻译器种类，已存在的同名种类会被替换
+    ///
+    /// # 参数
+    ///
+    /// * `kind` - 翻译器种类
+    /// * `factory` - 根据 JSON 配置创建翻译器的工厂函数
+    pub fn register(&mut self, kind: &str, factory: TranslatorFactory) -> &mut Self {
+        self.factories.insert(kind.to_string(), factory);
+        self
+    }
+
+    /// 根据 JSON 配置创建指定种类的翻译器
+    ///
+    /// # 参数
+    ///
+    /// * `kind` - 翻译器种类
+    /// * `config` - 翻译器的 JSON 配置
+    ///
+    /// # 返回值
+    ///
+    /// 返回创建的翻译器，种类未注册或配置无效时返回 [`TranslationError::ConfigurationError`]
+    pub fn create(
+        &self,
+        kind: &str,
+        config: serde_json::Value,
+    ) -> Result<Box<dyn Translator>, TranslationError> {
+        let factory = self.factories.get(kind).ok_or_else(|| {
+            TranslationError::ConfigurationError(format!("Unknown translator kind '{}'", kind))
+        })?;
+        factory(config)
+    }
+
+    /// 检查是否注册了指定种类
+    pub fn contains(&self, kind: &str) -> bool {
+        self.factories.contains_key(kind)
+    }
+
+    /// 已注册的种类，按字母顺序排列
+    pub fn kinds(&self) -> Vec<String> {
+        let mut kinds: Vec<String> = self.factories.keys().cloned().collect();
+        kinds.sort();
+        kinds
+    }
+}
+
+impl Default for TranslatorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for TranslatorRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TranslatorRegistry")
+            .field("kinds", &self.kinds())
+            .finish()
+    }
+}
+
+/// 将 JSON 反序列化为指定种类的配置，`null` 视为空配置
+#[cfg(any(feature = "openai", feature = "microsoft"))]
+fn parse_config<T: DeserializeOwned>(
+    kind: &str,
+    value: serde_json::Value,
+) -> Result<T, TranslationError> {
+    let value = match value {
+        serde_json::Value::Null => serde_json::json!({}),
+        value => value,
+    };
+    serde_json::from_value(value).map_err(|e| {
+        TranslationError::ConfigurationError(format!("Invalid {} config: {}", kind, e))
+    })
+}
+
+/// OpenAI 翻译器的 JSON 配置
+#[cfg(feature = "openai")]
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct OpenAIJsonConfig {
+    base_url: String,
+    model: String,
+    api_keys: Vec<String>,
+    rpm_limit: u32,
+    burst: Option<u32>,
+    min_request_interval_ms: Option<u64>,
+    concurrent_limit: usize,
+    max_queue_depth: Option<usize>,
+    max_request_bytes: Option<usize>,
+    adaptive_concurrency: bool,
+    honor_server_rate_limits: bool,
+    system_prompt: Option<String>,
+    locale_names: HashMap<String, String>,
+    max_input_tokens: Option<usize>,
+    context_window: Option<u32>,
+    detect_source_language: bool,
+    detect_before_translate: bool,
+    idempotency_header: Option<String>,
+    stream: bool,
+}
+
+#[cfg(feature = "openai")]
+impl Default for OpenAIJsonConfig {
+    fn default() -> Self {
+        let config = OpenAIConfig::default();
+        Self {
+            base_url: config.base_url,
+            model: config.model,
+            api_keys: Vec::new(),
+            rpm_limit: config.rpm_limit,
+            burst: config.burst,
+            min_request_interval_ms: None,
+            concurrent_limit: config.concurrent_limit,
+            max_queue_depth: config.max_queue_depth,
+            max_request_bytes: config.max_request_bytes,
+            adaptive_concurrency: config.adaptive_concurrency,
+            honor_server_rate_limits: config.honor_server_rate_limits,
+            system_prompt: config.system_prompt,
+            locale_names: config.locale_names,
+            max_input_tokens: config.max_input_tokens,
+            context_window: config.context_window,
+            detect_source_language: config.detect_source_language,
+            detect_before_translate: config.detect_before_translate,
+            idempotency_header: config.idempotency_header,
+            stream: config.stream,
+        }
+    }
+}
+
+#[cfg(feature = "openai")]
+impl From<OpenAIJsonConfig> for OpenAIConfig {
+    fn from(config: OpenAIJsonConfig) -> Self {
+        OpenAIConfig {
+            base_url: config.base_url,
+            model: config.model,
+            api_keys: config.api_keys.into_iter().map(Into::into).collect(),
+            rpm_limit: config.rpm_limit,
+            burst: config.burst,
+            min_request_interval: config.min_request_interval_ms.map(Duration::from_millis),
+            concurrent_limit: config.concurrent_limit,
+            max_queue_depth: config.max_queue_depth,
+            max_request_bytes: config.max_request_bytes,
+            adaptive_concurrency: config.adaptive_concurrency,
+            honor_server_rate_limits: config.honor_server_rate_limits,
+            system_prompt: config.system_prompt,
+            locale_names: config.locale_names,
+            max_input_tokens: config.max_input_tokens,
+            context_window: config.context_window,
+            detect_source_language: config.detect_source_language,
+            detect_before_translate: config.detect_before_translate,
+            idempotency_header: config.idempotency_header,
+            stream: config.stream,
+            ..Default::default()
+        }
+    }
+}
+
+/// 微软翻译器的 JSON 配置
+#[cfg(feature = "microsoft")]
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct MicrosoftJsonConfig {
+    endpoint: Option<String>,
+    api_version: Option<String>,
+    api_key: Option<String>,
+    auth_endpoint: Option<String>,
+    free_tier_hardening: bool,
+    concurrent_limit: usize,
+    max_queue_depth: Option<usize>,
+    max_request_bytes: Option<usize>,
+    adaptive_concurrency: bool,
+    min_request_interval_ms: Option<u64>,
+    category: Option<String>,
+    allow_fallback: Option<bool>,
+    suggested_from: Option<String>,
+    min_detection_score: Option<f64>,
+}
+
+#[cfg(feature = "microsoft")]
+impl Default for MicrosoftJsonConfig {
+    fn default() -> Self {
+        let config = MicrosoftConfig::default();
+        Self {
+            endpoint: config.endpoint,
+            api_version: config.api_version,
+            api_key: None,
+            auth_endpoint: config.auth_endpoint,
+            free_tier_hardening: config.free_tier_hardening,
+            concurrent_limit: config.concurrent_limit,
+            max_queue_depth: config.max_queue_depth,
+            max_request_bytes: config.max_request_bytes,
+            adaptive_concurrency: config.adaptive_concurrency,
+            min_request_interval_ms: None,
+            category: config.category,
+            allow_fallback: config.allow_fallback,
+            suggested_from: None,
+            min_detection_score: config.min_detection_score,
+        }
+    }
+}
+
+#[cfg(feature = "microsoft")]
+impl TryFrom<MicrosoftJsonConfig> for MicrosoftConfig {
+    type Error = TranslationError;
+
+    fn try_from(config: MicrosoftJsonConfig) -> Result<Self, Self::Error> {
+        let suggested_from = config
+            .suggested_from
+            .map(|lang| {
+                lang.parse().map_err(|e| {
+                    TranslationError::ConfigurationError(format!(
+                        "Invalid microsoft config: suggested_from '{}': {}",
+                        lang, e
+                    ))
+                })
+            })
+            .transpose()?;
+        Ok(MicrosoftConfig {
+            endpoint: config.endpoint,
+            api_version: config.api_version,
+            api_key: config.api_key.map(Into::into),
+            auth_endpoint: config.auth_endpoint,
+            free_tier_hardening: config.free_tier_hardening,
+            concurrent_limit: config.concurrent_limit,
+            max_queue_depth: config.max_queue_depth,
+            max_request_bytes: config.max_request_bytes,
+            adaptive_concurrency: config.adaptive_concurrency,
+            min_request_interval: config.min_request_interval_ms.map(Duration::from_millis),
+            category: config.category,
+            allow_fallback: config.allow_fallback,
+            suggested_from,
+            min_detection_score: config.min_detection_score,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,196 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::TranslationError;
+    use crate::manager::TranslationManager;
+    use crate::options::TranslateOptions;
+    use crate::registry::TranslatorRegistry;
+    use crate::translator::Translator;
+    use serde_json::json;
+    use unic_langid::LanguageIdentifier;
+
+    /// 在原文前加上配置中的前缀的翻译器
+    struct PrefixTranslator {
+        prefix: String,
+    }
+
+    #[async_trait::async_trait]
+    impl Translator for PrefixTranslator {
+        async fn translate_with_options(
+            &self,
+            text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            Ok(format!("{}{}", self.prefix, text))
+        }
+    }
+
+    fn prefix_registry() -> TranslatorRegistry {
+        let mut registry = TranslatorRegistry::new();
+        registry.register(
+            "prefix",
+            Box::new(|value| {
+                let prefix = value["prefix"].as_str().ok_or_else(|| {
+                    TranslationError::ConfigurationError("prefix is required".to_string())
+                })?;
+                Ok(Box::new(PrefixTranslator {
+                    prefix: prefix.to_string(),
+                }))
+            }),
+        );
+        registry
+    }
+
+    #[tokio::test]
+    async fn test_manager_from_config_with_custom_factory() {
+        let registry = prefix_registry();
+        let manager = TranslationManager::from_config(
+            json!({
+                "translators": {
+                    "a": {"kind": "prefix", "config": {"prefix": "A:"}},
+                    "b": {"kind": "prefix", "config": {"prefix": "B:"}}
+                }
+            }),
+            &registry,
+        )
+        .unwrap();
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        assert_eq!(manager.list_translators().len(), 2);
+        assert_eq!(
+            manager
+                .translate("a", "Hello", &target_lang, None)
+                .await
+                .unwrap(),
+            "A:Hello"
+        );
+        assert_eq!(
+            manager
+                .translate("b", "Hello", &target_lang, None)
+                .await
+                .unwrap(),
+            "B:Hello"
+        );
+    }
+
+    #[test]
+    fn test_from_config_errors() {
+        let registry = prefix_registry();
+        let cases = [
+            json!({"translators": {"a": {"kind": "unknown"}}}),
+            json!({"translators": {"a": {"kind": "prefix", "config": {}}}}),
+            json!({"translators": {"a": {"config": {"prefix": "A:"}}}}),
+            json!({"translators": {}, "extra": true}),
+        ];
+        for config in cases {
+            match TranslationManager::from_config(config.clone(), &registry) {
+                Err(TranslationError::ConfigurationError(_)) => {}
+                other => panic!(
+                    "Expected ConfigurationError for {}, got {:?}",
+                    config,
+                    other.err()
+                ),
+            }
+        }
+
+        match TranslationManager::from_config(
+            json!({"translators": {"a": {"kind": "unknown"}}}),
+            &registry,
+        ) {
+            Err(TranslationError::ConfigurationError(msg)) => {
+                assert_eq!(msg, "translator 'a': Unknown translator kind 'unknown'")
+            }
+            other => panic!("Expected ConfigurationError, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_register_and_kinds() {
+        let mut registry = TranslatorRegistry::empty();
+        assert!(registry.kinds().is_empty());
+        registry.register(
+            "prefix",
+            Box::new(|_| Err(TranslationError::Other("first".to_string()))),
+        );
+        registry.register(
+            "prefix",
+            Box::new(|_| Err(TranslationError::Other("second".to_string()))),
+        );
+        assert_eq!(registry.kinds(), vec!["prefix"]);
+        match registry.create("prefix", json!(null)) {
+            Err(TranslationError::Other(msg)) => assert_eq!(msg, "second"),
+            other => panic!("Expected Other, got {:?}", other.err()),
+        }
+        assert!(!registry.contains("openai") && !registry.contains("microsoft"));
+    }
+
+    #[cfg(feature = "openai")]
+    #[tokio::test]
+    async fn test_builtin_openai_from_json() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("Authorization", "Bearer test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"message": {"role": "assistant", "content": "你好"}}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let registry = TranslatorRegistry::new();
+        assert!(registry.contains("openai"));
+        let translator = registry
+            .create(
+                "openai",
+                json!({"base_url": server.uri(), "api_keys": ["test-key"], "rpm_limit": 0}),
+            )
+            .unwrap();
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        assert_eq!(
+            translator
+                .translate("Hello", &target_lang, None)
+                .await
+                .unwrap(),
+            "你好"
+        );
+
+        // 缺少 Key 和拼错字段名都会被拒绝
+        for config in [json!({}), json!({"api_keys": ["k"], "modle": "gpt-4"})] {
+            assert!(matches!(
+                registry.create("openai", config),
+                Err(TranslationError::ConfigurationError(_))
+            ));
+        }
+    }
+
+    #[cfg(feature = "microsoft")]
+    #[test]
+    fn test_builtin_microsoft_from_json() {
+        let registry = TranslatorRegistry::new();
+        assert!(registry.contains("microsoft"));
+        assert!(registry.create("microsoft", json!(null)).is_ok());
+        assert!(
+            registry
+                .create(
+                    "microsoft",
+                    json!({"category": "my-category", "allow_fallback": false, "suggested_from": "en"}),
+                )
+                .is_ok()
+        );
+        for config in [
+            json!({"allow_fallback": false}),
+            json!({"suggested_from": "not a language!"}),
+            json!({"concurrent_limit": "ten"}),
+        ] {
+            assert!(matches!(
+                registry.create("microsoft", config),
+                Err(TranslationError::ConfigurationError(_))
+            ));
+        }
+    }
+}
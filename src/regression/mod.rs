@@ -0,0 +1,204 @@
+//! 译文回归对比
+//!
+//! 需要启用 `test-util` feature。修改系统提示词或更换模型前后，分别用
+//! [`TranslationSnapshot::capture`] 翻译同一份 [`Corpus`] 并保存为 JSON，
+//! 再用 [`TranslationSnapshot::diff`] 列出译文发生变化的条目。
+//!
+//! 比较前会合并连续空白并去掉首尾空白，只有空白不同的译文视为相同。
+
+use crate::{error::TranslationError, options::TranslateOptions, translator::Translator};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use unic_langid::LanguageIdentifier;
+
+/// 用于回归对比的固定语料，键用于在两次快照间对应同一条文本
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Corpus {
+    entries: BTreeMap<String, String>,
+}
+
+impl Corpus {
+    /// 解析语料：以 `{` 开头时按 JSON 对象解析，否则按纯文本行解析
+    pub fn parse(input: &str) -> Result<Self, TranslationError> {
+        if input.trim_start().starts_with('{') {
+            Self::from_json(input)
+        } else {
+            Ok(Self::from_lines(input))
+        }
+    }
+
+    /// 从 `{"键": "文本"}` 形式的 JSON 对象解析语料
+    pub fn from_json(input: &str) -> Result<Self, TranslationError> {
+        let entries = serde_json::from_str(input)
+            .map_err(|e| TranslationError::ConfigurationError(format!("Invalid corpus: {}", e)))?;
+        Ok(Self { entries })
+    }
+
+    /// 从纯文本解析语料，每个非空行为一条文本，键为从 1 开始的行号
+    pub fn from_lines(input: &str) -> Self {
+        let entries = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| ((index + 1).to_string(), line.to_string()))
+            .collect();
+        Self { entries }
+    }
+
+    /// 添加一条文本，已存在的同名键会被替换
+    pub fn insert(&mut self, key: impl Into<String>, text: impl Into<String>) -> &mut Self {
+        self.entries.insert(key.into(), text.into());
+        self
+    }
+
+    /// 语料条数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 语料是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 按键的顺序遍历语料
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, text)| (key.as_str(), text.as_str()))
+    }
+}
+
+/// 一次翻译语料得到的译文快照，可以序列化为 JSON 保存
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranslationSnapshot {
+    /// 目标语言
+    pub target_lang: String,
+    /// 译文，键与语料一致
+    pub translations: BTreeMap<String, String>,
+}
+
+/// 两次快照间发生变化的一条译文
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffEntry {
+    /// 语料中的键
+    pub key: String,
+    /// 旧快照中的译文，旧快照没有该键时为 None
+    pub old: Option<String>,
+    /// 新快照中的译文，新快照没有该键时为 None
+    pub new: Option<String>,
+    /// 两段译文的相似度，见 [`similarity`]；一侧缺失时为 0
+    pub similarity: f64,
+}
+
+impl TranslationSnapshot {
+    /// 使用翻译器的批量接口翻译语料，源语言由翻译服务自动检测
+    ///
+    /// # 参数
+    ///
+    /// * `translator` - 执行翻译的翻译器
+    /// * `corpus` - 需要翻译的语料
+    /// * `target_lang` - 目标语言
+    /// * `options` - 翻译配置选项
+    ///
+    /// # 返回值
+    ///
+    /// 返回译文快照，任意一条翻译失败时返回错误
+    pub async fn capture(
+        translator: &dyn Translator,
+        corpus: &Corpus,
+        target_lang: &LanguageIdentifier,
+        options: &TranslateOptions,
+    ) -> Result<Self, TranslationError> {
+        let texts: Vec<&str> = corpus.entries.values().map(String::as_str).collect();
+        let translated = translator
+            .translate_batch_with_options(&texts, target_lang, None, options)
+            .await?;
+        if translated.len() != texts.len() {
+            return Err(TranslationError::ServiceError(format!(
+                "expected {} translations, got {}",
+                texts.len(),
+                translated.len()
+            )));
+        }
+        Ok(Self {
+            target_lang: target_lang.to_string(),
+            translations: corpus.entries.keys().cloned().zip(translated).collect(),
+        })
+    }
+
+    /// 列出与 `other`（新快照）相比发生变化的译文，只有空白不同的译文不会列出
+    pub fn diff(&self, other: &TranslationSnapshot) -> Vec<DiffEntry> {
+        self.diff_with_threshold(other, 1.0)
+    }
+
+    /// 列出与 `other`（新快照）相比相似度低于 `threshold` 的译文，按键排序
+    ///
+    /// `threshold` 取 1.0 时列出所有实际变化，取更小的值可以忽略个别字词的改动
+    pub fn diff_with_threshold(
+        &self,
+        other: &TranslationSnapshot,
+        threshold: f64,
+    ) -> Vec<DiffEntry> {
+        let mut keys: Vec<&String> = self
+            .translations
+            .keys()
+            .chain(other.translations.keys())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys.into_iter()
+            .filter_map(|key| {
+                let old = self.translations.get(key);
+                let new = other.translations.get(key);
+                let similarity = match (old, new) {
+                    (Some(old), Some(new)) => similarity(old, new),
+                    _ => 0.0,
+                };
+                (similarity < threshold).then(|| DiffEntry {
+                    key: key.clone(),
+                    old: old.cloned(),
+                    new: new.cloned(),
+                    similarity,
+                })
+            })
+            .collect()
+    }
+}
+
+/// 两段文本的相似度，取值 0 到 1
+///
+/// 合并连续空白并去掉首尾空白后，按字符计算编辑距离，相似度为
+/// `1 - 编辑距离 / 较长文本的字符数`；两段文本都为空时为 1。
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = collapse_whitespace(a).chars().collect();
+    let b: Vec<char> = collapse_whitespace(b).chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / longest as f64
+}
+
+/// 合并连续空白为一个空格并去掉首尾空白
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 字符级的 Levenshtein 编辑距离
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests;
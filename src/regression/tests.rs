@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::TranslationError;
+    use crate::options::TranslateOptions;
+    use crate::regression::{Corpus, DiffEntry, TranslationSnapshot, similarity};
+    use crate::translator::Translator;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use unic_langid::LanguageIdentifier;
+
+    /// 按预设的对照表返回译文，表中没有的文本原样返回
+    struct ScriptedTranslator(HashMap<&'static str, &'static str>);
+
+    impl ScriptedTranslator {
+        fn new(script: &[(&'static str, &'static str)]) -> Self {
+            Self(script.iter().copied().collect())
+        }
+    }
+
+    #[async_trait]
+    impl Translator for ScriptedTranslator {
+        async fn translate_with_options(
+            &self,
+            text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            Ok(self.0.get(text).copied().unwrap_or(text).to_string())
+        }
+    }
+
+    #[test]
+    fn test_corpus_parse_json_and_lines() {
+        let corpus = Corpus::parse(r#"{"greeting": "Hello", "farewell": "Bye"}"#).unwrap();
+        assert_eq!(
+            corpus.iter().collect::<Vec<_>>(),
+            vec![("farewell", "Bye"), ("greeting", "Hello")]
+        );
+
+        let corpus = Corpus::parse("Hello\n\n  \nGood morning\n").unwrap();
+        assert_eq!(
+            corpus.iter().collect::<Vec<_>>(),
+            vec![("1", "Hello"), ("4", "Good morning")]
+        );
+
+        assert!(matches!(
+            Corpus::parse(r#"{"greeting": 1}"#),
+            Err(TranslationError::ConfigurationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("你好", "你好"), 1.0);
+        assert_eq!(similarity("你好  世界\n", " 你好 世界"), 1.0);
+        assert_eq!(similarity("", "  "), 1.0);
+        assert_eq!(similarity("abcd", "abce"), 0.75);
+        assert_eq!(similarity("", "abc"), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_capture_and_diff() {
+        let corpus = Corpus::parse(
+            r#"{"greeting": "Hello", "morning": "Good morning", "thanks": "Thank you very much"}"#,
+        )
+        .unwrap();
+        let target_lang: LanguageIdentifier = "zh-Hans".parse().unwrap();
+        let options = TranslateOptions::default();
+
+        let before = ScriptedTranslator::new(&[
+            ("Hello", "你好"),
+            ("Good morning", "早上好"),
+            ("Thank you very much", "非常感谢你的帮助"),
+        ]);
+        let after = ScriptedTranslator::new(&[
+            ("Hello", " 你好 "),
+            ("Good morning", "早安"),
+            ("Thank you very much", "非常感谢您的帮助"),
+        ]);
+        let old = TranslationSnapshot::capture(&before, &corpus, &target_lang, &options)
+            .await
+            .unwrap();
+        let new = TranslationSnapshot::capture(&after, &corpus, &target_lang, &options)
+            .await
+            .unwrap();
+        assert_eq!(old.target_lang, "zh-Hans");
+        assert_eq!(old.translations["morning"], "早上好");
+
+        // 快照可以保存为 JSON 后再读取
+        let json = serde_json::to_string(&old).unwrap();
+        let restored: TranslationSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, old);
+
+        // 只有空白不同的 greeting 不列出
+        assert_eq!(
+            restored.diff(&new),
+            vec![
+                DiffEntry {
+                    key: "morning".to_string(),
+                    old: Some("早上好".to_string()),
+                    new: Some("早安".to_string()),
+                    similarity: 1.0 - 2.0 / 3.0,
+                },
+                DiffEntry {
+                    key: "thanks".to_string(),
+                    old: Some("非常感谢你的帮助".to_string()),
+                    new: Some("非常感谢您的帮助".to_string()),
+                    similarity: 0.875,
+                },
+            ]
+        );
+
+        // 调低阈值后忽略只改了一个字的 thanks
+        let keys: Vec<String> = old
+            .diff_with_threshold(&new, 0.8)
+            .into_iter()
+            .map(|entry| entry.key)
+            .collect();
+        assert_eq!(keys, vec!["morning"]);
+    }
+
+    #[test]
+    fn test_diff_reports_missing_keys() {
+        let old = TranslationSnapshot {
+            target_lang: "zh".to_string(),
+            translations: [("a".to_string(), "甲".to_string())].into(),
+        };
+        let new = TranslationSnapshot {
+            target_lang: "zh".to_string(),
+            translations: [("b".to_string(), "乙".to_string())].into(),
+        };
+        let diff = old.diff(&new);
+        assert_eq!(diff.len(), 2);
+        assert_eq!(
+            (diff[0].old.as_deref(), diff[0].new.as_deref()),
+            (Some("甲"), None)
+        );
+        assert_eq!(
+            (diff[1].old.as_deref(), diff[1].new.as_deref()),
+            (None, Some("乙"))
+        );
+        assert!(diff.iter().all(|entry| entry.similarity == 0.0));
+    }
+}
@@ -0,0 +1,379 @@
+//! 命令行的交互模式
+//!
+//! 需要启用 `cli` feature。[`ReplSession::run`] 逐行读取输入，每行作为一段文本翻译并
+//! 输出译文，检测到源语言时一并输出。以 `:` 开头的行是命令，用于切换目标语言、源语言、
+//! 翻译器和选项，或把本次会话的翻译记录保存为 TSV 文件，命令列表见 [`HELP`]。
+//!
+//! 所有行共用同一个 [`TranslationManager`]，认证令牌的缓存和连接复用在行与行之间保持。
+//! 翻译期间收到中断（Ctrl-C）时放弃当前请求，等待输入时收到中断则结束会话。
+
+use crate::error::TranslationError;
+use crate::manager::TranslationManager;
+use crate::options::TranslateOptions;
+use std::future::Future;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use unic_langid::LanguageIdentifier;
+
+/// 等待输入时的提示符
+const PROMPT: &str = "> ";
+
+/// `:help` 输出的命令列表
+pub const HELP: &str = "\
+:to <lang>                 set the target language
+:source <lang|auto>        set the source language, auto to detect it
+:provider <name>           switch to another registered translator
+:options key=value ...     set options: timeout and queue_wait (seconds), retries
+:options reset             restore the default options
+:save <file>               write the session history as TSV
+:help                      show this help
+:quit                      leave the REPL
+";
+
+/// 交互模式中的一行输入
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// 翻译这段文本
+    Translate(String),
+    /// 设置目标语言
+    To(LanguageIdentifier),
+    /// 设置源语言，None 表示自动检测
+    Source(Option<LanguageIdentifier>),
+    /// 切换翻译器
+    Provider(String),
+    /// 修改选项
+    Options(Vec<OptionSetting>),
+    /// 恢复默认选项
+    ResetOptions,
+    /// 把会话记录保存为 TSV 文件
+    Save(PathBuf),
+    /// 显示命令列表
+    Help,
+    /// 结束会话
+    Quit,
+}
+
+/// `:options` 设置的一个选项
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OptionSetting {
+    /// `timeout=<秒>`，单次翻译的超时时间
+    Timeout(Duration),
+    /// `retries=<次数>`，最大重试次数
+    Retries(u32),
+    /// `queue_wait=<秒>`，等待并发许可的最长时间
+    QueueWait(Duration),
+}
+
+/// 解析一行输入，空行返回 None，无法识别的命令返回错误信息
+pub fn parse_command(line: &str) -> Result<Option<Command>, String> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let Some(command) = line.strip_prefix(':') else {
+        return Ok(Some(Command::Translate(line.to_string())));
+    };
+    let (name, argument) = command
+        .split_once(char::is_whitespace)
+        .map_or((command, ""), |(name, argument)| (name, argument.trim()));
+    let required = |usage: &str| {
+        if argument.is_empty() {
+            Err(format!("usage: :{} {}", name, usage))
+        } else {
+            Ok(argument)
+        }
+    };
+    let command = match name {
+        "to" => Command::To(parse_lang(required("<lang>")?)?),
+        "source" => match required("<lang|auto>")? {
+            "auto" => Command::Source(None),
+            lang => Command::Source(Some(parse_lang(lang)?)),
+        },
+        "provider" => Command::Provider(required("<name>")?.to_string()),
+        "options" => match required("key=value ...")? {
+            "reset" => Command::ResetOptions,
+            settings => Command::Options(
+                settings
+                    .split_whitespace()
+                    .map(parse_option)
+                    .collect::<Result<_, _>>()?,
+            ),
+        },
+        "save" => Command::Save(PathBuf::from(required("<file>")?)),
+        "help" | "h" => Command::Help,
+        "quit" | "q" | "exit" => Command::Quit,
+        _ => {
+            return Err(format!(
+                "unknown command ':{}', type :help for a list",
+                name
+            ));
+        }
+    };
+    Ok(Some(command))
+}
+
+fn parse_lang(tag: &str) -> Result<LanguageIdentifier, String> {
+    tag.parse()
+        .map_err(|_| format!("invalid language tag '{}'", tag))
+}
+
+fn parse_option(setting: &str) -> Result<OptionSetting, String> {
+    let (key, value) = setting
+        .split_once('=')
+        .ok_or_else(|| format!("expected key=value, got '{}'", setting))?;
+    let seconds = || {
+        value
+            .parse::<f64>()
+            .ok()
+            .filter(|seconds| seconds.is_finite() && *seconds > 0.0)
+            .map(Duration::from_secs_f64)
+            .ok_or_else(|| format!("{} must be a positive number of seconds", key))
+    };
+    match key {
+        "timeout" => Ok(OptionSetting::Timeout(seconds()?)),
+        "queue_wait" => Ok(OptionSetting::QueueWait(seconds()?)),
+        "retries" => value
+            .parse()
+            .map(OptionSetting::Retries)
+            .map_err(|_| "retries must be a non-negative integer".to_string()),
+        _ => Err(format!("unknown option '{}'", key)),
+    }
+}
+
+/// 会话中的一次翻译
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    /// 使用的翻译器
+    pub provider: String,
+    /// 指定或检测到的源语言，未知时为 None
+    pub source_lang: Option<LanguageIdentifier>,
+    /// 目标语言
+    pub target_lang: LanguageIdentifier,
+    /// 原文
+    pub text: String,
+    /// 译文
+    pub translation: String,
+}
+
+/// 交互模式的会话状态
+pub struct ReplSession {
+    manager: TranslationManager,
+    provider: String,
+    target_lang: LanguageIdentifier,
+    source_lang: Option<LanguageIdentifier>,
+    options: TranslateOptions,
+    history: Vec<HistoryEntry>,
+}
+
+impl ReplSession {
+    /// 创建会话，`provider` 必须是 `manager` 中已注册的翻译器
+    pub fn new(
+        manager: TranslationManager,
+        provider: impl Into<String>,
+        target_lang: LanguageIdentifier,
+    ) -> Result<Self, TranslationError> {
+        let provider = provider.into();
+        if !manager.has_translator(&provider) {
+            return Err(TranslationError::ConfigurationError(format!(
+                "Translator '{}' is not registered",
+                provider
+            )));
+        }
+        Ok(Self {
+            manager,
+            provider,
+            target_lang,
+            source_lang: None,
+            options: TranslateOptions::default(),
+            history: Vec::new(),
+        })
+    }
+
+    /// 设置源语言，None 表示自动检测
+    pub fn source_lang(mut self, source_lang: Option<LanguageIdentifier>) -> Self {
+        self.source_lang = source_lang;
+        self
+    }
+
+    /// 本次会话的翻译记录
+    pub fn history(&self) -> &[HistoryEntry] {
+        &self.history
+    }
+
+    /// 以 TSV 格式写出翻译记录，第一行为列名；字段中的制表符、换行和反斜杠被转义
+    pub fn write_history_tsv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "provider\tsource\ttarget\ttext\ttranslation")?;
+        for entry in &self.history {
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}\t{}",
+                escape_tsv(&entry.provider),
+                entry
+                    .source_lang
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                entry.target_lang,
+                escape_tsv(&entry.text),
+                escape_tsv(&entry.translation)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 运行会话，直到输入结束、收到 `:quit` 或在等待输入时收到中断
+    ///
+    /// `interrupt` 每次调用返回一个在收到中断（例如 Ctrl-C）时完成的 future，
+    /// 翻译期间完成时放弃当前请求并继续读取下一行。
+    pub async fn run<R, W, I, F>(
+        &mut self,
+        input: R,
+        output: &mut W,
+        mut interrupt: I,
+    ) -> io::Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+        I: FnMut() -> F,
+        F: Future<Output = ()>,
+    {
+        let mut lines = input.lines();
+        loop {
+            output.write_all(PROMPT.as_bytes()).await?;
+            output.flush().await?;
+            let line = tokio::select! {
+                line = lines.next_line() => line?,
+                _ = interrupt() => None,
+            };
+            let Some(line) = line else {
+                break;
+            };
+            let message = match parse_command(&line) {
+                Ok(None) => continue,
+                Ok(Some(Command::Quit)) => break,
+                Ok(Some(Command::Translate(text))) => tokio::select! {
+                    message = self.translate(text) => message,
+                    _ = interrupt() => "interrupted".to_string(),
+                },
+                Ok(Some(command)) => self.apply(command),
+                Err(message) => format!("error: {}", message),
+            };
+            output.write_all(message.as_bytes()).await?;
+            output.write_all(b"\n").await?;
+        }
+        output.flush().await
+    }
+
+    /// 翻译一行文本并记录，返回要输出的内容
+    async fn translate(&mut self, text: String) -> String {
+        let result = self
+            .manager
+            .translate_detailed_with_options(
+                &self.provider,
+                &text,
+                &self.target_lang,
+                self.source_lang.as_ref(),
+                &self.options,
+            )
+            .await;
+        let detail = match result {
+            Ok(detail) => detail,
+            Err(error) => return format!("error: {}", error),
+        };
+        let mut message = detail.text.clone();
+        if let Some((lang, score)) = &detail.detected_source {
+            message.push_str(&format!("\n  detected: {} ({:.2})", lang, score));
+        }
+        self.history.push(HistoryEntry {
+            provider: self.provider.clone(),
+            source_lang: self
+                .source_lang
+                .clone()
+                .or(detail.detected_source.map(|(lang, _)| lang)),
+            target_lang: self.target_lang.clone(),
+            text,
+            translation: detail.text,
+        });
+        message
+    }
+
+    /// 执行除翻译和 `:quit` 以外的命令，返回要输出的内容
+    fn apply(&mut self, command: Command) -> String {
+        match command {
+            Command::To(lang) => {
+                self.target_lang = lang;
+                format!("target language: {}", self.target_lang)
+            }
+            Command::Source(lang) => {
+                self.source_lang = lang;
+                match &self.source_lang {
+                    Some(lang) => format!("source language: {}", lang),
+                    None => "source language: auto".to_string(),
+                }
+            }
+            Command::Provider(name) => {
+                if self.manager.has_translator(&name) {
+                    self.provider = name;
+                    format!("provider: {}", self.provider)
+                } else {
+                    let mut available = self.manager.list_translators();
+                    available.sort();
+                    format!(
+                        "error: unknown provider '{}', available: {}",
+                        name,
+                        available.join(", ")
+                    )
+                }
+            }
+            Command::Options(settings) => {
+                let mut options = std::mem::take(&mut self.options);
+                for setting in settings {
+                    options = match setting {
+                        OptionSetting::Timeout(timeout) => options.timeout(timeout),
+                        OptionSetting::Retries(retries) => options.max_retries(retries),
+                        OptionSetting::QueueWait(wait) => options.max_queue_wait(wait),
+                    };
+                }
+                self.options = options;
+                "options updated".to_string()
+            }
+            Command::ResetOptions => {
+                self.options = TranslateOptions::default();
+                "options reset".to_string()
+            }
+            Command::Save(path) => {
+                let result = std::fs::File::create(&path).and_then(|file| {
+                    let mut writer = io::BufWriter::new(file);
+                    self.write_history_tsv(&mut writer)?;
+                    writer.flush()
+                });
+                match result {
+                    Ok(()) => format!("saved {} entries to {}", self.history.len(), path.display()),
+                    Err(error) => format!("error: cannot write {}: {}", path.display(), error),
+                }
+            }
+            Command::Help => HELP.trim_end().to_string(),
+            Command::Translate(_) | Command::Quit => unreachable!("handled by run"),
+        }
+    }
+}
+
+/// 转义 TSV 字段中的反斜杠、制表符和换行
+fn escape_tsv(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod tests {
+    use crate::manager::TranslationManager;
+    use crate::repl::{Command, OptionSetting, ReplSession, parse_command};
+    use crate::testing::MockTranslator;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use unic_langid::LanguageIdentifier;
+
+    fn lang(tag: &str) -> LanguageIdentifier {
+        tag.parse().unwrap()
+    }
+
+    fn session(translators: &[(&str, MockTranslator)]) -> ReplSession {
+        let mut manager = TranslationManager::new();
+        for (name, translator) in translators {
+            manager.add_translator(name, Box::new(translator.clone()));
+        }
+        ReplSession::new(manager, translators[0].0, lang("zh")).unwrap()
+    }
+
+    /// 运行会话直到输入结束，返回去掉提示符后的输出行
+    async fn run(session: &mut ReplSession, input: &str) -> Vec<String> {
+        let mut output = Vec::new();
+        session
+            .run(input.as_bytes(), &mut output, std::future::pending)
+            .await
+            .unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .split("> ")
+            .flat_map(|chunk| chunk.lines().map(str::to_string).collect::<Vec<_>>())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_command() {
+        let cases = [
+            ("", None),
+            ("   ", None),
+            (
+                "Hello, world!",
+                Some(Command::Translate("Hello, world!".into())),
+            ),
+            (":to ja", Some(Command::To(lang("ja")))),
+            (":source en", Some(Command::Source(Some(lang("en"))))),
+            (":source auto", Some(Command::Source(None))),
+            (":provider openai", Some(Command::Provider("openai".into()))),
+            (
+                ":options timeout=60 retries=0 queue_wait=0.5",
+                Some(Command::Options(vec![
+                    OptionSetting::Timeout(Duration::from_secs(60)),
+                    OptionSetting::Retries(0),
+                    OptionSetting::QueueWait(Duration::from_millis(500)),
+                ])),
+            ),
+            (":options reset", Some(Command::ResetOptions)),
+            (
+                ":save /tmp/session.tsv",
+                Some(Command::Save(PathBuf::from("/tmp/session.tsv"))),
+            ),
+            (":help", Some(Command::Help)),
+            (":q", Some(Command::Quit)),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(parse_command(line), Ok(expected), "parsing {:?}", line);
+        }
+
+        let errors = [
+            (":to", "usage: :to <lang>"),
+            (":to not_a_tag!", "invalid language tag"),
+            (":options timeout=0", "positive number of seconds"),
+            (":options retries=-1", "non-negative integer"),
+            (":options colour=red", "unknown option 'colour'"),
+            (":options timeout", "expected key=value"),
+            (":frobnicate", "unknown command ':frobnicate'"),
+        ];
+        for (line, expected) in errors {
+            match parse_command(line) {
+                Err(message) => assert!(message.contains(expected), "{}: {}", line, message),
+                other => panic!("expected an error for {:?}, got {:?}", line, other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_commands_and_history() {
+        let microsoft = MockTranslator::new().prefix("MS: ");
+        let openai = MockTranslator::new().prefix("AI: ");
+        let mut session = session(&[("microsoft", microsoft.clone()), ("openai", openai.clone())]);
+        let output = run(
+            &mut session,
+            "Hello\n\
+             :to ja\n\
+             :source en\n\
+             :provider openai\n\
+             Tab\there\n\
+             :provider deepl\n\
+             :options timeout=5 retries=1\n\
+             :bogus\n\
+             :quit\n\
+             never translated\n",
+        )
+        .await;
+        assert_eq!(
+            output,
+            vec![
+                "MS: Hello",
+                "target language: ja",
+                "source language: en",
+                "provider: openai",
+                "AI: Tab\there",
+                "error: unknown provider 'deepl', available: microsoft, openai",
+                "options updated",
+                "error: unknown command ':bogus', type :help for a list",
+            ]
+        );
+        // 同一个管理器和翻译器实例处理所有行
+        assert_eq!((microsoft.calls(), openai.calls()), (1, 1));
+
+        let history = session.history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].source_lang, None);
+        assert_eq!(history[1].source_lang, Some(lang("en")));
+        assert_eq!(history[1].target_lang, lang("ja"));
+
+        let mut tsv = Vec::new();
+        session.write_history_tsv(&mut tsv).unwrap();
+        assert_eq!(
+            String::from_utf8(tsv).unwrap(),
+            "provider\tsource\ttarget\ttext\ttranslation\n\
+             microsoft\t\tzh\tHello\tMS: Hello\n\
+             openai\ten\tja\tTab\\there\tAI: Tab\\there\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_save_writes_history_file() {
+        let mut session = session(&[("mock", MockTranslator::new())]);
+        let path =
+            std::env::temp_dir().join(format!("async-translate-repl-{}.tsv", std::process::id()));
+        let output = run(&mut session, &format!("Hello\n:save {}\n", path.display())).await;
+        assert_eq!(output[1], format!("saved 1 entries to {}", path.display()));
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            saved.lines().nth(1),
+            Some("mock\t\tzh\tHello\tTranslated: Hello")
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_interrupt_cancels_request() {
+        let translator = MockTranslator::new().latency(Duration::from_secs(60));
+        let mut session = session(&[("mock", translator.clone())]);
+        let mut output = Vec::new();
+        // 等待输入时不中断，翻译期间中断
+        let mut interrupts = 0;
+        session
+            .run("slow\n".as_bytes(), &mut output, || {
+                interrupts += 1;
+                let fire = interrupts > 1;
+                async move {
+                    if fire {
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                    } else {
+                        std::future::pending::<()>().await;
+                    }
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "> interrupted\n> ");
+        assert_eq!(translator.calls(), 1);
+        assert!(session.history().is_empty());
+
+        // 等待输入时中断则结束会话
+        let (_writer, reader) = tokio::io::duplex(64);
+        let mut output = Vec::new();
+        session
+            .run(tokio::io::BufReader::new(reader), &mut output, || async {})
+            .await
+            .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "> ");
+    }
+}
@@ -0,0 +1,71 @@
+//! 重试与指数退避
+
+use crate::{
+    clock::Clock,
+    error::TranslationError,
+    options::{Deadline, RetryEvent, RetryHook},
+};
+use std::future::Future;
+use std::time::Duration;
+
+/// 第 `attempt` 次重试（从 1 开始）前的退避时长：100ms、200ms、400ms……
+pub(crate) fn backoff_delay(attempt: u32) -> Duration {
+    let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+    Duration::from_millis(100u64.saturating_mul(factor))
+}
+
+/// 执行操作，遇到可重试的错误时按指数退避重试
+///
+/// 最多执行 `max_retries + 1` 次；遇到不可重试的错误立即返回该错误，
+/// 重试次数耗尽时返回包含每次错误的 [`TranslationError::MaxRetriesExceeded`]。
+/// 遇到可重试的错误时，总时限已到期或退避等待会超出总时限则返回
+/// [`TranslationError::DeadlineExceeded`]。
+/// 每次退避等待之前调用 `on_retry`，回调看不到也改变不了重试的决策
+pub(crate) async fn retry<T, F, Fut>(
+    clock: &dyn Clock,
+    max_retries: u32,
+    deadline: Option<Deadline>,
+    provider: &str,
+    on_retry: Option<&RetryHook>,
+    mut operation: F,
+) -> Result<T, TranslationError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, TranslationError>>,
+{
+    let mut errors = Vec::new();
+    for attempt in 0..=max_retries {
+        match operation().await {
+            Ok(result) => return Ok(result),
+            // 只在可重试的错误上继续
+            Err(e) if e.is_retryable() => errors.push(e),
+            Err(e) => return Err(e),
+        }
+        let delay = if attempt < max_retries {
+            backoff_delay(attempt + 1)
+        } else {
+            Duration::ZERO
+        };
+        if let Some(deadline) = deadline {
+            deadline.check(clock.now(), delay)?;
+        }
+        if attempt < max_retries {
+            if let (Some(hook), Some(error)) = (on_retry, errors.last()) {
+                hook.notify(RetryEvent {
+                    attempt: attempt + 1,
+                    error,
+                    next_delay: delay,
+                    provider,
+                });
+            }
+            clock.sleep(delay).await;
+        }
+    }
+    Err(TranslationError::MaxRetriesExceeded {
+        attempts: max_retries + 1,
+        errors,
+    })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,222 @@
+#[cfg(test)]
+mod tests {
+    use crate::clock::Clock;
+    use crate::error::TranslationError;
+    use crate::options::{Deadline, RetryHook};
+    use crate::retry::{backoff_delay, retry};
+    use crate::testing::MockClock;
+    use crate::{RetryEvent, TranslateOptions};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    fn service_unavailable() -> TranslationError {
+        TranslationError::HttpError {
+            status: reqwest::StatusCode::SERVICE_UNAVAILABLE,
+            body: "busy".to_string(),
+            trace: None,
+        }
+    }
+
+    /// 通过选项构造重试回调，同时返回回调收到的 (次数, 错误, 退避时长, 服务名)
+    #[allow(clippy::type_complexity)]
+    fn recording_hook(
+        panic_on_first: bool,
+    ) -> (RetryHook, Arc<Mutex<Vec<(u32, String, Duration, String)>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let options =
+            TranslateOptions::default().on_retry(Arc::new(move |event: RetryEvent<'_>| {
+                recorded.lock().unwrap().push((
+                    event.attempt,
+                    event.error.to_string(),
+                    event.next_delay,
+                    event.provider.to_string(),
+                ));
+                if panic_on_first && event.attempt == 1 {
+                    panic!("callback failure");
+                }
+            }));
+        (options.get_on_retry().unwrap(), events)
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(1), Duration::from_millis(100));
+        assert_eq!(backoff_delay(2), Duration::from_millis(200));
+        assert_eq!(backoff_delay(3), Duration::from_millis(400));
+        // 极大的重试次数不会溢出
+        assert_eq!(backoff_delay(u32::MAX), Duration::from_millis(u64::MAX));
+    }
+
+    #[tokio::test]
+    async fn test_retry_backoff_sequence() {
+        let clock = MockClock::new().auto_advance(true);
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = retry(&clock, 3, None, "test", None, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(TranslationError::TimeoutError)
+        })
+        .await;
+
+        match result {
+            Err(TranslationError::MaxRetriesExceeded { attempts, errors }) => {
+                assert_eq!(attempts, 4);
+                assert_eq!(errors.len(), 4);
+            }
+            other => panic!("Expected MaxRetriesExceeded, got {:?}", other),
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            clock.sleeps(),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(400)
+            ]
+        );
+        assert_eq!(clock.elapsed(), Duration::from_millis(700));
+    }
+
+    #[tokio::test]
+    async fn test_retry_succeeds_after_failure() {
+        let clock = MockClock::new().auto_advance(true);
+        let calls = AtomicU32::new(0);
+        let result = retry(&clock, 3, None, "test", None, || async {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(TranslationError::TimeoutError)
+            } else {
+                Ok("done")
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(100)]);
+    }
+
+    #[tokio::test]
+    async fn test_retry_stops_on_non_retryable() {
+        let clock = MockClock::new().auto_advance(true);
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = retry(&clock, 3, None, "test", None, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(TranslationError::AuthenticationError("denied".to_string()))
+        })
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(TranslationError::AuthenticationError(_))
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(clock.sleeps().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retry_waits_for_clock() {
+        let clock = MockClock::new();
+        let calls = AtomicU32::new(0);
+        let operation = retry(&clock, 1, None, "test", None, || async {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(TranslationError::TimeoutError)
+            } else {
+                Ok(())
+            }
+        });
+        let driver = async {
+            clock.wait_for_sleepers(1).await;
+            // 时钟未推进到退避时长之前不会重试
+            clock.advance(Duration::from_millis(99));
+            tokio::task::yield_now().await;
+            assert_eq!(calls.load(Ordering::SeqCst), 1);
+            clock.advance(Duration::from_millis(1));
+        };
+        let (result, ()) = tokio::join!(operation, driver);
+        assert!(result.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_on_retry_reports_each_backoff() {
+        let clock = MockClock::new().auto_advance(true);
+        let (hook, events) = recording_hook(false);
+        let calls = AtomicU32::new(0);
+        let result = retry(&clock, 3, None, "scripted", Some(&hook), || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(TranslationError::TimeoutError),
+                1 => Err(service_unavailable()),
+                _ => Ok("done"),
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                (
+                    1,
+                    TranslationError::TimeoutError.to_string(),
+                    Duration::from_millis(100),
+                    "scripted".to_string()
+                ),
+                (
+                    2,
+                    service_unavailable().to_string(),
+                    Duration::from_millis(200),
+                    "scripted".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            clock.sleeps(),
+            vec![Duration::from_millis(100), Duration::from_millis(200)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_on_retry_panic_does_not_change_retries() {
+        let clock = MockClock::new().auto_advance(true);
+        let (hook, events) = recording_hook(true);
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = retry(&clock, 2, None, "scripted", Some(&hook), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(TranslationError::TimeoutError)
+        })
+        .await;
+
+        // 第一次回调 panic 后仍然按原计划重试，最后一次失败后不再调用回调
+        assert!(matches!(
+            result,
+            Err(TranslationError::MaxRetriesExceeded { attempts: 3, .. })
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        let attempts: Vec<u32> = events.lock().unwrap().iter().map(|e| e.0).collect();
+        assert_eq!(attempts, vec![1, 2]);
+        assert_eq!(
+            clock.sleeps(),
+            vec![Duration::from_millis(100), Duration::from_millis(200)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_stops_before_backoff_exceeds_deadline() {
+        let clock = MockClock::new().auto_advance(true);
+        let deadline = Deadline::new(Duration::from_millis(250)).start(clock.now());
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = retry(&clock, 5, Some(deadline), "test", None, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(TranslationError::TimeoutError)
+        })
+        .await;
+
+        // 100ms 和 200ms 的退避共 300ms，第二次退避前放弃
+        assert!(matches!(
+            result,
+            Err(TranslationError::DeadlineExceeded { .. })
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(clock.sleeps(), vec![Duration::from_millis(100)]);
+    }
+}
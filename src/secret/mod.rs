@@ -0,0 +1,99 @@
+//! API Key 等敏感信息的包装类型
+//!
+//! [`SecretString`] 的 `Debug` 输出固定为 `***`，原文只能通过
+//! [`SecretString::expose_secret`] 取得。启用 `zeroize` feature 后，释放时会清零内存。
+
+use std::borrow::Cow;
+use std::fmt;
+
+/// 不会出现在 `Debug` 输出中的字符串
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    /// 包装敏感字符串
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(secret.into())
+    }
+
+    /// 取得原文，仅应在构造请求头时使用
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
+    /// 是否为空字符串
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 把文本中出现的原文替换为 `***`，没有出现时不复制文本
+    pub(crate) fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.0.is_empty() || !text.contains(&self.0) {
+            Cow::Borrowed(text)
+        } else {
+            Cow::Owned(text.replace(&self.0, "***"))
+        }
+    }
+
+    /// 与 [`SecretString::redact`] 相同，没有出现原文时直接返回传入的字符串
+    pub(crate) fn redact_owned(&self, text: String) -> String {
+        match self.redact(&text) {
+            Cow::Borrowed(_) => text,
+            Cow::Owned(redacted) => redacted,
+        }
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        Self(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        Self(secret.to_string())
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.0);
+    }
+}
+
+/// 隐藏 URL 中的查询参数，避免其中的 Key 出现在错误信息中
+pub(crate) fn redact_url(url: &str) -> String {
+    match url.split_once('?') {
+        Some((base, _)) => format!("{}?***", base),
+        None => url.to_string(),
+    }
+}
+
+/// 值中含有凭据的请求头（不区分大小写）
+const SENSITIVE_HEADERS: &[&str] = &["authorization", "ocp-apim-subscription-key", "api-key"];
+
+/// 隐藏敏感请求头的值，`Authorization` 等带有认证方案的值保留方案名称，
+/// 例如 `Bearer sk-...` 显示为 `Bearer ***`
+pub(crate) fn redact_header(name: &str, value: &str) -> String {
+    if !SENSITIVE_HEADERS
+        .iter()
+        .any(|sensitive| name.eq_ignore_ascii_case(sensitive))
+    {
+        return value.to_string();
+    }
+    match value.split_once(' ') {
+        Some((scheme, _)) => format!("{} ***", scheme),
+        None => "***".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests;
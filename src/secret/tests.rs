@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use crate::secret::{SecretString, redact_header, redact_url};
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let secret = SecretString::new("sk-very-secret");
+        assert_eq!(format!("{:?}", secret), "***");
+        assert_eq!(format!("{:?}", Some(secret.clone())), "Some(***)");
+        assert_eq!(secret.expose_secret(), "sk-very-secret");
+    }
+
+    #[test]
+    fn test_redact_text() {
+        let secret = SecretString::from("sk-abc");
+        assert_eq!(
+            secret.redact("Incorrect API key provided: sk-abc."),
+            "Incorrect API key provided: ***."
+        );
+        assert_eq!(SecretString::default().redact("unchanged"), "unchanged");
+
+        // 没有出现原文时不复制文本
+        assert!(matches!(secret.redact("no key here"), Cow::Borrowed(_)));
+        assert_eq!(
+            secret.redact_owned("key sk-abc and sk-abc".to_string()),
+            "key *** and ***"
+        );
+        let body = "no key here".to_string();
+        let pointer = body.as_ptr();
+        let unchanged = secret.redact_owned(body);
+        assert_eq!(unchanged.as_ptr(), pointer);
+    }
+
+    #[test]
+    fn test_redact_url() {
+        assert_eq!(
+            redact_url("ftp://host/v1?key=sk-abc&x=1"),
+            "ftp://host/v1?***"
+        );
+        assert_eq!(redact_url("https://host/v1"), "https://host/v1");
+    }
+
+    #[test]
+    fn test_redact_header() {
+        assert_eq!(
+            redact_header("Authorization", "Bearer sk-abc"),
+            "Bearer ***"
+        );
+        assert_eq!(redact_header("api-key", "sk-abc"), "***");
+        assert_eq!(
+            redact_header("Content-Type", "application/json"),
+            "application/json"
+        );
+    }
+}
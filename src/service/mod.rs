@@ -0,0 +1,464 @@
+//! 在服务端中提供翻译
+//!
+//! [`TranslationService`] 把 [`TranslationManager`] 包装为
+//! `tower::Service<TranslationRequest>`，可以套用 tower 的超时、限流等中间件。
+//! [`translation_router`] 在此基础上提供现成的 axum 路由：
+//!
+//! * `POST /translate`：请求体为 `{"provider": "openai", "text": "Hello", "target": "zh"}`，
+//!   可选 `"source": "en"`，返回译文及检测到的源语言等信息；
+//! * `POST /translate_batch`：请求体中用 `texts` 数组代替 `text`，返回
+//!   `{"results": [...]}`，每一项是一条译文或该条的 `{"error": ...}`，一条失败不影响其他结果。
+//!
+//! 请求级别的错误按 [`ErrorCode`] 映射为 HTTP 状态码（见 [`status_for`]），响应体为
+//! `{"error": {"code": "rate_limited", "message": "..."}}`。HTTP 接口使用管理器和翻译器的
+//! 默认选项，需要按调用设置选项时直接调用 [`TranslationService`]。
+
+use crate::batch::{self, CharBudget};
+use crate::error::TranslationError;
+use crate::error_code::ErrorCode;
+use crate::manager::{TAGGED_BATCH_CONCURRENCY, TranslationManager};
+use crate::options::TranslateOptions;
+use crate::translator::{TranslationDetail, TranslationKind};
+use axum::Json;
+use axum::Router;
+use axum::extract::State;
+use axum::extract::rejection::JsonRejection;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::task::{Context, Poll};
+use unic_langid::LanguageIdentifier;
+
+/// 需要翻译的一条或多条文本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranslationInput {
+    /// 一条文本
+    Single(String),
+    /// 多条文本，按 [`crate::TranslationManager::translate_batch_tagged`] 的方式逐条翻译
+    Batch(Vec<String>),
+}
+
+/// 交给 [`TranslationService`] 的一次翻译
+#[derive(Debug, Clone)]
+pub struct TranslationRequest {
+    /// 翻译器名称或别名
+    pub provider: String,
+    /// 需要翻译的文本
+    pub texts: TranslationInput,
+    /// 目标语言
+    pub target: LanguageIdentifier,
+    /// 源语言，None 表示自动检测
+    pub source: Option<LanguageIdentifier>,
+    /// 翻译配置选项
+    pub options: TranslateOptions,
+}
+
+impl TranslationRequest {
+    /// 翻译一条文本的请求
+    pub fn single(
+        provider: impl Into<String>,
+        text: impl Into<String>,
+        target: LanguageIdentifier,
+    ) -> Self {
+        Self {
+            provider: provider.into(),
+            texts: TranslationInput::Single(text.into()),
+            target,
+            source: None,
+            options: TranslateOptions::default(),
+        }
+    }
+
+    /// 翻译多条文本的请求
+    pub fn batch(
+        provider: impl Into<String>,
+        texts: Vec<String>,
+        target: LanguageIdentifier,
+    ) -> Self {
+        Self {
+            provider: provider.into(),
+            texts: TranslationInput::Batch(texts),
+            target,
+            source: None,
+            options: TranslateOptions::default(),
+        }
+    }
+
+    /// 设置源语言
+    pub fn source(mut self, source: LanguageIdentifier) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// 设置翻译配置选项
+    pub fn options(mut self, options: TranslateOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+/// [`TranslationService`] 的翻译结果，与请求的 [`TranslationInput`] 对应
+#[derive(Debug)]
+pub enum TranslationResponse {
+    /// 一条文本的详细结果
+    Single(Box<TranslationDetail>),
+    /// 按输入顺序排列的每条文本的结果
+    Batch(Vec<Result<TranslationDetail, TranslationError>>),
+}
+
+/// 以 tower `Service` 的形式调用 [`TranslationManager`]，克隆得到的服务共享同一组翻译器
+///
+/// 批量请求中的每条文本单独翻译，单条失败记录在对应的结果中；翻译器不存在，或者整批超出
+/// `max_batch_chars` 且没有启用 `truncate_to_budget` 时，整个请求返回错误
+#[derive(Clone)]
+pub struct TranslationService {
+    manager: TranslationManager,
+}
+
+impl TranslationService {
+    /// 包装管理器
+    pub fn new(manager: TranslationManager) -> Self {
+        Self { manager }
+    }
+
+    /// 执行一次翻译
+    pub async fn translate(
+        &self,
+        request: TranslationRequest,
+    ) -> Result<TranslationResponse, TranslationError> {
+        let TranslationRequest {
+            provider,
+            texts,
+            target,
+            source,
+            options,
+        } = request;
+        match texts {
+            TranslationInput::Single(text) => self
+                .single(&provider, &text, &target, source.as_ref(), &options)
+                .await
+                .map(|detail| TranslationResponse::Single(Box::new(detail))),
+            TranslationInput::Batch(texts) => self
+                .batch(&provider, texts, &target, source.as_ref(), &options)
+                .await
+                .map(TranslationResponse::Batch),
+        }
+    }
+
+    async fn single(
+        &self,
+        provider: &str,
+        text: &str,
+        target: &LanguageIdentifier,
+        source: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<TranslationDetail, TranslationError> {
+        self.manager
+            .translate_detailed_with_options(provider, text, target, source, options)
+            .await
+    }
+
+    async fn batch(
+        &self,
+        provider: &str,
+        texts: Vec<String>,
+        target: &LanguageIdentifier,
+        source: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<Vec<Result<TranslationDetail, TranslationError>>, TranslationError> {
+        if !self.manager.has_translator(provider) {
+            return Err(TranslationError::ConfigurationError(format!(
+                "Translator '{}' not found",
+                provider
+            )));
+        }
+        let budget = CharBudget::new(
+            options.get_max_batch_chars(),
+            options.get_truncate_to_budget(),
+        );
+        budget.apply(&texts)?;
+        let results = batch::translate_tagged(
+            texts.into_iter().enumerate().collect(),
+            options.get_dedup(),
+            TAGGED_BATCH_CONCURRENCY,
+            budget,
+            |text| async move { self.single(provider, &text, target, source, options).await },
+        )
+        .await;
+        Ok(results.into_iter().map(|(_, result)| result).collect())
+    }
+}
+
+impl std::fmt::Debug for TranslationService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TranslationService")
+            .field("translators", &self.manager.list_translators())
+            .finish()
+    }
+}
+
+impl tower_service::Service<TranslationRequest> for TranslationService {
+    type Response = TranslationResponse;
+    type Error = TranslationError;
+    type Future = BoxFuture<'static, Result<TranslationResponse, TranslationError>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // 并发和限流由管理器及翻译器控制，服务始终可以接收请求
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: TranslationRequest) -> Self::Future {
+        let service = self.clone();
+        async move { service.translate(request).await }.boxed()
+    }
+}
+
+/// 错误码对应的 HTTP 状态码
+///
+/// 限流和额度用完返回 429；调用方的输入有问题（语言不支持、文本过长、无法识别源语言、
+/// 内容策略禁止发送）返回 400；翻译服务认证失败或返回了错误及无法使用的响应，
+/// 属于上游的问题，返回 502；超时返回 504；过载、维护和关闭中返回 503；其他返回 500
+pub fn status_for(code: ErrorCode) -> StatusCode {
+    match code {
+        ErrorCode::RateLimited | ErrorCode::QuotaExceeded => StatusCode::TOO_MANY_REQUESTS,
+        ErrorCode::UnsupportedLanguage
+        | ErrorCode::TextTooLong
+        | ErrorCode::DetectionUncertain
+        | ErrorCode::PolicyViolation => StatusCode::BAD_REQUEST,
+        ErrorCode::AuthFailed
+        | ErrorCode::NetworkUnavailable
+        | ErrorCode::ModelUnavailable
+        | ErrorCode::ProviderError
+        | ErrorCode::InvalidResponse
+        | ErrorCode::SuspiciousOutput
+        | ErrorCode::ProtectedTextLost => StatusCode::BAD_GATEWAY,
+        ErrorCode::Timeout => StatusCode::GATEWAY_TIMEOUT,
+        ErrorCode::Overloaded | ErrorCode::TranslatorUnavailable | ErrorCode::Cancelled => {
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+        ErrorCode::Configuration | ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// 创建提供 `POST /translate` 和 `POST /translate_batch` 的路由
+///
+/// ```no_run
+/// # async fn run(manager: async_translate::TranslationManager) -> std::io::Result<()> {
+/// use async_translate::service::translation_router;
+///
+/// let router = translation_router(manager);
+/// let listener = tokio::net::TcpListener::bind("127.0.0.1:8080").await?;
+/// axum::serve(listener, router).await
+/// # }
+/// ```
+pub fn translation_router(manager: TranslationManager) -> Router {
+    Router::new()
+        .route("/translate", post(translate))
+        .route("/translate_batch", post(translate_batch))
+        .with_state(TranslationService::new(manager))
+}
+
+#[derive(Deserialize)]
+struct TranslateBody {
+    provider: String,
+    text: String,
+    target: String,
+    #[serde(default)]
+    source: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TranslateBatchBody {
+    provider: String,
+    texts: Vec<String>,
+    target: String,
+    #[serde(default)]
+    source: Option<String>,
+}
+
+#[derive(Serialize)]
+struct DetailBody {
+    text: String,
+    detected_source: Option<DetectedBody>,
+    passthrough: bool,
+    partial: bool,
+    suspicious: bool,
+    unchanged: bool,
+}
+
+#[derive(Serialize)]
+struct DetectedBody {
+    language: String,
+    confidence: f64,
+}
+
+impl From<TranslationDetail> for DetailBody {
+    fn from(detail: TranslationDetail) -> Self {
+        Self {
+            text: detail.text,
+            detected_source: detail
+                .detected_source
+                .map(|(language, confidence)| DetectedBody {
+                    language: language.to_string(),
+                    confidence,
+                }),
+            passthrough: detail.passthrough,
+            partial: detail.partial,
+            suspicious: detail.suspicious,
+            unchanged: detail.result_kind == TranslationKind::Unchanged,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    code: String,
+    message: String,
+}
+
+impl From<&TranslationError> for ErrorBody {
+    fn from(error: &TranslationError) -> Self {
+        Self {
+            code: error.code().as_str().to_string(),
+            message: error.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ItemBody {
+    Translated(DetailBody),
+    Failed { error: ErrorBody },
+}
+
+#[derive(Serialize)]
+struct BatchBody {
+    results: Vec<ItemBody>,
+}
+
+/// 请求级别的错误响应
+struct ApiError {
+    status: StatusCode,
+    body: ErrorBody,
+}
+
+impl ApiError {
+    fn invalid_request(message: String) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            body: ErrorBody {
+                code: "invalid_request".to_string(),
+                message,
+            },
+        }
+    }
+}
+
+impl From<TranslationError> for ApiError {
+    fn from(error: TranslationError) -> Self {
+        Self {
+            status: status_for(error.code()),
+            body: ErrorBody::from(&error),
+        }
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::invalid_request(rejection.body_text())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        #[derive(Serialize)]
+        struct Wrapper {
+            error: ErrorBody,
+        }
+        (self.status, Json(Wrapper { error: self.body })).into_response()
+    }
+}
+
+/// 解析请求中的语言，并检查翻译器是否存在
+fn prepare(
+    service: &TranslationService,
+    provider: &str,
+    target: &str,
+    source: Option<&str>,
+) -> Result<(LanguageIdentifier, Option<LanguageIdentifier>), ApiError> {
+    if !service.manager.has_translator(provider) {
+        return Err(ApiError {
+            status: StatusCode::NOT_FOUND,
+            body: ErrorBody {
+                code: "unknown_provider".to_string(),
+                message: format!("Translator '{}' not found", provider),
+            },
+        });
+    }
+    let parse =
+        |tag: &str| crate::lang(tag).map_err(|error| ApiError::invalid_request(error.to_string()));
+    Ok((parse(target)?, source.map(parse).transpose()?))
+}
+
+async fn translate(
+    State(service): State<TranslationService>,
+    body: Result<Json<TranslateBody>, JsonRejection>,
+) -> Result<Json<DetailBody>, ApiError> {
+    let Json(body) = body?;
+    let (target, source) = prepare(
+        &service,
+        &body.provider,
+        &body.target,
+        body.source.as_deref(),
+    )?;
+    let detail = service
+        .single(
+            &body.provider,
+            &body.text,
+            &target,
+            source.as_ref(),
+            &TranslateOptions::default(),
+        )
+        .await?;
+    Ok(Json(detail.into()))
+}
+
+async fn translate_batch(
+    State(service): State<TranslationService>,
+    body: Result<Json<TranslateBatchBody>, JsonRejection>,
+) -> Result<Json<BatchBody>, ApiError> {
+    let Json(body) = body?;
+    let (target, source) = prepare(
+        &service,
+        &body.provider,
+        &body.target,
+        body.source.as_deref(),
+    )?;
+    let results = service
+        .batch(
+            &body.provider,
+            body.texts,
+            &target,
+            source.as_ref(),
+            &TranslateOptions::default(),
+        )
+        .await?;
+    Ok(Json(BatchBody {
+        results: results
+            .into_iter()
+            .map(|result| match result {
+                Ok(detail) => ItemBody::Translated(detail.into()),
+                Err(error) => ItemBody::Failed {
+                    error: ErrorBody::from(&error),
+                },
+            })
+            .collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::TranslationError;
+    use crate::error_code::ErrorCode;
+    use crate::manager::TranslationManager;
+    use crate::options::TranslateOptions;
+    use crate::service::{TranslationRequest, TranslationResponse, TranslationService, status_for};
+    use crate::testing::MockTranslator;
+    use axum::http::StatusCode;
+    use tower_service::Service;
+    use unic_langid::LanguageIdentifier;
+
+    fn service() -> TranslationService {
+        let mut manager = TranslationManager::new();
+        manager.add_translator("mock", Box::new(MockTranslator::new()));
+        TranslationService::new(manager)
+    }
+
+    #[test]
+    fn test_status_for_error_codes() {
+        let cases = [
+            (ErrorCode::RateLimited, StatusCode::TOO_MANY_REQUESTS),
+            (ErrorCode::QuotaExceeded, StatusCode::TOO_MANY_REQUESTS),
+            (ErrorCode::AuthFailed, StatusCode::BAD_GATEWAY),
+            (ErrorCode::ProviderError, StatusCode::BAD_GATEWAY),
+            (ErrorCode::UnsupportedLanguage, StatusCode::BAD_REQUEST),
+            (ErrorCode::TextTooLong, StatusCode::BAD_REQUEST),
+            (ErrorCode::PolicyViolation, StatusCode::BAD_REQUEST),
+            (ErrorCode::Timeout, StatusCode::GATEWAY_TIMEOUT),
+            (ErrorCode::Overloaded, StatusCode::SERVICE_UNAVAILABLE),
+            (ErrorCode::Cancelled, StatusCode::SERVICE_UNAVAILABLE),
+            (ErrorCode::Configuration, StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+        for (code, status) in cases {
+            assert_eq!(status_for(code), status, "{}", code);
+        }
+        // 每个错误码都有明确的状态码
+        assert!(ErrorCode::ALL.iter().all(|code| {
+            let status = status_for(*code);
+            status.is_client_error() || status.is_server_error()
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_service_call() {
+        let mut service = service();
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+
+        let response = service
+            .call(TranslationRequest::single("mock", "Hello", zh.clone()))
+            .await
+            .unwrap();
+        assert!(matches!(
+            response,
+            TranslationResponse::Single(detail) if detail.text == "Translated: Hello"
+        ));
+
+        let texts = vec!["a".to_string(), "bb".to_string(), "ccc".to_string()];
+        let request = TranslationRequest::batch("mock", texts.clone(), zh.clone()).options(
+            TranslateOptions::default()
+                .max_batch_chars(3)
+                .truncate_to_budget(true),
+        );
+        let TranslationResponse::Batch(results) = service.call(request).await.unwrap() else {
+            panic!("expected batch results");
+        };
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[1].as_ref().unwrap().text, "Translated: bb");
+        assert!(matches!(
+            results[2],
+            Err(TranslationError::BudgetExceeded { total: 6, max: 3 })
+        ));
+
+        // 超出预算且不截断、翻译器不存在时整个请求失败
+        let request = TranslationRequest::batch("mock", texts.clone(), zh.clone())
+            .options(TranslateOptions::default().max_batch_chars(3));
+        assert!(matches!(
+            service.call(request).await,
+            Err(TranslationError::BudgetExceeded { .. })
+        ));
+        let request = TranslationRequest::batch("missing", texts, zh);
+        assert!(matches!(
+            service.call(request).await,
+            Err(TranslationError::ConfigurationError(_))
+        ));
+    }
+}
@@ -0,0 +1,296 @@
+//! 按时间窗口统计
+//!
+//! 长时间运行的服务通常需要按小时或按天查看统计，而不是自启动以来的累计值。
+//! [`StatsWindow`] 把一组计数器放在同一把锁后面：更新、读取和
+//! [`StatsWindow::snapshot_and_reset`] 都在锁内完成，因此同一窗口中的所有计数器
+//! 同时切换，切换期间的并发更新只会计入旧窗口或新窗口之一，不会丢失。
+//!
+//! 设置了 [`WindowPeriod`] 时，窗口按 UTC 日历时间对齐（例如整点或零点），
+//! 时间取自注入的 [`Clock`]，每次访问时检查是否需要切换到新窗口。
+
+use crate::clock::{Clock, TokioClock};
+use crate::manager::{LanguagePair, PairStats};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 统计窗口的长度，窗口起点对齐到 Unix 纪元起该长度的整数倍
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowPeriod {
+    /// 每个整点开始一个窗口
+    Hourly,
+    /// 每天 UTC 零点开始一个窗口
+    Daily,
+    /// 自定义长度，为零时视为一秒
+    Every(Duration),
+}
+
+impl WindowPeriod {
+    /// 窗口长度
+    pub fn duration(self) -> Duration {
+        match self {
+            WindowPeriod::Hourly => Duration::from_secs(60 * 60),
+            WindowPeriod::Daily => Duration::from_secs(24 * 60 * 60),
+            WindowPeriod::Every(duration) => duration.max(Duration::from_secs(1)),
+        }
+    }
+
+    /// 包含 `time` 的窗口的起点
+    fn window_start(self, time: SystemTime) -> SystemTime {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let period = self.duration().as_nanos();
+        let offset = since_epoch.as_nanos() % period;
+        time - Duration::from_nanos(offset as u64)
+    }
+}
+
+/// 一个窗口中的统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowSnapshot<T> {
+    /// 窗口开始的时间
+    pub start: SystemTime,
+    /// 窗口结束的时间，当前窗口为读取时的时间
+    pub end: SystemTime,
+    /// 窗口中的统计
+    pub stats: T,
+}
+
+/// 按时间窗口重置的一组计数器
+pub struct StatsWindow<T> {
+    period: Option<WindowPeriod>,
+    clock: Arc<dyn Clock>,
+    state: Mutex<WindowState<T>>,
+}
+
+struct WindowState<T> {
+    start: SystemTime,
+    current: T,
+    previous: Option<WindowSnapshot<T>>,
+}
+
+impl<T: Default> StatsWindow<T> {
+    /// 创建只在调用 [`StatsWindow::snapshot_and_reset`] 时切换的窗口
+    pub fn new() -> Self {
+        Self::with_clock(None, Arc::new(TokioClock))
+    }
+
+    /// 创建按 `period` 自动切换的窗口，为 None 时只能手动切换
+    ///
+    /// # 参数
+    ///
+    /// * `period` - 窗口长度
+    /// * `clock` - 获取日历时间的时钟
+    pub fn with_clock(period: Option<WindowPeriod>, clock: Arc<dyn Clock>) -> Self {
+        let now = clock.system_time();
+        let start = period.map_or(now, |period| period.window_start(now));
+        Self {
+            period,
+            clock,
+            state: Mutex::new(WindowState {
+                start,
+                current: T::default(),
+                previous: None,
+            }),
+        }
+    }
+
+    /// 窗口长度，None 表示只能手动切换
+    pub fn period(&self) -> Option<WindowPeriod> {
+        self.period
+    }
+
+    /// 在当前窗口中更新统计
+    pub fn update<R>(&self, update: impl FnOnce(&mut T) -> R) -> R {
+        let mut state = self.lock();
+        update(&mut state.current)
+    }
+
+    /// 结束当前窗口并开始新窗口，返回刚结束的窗口
+    ///
+    /// 刚结束的窗口同时保存为 [`StatsWindow::previous`]
+    pub fn snapshot_and_reset(&self) -> WindowSnapshot<T>
+    where
+        T: Clone,
+    {
+        let mut state = self.lock();
+        let now = self.clock.system_time();
+        let snapshot = WindowSnapshot {
+            start: state.start,
+            end: now,
+            stats: std::mem::take(&mut state.current),
+        };
+        state.start = now;
+        state.previous = Some(snapshot.clone());
+        snapshot
+    }
+
+    /// 当前窗口到目前为止的统计
+    pub fn current(&self) -> WindowSnapshot<T>
+    where
+        T: Clone,
+    {
+        let state = self.lock();
+        let now = self.clock.system_time();
+        WindowSnapshot {
+            start: state.start,
+            end: now,
+            stats: state.current.clone(),
+        }
+    }
+
+    /// 上一个已结束的窗口，尚无已结束的窗口时为 None
+    pub fn previous(&self) -> Option<WindowSnapshot<T>>
+    where
+        T: Clone,
+    {
+        self.lock().previous.clone()
+    }
+
+    /// 当前窗口和上一个已结束的窗口，两者在同一次加锁中读取
+    pub fn current_and_previous(&self) -> (WindowSnapshot<T>, Option<WindowSnapshot<T>>)
+    where
+        T: Clone,
+    {
+        let state = self.lock();
+        let now = self.clock.system_time();
+        let current = WindowSnapshot {
+            start: state.start,
+            end: now,
+            stats: state.current.clone(),
+        };
+        (current, state.previous.clone())
+    }
+
+    /// 加锁，当前时间超出窗口时先切换到包含当前时间的窗口
+    ///
+    /// 中间没有任何更新的窗口不会保留，`previous` 总是最后一个已结束的窗口
+    fn lock(&self) -> std::sync::MutexGuard<'_, WindowState<T>> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(period) = self.period {
+            let now = self.clock.system_time();
+            // 手动切换后窗口起点不再对齐，结束时间仍按对齐的窗口计算
+            let end = period.window_start(state.start) + period.duration();
+            if now >= end {
+                let start = std::mem::replace(&mut state.start, period.window_start(now));
+                state.previous = Some(WindowSnapshot {
+                    start,
+                    end,
+                    stats: std::mem::take(&mut state.current),
+                });
+            }
+        }
+        state
+    }
+}
+
+impl<T: Default> Default for StatsWindow<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for StatsWindow<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StatsWindow")
+            .field("period", &self.period)
+            .finish_non_exhaustive()
+    }
+}
+
+/// [`crate::TranslationManager::export_stats`] 导出的统计，可以序列化为 JSON
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatsExport {
+    /// 窗口长度（毫秒），None 表示只能手动切换
+    pub period_ms: Option<u64>,
+    /// 当前窗口到目前为止的统计
+    pub current: WindowExport,
+    /// 上一个已结束的窗口，尚无已结束的窗口时为 None
+    pub previous: Option<WindowExport>,
+}
+
+/// 一个窗口的统计
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WindowExport {
+    /// 窗口开始的 Unix 时间戳（毫秒）
+    pub start_unix_ms: u64,
+    /// 窗口结束的 Unix 时间戳（毫秒），当前窗口为导出的时间
+    pub end_unix_ms: u64,
+    /// 成功的翻译数
+    pub successes: u64,
+    /// 失败的翻译数
+    pub failures: u64,
+    /// 各语言对的统计，按翻译器名称、源语言和目标语言排序
+    pub pairs: Vec<PairExport>,
+}
+
+/// 单个语言对的统计
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PairExport {
+    /// 翻译器名称
+    pub provider: String,
+    /// 调用方指定的源语言，None 表示自动检测
+    pub source_lang: Option<String>,
+    /// 目标语言
+    pub target_lang: String,
+    /// 成功的翻译数
+    pub successes: u64,
+    /// 失败的翻译数
+    pub failures: u64,
+    /// 成功翻译的平均耗时（毫秒）
+    pub average_latency_ms: f64,
+}
+
+impl WindowExport {
+    pub(crate) fn new(window: &WindowSnapshot<HashMap<LanguagePair, PairStats>>) -> Self {
+        let mut pairs: Vec<PairExport> = window
+            .stats
+            .iter()
+            .map(|(pair, stats)| PairExport {
+                provider: pair.provider.clone(),
+                source_lang: pair.source_lang.as_ref().map(ToString::to_string),
+                target_lang: pair.target_lang.to_string(),
+                successes: stats.successes,
+                failures: stats.failures,
+                average_latency_ms: stats.average_latency().as_secs_f64() * 1000.0,
+            })
+            .collect();
+        pairs.sort_by(|a, b| {
+            (&a.provider, &a.source_lang, &a.target_lang).cmp(&(
+                &b.provider,
+                &b.source_lang,
+                &b.target_lang,
+            ))
+        });
+        Self {
+            start_unix_ms: unix_ms(window.start),
+            end_unix_ms: unix_ms(window.end),
+            successes: pairs.iter().map(|pair| pair.successes).sum(),
+            failures: pairs.iter().map(|pair| pair.failures).sum(),
+            pairs,
+        }
+    }
+}
+
+impl StatsExport {
+    pub(crate) fn new(
+        period: Option<WindowPeriod>,
+        current: &WindowSnapshot<HashMap<LanguagePair, PairStats>>,
+        previous: Option<&WindowSnapshot<HashMap<LanguagePair, PairStats>>>,
+    ) -> Self {
+        Self {
+            period_ms: period.map(|period| period.duration().as_millis() as u64),
+            current: WindowExport::new(current),
+            previous: previous.map(WindowExport::new),
+        }
+    }
+}
+
+pub(crate) fn unix_ms(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests;
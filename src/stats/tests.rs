@@ -0,0 +1,163 @@
+#[cfg(test)]
+mod tests {
+    use crate::manager::TranslationManager;
+    use crate::stats::{StatsWindow, WindowPeriod};
+    use crate::testing::{MockClock, MockTranslator};
+    use std::sync::Arc;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use unic_langid::LanguageIdentifier;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// 从 Unix 纪元起第 `days` 天的 `hours` 时 `minutes` 分（UTC）
+    fn at(days: u64, hours: u64, minutes: u64) -> SystemTime {
+        UNIX_EPOCH + DAY * days as u32 + HOUR * hours as u32 + Duration::from_secs(minutes * 60)
+    }
+
+    #[test]
+    fn test_daily_window_rolls_over_at_midnight() {
+        let clock = MockClock::new().wall_clock(at(100, 23, 58));
+        let window: StatsWindow<u64> =
+            StatsWindow::with_clock(Some(WindowPeriod::Daily), Arc::new(clock.clone()));
+        assert_eq!(window.current().start, at(100, 0, 0));
+
+        window.update(|count| *count += 3);
+        clock.advance(Duration::from_secs(60));
+        window.update(|count| *count += 1);
+        assert_eq!(window.current().stats, 4);
+        assert_eq!(window.previous(), None);
+
+        // 跨过零点后第一次访问时切换
+        clock.advance(Duration::from_secs(120));
+        window.update(|count| *count += 10);
+        let previous = window.previous().unwrap();
+        assert_eq!(
+            (previous.start, previous.end, previous.stats),
+            (at(100, 0, 0), at(101, 0, 0), 4)
+        );
+        let current = window.current();
+        assert_eq!((current.start, current.stats), (at(101, 0, 0), 10));
+
+        // 中间没有更新的窗口不保留
+        clock.advance(DAY * 3);
+        let current = window.current();
+        assert_eq!((current.start, current.stats), (at(104, 0, 0), 0));
+        let previous = window.previous().unwrap();
+        assert_eq!((previous.start, previous.stats), (at(101, 0, 0), 10));
+    }
+
+    #[test]
+    fn test_manual_reset_keeps_hourly_alignment() {
+        let clock = MockClock::new().wall_clock(at(5, 10, 0));
+        let window: StatsWindow<u64> =
+            StatsWindow::with_clock(Some(WindowPeriod::Hourly), Arc::new(clock.clone()));
+        window.update(|count| *count += 1);
+
+        clock.advance(Duration::from_secs(20 * 60));
+        let snapshot = window.snapshot_and_reset();
+        assert_eq!(
+            (snapshot.start, snapshot.end, snapshot.stats),
+            (at(5, 10, 0), at(5, 10, 20), 1)
+        );
+        assert_eq!(window.current().start, at(5, 10, 20));
+
+        // 手动切换后的窗口仍在 11 点结束
+        window.update(|count| *count += 2);
+        clock.advance(Duration::from_secs(40 * 60));
+        window.update(|count| *count += 5);
+        let previous = window.previous().unwrap();
+        assert_eq!(
+            (previous.start, previous.end, previous.stats),
+            (at(5, 10, 20), at(5, 11, 0), 2)
+        );
+        assert_eq!(window.current().start, at(5, 11, 0));
+    }
+
+    #[test]
+    fn test_concurrent_updates_are_not_lost_across_resets() {
+        const THREADS: usize = 8;
+        const INCREMENTS: u64 = 10_000;
+        let window: Arc<StatsWindow<(u64, u64)>> = Arc::new(StatsWindow::new());
+
+        let writers: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let window = window.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..INCREMENTS {
+                        // 同一窗口中的两个计数器同时切换
+                        window.update(|(requests, characters)| {
+                            *requests += 1;
+                            *characters += 5;
+                        });
+                    }
+                })
+            })
+            .collect();
+        let mut collected = (0, 0);
+        while writers.iter().any(|writer| !writer.is_finished()) {
+            let (requests, characters) = window.snapshot_and_reset().stats;
+            assert_eq!(characters, requests * 5);
+            collected.0 += requests;
+            collected.1 += characters;
+        }
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        let (requests, characters) = window.snapshot_and_reset().stats;
+        collected.0 += requests;
+        collected.1 += characters;
+
+        let total = THREADS as u64 * INCREMENTS;
+        assert_eq!(collected, (total, total * 5));
+    }
+
+    #[tokio::test]
+    async fn test_manager_export_stats() {
+        let clock = MockClock::new().wall_clock(at(200, 0, 59));
+        let mut manager = TranslationManager::new()
+            .with_stats_window(WindowPeriod::Hourly, Arc::new(clock.clone()));
+        manager.add_translator("mock", Box::new(MockTranslator::new()));
+        let zh: LanguageIdentifier = "zh".parse().unwrap();
+        let en: LanguageIdentifier = "en".parse().unwrap();
+
+        manager.translate("mock", "a", &zh, None).await.unwrap();
+        manager
+            .translate("mock", "b", &zh, Some(&en))
+            .await
+            .unwrap();
+        assert!(manager.translate("missing", "c", &zh, None).await.is_err());
+
+        let export = manager.export_stats();
+        assert_eq!(export.period_ms, Some(3_600_000));
+        assert_eq!(export.current.successes, 2);
+        assert_eq!(export.current.pairs.len(), 2);
+        assert_eq!(export.current.pairs[0].source_lang, None);
+        assert_eq!(export.current.pairs[1].source_lang.as_deref(), Some("en"));
+        assert!(export.previous.is_none());
+
+        clock.advance(Duration::from_secs(60 * 60));
+        manager.translate("mock", "d", &zh, None).await.unwrap();
+        let export = manager.export_stats();
+        let previous = export.previous.as_ref().unwrap();
+        assert_eq!(previous.successes, 2);
+        assert_eq!(
+            previous.end_unix_ms,
+            at(200, 1, 0)
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64
+        );
+        assert_eq!(export.current.successes, 1);
+        assert_eq!(manager.pair_stats().len(), 1);
+
+        let json = serde_json::to_value(&export).unwrap();
+        assert_eq!(json["current"]["pairs"][0]["provider"], "mock");
+        assert_eq!(json["current"]["pairs"][0]["target_lang"], "zh");
+        assert_eq!(json["previous"]["successes"], 2);
+
+        let finished = manager.snapshot_and_reset_stats();
+        assert_eq!(finished.successes, 1);
+        assert_eq!(manager.export_stats().current.successes, 0);
+    }
+}
@@ -0,0 +1,117 @@
+//! 以流的形式逐条返回批量翻译的结果
+//!
+//! `translate_batch` 要等最慢的一条完成后才返回全部结果。这里的两个函数对任意
+//! [`Translator`] 逐条调用 [`Translator::translate_with_options`]，每条完成后立即产出
+//! `(输入中的下标, 结果)`：
+//!
+//! * [`translate_batch_ordered_stream`] 按输入顺序产出：第 i 条只在前面所有条目都产出后才产出，
+//!   提前完成的结果暂存在大小为 `window` 的缓冲区中，适合按文档顺序逐段渲染；
+//! * [`translate_batch_unordered_stream`] 按完成顺序产出，先完成的先返回。
+//!
+//! 同时进行的翻译数不超过 `window`。按顺序产出时，排在前面的条目未完成前不会开始
+//! 窗口之外的条目，因此缓冲区中暂存的结果最多为 `window - 1` 条。单条失败或 panic
+//! 只影响这一条的结果；超出 `max_batch_chars` 时每条都得到
+//! [`TranslationError::BudgetExceeded`]，不发送请求，同时设置了 `truncate_to_budget` 时
+//! 只有预算外的条目得到该错误。
+
+use crate::{
+    batch::{self, CharBudget},
+    error::TranslationError,
+    options::TranslateOptions,
+    translator::Translator,
+};
+use futures::stream::{self, Stream, StreamExt};
+use std::future::Future;
+use unic_langid::LanguageIdentifier;
+
+/// 按输入顺序逐条产出批量翻译的结果
+///
+/// # 参数
+///
+/// * `translator` - 使用的翻译器
+/// * `texts` - 需要翻译的文本
+/// * `target_lang` - 目标语言标识符
+/// * `source_lang` - 源语言标识符 (None表示自动检测)
+/// * `options` - 翻译配置选项
+/// * `window` - 同时进行的翻译数，也是暂存提前完成的结果的上限，0 按 1 处理
+///
+/// # 返回值
+///
+/// 返回按下标递增顺序产出 `(下标, 结果)` 的流，每条输入恰好产出一次
+pub fn translate_batch_ordered_stream<'a, S>(
+    translator: &'a dyn Translator,
+    texts: impl IntoIterator<Item = S>,
+    target_lang: &'a LanguageIdentifier,
+    source_lang: Option<&'a LanguageIdentifier>,
+    options: &'a TranslateOptions,
+    window: usize,
+) -> impl Stream<Item = (usize, Result<String, TranslationError>)> + Send + 'a
+where
+    S: AsRef<str> + Send + 'a,
+{
+    translations(translator, texts, target_lang, source_lang, options).buffered(window.max(1))
+}
+
+/// 按完成顺序逐条产出批量翻译的结果
+///
+/// 参数与 [`translate_batch_ordered_stream`] 相同，`window` 只限制同时进行的翻译数
+pub fn translate_batch_unordered_stream<'a, S>(
+    translator: &'a dyn Translator,
+    texts: impl IntoIterator<Item = S>,
+    target_lang: &'a LanguageIdentifier,
+    source_lang: Option<&'a LanguageIdentifier>,
+    options: &'a TranslateOptions,
+    window: usize,
+) -> impl Stream<Item = (usize, Result<String, TranslationError>)> + Send + 'a
+where
+    S: AsRef<str> + Send + 'a,
+{
+    translations(translator, texts, target_lang, source_lang, options)
+        .buffer_unordered(window.max(1))
+}
+
+/// 每条文本的翻译，按输入顺序排列，尚未开始执行
+fn translations<'a, S>(
+    translator: &'a dyn Translator,
+    texts: impl IntoIterator<Item = S>,
+    target_lang: &'a LanguageIdentifier,
+    source_lang: Option<&'a LanguageIdentifier>,
+    options: &'a TranslateOptions,
+) -> impl Stream<Item = impl Future<Output = (usize, Result<String, TranslationError>)> + Send + 'a>
++ Send
++ 'a
+where
+    S: AsRef<str> + Send + 'a,
+{
+    let texts: Vec<S> = texts.into_iter().collect();
+    let budget = CharBudget::new(
+        options.get_max_batch_chars(),
+        options.get_truncate_to_budget(),
+    );
+    let (fit, budget_error) = match budget.apply(&texts) {
+        Ok(truncation) => (truncation.fit, truncation.error),
+        Err(error) => (0, Some(error)),
+    };
+    stream::iter(texts.into_iter().enumerate()).map(move |(index, text)| {
+        let budget_error = budget_error
+            .as_ref()
+            .filter(|_| index >= fit)
+            .map(TranslationError::duplicate);
+        async move {
+            if let Some(error) = budget_error {
+                return (index, Err(error));
+            }
+            let result = batch::catch_panic(translator.translate_with_options(
+                text.as_ref(),
+                target_lang,
+                source_lang,
+                options,
+            ))
+            .await;
+            (index, result)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests;
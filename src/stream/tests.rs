@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::TranslationError;
+    use crate::options::TranslateOptions;
+    use crate::stream::{translate_batch_ordered_stream, translate_batch_unordered_stream};
+    use crate::translator::Translator;
+    use futures::StreamExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use unic_langid::LanguageIdentifier;
+
+    /// 按原文中的毫秒数等待后返回大写的原文，原文为 `fail` 时失败，为 `panic` 时 panic
+    ///
+    /// 原文的格式为 `<文本>:<毫秒数>`
+    #[derive(Default)]
+    struct ScriptedTranslator {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+        completed: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Translator for ScriptedTranslator {
+        async fn translate_with_options(
+            &self,
+            text: &str,
+            _target_lang: &LanguageIdentifier,
+            _source_lang: Option<&LanguageIdentifier>,
+            _options: &TranslateOptions,
+        ) -> Result<String, TranslationError> {
+            let (text, delay) = text.split_once(':').unwrap();
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(delay.parse().unwrap())).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            self.completed.fetch_add(1, Ordering::SeqCst);
+            match text {
+                "fail" => Err(TranslationError::ServiceError("scripted".to_string())),
+                "panic" => panic!("scripted panic"),
+                _ => Ok(text.to_uppercase()),
+            }
+        }
+    }
+
+    const TEXTS: [&str; 6] = ["a:50", "b:10", "fail:30", "c:0", "panic:20", "d:5"];
+
+    #[tokio::test(start_paused = true)]
+    async fn test_ordered_stream_yields_in_input_order() {
+        let translator = ScriptedTranslator::default();
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        let mut stream = Box::pin(translate_batch_ordered_stream(
+            &translator,
+            TEXTS,
+            &target_lang,
+            None,
+            &options,
+            3,
+        ));
+
+        let mut results = Vec::new();
+        let mut max_buffered = 0;
+        while let Some((index, result)) = stream.next().await {
+            // 已完成但尚未产出的结果，不包括刚产出的这一条
+            let buffered = translator.completed.load(Ordering::SeqCst) - results.len() - 1;
+            max_buffered = max_buffered.max(buffered);
+            results.push((index, result));
+        }
+
+        let indices: Vec<usize> = results.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indices, [0, 1, 2, 3, 4, 5]);
+        assert_eq!(results[0].1.as_deref().unwrap(), "A");
+        assert!(matches!(
+            results[2].1,
+            Err(TranslationError::ServiceError(_))
+        ));
+        match &results[4].1 {
+            Err(TranslationError::Other(msg)) => assert!(msg.contains("scripted panic")),
+            other => panic!("Expected Other error, got {:?}", other),
+        }
+        assert_eq!(results[5].1.as_deref().unwrap(), "D");
+        // 第一条最慢，完成前后面两条已经完成并暂存，但不会开始窗口之外的条目
+        assert_eq!(translator.max_in_flight.load(Ordering::SeqCst), 3);
+        assert_eq!(max_buffered, 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unordered_stream_yields_fastest_first() {
+        let translator = ScriptedTranslator::default();
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default();
+        let indices: Vec<usize> = translate_batch_unordered_stream(
+            &translator,
+            TEXTS.iter().map(|text| text.to_string()),
+            &target_lang,
+            None,
+            &options,
+            TEXTS.len(),
+        )
+        .map(|(index, _)| index)
+        .collect()
+        .await;
+        assert_eq!(indices, [3, 5, 1, 4, 2, 0]);
+
+        // 窗口为 1 时逐条翻译，两种顺序相同
+        let translator = ScriptedTranslator::default();
+        let indices: Vec<usize> =
+            translate_batch_unordered_stream(&translator, TEXTS, &target_lang, None, &options, 0)
+                .map(|(index, _)| index)
+                .collect()
+                .await;
+        assert_eq!(indices, [0, 1, 2, 3, 4, 5]);
+        assert_eq!(translator.max_in_flight.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stream_budget_exceeded() {
+        let translator = ScriptedTranslator::default();
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let options = TranslateOptions::default().max_batch_chars(8);
+        let results: Vec<_> =
+            translate_batch_ordered_stream(&translator, TEXTS, &target_lang, None, &options, 2)
+                .collect()
+                .await;
+        assert_eq!(results.len(), TEXTS.len());
+        assert!(results.iter().all(|(_, result)| matches!(
+            result,
+            Err(TranslationError::BudgetExceeded { max: 8, .. })
+        )));
+        assert_eq!(translator.completed.load(Ordering::SeqCst), 0);
+
+        // 截断时只有预算外的条目失败
+        let options = options.truncate_to_budget(true);
+        let results: Vec<_> =
+            translate_batch_ordered_stream(&translator, TEXTS, &target_lang, None, &options, 2)
+                .collect()
+                .await;
+        assert_eq!(results[0].1.as_deref().unwrap(), "A");
+        assert_eq!(results[1].1.as_deref().unwrap(), "B");
+        assert!(
+            results[2..]
+                .iter()
+                .all(|(_, result)| matches!(result, Err(TranslationError::BudgetExceeded { .. })))
+        );
+        assert_eq!(translator.completed.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,118 @@
+//! 多租户调用
+//!
+//! 一个进程为多个客户提供翻译时，各客户通常使用自己的 API Key，并有各自的用量配额和
+//! 速率限制。为每个客户创建一个翻译器开销很大，也无法共享连接池。通过
+//! [`crate::TranslateOptions::tenant`] 为单次调用附带 [`TenantContext`] 后，
+//! OpenAI 翻译器在这次调用中：
+//!
+//! * 使用租户的 API Key，不参与配置中多个 Key 的轮换，也不受这些 Key 的 RPM 限制；
+//! * 按租户的 [`SharedRateLimiter`] 限速，同一限速器可以在多次调用之间共享；
+//! * 成功后把原文的字符数计入租户的 [`crate::Quota`]；
+//! * 仍然受翻译器的并发限制约束。
+//!
+//! 租户信息不会出现在 `Debug` 输出和日志中。
+
+use crate::quota::QuotaTracker;
+use crate::ratelimit::RateLimiter;
+use crate::secret::SecretString;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 单次调用所属的租户
+#[derive(Clone)]
+pub struct TenantContext {
+    /// 租户标识
+    pub tenant_id: String,
+    /// 租户自己的 API Key，None 表示使用翻译器配置中的 Key
+    pub api_key_override: Option<SecretString>,
+    /// 计入用量的配额，None 表示不统计
+    pub quota_handle: Option<QuotaTracker>,
+    /// 租户的速率限制，None 表示不限制（使用配置中的 Key 时仍受其 RPM 限制）
+    pub rate_limiter: Option<SharedRateLimiter>,
+}
+
+impl TenantContext {
+    /// 创建只有租户标识的上下文
+    pub fn new(tenant_id: impl Into<String>) -> Self {
+        Self {
+            tenant_id: tenant_id.into(),
+            api_key_override: None,
+            quota_handle: None,
+            rate_limiter: None,
+        }
+    }
+
+    /// 设置租户的 API Key
+    pub fn api_key(mut self, api_key: impl Into<SecretString>) -> Self {
+        self.api_key_override = Some(api_key.into());
+        self
+    }
+
+    /// 设置计入用量的配额
+    pub fn quota(mut self, quota: QuotaTracker) -> Self {
+        self.quota_handle = Some(quota);
+        self
+    }
+
+    /// 设置租户的速率限制
+    pub fn rate_limiter(mut self, rate_limiter: SharedRateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+}
+
+/// 不输出租户标识和 Key，只显示设置了哪些限制
+impl std::fmt::Debug for TenantContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TenantContext")
+            .field("api_key_override", &self.api_key_override.is_some())
+            .field("quota_handle", &self.quota_handle.is_some())
+            .field("rate_limiter", &self.rate_limiter.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// 配额和限速器按实例比较
+impl PartialEq for TenantContext {
+    fn eq(&self, other: &Self) -> bool {
+        self.tenant_id == other.tenant_id
+            && self.api_key_override == other.api_key_override
+            && match (&self.quota_handle, &other.quota_handle) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+            && self.rate_limiter == other.rate_limiter
+    }
+}
+
+/// 可以在多次调用之间共享的速率限制器，克隆得到的是同一个限速器
+#[derive(Debug, Clone)]
+pub struct SharedRateLimiter(Arc<RateLimiter>);
+
+impl SharedRateLimiter {
+    /// 创建限速器
+    ///
+    /// # 参数
+    ///
+    /// * `rpm_limit` - 每分钟请求数，0 表示不限制
+    /// * `min_interval` - 相邻请求之间的最小间隔，None 表示不限制
+    pub fn new(rpm_limit: u32, min_interval: Option<Duration>) -> Self {
+        Self(Arc::new(RateLimiter::new(rpm_limit, None, min_interval)))
+    }
+
+    /// 等待到可以发送下一个请求
+    #[cfg(feature = "openai")]
+    pub(crate) async fn acquire(
+        &self,
+        clock: &dyn crate::clock::Clock,
+        deadline: Option<crate::options::Deadline>,
+    ) -> Result<(), crate::error::TranslationError> {
+        self.0.acquire(clock, deadline).await
+    }
+}
+
+impl PartialEq for SharedRateLimiter {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
@@ -0,0 +1,866 @@
+//! 测试与容量评估工具
+//!
+//! 需要启用 `test-util` feature。提供可编程的 [`MockTranslator`]、注入故障的
+//! [`ChaosTranslator`]、用于评估 `concurrent_limit` 等参数的 [`LoadGenerator`]、
+//! 可以分别延迟响应头和响应体的 [`DelayedServer`]，以及手动推进时间的 [`MockClock`]。
+//!
+//! 文档示例和 `examples/` 中的程序在设置了环境变量 `ASYNC_TRANSLATE_OFFLINE=1`
+//! 时（见 [`offline`]）改用返回确定性伪译文的 [`StaticTranslator`]，可以不访问
+//! 真实的翻译服务运行并检查输出。
+
+use crate::{
+    cache::CacheKeyInputs,
+    clock::{Clock, TokioClock},
+    error::TranslationError,
+    options::TranslateOptions,
+    translator::{TranslationDetail, Translator, TranslatorCapabilities},
+};
+use futures::stream::{self, StreamExt};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use unic_langid::LanguageIdentifier;
+
+/// 可编程的模拟翻译器
+///
+/// 返回 `"<前缀><原文>"` 形式的译文，可设置固定延迟，并统计调用次数
+#[derive(Debug, Clone)]
+pub struct MockTranslator {
+    prefix: String,
+    latency: Duration,
+    cache_key_inputs: Option<CacheKeyInputs>,
+    calls: Arc<AtomicUsize>,
+}
+
+impl Default for MockTranslator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockTranslator {
+    /// 创建默认的模拟翻译器，译文前缀为 `"Translated: "`，无延迟
+    pub fn new() -> Self {
+        Self {
+            prefix: "Translated: ".to_string(),
+            latency: Duration::ZERO,
+            cache_key_inputs: None,
+            calls: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// 设置译文前缀
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// 设置每次翻译的延迟
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// 设置 [`Translator::cache_key_inputs`] 返回的输入，默认不缓存
+    pub fn cache_key_inputs(mut self, inputs: CacheKeyInputs) -> Self {
+        self.cache_key_inputs = Some(inputs);
+        self
+    }
+
+    /// 获取调用次数（所有克隆共享同一个计数器）
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait::async_trait]
+impl Translator for MockTranslator {
+    async fn translate_with_options(
+        &self,
+        text: &str,
+        _target_lang: &LanguageIdentifier,
+        _source_lang: Option<&LanguageIdentifier>,
+        _options: &TranslateOptions,
+    ) -> Result<String, TranslationError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+        Ok(format!("{}{}", self.prefix, text))
+    }
+
+    fn cache_key_inputs(
+        &self,
+        _target_lang: &LanguageIdentifier,
+        _source_lang: Option<&LanguageIdentifier>,
+        _options: &TranslateOptions,
+    ) -> Option<CacheKeyInputs> {
+        self.cache_key_inputs.clone()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// 设置为 `1` 时示例改用 [`StaticTranslator`] 的环境变量
+pub const OFFLINE_ENV: &str = "ASYNC_TRANSLATE_OFFLINE";
+
+/// 是否设置了 `ASYNC_TRANSLATE_OFFLINE=1`
+pub fn offline() -> bool {
+    std::env::var(OFFLINE_ENV).is_ok_and(|value| value == "1")
+}
+
+/// [`StaticTranslator`] 生成伪译文的方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StaticStyle {
+    /// 在原文前加上目标语言标签并用「」括起，例如 `zh「Hello, world!」`
+    #[default]
+    Bracketed,
+    /// 按空白反转单词的顺序，例如 `world! Hello,`
+    ReversedWords,
+}
+
+/// 返回确定性伪译文的翻译器
+///
+/// 相同的原文和目标语言总是得到相同的译文，不访问网络，适合在文档示例和快照测试中
+/// 代替真实的翻译服务
+///
+/// ```
+/// use async_translate::Translator;
+/// use async_translate::testing::{StaticStyle, StaticTranslator};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), async_translate::TranslationError> {
+/// let zh = async_translate::lang("zh")?;
+/// let translated = StaticTranslator::new().translate("Hello, world!", &zh, None).await?;
+/// assert_eq!(translated, "zh「Hello, world!」");
+///
+/// let reversed = StaticTranslator::new().style(StaticStyle::ReversedWords);
+/// assert_eq!(reversed.translate("one two three", &zh, None).await?, "three two one");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StaticTranslator {
+    style: StaticStyle,
+}
+
+impl StaticTranslator {
+    /// 创建使用 [`StaticStyle::Bracketed`] 的翻译器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置生成伪译文的方式
+    pub fn style(mut self, style: StaticStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// 原文对应的伪译文
+    pub fn render(&self, text: &str, target_lang: &LanguageIdentifier) -> String {
+        match self.style {
+            StaticStyle::Bracketed => format!("{}「{}」", target_lang, text),
+            StaticStyle::ReversedWords => {
+                let mut words: Vec<&str> = text.split_whitespace().collect();
+                words.reverse();
+                words.join(" ")
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Translator for StaticTranslator {
+    async fn translate_with_options(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        _source_lang: Option<&LanguageIdentifier>,
+        _options: &TranslateOptions,
+    ) -> Result<String, TranslationError> {
+        Ok(self.render(text, target_lang))
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// 生成注入错误的函数
+type ErrorFactory = Arc<dyn Fn() -> TranslationError + Send + Sync>;
+
+/// [`ChaosTranslator`] 注入的延迟分布
+#[derive(Debug, Clone, PartialEq)]
+pub enum LatencyDistribution {
+    /// 不增加延迟
+    None,
+    /// 固定延迟
+    Fixed(Duration),
+    /// 在 `[min, max]` 内均匀分布的延迟
+    Uniform { min: Duration, max: Duration },
+    /// 通常为 `base`，以 `probability` 的概率变为 `spike`
+    Spike {
+        base: Duration,
+        spike: Duration,
+        probability: f64,
+    },
+}
+
+impl LatencyDistribution {
+    fn sample(&self, rng: &mut StdRng) -> Duration {
+        match self {
+            LatencyDistribution::None => Duration::ZERO,
+            LatencyDistribution::Fixed(latency) => *latency,
+            LatencyDistribution::Uniform { min, max } if min < max => rng.random_range(*min..=*max),
+            LatencyDistribution::Uniform { min, .. } => *min,
+            LatencyDistribution::Spike {
+                base,
+                spike,
+                probability,
+            } => {
+                if rng.random::<f64>() < *probability {
+                    *spike
+                } else {
+                    *base
+                }
+            }
+        }
+    }
+}
+
+/// 注入故障的翻译器包装
+///
+/// 按顺序检查脚本（第几次调用失败）和按概率注入的错误，未注入错误的调用转交给
+/// 内部翻译器。每次调用先按延迟分布等待。随机数可以设置种子以便复现，所有克隆
+/// 共享同一个内部翻译器、随机数生成器和计数器。
+#[derive(Clone)]
+pub struct ChaosTranslator {
+    inner: Arc<dyn Translator>,
+    scripts: Vec<(RangeInclusive<usize>, ErrorFactory)>,
+    faults: Vec<(f64, ErrorFactory)>,
+    latency: LatencyDistribution,
+    clock: Arc<dyn Clock>,
+    rng: Arc<Mutex<StdRng>>,
+    calls: Arc<AtomicUsize>,
+    injected: Arc<AtomicUsize>,
+    passed_through: Arc<AtomicUsize>,
+}
+
+impl std::fmt::Debug for ChaosTranslator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChaosTranslator")
+            .field("scripts", &self.scripts.len())
+            .field("faults", &self.faults.len())
+            .field("latency", &self.latency)
+            .field("calls", &self.calls())
+            .field("injected", &self.injected())
+            .field("passed_through", &self.passed_through())
+            .finish()
+    }
+}
+
+impl ChaosTranslator {
+    /// 包装内部翻译器，默认不注入任何故障
+    pub fn new(inner: impl Translator + 'static) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            scripts: Vec::new(),
+            faults: Vec::new(),
+            latency: LatencyDistribution::None,
+            clock: Arc::new(TokioClock),
+            rng: Arc::new(Mutex::new(StdRng::from_os_rng())),
+            calls: Arc::new(AtomicUsize::new(0)),
+            injected: Arc::new(AtomicUsize::new(0)),
+            passed_through: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// 第 `calls` 次调用（从 1 开始计数）返回 `error` 生成的错误，优先于按概率注入的错误
+    pub fn fail_calls(
+        mut self,
+        calls: RangeInclusive<usize>,
+        error: impl Fn() -> TranslationError + Send + Sync + 'static,
+    ) -> Self {
+        self.scripts.push((calls, Arc::new(error)));
+        self
+    }
+
+    /// 以 `probability` 的概率返回 `error` 生成的错误，多次设置时按设置顺序依次判定
+    pub fn fail_with(
+        mut self,
+        probability: f64,
+        error: impl Fn() -> TranslationError + Send + Sync + 'static,
+    ) -> Self {
+        self.faults
+            .push((probability.clamp(0.0, 1.0), Arc::new(error)));
+        self
+    }
+
+    /// 设置每次调用增加的延迟
+    pub fn latency(mut self, latency: LatencyDistribution) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// 设置等待延迟使用的时钟
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置随机数种子
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Arc::new(Mutex::new(StdRng::seed_from_u64(seed)));
+        self
+    }
+
+    /// 调用总次数
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    /// 被注入错误的调用次数
+    pub fn injected(&self) -> usize {
+        self.injected.load(Ordering::SeqCst)
+    }
+
+    /// 转交给内部翻译器的调用次数
+    pub fn passed_through(&self) -> usize {
+        self.passed_through.load(Ordering::SeqCst)
+    }
+
+    /// 等待注入的延迟，需要注入错误时返回该错误
+    async fn inject(&self) -> Result<(), TranslationError> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        let (delay, fault) = {
+            let mut rng = self.rng.lock().unwrap();
+            let delay = self.latency.sample(&mut rng);
+            let scripted = self
+                .scripts
+                .iter()
+                .find(|(calls, _)| calls.contains(&call))
+                .map(|(_, error)| error.clone());
+            let fault = scripted.or_else(|| {
+                self.faults
+                    .iter()
+                    .find(|(probability, _)| rng.random::<f64>() < *probability)
+                    .map(|(_, error)| error.clone())
+            });
+            (delay, fault)
+        };
+        if !delay.is_zero() {
+            self.clock.sleep(delay).await;
+        }
+        match fault {
+            Some(error) => {
+                self.injected.fetch_add(1, Ordering::SeqCst);
+                Err(error())
+            }
+            None => {
+                self.passed_through.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Translator for ChaosTranslator {
+    async fn translate_with_options(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<String, TranslationError> {
+        self.inject().await?;
+        self.inner
+            .translate_with_options(text, target_lang, source_lang, options)
+            .await
+    }
+
+    async fn translate_detailed_with_options(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<TranslationDetail, TranslationError> {
+        self.inject().await?;
+        self.inner
+            .translate_detailed_with_options(text, target_lang, source_lang, options)
+            .await
+    }
+
+    fn capabilities(&self) -> TranslatorCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// 负载生成器，以固定并发数向翻译器发送请求并统计吞吐量和延迟
+#[derive(Debug, Clone)]
+pub struct LoadGenerator {
+    total_requests: usize,
+    concurrency: usize,
+    text: String,
+    target_lang: LanguageIdentifier,
+    options: TranslateOptions,
+}
+
+/// 负载测试报告
+#[derive(Debug, Clone)]
+pub struct LoadReport {
+    /// 发送的请求总数
+    pub total: usize,
+    /// 成功的请求数
+    pub succeeded: usize,
+    /// 失败的请求数
+    pub failed: usize,
+    /// 总耗时
+    pub elapsed: Duration,
+    /// 延迟中位数
+    pub p50: Duration,
+    /// 95 分位延迟
+    pub p95: Duration,
+    /// 最大延迟
+    pub max: Duration,
+}
+
+impl LoadReport {
+    /// 每秒完成的请求数
+    pub fn requests_per_second(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs == 0.0 {
+            return self.total as f64;
+        }
+        self.total as f64 / secs
+    }
+}
+
+impl LoadGenerator {
+    /// 创建负载生成器，默认并发数为 10，目标语言为 `zh`
+    ///
+    /// # 参数
+    ///
+    /// * `total_requests` - 需要发送的请求总数
+    pub fn new(total_requests: usize) -> Self {
+        Self {
+            total_requests,
+            concurrency: 10,
+            text: "Hello, world!".to_string(),
+            target_lang: "zh".parse().expect("valid language identifier"),
+            options: TranslateOptions::default(),
+        }
+    }
+
+    /// 设置并发数（至少为 1）
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// 设置每个请求翻译的文本
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    /// 设置目标语言
+    pub fn target_lang(mut self, target_lang: LanguageIdentifier) -> Self {
+        self.target_lang = target_lang;
+        self
+    }
+
+    /// 设置翻译配置选项
+    pub fn options(mut self, options: TranslateOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// 执行负载测试
+    pub async fn run(&self, translator: &dyn Translator) -> LoadReport {
+        let started = Instant::now();
+        let outcomes: Vec<(bool, Duration)> = stream::iter(0..self.total_requests)
+            .map(|_| async {
+                let request_started = Instant::now();
+                let result = translator
+                    .translate_with_options(&self.text, &self.target_lang, None, &self.options)
+                    .await;
+                (result.is_ok(), request_started.elapsed())
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+        let elapsed = started.elapsed();
+
+        let succeeded = outcomes.iter().filter(|(ok, _)| *ok).count();
+        let mut latencies: Vec<Duration> = outcomes.iter().map(|(_, latency)| *latency).collect();
+        latencies.sort();
+        LoadReport {
+            total: outcomes.len(),
+            succeeded,
+            failed: outcomes.len() - succeeded,
+            elapsed,
+            p50: percentile(&latencies, 0.50),
+            p95: percentile(&latencies, 0.95),
+            max: latencies.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// 从已排序的延迟中取分位数
+fn percentile(sorted: &[Duration], quantile: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let index = ((sorted.len() - 1) as f64 * quantile).round() as usize;
+    sorted[index]
+}
+
+/// 手动推进时间的模拟时钟
+///
+/// 时间只在调用 [`MockClock::advance`] 时前进，`sleep` 会一直等待到时钟推进到
+/// 截止时间。启用 [`MockClock::auto_advance`] 后，`sleep` 会立即把时钟推进
+/// 相应的时长并返回，适合验证退避时长。日历时间从创建时的系统时间（或
+/// [`MockClock::wall_clock`] 指定的时间）起随时钟一起推进。所有克隆共享同一个时钟。
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    state: Arc<MockClockState>,
+}
+
+#[derive(Debug)]
+struct MockClockState {
+    start: std::time::Instant,
+    wall_start: Mutex<SystemTime>,
+    elapsed: Mutex<Duration>,
+    auto_advance: AtomicBool,
+    sleeps: Mutex<Vec<Duration>>,
+    sleepers: AtomicUsize,
+    notify: Notify,
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockClock {
+    /// 创建模拟时钟
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(MockClockState {
+                start: std::time::Instant::now(),
+                wall_start: Mutex::new(SystemTime::now()),
+                elapsed: Mutex::new(Duration::ZERO),
+                auto_advance: AtomicBool::new(false),
+                sleeps: Mutex::new(Vec::new()),
+                sleepers: AtomicUsize::new(0),
+                notify: Notify::new(),
+            }),
+        }
+    }
+
+    /// 设置 `sleep` 是否自动推进时钟
+    pub fn auto_advance(self, auto_advance: bool) -> Self {
+        self.state
+            .auto_advance
+            .store(auto_advance, Ordering::SeqCst);
+        self
+    }
+
+    /// 设置时钟创建时对应的日历时间
+    pub fn wall_clock(self, start: SystemTime) -> Self {
+        *self.state.wall_start.lock().unwrap() = start;
+        self
+    }
+
+    /// 推进时钟，唤醒截止时间已到的 `sleep`
+    pub fn advance(&self, duration: Duration) {
+        *self.state.elapsed.lock().unwrap() += duration;
+        self.state.notify.notify_waiters();
+    }
+
+    /// 自创建以来经过的时间
+    pub fn elapsed(&self) -> Duration {
+        *self.state.elapsed.lock().unwrap()
+    }
+
+    /// 按调用顺序返回所有 `sleep` 请求的时长
+    pub fn sleeps(&self) -> Vec<Duration> {
+        self.state.sleeps.lock().unwrap().clone()
+    }
+
+    /// 当前正在等待的 `sleep` 数量
+    pub fn sleepers(&self) -> usize {
+        self.state.sleepers.load(Ordering::SeqCst)
+    }
+
+    /// 等待直到至少有 `count` 个 `sleep` 正在等待
+    pub async fn wait_for_sleepers(&self, count: usize) {
+        while self.sleepers() < count {
+            tokio::task::yield_now().await;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Clock for MockClock {
+    fn now(&self) -> std::time::Instant {
+        self.state.start + self.elapsed()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        *self.state.wall_start.lock().unwrap() + self.elapsed()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        self.state.sleeps.lock().unwrap().push(duration);
+        if self.state.auto_advance.load(Ordering::SeqCst) {
+            self.advance(duration);
+            return;
+        }
+
+        let deadline = self.elapsed() + duration;
+        self.state.sleepers.fetch_add(1, Ordering::SeqCst);
+        loop {
+            // 先注册通知再检查时间，避免错过推进
+            let notified = self.state.notify.notified();
+            if self.elapsed() >= deadline {
+                break;
+            }
+            notified.await;
+        }
+        self.state.sleepers.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// [`DelayedServer`] 返回的响应
+#[derive(Debug, Clone)]
+pub struct DelayedResponse {
+    status: u16,
+    body: String,
+    header_delay: Duration,
+    body_delay: Duration,
+    endless: bool,
+    stall: bool,
+}
+
+impl DelayedResponse {
+    /// 创建状态码为 200、内容为 `body` 的响应
+    pub fn new(body: impl Into<String>) -> Self {
+        Self {
+            status: 200,
+            body: body.into(),
+            header_delay: Duration::ZERO,
+            body_delay: Duration::ZERO,
+            endless: false,
+            stall: false,
+        }
+    }
+
+    /// 设置 HTTP 状态码
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// 设置收到请求后、发送响应头之前的延迟
+    pub fn header_delay(mut self, delay: Duration) -> Self {
+        self.header_delay = delay;
+        self
+    }
+
+    /// 设置发送响应头之后、发送响应体之前的延迟
+    pub fn body_delay(mut self, delay: Duration) -> Self {
+        self.body_delay = delay;
+        self
+    }
+
+    /// 以分块传输编码无限重复发送响应体，直到客户端断开连接
+    pub fn endless(mut self, endless: bool) -> Self {
+        self.endless = endless;
+        self
+    }
+
+    /// 以分块传输编码发送一次响应体后不再结束响应，模拟生成中途停顿的服务端
+    pub fn stall(mut self, stall: bool) -> Self {
+        self.stall = stall;
+        self
+    }
+}
+
+/// 可以分别延迟响应头和响应体的 HTTP 服务器
+///
+/// 所有请求都返回同一个 [`DelayedResponse`]，用于模拟生成缓慢的服务端；
+/// [`DelayedServer::start_sequence`] 按请求到达的顺序依次返回不同的响应。
+/// 服务器在被丢弃时停止。
+#[derive(Debug)]
+pub struct DelayedServer {
+    uri: String,
+    requests: Arc<AtomicUsize>,
+    /// 按到达顺序记录的请求头
+    headers: Arc<Mutex<Vec<String>>>,
+    handle: JoinHandle<()>,
+}
+
+impl DelayedServer {
+    /// 在本地随机端口上启动服务器
+    pub async fn start(response: DelayedResponse) -> Self {
+        Self::start_sequence(vec![response]).await
+    }
+
+    /// 启动按顺序返回响应的服务器：第 n 个完整到达的请求得到第 n 个响应，
+    /// 之后的请求都得到最后一个响应
+    pub async fn start_sequence(responses: Vec<DelayedResponse>) -> Self {
+        assert!(!responses.is_empty(), "at least one response is required");
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind test server");
+        let uri = format!("http://{}", listener.local_addr().expect("local address"));
+        let requests = Arc::new(AtomicUsize::new(0));
+        let headers = Arc::new(Mutex::new(Vec::new()));
+        let responses: Arc<[DelayedResponse]> = responses.into();
+        let (counter, received) = (requests.clone(), headers.clone());
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let responses = responses.clone();
+                let (counter, received) = (counter.clone(), received.clone());
+                tokio::spawn(async move {
+                    let _ = serve_connection(stream, &responses, &counter, &received).await;
+                });
+            }
+        });
+        Self {
+            uri,
+            requests,
+            headers,
+            handle,
+        }
+    }
+
+    /// 服务器地址，例如 `http://127.0.0.1:12345`
+    pub fn uri(&self) -> String {
+        self.uri.clone()
+    }
+
+    /// 已收到的请求数
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    /// 按到达顺序列出每个请求中该请求头的值（名称不区分大小写），没有该请求头时为 None
+    pub fn request_headers(&self, name: &str) -> Vec<Option<String>> {
+        self.headers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|head| {
+                head.lines().find_map(|line| {
+                    let (header, value) = line.split_once(':')?;
+                    header
+                        .eq_ignore_ascii_case(name)
+                        .then(|| value.trim().to_string())
+                })
+            })
+            .collect()
+    }
+}
+
+impl Drop for DelayedServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// 读取一个完整的请求后按对应响应配置的延迟返回
+async fn serve_connection(
+    mut stream: TcpStream,
+    responses: &[DelayedResponse],
+    requests: &AtomicUsize,
+    headers: &Mutex<Vec<String>>,
+) -> std::io::Result<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buffer[..header_end]).into_owned();
+    let content_length = head
+        .lines()
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("content-length")
+                .then(|| value.trim().parse::<usize>().ok())
+                .flatten()
+        })
+        .unwrap_or(0);
+    while buffer.len() < header_end + content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    }
+    let response = {
+        let mut headers = headers.lock().unwrap();
+        headers.push(head);
+        let index = requests.fetch_add(1, Ordering::SeqCst);
+        &responses[index.min(responses.len() - 1)]
+    };
+
+    tokio::time::sleep(response.header_delay).await;
+    let length_header = if response.endless || response.stall {
+        "Transfer-Encoding: chunked".to_string()
+    } else {
+        format!("Content-Length: {}", response.body.len())
+    };
+    let head = format!(
+        "HTTP/1.1 {} Test\r\nContent-Type: application/json\r\n{}\r\nConnection: close\r\n\r\n",
+        response.status, length_header
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.flush().await?;
+
+    tokio::time::sleep(response.body_delay).await;
+    if response.endless {
+        let chunk = format!("{:x}\r\n{}\r\n", response.body.len(), response.body);
+        loop {
+            stream.write_all(chunk.as_bytes()).await?;
+        }
+    }
+    if response.stall {
+        let chunk = format!("{:x}\r\n{}\r\n", response.body.len(), response.body);
+        stream.write_all(chunk.as_bytes()).await?;
+        stream.flush().await?;
+        // 保持连接直到客户端断开
+        while stream.read(&mut [0u8; 1]).await? > 0 {}
+        return Ok(());
+    }
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::TranslationError;
+    use crate::options::TranslateOptions;
+    use crate::testing::{
+        ChaosTranslator, LatencyDistribution, LoadGenerator, MockClock, MockTranslator,
+        StaticStyle, StaticTranslator, percentile,
+    };
+    use crate::translator::Translator;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use unic_langid::LanguageIdentifier;
+
+    #[tokio::test]
+    async fn test_mock_translator_counts_calls() {
+        let translator = MockTranslator::new().prefix("T:");
+        let clone = translator.clone();
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+
+        let result = translator
+            .translate_with_options("Hello", &target_lang, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(result, "T:Hello");
+        assert_eq!(translator.calls(), 1);
+        // 克隆共享计数器
+        assert_eq!(clone.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_static_translator_is_deterministic() {
+        let mut manager = crate::manager::TranslationManager::new();
+        manager.add_translator("static", Box::new(StaticTranslator::new()));
+        let ja: LanguageIdentifier = "ja".parse().unwrap();
+        let zh_hant: LanguageIdentifier = "zh-Hant".parse().unwrap();
+
+        for _ in 0..2 {
+            assert_eq!(
+                manager
+                    .translate("static", "Hello", &ja, None)
+                    .await
+                    .unwrap(),
+                "ja「Hello」"
+            );
+        }
+        let texts = ["a b", "  spaced   out  "];
+        let translated = StaticTranslator::new()
+            .translate_batch_with_options(&texts, &zh_hant, None, &TranslateOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            translated,
+            ["zh-Hant「a b」", "zh-Hant「  spaced   out  」"]
+        );
+        let reversed = StaticTranslator::new().style(StaticStyle::ReversedWords);
+        assert_eq!(reversed.render("  spaced   out  ", &ja), "out spaced");
+        assert_eq!(reversed.render("", &ja), "");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_load_generator_report() {
+        let translator = MockTranslator::new().latency(Duration::from_millis(100));
+        let report = LoadGenerator::new(20).concurrency(5).run(&translator).await;
+
+        assert_eq!(report.total, 20);
+        assert_eq!(report.succeeded, 20);
+        assert_eq!(report.failed, 0);
+        assert_eq!(translator.calls(), 20);
+        // 20 个请求、5 并发、每个 100ms，共 4 轮
+        assert_eq!(report.elapsed, Duration::from_millis(400));
+        assert_eq!(report.p50, Duration::from_millis(100));
+        assert_eq!(report.max, Duration::from_millis(100));
+        assert_eq!(report.requests_per_second(), 50.0);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let latencies: Vec<Duration> = (1..=10).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&latencies, 0.0), Duration::from_millis(1));
+        assert_eq!(percentile(&latencies, 0.5), Duration::from_millis(6));
+        assert_eq!(percentile(&latencies, 0.95), Duration::from_millis(10));
+        assert_eq!(percentile(&[], 0.5), Duration::ZERO);
+    }
+
+    /// 依次调用 `count` 次，记录每次是否成功
+    async fn outcomes(translator: &ChaosTranslator, count: usize) -> Vec<bool> {
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let mut outcomes = Vec::new();
+        for _ in 0..count {
+            outcomes.push(translator.translate("hi", &target_lang, None).await.is_ok());
+        }
+        outcomes
+    }
+
+    #[tokio::test]
+    async fn test_chaos_script_fails_then_recovers() {
+        let inner = MockTranslator::new();
+        let chaos = ChaosTranslator::new(inner.clone()).fail_calls(3..=5, || {
+            TranslationError::ServiceError("injected".to_string())
+        });
+
+        assert_eq!(
+            outcomes(&chaos, 7).await,
+            [true, true, false, false, false, true, true]
+        );
+        assert_eq!(chaos.calls(), 7);
+        assert_eq!(chaos.injected(), 3);
+        assert_eq!(chaos.passed_through(), 4);
+        assert_eq!(inner.calls(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_chaos_probability_is_seedable() {
+        let chaos = |seed| {
+            ChaosTranslator::new(MockTranslator::new())
+                .fail_with(0.3, || TranslationError::TimeoutError)
+                .seed(seed)
+        };
+        let first = outcomes(&chaos(7), 1000).await;
+        assert_eq!(first, outcomes(&chaos(7), 1000).await);
+        assert_ne!(first, outcomes(&chaos(8), 1000).await);
+
+        let failures = first.iter().filter(|ok| !**ok).count();
+        assert!((250..350).contains(&failures), "{}", failures);
+    }
+
+    #[tokio::test]
+    async fn test_chaos_error_variants_in_order() {
+        let chaos = ChaosTranslator::new(MockTranslator::new())
+            .fail_with(0.5, || TranslationError::TimeoutError)
+            .fail_with(1.0, || TranslationError::ShuttingDown)
+            .seed(1);
+        let target_lang: LanguageIdentifier = "zh".parse().unwrap();
+        let (mut timeouts, mut shutdowns) = (0, 0);
+        for _ in 0..100 {
+            match chaos.translate("hi", &target_lang, None).await {
+                Err(TranslationError::TimeoutError) => timeouts += 1,
+                Err(TranslationError::ShuttingDown) => shutdowns += 1,
+                other => panic!("Expected injected error, got {:?}", other),
+            }
+        }
+        assert!(timeouts > 0 && shutdowns > 0);
+        assert_eq!(chaos.injected(), 100);
+        assert_eq!(chaos.passed_through(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_chaos_latency_distributions() {
+        let clock = MockClock::new().auto_advance(true);
+        let min = Duration::from_millis(10);
+        let max = Duration::from_millis(50);
+        let chaos = ChaosTranslator::new(MockTranslator::new())
+            .latency(LatencyDistribution::Uniform { min, max })
+            .clock(Arc::new(clock.clone()))
+            .seed(3);
+        outcomes(&chaos, 50).await;
+        let sleeps = clock.sleeps();
+        assert_eq!(sleeps.len(), 50);
+        assert!(sleeps.iter().all(|sleep| (min..=max).contains(sleep)));
+
+        let clock = MockClock::new().auto_advance(true);
+        let chaos = ChaosTranslator::new(MockTranslator::new())
+            .latency(LatencyDistribution::Spike {
+                base: Duration::from_millis(1),
+                spike: Duration::from_secs(2),
+                probability: 0.1,
+            })
+            .clock(Arc::new(clock.clone()))
+            .seed(3);
+        outcomes(&chaos, 200).await;
+        let spikes = clock
+            .sleeps()
+            .iter()
+            .filter(|sleep| **sleep == Duration::from_secs(2))
+            .count();
+        assert!((5..40).contains(&spikes), "{}", spikes);
+
+        // 延迟同样作用于注入错误的调用
+        let clock = MockClock::new().auto_advance(true);
+        let chaos = ChaosTranslator::new(MockTranslator::new())
+            .latency(LatencyDistribution::Fixed(Duration::from_millis(5)))
+            .fail_calls(1..=1, || TranslationError::TimeoutError)
+            .clock(Arc::new(clock.clone()));
+        assert_eq!(outcomes(&chaos, 2).await, [false, true]);
+        assert_eq!(clock.elapsed(), Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,141 @@
+//! 请求ID与链路追踪上下文传播
+//!
+//! 两个翻译器发出的每个 HTTP 请求都会携带 `X-Request-Id`、`traceparent`
+//! 和 `tracestate` 请求头（如果已配置），便于在链路追踪系统中关联服务端延迟。
+//! 请求ID和 traceparent 同时写入详细结果的附加信息，以及服务端返回错误时的
+//! [`TranslationError::HttpError`] 和 [`TranslationError::Provider`]。
+
+use crate::error::TranslationError;
+use crate::options::{EffectiveOptions, TraceContext};
+use std::fmt;
+
+/// 请求ID请求头名称
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+/// W3C traceparent 请求头名称
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+/// W3C tracestate 请求头名称
+pub const TRACESTATE_HEADER: &str = "tracestate";
+
+/// 确定本次请求使用的链路追踪上下文
+///
+/// 显式设置的上下文优先；否则在启用 `tracing` feature 时根据当前 span 生成
+pub(crate) fn resolve_trace_context(options: &EffectiveOptions) -> Option<TraceContext> {
+    if let Some(context) = &options.trace_context {
+        return Some(context.clone());
+    }
+    current_span_context()
+}
+
+/// 请求ID和链路追踪请求头，按发送顺序排列
+pub(crate) fn headers(options: &EffectiveOptions) -> Vec<(String, String)> {
+    let mut headers = Vec::new();
+    if let Some(request_id) = &options.request_id {
+        headers.push((REQUEST_ID_HEADER.to_string(), request_id.clone()));
+    }
+    if let Some(context) = resolve_trace_context(options) {
+        headers.push((TRACEPARENT_HEADER.to_string(), context.traceparent));
+        if let Some(tracestate) = context.tracestate {
+            headers.push((TRACESTATE_HEADER.to_string(), tracestate));
+        }
+    }
+    headers
+}
+
+/// 出错的请求携带的请求ID和 traceparent，用于在日志中关联失败的请求
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestTrace {
+    /// `X-Request-Id` 请求头的值
+    pub request_id: Option<String>,
+    /// `traceparent` 请求头的值
+    pub traceparent: Option<String>,
+}
+
+impl RequestTrace {
+    /// 本次请求的请求ID和 traceparent，都没有时为 None
+    pub(crate) fn of(options: &EffectiveOptions) -> Option<Self> {
+        let trace = Self {
+            request_id: options.request_id.clone(),
+            traceparent: resolve_trace_context(options).map(|context| context.traceparent),
+        };
+        (trace != Self::default()).then_some(trace)
+    }
+}
+
+impl fmt::Display for RequestTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = "";
+        if let Some(request_id) = &self.request_id {
+            write!(f, "request_id={}", request_id)?;
+            separator = ", ";
+        }
+        if let Some(traceparent) = &self.traceparent {
+            write!(f, "{}traceparent={}", separator, traceparent)?;
+        }
+        Ok(())
+    }
+}
+
+/// 将请求ID和 traceparent 写入详细结果的附加信息，便于日志关联
+pub(crate) fn annotate_metadata(metadata: &mut serde_json::Value, options: &EffectiveOptions) {
+    let Some(map) = metadata.as_object_mut() else {
+        return;
+    };
+    let Some(trace) = RequestTrace::of(options) else {
+        return;
+    };
+    if let Some(request_id) = trace.request_id {
+        map.insert("request_id".to_string(), request_id.into());
+    }
+    if let Some(traceparent) = trace.traceparent {
+        map.insert("traceparent".to_string(), traceparent.into());
+    }
+}
+
+/// 将请求ID和 traceparent 写入服务端返回的错误，其他错误原样返回
+pub(crate) fn annotate_error(
+    mut error: TranslationError,
+    options: &EffectiveOptions,
+) -> TranslationError {
+    if let TranslationError::HttpError { trace, .. } | TranslationError::Provider { trace, .. } =
+        &mut error
+    {
+        *trace = RequestTrace::of(options).map(Box::new);
+    }
+    error
+}
+
+/// 根据当前 tracing span 生成 traceparent
+///
+/// tracing 本身没有 trace-id 的概念，这里使用进程级随机前缀加上 span ID 作为
+/// trace-id，span ID 作为 parent-id，足以在日志中关联同一 span 发出的请求。
+#[cfg(feature = "tracing")]
+fn current_span_context() -> Option<TraceContext> {
+    use std::sync::OnceLock;
+
+    static TRACE_ID_PREFIX: OnceLock<u64> = OnceLock::new();
+
+    let span = tracing::Span::current();
+    let span_id = span.id()?.into_u64();
+    let prefix = *TRACE_ID_PREFIX.get_or_init(|| rand::random::<u64>() | 1);
+    Some(TraceContext {
+        traceparent: format_traceparent(prefix, span_id),
+        tracestate: None,
+    })
+}
+
+#[cfg(not(feature = "tracing"))]
+fn current_span_context() -> Option<TraceContext> {
+    None
+}
+
+/// 按 W3C 格式拼接 traceparent：`00-<trace-id>-<parent-id>-01`
+#[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+fn format_traceparent(trace_id_prefix: u64, span_id: u64) -> String {
+    format!(
+        "00-{:016x}{:016x}-{:016x}-01",
+        trace_id_prefix, span_id, span_id
+    )
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use crate::options::{EffectiveOptions, TraceContext, TranslateOptions};
+    use crate::trace::{format_traceparent, resolve_trace_context};
+
+    #[test]
+    fn test_format_traceparent() {
+        let traceparent = format_traceparent(0xabc, 0x1f);
+        assert_eq!(
+            traceparent,
+            "00-0000000000000abc000000000000001f-000000000000001f-01"
+        );
+    }
+
+    #[test]
+    fn test_explicit_trace_context_wins() {
+        let context = TraceContext {
+            traceparent: "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string(),
+            tracestate: Some("vendor=value".to_string()),
+        };
+        let options = TranslateOptions::default().trace_context(context.clone());
+        assert_eq!(
+            resolve_trace_context(&EffectiveOptions::from_call_site(&options)),
+            Some(context)
+        );
+    }
+
+    #[test]
+    fn test_no_span_no_context() {
+        // 没有活动 span 时不生成 traceparent
+        let options = TranslateOptions::default();
+        assert_eq!(
+            resolve_trace_context(&EffectiveOptions::from_call_site(&options)),
+            None
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_traceparent_generated_from_current_span() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let subscriber = tracing_subscriber::registry().with(tracing_subscriber::fmt::layer());
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("translate");
+            let span_id = span.id().unwrap().into_u64();
+            let _guard = span.enter();
+
+            let context = resolve_trace_context(&EffectiveOptions::from_call_site(
+                &TranslateOptions::default(),
+            ))
+            .unwrap();
+            let parts: Vec<&str> = context.traceparent.split('-').collect();
+            assert_eq!(parts.len(), 4);
+            assert_eq!(parts[0], "00");
+            assert_eq!(parts[1].len(), 32);
+            assert_eq!(parts[2], format!("{:016x}", span_id));
+            assert_eq!(parts[3], "01");
+            assert_eq!(context.tracestate, None);
+
+            // 显式设置的上下文不会被覆盖
+            let explicit = TraceContext {
+                traceparent: "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string(),
+                tracestate: None,
+            };
+            let options = TranslateOptions::default().trace_context(explicit.clone());
+            assert_eq!(
+                resolve_trace_context(&EffectiveOptions::from_call_site(&options)),
+                Some(explicit)
+            );
+        });
+    }
+}
@@ -1,8 +1,124 @@
 //! 翻译器trait定义
 
-use crate::{error::TranslationError, options::TranslateOptions};
+use crate::{
+    cache::CacheKeyInputs, error::TranslationError, glossary::Glossary, options::TranslateOptions,
+    stats,
+};
+use std::any::Any;
+use std::time::SystemTime;
 use unic_langid::LanguageIdentifier;
 
+/// 带详细信息的翻译结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TranslationDetail {
+    /// 翻译后的文本
+    pub text: String,
+    /// 自动检测到的源语言及置信度（0.0 ~ 1.0）
+    pub detected_source: Option<(LanguageIdentifier, f64)>,
+    /// 翻译服务相关的附加信息
+    pub provider_metadata: serde_json::Value,
+    /// 是否为不完整的译文（流式响应中断后保留的部分结果）
+    pub partial: bool,
+    /// 是否为未通过长度比例检查、在重试耗尽后保留的译文
+    pub suspicious: bool,
+    /// 翻译服务返回的原始 JSON，仅在启用 [`crate::TranslateOptions::include_raw_response`] 时提供
+    pub raw: Option<serde_json::Value>,
+    /// 是否为翻译失败后按 [`crate::TranslateOptions::on_failure`] 原样返回的原文，
+    /// 这类结果不应被缓存
+    pub passthrough: bool,
+    /// 生成译文的翻译服务、模型和请求等来源信息，原样返回的原文没有来源信息
+    pub provenance: Option<Provenance>,
+    /// 译文是否与原文相同，由 [`crate::TranslationManager`] 按
+    /// [`crate::TranslateOptions::unchanged_comparison`] 判断
+    pub result_kind: TranslationKind,
+}
+
+/// 译文与原文的关系
+///
+/// 原文已经是目标语言，或者翻译服务无法翻译而原样返回时，译文与原文相同
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TranslationKind {
+    /// 译文与原文不同
+    #[default]
+    Translated,
+    /// 译文与原文相同
+    Unchanged,
+}
+
+impl TranslationDetail {
+    /// 仅包含翻译文本的结果
+    pub fn from_text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    /// 翻译失败后原样返回的原文，附加信息中记录失败原因
+    pub fn pass_through(text: impl Into<String>, error: &TranslationError) -> Self {
+        Self {
+            text: text.into(),
+            provider_metadata: serde_json::json!({ "error": error.to_string() }),
+            passthrough: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// 译文的来源信息，用于审计记录每条译文由哪个服务、模型和设置生成
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    /// 翻译服务名称，例如 `"openai"`
+    pub provider: String,
+    /// 生成译文的模型：OpenAI 为响应中的模型名称，微软翻译为自定义模型的类别
+    pub model: Option<String>,
+    /// 实际发送的系统提示词的哈希，与缓存键使用相同的规范化方式
+    pub prompt_fingerprint: Option<String>,
+    /// 翻译服务为这次请求分配的标识：OpenAI 为响应的 `id`，微软翻译为响应头 `X-RequestId`
+    pub request_id: Option<String>,
+    /// OpenAI 响应中的 `system_fingerprint`，标识生成译文的后端配置
+    pub system_fingerprint: Option<String>,
+    /// 收到响应的时间
+    pub timestamp: SystemTime,
+    /// 影响译文的设置的哈希，不含原文、语言和提示词，
+    /// 见 [`CacheKeyInputs::fingerprint`]
+    pub options_fingerprint: String,
+}
+
+impl Provenance {
+    /// 转换为便于存储的 JSON，时间为 Unix 毫秒时间戳 `timestamp_ms`
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "provider": self.provider,
+            "model": self.model,
+            "prompt_fingerprint": self.prompt_fingerprint,
+            "request_id": self.request_id,
+            "system_fingerprint": self.system_fingerprint,
+            "timestamp_ms": stats::unix_ms(self.timestamp),
+            "options_fingerprint": self.options_fingerprint,
+        })
+    }
+}
+
+/// 文本长度的计量单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LengthUnit {
+    /// 按字符数计量
+    #[default]
+    Chars,
+    /// 按（估算的）token 数计量
+    Tokens,
+}
+
+/// 翻译器能力描述
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranslatorCapabilities {
+    /// 单次请求允许的最大文本长度，None 表示不限制
+    pub max_text_len: Option<usize>,
+    /// `max_text_len` 的计量单位
+    pub length_unit: LengthUnit,
+}
+
 /// 翻译器trait，定义了统一的翻译接口
 #[async_trait::async_trait]
 pub trait Translator: Send + Sync {
@@ -46,4 +162,128 @@ pub trait Translator: Send + Sync {
         self.translate_with_options(text, target_lang, source_lang, &TranslateOptions::default())
             .await
     }
+
+    /// 批量翻译文本（带配置选项）
+    ///
+    /// 默认实现对每条文本调用 [`Translator::translate_with_options`]，任意一条失败即返回错误，
+    /// 其中的 panic 转换为 [`TranslationError::Other`]；有原生批量接口的翻译器会覆盖此方法
+    ///
+    /// # 参数
+    ///
+    /// * `texts` - 需要翻译的文本
+    /// * `target_lang` - 目标语言标识符
+    /// * `source_lang` - 源语言标识符 (None表示自动检测)
+    /// * `options` - 翻译配置选项
+    ///
+    /// # 返回值
+    ///
+    /// 返回翻译后的文本，顺序与输入一致
+    async fn translate_batch_with_options(
+        &self,
+        texts: &[&str],
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<Vec<String>, TranslationError> {
+        crate::batch::CharBudget::new(options.get_max_batch_chars(), false).check(texts)?;
+        futures::future::try_join_all(texts.iter().map(|text| {
+            crate::batch::catch_panic(self.translate_with_options(
+                text,
+                target_lang,
+                source_lang,
+                options,
+            ))
+        }))
+        .await
+    }
+
+    /// 翻译文本并返回详细结果（带配置选项）
+    ///
+    /// 默认实现调用 [`Translator::translate_with_options`]，只填充翻译文本；
+    /// 支持语言检测的翻译器会覆盖此方法以提供检测结果
+    ///
+    /// # 参数
+    ///
+    /// * `text` - 需要翻译的文本
+    /// * `target_lang` - 目标语言标识符
+    /// * `source_lang` - 源语言标识符 (None表示自动检测)
+    /// * `options` - 翻译配置选项
+    ///
+    /// # 返回值
+    ///
+    /// 返回带详细信息的翻译结果，如果出错则返回错误信息
+    async fn translate_detailed_with_options(
+        &self,
+        text: &str,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<TranslationDetail, TranslationError> {
+        self.translate_with_options(text, target_lang, source_lang, options)
+            .await
+            .map(TranslationDetail::from_text)
+    }
+
+    /// 按术语表翻译文本
+    ///
+    /// 默认实现先把原文中的术语替换为指定译法再翻译；
+    /// 支持术语约束的翻译器会覆盖此方法
+    ///
+    /// # 参数
+    ///
+    /// * `text` - 需要翻译的文本
+    /// * `glossary` - 术语表
+    /// * `target_lang` - 目标语言标识符
+    /// * `source_lang` - 源语言标识符 (None表示自动检测)
+    /// * `options` - 翻译配置选项
+    ///
+    /// # 返回值
+    ///
+    /// 返回翻译后的文本，如果出错则返回错误信息
+    async fn translate_with_glossary(
+        &self,
+        text: &str,
+        glossary: &Glossary,
+        target_lang: &LanguageIdentifier,
+        source_lang: Option<&LanguageIdentifier>,
+        options: &TranslateOptions,
+    ) -> Result<String, TranslationError> {
+        let text = glossary.apply(text);
+        self.translate_with_options(&text, target_lang, source_lang, options)
+            .await
+    }
+
+    /// 计算缓存键时使用的、影响译文的输入，见 [`crate::cache::CacheKey`]
+    ///
+    /// 默认实现返回 None，表示翻译器的译文不应被缓存；实现时应包含模型、系统提示词
+    /// 以及所有会改变译文的配置和选项，使修改它们后旧的缓存不再命中
+    ///
+    /// # 参数
+    ///
+    /// * `target_lang` - 目标语言标识符
+    /// * `source_lang` - 源语言标识符 (None表示自动检测)
+    /// * `options` - 翻译配置选项
+    fn cache_key_inputs(
+        &self,
+        _target_lang: &LanguageIdentifier,
+        _source_lang: Option<&LanguageIdentifier>,
+        _options: &TranslateOptions,
+    ) -> Option<CacheKeyInputs> {
+        None
+    }
+
+    /// 获取翻译器能力描述
+    ///
+    /// 默认实现表示没有任何已知限制
+    fn capabilities(&self) -> TranslatorCapabilities {
+        TranslatorCapabilities::default()
+    }
+
+    /// 取得具体类型的引用，供 [`crate::TranslationManager::get_translator_as`] 向下转型，
+    /// 以便调用翻译器特有的方法
+    ///
+    /// 默认实现返回 `&()`，表示不支持向下转型；需要支持时在实现中返回 `self`
+    fn as_any(&self) -> &dyn Any {
+        &()
+    }
 }
@@ -0,0 +1,454 @@
+//! 微软翻译器的集成测试
+//!
+//! 使用录制的服务端响应（`tests/fixtures/microsoft`）搭建模拟服务器，
+//! 检查翻译器发出的查询参数、请求头和请求体，以及对响应和错误的解析。
+
+use async_translate::microsoft::DEFAULT_AUTH_ENDPOINT;
+use async_translate::{
+    MicrosoftConfig, MicrosoftTranslator, TraceContext, TranslateOptions, TranslationError,
+    Translator,
+};
+use serde_json::json;
+use unic_langid::LanguageIdentifier;
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const AUTH_TOKEN: &str = include_str!("fixtures/microsoft/auth_token.txt");
+const TRANSLATE_DETECTED: &str = include_str!("fixtures/microsoft/translate_detected.json");
+const TRANSLATE_WITH_SOURCE: &str = include_str!("fixtures/microsoft/translate_with_source.json");
+const ERROR_INVALID_TARGET: &str = include_str!("fixtures/microsoft/error_invalid_target.json");
+const ERROR_UNAUTHORIZED: &str = include_str!("fixtures/microsoft/error_unauthorized.json");
+
+/// 实际遇到过的非常规响应（`tests/fixtures/microsoft/quirks`），以及期望的译文或
+/// [`TranslationError::ServiceError`] 信息
+const QUIRKS: &[(&str, &str, Result<&str, &str>)] = &[
+    (
+        "score_integer",
+        include_str!("fixtures/microsoft/quirks/score_integer.json"),
+        Ok("你好，世界！"),
+    ),
+    (
+        "detected_null",
+        include_str!("fixtures/microsoft/quirks/detected_null.json"),
+        Ok("你好，世界！"),
+    ),
+    (
+        "unknown_fields",
+        include_str!("fixtures/microsoft/quirks/unknown_fields.json"),
+        Ok("你好，世界！"),
+    ),
+    (
+        "to_missing",
+        include_str!("fixtures/microsoft/quirks/to_missing.json"),
+        Ok("你好，世界！"),
+    ),
+    (
+        "translations_empty",
+        include_str!("fixtures/microsoft/quirks/translations_empty.json"),
+        Err("No translation results returned"),
+    ),
+    (
+        "translations_null",
+        include_str!("fixtures/microsoft/quirks/translations_null.json"),
+        Err("No translation results returned"),
+    ),
+    (
+        "translations_missing",
+        include_str!("fixtures/microsoft/quirks/translations_missing.json"),
+        Err("No translation results returned"),
+    ),
+];
+
+fn lang(tag: &str) -> LanguageIdentifier {
+    tag.parse().unwrap()
+}
+
+fn json_response(status: u16, fixture: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status).set_body_raw(fixture, "application/json")
+}
+
+/// 按发送顺序排列的查询参数
+fn query_pairs(request: &Request) -> Vec<(String, String)> {
+    request.url.query_pairs().into_owned().collect()
+}
+
+fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+    expected
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// 挂载返回录制 token 的认证接口
+async fn mount_auth(server: &MockServer, expected_calls: u64) {
+    Mock::given(method("GET"))
+        .and(path("/translate/auth"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(AUTH_TOKEN))
+        .expect(expected_calls)
+        .mount(server)
+        .await;
+}
+
+/// 使用自动认证、端点和认证地址都指向模拟服务器的翻译器
+fn auto_auth_translator(server: &MockServer) -> MicrosoftTranslator {
+    MicrosoftTranslator::new(
+        MicrosoftConfig::builder()
+            .endpoint(server.uri())
+            .auth_endpoint(format!("{}/translate/auth", server.uri()))
+            .build(),
+    )
+}
+
+#[test]
+fn test_default_auth_endpoint() {
+    assert_eq!(
+        DEFAULT_AUTH_ENDPOINT,
+        "https://edge.microsoft.com/translate/auth"
+    );
+    assert_eq!(MicrosoftConfig::default().auth_endpoint, None);
+    let config = MicrosoftConfig::builder()
+        .auth_endpoint("not a url")
+        .build();
+    assert!(matches!(
+        config.validate(),
+        Err(TranslationError::ConfigurationError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_auto_auth_batch_request_and_response() {
+    let server = MockServer::start().await;
+    mount_auth(&server, 1).await;
+    Mock::given(method("POST"))
+        .and(path("/translate"))
+        .and(header(
+            "Authorization",
+            format!("Bearer {}", AUTH_TOKEN.trim()),
+        ))
+        .and(header("Content-Type", "application/json"))
+        .and(body_json(
+            json!([{"text": "Hello, world!"}, {"text": "Good morning"}]),
+        ))
+        .respond_with(json_response(200, TRANSLATE_DETECTED).insert_header("X-MT-System", "Team"))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let translator = auto_auth_translator(&server);
+    let texts = ["Hello, world!", "Good morning"];
+    let options = TranslateOptions::default();
+    let results = translator
+        .translate_batch(&texts, &lang("zh-Hans"), None, &options)
+        .await
+        .unwrap();
+    // 第二次请求复用缓存的 token
+    translator
+        .translate_batch(&texts, &lang("zh-Hans"), None, &options)
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 2);
+    let detected = results[0].detected_language.as_ref().unwrap();
+    assert_eq!((detected.language.as_str(), detected.score), ("en", 1.0));
+    assert_eq!(results[0].translations[0].text, "你好，世界！");
+    assert_eq!(results[0].translations[0].to, "zh-Hans");
+    assert_eq!(results[1].detected_language.as_ref().unwrap().score, 0.97);
+    assert_eq!(results[1].translations[0].text, "早上好");
+    assert_eq!(results[0].system.as_deref(), Some("Team"));
+    assert!(results[0].raw.is_none());
+
+    let requests = server.received_requests().await.unwrap();
+    let translate_request = requests
+        .iter()
+        .find(|request| request.url.path() == "/translate")
+        .unwrap();
+    assert_eq!(
+        query_pairs(translate_request),
+        pairs(&[
+            ("api-version", "3.0"),
+            ("to", "zh-Hans"),
+            ("includeSentenceLength", "true"),
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_api_key_request_with_source_and_options() {
+    let server = MockServer::start().await;
+    mount_auth(&server, 0).await;
+    Mock::given(method("POST"))
+        .and(path("/translate"))
+        .and(header(
+            "Authorization",
+            "Ocp-Apim-Subscription-Key test-key",
+        ))
+        .and(header("X-Request-Id", "req-42"))
+        .and(header(
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        ))
+        .and(header("tracestate", "vendor=value"))
+        .and(body_json(json!([{"text": "<b>Hello</b>"}])))
+        .respond_with(
+            json_response(200, TRANSLATE_WITH_SOURCE).insert_header("X-MT-System", "Custom"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let translator = MicrosoftTranslator::new(
+        MicrosoftConfig::builder()
+            .endpoint(server.uri())
+            .auth_endpoint(format!("{}/translate/auth", server.uri()))
+            .api_key(Some("test-key"))
+            .category("my-category")
+            .allow_fallback(true)
+            .suggested_from(lang("fr"))
+            .build(),
+    );
+    let options = TranslateOptions::default()
+        .html(true)
+        .request_id("req-42")
+        .trace_context(TraceContext {
+            traceparent: "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string(),
+            tracestate: Some("vendor=value".to_string()),
+        })
+        .include_raw_response(true);
+    let detail = translator
+        .translate_detailed_with_options(
+            "<b>Hello</b>",
+            &lang("zh-Hans"),
+            Some(&lang("en")),
+            &options,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(detail.text, "<b>你好</b>");
+    assert_eq!(detail.detected_source, None);
+    assert_eq!(detail.provider_metadata["provider"], "microsoft");
+    assert_eq!(detail.provider_metadata["to"], "zh-Hans");
+    assert_eq!(detail.provider_metadata["category"], "my-category");
+    assert_eq!(detail.provider_metadata["system"], "Custom");
+    assert_eq!(detail.provider_metadata["request_id"], "req-42");
+    let fixture: serde_json::Value = serde_json::from_str(TRANSLATE_WITH_SOURCE).unwrap();
+    assert_eq!(detail.raw, Some(fixture));
+
+    // 指定源语言时不发送 suggestedFrom
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(
+        query_pairs(&requests[0]),
+        pairs(&[
+            ("api-version", "3.0"),
+            ("to", "zh-Hans"),
+            ("includeSentenceLength", "true"),
+            ("from", "en"),
+            ("textType", "html"),
+            ("category", "my-category"),
+            ("allowFallback", "true"),
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_suggested_from_and_detected_source() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/translate"))
+        .respond_with(json_response(200, TRANSLATE_DETECTED))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let translator = MicrosoftTranslator::new(
+        MicrosoftConfig::builder()
+            .endpoint(server.uri())
+            .api_key(Some("test-key"))
+            .suggested_from(lang("en"))
+            .build(),
+    );
+    let detail = translator
+        .translate_detailed_with_options(
+            "Hello, world!",
+            &lang("zh-Hans"),
+            None,
+            &TranslateOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(detail.text, "你好，世界！");
+    assert_eq!(detail.detected_source, Some((lang("en"), 1.0)));
+    assert_eq!(detail.provider_metadata["detected_language"], "en");
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(
+        query_pairs(&requests[0]),
+        pairs(&[
+            ("api-version", "3.0"),
+            ("to", "zh-Hans"),
+            ("includeSentenceLength", "true"),
+            ("suggestedFrom", "en"),
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_provider_error_is_parsed_and_not_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/translate"))
+        .respond_with(json_response(400, ERROR_INVALID_TARGET))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let translator = MicrosoftTranslator::new(
+        MicrosoftConfig::builder()
+            .endpoint(server.uri())
+            .api_key(Some("test-key"))
+            .build(),
+    );
+    match translator.translate("Hello", &lang("xx"), None).await {
+        Err(TranslationError::Provider {
+            provider,
+            code,
+            message,
+            http_status,
+            retryable,
+            trace,
+        }) => {
+            assert_eq!(provider, "microsoft");
+            assert_eq!(code, "400036");
+            assert_eq!(message, "The target language is not valid.");
+            assert_eq!(http_status, Some(reqwest::StatusCode::BAD_REQUEST));
+            assert!(!retryable);
+            assert_eq!(trace, None);
+        }
+        other => panic!("Expected Provider error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_quirky_responses() {
+    for (name, fixture, expected) in QUIRKS {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(json_response(200, fixture))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = MicrosoftTranslator::new(
+            MicrosoftConfig::builder()
+                .endpoint(server.uri())
+                .api_key(Some("test-key"))
+                .build(),
+        );
+        let result = translator
+            .translate("Hello, world!", &lang("zh-Hans"), None)
+            .await;
+        match (result, expected) {
+            (Ok(text), Ok(expected)) => assert_eq!(text, *expected, "{}", name),
+            (Err(TranslationError::ServiceError(msg)), Err(expected)) => {
+                assert_eq!(msg, *expected, "{}", name)
+            }
+            (other, _) => panic!("{}: unexpected result {:?}", name, other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_quirky_batch_does_not_misalign() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/translate"))
+        .respond_with(json_response(
+            200,
+            r#"[{"translations": []}, {"translations": [{"text": "早上好", "to": "zh-Hans"}]}]"#,
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let translator = MicrosoftTranslator::new(
+        MicrosoftConfig::builder()
+            .endpoint(server.uri())
+            .api_key(Some("test-key"))
+            .build(),
+    );
+    let result = translator
+        .translate_batch_to_strings(
+            ["Hello", "Good morning"],
+            &lang("zh-Hans"),
+            None,
+            &TranslateOptions::default(),
+        )
+        .await;
+    assert!(
+        matches!(result, Err(TranslationError::ServiceError(_))),
+        "{:?}",
+        result
+    );
+}
+
+#[tokio::test]
+async fn test_unauthorized_clears_cached_token() {
+    let server = MockServer::start().await;
+    mount_auth(&server, 2).await;
+    Mock::given(method("POST"))
+        .and(path("/translate"))
+        .respond_with(json_response(401, ERROR_UNAUTHORIZED))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let translator = auto_auth_translator(&server);
+    let options = TranslateOptions::default().no_retries();
+    for _ in 0..2 {
+        match translator
+            .translate_with_options("Hello", &lang("zh-Hans"), None, &options)
+            .await
+        {
+            Err(TranslationError::Provider { code, .. }) => assert_eq!(code, "401000"),
+            other => panic!("Expected Provider error, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn test_auth_endpoint_failure() {
+    use async_translate::testing::MockClock;
+    use std::sync::Arc;
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/translate/auth"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(3)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/translate"))
+        .respond_with(json_response(200, TRANSLATE_DETECTED))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let translator = MicrosoftTranslator::new(
+        MicrosoftConfig::builder()
+            .endpoint(server.uri())
+            .auth_endpoint(format!("{}/translate/auth", server.uri()))
+            .clock(Arc::new(MockClock::new().auto_advance(true)))
+            .build(),
+    );
+    let options = TranslateOptions::default().no_retries();
+    match translator
+        .translate_with_options("Hello", &lang("zh-Hans"), None, &options)
+        .await
+    {
+        Err(TranslationError::AuthenticationError(msg)) => assert!(msg.contains("503"), "{}", msg),
+        other => panic!("Expected AuthenticationError, got {:?}", other),
+    }
+}
@@ -0,0 +1,336 @@
+//! OpenAI 翻译器的集成测试
+//!
+//! 使用录制的 Chat Completions 响应（`tests/fixtures/openai`）搭建模拟服务器，
+//! 检查翻译器发出的请求头和请求体，以及对响应和错误的解析。
+
+use async_translate::{
+    OpenAIConfig, OpenAITranslator, TraceContext, TranslateOptions, TranslationError, Translator,
+};
+use serde_json::json;
+use unic_langid::LanguageIdentifier;
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const CHAT_COMPLETION: &str = include_str!("fixtures/openai/chat_completion.json");
+const CHAT_COMPLETION_DETECTED: &str =
+    include_str!("fixtures/openai/chat_completion_detected.json");
+const CHAT_COMPLETION_EMPTY: &str = include_str!("fixtures/openai/chat_completion_empty.json");
+const ERROR_INVALID_API_KEY: &str = include_str!("fixtures/openai/error_invalid_api_key.json");
+const ERROR_RATE_LIMIT: &str = include_str!("fixtures/openai/error_rate_limit.json");
+
+/// 实际遇到过的非常规响应（`tests/fixtures/openai/quirks`），以及期望的译文或
+/// [`TranslationError::ServiceError`] 信息
+const QUIRKS: &[(&str, &str, Result<&str, &str>)] = &[
+    (
+        "choices_null",
+        include_str!("fixtures/openai/quirks/choices_null.json"),
+        Err("No translation results returned"),
+    ),
+    (
+        "choices_null_with_error",
+        include_str!("fixtures/openai/quirks/choices_null_with_error.json"),
+        Err("No translation results returned: upstream request timeout"),
+    ),
+    (
+        "choices_missing",
+        include_str!("fixtures/openai/quirks/choices_missing.json"),
+        Err("No translation results returned"),
+    ),
+    (
+        "content_null",
+        include_str!("fixtures/openai/quirks/content_null.json"),
+        Err("No translation results returned"),
+    ),
+    (
+        "message_null",
+        include_str!("fixtures/openai/quirks/message_null.json"),
+        Err("No translation results returned"),
+    ),
+    (
+        "message_without_role",
+        include_str!("fixtures/openai/quirks/message_without_role.json"),
+        Ok("你好，世界！"),
+    ),
+    (
+        "unknown_fields",
+        include_str!("fixtures/openai/quirks/unknown_fields.json"),
+        Ok("你好，世界！"),
+    ),
+];
+
+/// 与 `error_invalid_api_key.json` 中回显的 Key 一致
+const API_KEY: &str = "sk-test-0000";
+
+fn lang(tag: &str) -> LanguageIdentifier {
+    tag.parse().unwrap()
+}
+
+fn json_response(status: u16, fixture: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status).set_body_raw(fixture, "application/json")
+}
+
+fn translator(server: &MockServer) -> OpenAITranslator {
+    OpenAITranslator::new(
+        OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec![API_KEY])
+            .model("gpt-4o-mini")
+            .rpm_limit(0)
+            .system_prompt("Translate {source_lang} to {target_lang}.")
+            .build(),
+    )
+}
+
+#[tokio::test]
+async fn test_chat_completion_request_and_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(header("Authorization", format!("Bearer {}", API_KEY)))
+        .and(header("Content-Type", "application/json"))
+        .and(header("X-Request-Id", "req-7"))
+        .and(header(
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        ))
+        .and(body_json(json!({
+            "model": "gpt-4o-mini",
+            "messages": [
+                {"role": "system", "content": "Translate en to zh-Hans."},
+                {"role": "user", "content": "Hello, world!"}
+            ],
+            "temperature": 0.0
+        })))
+        .respond_with(json_response(200, CHAT_COMPLETION))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let options = TranslateOptions::default()
+        .request_id("req-7")
+        .trace_context(TraceContext {
+            traceparent: "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string(),
+            tracestate: None,
+        })
+        .include_raw_response(true);
+    let detail = translator(&server)
+        .translate_detailed_with_options(
+            "Hello, world!",
+            &lang("zh-Hans"),
+            Some(&lang("en")),
+            &options,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(detail.text, "你好，世界！");
+    assert_eq!(detail.detected_source, None);
+    assert!(!detail.partial && !detail.suspicious);
+    assert_eq!(detail.provider_metadata["provider"], "openai");
+    assert_eq!(detail.provider_metadata["model"], "gpt-4o-mini");
+    assert_eq!(detail.provider_metadata["request_id"], "req-7");
+    let fixture: serde_json::Value = serde_json::from_str(CHAT_COMPLETION).unwrap();
+    assert_eq!(detail.raw, Some(fixture));
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests[0].url.query(), None);
+    assert!(requests[0].headers.get("tracestate").is_none());
+}
+
+#[tokio::test]
+async fn test_detected_language_preamble() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(json_response(200, CHAT_COMPLETION_DETECTED))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let translator = OpenAITranslator::new(
+        OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec![API_KEY])
+            .model("gpt-4o-mini")
+            .rpm_limit(0)
+            .system_prompt("Translate {source_lang} to {target_lang}.")
+            .detect_source_language(true)
+            .build(),
+    );
+    let detail = translator
+        .translate_detailed_with_options(
+            "Bonjour à tous !",
+            &lang("en"),
+            None,
+            &TranslateOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(detail.text, "Good morning, everyone!");
+    assert_eq!(
+        detail.detected_source.map(|(lang, _)| lang),
+        Some(lang("fr"))
+    );
+
+    // 未指定源语言时提示词使用 auto，并要求模型先输出检测到的语言
+    let requests = server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let system = body["messages"][0]["content"].as_str().unwrap();
+    assert!(system.starts_with("Translate auto to en."), "{}", system);
+    assert!(system.contains("[lang: <tag>]"), "{}", system);
+    assert_eq!(body["messages"][1]["content"], "Bonjour à tous !");
+}
+
+#[tokio::test]
+async fn test_empty_choices_is_service_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(json_response(200, CHAT_COMPLETION_EMPTY))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    match translator(&server)
+        .translate("Hello", &lang("zh"), None)
+        .await
+    {
+        Err(TranslationError::ServiceError(msg)) => {
+            assert_eq!(msg, "No translation results returned")
+        }
+        other => panic!("Expected ServiceError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_quirky_responses() {
+    for (name, fixture, expected) in QUIRKS {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(json_response(200, fixture))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let result = translator(&server)
+            .translate("Hello, world!", &lang("zh-Hans"), None)
+            .await;
+        match (result, expected) {
+            (Ok(text), Ok(expected)) => assert_eq!(text, *expected, "{}", name),
+            (Err(TranslationError::ServiceError(msg)), Err(expected)) => {
+                assert_eq!(msg, *expected, "{}", name)
+            }
+            (other, _) => panic!("{}: unexpected result {:?}", name, other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_api_key_error_is_redacted() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(json_response(401, ERROR_INVALID_API_KEY))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    match translator(&server)
+        .translate("Hello", &lang("zh"), None)
+        .await
+    {
+        Err(TranslationError::Provider {
+            provider,
+            code,
+            message,
+            http_status,
+            retryable,
+            trace,
+        }) => {
+            assert_eq!(provider, "openai");
+            assert_eq!(code, "invalid_api_key");
+            assert!(
+                message.starts_with("Incorrect API key provided: ***."),
+                "{}",
+                message
+            );
+            assert!(!message.contains(API_KEY));
+            assert_eq!(http_status, Some(reqwest::StatusCode::UNAUTHORIZED));
+            assert!(!retryable);
+            assert_eq!(trace, None);
+        }
+        other => panic!("Expected Provider error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_rate_limit_error_is_retryable() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(json_response(429, ERROR_RATE_LIMIT))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // 可以重试的错误在重试次数耗尽后包装为 MaxRetriesExceeded
+    let options = TranslateOptions::default().no_retries();
+    match translator(&server)
+        .translate_with_options("Hello", &lang("zh"), None, &options)
+        .await
+    {
+        Err(TranslationError::MaxRetriesExceeded { attempts, errors }) => {
+            assert_eq!(attempts, 1);
+            match &errors[..] {
+                [
+                    TranslationError::Provider {
+                        code,
+                        http_status,
+                        retryable,
+                        ..
+                    },
+                ] => {
+                    assert_eq!(code, "rate_limit_exceeded");
+                    assert_eq!(*http_status, Some(reqwest::StatusCode::TOO_MANY_REQUESTS));
+                    assert!(retryable);
+                }
+                other => panic!("Expected one Provider error, got {:?}", other),
+            }
+        }
+        other => panic!("Expected MaxRetriesExceeded, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_http2_keep_alive_across_idle_period() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(json_response(200, CHAT_COMPLETION))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    // 模拟服务器支持明文 HTTP/2，空闲期间心跳保持连接
+    let translator = OpenAITranslator::new(
+        OpenAIConfig::builder()
+            .base_url(server.uri())
+            .api_keys(vec![API_KEY])
+            .model("gpt-4o-mini")
+            .rpm_limit(0)
+            .http2_prior_knowledge(true)
+            .http2_keep_alive_interval(std::time::Duration::from_millis(50))
+            .http2_keep_alive_while_idle(true)
+            .build(),
+    );
+    for _ in 0..2 {
+        let translated = translator
+            .translate("Hello, world!", &lang("zh-Hans"), Some(&lang("en")))
+            .await
+            .unwrap();
+        assert_eq!(translated, "你好，世界！");
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    }
+}
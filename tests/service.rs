@@ -0,0 +1,138 @@
+//! axum 路由的集成测试
+//!
+//! 在本地端口上启动 [`translation_router`]，用模拟翻译器检查 `/translate` 和
+//! `/translate_batch` 的响应体，以及错误到 HTTP 状态码的映射。
+
+use async_translate::service::translation_router;
+use async_translate::{TranslateOptions, TranslationError, TranslationManager, Translator};
+use serde_json::{Value, json};
+use unic_langid::LanguageIdentifier;
+
+/// 按原文返回不同错误的翻译器，其余文本译为 `T:<原文>`
+struct ScriptedTranslator;
+
+#[async_trait::async_trait]
+impl Translator for ScriptedTranslator {
+    async fn translate_with_options(
+        &self,
+        text: &str,
+        _target_lang: &LanguageIdentifier,
+        _source_lang: Option<&LanguageIdentifier>,
+        _options: &TranslateOptions,
+    ) -> Result<String, TranslationError> {
+        match text {
+            "rate limit" => Err(TranslationError::HttpError {
+                status: reqwest::StatusCode::TOO_MANY_REQUESTS,
+                body: "slow down".to_string(),
+                trace: None,
+            }),
+            "bad key" => Err(TranslationError::AuthenticationError(
+                "invalid api key".to_string(),
+            )),
+            "too long" => Err(TranslationError::TextTooLong { length: 8, max: 4 }),
+            _ => Ok(format!("T:{}", text)),
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// 启动路由，返回服务地址
+async fn spawn_router() -> String {
+    let mut manager = TranslationManager::new();
+    manager.add_translator("mock", Box::new(ScriptedTranslator));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, translation_router(manager))
+            .await
+            .unwrap();
+    });
+    format!("http://{}", address)
+}
+
+async fn post(url: String, body: Value) -> (u16, Value) {
+    let response = reqwest::Client::new()
+        .post(url)
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    let status = response.status().as_u16();
+    (status, response.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn test_translate_endpoint() {
+    let base = spawn_router().await;
+    let (status, body) = post(
+        format!("{}/translate", base),
+        json!({"provider": "mock", "text": "Hello", "target": "zh", "source": "en"}),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(
+        body,
+        json!({
+            "text": "T:Hello",
+            "detected_source": null,
+            "passthrough": false,
+            "partial": false,
+            "suspicious": false,
+            "unchanged": false,
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_translate_error_mapping() {
+    let base = spawn_router().await;
+    let cases = [
+        ("mock", "rate limit", "zh", 429, "rate_limited"),
+        ("mock", "bad key", "zh", 502, "auth_failed"),
+        ("mock", "too long", "zh", 400, "text_too_long"),
+        ("mock", "Hello", "not a language!", 400, "invalid_request"),
+        ("missing", "Hello", "zh", 404, "unknown_provider"),
+    ];
+    for (provider, text, target, expected_status, expected_code) in cases {
+        let (status, body) = post(
+            format!("{}/translate", base),
+            json!({"provider": provider, "text": text, "target": target}),
+        )
+        .await;
+        assert_eq!(status, expected_status, "{}: {}", text, body);
+        assert_eq!(body["error"]["code"], expected_code, "{}", body);
+        assert!(body["error"]["message"].is_string());
+    }
+
+    // 请求体不是合法的 JSON 或缺少字段
+    let (status, body) = post(format!("{}/translate", base), json!({"text": "Hello"})).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"]["code"], "invalid_request");
+}
+
+#[tokio::test]
+async fn test_translate_batch_endpoint() {
+    let base = spawn_router().await;
+    let (status, body) = post(
+        format!("{}/translate_batch", base),
+        json!({"provider": "mock", "texts": ["Hello", "rate limit", "World"], "target": "zh"}),
+    )
+    .await;
+    assert_eq!(status, 200);
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["text"], "T:Hello");
+    assert_eq!(results[1]["error"]["code"], "rate_limited");
+    assert_eq!(results[2]["text"], "T:World");
+
+    let (status, body) = post(
+        format!("{}/translate_batch", base),
+        json!({"provider": "missing", "texts": ["Hello"], "target": "zh"}),
+    )
+    .await;
+    assert_eq!(status, 404);
+    assert_eq!(body["error"]["code"], "unknown_provider");
+}